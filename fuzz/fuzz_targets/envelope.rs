@@ -0,0 +1,10 @@
+#![no_main]
+use halo2_snark_aggregator_circuit::envelope::ProofEnvelope;
+use libfuzzer_sys::fuzz_target;
+
+// verify_circuit_proof.envelope, a decoded envelope encodes back to its bytes.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(envelope) = ProofEnvelope::decode(data) {
+        assert_eq!(envelope.encode(), data);
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+use halo2_snark_aggregator_circuit::fs::try_load_instances;
+use libfuzzer_sys::fuzz_target;
+use pairing_bn256::bn256::Bn256;
+
+// verify_circuit_instance.data and the instances of a submitted target proof.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(instances) = try_load_instances::<Bn256>(data) {
+        assert_eq!(instances[0][0].len() * 32, data.len());
+    }
+});
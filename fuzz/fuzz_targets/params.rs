@@ -0,0 +1,9 @@
+#![no_main]
+use halo2_snark_aggregator_circuit::fs::try_read_params;
+use libfuzzer_sys::fuzz_target;
+use pairing_bn256::bn256::G1Affine;
+
+// verify_circuit.params and sample_circuit_*.params.
+fuzz_target!(|data: &[u8]| {
+    let _ = try_read_params::<G1Affine>(data);
+});
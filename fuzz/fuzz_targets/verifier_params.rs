@@ -0,0 +1,61 @@
+#![no_main]
+use halo2_proofs::{
+    pairing::bn256::Fr as Fp,
+    plonk::{keygen_vk, Error, VerifyingKey},
+    poly::commitment::{Params, ParamsVerifier},
+};
+use halo2_snark_aggregator_api::{
+    mock::{
+        arith::{
+            ecc::MockEccChip,
+            field::{MockChipCtx, MockFieldChip},
+        },
+        transcript_encode::PoseidonEncode,
+    },
+    systems::halo2::{
+        transcript::PoseidonTranscriptRead,
+        verify::{assign_instance_commitment, build_verifier_params},
+    },
+    tests::systems::halo2::add_mul_test::test_circuit_builder,
+};
+use libfuzzer_sys::fuzz_target;
+use once_cell::sync::Lazy;
+use pairing_bn256::bn256::{Bn256, G1Affine};
+
+const K: u32 = 10;
+
+static SETUP: Lazy<(ParamsVerifier<Bn256>, VerifyingKey<G1Affine>)> = Lazy::new(|| {
+    let params = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+    let circuit = test_circuit_builder(Fp::from(1), Fp::from(1));
+    let vk = keygen_vk(&params, &circuit).unwrap();
+    (params.verifier(1).unwrap(), vk)
+});
+
+// The transcript of a target proof, read against the vk of the add_mul test circuit.
+fuzz_target!(|data: &[u8]| {
+    let (params, vk) = &*SETUP;
+    let nchip = MockFieldChip::<Fp, Error>::default();
+    let schip = MockFieldChip::<Fp, Error>::default();
+    let pchip = MockEccChip::<G1Affine, Error>::default();
+    let ctx = &mut MockChipCtx::default();
+
+    let instances: &[&[&[Fp]]] = &[&[&[Fp::from(7)]]];
+    let (_, assigned_instances) =
+        assign_instance_commitment::<Bn256, _>(ctx, &schip, &pchip, instances, vk, params).unwrap();
+    let mut transcript =
+        PoseidonTranscriptRead::<_, G1Affine, _, PoseidonEncode, 9usize, 8usize>::new(
+            data, ctx, &nchip, 8usize, 33usize,
+        )
+        .unwrap();
+    let _ = build_verifier_params(
+        ctx,
+        &nchip,
+        &schip,
+        &pchip,
+        assigned_instances,
+        vk,
+        params,
+        &mut transcript,
+        "p0".to_owned(),
+    );
+});
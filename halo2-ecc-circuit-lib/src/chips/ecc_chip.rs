@@ -3,7 +3,7 @@ use crate::gates::{
     base_gate::{AssignedCondition, BaseGateOps, Context},
     range_gate::RangeGateOps,
 };
-use group::ff::Field;
+use group::ff::{Field, PrimeField};
 use group::Curve;
 use group::Group;
 use halo2_proofs::{
@@ -30,6 +30,8 @@ pub struct AssignedPoint<C: CurveAffine, N: FieldExt> {
     pub z: AssignedCondition<N>,
 
     curvature: Option<AssignedCurvature<C, N>>,
+    // set by assign_constant_point only, the cells are then fixed to this value
+    constant: Option<C::CurveExt>,
 }
 
 impl<C: CurveAffine, N: FieldExt> AssignedPoint<C, N> {
@@ -43,6 +45,7 @@ impl<C: CurveAffine, N: FieldExt> AssignedPoint<C, N> {
             y,
             z,
             curvature: None,
+            constant: None,
         }
     }
 
@@ -52,12 +55,23 @@ impl<C: CurveAffine, N: FieldExt> AssignedPoint<C, N> {
         z: AssignedCondition<N>,
         curvature: Option<AssignedCurvature<C, N>>,
     ) -> Self {
-        Self { x, y, z, curvature }
+        Self {
+            x,
+            y,
+            z,
+            curvature,
+            constant: None,
+        }
     }
 
     pub fn set_curvature(&mut self, curvature: AssignedCurvature<C, N>) {
         self.curvature = Some(curvature);
     }
+
+    // The value of a point of assign_constant_point, None for a point with witness cells.
+    pub fn constant(&self) -> Option<C::CurveExt> {
+        self.constant
+    }
 }
 
 pub struct EccChip<'a, C: CurveAffine, N: FieldExt> {
@@ -72,6 +86,52 @@ impl<'a, C: CurveAffine, N: FieldExt> EccChip<'a, C, N> {
 
 const CONFIG_WINDOW_SIZE: usize = 4usize;
 
+// Window of the fixed-base tables of the constants of a verifier, the vk commitments.
+pub const FIXED_BASE_WINDOW_SIZE: usize = 4usize;
+
+// Multiples of a constant point by every digit of every window of a scalar, the lowest window
+// first: windows[i][j] = j * 2^(WINDOW_SIZE * i) * base. A multiplication by the base is then
+// one addition per window and no doubling.
+#[derive(Clone, Debug)]
+pub struct FixedBaseTable<C: CurveAffine, const WINDOW_SIZE: usize> {
+    pub windows: Vec<Vec<C::CurveExt>>,
+}
+
+impl<C: CurveAffine, const WINDOW_SIZE: usize> FixedBaseTable<C, WINDOW_SIZE> {
+    pub fn new(base: C::CurveExt) -> Self {
+        // as many windows as decompose_scalar gives
+        let num_windows = (<C::ScalarExt as PrimeField>::NUM_BITS - 1 + WINDOW_SIZE as u32)
+            / (WINDOW_SIZE as u32);
+        let mut windows = vec![];
+        let mut base = base;
+        for _ in 0..num_windows {
+            let mut window = vec![C::CurveExt::identity()];
+            for j in 1..(1 << WINDOW_SIZE) {
+                window.push(window[j - 1] + base);
+            }
+            base = window[(1 << WINDOW_SIZE) - 1] + base;
+            windows.push(window);
+        }
+        FixedBaseTable { windows }
+    }
+
+    pub fn mul(&self, s: &C::ScalarExt) -> C::CurveExt {
+        let repr = s.to_repr();
+        let bit = |i: usize| {
+            repr.as_ref()
+                .get(i / 8)
+                .map_or(0, |byte| ((byte >> (i % 8)) & 1) as usize)
+        };
+        self.windows
+            .iter()
+            .enumerate()
+            .fold(C::CurveExt::identity(), |acc, (i, window)| {
+                let digit = (0..WINDOW_SIZE).fold(0, |d, j| d | (bit(i * WINDOW_SIZE + j) << j));
+                acc + window[digit]
+            })
+    }
+}
+
 pub trait EccChipOps<C: CurveAffine, N: FieldExt> {
     type AssignedScalar;
     fn integer_chip(&self) -> &dyn IntegerChipOps<C::Base, N>;
@@ -275,6 +335,42 @@ pub trait EccChipOps<C: CurveAffine, N: FieldExt> {
 
         Ok(acc.unwrap())
     }
+    // constant_mul with the multiples of a table, each window picks its constant multiple
+    // with WINDOW_SIZE selections.
+    fn fixed_base_mul<const WINDOW_SIZE: usize>(
+        &self,
+        ctx: &mut Context<N>,
+        table: &FixedBaseTable<C, WINDOW_SIZE>,
+        s: &Self::AssignedScalar,
+    ) -> Result<AssignedPoint<C, N>, Error> {
+        let windows_in_be = self.decompose_scalar::<WINDOW_SIZE>(ctx, s)?;
+        assert_eq!(windows_in_be.len(), table.windows.len());
+
+        let mut acc = None;
+        for (bits_in_le, window) in windows_in_be.iter().rev().zip(table.windows.iter()) {
+            let mut candidates = window
+                .iter()
+                .map(|p| self.assign_constant_point_with_curvature(ctx, *p))
+                .collect::<Result<Vec<_>, _>>()?;
+            for bit in bits_in_le {
+                candidates = candidates
+                    .chunks_mut(2)
+                    .map(|pair| {
+                        let (a0, a1) = pair.split_at_mut(1);
+                        self.bisec_point_with_curvature(ctx, bit, &mut a1[0], &mut a0[0])
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+            }
+
+            let mut slot = candidates.remove(0);
+            acc = match acc {
+                None => Some(slot),
+                Some(acc_) => Some(self.add(ctx, &mut slot, &acc_)?),
+            };
+        }
+
+        Ok(acc.unwrap())
+    }
     fn curvature<'a>(
         &self,
         ctx: &mut Context<N>,
@@ -435,7 +531,9 @@ pub trait EccChipOps<C: CurveAffine, N: FieldExt> {
         let y = integer_chip.assign_constant(ctx, y)?;
         let z = base_gate.assign_constant(ctx, z)?;
 
-        Ok(AssignedPoint::new(x, y, z.into()))
+        let mut p = AssignedPoint::new(x, y, z.into());
+        p.constant = Some(c);
+        Ok(p)
     }
     fn assign_constant_point_with_curvature(
         &self,
@@ -469,12 +567,14 @@ pub trait EccChipOps<C: CurveAffine, N: FieldExt> {
         let y = integer_chip.assign_constant(ctx, y)?;
         let z = base_gate.assign_constant(ctx, z)?;
 
-        Ok(AssignedPoint::new_with_curvature(
+        let mut p = AssignedPoint::new_with_curvature(
             x,
             y,
             z.into(),
             Some(AssignedCurvature::new(curvature_v, curvature_z.into())),
-        ))
+        );
+        p.constant = Some(c);
+        Ok(p)
     }
     fn assign_point(
         &self,
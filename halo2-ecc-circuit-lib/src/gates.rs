@@ -1,2 +1,3 @@
 pub mod base_gate;
+pub mod byte_range_gate;
 pub mod range_gate;
@@ -547,6 +547,17 @@ pub trait BaseGateOps<N: FieldExt> {
         Ok(())
     }
 
+    // a as a condition, constrained to be 0 or 1. The From conversions only copy the cell and
+    // must be kept for values that already are.
+    fn assert_condition(
+        &self,
+        ctx: &mut Context<'_, N>,
+        a: &AssignedValue<N>,
+    ) -> Result<AssignedCondition<N>, Error> {
+        self.assert_bit(ctx, a)?;
+        Ok(a.into())
+    }
+
     fn and(
         &self,
         ctx: &mut Context<'_, N>,
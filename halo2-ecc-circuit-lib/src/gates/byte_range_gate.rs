@@ -0,0 +1,51 @@
+use crate::gates::base_gate::{AssignedValue, Context};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Layouter,
+    plonk::{ConstraintSystem, Error},
+};
+use halo2_snark_aggregator_api::arith::range::{ByteRangeChip, ByteRangeConfig};
+
+// Range checks an assigned value against the shared BITS-bit table of the api crate.
+// The limbs live in the chip's own columns, on the rows the context is currently at.
+pub struct ByteRangeGate<N: FieldExt, const BITS: usize> {
+    pub chip: ByteRangeChip<N, BITS>,
+}
+
+impl<N: FieldExt, const BITS: usize> ByteRangeGate<N, BITS> {
+    pub fn configure(meta: &mut ConstraintSystem<N>) -> ByteRangeConfig<BITS> {
+        ByteRangeChip::<N, BITS>::configure(meta)
+    }
+
+    pub fn new(config: ByteRangeConfig<BITS>) -> Self {
+        ByteRangeGate {
+            chip: ByteRangeChip::new(config),
+        }
+    }
+
+    pub fn load_table(&self, layouter: &mut impl Layouter<N>) -> Result<(), Error> {
+        self.chip.load_table(layouter)
+    }
+
+    // Constrains a < 2^bits, bits must be a multiple of BITS.
+    // The decomposition takes bits / BITS rows and moves the context past them.
+    pub fn assert_in_range(
+        &self,
+        ctx: &mut Context<'_, N>,
+        a: &AssignedValue<N>,
+        bits: usize,
+    ) -> Result<(), Error> {
+        assert!(bits > 0 && bits % BITS == 0);
+
+        let limbs = bits / BITS;
+        self.chip.assign_decompose(
+            ctx.region.as_mut(),
+            *ctx.offset,
+            a.cell,
+            Some(a.value),
+            limbs,
+        )?;
+        *ctx.offset += limbs;
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
 pub mod five_base;
+pub mod five_byte_range;
 pub mod five_integer;
 pub mod five_native_ecc;
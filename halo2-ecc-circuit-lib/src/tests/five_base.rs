@@ -22,6 +22,7 @@ enum TestCase {
     MulAddWithNextLine,
     InvertUnsafe,
     DivUnsafe,
+    AssertCondition(u64),
 }
 
 impl Default for TestCase {
@@ -307,6 +308,29 @@ impl<N: FieldExt> TestFiveColumnBaseGateCircuit<N> {
         base_gate.assert_equal(ctx, &assigned_result, &op_result)?;
         Ok(())
     }
+
+    fn setup_test_assert_condition(
+        &self,
+        base_gate: &FiveColumnBaseGate<N>,
+        ctx: &mut Context<'_, N>,
+        v: u64,
+    ) -> Result<(), Error> {
+        let vars = [(); 2].map(|_| Self::random());
+        let a = base_gate.assign_constant(ctx, vars[0])?;
+        let b = base_gate.assign_constant(ctx, vars[1])?;
+
+        // a witness, not a constant, so that only the bit constraint holds it
+        let cond = base_gate.assign(ctx, N::from(v))?;
+        let cond = base_gate.assert_condition(ctx, &cond)?;
+        let op_result = base_gate.bisec(ctx, &cond, &a, &b)?;
+
+        if v < 2 {
+            let result = if v == 1 { vars[0] } else { vars[1] };
+            let assigned_result = base_gate.assign_constant(ctx, result)?;
+            base_gate.assert_equal(ctx, &assigned_result, &op_result)?;
+        }
+        Ok(())
+    }
 }
 
 impl<N: FieldExt> Circuit<N> for TestFiveColumnBaseGateCircuit<N> {
@@ -346,6 +370,9 @@ impl<N: FieldExt> Circuit<N> for TestFiveColumnBaseGateCircuit<N> {
                     }
                     TestCase::InvertUnsafe => self.setup_test_invert_unsafe(&base_gate, r),
                     TestCase::DivUnsafe => self.setup_test_div_unsafe(&base_gate, r),
+                    TestCase::AssertCondition(v) => {
+                        self.setup_test_assert_condition(&base_gate, r, v)
+                    }
                 }
             },
         )?;
@@ -465,3 +492,33 @@ fn test_five_column_base_gate_mul_div_unsafe() {
     };
     assert_eq!(prover.verify(), Ok(()));
 }
+
+#[test]
+fn test_five_column_base_gate_assert_condition() {
+    const K: u32 = 8;
+    for v in [0, 1] {
+        let circuit = TestFiveColumnBaseGateCircuit::<Fr> {
+            test_case: TestCase::AssertCondition(v),
+            _phantom: PhantomData,
+        };
+        let prover = match MockProver::run(K, &circuit, vec![]) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{:#?}", e),
+        };
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
+
+#[test]
+fn test_five_column_base_gate_assert_condition_non_boolean() {
+    const K: u32 = 8;
+    let circuit = TestFiveColumnBaseGateCircuit::<Fr> {
+        test_case: TestCase::AssertCondition(2),
+        _phantom: PhantomData,
+    };
+    let prover = match MockProver::run(K, &circuit, vec![]) {
+        Ok(prover) => prover,
+        Err(e) => panic!("{:#?}", e),
+    };
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,94 @@
+use crate::five::base_gate::{FiveColumnBaseGate, FiveColumnBaseGateConfig};
+use crate::gates::base_gate::{BaseGateOps, Context};
+use crate::gates::byte_range_gate::ByteRangeGate;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    dev::MockProver,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use halo2_snark_aggregator_api::arith::range::ByteRangeConfig;
+use pairing_bn256::bn256::Fr;
+use std::marker::PhantomData;
+
+const BITS: usize = 8;
+
+#[derive(Clone)]
+struct TestByteRangeGateConfig {
+    base_gate_config: FiveColumnBaseGateConfig,
+    byte_range_config: ByteRangeConfig<BITS>,
+}
+
+#[derive(Default)]
+struct TestByteRangeGateCircuit<N: FieldExt> {
+    value: u64,
+    bits: usize,
+    _phantom: PhantomData<N>,
+}
+
+impl<N: FieldExt> Circuit<N> for TestByteRangeGateCircuit<N> {
+    type Config = TestByteRangeGateConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<N>) -> Self::Config {
+        let base_gate_config = FiveColumnBaseGate::<N>::configure(meta);
+        let byte_range_config = ByteRangeGate::<N, BITS>::configure(meta);
+        TestByteRangeGateConfig {
+            base_gate_config,
+            byte_range_config,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<N>,
+    ) -> Result<(), Error> {
+        let base_gate = FiveColumnBaseGate::new(config.base_gate_config);
+        let byte_range_gate = ByteRangeGate::<N, BITS>::new(config.byte_range_config);
+        byte_range_gate.load_table(&mut layouter)?;
+
+        layouter.assign_region(
+            || "byte range",
+            |region| {
+                let base_offset = 0usize;
+                let mut aux = Context::new(region, base_offset);
+                let r = &mut aux;
+                let a = base_gate.assign(r, N::from(self.value))?;
+                byte_range_gate.assert_in_range(r, &a, self.bits)
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_five_column_byte_range_gate() {
+    const K: u32 = 10;
+    let circuit = TestByteRangeGateCircuit::<Fr> {
+        value: 0x12345678,
+        bits: 32,
+        _phantom: PhantomData,
+    };
+    let prover = match MockProver::run(K, &circuit, vec![]) {
+        Ok(prover) => prover,
+        Err(e) => panic!("{:#?}", e),
+    };
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_five_column_byte_range_gate_out_of_range() {
+    const K: u32 = 10;
+    let circuit = TestByteRangeGateCircuit::<Fr> {
+        value: 0x1_0000_0000,
+        bits: 32,
+        _phantom: PhantomData,
+    };
+    assert!(MockProver::run(K, &circuit, vec![]).is_err());
+}
@@ -1,4 +1,4 @@
-use crate::chips::ecc_chip::EccChipOps;
+use crate::chips::ecc_chip::{EccChipOps, FixedBaseTable, FIXED_BASE_WINDOW_SIZE};
 use crate::chips::native_ecc_chip::NativeEccChip;
 use crate::five::base_gate::{FiveColumnBaseGate, FiveColumnBaseGateConfig};
 use crate::five::integer_chip::FiveColumnIntegerChip;
@@ -25,6 +25,8 @@ enum TestCase {
     Mul,
     ShaMir,
     ConstantMul,
+    FixedBaseMul,
+    FixedBaseMulRows,
     Sub,
 }
 
@@ -216,6 +218,78 @@ impl<C: CurveAffine> TestFiveColumnNativeEccChipCircuit<C> {
         Ok(())
     }
 
+    fn setup_test_fixed_base_mul(
+        &self,
+        ecc_gate: &NativeEccChip<'_, C>,
+        ctx: &mut Context<'_, C::ScalarExt>,
+    ) -> Result<(), Error> {
+        let base_gate = ecc_gate.base_gate();
+
+        let s1 = Self::random();
+        let s2 = Self::random();
+
+        let s3 = s1 * s2;
+        let identity = C::ScalarExt::zero();
+
+        let table = FixedBaseTable::<C, FIXED_BASE_WINDOW_SIZE>::new(C::generator() * s1);
+        assert_eq!(table.mul(&s2), C::generator() * s3);
+        assert_eq!(table.mul(&identity), C::CurveExt::identity());
+
+        let s2 = base_gate.assign_constant(ctx, s2)?;
+        let mut assigned_pi = ecc_gate.assign_identity(ctx)?;
+        let si = base_gate.assign_constant(ctx, identity)?;
+
+        let mut p3 = ecc_gate.assign_constant_point_from_scalar(ctx, s3)?;
+        let mut p3_ = ecc_gate.fixed_base_mul(ctx, &table, &s2)?;
+        // only the points of assign_constant_point carry their value
+        assert_eq!(p3.constant(), Some(C::generator() * s3));
+        assert_eq!(p3_.constant(), None);
+        let p1 = ecc_gate.assign_point(ctx, C::generator() * s1)?;
+        assert_eq!(p1.constant(), None);
+        ecc_gate.assert_equal(ctx, &mut p3, &mut p3_)?;
+
+        let mut pi_ = ecc_gate.fixed_base_mul(ctx, &table, &si)?;
+        ecc_gate.assert_equal(ctx, &mut assigned_pi, &mut pi_)?;
+
+        let table = FixedBaseTable::<C, FIXED_BASE_WINDOW_SIZE>::new(C::CurveExt::identity());
+        let mut pi_ = ecc_gate.fixed_base_mul(ctx, &table, &s2)?;
+        ecc_gate.assert_equal(ctx, &mut assigned_pi, &mut pi_)?;
+
+        Ok(())
+    }
+
+    // Rows of one multiplication of a constant point by a witness scalar, with the 2 bit windows
+    // of constant_mul and with the tables of fixed_base_mul.
+    fn setup_test_fixed_base_mul_rows(
+        &self,
+        ecc_gate: &NativeEccChip<'_, C>,
+        ctx: &mut Context<'_, C::ScalarExt>,
+    ) -> Result<(), Error> {
+        let base_gate = ecc_gate.base_gate();
+
+        let p1 = C::generator() * Self::random();
+        let s = base_gate.assign(ctx, Self::random())?;
+        let table = FixedBaseTable::<C, FIXED_BASE_WINDOW_SIZE>::new(p1);
+
+        let start = *ctx.offset;
+        let mut p = ecc_gate.constant_mul(ctx, p1, &s)?;
+        let constant_mul_rows = *ctx.offset - start;
+
+        let start = *ctx.offset;
+        let mut p_ = ecc_gate.fixed_base_mul(ctx, &table, &s)?;
+        let fixed_base_mul_rows = *ctx.offset - start;
+
+        assert!(
+            fixed_base_mul_rows < constant_mul_rows,
+            "fixed_base_mul takes {} rows, constant_mul {}",
+            fixed_base_mul_rows,
+            constant_mul_rows
+        );
+        ecc_gate.assert_equal(ctx, &mut p, &mut p_)?;
+
+        Ok(())
+    }
+
     fn setup_test_double(
         &self,
         ecc_gate: &NativeEccChip<'_, C>,
@@ -294,6 +368,10 @@ impl<C: CurveAffine> Circuit<C::ScalarExt> for TestFiveColumnNativeEccChipCircui
                         TestCase::Mul => self.setup_test_mul(&ecc_gate, r),
                         TestCase::Sub => self.setup_test_sub(&ecc_gate, r),
                         TestCase::ConstantMul => self.setup_test_constant_mul(&ecc_gate, r),
+                        TestCase::FixedBaseMul => self.setup_test_fixed_base_mul(&ecc_gate, r),
+                        TestCase::FixedBaseMulRows => {
+                            self.setup_test_fixed_base_mul_rows(&ecc_gate, r)
+                        }
                         TestCase::ShaMir => self.setup_test_shamir(&ecc_gate, r),
                     }?;
                 }
@@ -381,6 +459,37 @@ fn test_five_column_natvie_ecc_chip_constant_mul() {
     assert_eq!(prover.verify(), Ok(()));
 }
 
+#[test]
+fn test_five_column_natvie_ecc_chip_fixed_base_mul() {
+    const K: u32 = (COMMON_RANGE_BITS + 2) as u32;
+    let chip = TestFiveColumnNativeEccChipCircuit::<G1Affine> {
+        test_case: TestCase::FixedBaseMul,
+        _phantom_w: PhantomData,
+        _phantom_n: PhantomData,
+    };
+    let prover = match MockProver::run(K, &chip, vec![]) {
+        Ok(prover) => prover,
+        Err(e) => panic!("{:#?}", e),
+    };
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+// cargo test -p halo2-ecc-circuit-lib fixed_base_mul_rows -- --nocapture
+#[test]
+fn test_five_column_natvie_ecc_chip_fixed_base_mul_rows() {
+    const K: u32 = (COMMON_RANGE_BITS + 2) as u32;
+    let chip = TestFiveColumnNativeEccChipCircuit::<G1Affine> {
+        test_case: TestCase::FixedBaseMulRows,
+        _phantom_w: PhantomData,
+        _phantom_n: PhantomData,
+    };
+    let prover = match MockProver::run(K, &chip, vec![]) {
+        Ok(prover) => prover,
+        Err(e) => panic!("{:#?}", e),
+    };
+    assert_eq!(prover.verify(), Ok(()));
+}
+
 #[test]
 fn test_five_column_natvie_ecc_chip_shamir() {
     const K: u32 = (COMMON_RANGE_BITS + 2) as u32;
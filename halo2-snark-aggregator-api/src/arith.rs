@@ -2,3 +2,4 @@ pub mod ast;
 pub mod common;
 pub mod ecc;
 pub mod field;
+pub mod range;
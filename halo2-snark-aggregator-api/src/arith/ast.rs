@@ -75,12 +75,137 @@ impl<A: ArithFieldChip> Div<FieldArithHelper<A>> for FieldArithHelper<A> {
     }
 }
 
+// Builds a FieldArithHelper of an arithmetic expression on assigned fields, e.g.
+// arith_ast!((a - b) * c + d / e), with the precedence and associativity of rust.
 #[macro_export]
 macro_rules! arith_ast {
-    ($postfix:tt + $($tail:tt)*) => { arith_ast!($postfix) + arith_ast!($($tail)*) };
-    ($postfix:tt - $($tail:tt)*) => { arith_ast!($postfix) - arith_ast!($($tail)*) };
-    (($postfix:tt * $($tail:tt)*)) => { arith_ast!($postfix) * arith_ast!($($tail)*) };
-    (($postfix:tt / $($tail:tt)*)) => { arith_ast!($postfix) / arith_ast!($($tail)*) };
-    (($($inner:tt)*)) => { (arith_ast!($($inner)*)) };
-    ($inner:tt) => { FieldArithHelper::from($inner) };
+    (@munch [$($out:tt)*]) => { $($out)* };
+    (@munch [$($out:tt)*] + $($rest:tt)*) => {
+        $crate::arith_ast!(@munch [$($out)* +] $($rest)*)
+    };
+    (@munch [$($out:tt)*] - $($rest:tt)*) => {
+        $crate::arith_ast!(@munch [$($out)* -] $($rest)*)
+    };
+    (@munch [$($out:tt)*] * $($rest:tt)*) => {
+        $crate::arith_ast!(@munch [$($out)* *] $($rest)*)
+    };
+    (@munch [$($out:tt)*] / $($rest:tt)*) => {
+        $crate::arith_ast!(@munch [$($out)* /] $($rest)*)
+    };
+    (@munch [$($out:tt)*] ($($inner:tt)*) $($rest:tt)*) => {
+        $crate::arith_ast!(@munch [$($out)* ($crate::arith_ast!($($inner)*))] $($rest)*)
+    };
+    (@munch [$($out:tt)*] $leaf:tt $($rest:tt)*) => {
+        $crate::arith_ast!(
+            @munch [$($out)* $crate::arith::ast::FieldArithHelper::from($leaf)] $($rest)*
+        )
+    };
+    ($($tokens:tt)*) => { $crate::arith_ast!(@munch [] $($tokens)*) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::arith::field::{MockChipCtx, MockFieldChip};
+    use halo2_proofs::{arithmetic::Field, pairing::bn256::Fr, plonk::Error};
+    use proptest::prelude::*;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    type Chip = MockFieldChip<Fr, Error>;
+
+    #[derive(Clone, Debug)]
+    enum Expr {
+        Leaf(usize),
+        Add(Box<Expr>, Box<Expr>),
+        Sub(Box<Expr>, Box<Expr>),
+        Mul(Box<Expr>, Box<Expr>),
+        Div(Box<Expr>, Box<Expr>),
+    }
+
+    fn expr(leaves: usize) -> impl Strategy<Value = Expr> {
+        let leaf = (0..leaves).prop_map(Expr::Leaf);
+        leaf.prop_recursive(6, 64, 2, |inner| {
+            (0..4usize, inner.clone(), inner).prop_map(|(op, l, r)| {
+                let (l, r) = (Box::new(l), Box::new(r));
+                match op {
+                    0 => Expr::Add(l, r),
+                    1 => Expr::Sub(l, r),
+                    2 => Expr::Mul(l, r),
+                    _ => Expr::Div(l, r),
+                }
+            })
+        })
+    }
+
+    // Direct field arithmetic, None on a division by zero.
+    fn native(e: &Expr, values: &[Fr]) -> Option<Fr> {
+        Some(match e {
+            Expr::Leaf(i) => values[*i],
+            Expr::Add(l, r) => native(l, values)? + native(r, values)?,
+            Expr::Sub(l, r) => native(l, values)? - native(r, values)?,
+            Expr::Mul(l, r) => native(l, values)? * native(r, values)?,
+            Expr::Div(l, r) => {
+                let inv: Option<Fr> = native(r, values)?.invert().into();
+                native(l, values)? * inv?
+            }
+        })
+    }
+
+    // The operators arith_ast! expands to.
+    fn helper(e: &Expr, values: &[Fr]) -> FieldArithHelper<Chip> {
+        match e {
+            Expr::Leaf(i) => FieldArithHelper::from(&values[*i]),
+            Expr::Add(l, r) => helper(l, values) + helper(r, values),
+            Expr::Sub(l, r) => helper(l, values) - helper(r, values),
+            Expr::Mul(l, r) => helper(l, values) * helper(r, values),
+            Expr::Div(l, r) => helper(l, values) / helper(r, values),
+        }
+    }
+
+    // The mock chip panics on a division by zero, as a witness that can not be assigned.
+    fn eval(h: FieldArithHelper<Chip>) -> Option<Fr> {
+        let chip = Chip::default();
+        catch_unwind(AssertUnwindSafe(|| {
+            h.eval(&mut MockChipCtx::default(), &chip).unwrap()
+        }))
+        .ok()
+    }
+
+    fn field() -> impl Strategy<Value = Fr> {
+        // small values make divisions by zero likely enough
+        prop_oneof![
+            (0..3u64).prop_map(Fr::from),
+            any::<u64>().prop_map(Fr::from)
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_helper_matches_field_arithmetic(
+            values in prop::collection::vec(field(), 4),
+            e in expr(4),
+        ) {
+            prop_assert_eq!(eval(helper(&e, &values)), native(&e, &values));
+        }
+
+        #[test]
+        fn test_arith_ast_precedence(values in prop::collection::vec(field(), 5)) {
+            let [a, b, c, d, e] = [values[0], values[1], values[2], values[3], values[4]];
+            let (ra, rb, rc, rd, re) = (&a, &b, &c, &d, &e);
+            let div = |x: Fr, y: Fr| Option::from(y.invert()).map(|inv: Fr| x * inv);
+
+            prop_assert_eq!(eval(arith_ast!(ra - rb + rc)), Some(a - b + c));
+            prop_assert_eq!(eval(arith_ast!(ra - rb - rc)), Some(a - b - c));
+            prop_assert_eq!(eval(arith_ast!(ra + rb * rc - rd)), Some(a + b * c - d));
+            prop_assert_eq!(eval(arith_ast!((ra - rb) * (rc + rd))), Some((a - b) * (c + d)));
+            prop_assert_eq!(
+                eval(arith_ast!(ra / rb / rc)),
+                div(a, b).and_then(|ab| div(ab, c))
+            );
+            prop_assert_eq!(
+                eval(arith_ast!(ra * rb / rc + rd / (re - ra))),
+                div(a * b, c).and_then(|x| div(d, e - a).map(|y| x + y))
+            );
+        }
+    }
 }
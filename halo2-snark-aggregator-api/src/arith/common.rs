@@ -39,4 +39,9 @@ pub trait ArithCommonChip {
         ctx: &mut Self::Context,
         v: &Self::AssignedValue,
     ) -> Result<Self::AssignedValue, Self::Error>;
+
+    // Next row the context assigns, for chips laying out cells in rows.
+    fn current_row(&self, _ctx: &Self::Context) -> Option<usize> {
+        None
+    }
 }
@@ -39,6 +39,19 @@ pub trait ArithEccChip:
         rhs: Self::Point,
     ) -> Result<Self::AssignedPoint, Self::Error>;
 
+    // lhs * rhs when rhs was assigned by assign_const, a constant of the circuit such as a vk
+    // commitment. The chip tells its constants from the point itself, never from a query key.
+    // None for any other point or when the chip has no cheaper way than a generic scalar_mul,
+    // the caller keeps the term in its multi_exp then.
+    fn scalar_mul_fixed_base(
+        &self,
+        _ctx: &mut Self::Context,
+        _lhs: &Self::AssignedScalar,
+        _rhs: &Self::AssignedPoint,
+    ) -> Result<Option<Self::AssignedPoint>, Self::Error> {
+        Ok(None)
+    }
+
     fn multi_exp(
         &self,
         ctx: &mut Self::Context,
@@ -58,4 +71,17 @@ pub trait ArithEccChip:
         }
         Ok(acc.unwrap())
     }
+
+    // cond ? a : b. The circuit chips constrain cond to be 0 or 1, this default does not.
+    fn select(
+        &self,
+        ctx: &mut Self::Context,
+        cond: &Self::AssignedScalar,
+        a: &Self::AssignedPoint,
+        b: &Self::AssignedPoint,
+    ) -> Result<Self::AssignedPoint, Self::Error> {
+        let diff = self.sub(ctx, a, b)?;
+        let t = self.scalar_mul(ctx, cond, &diff)?;
+        self.add(ctx, &t, b)
+    }
 }
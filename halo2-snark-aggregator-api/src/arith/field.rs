@@ -102,4 +102,16 @@ pub trait ArithFieldChip:
         }
         Ok(acc)
     }
+
+    // cond ? a : b. The circuit chips constrain cond to be 0 or 1, this default does not.
+    fn select(
+        &self,
+        ctx: &mut Self::Context,
+        cond: &Self::AssignedField,
+        a: &Self::AssignedField,
+        b: &Self::AssignedField,
+    ) -> Result<Self::AssignedField, Self::Error> {
+        let diff = self.sub(ctx, a, b)?;
+        self.mul_add(ctx, cond, &diff, b)
+    }
 }
@@ -0,0 +1,284 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Cell, Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+// A shared BITS-bit range table, so non-native limbs and point decompression
+// can range check their values without each gate owning its own table column.
+#[derive(Clone, Debug)]
+pub struct ByteRangeConfig<const BITS: usize> {
+    pub limb: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub q_lookup: Selector,
+    pub q_first: Selector,
+    pub q_running: Selector,
+    pub table: TableColumn,
+}
+
+pub struct ByteRangeChip<F: FieldExt, const BITS: usize> {
+    pub config: ByteRangeConfig<BITS>,
+    _phantom: PhantomData<F>,
+}
+
+pub type Range8Chip<F> = ByteRangeChip<F, 8>;
+pub type Range16Chip<F> = ByteRangeChip<F, 16>;
+
+impl<F: FieldExt, const BITS: usize> ByteRangeChip<F, BITS> {
+    pub fn new(config: ByteRangeConfig<BITS>) -> Self {
+        ByteRangeChip {
+            config,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> ByteRangeConfig<BITS> {
+        assert!(BITS > 0 && BITS <= 16);
+
+        let config = ByteRangeConfig {
+            limb: meta.advice_column(),
+            acc: meta.advice_column(),
+            q_lookup: meta.complex_selector(),
+            q_first: meta.selector(),
+            q_running: meta.selector(),
+            table: meta.lookup_table_column(),
+        };
+        // the recomposed value is copied to the cell it checks
+        meta.enable_equality(config.acc);
+
+        meta.lookup(|meta| {
+            let q_lookup = meta.query_selector(config.q_lookup);
+            let limb = meta.query_advice(config.limb, Rotation::cur());
+            vec![(q_lookup * limb, config.table)]
+        });
+
+        // limbs are assigned from the most significant one,
+        // acc accumulates acc_prev * 2^BITS + limb.
+        meta.create_gate("byte range first limb", |meta| {
+            let q_first = meta.query_selector(config.q_first);
+            let limb = meta.query_advice(config.limb, Rotation::cur());
+            let acc = meta.query_advice(config.acc, Rotation::cur());
+            vec![q_first * (acc - limb)]
+        });
+
+        meta.create_gate("byte range running sum", |meta| {
+            let q_running = meta.query_selector(config.q_running);
+            let limb = meta.query_advice(config.limb, Rotation::cur());
+            let acc = meta.query_advice(config.acc, Rotation::cur());
+            let acc_prev = meta.query_advice(config.acc, Rotation::prev());
+            let shift = F::from(1u64 << BITS);
+            vec![q_running * (acc - (acc_prev * shift + limb))]
+        });
+
+        config
+    }
+
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "byte range table",
+            |mut table| {
+                for i in 0..1 << BITS {
+                    table.assign_cell(
+                        || "byte range table",
+                        self.config.table,
+                        i,
+                        || Ok(F::from(i as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+
+    // Splits the value of input into `limbs` BITS-bit limbs, checks each of them in the table
+    // and constrains their recomposition to input, so input is below 2^(limbs * BITS). A value
+    // that does not fit fails the synthesis. Returns the cell of the recomposed value.
+    pub fn assign_decompose(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        input: Cell,
+        value: Option<F>,
+        limbs: usize,
+    ) -> Result<Cell, Error> {
+        // the recomposition must not wrap around the modulus
+        assert!(limbs > 0 && limbs * BITS < F::NUM_BITS as usize);
+
+        let limb_values = match value {
+            Some(v) => Some(Self::decompose(v, limbs).ok_or(Error::Synthesis)?),
+            None => None,
+        };
+        let shift = F::from(1u64 << BITS);
+        let mut acc = Some(F::zero());
+        let mut acc_cell = None;
+
+        for i in 0..limbs {
+            let limb = limb_values.as_ref().map(|l| l[i]);
+            acc = acc.zip(limb).map(|(acc, limb)| acc * shift + limb);
+
+            self.config.q_lookup.enable(region, offset + i)?;
+            if i == 0 {
+                self.config.q_first.enable(region, offset + i)?;
+            } else {
+                self.config.q_running.enable(region, offset + i)?;
+            }
+
+            region.assign_advice(
+                || "byte range limb",
+                self.config.limb,
+                offset + i,
+                || limb.ok_or(Error::Synthesis),
+            )?;
+            let cell = region.assign_advice(
+                || "byte range acc",
+                self.config.acc,
+                offset + i,
+                || acc.ok_or(Error::Synthesis),
+            )?;
+            acc_cell = Some(cell.cell());
+        }
+
+        let acc_cell = acc_cell.unwrap();
+        region.constrain_equal(acc_cell, input)?;
+        Ok(acc_cell)
+    }
+
+    pub fn assign_in_range(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        input: Cell,
+        value: Option<F>,
+    ) -> Result<Cell, Error> {
+        self.assign_decompose(region, offset, input, value, 1)
+    }
+
+    // Little-endian bytes are regrouped into BITS-bit limbs, most significant first. None when
+    // v has a bit set at or above limbs * BITS.
+    fn decompose(v: F, limbs: usize) -> Option<Vec<F>> {
+        let repr = v.to_repr();
+        let bytes = repr.as_ref();
+        let bit = |i: usize| {
+            bytes
+                .get(i / 8)
+                .map_or(0u64, |b| ((b >> (i % 8)) & 1) as u64)
+        };
+
+        if (limbs * BITS..bytes.len() * 8).any(|i| bit(i) == 1) {
+            return None;
+        }
+
+        let limbs = (0..limbs)
+            .rev()
+            .map(|l| {
+                let limb = (0..BITS).fold(0u64, |acc, j| acc | (bit(l * BITS + j) << j));
+                F::from(limb)
+            })
+            .collect();
+        Some(limbs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner, dev::MockProver, pairing::bn256::Fr as Fp, plonk::Circuit,
+    };
+
+    // input is assigned in a column of its own, value is the witness handed to the chip
+    #[derive(Default)]
+    struct TestCircuit {
+        input: Option<Fp>,
+        value: Option<Fp>,
+        limbs: usize,
+    }
+
+    impl TestCircuit {
+        fn new(input: u64, value: u64, limbs: usize) -> Self {
+            TestCircuit {
+                input: Some(Fp::from(input)),
+                value: Some(Fp::from(value)),
+                limbs,
+            }
+        }
+    }
+
+    impl Circuit<Fp> for TestCircuit {
+        type Config = (ByteRangeConfig<8>, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                input: None,
+                value: None,
+                limbs: self.limbs,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let input = meta.advice_column();
+            meta.enable_equality(input);
+            (Range8Chip::configure(meta), input)
+        }
+
+        fn synthesize(
+            &self,
+            (config, input): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = Range8Chip::new(config);
+            chip.load_table(&mut layouter)?;
+            layouter.assign_region(
+                || "range",
+                |mut region| {
+                    let cell = region.assign_advice(
+                        || "input",
+                        input,
+                        0,
+                        || self.input.ok_or(Error::Synthesis),
+                    )?;
+                    chip.assign_decompose(&mut region, 0, cell.cell(), self.value, self.limbs)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_byte_range_decompose() {
+        let circuit = TestCircuit::new(0x12345678, 0x12345678, 4);
+        let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_byte_range_recomposition_is_bound_to_input() {
+        // limbs of another value are all in range, the copy to the input cell fails
+        let circuit = TestCircuit::new(0x12345678, 0x12345679, 4);
+        let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_byte_range_rejects_oversize_input() {
+        let circuit = TestCircuit::new(0x1_0000_0000, 0x1_0000_0000, 4);
+        assert!(MockProver::run(10, &circuit, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_byte_range_decompose_limbs() {
+        let limbs = ByteRangeChip::<Fp, 16>::decompose(Fp::from(0x0001_0002_0003u64), 3);
+        assert_eq!(
+            limbs,
+            Some(vec![Fp::from(1u64), Fp::from(2u64), Fp::from(3u64)])
+        );
+        assert_eq!(
+            ByteRangeChip::<Fp, 16>::decompose(Fp::from(0x0001_0002_0003u64), 2),
+            None
+        );
+    }
+}
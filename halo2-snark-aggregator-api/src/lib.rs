@@ -2,6 +2,7 @@
 pub mod arith;
 pub mod hash;
 pub mod mock;
+pub mod prelude;
 pub mod systems;
 pub mod tests;
 pub mod transcript;
@@ -1,2 +1,3 @@
 pub mod arith;
+pub mod trace;
 pub mod transcript_encode;
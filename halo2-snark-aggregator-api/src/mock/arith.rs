@@ -1,2 +1,3 @@
 pub mod ecc;
 pub mod field;
+pub mod msm;
@@ -1,8 +1,10 @@
 use super::field::MockFieldChip;
+use super::msm::multiexp_parallel;
 use crate::arith::{common::ArithCommonChip, ecc::ArithEccChip};
 use crate::mock::arith::field::MockChipCtx;
 use group::{Curve, Group};
 use halo2_proofs::arithmetic::CurveAffine;
+use rayon::prelude::*;
 use std::marker::PhantomData;
 
 pub struct MockEccChip<C: CurveAffine, E> {
@@ -111,18 +113,85 @@ impl<C: CurveAffine, E> ArithEccChip for MockEccChip<C, E> {
         scalars: Vec<Self::AssignedScalar>,
     ) -> Result<Self::AssignedPoint, Self::Error> {
         ctx.point_list = points.clone().into_iter().map(|x| format!("{:?}", x)).collect();
-        let mut acc = None;
-        for (p, s) in points.iter().zip(scalars.iter()) {
-            let curr = self.scalar_mul(ctx, s, p)?;
-            acc = match acc {
-                None => Some(curr),
-                Some(_acc) => {
-                    let p = self.add(ctx, &_acc, &curr)?;
-                    Some(p)
-                }
-            }
-        }
-        Ok(acc.unwrap())
+        assert!(!points.is_empty());
+
+        Ok(multiexp_parallel::<C>(&scalars[..], &points[..]))
+    }
+}
+
+impl<C: CurveAffine, E> MockEccChip<C, E> {
+    pub fn batch_scalar_mul(
+        &self,
+        _ctx: &mut MockChipCtx,
+        scalars: &[C::ScalarExt],
+        points: &[C::CurveExt],
+    ) -> Result<Vec<C::CurveExt>, E> {
+        assert_eq!(scalars.len(), points.len());
+        Ok(points
+            .par_iter()
+            .zip(scalars.par_iter())
+            .map(|(p, s)| *p * *s)
+            .collect())
+    }
+
+    pub fn batch_scalar_mul_constant(
+        &self,
+        _ctx: &mut MockChipCtx,
+        scalars: &[C::ScalarExt],
+        points: &[C],
+    ) -> Result<Vec<C::CurveExt>, E> {
+        assert_eq!(scalars.len(), points.len());
+        Ok(points
+            .par_iter()
+            .zip(scalars.par_iter())
+            .map(|(p, s)| *p * *s)
+            .collect())
     }
 
+    // Converts chunks in parallel, each chunk shares one inversion.
+    pub fn batch_to_value(&self, points: &[C::CurveExt]) -> Result<Vec<C>, E> {
+        let mut values = vec![C::identity(); points.len()];
+        let chunk_size = usize::max(1, points.len() / rayon::current_num_threads());
+        points
+            .par_chunks(chunk_size)
+            .zip(values.par_chunks_mut(chunk_size))
+            .for_each(|(points, values)| C::CurveExt::batch_normalize(points, values));
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{arithmetic::Field, pairing::bn256::Fr as Fp, plonk::Error};
+    use pairing_bn256::bn256::G1Affine;
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    #[test]
+    fn test_batch_ops_match_single_ops() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        let pchip = MockEccChip::<G1Affine, Error>::default();
+        let ctx = &mut MockChipCtx::default();
+
+        let scalars = (0..17).map(|_| Fp::random(&mut rng)).collect::<Vec<_>>();
+        let points = scalars.iter().map(|s| pchip.one * s).collect::<Vec<_>>();
+
+        let batched = pchip
+            .batch_scalar_mul(ctx, &scalars[..], &points[..])
+            .unwrap();
+        for ((s, p), b) in scalars.iter().zip(points.iter()).zip(batched.iter()) {
+            assert_eq!(pchip.scalar_mul(ctx, s, p).unwrap(), *b);
+        }
+
+        let values = pchip.batch_to_value(&batched[..]).unwrap();
+        for (b, v) in batched.iter().zip(values.iter()) {
+            assert_eq!(pchip.to_value(b).unwrap(), *v);
+        }
+
+        let msm = pchip
+            .multi_exp(ctx, points.clone(), scalars.clone())
+            .unwrap();
+        assert_eq!(msm, batched.iter().fold(pchip.zero, |acc, p| acc + p));
+    }
 }
@@ -1,5 +1,7 @@
 use crate::arith::{common::ArithCommonChip, field::ArithFieldChip};
+use group::ff::BatchInvert;
 use halo2_proofs::arithmetic::FieldExt;
+use rayon::prelude::*;
 use std::marker::PhantomData;
 
 pub struct MockFieldChip<F: FieldExt, E> {
@@ -110,7 +112,7 @@ impl<F: FieldExt, E> ArithFieldChip for MockFieldChip<F, E> {
         a: &Self::AssignedField,
         b: &Self::AssignedField,
     ) -> Result<Self::AssignedField, Self::Error> {
-        Ok(*a * b.invert().unwrap())
+        Ok(*a * b.invert().expect("division by zero"))
     }
 
     fn square(
@@ -144,3 +146,23 @@ impl<F: FieldExt, E> ArithFieldChip for MockFieldChip<F, E> {
         Ok(*a * *b + c)
     }
 }
+
+impl<F: FieldExt, E> MockFieldChip<F, E> {
+    pub fn batch_mul(&self, _ctx: &mut MockChipCtx, a: &[F], b: &[F]) -> Result<Vec<F>, E> {
+        assert_eq!(a.len(), b.len());
+        Ok(a.par_iter()
+            .zip(b.par_iter())
+            .map(|(a, b)| *a * *b)
+            .collect())
+    }
+
+    // Chunks are inverted in parallel with one inversion each, zeros are left untouched.
+    pub fn batch_invert(&self, _ctx: &mut MockChipCtx, a: &[F]) -> Result<Vec<F>, E> {
+        let mut values = a.to_vec();
+        let chunk_size = usize::max(1, a.len() / rayon::current_num_threads());
+        values.par_chunks_mut(chunk_size).for_each(|chunk| {
+            chunk.iter_mut().batch_invert();
+        });
+        Ok(values)
+    }
+}
@@ -0,0 +1,105 @@
+// Native Pippenger multi exponentiation, for the accumulator evaluation of MockEccChip: the
+// scalars are cut into c bit windows, the bases are summed into one bucket per window digit
+// and the buckets are combined with a running sum, c doublings per window.
+use group::ff::PrimeField;
+use group::Group;
+use halo2_proofs::arithmetic::CurveAffine;
+use rayon::prelude::*;
+
+// digit of the window `segment` of a little endian scalar repr
+fn window_digit(segment: usize, c: usize, bytes: &[u8]) -> usize {
+    let skip_bits = segment * c;
+    (0..c).fold(0, |digit, i| {
+        let bit = skip_bits + i;
+        let b = bytes
+            .get(bit / 8)
+            .map_or(0, |byte| ((byte >> (bit % 8)) & 1) as usize);
+        digit | (b << i)
+    })
+}
+
+fn window_size(n: usize) -> usize {
+    if n < 4 {
+        1
+    } else if n < 32 {
+        3
+    } else {
+        (n as f64).ln().ceil() as usize
+    }
+}
+
+pub fn multiexp_serial<C: CurveAffine>(
+    scalars: &[C::ScalarExt],
+    bases: &[C::CurveExt],
+) -> C::CurveExt {
+    assert_eq!(scalars.len(), bases.len());
+    let reprs = scalars.iter().map(|s| s.to_repr()).collect::<Vec<_>>();
+    let c = window_size(bases.len());
+    let segments = (<C::ScalarExt as PrimeField>::NUM_BITS as usize + c - 1) / c;
+
+    let mut acc = C::CurveExt::identity();
+    for segment in (0..segments).rev() {
+        for _ in 0..c {
+            acc = acc.double();
+        }
+
+        let mut buckets = vec![C::CurveExt::identity(); (1 << c) - 1];
+        for (repr, base) in reprs.iter().zip(bases.iter()) {
+            let digit = window_digit(segment, c, repr.as_ref());
+            if digit != 0 {
+                buckets[digit - 1] += base;
+            }
+        }
+
+        // sum of digit * bucket[digit]
+        let mut running_sum = C::CurveExt::identity();
+        for bucket in buckets.into_iter().rev() {
+            running_sum += bucket;
+            acc += running_sum;
+        }
+    }
+    acc
+}
+
+// Splits the terms in one chunk per thread, each chunk runs its own Pippenger.
+pub fn multiexp_parallel<C: CurveAffine>(
+    scalars: &[C::ScalarExt],
+    bases: &[C::CurveExt],
+) -> C::CurveExt {
+    assert_eq!(scalars.len(), bases.len());
+    let threads = rayon::current_num_threads();
+    let chunk_size = usize::max(1, (bases.len() + threads - 1) / threads);
+    scalars
+        .par_chunks(chunk_size)
+        .zip(bases.par_chunks(chunk_size))
+        .map(|(scalars, bases)| multiexp_serial::<C>(scalars, bases))
+        .reduce(C::CurveExt::identity, |a, b| a + b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{arithmetic::Field, pairing::bn256::Fr as Fp};
+    use pairing_bn256::bn256::{G1Affine, G1};
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    #[test]
+    fn test_multiexp_matches_naive_sum() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        for n in [0, 1, 3, 17, 40, 130] {
+            let scalars = (0..n).map(|_| Fp::random(&mut rng)).collect::<Vec<_>>();
+            let bases = (0..n).map(|_| G1::random(&mut rng)).collect::<Vec<_>>();
+            let naive = scalars
+                .iter()
+                .zip(bases.iter())
+                .fold(G1::identity(), |acc, (s, p)| acc + *p * s);
+
+            assert_eq!(multiexp_serial::<G1Affine>(&scalars[..], &bases[..]), naive);
+            assert_eq!(
+                multiexp_parallel::<G1Affine>(&scalars[..], &bases[..]),
+                naive
+            );
+        }
+    }
+}
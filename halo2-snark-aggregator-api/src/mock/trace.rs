@@ -0,0 +1,417 @@
+use crate::arith::{common::ArithCommonChip, ecc::ArithEccChip, field::ArithFieldChip};
+use group::Curve;
+use halo2_proofs::arithmetic::{CurveAffine, FieldExt};
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::rc::Rc;
+
+#[derive(Clone, Debug)]
+pub struct TraceNode {
+    pub op: &'static str,
+    pub operands: Vec<usize>,
+    pub constants: Vec<String>,
+    pub value: String,
+}
+
+impl std::fmt::Display for TraceNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operands = self
+            .operands
+            .iter()
+            .map(|id| format!("%{}", id))
+            .chain(self.constants.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "{}({}) = {}", self.op, operands, self.value)
+    }
+}
+
+// Records every operation of the native chips as a node of a DAG,
+// nodes refer to their operands by index so the dump can be diffed op by op.
+// Scripted nodes return a given value instead of the one they compute, so that the gate
+// implementations (lookup, permutation, vanish) can be unit tested on chosen intermediates.
+#[derive(Default, Clone)]
+pub struct TracingContext {
+    pub nodes: Vec<TraceNode>,
+    scripted: BTreeMap<usize, Rc<dyn Any>>,
+}
+
+impl TracingContext {
+    // The node `id` of the next run returns `v`, ids are deterministic so they can be taken
+    // from a first run on the same inputs. `v` is a field element or a C::CurveExt, the type
+    // of the node.
+    pub fn script<T: 'static>(&mut self, id: usize, v: T) {
+        self.scripted.insert(id, Rc::new(v));
+    }
+
+    fn scripted<T: Clone + 'static>(&self, v: T) -> T {
+        match self.scripted.get(&self.nodes.len()) {
+            Some(scripted) => scripted
+                .downcast_ref::<T>()
+                .expect("scripted value of another type than its node")
+                .clone(),
+            None => v,
+        }
+    }
+
+    pub fn ops(&self) -> Vec<&'static str> {
+        self.nodes.iter().map(|node| node.op).collect()
+    }
+
+    // First node of `op` on exactly these operands.
+    pub fn find(&self, op: &str, operands: &[usize]) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|node| node.op == op && node.operands == operands)
+    }
+
+    fn record(
+        &mut self,
+        op: &'static str,
+        operands: Vec<usize>,
+        constants: Vec<String>,
+        value: String,
+    ) -> usize {
+        self.nodes.push(TraceNode {
+            op,
+            operands,
+            constants,
+            value,
+        });
+        self.nodes.len() - 1
+    }
+
+    pub fn dump<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut fd = std::fs::File::create(path)?;
+        for (id, node) in self.nodes.iter().enumerate() {
+            writeln!(fd, "%{} = {}", id, node)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for TracingContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "(traced ops: {})", self.nodes.len())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Traced<T> {
+    pub id: usize,
+    pub v: T,
+}
+
+pub struct TracingFieldChip<F: FieldExt, E> {
+    _data: PhantomData<(F, E)>,
+}
+
+impl<F: FieldExt, E> Default for TracingFieldChip<F, E> {
+    fn default() -> Self {
+        Self { _data: PhantomData }
+    }
+}
+
+impl<F: FieldExt, E> TracingFieldChip<F, E> {
+    fn trace(
+        ctx: &mut TracingContext,
+        op: &'static str,
+        operands: Vec<&Traced<F>>,
+        constants: Vec<F>,
+        v: F,
+    ) -> Traced<F> {
+        let v = ctx.scripted(v);
+        let id = ctx.record(
+            op,
+            operands.iter().map(|x| x.id).collect(),
+            constants.iter().map(|c| format!("{:?}", c)).collect(),
+            format!("{:?}", v),
+        );
+        Traced { id, v }
+    }
+}
+
+impl<F: FieldExt, E> ArithCommonChip for TracingFieldChip<F, E> {
+    type Context = TracingContext;
+    type Value = F;
+    type AssignedValue = Traced<F>;
+    type Error = E;
+
+    fn add(
+        &self,
+        ctx: &mut Self::Context,
+        a: &Self::AssignedValue,
+        b: &Self::AssignedValue,
+    ) -> Result<Self::AssignedValue, Self::Error> {
+        Ok(Self::trace(ctx, "add", vec![a, b], vec![], a.v + b.v))
+    }
+
+    fn sub(
+        &self,
+        ctx: &mut Self::Context,
+        a: &Self::AssignedValue,
+        b: &Self::AssignedValue,
+    ) -> Result<Self::AssignedValue, Self::Error> {
+        Ok(Self::trace(ctx, "sub", vec![a, b], vec![], a.v - b.v))
+    }
+
+    fn assign_zero(&self, ctx: &mut Self::Context) -> Result<Self::AssignedValue, Self::Error> {
+        self.assign_const(ctx, F::zero())
+    }
+
+    fn assign_one(&self, ctx: &mut Self::Context) -> Result<Self::AssignedValue, Self::Error> {
+        self.assign_const(ctx, F::one())
+    }
+
+    fn assign_const(
+        &self,
+        ctx: &mut Self::Context,
+        c: Self::Value,
+    ) -> Result<Self::AssignedValue, Self::Error> {
+        Ok(Self::trace(ctx, "const", vec![], vec![], c))
+    }
+
+    fn assign_var(
+        &self,
+        ctx: &mut Self::Context,
+        v: Self::Value,
+    ) -> Result<Self::AssignedValue, Self::Error> {
+        Ok(Self::trace(ctx, "var", vec![], vec![], v))
+    }
+
+    fn to_value(&self, v: &Self::AssignedValue) -> Result<Self::Value, Self::Error> {
+        Ok(v.v)
+    }
+
+    fn normalize(
+        &self,
+        _ctx: &mut Self::Context,
+        v: &Self::AssignedValue,
+    ) -> Result<Self::AssignedValue, Self::Error> {
+        Ok(v.clone())
+    }
+}
+
+impl<F: FieldExt, E> ArithFieldChip for TracingFieldChip<F, E> {
+    type Field = F;
+    type AssignedField = Traced<F>;
+
+    fn mul(
+        &self,
+        ctx: &mut Self::Context,
+        a: &Self::AssignedField,
+        b: &Self::AssignedField,
+    ) -> Result<Self::AssignedField, Self::Error> {
+        Ok(Self::trace(ctx, "mul", vec![a, b], vec![], a.v * b.v))
+    }
+
+    fn div(
+        &self,
+        ctx: &mut Self::Context,
+        a: &Self::AssignedField,
+        b: &Self::AssignedField,
+    ) -> Result<Self::AssignedField, Self::Error> {
+        let v = a.v * b.v.invert().unwrap();
+        Ok(Self::trace(ctx, "div", vec![a, b], vec![], v))
+    }
+
+    fn square(
+        &self,
+        ctx: &mut Self::Context,
+        a: &Self::AssignedField,
+    ) -> Result<Self::AssignedField, Self::Error> {
+        Ok(Self::trace(ctx, "square", vec![a], vec![], a.v * a.v))
+    }
+
+    fn sum_with_coeff_and_constant(
+        &self,
+        ctx: &mut Self::Context,
+        a_with_coeff: Vec<(&Self::AssignedField, Self::Value)>,
+        b: Self::Field,
+    ) -> Result<Self::AssignedField, Self::Error> {
+        let mut acc = b;
+        for (x, coeff) in a_with_coeff.iter() {
+            acc = acc + x.v * coeff
+        }
+        let (operands, mut constants): (Vec<_>, Vec<_>) = a_with_coeff.into_iter().unzip();
+        constants.push(b);
+        Ok(Self::trace(
+            ctx,
+            "sum_with_coeff_and_constant",
+            operands,
+            constants,
+            acc,
+        ))
+    }
+
+    fn mul_add_constant(
+        &self,
+        ctx: &mut Self::Context,
+        a: &Self::AssignedField,
+        b: &Self::AssignedField,
+        c: Self::Field,
+    ) -> Result<Self::AssignedField, Self::Error> {
+        let v = a.v * b.v + c;
+        Ok(Self::trace(ctx, "mul_add_constant", vec![a, b], vec![c], v))
+    }
+}
+
+pub struct TracingEccChip<C: CurveAffine, E> {
+    _data: PhantomData<(C, E)>,
+}
+
+impl<C: CurveAffine, E> Default for TracingEccChip<C, E> {
+    fn default() -> Self {
+        Self { _data: PhantomData }
+    }
+}
+
+impl<C: CurveAffine, E> TracingEccChip<C, E> {
+    fn trace<T: Debug>(
+        ctx: &mut TracingContext,
+        op: &'static str,
+        operands: Vec<usize>,
+        constants: Vec<T>,
+        v: C::CurveExt,
+    ) -> Traced<C::CurveExt> {
+        let v = ctx.scripted(v);
+        let id = ctx.record(
+            op,
+            operands,
+            constants.iter().map(|c| format!("{:?}", c)).collect(),
+            format!("{:?}", v.to_affine()),
+        );
+        Traced { id, v }
+    }
+}
+
+impl<C: CurveAffine, E> ArithCommonChip for TracingEccChip<C, E> {
+    type Context = TracingContext;
+    type Value = C;
+    type AssignedValue = Traced<C::CurveExt>;
+    type Error = E;
+
+    fn add(
+        &self,
+        ctx: &mut Self::Context,
+        a: &Self::AssignedValue,
+        b: &Self::AssignedValue,
+    ) -> Result<Self::AssignedValue, Self::Error> {
+        Ok(Self::trace::<C>(
+            ctx,
+            "ecc_add",
+            vec![a.id, b.id],
+            vec![],
+            a.v + b.v,
+        ))
+    }
+
+    fn sub(
+        &self,
+        ctx: &mut Self::Context,
+        a: &Self::AssignedValue,
+        b: &Self::AssignedValue,
+    ) -> Result<Self::AssignedValue, Self::Error> {
+        Ok(Self::trace::<C>(
+            ctx,
+            "ecc_sub",
+            vec![a.id, b.id],
+            vec![],
+            a.v - b.v,
+        ))
+    }
+
+    fn assign_zero(&self, ctx: &mut Self::Context) -> Result<Self::AssignedValue, Self::Error> {
+        self.assign_const(ctx, C::identity())
+    }
+
+    fn assign_one(&self, ctx: &mut Self::Context) -> Result<Self::AssignedValue, Self::Error> {
+        self.assign_const(ctx, C::generator())
+    }
+
+    fn assign_const(
+        &self,
+        ctx: &mut Self::Context,
+        c: C,
+    ) -> Result<Self::AssignedValue, Self::Error> {
+        Ok(Self::trace::<C>(
+            ctx,
+            "ecc_const",
+            vec![],
+            vec![],
+            c.to_curve(),
+        ))
+    }
+
+    fn assign_var(
+        &self,
+        ctx: &mut Self::Context,
+        v: C,
+    ) -> Result<Self::AssignedValue, Self::Error> {
+        Ok(Self::trace::<C>(
+            ctx,
+            "ecc_var",
+            vec![],
+            vec![],
+            v.to_curve(),
+        ))
+    }
+
+    fn to_value(&self, v: &Self::AssignedValue) -> Result<C, Self::Error> {
+        Ok(v.v.to_affine())
+    }
+
+    fn normalize(
+        &self,
+        _ctx: &mut Self::Context,
+        v: &Self::AssignedValue,
+    ) -> Result<Self::AssignedValue, Self::Error> {
+        Ok(v.clone())
+    }
+}
+
+impl<C: CurveAffine, E> ArithEccChip for TracingEccChip<C, E> {
+    type Point = C;
+    type AssignedPoint = Traced<C::CurveExt>;
+    type Scalar = C::ScalarExt;
+    type AssignedScalar = Traced<C::ScalarExt>;
+    type Native = C::ScalarExt;
+    type AssignedNative = Traced<C::ScalarExt>;
+
+    type ScalarChip = TracingFieldChip<C::ScalarExt, E>;
+    type NativeChip = TracingFieldChip<C::ScalarExt, E>;
+
+    fn scalar_mul(
+        &self,
+        ctx: &mut Self::Context,
+        lhs: &Self::AssignedScalar,
+        rhs: &Self::AssignedPoint,
+    ) -> Result<Self::AssignedPoint, Self::Error> {
+        Ok(Self::trace::<C>(
+            ctx,
+            "scalar_mul",
+            vec![lhs.id, rhs.id],
+            vec![],
+            rhs.v * lhs.v,
+        ))
+    }
+
+    fn scalar_mul_constant(
+        &self,
+        ctx: &mut Self::Context,
+        lhs: &Self::AssignedScalar,
+        rhs: Self::Point,
+    ) -> Result<Self::AssignedPoint, Self::Error> {
+        Ok(Self::trace(
+            ctx,
+            "scalar_mul_constant",
+            vec![lhs.id],
+            vec![rhs],
+            rhs * lhs.v,
+        ))
+    }
+}
@@ -0,0 +1,21 @@
+// Stable surface of the api crate, `use halo2_snark_aggregator_api::prelude::*;`
+// covers what downstream circuits and code generators need.
+pub use crate::arith::{common::ArithCommonChip, ecc::ArithEccChip, field::ArithFieldChip};
+pub use crate::hash::poseidon::PoseidonChip;
+pub use crate::systems::halo2::{
+    evaluation::{Accumulator, CommitQuery, EvaluationQuery, EvaluationQuerySchema, QueryItem},
+    multiopen::{CostProfile, MultiOpenProof},
+    params::{PlonkCommonSetup, VerifierParams},
+    transcript::PoseidonTranscriptRead,
+    verify::{
+        assign_instance_commitment, build_verifier_params, verify_aggregation_proofs_in_chip,
+        verify_single_proof_in_chip, verify_single_proof_no_eval, CircuitProof, ProofData,
+    },
+};
+pub use crate::systems::pairing::{multi_pairing_native, MultiPairingCheck};
+pub use crate::transcript::{
+    encode::Encode,
+    read::TranscriptRead,
+    sha::{Challenge128, ShaRead, ShaWrite},
+};
+pub use crate::{arith_ast, commit, eval, scalar};
@@ -1 +1,5 @@
+#[cfg(feature = "gnark")]
+pub mod gnark;
+pub mod groth16;
 pub mod halo2;
+pub mod pairing;
@@ -0,0 +1,403 @@
+// Readers of the BN254 PLONK verifying key and proof of gnark (v0.9 WriteTo, compressed
+// points), behind the off-by-default `gnark` feature. Only the readers: gnark proofs can not
+// be aggregated.
+//
+// They are not mapped into VerifierParams, and won't be: that is the halo2 verifier, its
+// constraint system and multiopen argument do not describe a gnark circuit, and gnark
+// derives its challenges with a SHA-256 transcript there is no chip for in this tree. The
+// parsed points and claimed values are what a separate in-chip gnark verifier would start from.
+use group::ff::PrimeField;
+use group::prime::PrimeCurveAffine;
+use halo2_proofs::arithmetic::{CurveAffine, Field};
+use num_bigint::BigUint;
+use pairing_bn256::bn256::{Fq, Fq2, Fr, G1Affine, G2Affine};
+
+// flags in the top two bits of the first byte of a point
+const FLAG_MASK: u8 = 0b11 << 6;
+const UNCOMPRESSED: u8 = 0;
+const COMPRESSED_SMALLEST: u8 = 0b10 << 6;
+const COMPRESSED_LARGEST: u8 = 0b11 << 6;
+const COMPRESSED_INFINITY: u8 = 0b01 << 6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GnarkError {
+    UnexpectedEnd,
+    TrailingBytes,
+    InvalidField,
+    InvalidPoint,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GnarkPlonkVerifyingKey {
+    pub size: u64,
+    pub size_inv: Fr,
+    pub generator: Fr,
+    pub nb_public_variables: u64,
+    pub coset_shift: Fr,
+    // permutation commitments
+    pub s: [G1Affine; 3],
+    pub ql: G1Affine,
+    pub qr: G1Affine,
+    pub qm: G1Affine,
+    pub qo: G1Affine,
+    pub qk: G1Affine,
+    // commitments of the custom gates of bsb22 commitments
+    pub qcp: Vec<G1Affine>,
+    pub kzg_g1: G1Affine,
+    // [1] and [s] of the srs
+    pub kzg_g2: [G2Affine; 2],
+    pub commitment_constraint_indexes: Vec<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GnarkPlonkProof {
+    // commitments of the l, r and o wires
+    pub lro: [G1Affine; 3],
+    pub z: G1Affine,
+    pub h: [G1Affine; 3],
+    pub bsb22_commitments: Vec<G1Affine>,
+    // opening at zeta of the batched polynomials, and their claimed values
+    pub batched_proof_h: G1Affine,
+    pub batched_claimed_values: Vec<Fr>,
+    // opening of z at zeta * generator
+    pub z_shifted_opening_h: G1Affine,
+    pub z_shifted_claimed_value: Fr,
+}
+
+fn to_biguint<F: PrimeField>(f: &F) -> BigUint {
+    BigUint::from_bytes_le(f.to_repr().as_ref())
+}
+
+// y > -y, the choice of gnark between the two roots
+fn fq_lexicographically_largest(y: &Fq) -> bool {
+    to_biguint(y) > to_biguint(&-*y)
+}
+
+fn fq2_lexicographically_largest(y: &Fq2) -> bool {
+    if bool::from(y.c1.is_zero()) {
+        fq_lexicographically_largest(&y.c0)
+    } else {
+        fq_lexicographically_largest(&y.c1)
+    }
+}
+
+// Field element of 32 big endian bytes, None if it is not reduced.
+fn from_be<F: PrimeField>(bytes: &[u8]) -> Option<F> {
+    let mut repr = F::Repr::default();
+    repr.as_mut()
+        .iter_mut()
+        .zip(bytes.iter().rev())
+        .for_each(|(r, b)| *r = *b);
+    Option::from(F::from_repr(repr))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], GnarkError> {
+        if self.bytes.len() < n {
+            return Err(GnarkError::UnexpectedEnd);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, GnarkError> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, GnarkError> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn fr(&mut self) -> Result<Fr, GnarkError> {
+        from_be(self.take(32)?).ok_or(GnarkError::InvalidField)
+    }
+
+    fn fq(bytes: &[u8]) -> Result<Fq, GnarkError> {
+        from_be(bytes).ok_or(GnarkError::InvalidField)
+    }
+
+    // flag and first word of a point, with the flag bits cleared
+    fn flagged(&mut self) -> Result<(u8, [u8; 32]), GnarkError> {
+        let mut word: [u8; 32] = self.take(32)?.try_into().unwrap();
+        let flag = word[0] & FLAG_MASK;
+        word[0] &= !FLAG_MASK;
+        Ok((flag, word))
+    }
+
+    fn g1(&mut self) -> Result<G1Affine, GnarkError> {
+        let (flag, word) = self.flagged()?;
+        let point = match flag {
+            COMPRESSED_INFINITY if word.iter().all(|b| *b == 0) => {
+                return Ok(G1Affine::identity());
+            }
+            UNCOMPRESSED => {
+                let (x, y) = (Self::fq(&word)?, Self::fq(self.take(32)?)?);
+                G1Affine::from_xy(x, y)
+            }
+            COMPRESSED_SMALLEST | COMPRESSED_LARGEST => {
+                let x = Self::fq(&word)?;
+                let y = Option::<Fq>::from((x.square() * x + G1Affine::b()).sqrt())
+                    .ok_or(GnarkError::InvalidPoint)?;
+                let largest = flag == COMPRESSED_LARGEST;
+                let y = if fq_lexicographically_largest(&y) == largest {
+                    y
+                } else {
+                    -y
+                };
+                G1Affine::from_xy(x, y)
+            }
+            _ => return Err(GnarkError::InvalidPoint),
+        };
+        Option::from(point).ok_or(GnarkError::InvalidPoint)
+    }
+
+    // x.A1 then x.A0, the flags are in x.A1
+    fn g2(&mut self) -> Result<G2Affine, GnarkError> {
+        let (flag, word) = self.flagged()?;
+        let rest = self.take(32)?;
+        if flag == COMPRESSED_INFINITY {
+            return if word.iter().chain(rest.iter()).all(|b| *b == 0) {
+                Ok(G2Affine::identity())
+            } else {
+                Err(GnarkError::InvalidPoint)
+            };
+        }
+        if flag != COMPRESSED_SMALLEST && flag != COMPRESSED_LARGEST {
+            return Err(GnarkError::InvalidPoint);
+        }
+        let x = Fq2 {
+            c0: Self::fq(rest)?,
+            c1: Self::fq(&word)?,
+        };
+        let y = Option::<Fq2>::from((x.square() * x + G2Affine::b()).sqrt())
+            .ok_or(GnarkError::InvalidPoint)?;
+        let largest = flag == COMPRESSED_LARGEST;
+        let y = if fq2_lexicographically_largest(&y) == largest {
+            y
+        } else {
+            -y
+        };
+        Option::from(G2Affine::from_xy(x, y)).ok_or(GnarkError::InvalidPoint)
+    }
+
+    fn vec<T>(
+        &mut self,
+        read: impl Fn(&mut Self) -> Result<T, GnarkError>,
+    ) -> Result<Vec<T>, GnarkError> {
+        let len = self.u32()?;
+        (0..len).map(|_| read(self)).collect()
+    }
+
+    fn end(&self) -> Result<(), GnarkError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(GnarkError::TrailingBytes)
+        }
+    }
+}
+
+pub fn read_gnark_plonk_vk(bytes: &[u8]) -> Result<GnarkPlonkVerifyingKey, GnarkError> {
+    let r = &mut Reader { bytes };
+    let vk = GnarkPlonkVerifyingKey {
+        size: r.u64()?,
+        size_inv: r.fr()?,
+        generator: r.fr()?,
+        nb_public_variables: r.u64()?,
+        coset_shift: r.fr()?,
+        s: [r.g1()?, r.g1()?, r.g1()?],
+        ql: r.g1()?,
+        qr: r.g1()?,
+        qm: r.g1()?,
+        qo: r.g1()?,
+        qk: r.g1()?,
+        qcp: r.vec(Reader::g1)?,
+        kzg_g1: r.g1()?,
+        kzg_g2: [r.g2()?, r.g2()?],
+        commitment_constraint_indexes: r.vec(Reader::u64)?,
+    };
+    r.end()?;
+    Ok(vk)
+}
+
+pub fn read_gnark_plonk_proof(bytes: &[u8]) -> Result<GnarkPlonkProof, GnarkError> {
+    let r = &mut Reader { bytes };
+    let proof = GnarkPlonkProof {
+        lro: [r.g1()?, r.g1()?, r.g1()?],
+        z: r.g1()?,
+        h: [r.g1()?, r.g1()?, r.g1()?],
+        bsb22_commitments: r.vec(Reader::g1)?,
+        batched_proof_h: r.g1()?,
+        batched_claimed_values: r.vec(Reader::fr)?,
+        z_shifted_opening_h: r.g1()?,
+        z_shifted_claimed_value: r.fr()?,
+    };
+    r.end()?;
+    Ok(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use group::Curve;
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    fn be<F: PrimeField>(f: &F) -> Vec<u8> {
+        f.to_repr().as_ref().iter().rev().cloned().collect()
+    }
+
+    fn compress_g1(p: &G1Affine) -> Vec<u8> {
+        if bool::from(p.is_identity()) {
+            let mut bytes = vec![0u8; 32];
+            bytes[0] = COMPRESSED_INFINITY;
+            return bytes;
+        }
+        let coordinates = p.coordinates().unwrap();
+        let mut bytes = be(coordinates.x());
+        bytes[0] |= if fq_lexicographically_largest(coordinates.y()) {
+            COMPRESSED_LARGEST
+        } else {
+            COMPRESSED_SMALLEST
+        };
+        bytes
+    }
+
+    fn compress_g2(p: &G2Affine) -> Vec<u8> {
+        let coordinates = p.coordinates().unwrap();
+        let mut bytes = be(&coordinates.x().c1);
+        bytes.extend(be(&coordinates.x().c0));
+        bytes[0] |= if fq2_lexicographically_largest(coordinates.y()) {
+            COMPRESSED_LARGEST
+        } else {
+            COMPRESSED_SMALLEST
+        };
+        bytes
+    }
+
+    fn random_g1(rng: &mut XorShiftRng) -> G1Affine {
+        (G1Affine::generator() * Fr::random(rng)).to_affine()
+    }
+
+    #[test]
+    fn test_read_gnark_points() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        for _ in 0..8 {
+            let p = random_g1(&mut rng);
+            assert_eq!(
+                Reader {
+                    bytes: &compress_g1(&p)
+                }
+                .g1(),
+                Ok(p)
+            );
+            assert_eq!(
+                Reader {
+                    bytes: &compress_g1(&-p)
+                }
+                .g1(),
+                Ok(-p)
+            );
+
+            let q = (G2Affine::generator() * Fr::random(&mut rng)).to_affine();
+            assert_eq!(
+                Reader {
+                    bytes: &compress_g2(&q)
+                }
+                .g2(),
+                Ok(q)
+            );
+        }
+        let identity = compress_g1(&G1Affine::identity());
+        assert_eq!(Reader { bytes: &identity }.g1(), Ok(G1Affine::identity()));
+
+        // x = 0 has no point, y^2 = 3 is not a square
+        let mut off_curve = vec![0u8; 32];
+        off_curve[0] = COMPRESSED_SMALLEST;
+        assert_eq!(
+            Reader { bytes: &off_curve }.g1(),
+            Err(GnarkError::InvalidPoint)
+        );
+        assert_eq!(
+            Reader { bytes: &[0u8; 8] }.g1(),
+            Err(GnarkError::UnexpectedEnd)
+        );
+    }
+
+    #[test]
+    fn test_read_gnark_plonk_proof() {
+        let mut rng = XorShiftRng::seed_from_u64(1);
+        let points = (0..9).map(|_| random_g1(&mut rng)).collect::<Vec<_>>();
+        let values = vec![Fr::from(1), Fr::from(2)];
+
+        let mut bytes = vec![];
+        points[..7]
+            .iter()
+            .for_each(|p| bytes.extend(compress_g1(p)));
+        bytes.extend(1u32.to_be_bytes());
+        bytes.extend(compress_g1(&points[7]));
+        bytes.extend(compress_g1(&points[8]));
+        bytes.extend(2u32.to_be_bytes());
+        values.iter().for_each(|v| bytes.extend(be(v)));
+        bytes.extend(compress_g1(&points[0]));
+        bytes.extend(be(&Fr::from(3)));
+
+        let proof = read_gnark_plonk_proof(&bytes).unwrap();
+        assert_eq!(proof.lro, [points[0], points[1], points[2]]);
+        assert_eq!(proof.h[2], points[6]);
+        assert_eq!(proof.bsb22_commitments, vec![points[7]]);
+        assert_eq!(proof.batched_proof_h, points[8]);
+        assert_eq!(proof.batched_claimed_values, values);
+        assert_eq!(proof.z_shifted_claimed_value, Fr::from(3));
+
+        bytes.push(0);
+        assert_eq!(
+            read_gnark_plonk_proof(&bytes),
+            Err(GnarkError::TrailingBytes)
+        );
+        bytes.truncate(bytes.len() - 2);
+        assert_eq!(
+            read_gnark_plonk_proof(&bytes),
+            Err(GnarkError::UnexpectedEnd)
+        );
+    }
+
+    #[test]
+    fn test_read_gnark_plonk_vk() {
+        let mut rng = XorShiftRng::seed_from_u64(2);
+        let points = (0..9).map(|_| random_g1(&mut rng)).collect::<Vec<_>>();
+        let g2 = G2Affine::generator();
+        let s_g2 = (g2 * Fr::random(&mut rng)).to_affine();
+
+        let mut bytes = vec![];
+        bytes.extend(8u64.to_be_bytes());
+        bytes.extend(be(&Fr::from(8).invert().unwrap()));
+        bytes.extend(be(&Fr::from(5)));
+        bytes.extend(2u64.to_be_bytes());
+        bytes.extend(be(&Fr::from(7)));
+        points[..8]
+            .iter()
+            .for_each(|p| bytes.extend(compress_g1(p)));
+        bytes.extend(0u32.to_be_bytes());
+        bytes.extend(compress_g1(&points[8]));
+        bytes.extend(compress_g2(&g2));
+        bytes.extend(compress_g2(&s_g2));
+        bytes.extend(1u32.to_be_bytes());
+        bytes.extend(4u64.to_be_bytes());
+
+        let vk = read_gnark_plonk_vk(&bytes).unwrap();
+        assert_eq!(vk.size, 8);
+        assert_eq!(vk.size_inv * Fr::from(8), Fr::one());
+        assert_eq!(vk.nb_public_variables, 2);
+        assert_eq!(vk.s[1], points[1]);
+        assert_eq!(vk.qk, points[7]);
+        assert!(vk.qcp.is_empty());
+        assert_eq!(vk.kzg_g2, [g2, s_g2]);
+        assert_eq!(vk.commitment_constraint_indexes, vec![4]);
+    }
+}
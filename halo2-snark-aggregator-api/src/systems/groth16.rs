@@ -0,0 +1,201 @@
+use super::pairing::MultiPairingCheck;
+use crate::arith::{common::ArithCommonChip, ecc::ArithEccChip};
+use group::Curve;
+use halo2_proofs::{
+    arithmetic::{CurveAffine, MultiMillerLoop},
+    plonk::Error,
+};
+use std::fmt;
+
+// Groth16 verifying key over a pairing friendly curve, e.g. the one exported by circom/snarkjs.
+#[derive(Clone, Debug)]
+pub struct Groth16VerifyingKey<E: MultiMillerLoop> {
+    pub alpha_g1: E::G1Affine,
+    pub beta_g2: E::G2Affine,
+    pub gamma_g2: E::G2Affine,
+    pub delta_g2: E::G2Affine,
+    // ic[0] + sum(instance[i] * ic[i + 1])
+    pub ic: Vec<E::G1Affine>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Groth16Proof<E: MultiMillerLoop> {
+    pub a: E::G1Affine,
+    pub b: E::G2Affine,
+    pub c: E::G1Affine,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Groth16Error {
+    InstanceCount { expected: usize, actual: usize },
+}
+
+impl fmt::Display for Groth16Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Groth16Error::InstanceCount { expected, actual } => write!(
+                f,
+                "groth16 proof has {} public inputs, the vk expects {}",
+                actual, expected
+            ),
+        }
+    }
+}
+
+impl From<Groth16Error> for Error {
+    fn from(_: Groth16Error) -> Self {
+        Error::InvalidInstances
+    }
+}
+
+// e(-A, B) * e(alpha, beta) * e(IC, gamma) * e(C, delta) == 1
+//
+// This is a check of its own, it is not folded into the kzg accumulator of the halo2 proofs:
+// the accumulator only keeps the G1 sides of e(w_x, [s]) * e(w_g, -[1]), while B is a G2 point
+// of the proof and beta, gamma, delta come from the groth16 vk. Whoever consumes the final
+// pairing has to check these four pairs next to the accumulator pair.
+pub type Groth16PairingCheck<E, A> = MultiPairingCheck<E, A>;
+
+pub fn verify_groth16_proof_in_chip<
+    E: MultiMillerLoop,
+    A: ArithEccChip<
+        Point = E::G1Affine,
+        Scalar = <E::G1Affine as CurveAffine>::ScalarExt,
+        Native = <E::G1Affine as CurveAffine>::ScalarExt,
+    >,
+>(
+    ctx: &mut A::Context,
+    pchip: &A,
+    vk: &Groth16VerifyingKey<E>,
+    proof: &Groth16Proof<E>,
+    instances: &[A::AssignedScalar],
+) -> Result<Groth16PairingCheck<E, A>, A::Error>
+where
+    A::Error: From<Groth16Error>,
+{
+    if instances.len() + 1 != vk.ic.len() {
+        return Err(Groth16Error::InstanceCount {
+            expected: vk.ic.len().saturating_sub(1),
+            actual: instances.len(),
+        }
+        .into());
+    }
+
+    let mut ic = pchip.assign_const(ctx, vk.ic[0])?;
+    for (instance, base) in instances.iter().zip(vk.ic.iter().skip(1)) {
+        let p = pchip.scalar_mul_constant(ctx, instance, *base)?;
+        ic = pchip.add(ctx, &ic, &p)?;
+    }
+    let ic = pchip.normalize(ctx, &ic)?;
+
+    let neg_a = pchip.assign_var(ctx, (-proof.a.to_curve()).to_affine())?;
+    let alpha = pchip.assign_const(ctx, vk.alpha_g1)?;
+    let c = pchip.assign_var(ctx, proof.c)?;
+
+    Ok(Groth16PairingCheck {
+        pairs: vec![
+            (neg_a, proof.b),
+            (alpha, vk.beta_g2),
+            (ic, vk.gamma_g2),
+            (c, vk.delta_g2),
+        ],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::arith::{
+        ecc::MockEccChip,
+        field::{MockChipCtx, MockFieldChip},
+    };
+    use group::prime::PrimeCurveAffine;
+    use halo2_proofs::{arithmetic::Field, pairing::bn256::Fr as Fp, plonk::Error};
+    use pairing_bn256::bn256::{Bn256, G1Affine, G2Affine};
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    // Builds a vk from known trapdoors so a valid proof can be derived without a prover:
+    // a * b = alpha * beta + ic * gamma + c * delta
+    fn setup(
+        n_instances: usize,
+        instances: &[Fp],
+    ) -> (Groth16VerifyingKey<Bn256>, Groth16Proof<Bn256>) {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        let g1 = G1Affine::generator();
+        let g2 = G2Affine::generator();
+
+        let alpha = Fp::random(&mut rng);
+        let beta = Fp::random(&mut rng);
+        let gamma = Fp::random(&mut rng);
+        let delta = Fp::random(&mut rng);
+        let ic_s = (0..n_instances + 1)
+            .map(|_| Fp::random(&mut rng))
+            .collect::<Vec<_>>();
+
+        let ic = instances
+            .iter()
+            .zip(ic_s.iter().skip(1))
+            .fold(ic_s[0], |acc, (x, k)| acc + x * k);
+
+        let a = Fp::random(&mut rng);
+        let b = Fp::random(&mut rng);
+        let c = (a * b - alpha * beta - ic * gamma) * delta.invert().unwrap();
+
+        let vk = Groth16VerifyingKey {
+            alpha_g1: (g1 * alpha).to_affine(),
+            beta_g2: (g2 * beta).to_affine(),
+            gamma_g2: (g2 * gamma).to_affine(),
+            delta_g2: (g2 * delta).to_affine(),
+            ic: ic_s.iter().map(|k| (g1 * k).to_affine()).collect(),
+        };
+        let proof = Groth16Proof {
+            a: (g1 * a).to_affine(),
+            b: (g2 * b).to_affine(),
+            c: (g1 * c).to_affine(),
+        };
+        (vk, proof)
+    }
+
+    #[test]
+    fn test_verify_groth16_proof_in_chip() {
+        let schip = MockFieldChip::<Fp, Error>::default();
+        let pchip = MockEccChip::<G1Affine, Error>::default();
+        let ctx = &mut MockChipCtx::default();
+
+        let instances = vec![Fp::from(3u64), Fp::from(5u64)];
+        let (vk, proof) = setup(instances.len(), &instances);
+
+        let assigned_instances = instances
+            .iter()
+            .map(|x| schip.assign_var(ctx, *x))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let check = verify_groth16_proof_in_chip(ctx, &pchip, &vk, &proof, &assigned_instances[..])
+            .unwrap();
+        assert!(check.check_native(&pchip).unwrap());
+
+        // a different public input must not pass
+        let wrong_instances = vec![
+            assigned_instances[0],
+            schip.assign_var(ctx, Fp::from(6u64)).unwrap(),
+        ];
+        let check =
+            verify_groth16_proof_in_chip(ctx, &pchip, &vk, &proof, &wrong_instances[..]).unwrap();
+        assert!(!check.check_native(&pchip).unwrap());
+    }
+
+    #[test]
+    fn test_verify_groth16_proof_in_chip_instance_count() {
+        let schip = MockFieldChip::<Fp, Error>::default();
+        let pchip = MockEccChip::<G1Affine, Error>::default();
+        let ctx = &mut MockChipCtx::default();
+
+        let (vk, proof) = setup(2, &[Fp::from(3u64), Fp::from(5u64)]);
+        let assigned_instances = vec![schip.assign_var(ctx, Fp::from(3u64)).unwrap()];
+
+        let res = verify_groth16_proof_in_chip(ctx, &pchip, &vk, &proof, &assigned_instances[..]);
+        assert!(matches!(res, Err(Error::InvalidInstances)));
+    }
+}
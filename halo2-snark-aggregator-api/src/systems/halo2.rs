@@ -1,5 +1,11 @@
+pub mod arena;
+#[cfg(feature = "differential")]
+pub mod diff;
 pub mod evaluation;
+pub mod explain;
 pub mod expression;
+pub mod instance;
+pub mod ipa;
 pub mod lagrange;
 pub mod lookup;
 pub mod multiopen;
@@ -0,0 +1,247 @@
+use super::evaluation::{CommitQuery, EvaluationQuerySchema};
+use crate::arith::{ecc::ArithEccChip, field::ArithFieldChip};
+use halo2_proofs::arithmetic::FieldExt;
+use std::rc::Rc;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodeId(pub usize);
+
+// Flattened EvaluationQuerySchema, children are referred to by index
+// and the bool tells whether the child subtree contains a commitment.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaNode<P, S> {
+    Commitment(CommitQuery<P, S>),
+    Eval(CommitQuery<P, S>),
+    Scalar(S),
+    Add((NodeId, bool), (NodeId, bool)),
+    Mul((NodeId, bool), (NodeId, bool)),
+}
+
+// The key is the one of the commitment, or empty for the accumulated scalar.
+pub type PreparedTerm<P, S> = (Rc<str>, Option<P>, Option<S>);
+
+enum Task<S> {
+    Visit(NodeId, Option<S>),
+    PureAdd(Option<S>),
+    StartMerge,
+    Merge,
+    MulThen(NodeId, Option<S>),
+}
+
+// Flat copy of a schema for its evaluation. The macros and operators still build the boxed
+// EvaluationQuerySchema, with one allocation per node, and dropping a boxed schema that was
+// never pushed still recurses. push takes the boxed tree apart level by level, so a pushed
+// schema is evaluated with an explicit stack and freed without recursion.
+#[derive(Clone, Debug)]
+pub struct SchemaArena<P, S> {
+    pub nodes: Vec<SchemaNode<P, S>>,
+}
+
+impl<P, S> Default for SchemaArena<P, S> {
+    fn default() -> Self {
+        Self { nodes: vec![] }
+    }
+}
+
+impl<P: Clone, S: Clone> SchemaArena<P, S> {
+    pub fn node(&self, id: NodeId) -> &SchemaNode<P, S> {
+        &self.nodes[id.0]
+    }
+
+    pub fn push(&mut self, schema: EvaluationQuerySchema<P, S>) -> NodeId {
+        let base = self.nodes.len();
+        let mut slots = vec![None];
+        let mut pending = vec![(0, schema)];
+
+        while let Some((i, schema)) = pending.pop() {
+            let mut child = |(schema, has_commitment): (EvaluationQuerySchema<P, S>, bool)| {
+                slots.push(None);
+                pending.push((slots.len() - 1, schema));
+                (NodeId(base + slots.len() - 1), has_commitment)
+            };
+            let node = match schema {
+                EvaluationQuerySchema::Commitment(cq) => SchemaNode::Commitment(cq),
+                EvaluationQuerySchema::Eval(cq) => SchemaNode::Eval(cq),
+                EvaluationQuerySchema::Scalar(s) => SchemaNode::Scalar(s),
+                EvaluationQuerySchema::Add(l, r) => SchemaNode::Add(child(*l), child(*r)),
+                EvaluationQuerySchema::Mul(l, r) => SchemaNode::Mul(child(*l), child(*r)),
+            };
+            slots[i] = Some(node);
+        }
+
+        self.nodes
+            .extend(slots.into_iter().map(|node| node.unwrap()));
+        NodeId(base)
+    }
+
+    // Returns the (key, commitment, scalar) terms of the subtree,
+    // scalars without a commitment are accumulated under the empty key.
+    pub fn eval_prepare<
+        Scalar: FieldExt,
+        A: ArithEccChip<AssignedPoint = P, AssignedScalar = S, Scalar = Scalar>,
+    >(
+        &self,
+        root: NodeId,
+        ctx: &mut A::Context,
+        schip: &A::ScalarChip,
+        one: &A::AssignedScalar,
+    ) -> Result<Vec<PreparedTerm<P, S>>, A::Error> {
+        let mut tasks = vec![Task::Visit(root, None)];
+        let mut values: Vec<Vec<PreparedTerm<P, S>>> = vec![];
+        let scalar_key: Rc<str> = Rc::from("");
+
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Visit(id, scalar) => match self.node(id) {
+                    SchemaNode::Commitment(cq) => values.push(vec![(
+                        cq.key.clone(),
+                        Some(cq.commitment.clone().unwrap()),
+                        scalar,
+                    )]),
+                    SchemaNode::Eval(cq) => {
+                        let eval = cq.eval.clone().unwrap();
+                        let e = match scalar {
+                            Some(s) => schip.mul(ctx, &s, &eval)?,
+                            None => eval,
+                        };
+                        values.push(vec![(scalar_key.clone(), None, Some(e))]);
+                    }
+                    SchemaNode::Scalar(s) => {
+                        let s = match scalar {
+                            Some(scalar) => schip.mul(ctx, s, &scalar)?,
+                            None => s.clone(),
+                        };
+                        values.push(vec![(scalar_key.clone(), None, Some(s))]);
+                    }
+                    SchemaNode::Add(l, r) => {
+                        if !l.1 && !r.1 {
+                            tasks.push(Task::PureAdd(scalar));
+                            tasks.push(Task::Visit(r.0, None));
+                            tasks.push(Task::Visit(l.0, None));
+                        } else {
+                            tasks.push(Task::Merge);
+                            tasks.push(Task::Visit(r.0, scalar.clone()));
+                            tasks.push(Task::Merge);
+                            tasks.push(Task::Visit(l.0, scalar));
+                            tasks.push(Task::StartMerge);
+                        }
+                    }
+                    SchemaNode::Mul(l, r) => {
+                        let (s, rem) = if !l.1 { (l.0, r.0) } else { (r.0, l.0) };
+                        tasks.push(Task::MulThen(rem, scalar));
+                        tasks.push(Task::Visit(s, None));
+                    }
+                },
+                Task::PureAdd(scalar) => {
+                    let r = values.pop().unwrap();
+                    let l = values.pop().unwrap();
+                    assert!(l.len() == 1);
+                    assert!(r.len() == 1);
+                    let (l, r) = (l[0].2.as_ref().unwrap(), r[0].2.as_ref().unwrap());
+                    let sum = schip.add(ctx, l, r)?;
+                    let sum = match scalar {
+                        Some(scalar) => schip.mul(ctx, &scalar, &sum)?,
+                        None => sum,
+                    };
+                    values.push(vec![(scalar_key.clone(), None, Some(sum))]);
+                }
+                Task::StartMerge => values.push(vec![]),
+                Task::Merge => {
+                    let evaluated = values.pop().unwrap();
+                    let res = values.last_mut().unwrap();
+                    for evaluated in evaluated {
+                        match res.iter_mut().find(|p| p.0 == evaluated.0) {
+                            Some(p) => {
+                                let s = schip.add(
+                                    ctx,
+                                    p.2.as_ref().unwrap_or(one),
+                                    evaluated.2.as_ref().unwrap_or(one),
+                                )?;
+                                p.2 = Some(s);
+                            }
+                            None => res.push(evaluated),
+                        }
+                    }
+                }
+                Task::MulThen(rem, scalar) => {
+                    let s = values.pop().unwrap();
+                    assert_eq!(s.len(), 1);
+                    let s = s[0].2.clone();
+                    let s = match scalar {
+                        Some(scalar) => schip.mul(ctx, &scalar, s.as_ref().unwrap())?,
+                        None => s.unwrap(),
+                    };
+                    tasks.push(Task::Visit(rem, Some(s)));
+                }
+            }
+        }
+
+        assert_eq!(values.len(), 1);
+        Ok(values.pop().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::arith::{
+        ecc::MockEccChip,
+        field::{MockChipCtx, MockFieldChip},
+    };
+    use crate::systems::halo2::evaluation::QueryItem;
+    use crate::{commit, eval, scalar};
+    use group::{Curve, Group};
+    use halo2_proofs::{arithmetic::Field, pairing::bn256::Fr as Fp, plonk::Error};
+    use pairing_bn256::bn256::{G1Affine, G1};
+
+    type Schema = EvaluationQuerySchema<G1, Fp>;
+
+    fn query(i: u64) -> CommitQuery<G1, Fp> {
+        CommitQuery {
+            key: format!("p_advice_commitments{}", i).into(),
+            item: QueryItem::Advice,
+            commitment: Some(G1::generator() * Fp::from(i + 1)),
+            eval: Some(Fp::from(i + 2)),
+        }
+    }
+
+    #[test]
+    fn test_arena_eval() {
+        let schip = MockFieldChip::<Fp, Error>::default();
+        let pchip = MockEccChip::<G1Affine, Error>::default();
+        let ctx = &mut MockChipCtx::default();
+        let one = Fp::one();
+
+        let (a, b, c) = (query(0), query(1), query(2));
+        let s: Schema = scalar!(Fp::from(5)) * (commit!(a) + eval!(a) + commit!(b))
+            + (scalar!(Fp::from(2)) + scalar!(Fp::from(3))) * commit!(c)
+            + eval!(b) * scalar!(Fp::from(7))
+            + commit!(a);
+
+        // 6 * [1] + 5 * [2] + 5 * [3], 5 * 2 + 7 * 3
+        let (p, s) = s
+            .eval::<Fp, MockEccChip<G1Affine, Error>>(ctx, &schip, &pchip, &one)
+            .unwrap();
+        assert_eq!(p.to_affine(), (G1::generator() * Fp::from(31)).to_affine());
+        assert_eq!(s, Some(Fp::from(31)));
+    }
+
+    #[test]
+    fn test_arena_deep_schema() {
+        let schip = MockFieldChip::<Fp, Error>::default();
+        let ctx = &mut MockChipCtx::default();
+        let one = Fp::one();
+
+        let mut s: Schema = commit!(query(0));
+        for i in 1..100000u64 {
+            s = scalar!(Fp::from(2)) * s + commit!(query(i % 4));
+        }
+
+        let mut arena = SchemaArena::default();
+        let root = arena.push(s);
+        let terms = arena
+            .eval_prepare::<Fp, MockEccChip<G1Affine, Error>>(root, ctx, &schip, &one)
+            .unwrap();
+        assert_eq!(terms.len(), 4);
+    }
+}
@@ -0,0 +1,706 @@
+use super::arena::SchemaArena;
+use super::params::VerifierParams;
+use crate::mock::arith::{
+    ecc::MockEccChip,
+    field::{MockChipCtx, MockFieldChip},
+};
+use group::{Curve, Group};
+use halo2_proofs::{
+    arithmetic::{CurveAffine, Field, MultiMillerLoop},
+    plonk::{verify_proof, Error, SingleVerifier, VerifyingKey},
+    poly::{commitment::ParamsVerifier, Rotation},
+    transcript::{Challenge255, EncodedChallenge, PoseidonRead, Transcript, TranscriptRead},
+};
+use std::{fmt, io};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiffValue<C: CurveAffine> {
+    Scalar(C::ScalarExt),
+    Point(C),
+}
+
+#[derive(Debug)]
+pub struct Divergence {
+    pub index: usize,
+    pub name: String,
+    pub native: String,
+    pub in_chip: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "first divergence at #{} ({}): native {}, in chip {}",
+            self.index, self.name, self.native, self.in_chip
+        )
+    }
+}
+
+// Named intermediate values in the order the verifier produces them,
+// so the native and the in-chip runs can be compared step by step.
+#[derive(Clone, Debug)]
+pub struct DiffTrace<C: CurveAffine> {
+    pub values: Vec<(String, DiffValue<C>)>,
+}
+
+impl<C: CurveAffine> Default for DiffTrace<C> {
+    fn default() -> Self {
+        Self { values: vec![] }
+    }
+}
+
+impl<C: CurveAffine> DiffTrace<C> {
+    fn scalar(&mut self, name: &str, v: C::ScalarExt) {
+        self.values.push((name.to_owned(), DiffValue::Scalar(v)));
+    }
+
+    fn point(&mut self, name: &str, p: C) {
+        self.values.push((name.to_owned(), DiffValue::Point(p)));
+    }
+
+    pub fn first_divergence(&self, in_chip: &DiffTrace<C>) -> Option<Divergence> {
+        let len = usize::max(self.values.len(), in_chip.values.len());
+        (0..len).find_map(|i| {
+            let native = self.values.get(i);
+            let chip = in_chip.values.get(i);
+            if native == chip {
+                None
+            } else {
+                Some(Divergence {
+                    index: i,
+                    name: native.or(chip).map(|(name, _)| name.clone()).unwrap(),
+                    native: format!("{:?}", native.map(|(_, v)| v)),
+                    in_chip: format!("{:?}", chip.map(|(_, v)| v)),
+                })
+            }
+        })
+    }
+}
+
+// Every value halo2's verifier takes from or feeds into its transcript, in its order.
+#[derive(Clone, Debug)]
+enum TranscriptEvent<C: CurveAffine> {
+    Common(C),
+    Point(C),
+    Scalar(C::ScalarExt),
+    Challenge(C::ScalarExt),
+}
+
+// Records what halo2's own verify_proof reads and squeezes, so the native side of the diff is
+// the values of halo2's verifier rather than a replay of its read order.
+struct RecordingTranscript<C: CurveAffine, T> {
+    inner: T,
+    events: Vec<TranscriptEvent<C>>,
+}
+
+impl<C: CurveAffine, T: TranscriptRead<C, Challenge255<C>>> Transcript<C, Challenge255<C>>
+    for RecordingTranscript<C, T>
+{
+    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+        let challenge = self.inner.squeeze_challenge();
+        self.events
+            .push(TranscriptEvent::Challenge(challenge.get_scalar()));
+        challenge
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.inner.common_point(point)?;
+        self.events.push(TranscriptEvent::Common(point));
+        Ok(())
+    }
+
+    // only the vk hash goes through here, it is not part of the in-chip trace
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.common_scalar(scalar)
+    }
+}
+
+impl<C: CurveAffine, T: TranscriptRead<C, Challenge255<C>>> TranscriptRead<C, Challenge255<C>>
+    for RecordingTranscript<C, T>
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let point = self.inner.read_point()?;
+        self.events.push(TranscriptEvent::Point(point));
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let scalar = self.inner.read_scalar()?;
+        self.events.push(TranscriptEvent::Scalar(scalar));
+        Ok(scalar)
+    }
+}
+
+fn transcript_error(msg: String) -> Error {
+    Error::Transcript(io::Error::new(io::ErrorKind::InvalidData, msg))
+}
+
+// Names the recorded events after the verifier steps they belong to. An event of another
+// kind than expected, or one left over, means halo2 and this labelling disagree on the layout.
+struct Labeller<C: CurveAffine> {
+    events: std::vec::IntoIter<TranscriptEvent<C>>,
+    trace: DiffTrace<C>,
+}
+
+impl<C: CurveAffine> Labeller<C> {
+    fn next(&mut self, name: &str) -> Result<TranscriptEvent<C>, Error> {
+        self.events
+            .next()
+            .ok_or_else(|| transcript_error(format!("halo2 verifier stopped before {}", name)))
+    }
+
+    fn unexpected(name: &str, event: TranscriptEvent<C>) -> Error {
+        transcript_error(format!(
+            "expected {}, halo2 verifier gave {:?}",
+            name, event
+        ))
+    }
+
+    fn common(&mut self, name: &str) -> Result<(), Error> {
+        match self.next(name)? {
+            TranscriptEvent::Common(p) => {
+                self.trace.point(name, p);
+                Ok(())
+            }
+            e => Err(Self::unexpected(name, e)),
+        }
+    }
+
+    fn points(&mut self, name: &str, n: usize) -> Result<(), Error> {
+        for _ in 0..n {
+            match self.next(name)? {
+                TranscriptEvent::Point(p) => self.trace.point(name, p),
+                e => return Err(Self::unexpected(name, e)),
+            }
+        }
+        Ok(())
+    }
+
+    fn scalars(&mut self, name: &str, n: usize) -> Result<(), Error> {
+        for _ in 0..n {
+            match self.next(name)? {
+                TranscriptEvent::Scalar(s) => self.trace.scalar(name, s),
+                e => return Err(Self::unexpected(name, e)),
+            }
+        }
+        Ok(())
+    }
+
+    fn challenge(&mut self, name: &str) -> Result<C::ScalarExt, Error> {
+        match self.next(name)? {
+            TranscriptEvent::Challenge(s) => {
+                self.trace.scalar(name, s);
+                Ok(s)
+            }
+            e => Err(Self::unexpected(name, e)),
+        }
+    }
+}
+
+// Runs halo2's verify_proof on the proof and records the values it reads and squeezes from
+// its poseidon transcript, named as in VerifierParams::diff_trace. Fails if halo2 rejects the
+// proof, or if bytes are left in the proof once the verifier is done with it.
+pub fn replay_native<E: MultiMillerLoop>(
+    vk: &VerifyingKey<E::G1Affine>,
+    params: &ParamsVerifier<E>,
+    instances: &[&[&[<E::G1Affine as CurveAffine>::ScalarExt]]],
+    proof: &[u8],
+) -> Result<DiffTrace<E::G1Affine>, Error> {
+    let mut rest = proof;
+    let mut transcript = RecordingTranscript {
+        inner: PoseidonRead::<_, E::G1Affine, Challenge255<_>>::init(&mut rest),
+        events: vec![],
+    };
+    verify_proof(
+        params,
+        vk,
+        SingleVerifier::new(params),
+        instances,
+        &mut transcript,
+    )?;
+    let events = transcript.events;
+    if !rest.is_empty() {
+        return Err(transcript_error(format!(
+            "{} bytes left after the halo2 verifier",
+            rest.len()
+        )));
+    }
+
+    let num_proofs = instances.len();
+    let num_lookups = vk.cs.lookups.len();
+    let chunk_len = vk.cs.degree() - 2;
+    let num_permutation_chunks = vk.cs.permutation.columns.chunks(chunk_len).len();
+    let mut l = Labeller {
+        events: events.into_iter(),
+        trace: DiffTrace::default(),
+    };
+
+    for _ in 0..num_proofs * vk.cs.num_instance_columns {
+        l.common("instance_commitment")?;
+    }
+    l.points("advice_commitment", num_proofs * vk.cs.num_advice_columns)?;
+    l.challenge("theta")?;
+
+    for _ in 0..num_proofs * num_lookups {
+        l.points("lookup_permuted_input_commitment", 1)?;
+        l.points("lookup_permuted_table_commitment", 1)?;
+    }
+    l.challenge("beta")?;
+    l.challenge("gamma")?;
+
+    let name = "permutation_product_commitment";
+    l.points(name, num_proofs * num_permutation_chunks)?;
+    l.points("lookup_product_commitment", num_proofs * num_lookups)?;
+    l.points("random_commitment", 1)?;
+    l.challenge("y")?;
+
+    l.points("vanish_commitment", vk.domain.get_quotient_poly_degree())?;
+    let x = l.challenge("x")?;
+
+    l.scalars("instance_eval", num_proofs * vk.cs.instance_queries.len())?;
+    l.scalars("advice_eval", num_proofs * vk.cs.advice_queries.len())?;
+    l.scalars("fixed_eval", vk.cs.fixed_queries.len())?;
+    l.scalars("random_eval", 1)?;
+    l.scalars("permutation_eval", vk.permutation.commitments.len())?;
+    for _ in 0..num_proofs {
+        for i in 0..num_permutation_chunks {
+            l.scalars("permutation_product_eval", 1)?;
+            l.scalars("permutation_product_next_eval", 1)?;
+            if i + 1 < num_permutation_chunks {
+                l.scalars("permutation_product_last_eval", 1)?;
+            }
+        }
+    }
+    for _ in 0..num_proofs * num_lookups {
+        l.scalars("lookup_product_eval", 1)?;
+        l.scalars("lookup_product_next_eval", 1)?;
+        l.scalars("lookup_permuted_input_eval", 1)?;
+        l.scalars("lookup_permuted_input_inv_eval", 1)?;
+        l.scalars("lookup_permuted_table_eval", 1)?;
+    }
+
+    l.challenge("v")?;
+    l.challenge("u")?;
+    // one opening proof per point set, their count is whatever halo2's multiopen read
+    let ws = l.events.len();
+    l.points("w", ws)?;
+
+    let mut trace = l.trace;
+    let last = -(vk.cs.blinding_factors() as i32 + 1);
+    trace.scalar("x_next", vk.domain.rotate_omega(x, Rotation::next()));
+    trace.scalar("x_last", vk.domain.rotate_omega(x, Rotation(last)));
+    trace.scalar("x_inv", vk.domain.rotate_omega(x, Rotation::prev()));
+    trace.scalar("xn", x.pow_vartime(&[params.n as u64]));
+
+    Ok(trace)
+}
+
+// One opening of the multiopen: its rotation, its commitment, a combination of them for the
+// quotient, and its eval, which native_queries leaves to None for the quotient since halo2
+// derives it from the gates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuerySnapshot<C: CurveAffine> {
+    pub rotation: i32,
+    pub commitment: C,
+    pub eval: Option<C::ScalarExt>,
+}
+
+// The query set halo2's verifier builds for the proof, in its order: per proof the instance,
+// advice, permutation product and lookup openings, then the fixed, permutation and vanishing
+// ones.
+pub fn native_queries<E: MultiMillerLoop>(
+    vk: &VerifyingKey<E::G1Affine>,
+    params: &ParamsVerifier<E>,
+    instances: &[&[&[<E::G1Affine as CurveAffine>::ScalarExt]]],
+    proof: &[u8],
+) -> Result<Vec<QuerySnapshot<E::G1Affine>>, Error> {
+    let trace = replay_native(vk, params, instances, proof)?;
+    let points = |name: &str| {
+        trace
+            .values
+            .iter()
+            .filter_map(|(n, v)| match v {
+                DiffValue::Point(p) if n == name => Some(*p),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let scalars = |name: &str| {
+        trace
+            .values
+            .iter()
+            .filter_map(|(n, v)| match v {
+                DiffValue::Scalar(s) if n == name => Some(*s),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let q = |rotation: i32, commitment: E::G1Affine, eval| QuerySnapshot {
+        rotation,
+        commitment,
+        eval: Some(eval),
+    };
+
+    let cs = &vk.cs;
+    let sets = cs.permutation.columns.chunks(cs.degree() - 2).len();
+    let lookups = cs.lookups.len();
+    let last = -(cs.blinding_factors() as i32 + 1);
+
+    let instance_commitments = points("instance_commitment");
+    let instance_evals = scalars("instance_eval");
+    let advice_commitments = points("advice_commitment");
+    let advice_evals = scalars("advice_eval");
+    let product_commitments = points("permutation_product_commitment");
+    let product_evals = scalars("permutation_product_eval");
+    let product_next_evals = scalars("permutation_product_next_eval");
+    let product_last_evals = scalars("permutation_product_last_eval");
+    let permuted_inputs = points("lookup_permuted_input_commitment");
+    let permuted_tables = points("lookup_permuted_table_commitment");
+    let lookup_products = points("lookup_product_commitment");
+    let lookup_product_evals = scalars("lookup_product_eval");
+    let lookup_product_next_evals = scalars("lookup_product_next_eval");
+    let permuted_input_evals = scalars("lookup_permuted_input_eval");
+    let permuted_input_inv_evals = scalars("lookup_permuted_input_inv_eval");
+    let permuted_table_evals = scalars("lookup_permuted_table_eval");
+
+    let mut queries = vec![];
+    for k in 0..instances.len() {
+        for (i, (column, at)) in cs.instance_queries.iter().enumerate() {
+            queries.push(q(
+                at.0,
+                instance_commitments[k * cs.num_instance_columns + column.index()],
+                instance_evals[k * cs.instance_queries.len() + i],
+            ));
+        }
+        for (i, (column, at)) in cs.advice_queries.iter().enumerate() {
+            queries.push(q(
+                at.0,
+                advice_commitments[k * cs.num_advice_columns + column.index()],
+                advice_evals[k * cs.advice_queries.len() + i],
+            ));
+        }
+
+        // every product at x and x_next, all but the last one at x_last
+        for j in k * sets..(k + 1) * sets {
+            queries.push(q(0, product_commitments[j], product_evals[j]));
+            queries.push(q(1, product_commitments[j], product_next_evals[j]));
+        }
+        for j in (0..sets).rev().skip(1) {
+            let last_eval = product_last_evals[k * (sets - 1) + j];
+            queries.push(q(last, product_commitments[k * sets + j], last_eval));
+        }
+
+        for j in k * lookups..(k + 1) * lookups {
+            queries.push(q(0, lookup_products[j], lookup_product_evals[j]));
+            queries.push(q(0, permuted_inputs[j], permuted_input_evals[j]));
+            queries.push(q(0, permuted_tables[j], permuted_table_evals[j]));
+            queries.push(q(-1, permuted_inputs[j], permuted_input_inv_evals[j]));
+            queries.push(q(1, lookup_products[j], lookup_product_next_evals[j]));
+        }
+    }
+
+    let fixed_evals = scalars("fixed_eval");
+    for (i, (column, at)) in cs.fixed_queries.iter().enumerate() {
+        queries.push(q(
+            at.0,
+            vk.fixed_commitments[column.index()],
+            fixed_evals[i],
+        ));
+    }
+    let permutation_evals = scalars("permutation_eval");
+    for (commitment, eval) in vk.permutation.commitments.iter().zip(permutation_evals) {
+        queries.push(q(0, *commitment, eval));
+    }
+
+    // h(X) = h_0(X) + xn h_1(X) + ..., opened at x
+    let xn = scalars("xn")[0];
+    let h = points("vanish_commitment").iter().rev().fold(
+        <E::G1Affine as CurveAffine>::CurveExt::identity(),
+        |acc, h| acc * xn + h.to_curve(),
+    );
+    queries.push(QuerySnapshot {
+        rotation: 0,
+        commitment: h.to_affine(),
+        eval: None,
+    });
+    queries.push(q(
+        0,
+        points("random_commitment")[0],
+        scalars("random_eval")[0],
+    ));
+
+    Ok(queries)
+}
+
+// Index of the first query that differs between the two lists, the evals left to None by
+// native_queries are not compared.
+pub fn first_query_mismatch<C: CurveAffine>(
+    native: &[QuerySnapshot<C>],
+    in_chip: &[QuerySnapshot<C>],
+) -> Option<usize> {
+    (0..usize::max(native.len(), in_chip.len())).find(|i| match (native.get(*i), in_chip.get(*i)) {
+        (Some(n), Some(c)) => {
+            n.rotation != c.rotation
+                || n.commitment != c.commitment
+                || (n.eval.is_some() && n.eval != c.eval)
+        }
+        _ => true,
+    })
+}
+
+impl<C: CurveAffine, E> VerifierParams<MockEccChip<C, E>> {
+    // Same order as replay_native.
+    pub fn diff_trace(&self) -> DiffTrace<C> {
+        let mut trace = DiffTrace::default();
+
+        for p in self.instance_commitments.iter().flatten() {
+            trace.point("instance_commitment", p.to_affine());
+        }
+        for p in self.advice_commitments.iter().flatten() {
+            trace.point("advice_commitment", p.to_affine());
+        }
+        trace.scalar("theta", self.theta);
+
+        for lookup in self.lookup_evaluated.iter().flatten() {
+            let permuted = &lookup.committed.permuted;
+            trace.point(
+                "lookup_permuted_input_commitment",
+                permuted.permuted_input_commitment.to_affine(),
+            );
+            trace.point(
+                "lookup_permuted_table_commitment",
+                permuted.permuted_table_commitment.to_affine(),
+            );
+        }
+        trace.scalar("beta", self.beta);
+        trace.scalar("gamma", self.gamma);
+
+        for set in self
+            .permutation_evaluated
+            .iter()
+            .flat_map(|p| p.sets.iter())
+        {
+            trace.point(
+                "permutation_product_commitment",
+                set.permutation_product_commitment.to_affine(),
+            );
+        }
+        for lookup in self.lookup_evaluated.iter().flatten() {
+            trace.point(
+                "lookup_product_commitment",
+                lookup.committed.product_commitment.to_affine(),
+            );
+        }
+        trace.point("random_commitment", self.random_commitment.to_affine());
+        trace.scalar("y", self.y);
+        for p in self.vanish_commitments.iter() {
+            trace.point("vanish_commitment", p.to_affine());
+        }
+        trace.scalar("x", self.x);
+
+        for s in self.instance_evals.iter().flatten() {
+            trace.scalar("instance_eval", *s);
+        }
+        for s in self.advice_evals.iter().flatten() {
+            trace.scalar("advice_eval", *s);
+        }
+        for s in self.fixed_evals.iter() {
+            trace.scalar("fixed_eval", *s);
+        }
+        trace.scalar("random_eval", self.random_eval);
+        for s in self.permutation_evals.iter() {
+            trace.scalar("permutation_eval", *s);
+        }
+        for set in self
+            .permutation_evaluated
+            .iter()
+            .flat_map(|p| p.sets.iter())
+        {
+            trace.scalar("permutation_product_eval", set.permutation_product_eval);
+            trace.scalar(
+                "permutation_product_next_eval",
+                set.permutation_product_next_eval,
+            );
+            if let Some(s) = set.permutation_product_last_eval {
+                trace.scalar("permutation_product_last_eval", s);
+            }
+        }
+        for lookup in self.lookup_evaluated.iter().flatten() {
+            trace.scalar("lookup_product_eval", lookup.product_eval);
+            trace.scalar("lookup_product_next_eval", lookup.product_next_eval);
+            trace.scalar("lookup_permuted_input_eval", lookup.permuted_input_eval);
+            trace.scalar(
+                "lookup_permuted_input_inv_eval",
+                lookup.permuted_input_inv_eval,
+            );
+            trace.scalar("lookup_permuted_table_eval", lookup.permuted_table_eval);
+        }
+
+        trace.scalar("v", self.v);
+        trace.scalar("u", self.u);
+        for p in self.w.iter() {
+            trace.point("w", p.to_affine());
+        }
+
+        trace.scalar("x_next", self.x_next);
+        trace.scalar("x_last", self.x_last);
+        trace.scalar("x_inv", self.x_inv);
+        trace.scalar("xn", self.xn);
+
+        trace
+    }
+
+    // The queries of the multiopen, each schema summed up natively, comparable with
+    // native_queries.
+    pub fn query_snapshot(
+        &self,
+        ctx: &mut MockChipCtx,
+        schip: &MockFieldChip<C::ScalarExt, E>,
+    ) -> Result<Vec<QuerySnapshot<C>>, E> {
+        let one = C::ScalarExt::one();
+        self.queries(ctx, schip)?
+            .into_iter()
+            .map(|query| {
+                let mut arena = SchemaArena::default();
+                let root = arena.push(query.s);
+                let terms = arena.eval_prepare::<_, MockEccChip<C, E>>(root, ctx, schip, &one)?;
+                let commitment =
+                    terms
+                        .iter()
+                        .fold(C::CurveExt::identity(), |acc, term| match &term.1 {
+                            Some(p) => acc + *p * term.2.unwrap_or(one),
+                            None => acc,
+                        });
+                Ok(QuerySnapshot {
+                    rotation: query.rotation,
+                    commitment: commitment.to_affine(),
+                    eval: terms
+                        .iter()
+                        .find(|term| term.0.is_empty())
+                        .and_then(|term| term.2),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock::{
+            arith::{
+                ecc::MockEccChip,
+                field::{MockChipCtx, MockFieldChip},
+            },
+            transcript_encode::PoseidonEncode,
+        },
+        systems::halo2::{
+            transcript::PoseidonTranscriptRead,
+            verify::{assign_instance_commitment, build_verifier_params},
+        },
+        tests::systems::halo2::add_mul_test::test_circuit::test_circuit_builder,
+    };
+    use halo2_proofs::{
+        pairing::bn256::Fr as Fp,
+        plonk::{create_proof, keygen_pk, keygen_vk, Error},
+        poly::commitment::Params,
+        transcript::PoseidonWrite,
+    };
+    use pairing_bn256::bn256::{Bn256, G1Affine};
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+    use rand_xorshift::XorShiftRng;
+
+    #[test]
+    fn test_diff_add_mul_against_native() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        let a = Fp::random(&mut rng);
+        let b = Fp::random(&mut rng);
+        let c = Fp::from(7) * a.square() * b.square();
+
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(10);
+        let vk = keygen_vk(&params, &test_circuit_builder(a, b)).unwrap();
+        let pk = keygen_pk(&params, vk, &test_circuit_builder(a, b)).unwrap();
+        let instances: &[&[&[_]]] = &[&[&[c]]];
+
+        let mut transcript = PoseidonWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            &[test_circuit_builder(a, b)],
+            instances,
+            Pcg32::seed_from_u64(0),
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.finalize();
+        let params_verifier: ParamsVerifier<Bn256> = params.verifier(1).unwrap();
+
+        let nchip = MockFieldChip::<Fp, Error>::default();
+        let schip = MockFieldChip::<Fp, Error>::default();
+        let pchip = MockEccChip::<G1Affine, Error>::default();
+        let ctx = &mut MockChipCtx::default();
+
+        let (_, assigned_instances) = assign_instance_commitment::<Bn256, _>(
+            ctx,
+            &schip,
+            &pchip,
+            instances,
+            pk.get_vk(),
+            &params_verifier,
+        )
+        .unwrap();
+        let mut transcript =
+            PoseidonTranscriptRead::<_, G1Affine, _, PoseidonEncode, 9usize, 8usize>::new(
+                &proof[..],
+                ctx,
+                &nchip,
+                8usize,
+                33usize,
+            )
+            .unwrap();
+        let chip_params = build_verifier_params(
+            ctx,
+            &nchip,
+            &schip,
+            &pchip,
+            assigned_instances,
+            pk.get_vk(),
+            &params_verifier,
+            &mut transcript,
+            "p0".to_owned(),
+        )
+        .unwrap();
+
+        let native = replay_native(pk.get_vk(), &params_verifier, instances, &proof[..]).unwrap();
+
+        // halo2 ignores trailing bytes, the replay does not
+        let mut padded = proof.clone();
+        padded.push(0);
+        assert!(replay_native(pk.get_vk(), &params_verifier, instances, &padded[..]).is_err());
+        let mut in_chip = chip_params.diff_trace();
+        if let Some(divergence) = native.first_divergence(&in_chip) {
+            panic!("{}", divergence);
+        }
+
+        let native_queries =
+            native_queries(pk.get_vk(), &params_verifier, instances, &proof[..]).unwrap();
+        let in_chip_queries = chip_params.query_snapshot(ctx, &schip).unwrap();
+        assert_eq!(
+            first_query_mismatch(&native_queries, &in_chip_queries),
+            None
+        );
+
+        let theta = in_chip
+            .values
+            .iter()
+            .position(|(name, _)| name == "theta")
+            .unwrap();
+        in_chip.values[theta].1 = DiffValue::Scalar(Fp::one());
+        let divergence = native.first_divergence(&in_chip).unwrap();
+        assert_eq!(divergence.index, theta);
+        assert_eq!(divergence.name, "theta");
+    }
+}
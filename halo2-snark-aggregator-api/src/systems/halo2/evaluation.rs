@@ -1,10 +1,41 @@
 use halo2_proofs::arithmetic::FieldExt;
+use std::collections::BTreeMap;
+use std::rc::Rc;
 
-use crate::arith::{common::ArithCommonChip, ecc::ArithEccChip, field::ArithFieldChip};
+use super::arena::SchemaArena;
+use crate::arith::{common::ArithCommonChip, ecc::ArithEccChip};
 
+// Sub-protocol of the verifier a query comes from, set where the query is built.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueryItem {
+    Instance,
+    Advice,
+    Fixed,
+    Permutation,
+    Lookup,
+    Vanish,
+    Multiopen,
+}
+
+impl QueryItem {
+    pub fn name(&self) -> &'static str {
+        match self {
+            QueryItem::Instance => "instance",
+            QueryItem::Advice => "advice",
+            QueryItem::Fixed => "fixed",
+            QueryItem::Permutation => "permutation",
+            QueryItem::Lookup => "lookup",
+            QueryItem::Vanish => "vanish",
+            QueryItem::Multiopen => "multiopen",
+        }
+    }
+}
+
+// The key is shared, a query is cloned into every schema that opens it.
 #[derive(Clone, Debug, PartialEq)]
 pub struct CommitQuery<P, S> {
-    pub key: String,
+    pub key: Rc<str>,
+    pub item: QueryItem,
     pub commitment: Option<P>,
     pub eval: Option<S>,
 }
@@ -97,13 +128,15 @@ pub struct EvaluationQuery<A: ArithEccChip> {
 impl<A: ArithEccChip> EvaluationQuery<A> {
     pub fn new(
         rotation: i32,
+        item: QueryItem,
         commitment_key: String,
         point: A::AssignedScalar,
         commitment: A::AssignedPoint,
         eval: A::AssignedScalar,
     ) -> Self {
         let s = CommitQuery {
-            key: commitment_key,
+            key: commitment_key.into(),
+            item,
             commitment: Some(commitment),
             eval: Some(eval),
         };
@@ -124,6 +157,54 @@ impl<A: ArithEccChip> EvaluationQuery<A> {
     }
 }
 
+// Pending kzg pair, lhs is checked against [s] and rhs against [1] in G2.
+// Folding with a challenge r gives (r * lhs + other.lhs, r * rhs + other.rhs),
+// P is either an assigned point or a schema that is evaluated later.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Accumulator<P> {
+    pub lhs: P,
+    pub rhs: P,
+}
+
+impl<P: Clone> Accumulator<P> {
+    pub fn fold<A: ArithEccChip<AssignedPoint = P>>(
+        &self,
+        ctx: &mut A::Context,
+        pchip: &A,
+        other: &Self,
+        challenge: &A::AssignedScalar,
+    ) -> Result<Self, A::Error> {
+        let lhs = pchip.scalar_mul(ctx, challenge, &self.lhs)?;
+        let rhs = pchip.scalar_mul(ctx, challenge, &self.rhs)?;
+        Ok(Accumulator {
+            lhs: pchip.add(ctx, &lhs, &other.lhs)?,
+            rhs: pchip.add(ctx, &rhs, &other.rhs)?,
+        })
+    }
+}
+
+impl<P, S: Clone> Accumulator<EvaluationQuerySchema<P, S>> {
+    // Same fold on schemas, the scalar multiplications are merged into the final msm.
+    pub fn fold_schema(self, other: Self, challenge: &S) -> Self {
+        Accumulator {
+            lhs: self.lhs * scalar!(challenge) + other.lhs,
+            rhs: self.rhs * scalar!(challenge) + other.rhs,
+        }
+    }
+}
+
+fn add_to<A: ArithEccChip>(
+    pchip: &A,
+    ctx: &mut A::Context,
+    acc: Option<A::AssignedPoint>,
+    p: A::AssignedPoint,
+) -> Result<A::AssignedPoint, A::Error> {
+    match acc {
+        None => Ok(p),
+        Some(acc) => pchip.add(ctx, &acc, &p),
+    }
+}
+
 impl<P: Clone, S: Clone> EvaluationQuerySchema<P, S> {
     pub fn eval<
         Scalar: FieldExt,
@@ -135,159 +216,318 @@ impl<P: Clone, S: Clone> EvaluationQuerySchema<P, S> {
         pchip: &A,
         one: &A::AssignedScalar,
     ) -> Result<(A::AssignedPoint, Option<A::AssignedScalar>), A::Error> {
-        let points = self.eval_prepare::<Scalar, A>(ctx, schip, one, None)?;
+        let mut arena = SchemaArena::default();
+        let root = arena.push(self);
+        let points = arena.eval_prepare::<Scalar, A>(root, ctx, schip, one)?;
         let s = points
             .iter()
-            .find(|b| b.0 == "")
+            .find(|b| b.0.is_empty())
             .map(|b| b.2.as_ref().unwrap().clone());
         let p_wo_scalar = points
             .iter()
             .filter_map(|b| if b.2.is_none() { b.1.clone() } else { None })
             .collect::<Vec<_>>();
-        let (p_l, s_l) = points
-            .into_iter()
-            .filter_map(|(_, p, s)| p.and_then(|p| s.and_then(|s| Some((p, s)))))
-            .unzip();
-        let mut acc = pchip.multi_exp(ctx, p_l, s_l)?;
+        let mut acc = None;
+        let mut p_l = vec![];
+        let mut s_l = vec![];
+        for (_, p, s) in points.into_iter() {
+            if let (Some(p), Some(s)) = (p, s) {
+                // the chip takes the points it assigned as constants, the vk commitments, to its
+                // fixed-base tables if it has any
+                let fixed = pchip.scalar_mul_fixed_base(ctx, &s, &p)?;
+                match fixed {
+                    Some(fixed) => acc = Some(add_to(pchip, ctx, acc, fixed)?),
+                    None => {
+                        p_l.push(p);
+                        s_l.push(s);
+                    }
+                }
+            }
+        }
+        // chips without tables keep their single multi_exp
+        if !p_l.is_empty() || acc.is_none() {
+            let p = pchip.multi_exp(ctx, p_l, s_l)?;
+            acc = Some(add_to(pchip, ctx, acc, p)?);
+        }
         for p in p_wo_scalar {
-            acc = pchip.add(ctx, &acc, &p)?;
+            acc = Some(add_to(pchip, ctx, acc, p)?);
         }
 
-        Ok((acc, s))
+        Ok((acc.unwrap(), s))
     }
 
-    fn eval_prepare<
-        Scalar: FieldExt,
-        A: ArithEccChip<AssignedPoint = P, AssignedScalar = S, Scalar = Scalar>,
-    >(
-        self,
-        ctx: &mut A::Context,
-        schip: &A::ScalarChip,
-        one: &A::AssignedScalar,
-        scalar: Option<A::AssignedScalar>,
-    ) -> Result<Vec<(String, Option<A::AssignedPoint>, Option<A::AssignedScalar>)>, A::Error> {
+    pub fn estimate(&self, scalar: Option<()>) -> usize {
         match self {
-            EvaluationQuerySchema::Commitment(cq) => {
-                Ok(vec![(cq.key, Some(cq.commitment.unwrap()), scalar)])
-            }
-            EvaluationQuerySchema::Eval(cq) => {
-                let e = match scalar {
-                    Some(s) => schip.mul(ctx, &s, &cq.eval.unwrap())?,
-                    None => cq.eval.unwrap(),
-                };
-                Ok(vec![("".to_owned(), None, Some(e))])
-            }
-            EvaluationQuerySchema::Scalar(s) => {
-                let s = match scalar {
-                    Some(scalar) => schip.mul(ctx, &s, &scalar)?,
-                    None => s,
-                };
-                Ok(vec![("".to_owned(), None, Some(s))])
-            }
+            EvaluationQuerySchema::Commitment(_) => 1,
+            EvaluationQuerySchema::Eval(_) => match scalar {
+                Some(_) => 1,
+                None => 0,
+            },
+            EvaluationQuerySchema::Scalar(_) => match scalar {
+                Some(_) => 1,
+                None => 0,
+            },
             EvaluationQuerySchema::Add(l, r) => {
                 if !l.1 && !r.1 {
-                    let l = l.0.eval_prepare::<Scalar, A>(ctx, schip, one, None)?;
-                    let r = r.0.eval_prepare::<Scalar, A>(ctx, schip, one, None)?;
-                    assert!(l.len() == 1);
-                    assert!(r.len() == 1);
-                    let sum =
-                        schip.add(ctx, l[0].2.as_ref().unwrap(), &r[0].2.as_ref().unwrap())?;
-                    let sum = match scalar {
-                        Some(scalar) => schip.mul(ctx, &scalar, &sum)?,
-                        None => sum,
-                    };
-                    Ok(vec![("".to_owned(), None, Some(sum))])
+                    let l = l.0.estimate(None);
+                    let r = r.0.estimate(None);
+                    match scalar {
+                        Some(_) => l + r + 1,
+                        None => l + r,
+                    }
                 } else {
-                    let mut res: Vec<(_, _, Option<_>)> = vec![];
+                    let mut est = 0;
                     for s in vec![l, r] {
-                        for evalated in
-                            s.0.eval_prepare::<Scalar, A>(ctx, schip, one, scalar.clone())?
-                        {
-                            let found = res.iter_mut().find(|p| p.0 == evalated.0);
-
-                            match found {
-                                Some(p) => {
-                                    let s = schip.add(
-                                        ctx,
-                                        p.2.as_ref().unwrap_or(one),
-                                        evalated.2.as_ref().unwrap_or(one),
-                                    )?;
-                                    p.2 = Some(s);
-                                }
-                                None => {
-                                    res.push(evalated);
-                                }
-                            }
-                        }
+                        est += s.0.estimate(scalar.clone())
                     }
-                    Ok(res)
+                    est
                 }
             }
             EvaluationQuerySchema::Mul(l, r) => {
-                let (s, rem) = if !l.1 {
-                    let s = l.0.eval_prepare::<Scalar, A>(ctx, schip, one, None)?;
-                    let rem = r.0;
-                    (s, rem)
+                if !l.1 {
+                    r.0.estimate(Some(()))
                 } else {
-                    let s = r.0.eval_prepare::<Scalar, A>(ctx, schip, one, None)?;
-                    let rem = l.0;
-                    (s, rem)
-                };
-
-                assert_eq!(s.len(), 1);
-
-                let s = s[0].2.clone();
-                let s = match scalar {
-                    Some(scalar) => schip.mul(ctx, &scalar, s.as_ref().unwrap())?,
-                    None => s.unwrap(),
-                };
-
-                rem.eval_prepare::<Scalar, A>(ctx, schip, one, Some(s))
+                    l.0.estimate(Some(()))
+                }
             }
         }
     }
 
-    pub fn estimate(&self, scalar: Option<()>) -> usize {
+    // Same walk as estimate(), but attributes each scalar mult to the schema item it comes from.
+    pub fn estimate_profile(
+        &self,
+        scalar: Option<()>,
+        profile: &mut BTreeMap<&'static str, usize>,
+    ) {
         match self {
-            EvaluationQuerySchema::Commitment(_) => {
-                1
+            EvaluationQuerySchema::Commitment(cq) => {
+                *profile.entry(cq.item.name()).or_insert(0) += 1;
             }
-            EvaluationQuerySchema::Eval(_) => {
-                match scalar {
-                    Some(_) => 1,
-                    None => 0
+            EvaluationQuerySchema::Eval(cq) => {
+                if scalar.is_some() {
+                    *profile.entry(cq.item.name()).or_insert(0) += 1;
                 }
             }
             EvaluationQuerySchema::Scalar(_) => {
-                match scalar {
-                    Some(_) => 1,
-                    None => 0
+                if scalar.is_some() {
+                    *profile.entry("scalar").or_insert(0) += 1;
                 }
             }
             EvaluationQuerySchema::Add(l, r) => {
                 if !l.1 && !r.1 {
-                    let l = l.0.estimate(None);
-                    let r = r.0.estimate(None);
-                    match scalar {
-                        Some(_) => l+r+1,
-                        None => l+r,
+                    l.0.estimate_profile(None, profile);
+                    r.0.estimate_profile(None, profile);
+                    if scalar.is_some() {
+                        *profile.entry("scalar").or_insert(0) += 1;
                     }
                 } else {
-                    let mut est = 0;
-                    for s in vec![l, r] {
-                        est += s.0.estimate(scalar.clone())
-                    }
-                    est
+                    l.0.estimate_profile(scalar, profile);
+                    r.0.estimate_profile(scalar, profile);
                 }
             }
             EvaluationQuerySchema::Mul(l, r) => {
                 if !l.1 {
-                    r.0.estimate(Some(()))
+                    r.0.estimate_profile(Some(()), profile)
                 } else {
-                    l.0.estimate(Some(()))
+                    l.0.estimate_profile(Some(()), profile)
                 }
             }
         }
     }
 
+    // Render the schema tree as a graphviz digraph,
+    // each node is annotated with its estimated scalar mults.
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut lines = vec![format!("digraph {} {{", name)];
+        self.dot_nodes(name, &mut 0, &mut lines);
+        lines.push("}".to_owned());
+        lines.join("\n")
+    }
+
+    // Pushes the nodes of the tree, returns the id of its root with the costs of estimate(None)
+    // and estimate(Some(())), computed from those of the children in the same walk.
+    pub(crate) fn dot_nodes(
+        &self,
+        prefix: &str,
+        counter: &mut usize,
+        lines: &mut Vec<String>,
+    ) -> (String, (usize, usize)) {
+        let id = format!("{}_{}", prefix, counter);
+        *counter += 1;
+
+        let line = lines.len();
+        lines.push(String::new());
+
+        let (label, cost) = match self {
+            EvaluationQuerySchema::Commitment(cq) => (format!("commit {}", cq.key), (1, 1)),
+            EvaluationQuerySchema::Eval(cq) => (format!("eval {}", cq.key), (0, 1)),
+            EvaluationQuerySchema::Scalar(_) => ("scalar".to_owned(), (0, 1)),
+            EvaluationQuerySchema::Add(l, r) | EvaluationQuerySchema::Mul(l, r) => {
+                let mut costs = vec![];
+                for child in [l, r] {
+                    let (child_id, cost) = child.0.dot_nodes(prefix, counter, lines);
+                    lines.push(format!("    {} -> {};", id, child_id));
+                    costs.push(cost);
+                }
+                let ((l_none, l_some), (r_none, r_some)) = (costs[0], costs[1]);
+                match self {
+                    EvaluationQuerySchema::Add(..) if !l.1 && !r.1 => {
+                        ("+".to_owned(), (l_none + r_none, l_none + r_none + 1))
+                    }
+                    EvaluationQuerySchema::Add(..) => {
+                        ("+".to_owned(), (l_none + r_none, l_some + r_some))
+                    }
+                    _ if !l.1 => ("*".to_owned(), (r_some, r_some)),
+                    _ => ("*".to_owned(), (l_some, l_some)),
+                }
+            }
+        };
+        lines[line] = format!("    {} [label=\"{}\\ncost: {}\"];", id, label, cost.0);
+
+        (id, cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{commit, eval, scalar};
+
+    #[test]
+    fn test_accumulator_fold() {
+        use crate::mock::arith::{ecc::MockEccChip, field::MockChipCtx};
+        use group::{Curve, Group};
+        use halo2_proofs::{pairing::bn256::Fr as Fp, plonk::Error};
+        use pairing_bn256::bn256::{G1Affine, G1};
+
+        let pchip = MockEccChip::<G1Affine, Error>::default();
+        let ctx = &mut MockChipCtx::default();
+        let g = |i: u64| G1::generator() * Fp::from(i);
+
+        let acc = Accumulator {
+            lhs: g(1),
+            rhs: g(2),
+        };
+        let other = Accumulator {
+            lhs: g(3),
+            rhs: g(4),
+        };
+        let folded = acc.fold(ctx, &pchip, &other, &Fp::from(5)).unwrap();
+        assert_eq!(folded.lhs.to_affine(), g(8).to_affine());
+        assert_eq!(folded.rhs.to_affine(), g(14).to_affine());
+
+        let query = |i: u64| CommitQuery::<G1, Fp> {
+            key: format!("w{}", i).into(),
+            item: QueryItem::Multiopen,
+            commitment: Some(g(i)),
+            eval: None,
+        };
+        let acc = Accumulator {
+            lhs: commit!(query(1)),
+            rhs: commit!(query(2)),
+        };
+        let other = Accumulator {
+            lhs: commit!(query(3)),
+            rhs: commit!(query(4)),
+        };
+        let folded = acc.fold_schema(other, &Fp::from(5));
+        assert_eq!(
+            folded.lhs,
+            scalar!(Fp::from(5)) * commit!(query(1)) + commit!(query(3))
+        );
+    }
+
+    #[test]
+    fn test_query_key_is_shared() {
+        use crate::mock::arith::ecc::MockEccChip;
+        use group::Group;
+        use halo2_proofs::{pairing::bn256::Fr as Fp, plonk::Error};
+        use pairing_bn256::bn256::{G1Affine, G1};
+
+        let query = EvaluationQuery::<MockEccChip<G1Affine, Error>>::new(
+            0,
+            "p_advice_commitments0".to_owned(),
+            Fp::from(1),
+            G1::generator(),
+            Fp::from(2),
+        );
+        let (commitment, eval) = match &query.s {
+            EvaluationQuerySchema::Add(l, r) => match (&l.0, &r.0) {
+                (EvaluationQuerySchema::Commitment(l), EvaluationQuerySchema::Eval(r)) => (l, r),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        assert!(Rc::ptr_eq(&commitment.key, &eval.key));
+        // cloning the query does not copy the key
+        match query.clone().s {
+            EvaluationQuerySchema::Add(l, _) => match l.0 {
+                EvaluationQuerySchema::Commitment(cq) => {
+                    assert!(Rc::ptr_eq(&cq.key, &commitment.key))
+                }
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_estimate_profile_matches_estimate() {
+        let query = |key: &str, item| CommitQuery::<(), u64> {
+            key: key.into(),
+            item,
+            commitment: Some(()),
+            eval: Some(1),
+        };
+        let advice = query("p_advice_commitments0", QueryItem::Advice);
+        let fixed = query("p_fixed_commitments1", QueryItem::Fixed);
+        // the item is the tag of the query, not a guess from its key
+        let permutation = query("p_0_product_commitment", QueryItem::Permutation);
+        let lookup = query("p_0_0_product_commitment", QueryItem::Lookup);
+
+        let s = scalar!(2u64) * (commit!(advice) + eval!(advice))
+            + scalar!(3u64) * (commit!(fixed) + eval!(fixed))
+            + commit!(permutation)
+            + scalar!(4u64) * (commit!(lookup) + eval!(lookup));
+
+        let mut profile = BTreeMap::new();
+        s.estimate_profile(None, &mut profile);
+
+        assert_eq!(profile.values().sum::<usize>(), s.estimate(None));
+        assert_eq!(profile["advice"], 2);
+        assert_eq!(profile["fixed"], 2);
+        assert_eq!(profile["permutation"], 1);
+        assert_eq!(profile["lookup"], 2);
+    }
+
+    #[test]
+    fn test_to_dot_costs_match_estimate() {
+        let query = |key: &str, item| CommitQuery::<(), u64> {
+            key: key.into(),
+            item,
+            commitment: Some(()),
+            eval: Some(1),
+        };
+        let a = query("p_advice_commitments0", QueryItem::Advice);
+        let b = query("p_fixed_commitments1", QueryItem::Fixed);
+
+        let mul = scalar!(2u64) * (commit!(a) + eval!(a));
+        let s = mul.clone() + commit!(b) + scalar!(3u64) * eval!(b);
+        let dot = s.to_dot("w");
+
+        assert!(dot.starts_with("digraph w {\n"));
+        assert!(dot.ends_with("\n}"));
+        assert!(dot.contains(&format!("w_0 [label=\"+\\ncost: {}\"];", s.estimate(None))));
+        // the first * of the tree, its cost is that of its commitment operand
+        assert!(dot.contains(&format!(
+            "w_2 [label=\"*\\ncost: {}\"];",
+            mul.estimate(None)
+        )));
+        assert!(dot.contains("[label=\"commit p_fixed_commitments1\\ncost: 1\"];"));
+        assert!(dot.contains("w_0 -> w_1;"));
+        // one line per node and per edge, between the braces
+        let nodes = dot.matches("[label=").count();
+        assert_eq!(nodes, 11);
+        assert_eq!(dot.matches(" -> ").count(), nodes - 1);
+    }
 }
@@ -0,0 +1,281 @@
+use super::instance::instance_commitments;
+use halo2_proofs::{
+    arithmetic::{CurveAffine, MultiMillerLoop},
+    plonk::VerifyingKey,
+    poly::commitment::ParamsVerifier,
+    transcript::{Challenge255, EncodedChallenge, Transcript, TranscriptRead},
+};
+use std::{fmt, io};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProofValue<C: CurveAffine> {
+    Commitment(C),
+    Challenge(C::ScalarExt),
+    Evaluation(C::ScalarExt),
+}
+
+// A value of the transcript of a proof, labelled with what it is for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProofEntry<C: CurveAffine> {
+    pub label: String,
+    pub value: ProofValue<C>,
+}
+
+impl<C: CurveAffine> fmt::Display for ProofEntry<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            ProofValue::Commitment(p) => match Option::from(p.coordinates()) {
+                Some(c) => write!(f, "{} commitment ({:?}, {:?})", self.label, c.x(), c.y()),
+                None => write!(f, "{} commitment identity", self.label),
+            },
+            ProofValue::Challenge(s) => write!(f, "{} challenge {:?}", self.label, s),
+            ProofValue::Evaluation(s) => write!(f, "{} evaluation {:?}", self.label, s),
+        }
+    }
+}
+
+struct Explainer<'a, C: CurveAffine, T> {
+    transcript: &'a mut T,
+    entries: Vec<ProofEntry<C>>,
+    // labels are prefixed with the proof index when the transcript holds several proofs
+    prefix: String,
+}
+
+impl<'a, C: CurveAffine, T: TranscriptRead<C, Challenge255<C>>> Explainer<'a, C, T> {
+    fn push(&mut self, label: String, value: ProofValue<C>) {
+        let label = format!("{}{}", self.prefix, label);
+        self.entries.push(ProofEntry { label, value });
+    }
+
+    fn point(&mut self, label: String) -> io::Result<()> {
+        let p = self.transcript.read_point()?;
+        self.push(label, ProofValue::Commitment(p));
+        Ok(())
+    }
+
+    fn scalar(&mut self, label: String) -> io::Result<()> {
+        let s = self.transcript.read_scalar()?;
+        self.push(label, ProofValue::Evaluation(s));
+        Ok(())
+    }
+
+    fn challenge(&mut self, label: &str) {
+        let s = self.transcript.squeeze_challenge().get_scalar();
+        self.entries.push(ProofEntry {
+            label: label.to_owned(),
+            value: ProofValue::Challenge(s),
+        });
+    }
+
+    // Runs f once per proof, with the labels prefixed by the proof index if there are several.
+    fn per_proof(
+        &mut self,
+        num_proofs: usize,
+        mut f: impl FnMut(&mut Self) -> io::Result<()>,
+    ) -> io::Result<()> {
+        for i in 0..num_proofs {
+            if num_proofs > 1 {
+                self.prefix = format!("proof {} ", i);
+            }
+            f(self)?;
+        }
+        self.prefix = String::new();
+        Ok(())
+    }
+}
+
+// Walks the transcript of a proof in the read order of halo2's verifier, as
+// build_params does, and labels every commitment, challenge and evaluation.
+// The transcript is the one the proof was written with: poseidon for target
+// proofs, sha256 for the verify circuit proofs.
+pub fn explain_proof<E: MultiMillerLoop, T>(
+    vk: &VerifyingKey<E::G1Affine>,
+    params: &ParamsVerifier<E>,
+    instances: &[&[&[<E::G1Affine as CurveAffine>::ScalarExt]]],
+    transcript: &mut T,
+) -> io::Result<Vec<ProofEntry<E::G1Affine>>>
+where
+    T: TranscriptRead<E::G1Affine, Challenge255<E::G1Affine>>,
+{
+    let commitments = instance_commitments(params, instances);
+    explain_proof_with_instance_commitments(vk, &commitments, transcript)
+}
+
+// explain_proof from the instance commitments of each proof.
+fn explain_proof_with_instance_commitments<C: CurveAffine, T>(
+    vk: &VerifyingKey<C>,
+    instance_commitments: &[Vec<C>],
+    transcript: &mut T,
+) -> io::Result<Vec<ProofEntry<C>>>
+where
+    T: TranscriptRead<C, Challenge255<C>>,
+{
+    let cs = &vk.cs;
+    let num_proofs = instance_commitments.len();
+    let num_permutation_chunks = cs.permutation.columns.chunks(cs.degree() - 2).len();
+
+    vk.hash_into(&mut *transcript)?;
+    let mut e = Explainer {
+        transcript,
+        entries: vec![],
+        prefix: String::new(),
+    };
+
+    for (i, commitments) in instance_commitments.iter().enumerate() {
+        for (column, commitment) in commitments.iter().enumerate() {
+            e.transcript.common_point(*commitment)?;
+            let label = match num_proofs {
+                1 => format!("instance[{}]", column),
+                _ => format!("proof {} instance[{}]", i, column),
+            };
+            e.entries.push(ProofEntry {
+                label,
+                value: ProofValue::Commitment(*commitment),
+            });
+        }
+    }
+
+    e.per_proof(num_proofs, |e| {
+        (0..cs.num_advice_columns).try_for_each(|i| e.point(format!("advice[{}]", i)))
+    })?;
+    e.challenge("theta");
+
+    e.per_proof(num_proofs, |e| {
+        (0..cs.lookups.len()).try_for_each(|i| {
+            e.point(format!("lookup#{} permuted input", i))?;
+            e.point(format!("lookup#{} permuted table", i))
+        })
+    })?;
+    e.challenge("beta");
+    e.challenge("gamma");
+
+    e.per_proof(num_proofs, |e| {
+        (0..num_permutation_chunks).try_for_each(|i| e.point(format!("permutation#{} product", i)))
+    })?;
+    e.per_proof(num_proofs, |e| {
+        (0..cs.lookups.len()).try_for_each(|i| e.point(format!("lookup#{} product", i)))
+    })?;
+    e.point("vanishing random".to_owned())?;
+    e.challenge("y");
+
+    (0..vk.domain.get_quotient_poly_degree())
+        .try_for_each(|i| e.point(format!("h-piece {}", i)))?;
+    e.challenge("x");
+
+    e.per_proof(num_proofs, |e| {
+        cs.instance_queries
+            .iter()
+            .try_for_each(|(column, rotation)| {
+                e.scalar(format!(
+                    "instance[{}] at rotation {}",
+                    column.index(),
+                    rotation.0
+                ))
+            })
+    })?;
+    e.per_proof(num_proofs, |e| {
+        cs.advice_queries.iter().try_for_each(|(column, rotation)| {
+            e.scalar(format!(
+                "advice[{}] at rotation {}",
+                column.index(),
+                rotation.0
+            ))
+        })
+    })?;
+    cs.fixed_queries.iter().try_for_each(|(column, rotation)| {
+        e.scalar(format!(
+            "fixed[{}] at rotation {}",
+            column.index(),
+            rotation.0
+        ))
+    })?;
+    e.scalar("vanishing random".to_owned())?;
+    (0..vk.permutation.commitments.len())
+        .try_for_each(|i| e.scalar(format!("permutation sigma[{}]", i)))?;
+
+    e.per_proof(num_proofs, |e| {
+        (0..num_permutation_chunks).try_for_each(|i| {
+            e.scalar(format!("permutation#{} product", i))?;
+            e.scalar(format!("permutation#{} product next", i))?;
+            if i + 1 < num_permutation_chunks {
+                e.scalar(format!("permutation#{} product last", i))?;
+            }
+            Ok(())
+        })
+    })?;
+    e.per_proof(num_proofs, |e| {
+        (0..cs.lookups.len()).try_for_each(|i| {
+            e.scalar(format!("lookup#{} product", i))?;
+            e.scalar(format!("lookup#{} product next", i))?;
+            e.scalar(format!("lookup#{} permuted input", i))?;
+            e.scalar(format!("lookup#{} permuted input prev", i))?;
+            e.scalar(format!("lookup#{} permuted table", i))
+        })
+    })?;
+
+    e.challenge("v");
+    e.challenge("u");
+    let mut i = 0;
+    while e.point(format!("multiopen w[{}]", i)).is_ok() {
+        i += 1;
+    }
+
+    Ok(e.entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::systems::halo2::add_mul_test::test_circuit::test_circuit_builder;
+    use halo2_proofs::{
+        arithmetic::Field,
+        pairing::bn256::Fr as Fp,
+        plonk::{create_proof, keygen_pk, keygen_vk},
+        poly::commitment::Params,
+        transcript::{PoseidonRead, PoseidonWrite},
+    };
+    use pairing_bn256::bn256::{Bn256, G1Affine};
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+    use rand_xorshift::XorShiftRng;
+
+    #[test]
+    fn test_explain_add_mul_proof() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        let a = Fp::random(&mut rng);
+        let b = Fp::random(&mut rng);
+        let c = Fp::from(7) * a.square() * b.square();
+
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(10);
+        let vk = keygen_vk(&params, &test_circuit_builder(a, b)).unwrap();
+        let pk = keygen_pk(&params, vk, &test_circuit_builder(a, b)).unwrap();
+        let instances: &[&[&[_]]] = &[&[&[c]]];
+
+        let mut transcript = PoseidonWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            &[test_circuit_builder(a, b)],
+            instances,
+            Pcg32::seed_from_u64(0),
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.finalize();
+        let params_verifier: ParamsVerifier<Bn256> = params.verifier(1).unwrap();
+
+        let mut transcript = PoseidonRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        let entries =
+            explain_proof(pk.get_vk(), &params_verifier, instances, &mut transcript).unwrap();
+        let labels = entries.iter().map(|e| e.label.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(labels[0], "instance[0]");
+        assert_eq!(labels[1], "advice[0]");
+        assert!(labels.contains(&"h-piece 0"));
+        assert!(labels.contains(&"multiopen w[0]"));
+        let x = labels.iter().position(|l| *l == "x").unwrap();
+        assert!(matches!(entries[x].value, ProofValue::Challenge(_)));
+        assert!(matches!(entries[x + 1].value, ProofValue::Evaluation(_)));
+        assert!(entries[x].to_string().starts_with("x challenge "));
+    }
+}
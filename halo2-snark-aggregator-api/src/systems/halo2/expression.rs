@@ -1,6 +1,8 @@
 use crate::arith::ast::FieldArithHelper;
 use crate::{arith::ecc::ArithEccChip, arith_ast};
 use halo2_proofs::plonk::Expression;
+use std::collections::HashMap;
+
 pub trait Evaluable<A: ArithEccChip> {
     fn chip_evaluate(
         &self,
@@ -11,6 +13,35 @@ pub trait Evaluable<A: ArithEccChip> {
         instance: &impl Fn(usize) -> A::AssignedScalar,
         zero: &A::AssignedScalar,
     ) -> Result<A::AssignedScalar, A::Error>;
+
+    // Same as chip_evaluate, but sub-expressions already evaluated for the same proof are reused.
+    // Returns the structural key of the expression together with its value.
+    fn chip_evaluate_cached(
+        &self,
+        ctx: &mut A::Context,
+        schip: &A::ScalarChip,
+        fixed: &impl Fn(usize) -> A::AssignedScalar,
+        advice: &impl Fn(usize) -> A::AssignedScalar,
+        instance: &impl Fn(usize) -> A::AssignedScalar,
+        zero: &A::AssignedScalar,
+        cache: &mut EvaluationCache<A>,
+    ) -> Result<(String, A::AssignedScalar), A::Error>;
+}
+
+// Evaluated sub-expressions of one proof, keyed by their structure.
+// Must not be shared between proofs since the advice and instance evals differ.
+pub struct EvaluationCache<A: ArithEccChip> {
+    values: HashMap<String, A::AssignedScalar>,
+    pub hits: usize,
+}
+
+impl<A: ArithEccChip> Default for EvaluationCache<A> {
+    fn default() -> Self {
+        Self {
+            values: HashMap::new(),
+            hits: 0,
+        }
+    }
 }
 
 impl<A: ArithEccChip> Evaluable<A> for Expression<A::AssignedScalar> {
@@ -113,4 +144,146 @@ impl<A: ArithEccChip> Evaluable<A> for Expression<A::AssignedScalar> {
 
         Ok(res)
     }
+
+    fn chip_evaluate_cached(
+        &self,
+        ctx: &mut A::Context,
+        schip: &A::ScalarChip,
+        fixed: &impl Fn(usize) -> A::AssignedScalar,
+        advice: &impl Fn(usize) -> A::AssignedScalar,
+        instance: &impl Fn(usize) -> A::AssignedScalar,
+        zero: &A::AssignedScalar,
+        cache: &mut EvaluationCache<A>,
+    ) -> Result<(String, A::AssignedScalar), A::Error> {
+        let eval = |e: &Expression<A::AssignedScalar>,
+                    ctx: &mut A::Context,
+                    cache: &mut EvaluationCache<A>| {
+            Evaluable::<A>::chip_evaluate_cached(
+                e, ctx, schip, fixed, advice, instance, zero, cache,
+            )
+        };
+
+        let (key, children) = match self {
+            Expression::Constant(scalar) => return Ok((format!("c{:?}", scalar), scalar.clone())),
+            Expression::Selector(_selector) => {
+                panic!("virtual selectors are removed during optimization")
+            }
+            Expression::Fixed { query_index, .. } => {
+                return Ok((format!("f{}", query_index), fixed(*query_index)))
+            }
+            Expression::Advice { query_index, .. } => {
+                return Ok((format!("a{}", query_index), advice(*query_index)))
+            }
+            Expression::Instance { query_index, .. } => {
+                return Ok((format!("i{}", query_index), instance(*query_index)))
+            }
+            Expression::Negated(a) => {
+                let a = eval(a, ctx, cache)?;
+                (format!("-({})", a.0), vec![a.1])
+            }
+            Expression::Sum(a, b) => {
+                let a = eval(a, ctx, cache)?;
+                let b = eval(b, ctx, cache)?;
+                (format!("({}+{})", a.0, b.0), vec![a.1, b.1])
+            }
+            Expression::Product(a, b) => {
+                let a = eval(a, ctx, cache)?;
+                let b = eval(b, ctx, cache)?;
+                (format!("({}*{})", a.0, b.0), vec![a.1, b.1])
+            }
+            Expression::Scaled(a, f) => {
+                let a = eval(a, ctx, cache)?;
+                (format!("({:?}*{})", f, a.0), vec![a.1])
+            }
+        };
+
+        if let Some(v) = cache.values.get(&key) {
+            cache.hits += 1;
+            return Ok((key, v.clone()));
+        }
+
+        let res = match (self, &children[..]) {
+            (Expression::Negated(_), [a]) => arith_ast!(zero - a).eval(ctx, schip)?,
+            (Expression::Sum(..), [a, b]) => arith_ast!(a + b).eval(ctx, schip)?,
+            (Expression::Product(..), [a, b]) => arith_ast!((a * b)).eval(ctx, schip)?,
+            (Expression::Scaled(_, f), [a]) => arith_ast!((f * a)).eval(ctx, schip)?,
+            _ => unreachable!(),
+        };
+        cache.values.insert(key.clone(), res.clone());
+
+        Ok((key, res))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::arith::{
+        ecc::MockEccChip,
+        field::{MockChipCtx, MockFieldChip},
+    };
+    use halo2_proofs::{pairing::bn256::Fr as Fp, plonk::Error, poly::Rotation};
+    use pairing_bn256::bn256::G1Affine;
+
+    type A = MockEccChip<G1Affine, Error>;
+
+    fn advice(query_index: usize) -> Expression<Fp> {
+        Expression::Advice {
+            query_index,
+            column_index: query_index,
+            rotation: Rotation::cur(),
+        }
+    }
+
+    fn fixed(query_index: usize) -> Expression<Fp> {
+        Expression::Fixed {
+            query_index,
+            column_index: query_index,
+            rotation: Rotation::cur(),
+        }
+    }
+
+    #[test]
+    fn test_cached_evaluation_matches_uncached() {
+        let schip = MockFieldChip::<Fp, Error>::default();
+        let ctx = &mut MockChipCtx::default();
+        let zero = Fp::zero();
+        let fixed_evals = [Fp::from(3), Fp::from(5)];
+        let advice_evals = [Fp::from(7), Fp::from(11)];
+
+        let shared = advice(0) * fixed(1);
+        let gates = vec![
+            shared.clone() + advice(1),
+            -shared.clone() * Expression::Constant(Fp::from(2)),
+            shared.clone() * shared,
+        ];
+
+        let mut cache = EvaluationCache::<A>::default();
+        for gate in gates.iter() {
+            let expected = Evaluable::<A>::chip_evaluate(
+                gate,
+                ctx,
+                &schip,
+                &|n| fixed_evals[n],
+                &|n| advice_evals[n],
+                &|_| unreachable!(),
+                &zero,
+            )
+            .unwrap();
+            let (_, cached) = Evaluable::<A>::chip_evaluate_cached(
+                gate,
+                ctx,
+                &schip,
+                &|n| fixed_evals[n],
+                &|n| advice_evals[n],
+                &|_| unreachable!(),
+                &zero,
+                &mut cache,
+            )
+            .unwrap();
+            assert_eq!(cached, expected);
+        }
+        // advice(0) * fixed(1) is evaluated once and hit three more times
+        assert_eq!(cache.hits, 3);
+    }
 }
@@ -0,0 +1,368 @@
+use group::{ff::PrimeField, Curve, Group, GroupEncoding};
+use halo2_proofs::{
+    arithmetic::{CurveAffine, MultiMillerLoop},
+    plonk::{Error, VerifyingKey},
+    poly::commitment::ParamsVerifier,
+};
+use std::collections::BTreeMap;
+use std::{fmt, io};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InstanceError {
+    ColumnCount {
+        proof: usize,
+        expected: usize,
+        actual: usize,
+    },
+    TooLong {
+        proof: usize,
+        column: usize,
+        max: usize,
+        actual: usize,
+    },
+    NonCanonical {
+        proof: usize,
+        column: usize,
+        row: usize,
+    },
+}
+
+impl fmt::Display for InstanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstanceError::ColumnCount {
+                proof,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "proof {} has {} instance columns, expected {}",
+                proof, actual, expected
+            ),
+            InstanceError::TooLong {
+                proof,
+                column,
+                max,
+                actual,
+            } => write!(
+                f,
+                "proof {} instance column {} has {} rows, at most {} are usable",
+                proof, column, actual, max
+            ),
+            InstanceError::NonCanonical { proof, column, row } => write!(
+                f,
+                "proof {} instance column {} row {} is not a canonical field element",
+                proof, column, row
+            ),
+        }
+    }
+}
+
+impl From<InstanceError> for Error {
+    fn from(_: InstanceError) -> Self {
+        Error::InvalidInstances
+    }
+}
+
+// Rows left for instances once the blinding rows and the last row are taken.
+pub fn max_instance_rows<E: MultiMillerLoop>(
+    vk: &VerifyingKey<E::G1Affine>,
+    params: &ParamsVerifier<E>,
+) -> usize {
+    params.n as usize - (vk.cs.blinding_factors() + 1)
+}
+
+pub fn check_instance_shape<F>(
+    num_columns: usize,
+    max_rows: usize,
+    instances: &[&[&[F]]],
+) -> Result<(), InstanceError> {
+    for (proof, columns) in instances.iter().enumerate() {
+        if columns.len() != num_columns {
+            return Err(InstanceError::ColumnCount {
+                proof,
+                expected: num_columns,
+                actual: columns.len(),
+            });
+        }
+        for (column, values) in columns.iter().enumerate() {
+            if values.len() > max_rows {
+                return Err(InstanceError::TooLong {
+                    proof,
+                    column,
+                    max: max_rows,
+                    actual: values.len(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_instances<E: MultiMillerLoop>(
+    vk: &VerifyingKey<E::G1Affine>,
+    params: &ParamsVerifier<E>,
+    instances: &[&[&[E::Scalar]]],
+) -> Result<(), InstanceError> {
+    check_instance_shape(
+        vk.cs.num_instance_columns,
+        max_instance_rows(vk, params),
+        instances,
+    )
+}
+
+// Parses little-endian encoded instances, e.g. taken from calldata or a file,
+// rejecting values that are not reduced modulo the scalar field.
+pub fn instances_from_repr<F: PrimeField>(
+    instances: &[Vec<Vec<F::Repr>>],
+) -> Result<Vec<Vec<Vec<F>>>, InstanceError> {
+    instances
+        .iter()
+        .enumerate()
+        .map(|(proof, columns)| {
+            columns
+                .iter()
+                .enumerate()
+                .map(|(column, values)| {
+                    values
+                        .iter()
+                        .enumerate()
+                        .map(|(row, repr)| {
+                            let err = InstanceError::NonCanonical { proof, column, row };
+                            Option::<F>::from(F::from_repr(*repr)).ok_or(err)
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Native commitments of the instance columns of each proof, sum_i instance[i] * g_lagrange[i],
+// as halo2's verifier absorbs them.
+pub fn instance_commitments<E: MultiMillerLoop>(
+    params: &ParamsVerifier<E>,
+    instances: &[&[&[<E::G1Affine as CurveAffine>::ScalarExt]]],
+) -> Vec<Vec<E::G1Affine>> {
+    instances
+        .iter()
+        .map(|columns| {
+            columns
+                .iter()
+                .map(|values| {
+                    values
+                        .iter()
+                        .zip(params.g_lagrange.iter())
+                        .fold(
+                            <E::G1Affine as CurveAffine>::CurveExt::identity(),
+                            |acc, (s, g)| acc + *g * *s,
+                        )
+                        .to_affine()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn blake2b(personal: &[u8]) -> blake2b_simd::State {
+    blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(personal)
+        .to_state()
+}
+
+// Native instance commitments of target proofs already aggregated, keyed by the hash of their
+// instances and of the params, so that the same instances aggregated again (a retried run, a
+// re-org) are not committed again for the final pair.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InstanceCommitmentCache<C: CurveAffine> {
+    entries: BTreeMap<[u8; 32], Vec<C>>,
+}
+
+impl<C: CurveAffine> InstanceCommitmentCache<C> {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Hash of the lagrange bases of the params, the id of the params in the keys.
+    pub fn params_id<E: MultiMillerLoop<G1Affine = C>>(params: &ParamsVerifier<E>) -> [u8; 32] {
+        let mut hasher = blake2b(b"H2AG-Inst-Params");
+        hasher.update(&params.n.to_le_bytes());
+        for g in params.g_lagrange.iter() {
+            hasher.update(g.to_bytes().as_ref());
+        }
+        hasher.finalize().as_bytes().try_into().unwrap()
+    }
+
+    pub fn key(params_id: &[u8; 32], columns: &[&[C::Scalar]]) -> [u8; 32] {
+        let mut hasher = blake2b(b"H2AG-Inst-Commit");
+        hasher.update(params_id);
+        for values in columns.iter() {
+            hasher.update(&(values.len() as u32).to_le_bytes());
+            for value in values.iter() {
+                hasher.update(value.to_repr().as_ref());
+            }
+        }
+        hasher.finalize().as_bytes().try_into().unwrap()
+    }
+
+    // instance_commitments, taking the proofs already in the cache from it and adding the
+    // others to it. The params are hashed once for all the proofs.
+    pub fn commitments<E: MultiMillerLoop<G1Affine = C>>(
+        &mut self,
+        params: &ParamsVerifier<E>,
+        instances: &[&[&[C::Scalar]]],
+    ) -> Vec<Vec<C>> {
+        let params_id = Self::params_id(params);
+        instances
+            .iter()
+            .map(|columns| {
+                let key = Self::key(&params_id, columns);
+                self.entries
+                    .entry(key)
+                    .or_insert_with(|| instance_commitments(params, &[*columns]).remove(0))
+                    .clone()
+            })
+            .collect()
+    }
+
+    // entries u32 | per entry: key [u8; 32] | columns u32 | compressed commitments
+    pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&(self.entries.len() as u32).to_le_bytes())?;
+        for (key, commitments) in self.entries.iter() {
+            writer.write_all(key)?;
+            writer.write_all(&(commitments.len() as u32).to_le_bytes())?;
+            for c in commitments.iter() {
+                writer.write_all(c.to_bytes().as_ref())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let read_u32 = |reader: &mut R| -> io::Result<u32> {
+            let mut buf = [0u8; 4];
+            reader.read_exact(&mut buf)?;
+            Ok(u32::from_le_bytes(buf))
+        };
+
+        let mut entries = BTreeMap::new();
+        for _ in 0..read_u32(reader)? {
+            let mut key = [0u8; 32];
+            reader.read_exact(&mut key)?;
+            let commitments = (0..read_u32(reader)?)
+                .map(|_| {
+                    let mut repr = C::Repr::default();
+                    reader.read_exact(repr.as_mut())?;
+                    Option::from(C::from_bytes(&repr)).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "invalid instance commitment")
+                    })
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+            entries.insert(key, commitments);
+        }
+        Ok(InstanceCommitmentCache { entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{arithmetic::Field, pairing::bn256::Fr as Fp};
+
+    #[test]
+    fn test_check_instance_shape() {
+        let column = [Fp::from(1u64), Fp::from(2u64)];
+        let ok: &[&[Fp]] = &[&column[..], &column[..1]];
+        assert_eq!(check_instance_shape(2, 2, &[ok]), Ok(()));
+
+        assert_eq!(
+            check_instance_shape(3, 2, &[ok, ok]),
+            Err(InstanceError::ColumnCount {
+                proof: 0,
+                expected: 3,
+                actual: 2
+            })
+        );
+        assert_eq!(
+            check_instance_shape(2, 1, &[ok]),
+            Err(InstanceError::TooLong {
+                proof: 0,
+                column: 0,
+                max: 1,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_instance_commitments() {
+        use halo2_proofs::poly::commitment::Params;
+        use pairing_bn256::bn256::{Bn256, G1Affine};
+
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(4);
+        let params: ParamsVerifier<Bn256> = params.verifier(2).unwrap();
+        let column = [Fp::from(3u64), Fp::from(5u64)];
+        let other = [Fp::from(7u64)];
+        let instances: &[&[&[Fp]]] = &[&[&column[..]], &[&other[..]]];
+
+        let commitments = instance_commitments(&params, instances);
+        let g = params.g_lagrange.clone();
+        assert_eq!(
+            commitments[0][0],
+            (g[0] * Fp::from(3u64) + g[1] * Fp::from(5u64)).to_affine()
+        );
+        assert_eq!(commitments[1][0], (g[0] * Fp::from(7u64)).to_affine());
+    }
+
+    #[test]
+    fn test_instance_commitment_cache() {
+        use halo2_proofs::poly::commitment::Params;
+        use pairing_bn256::bn256::{Bn256, G1Affine};
+
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(4);
+        let params: ParamsVerifier<Bn256> = params.verifier(2).unwrap();
+        let column = [Fp::from(3u64), Fp::from(5u64)];
+        let other = [Fp::from(7u64)];
+        let instances: &[&[&[Fp]]] = &[&[&column[..]], &[&other[..]]];
+        let expected = instance_commitments(&params, instances);
+
+        let mut cache = InstanceCommitmentCache::default();
+        assert_eq!(cache.commitments(&params, instances), expected);
+        assert_eq!(cache.len(), 2);
+        // the same instances again hit the cache
+        assert_eq!(
+            cache.commitments(&params, &instances[..1]),
+            expected[..1].to_vec()
+        );
+        assert_eq!(cache.len(), 2);
+
+        let mut bytes = vec![];
+        cache.write(&mut bytes).unwrap();
+        let read = InstanceCommitmentCache::<G1Affine>::read(&mut &bytes[..]).unwrap();
+        assert_eq!(read, cache);
+        assert!(InstanceCommitmentCache::<G1Affine>::read(&mut &bytes[..10]).is_err());
+    }
+
+    #[test]
+    fn test_instances_from_repr() {
+        let one = Fp::from(1u64).to_repr();
+        let parsed = instances_from_repr::<Fp>(&[vec![vec![one]]]).unwrap();
+        assert_eq!(parsed, vec![vec![vec![Fp::from(1u64)]]]);
+
+        let mut modulus = (-Fp::one()).to_repr();
+        modulus.as_mut()[0] += 1;
+        assert_eq!(
+            instances_from_repr::<Fp>(&[vec![vec![one, modulus]]]),
+            Err(InstanceError::NonCanonical {
+                proof: 0,
+                column: 0,
+                row: 1
+            })
+        );
+    }
+}
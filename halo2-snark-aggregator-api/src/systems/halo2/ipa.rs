@@ -0,0 +1,266 @@
+use crate::arith::{common::ArithCommonChip, ecc::ArithEccChip, field::ArithFieldChip};
+use crate::transcript::read::TranscriptRead;
+use group::{Curve, Group};
+use halo2_proofs::arithmetic::{CurveAffine, Field};
+
+// Deferred part of an inner product argument opening:
+// q == [a] * <s(u), G>, where s(u) only depends on the round challenges.
+// Computing <s(u), G> is linear in the size of the srs, so it is left to
+// whoever holds the generators, as in the halo accumulation scheme.
+pub struct IpaAccumulator<A: ArithEccChip> {
+    pub challenges: Vec<A::AssignedScalar>,
+    pub a: A::AssignedScalar,
+    pub q: A::AssignedPoint,
+}
+
+// s_i = prod_j u_j^{b_ij}, where b_ij is the j-th most significant bit of i mapped to {-1, 1}.
+pub fn compute_s<F: Field>(challenges: &[F]) -> Vec<F> {
+    let mut s = vec![F::one()];
+    for u in challenges.iter() {
+        let u_inv = u.invert().unwrap();
+        s = s.iter().flat_map(|v| vec![*v * u_inv, *v * u]).collect();
+    }
+    s
+}
+
+impl<C: CurveAffine, A: ArithEccChip<Point = C, Scalar = C::ScalarExt>> IpaAccumulator<A> {
+    pub fn check_native(
+        &self,
+        schip: &A::ScalarChip,
+        pchip: &A,
+        generators: &[C],
+    ) -> Result<bool, A::Error> {
+        let challenges = self
+            .challenges
+            .iter()
+            .map(|u| schip.to_value(u))
+            .collect::<Result<Vec<_>, _>>()?;
+        let a = schip.to_value(&self.a)?;
+        let s = compute_s(&challenges[..]);
+        assert_eq!(s.len(), generators.len());
+
+        let g = s
+            .iter()
+            .zip(generators.iter())
+            .fold(C::CurveExt::identity(), |acc, (s, g)| acc + *g * *s);
+        Ok(pchip.to_value(&self.q)? == (g * a).to_affine())
+    }
+}
+
+// Succinct part of an ipa opening check for <coeffs, (1, x, ..., x^{n-1})> = v,
+// where commitment = <coeffs, G> and n = 2^k.
+//
+// The transcript is the plain bulletproofs layout, not the one of the zcash halo2 ipa backend:
+// each round reads L_j, R_j and squeezes u_j, L_j and R_j are scaled by u_j^2 and u_j^-2, and
+// the prover finally sends the folded coefficient a. zcash first reads the commitment to a
+// random polynomial and squeezes xi and z, scales the rounds by u_j^-1 and u_j, and ends with
+// the folded coefficient and a blinding factor, so its proofs can not be read by this function.
+pub fn verify_bulletproofs_ipa_in_chip<
+    C: CurveAffine,
+    A: ArithEccChip<Point = C, Scalar = C::ScalarExt>,
+    T: TranscriptRead<A>,
+>(
+    ctx: &mut A::Context,
+    nchip: &A::NativeChip,
+    schip: &A::ScalarChip,
+    pchip: &A,
+    transcript: &mut T,
+    commitment: &A::AssignedPoint,
+    x: &A::AssignedScalar,
+    v: &A::AssignedScalar,
+    u_generator: C,
+    k: usize,
+) -> Result<IpaAccumulator<A>, A::Error> {
+    let vu = pchip.scalar_mul_constant(ctx, v, u_generator)?;
+    let mut q = pchip.add(ctx, commitment, &vu)?;
+
+    let mut challenges = vec![];
+    for _ in 0..k {
+        let l = transcript.read_point(ctx, nchip, schip, pchip)?;
+        let r = transcript.read_point(ctx, nchip, schip, pchip)?;
+        let u = transcript.squeeze_challenge_scalar(ctx, nchip, schip)?;
+
+        let u_inv = schip.div(ctx, &schip.assign_one(ctx)?, &u)?;
+        let u_square = schip.square(ctx, &u)?;
+        let u_inv_square = schip.square(ctx, &u_inv)?;
+        let l = pchip.scalar_mul(ctx, &u_square, &l)?;
+        let r = pchip.scalar_mul(ctx, &u_inv_square, &r)?;
+        q = pchip.add(ctx, &q, &l)?;
+        q = pchip.add(ctx, &q, &r)?;
+
+        challenges.push(u);
+    }
+
+    let a = transcript.read_scalar(ctx, nchip, schip)?;
+
+    // b(u) = prod_j (u_j^{-1} + u_j * x^{2^{k-1-j}})
+    let mut x_pows = vec![x.clone()];
+    for i in 1..k {
+        let x_pow = schip.square(ctx, &x_pows[i - 1])?;
+        x_pows.push(x_pow);
+    }
+    let mut b = schip.assign_one(ctx)?;
+    for (j, u) in challenges.iter().enumerate() {
+        let u_inv = schip.div(ctx, &schip.assign_one(ctx)?, u)?;
+        let t = schip.mul(ctx, u, &x_pows[k - 1 - j])?;
+        let t = schip.add(ctx, &t, &u_inv)?;
+        b = schip.mul(ctx, &b, &t)?;
+    }
+
+    let ab = schip.mul(ctx, &a, &b)?;
+    let abu = pchip.scalar_mul_constant(ctx, &ab, u_generator)?;
+    let q = pchip.sub(ctx, &q, &abu)?;
+    let q = pchip.normalize(ctx, &q)?;
+
+    Ok(IpaAccumulator { challenges, a, q })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{
+        arith::{
+            ecc::MockEccChip,
+            field::{MockChipCtx, MockFieldChip},
+        },
+        transcript_encode::PoseidonEncode,
+    };
+    use crate::systems::halo2::transcript::PoseidonTranscriptRead;
+    use group::prime::PrimeCurveAffine;
+    use halo2_proofs::{
+        pairing::bn256::Fr as Fp,
+        plonk::Error,
+        transcript::{Challenge255, EncodedChallenge, PoseidonWrite, Transcript, TranscriptWrite},
+    };
+    use pairing_bn256::bn256::{G1Affine, G1};
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    fn inner_product(a: &[Fp], b: &[Fp]) -> Fp {
+        a.iter()
+            .zip(b.iter())
+            .fold(Fp::zero(), |acc, (a, b)| acc + a * b)
+    }
+
+    fn msm(s: &[Fp], g: &[G1Affine]) -> G1 {
+        s.iter()
+            .zip(g.iter())
+            .fold(G1::identity(), |acc, (s, g)| acc + g * s)
+    }
+
+    // Bulletproofs style prover folding (coeffs, powers of x, generators) in halves.
+    fn prove(coeffs: &[Fp], x: Fp, generators: &[G1Affine], u_generator: G1Affine) -> Vec<u8> {
+        let mut transcript = PoseidonWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        let mut a = coeffs.to_vec();
+        let mut b = (0..coeffs.len())
+            .scan(Fp::one(), |acc, _| {
+                let v = *acc;
+                *acc = *acc * x;
+                Some(v)
+            })
+            .collect::<Vec<_>>();
+        let mut g = generators.to_vec();
+
+        while a.len() > 1 {
+            let half = a.len() / 2;
+            let (a_lo, a_hi) = a.split_at(half);
+            let (b_lo, b_hi) = b.split_at(half);
+            let (g_lo, g_hi) = g.split_at(half);
+
+            let l = msm(a_lo, g_hi) + u_generator * inner_product(a_lo, b_hi);
+            let r = msm(a_hi, g_lo) + u_generator * inner_product(a_hi, b_lo);
+            transcript.write_point(l.to_affine()).unwrap();
+            transcript.write_point(r.to_affine()).unwrap();
+            let u = transcript.squeeze_challenge().get_scalar();
+            let u_inv = u.invert().unwrap();
+
+            let a_next = (0..half).map(|i| a_lo[i] * u + a_hi[i] * u_inv).collect();
+            let b_next = (0..half).map(|i| b_lo[i] * u_inv + b_hi[i] * u).collect();
+            let g_next = (0..half)
+                .map(|i| (g_lo[i] * u_inv + g_hi[i] * u).to_affine())
+                .collect();
+            a = a_next;
+            b = b_next;
+            g = g_next;
+        }
+
+        transcript.write_scalar(a[0]).unwrap();
+        transcript.finalize()
+    }
+
+    #[test]
+    fn test_verify_bulletproofs_ipa_in_chip() {
+        const K: usize = 3;
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        let generators = (0..1 << K)
+            .map(|_| (G1Affine::generator() * Fp::random(&mut rng)).to_affine())
+            .collect::<Vec<_>>();
+        let u_generator = (G1Affine::generator() * Fp::random(&mut rng)).to_affine();
+        let coeffs = (0..1 << K)
+            .map(|_| Fp::random(&mut rng))
+            .collect::<Vec<_>>();
+        let x = Fp::random(&mut rng);
+        let v = coeffs.iter().rev().fold(Fp::zero(), |acc, c| acc * x + c);
+        let commitment = msm(&coeffs[..], &generators[..]).to_affine();
+
+        let proof = prove(&coeffs[..], x, &generators[..], u_generator);
+
+        let nchip = MockFieldChip::<Fp, Error>::default();
+        let schip = MockFieldChip::<Fp, Error>::default();
+        let pchip = MockEccChip::<G1Affine, Error>::default();
+        let ctx = &mut MockChipCtx::default();
+        let mut transcript =
+            PoseidonTranscriptRead::<_, G1Affine, _, PoseidonEncode, 9usize, 8usize>::new(
+                &proof[..],
+                ctx,
+                &nchip,
+                8usize,
+                33usize,
+            )
+            .unwrap();
+
+        let assigned_commitment = pchip.assign_var(ctx, commitment).unwrap();
+        let assigned_x = schip.assign_var(ctx, x).unwrap();
+        let assigned_v = schip.assign_var(ctx, v).unwrap();
+        let acc = verify_bulletproofs_ipa_in_chip(
+            ctx,
+            &nchip,
+            &schip,
+            &pchip,
+            &mut transcript,
+            &assigned_commitment,
+            &assigned_x,
+            &assigned_v,
+            u_generator,
+            K,
+        )
+        .unwrap();
+        assert!(acc.check_native(&schip, &pchip, &generators[..]).unwrap());
+
+        // a wrong evaluation leaves a non-zero term on the U side
+        let wrong_v = schip.assign_var(ctx, v + Fp::one()).unwrap();
+        let mut transcript =
+            PoseidonTranscriptRead::<_, G1Affine, _, PoseidonEncode, 9usize, 8usize>::new(
+                &proof[..],
+                ctx,
+                &nchip,
+                8usize,
+                33usize,
+            )
+            .unwrap();
+        let acc = verify_bulletproofs_ipa_in_chip(
+            ctx,
+            &nchip,
+            &schip,
+            &pchip,
+            &mut transcript,
+            &assigned_commitment,
+            &assigned_x,
+            &wrong_v,
+            u_generator,
+            K,
+        )
+        .unwrap();
+        assert!(!acc.check_native(&schip, &pchip, &generators[..]).unwrap());
+    }
+}
@@ -1,9 +1,10 @@
 use super::expression::Evaluable;
 use crate::arith::ast::FieldArithHelper;
 use crate::arith::field::ArithFieldChip;
-use crate::systems::halo2::evaluation::EvaluationQuery;
+use crate::systems::halo2::evaluation::{EvaluationQuery, QueryItem};
 use crate::{arith::ecc::ArithEccChip, arith_ast};
 use halo2_proofs::plonk::Expression;
+use std::rc::Rc;
 
 #[derive(Debug)]
 pub struct PermutationCommitments<P> {
@@ -11,6 +12,15 @@ pub struct PermutationCommitments<P> {
     pub(in crate::systems::halo2) permuted_table_commitment: P,
 }
 
+impl<P> PermutationCommitments<P> {
+    pub fn new(permuted_input_commitment: P, permuted_table_commitment: P) -> Self {
+        PermutationCommitments {
+            permuted_input_commitment,
+            permuted_table_commitment,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Committed<P> {
     pub(in crate::systems::halo2) permuted: PermutationCommitments<P>,
@@ -20,8 +30,8 @@ pub struct Committed<P> {
 #[derive(Debug)]
 pub struct Evaluated<A: ArithEccChip> {
     pub(in crate::systems::halo2) key: String,
-    pub(in crate::systems::halo2) input_expressions: Vec<Expression<A::AssignedScalar>>,
-    pub(in crate::systems::halo2) table_expressions: Vec<Expression<A::AssignedScalar>>,
+    pub(in crate::systems::halo2) input_expressions: Rc<Vec<Expression<A::AssignedScalar>>>,
+    pub(in crate::systems::halo2) table_expressions: Rc<Vec<Expression<A::AssignedScalar>>>,
     pub(in crate::systems::halo2) committed: Committed<A::AssignedPoint>,
     pub(in crate::systems::halo2) product_eval: A::AssignedScalar, // X
     pub(in crate::systems::halo2) product_next_eval: A::AssignedScalar, // ωX
@@ -122,6 +132,7 @@ impl<A: ArithEccChip> Evaluated<A> {
         vec![
             EvaluationQuery::new(
                 0,
+                QueryItem::Lookup,
                 format!("{}_product_commitment", self.key),
                 x.clone(),
                 self.committed.product_commitment.clone(),
@@ -129,6 +140,7 @@ impl<A: ArithEccChip> Evaluated<A> {
             ),
             EvaluationQuery::new(
                 0,
+                QueryItem::Lookup,
                 format!("{}_permuted_input_commitment", self.key),
                 x.clone(),
                 self.committed.permuted.permuted_input_commitment.clone(),
@@ -136,6 +148,7 @@ impl<A: ArithEccChip> Evaluated<A> {
             ),
             EvaluationQuery::new(
                 0,
+                QueryItem::Lookup,
                 format!("{}_permuted_table_commitment", self.key),
                 x.clone(),
                 self.committed.permuted.permuted_table_commitment.clone(),
@@ -143,6 +156,7 @@ impl<A: ArithEccChip> Evaluated<A> {
             ),
             EvaluationQuery::new(
                 -1,
+                QueryItem::Lookup,
                 format!("{}_permuted_input_commitment", self.key),
                 x_inv.clone(),
                 self.committed.permuted.permuted_input_commitment.clone(),
@@ -150,6 +164,7 @@ impl<A: ArithEccChip> Evaluated<A> {
             ),
             EvaluationQuery::new(
                 1,
+                QueryItem::Lookup,
                 format!("{}_product_commitment", self.key),
                 x_next.clone(),
                 self.committed.product_commitment.clone(),
@@ -158,3 +173,96 @@ impl<A: ArithEccChip> Evaluated<A> {
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arith::common::ArithCommonChip;
+    use crate::mock::trace::{TracingContext, TracingEccChip, TracingFieldChip};
+    use halo2_proofs::{arithmetic::Field, pairing::bn256::Fr as Fp, plonk::Error, poly::Rotation};
+    use pairing_bn256::bn256::G1Affine;
+
+    type A = TracingEccChip<G1Affine, Error>;
+
+    // One advice column looked up in one fixed column, both evaluated to 5 as the permuted
+    // columns, products of 1: the five expressions vanish. Returns them with the node of
+    // a'(X) - s'(X).
+    fn run(ctx: &mut TracingContext) -> (Vec<Fp>, usize) {
+        let schip = TracingFieldChip::<Fp, Error>::default();
+        let pchip = A::default();
+        let s = |ctx: &mut TracingContext, v: u64| schip.assign_var(ctx, Fp::from(v)).unwrap();
+
+        let (zero, one, v) = (s(ctx, 0), s(ctx, 1), s(ctx, 5));
+        let g = pchip.assign_one(ctx).unwrap();
+        let lookup = Evaluated::<A> {
+            key: "p0_0".to_owned(),
+            input_expressions: Rc::new(vec![Expression::Advice {
+                query_index: 0,
+                column_index: 0,
+                rotation: Rotation::cur(),
+            }]),
+            table_expressions: Rc::new(vec![Expression::Fixed {
+                query_index: 0,
+                column_index: 0,
+                rotation: Rotation::cur(),
+            }]),
+            committed: Committed {
+                permuted: PermutationCommitments {
+                    permuted_input_commitment: g.clone(),
+                    permuted_table_commitment: g.clone(),
+                },
+                product_commitment: g,
+            },
+            product_eval: one.clone(),
+            product_next_eval: one.clone(),
+            permuted_input_eval: v.clone(),
+            permuted_input_inv_eval: s(ctx, 9),
+            permuted_table_eval: v.clone(),
+        };
+
+        let (l_0, l_last, l_blind) = (s(ctx, 2), s(ctx, 3), s(ctx, 4));
+        let (theta, beta, gamma) = (s(ctx, 6), s(ctx, 7), s(ctx, 8));
+        let expressions = lookup
+            .expressions(
+                ctx,
+                &schip,
+                &vec![v.clone()],
+                &vec![],
+                &vec![v.clone()],
+                &l_0,
+                &l_last,
+                &l_blind,
+                &theta,
+                &beta,
+                &gamma,
+                &zero,
+                &one,
+            )
+            .unwrap();
+        let t1 = ctx.find("sub", &[v.id, v.id]).unwrap();
+        (expressions.iter().map(|e| e.v).collect(), t1)
+    }
+
+    #[test]
+    fn test_expressions_vanish_on_consistent_evals() {
+        let ctx = &mut TracingContext::default();
+        let (expressions, _) = run(ctx);
+        assert_eq!(expressions, vec![Fp::zero(); 5]);
+        // the lookup argument never inverts
+        assert!(!ctx.ops().contains(&"div"));
+    }
+
+    #[test]
+    fn test_expressions_see_a_scripted_permuted_difference() {
+        let (_, t1) = run(&mut TracingContext::default());
+        let ctx = &mut TracingContext::default();
+        ctx.script(t1, Fp::one());
+        let (expressions, _) = run(ctx);
+
+        // only the two constraints on a'(X) - s'(X) depend on it
+        assert_eq!(expressions[..3], [Fp::zero(); 3]);
+        assert_eq!(expressions[3], Fp::from(2));
+        let t0 = Fp::one() - (Fp::from(3) + Fp::from(4));
+        assert_eq!(expressions[4], (Fp::from(5) - Fp::from(9)) * t0);
+    }
+}
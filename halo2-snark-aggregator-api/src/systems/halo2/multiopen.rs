@@ -5,7 +5,7 @@ use crate::{
 };
 
 use super::{
-    evaluation::{CommitQuery, EvaluationProof},
+    evaluation::{Accumulator, CommitQuery, EvaluationProof, QueryItem},
     params::VerifierParams,
 };
 
@@ -14,6 +14,28 @@ pub struct MultiOpenProof<A: ArithEccChip> {
     pub w_g: EvaluationQuerySchema<A::AssignedPoint, A::AssignedScalar>,
 }
 
+impl<A: ArithEccChip> From<MultiOpenProof<A>>
+    for Accumulator<EvaluationQuerySchema<A::AssignedPoint, A::AssignedScalar>>
+{
+    fn from(proof: MultiOpenProof<A>) -> Self {
+        Accumulator {
+            lhs: proof.w_x,
+            rhs: proof.w_g,
+        }
+    }
+}
+
+impl<A: ArithEccChip> From<Accumulator<EvaluationQuerySchema<A::AssignedPoint, A::AssignedScalar>>>
+    for MultiOpenProof<A>
+{
+    fn from(acc: Accumulator<EvaluationQuerySchema<A::AssignedPoint, A::AssignedScalar>>) -> Self {
+        MultiOpenProof {
+            w_x: acc.lhs,
+            w_g: acc.rhs,
+        }
+    }
+}
+
 impl<A: ArithEccChip> std::fmt::Display for MultiOpenProof<A> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let nb_points = self.w_x.estimate(None) + self.w_g.estimate(None);
@@ -21,6 +43,48 @@ impl<A: ArithEccChip> std::fmt::Display for MultiOpenProof<A> {
     }
 }
 
+pub struct CostProfile(pub BTreeMap<&'static str, usize>);
+
+impl std::fmt::Display for CostProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let total: usize = self.0.values().sum();
+        writeln!(f, "estimated scalar mult of points: {}", total)?;
+        for (item, cost) in self.0.iter() {
+            writeln!(
+                f,
+                "  {:<12} {:>8} ({:.1}%)",
+                item,
+                cost,
+                *cost as f64 * 100f64 / total.max(1) as f64
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<A: ArithEccChip> MultiOpenProof<A> {
+    pub fn profile(&self) -> CostProfile {
+        let mut profile = BTreeMap::new();
+        self.w_x.estimate_profile(None, &mut profile);
+        self.w_g.estimate_profile(None, &mut profile);
+        CostProfile(profile)
+    }
+
+    // Render w_x and w_g as two clusters of one graphviz digraph.
+    pub fn to_dot(&self) -> String {
+        let mut lines = vec!["digraph multiopen {".to_owned()];
+        for (name, s) in [("w_x", &self.w_x), ("w_g", &self.w_g)] {
+            lines.push(format!("  subgraph cluster_{} {{", name));
+            let label = lines.len();
+            lines.push(String::new());
+            let (_, (cost, _)) = s.dot_nodes(name, &mut 0, &mut lines);
+            lines[label] = format!("    label=\"{} (cost: {})\";", name, cost);
+            lines.push("  }".to_owned());
+        }
+        lines.push("}".to_owned());
+        lines.join("\n")
+    }
+}
 
 impl<A: ArithEccChip> VerifierParams<A> {
     fn get_point_schemas<'a>(
@@ -72,9 +136,10 @@ impl<A: ArithEccChip> VerifierParams<A> {
         let mut w_g = None;
 
         for (i, p) in proofs.into_iter().enumerate() {
-            let s = &p.s;
+            let s = p.s;
             let w = CommitQuery {
-                key: format!("{}_w{}", self.key, i),
+                key: format!("{}_w{}", self.key, i).into(),
+                item: QueryItem::Multiopen,
                 commitment: Some(p.w.clone()),
                 eval: None,
             };
@@ -82,8 +147,10 @@ impl<A: ArithEccChip> VerifierParams<A> {
                 Some(scalar!(self.u) * w_x + commit!(w))
             });
 
-            w_g = w_g.map_or(Some(scalar!(p.point) * commit!(w) + s.clone()), |w_g| {
-                Some(scalar!(self.u) * w_g + scalar!(p.point) * commit!(w) + s.clone())
+            // s is moved in, it holds every query of the rotation
+            w_g = Some(match w_g {
+                None => scalar!(p.point) * commit!(w) + s,
+                Some(w_g) => scalar!(self.u) * w_g + scalar!(p.point) * commit!(w) + s,
             });
         }
 
@@ -1,13 +1,52 @@
 use super::{
-    evaluation::EvaluationQuery, expression::Evaluable, lagrange::LagrangeGenerator, lookup,
-    permutation, vanish,
+    evaluation::{EvaluationQuery, QueryItem},
+    expression::{Evaluable, EvaluationCache},
+    lagrange::LagrangeGenerator,
+    lookup, permutation, vanish,
 };
 use crate::arith::{common::ArithCommonChip, ecc::ArithEccChip, field::ArithFieldChip};
-use halo2_proofs::{arithmetic::FieldExt, plonk::Expression};
+use crate::mock::arith::ecc::MockEccChip;
+use group::{ff::PrimeField, Curve, GroupEncoding};
+use halo2_proofs::{
+    arithmetic::{CurveAffine, FieldExt},
+    plonk::Expression,
+};
 
 pub struct PlonkCommonSetup {
     pub l: u32,
     pub n: u32,
+    pub blinding_factors: u32,
+}
+
+impl PlonkCommonSetup {
+    pub fn new(blinding_factors: u32, n: u32) -> Self {
+        PlonkCommonSetup {
+            l: blinding_factors + 1,
+            n,
+            blinding_factors,
+        }
+    }
+
+    // The rotation of the last usable row, x_last = x * omega^{-(blinding_factors + 1)}
+    pub fn last_rotation(&self) -> i32 {
+        -((self.blinding_factors + 1) as i32)
+    }
+}
+
+pub fn rotate_omega<A: ArithEccChip>(
+    ctx: &mut A::Context,
+    schip: &A::ScalarChip,
+    x: &A::AssignedScalar,
+    omega: A::Scalar,
+    at: i32,
+) -> Result<A::AssignedScalar, A::Error> {
+    let (base, exp) = if at < 0 {
+        (omega.invert().unwrap(), [(-at) as u64, 0, 0, 0])
+    } else {
+        (omega, [at as u64, 0, 0, 0])
+    };
+    let omega_at = base.pow_vartime(exp);
+    schip.sum_with_coeff_and_constant(ctx, vec![(x, omega_at)], A::Scalar::zero())
 }
 
 pub struct VerifierParams<A: ArithEccChip> {
@@ -58,15 +97,8 @@ impl<Scalar: FieldExt, A: ArithEccChip<Scalar = Scalar>> VerifierParams<A> {
         schip: &A::ScalarChip,
         at: i32,
     ) -> Result<A::AssignedScalar, A::Error> {
-        let x = &self.x;
         let omega = schip.to_value(&self.omega)?;
-        let (base, exp) = if at < 0 {
-            (omega.invert().unwrap(), [(-at) as u64, 0, 0, 0])
-        } else {
-            (omega, [at as u64, 0, 0, 0])
-        };
-        let omega_at = base.pow_vartime(exp);
-        schip.sum_with_coeff_and_constant(ctx, vec![(x, omega_at)], A::Scalar::zero())
+        rotate_omega::<A>(ctx, schip, &self.x, omega, at)
     }
 
     pub fn queries(
@@ -75,16 +107,30 @@ impl<Scalar: FieldExt, A: ArithEccChip<Scalar = Scalar>> VerifierParams<A> {
         schip: &A::ScalarChip,
     ) -> Result<Vec<EvaluationQuery<A>>, A::Error> {
         let x = &self.x;
-        let ls = self.get_lagrange_commits(ctx, schip)?;
-        let l_0 = &ls[0];
-        let l_last = &ls[self.common.l as usize];
-        let l_blind = &schip.sum_with_constant(
-            ctx,
-            ls[1..(self.common.l as usize)].iter().collect(),
-            Scalar::zero(),
-        )?;
         let zero = &self.zero;
 
+        // Lagrange evaluations are only consumed by the permutation and lookup arguments,
+        // skip them entirely for circuits that have neither.
+        let has_permutation = self
+            .permutation_evaluated
+            .iter()
+            .any(|permutation| !permutation.sets.is_empty());
+        let has_lookup = self
+            .lookup_evaluated
+            .iter()
+            .any(|lookups| !lookups.is_empty());
+        let lagrange = if has_permutation || has_lookup {
+            let ls = self.get_lagrange_commits(ctx, schip)?;
+            let l_blind = schip.sum_with_constant(
+                ctx,
+                ls[1..(self.common.l as usize)].iter().collect(),
+                Scalar::zero(),
+            )?;
+            Some((ls[0].clone(), ls[self.common.l as usize].clone(), l_blind))
+        } else {
+            None
+        };
+
         let pcommon = permutation::CommonEvaluated {
             key: self.key.clone(),
             permutation_evals: &self.permutation_evals,
@@ -98,10 +144,12 @@ impl<Scalar: FieldExt, A: ArithEccChip<Scalar = Scalar>> VerifierParams<A> {
             let instance_evals = &self.instance_evals[k];
             let permutation = &self.permutation_evaluated[k];
             let lookups = &self.lookup_evaluated[k];
+            // gates of one circuit share sub-expressions, evaluate each of them once per proof
+            let mut cache = EvaluationCache::default();
             for i in 0..self.gates.len() {
                 for j in 0..self.gates[i].len() {
                     let poly = &self.gates[i][j];
-                    expression.push(Evaluable::<A>::chip_evaluate(
+                    let (_, eval) = Evaluable::<A>::chip_evaluate_cached(
                         poly,
                         ctx,
                         schip,
@@ -109,42 +157,48 @@ impl<Scalar: FieldExt, A: ArithEccChip<Scalar = Scalar>> VerifierParams<A> {
                         &|n| advice_evals[n].clone(),
                         &|n| instance_evals[n].clone(),
                         &zero,
-                    )?);
+                        &mut cache,
+                    )?;
+                    expression.push(eval);
                 }
             }
 
-            let mut p = permutation.expressions(
-                ctx,
-                schip,
-                &pcommon,
-                l_0,
-                l_last,
-                l_blind,
-                &self.delta,
-                &self.beta,
-                &self.gamma,
-                x,
-                &self.one,
-            )?;
-            expression.append(&mut p);
+            if let Some((l_0, l_last, l_blind)) = &lagrange {
+                if !permutation.sets.is_empty() {
+                    let mut p = permutation.expressions(
+                        ctx,
+                        schip,
+                        &pcommon,
+                        l_0,
+                        l_last,
+                        l_blind,
+                        &self.delta,
+                        &self.beta,
+                        &self.gamma,
+                        x,
+                        &self.one,
+                    )?;
+                    expression.append(&mut p);
+                }
 
-            for lookup in lookups {
-                let l = lookup.expressions(
-                    ctx,
-                    schip,
-                    &self.fixed_evals,
-                    &instance_evals,
-                    &advice_evals,
-                    l_0,
-                    l_last,
-                    l_blind,
-                    &self.theta,
-                    &self.beta,
-                    &self.gamma,
-                    &self.zero,
-                    &self.one,
-                )?;
-                expression.extend(l);
+                for lookup in lookups {
+                    let l = lookup.expressions(
+                        ctx,
+                        schip,
+                        &self.fixed_evals,
+                        &instance_evals,
+                        &advice_evals,
+                        l_0,
+                        l_last,
+                        l_blind,
+                        &self.theta,
+                        &self.beta,
+                        &self.gamma,
+                        &self.zero,
+                        &self.one,
+                    )?;
+                    expression.extend(l);
+                }
             }
         }
 
@@ -160,6 +214,7 @@ impl<Scalar: FieldExt, A: ArithEccChip<Scalar = Scalar>> VerifierParams<A> {
             for (query_index, &(column, at)) in self.instance_queries.iter().enumerate() {
                 queries.push(EvaluationQuery::new(
                     at,
+                    QueryItem::Instance,
                     format!("{}_instance_commitments{}", self.key, column),
                     self.x_rotate_omega(ctx, schip, at)?,
                     instance_commitments[column].clone(),
@@ -170,6 +225,7 @@ impl<Scalar: FieldExt, A: ArithEccChip<Scalar = Scalar>> VerifierParams<A> {
             for (query_index, &(column, at)) in self.advice_queries.iter().enumerate() {
                 queries.push(EvaluationQuery::new(
                     at,
+                    QueryItem::Advice,
                     format!("{}_advice_commitments{}", self.key, column),
                     self.x_rotate_omega(ctx, schip, at)?,
                     advice_commitments[column].clone(),
@@ -177,18 +233,23 @@ impl<Scalar: FieldExt, A: ArithEccChip<Scalar = Scalar>> VerifierParams<A> {
                 ))
             }
 
-            queries.append(&mut permutation.queries(&self.x_next, &self.x_last));
-            queries.append(
-                &mut lookups
-                    .iter()
-                    .flat_map(move |p| p.queries(x, &self.x_inv, &self.x_next))
-                    .collect(),
-            );
+            if !permutation.sets.is_empty() {
+                queries.append(&mut permutation.queries(&self.x_next, &self.x_last));
+            }
+            if !lookups.is_empty() {
+                queries.append(
+                    &mut lookups
+                        .iter()
+                        .flat_map(move |p| p.queries(x, &self.x_inv, &self.x_next))
+                        .collect(),
+                );
+            }
         }
 
         for (query_index, &(column, at)) in self.fixed_queries.iter().enumerate() {
             queries.push(EvaluationQuery::new(
                 at,
+                QueryItem::Fixed,
                 format!("{}_fixed_commitments{}", self.key, column),
                 self.x_rotate_omega(ctx, schip, at)?,
                 self.fixed_commitments[column].clone(),
@@ -218,3 +279,260 @@ impl<Scalar: FieldExt, A: ArithEccChip<Scalar = Scalar>> VerifierParams<A> {
         Ok(queries)
     }
 }
+
+// Plain copy of the native VerifierParams, points and scalars as the hex of their canonical
+// encodings, so that a failed aggregation can be diffed against a known-good run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct VerifierParamsDump {
+    pub key: String,
+    pub common_l: u32,
+    pub common_n: u32,
+    pub blinding_factors: u32,
+    pub gates: Vec<Vec<String>>,
+    pub lookup_evaluated: Vec<Vec<LookupDump>>,
+    pub permutation_evaluated: Vec<PermutationDump>,
+    pub instance_commitments: Vec<Vec<String>>,
+    pub instance_evals: Vec<Vec<String>>,
+    pub instance_queries: Vec<(usize, i32)>,
+    pub advice_commitments: Vec<Vec<String>>,
+    pub advice_evals: Vec<Vec<String>>,
+    pub advice_queries: Vec<(usize, i32)>,
+    pub fixed_commitments: Vec<String>,
+    pub fixed_evals: Vec<String>,
+    pub fixed_queries: Vec<(usize, i32)>,
+    pub permutation_commitments: Vec<String>,
+    pub permutation_evals: Vec<String>,
+    pub vanish_commitments: Vec<String>,
+    pub random_commitment: String,
+    pub w: Vec<String>,
+    pub random_eval: String,
+    pub beta: String,
+    pub gamma: String,
+    pub theta: String,
+    pub delta: String,
+    pub x: String,
+    pub x_next: String,
+    pub x_last: String,
+    pub x_inv: String,
+    pub xn: String,
+    pub y: String,
+    pub u: String,
+    pub v: String,
+    pub omega: String,
+    pub zero: String,
+    pub one: String,
+    pub n: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct LookupDump {
+    pub key: String,
+    pub permuted_input_commitment: String,
+    pub permuted_table_commitment: String,
+    pub product_commitment: String,
+    pub product_eval: String,
+    pub product_next_eval: String,
+    pub permuted_input_eval: String,
+    pub permuted_input_inv_eval: String,
+    pub permuted_table_eval: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct PermutationDump {
+    pub key: String,
+    pub blinding_factors: usize,
+    pub chunk_len: usize,
+    pub sets: Vec<PermutationSetDump>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct PermutationSetDump {
+    pub permutation_product_commitment: String,
+    pub permutation_product_eval: String,
+    pub permutation_product_next_eval: String,
+    pub permutation_product_last_eval: Option<String>,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl<C: CurveAffine, E> VerifierParams<MockEccChip<C, E>> {
+    fn dump_point(p: &C::CurveExt) -> String {
+        to_hex(p.to_affine().to_bytes().as_ref())
+    }
+
+    fn dump_points(ps: &Vec<C::CurveExt>) -> Vec<String> {
+        ps.iter().map(Self::dump_point).collect()
+    }
+
+    fn dump_scalar(s: &C::ScalarExt) -> String {
+        to_hex(s.to_repr().as_ref())
+    }
+
+    fn dump_scalars(ss: &Vec<C::ScalarExt>) -> Vec<String> {
+        ss.iter().map(Self::dump_scalar).collect()
+    }
+
+    pub fn dump(&self) -> VerifierParamsDump {
+        let lookup_evaluated = self
+            .lookup_evaluated
+            .iter()
+            .map(|lookups| {
+                lookups
+                    .iter()
+                    .map(|lookup| LookupDump {
+                        key: lookup.key.clone(),
+                        permuted_input_commitment: Self::dump_point(
+                            &lookup.committed.permuted.permuted_input_commitment,
+                        ),
+                        permuted_table_commitment: Self::dump_point(
+                            &lookup.committed.permuted.permuted_table_commitment,
+                        ),
+                        product_commitment: Self::dump_point(&lookup.committed.product_commitment),
+                        product_eval: Self::dump_scalar(&lookup.product_eval),
+                        product_next_eval: Self::dump_scalar(&lookup.product_next_eval),
+                        permuted_input_eval: Self::dump_scalar(&lookup.permuted_input_eval),
+                        permuted_input_inv_eval: Self::dump_scalar(&lookup.permuted_input_inv_eval),
+                        permuted_table_eval: Self::dump_scalar(&lookup.permuted_table_eval),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let permutation_evaluated = self
+            .permutation_evaluated
+            .iter()
+            .map(|permutation| PermutationDump {
+                key: permutation.key.clone(),
+                blinding_factors: permutation.blinding_factors,
+                chunk_len: permutation.chunk_len,
+                sets: permutation
+                    .sets
+                    .iter()
+                    .map(|set| PermutationSetDump {
+                        permutation_product_commitment: Self::dump_point(
+                            &set.permutation_product_commitment,
+                        ),
+                        permutation_product_eval: Self::dump_scalar(&set.permutation_product_eval),
+                        permutation_product_next_eval: Self::dump_scalar(
+                            &set.permutation_product_next_eval,
+                        ),
+                        permutation_product_last_eval: set
+                            .permutation_product_last_eval
+                            .as_ref()
+                            .map(Self::dump_scalar),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        VerifierParamsDump {
+            key: self.key.clone(),
+            common_l: self.common.l,
+            common_n: self.common.n,
+            blinding_factors: self.common.blinding_factors,
+            gates: self
+                .gates
+                .iter()
+                .map(|polys| polys.iter().map(|poly| format!("{:?}", poly)).collect())
+                .collect(),
+            lookup_evaluated,
+            permutation_evaluated,
+            instance_commitments: self
+                .instance_commitments
+                .iter()
+                .map(Self::dump_points)
+                .collect(),
+            instance_evals: self.instance_evals.iter().map(Self::dump_scalars).collect(),
+            instance_queries: self.instance_queries.clone(),
+            advice_commitments: self
+                .advice_commitments
+                .iter()
+                .map(Self::dump_points)
+                .collect(),
+            advice_evals: self.advice_evals.iter().map(Self::dump_scalars).collect(),
+            advice_queries: self.advice_queries.clone(),
+            fixed_commitments: Self::dump_points(&self.fixed_commitments),
+            fixed_evals: Self::dump_scalars(&self.fixed_evals),
+            fixed_queries: self.fixed_queries.clone(),
+            permutation_commitments: Self::dump_points(&self.permutation_commitments),
+            permutation_evals: Self::dump_scalars(&self.permutation_evals),
+            vanish_commitments: Self::dump_points(&self.vanish_commitments),
+            random_commitment: Self::dump_point(&self.random_commitment),
+            w: Self::dump_points(&self.w),
+            random_eval: Self::dump_scalar(&self.random_eval),
+            beta: Self::dump_scalar(&self.beta),
+            gamma: Self::dump_scalar(&self.gamma),
+            theta: Self::dump_scalar(&self.theta),
+            delta: Self::dump_scalar(&self.delta),
+            x: Self::dump_scalar(&self.x),
+            x_next: Self::dump_scalar(&self.x_next),
+            x_last: Self::dump_scalar(&self.x_last),
+            x_inv: Self::dump_scalar(&self.x_inv),
+            xn: Self::dump_scalar(&self.xn),
+            y: Self::dump_scalar(&self.y),
+            u: Self::dump_scalar(&self.u),
+            v: Self::dump_scalar(&self.v),
+            omega: Self::dump_scalar(&self.omega),
+            zero: Self::dump_scalar(&self.zero),
+            one: Self::dump_scalar(&self.one),
+            n: Self::dump_scalar(&self.n),
+        }
+    }
+
+    // Dump the whole parsed state as pretty json.
+    #[cfg(feature = "json")]
+    pub fn to_json_string(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.dump())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::arith::{
+        ecc::MockEccChip,
+        field::{MockChipCtx, MockFieldChip},
+    };
+    use halo2_proofs::{
+        arithmetic::Field,
+        pairing::bn256::Fr as Fp,
+        plonk::Error,
+        poly::{EvaluationDomain, Rotation},
+    };
+    use pairing_bn256::bn256::G1Affine;
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    #[test]
+    fn test_x_last_matches_halo2_domain() {
+        let schip = MockFieldChip::<Fp, Error>::default();
+        let ctx = &mut MockChipCtx::default();
+        let rng = XorShiftRng::seed_from_u64(0);
+        let x = Fp::random(rng);
+
+        for k in [4u32, 10] {
+            let domain = EvaluationDomain::<Fp>::new(3, k);
+            for blinding_factors in [0u32, 1, 5] {
+                let common = PlonkCommonSetup::new(blinding_factors, 1 << k);
+                assert_eq!(common.l, blinding_factors + 1);
+
+                let x_last = rotate_omega::<MockEccChip<G1Affine, Error>>(
+                    ctx,
+                    &schip,
+                    &x,
+                    domain.get_omega(),
+                    common.last_rotation(),
+                )
+                .unwrap();
+                let expected = domain.rotate_omega(x, Rotation(-((blinding_factors + 1) as i32)));
+                assert_eq!(x_last, expected);
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::iter;
 
-use super::evaluation::EvaluationQuery;
+use super::evaluation::{EvaluationQuery, QueryItem};
 use crate::arith::ast::FieldArithHelper;
 use crate::arith::field::ArithFieldChip;
 use crate::{arith::ecc::ArithEccChip, arith_ast};
@@ -40,6 +40,7 @@ impl<'a, A: ArithEccChip> CommonEvaluated<'a, A> {
             .map(|(i, (commitment, eval))| {
                 EvaluationQuery::new(
                     0,
+                    QueryItem::Permutation,
                     format!("{}_permutation_commitments{}", self.key, i),
                     x.clone(),
                     commitment.clone(),
@@ -65,6 +66,11 @@ impl<A: ArithEccChip> Evaluated<A> {
         x: &A::AssignedScalar,
         one: &A::AssignedScalar,
     ) -> Result<Vec<A::AssignedScalar>, A::Error> {
+        // No equality constraint, nothing to enforce.
+        if self.sets.is_empty() {
+            return Ok(vec![]);
+        }
+
         let mut res = vec![];
 
         //let left = arith_ast!(z_wx * (a_x + beta) * (s_x + gamma));
@@ -147,6 +153,7 @@ impl<A: ArithEccChip> Evaluated<A> {
                     // Open permutation product commitments at x and \omega x
                     .chain(Some(EvaluationQuery::new(
                         0,
+                        QueryItem::Permutation,
                         format!("{}_permutation_product_commitment_{}", self.key, i),
                         self.x.clone(),
                         set.permutation_product_commitment.clone(),
@@ -154,6 +161,7 @@ impl<A: ArithEccChip> Evaluated<A> {
                     )))
                     .chain(Some(EvaluationQuery::new(
                         1,
+                        QueryItem::Permutation,
                         format!("{}_permutation_product_commitment_{}", self.key, i),
                         x_next.clone(),
                         set.permutation_product_commitment.clone(),
@@ -170,6 +178,7 @@ impl<A: ArithEccChip> Evaluated<A> {
                     .flat_map(|(i, set)| {
                         Some(EvaluationQuery::new(
                             -((self.blinding_factors + 1) as i32),
+                            QueryItem::Permutation,
                             format!("{}_permutation_product_commitment_{}", self.key, i),
                             x_last.clone(),
                             set.permutation_product_commitment.clone(),
@@ -180,3 +189,90 @@ impl<A: ArithEccChip> Evaluated<A> {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arith::common::ArithCommonChip;
+    use crate::mock::trace::{TracingContext, TracingEccChip, TracingFieldChip};
+    use halo2_proofs::{arithmetic::Field, pairing::bn256::Fr as Fp, plonk::Error};
+    use pairing_bn256::bn256::G1Affine;
+
+    type A = TracingEccChip<G1Affine, Error>;
+
+    const V: u64 = 5;
+    const P: u64 = 11;
+    const X: u64 = 13;
+    const BETA: u64 = 7;
+    const GAMMA: u64 = 8;
+
+    // z(omega X) of a single set of one column, z(X) = 1, that satisfies the product constraint
+    fn z_next() -> Fp {
+        let (v, gamma, beta) = (Fp::from(V), Fp::from(GAMMA), Fp::from(BETA));
+        (v + beta * Fp::from(X) + gamma) * (v + beta * Fp::from(P) + gamma).invert().unwrap()
+    }
+
+    // A single set of a single column evaluated to V, permutation eval P. Returns the
+    // expressions with the node of beta * x.
+    fn run(ctx: &mut TracingContext) -> (Vec<Fp>, usize) {
+        let schip = TracingFieldChip::<Fp, Error>::default();
+        let pchip = A::default();
+        let s = |ctx: &mut TracingContext, v: u64| schip.assign_var(ctx, Fp::from(v)).unwrap();
+
+        let one = s(ctx, 1);
+        let x = s(ctx, X);
+        let g = pchip.assign_one(ctx).unwrap();
+        let permutation = Evaluated::<A> {
+            key: "p0".to_owned(),
+            blinding_factors: 5,
+            x: x.clone(),
+            sets: vec![EvaluatedSet {
+                permutation_product_commitment: g.clone(),
+                permutation_product_eval: one.clone(),
+                permutation_product_next_eval: schip.assign_var(ctx, z_next()).unwrap(),
+                permutation_product_last_eval: None,
+            }],
+            evals: vec![s(ctx, V)],
+            chunk_len: 1,
+        };
+        let permutation_evals = vec![s(ctx, P)];
+        let permutation_commitments = vec![g];
+        let common = CommonEvaluated {
+            key: "p0".to_owned(),
+            permutation_evals: &permutation_evals,
+            permutation_commitments: &permutation_commitments,
+        };
+
+        let (l_0, l_last, l_blind) = (s(ctx, 2), s(ctx, 3), s(ctx, 4));
+        let (delta, beta, gamma) = (s(ctx, 6), s(ctx, BETA), s(ctx, GAMMA));
+        let expressions = permutation
+            .expressions(
+                ctx, &schip, &common, &l_0, &l_last, &l_blind, &delta, &beta, &gamma, &x, &one,
+            )
+            .unwrap();
+        let beta_x = ctx.find("mul", &[beta.id, x.id]).unwrap();
+        (expressions.iter().map(|e| e.v).collect(), beta_x)
+    }
+
+    #[test]
+    fn test_expressions_vanish_on_consistent_evals() {
+        let (expressions, _) = run(&mut TracingContext::default());
+        // first set, last set and the product of the only chunk
+        assert_eq!(expressions, vec![Fp::zero(); 3]);
+    }
+
+    #[test]
+    fn test_expressions_see_a_scripted_identity_term() {
+        let (_, beta_x) = run(&mut TracingContext::default());
+        let ctx = &mut TracingContext::default();
+        ctx.script(beta_x, Fp::from(100));
+        let (expressions, _) = run(ctx);
+
+        let (v, gamma, beta) = (Fp::from(V), Fp::from(GAMMA), Fp::from(BETA));
+        let left = (v + gamma + beta * Fp::from(P)) * z_next();
+        let right = v + gamma + Fp::from(100);
+        let t1 = Fp::one() - (Fp::from(3) + Fp::from(4));
+        assert_eq!(expressions[..2], [Fp::zero(); 2]);
+        assert_eq!(expressions[2], (left - right) * t1);
+    }
+}
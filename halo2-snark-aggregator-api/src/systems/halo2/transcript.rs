@@ -5,7 +5,76 @@ use crate::{
 };
 use group::ff::PrimeField;
 use halo2_proofs::{arithmetic::CurveAffine, plonk::Error};
-use std::{io, marker::PhantomData};
+use std::{fmt, io, marker::PhantomData};
+
+use rayon::prelude::*;
+
+// A read the proof could not satisfy, with the bytes of the proof consumed before it and the
+// items the verifier expected there.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptError {
+    Eof { expected: String, offset: usize },
+    InvalidPoint { expected: String, offset: usize },
+    InvalidScalar { expected: String, offset: usize },
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranscriptError::Eof { expected, offset } => {
+                write!(f, "expected {}, got EOF at byte {}", expected, offset)
+            }
+            TranscriptError::InvalidPoint { expected, offset } => write!(
+                f,
+                "expected {}, got an invalid point encoding at byte {}",
+                expected, offset
+            ),
+            TranscriptError::InvalidScalar { expected, offset } => write!(
+                f,
+                "expected {}, got an invalid field element encoding at byte {}",
+                expected, offset
+            ),
+        }
+    }
+}
+
+impl From<TranscriptError> for Error {
+    fn from(e: TranscriptError) -> Self {
+        let kind = match e {
+            TranscriptError::Eof { .. } => io::ErrorKind::UnexpectedEof,
+            _ => io::ErrorKind::InvalidData,
+        };
+        Error::Transcript(io::Error::new(kind, e.to_string()))
+    }
+}
+
+// Decompresses the points of a batch in parallel, each takes a square root.
+// Fails with the index of the first invalid point in transcript order, as one by one reads do.
+fn decode_points<C: CurveAffine>(reprs: &[C::Repr]) -> Result<Vec<C>, usize> {
+    let points = reprs
+        .par_iter()
+        .map(|repr| Option::from(C::from_bytes(repr)))
+        .collect::<Vec<Option<C>>>();
+
+    points
+        .into_iter()
+        .enumerate()
+        .map(|(i, p)| p.ok_or(i))
+        .collect()
+}
+
+fn decode_scalars<F: PrimeField>(reprs: &[F::Repr]) -> Result<Vec<F>, usize> {
+    let scalars = reprs
+        .par_iter()
+        .map(|repr| Option::from(F::from_repr(*repr)))
+        .collect::<Vec<Option<F>>>();
+
+    scalars
+        .into_iter()
+        .enumerate()
+        .map(|(i, s)| s.ok_or(i))
+        .collect()
+}
 
 pub struct PoseidonTranscriptRead<
     R: io::Read,
@@ -17,6 +86,10 @@ pub struct PoseidonTranscriptRead<
 > {
     hash: PoseidonChip<A::NativeChip, T, RATE>,
     reader: R,
+    // bytes read so far
+    offset: usize,
+    // the items of the reads to come, see TranscriptRead::expect
+    expected: Option<String>,
     _phantom: PhantomData<E>,
 }
 
@@ -39,9 +112,58 @@ impl<
         Ok(PoseidonTranscriptRead {
             hash: PoseidonChip::new(ctx, schip, r_f, r_p)?,
             reader,
+            offset: 0,
+            expected: None,
             _phantom: PhantomData,
         })
     }
+
+    fn expected(&self, item: &str) -> String {
+        self.expected.clone().unwrap_or_else(|| item.to_owned())
+    }
+
+    // Reads the encodings of n items of size bytes, returns the offset of the first one.
+    fn read_reprs<B: AsMut<[u8]>>(&mut self, reprs: &mut [B], item: &str) -> Result<usize, Error> {
+        let start = self.offset;
+        for repr in reprs.iter_mut() {
+            match self.reader.read_exact(repr.as_mut()) {
+                Ok(()) => self.offset += repr.as_mut().len(),
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(TranscriptError::Eof {
+                        expected: self.expected(item),
+                        offset: self.offset,
+                    }
+                    .into())
+                }
+                Err(e) => return Err(Error::Transcript(e)),
+            }
+        }
+        Ok(start)
+    }
+
+    fn read_point_repr(&mut self) -> Result<C, Error> {
+        let mut compressed = [C::Repr::default()];
+        let offset = self.read_reprs(&mut compressed, "a point")?;
+        Option::from(C::from_bytes(&compressed[0])).ok_or_else(|| {
+            TranscriptError::InvalidPoint {
+                expected: self.expected("a point"),
+                offset,
+            }
+            .into()
+        })
+    }
+
+    fn read_scalar_repr(&mut self) -> Result<C::Scalar, Error> {
+        let mut data = [<C::Scalar as PrimeField>::Repr::default()];
+        let offset = self.read_reprs(&mut data, "a scalar")?;
+        Option::from(C::Scalar::from_repr(data[0])).ok_or_else(|| {
+            TranscriptError::InvalidScalar {
+                expected: self.expected("a scalar"),
+                offset,
+            }
+            .into()
+        })
+    }
 }
 
 impl<
@@ -53,6 +175,10 @@ impl<
         const RATE: usize,
     > TranscriptRead<A> for PoseidonTranscriptRead<R, C, A, E, T, RATE>
 {
+    fn expect(&mut self, items: String) {
+        self.expected = Some(items);
+    }
+
     fn read_point(
         &mut self,
         ctx: &mut A::Context,
@@ -60,14 +186,7 @@ impl<
         schip: &A::ScalarChip,
         pchip: &A,
     ) -> Result<A::AssignedPoint, A::Error> {
-        let mut compressed = C::Repr::default();
-        self.reader.read_exact(compressed.as_mut())?;
-        let point: C = Option::from(C::from_bytes(&compressed)).ok_or_else(|| {
-            A::Error::Transcript(io::Error::new(
-                io::ErrorKind::Other,
-                "invalid point encoding in proof",
-            ))
-        })?;
+        let point = self.read_point_repr()?;
         let assigned_point = pchip.assign_var(ctx, point)?;
 
         self.common_point(ctx, nchip, schip, pchip, &assigned_point)?;
@@ -75,6 +194,60 @@ impl<
         Ok(assigned_point)
     }
 
+    // Reads the encodings of the batch first and decodes them off the context, the context
+    // only sees the assignments and the transcript updates, in the order of read_point.
+    // The limb decomposition of a non native point is done by assign_var, on the context.
+    fn read_n_points(
+        &mut self,
+        ctx: &mut A::Context,
+        nchip: &A::NativeChip,
+        schip: &A::ScalarChip,
+        pchip: &A,
+        n: usize,
+    ) -> Result<Vec<A::AssignedPoint>, A::Error> {
+        let mut reprs = vec![C::Repr::default(); n];
+        let start = self.read_reprs(&mut reprs, "points")?;
+
+        let size = C::Repr::default().as_ref().len();
+        decode_points::<C>(&reprs)
+            .map_err(|i| TranscriptError::InvalidPoint {
+                expected: self.expected("points"),
+                offset: start + i * size,
+            })?
+            .into_iter()
+            .map(|point| {
+                let assigned_point = pchip.assign_var(ctx, point)?;
+                self.common_point(ctx, nchip, schip, pchip, &assigned_point)?;
+                Ok(assigned_point)
+            })
+            .collect()
+    }
+
+    fn read_n_scalars(
+        &mut self,
+        ctx: &mut A::Context,
+        nchip: &A::NativeChip,
+        schip: &A::ScalarChip,
+        n: usize,
+    ) -> Result<Vec<A::AssignedScalar>, A::Error> {
+        let mut reprs = vec![<C::Scalar as PrimeField>::Repr::default(); n];
+        let start = self.read_reprs(&mut reprs, "scalars")?;
+
+        let size = <C::Scalar as PrimeField>::Repr::default().as_ref().len();
+        decode_scalars::<C::Scalar>(&reprs)
+            .map_err(|i| TranscriptError::InvalidScalar {
+                expected: self.expected("scalars"),
+                offset: start + i * size,
+            })?
+            .into_iter()
+            .map(|scalar| {
+                let assigned_scalar = schip.assign_var(ctx, scalar)?;
+                self.common_scalar(ctx, nchip, schip, &assigned_scalar)?;
+                Ok(assigned_scalar)
+            })
+            .collect()
+    }
+
     fn read_constant_point(
         &mut self,
         ctx: &mut A::Context,
@@ -82,14 +255,7 @@ impl<
         schip: &A::ScalarChip,
         pchip: &A,
     ) -> Result<A::AssignedPoint, A::Error> {
-        let mut compressed = C::Repr::default();
-        self.reader.read_exact(compressed.as_mut())?;
-        let point: C = Option::from(C::from_bytes(&compressed)).ok_or_else(|| {
-            Error::Transcript(io::Error::new(
-                io::ErrorKind::Other,
-                "invalid point encoding in proof",
-            ))
-        })?;
+        let point = self.read_point_repr()?;
         let assigned_point = pchip.assign_const(ctx, point)?;
 
         self.common_point(ctx, nchip, schip, pchip, &assigned_point)?;
@@ -103,14 +269,7 @@ impl<
         nchip: &A::NativeChip,
         schip: &A::ScalarChip,
     ) -> Result<A::AssignedScalar, A::Error> {
-        let mut data = <C::Scalar as PrimeField>::Repr::default();
-        self.reader.read_exact(data.as_mut())?;
-        let scalar: C::Scalar = Option::from(C::Scalar::from_repr(data)).ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                "invalid field element encoding in proof",
-            )
-        })?;
+        let scalar = self.read_scalar_repr()?;
         let assigned_scalar = schip.assign_var(ctx, scalar)?;
 
         self.common_scalar(ctx, nchip, schip, &assigned_scalar)?;
@@ -124,14 +283,7 @@ impl<
         nchip: &A::NativeChip,
         schip: &A::ScalarChip,
     ) -> Result<A::AssignedScalar, A::Error> {
-        let mut data = <C::Scalar as PrimeField>::Repr::default();
-        self.reader.read_exact(data.as_mut())?;
-        let scalar: C::Scalar = Option::from(C::Scalar::from_repr(data)).ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                "invalid field element encoding in proof",
-            )
-        })?;
+        let scalar = self.read_scalar_repr()?;
         let assigned_scalar = schip.assign_const(ctx, scalar)?;
 
         self.common_scalar(ctx, nchip, schip, &assigned_scalar)?;
@@ -177,3 +329,135 @@ impl<
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{
+        arith::{
+            ecc::MockEccChip,
+            field::{MockChipCtx, MockFieldChip},
+        },
+        transcript_encode::PoseidonEncode,
+    };
+    use group::{Curve, Group};
+    use halo2_proofs::{
+        arithmetic::Field,
+        pairing::bn256::{Fr, G1Affine, G1},
+        transcript::{Challenge255, PoseidonWrite, TranscriptWrite},
+    };
+    use rand::rngs::OsRng;
+
+    type Transcript<'a> = PoseidonTranscriptRead<
+        &'a [u8],
+        G1Affine,
+        MockEccChip<G1Affine, Error>,
+        PoseidonEncode,
+        9,
+        8,
+    >;
+
+    fn proof(n: usize) -> Vec<u8> {
+        let mut writer = PoseidonWrite::<_, _, Challenge255<_>>::init(vec![]);
+        for _ in 0..n {
+            writer.write_point(G1::random(OsRng).to_affine()).unwrap();
+        }
+        for _ in 0..n {
+            writer.write_scalar(Fr::random(OsRng)).unwrap();
+        }
+        writer.finalize()
+    }
+
+    #[test]
+    fn test_batch_reads_match_single_reads() {
+        let n = 17;
+        let proof = proof(n);
+        let nchip = MockFieldChip::<Fr, Error>::default();
+        let schip = MockFieldChip::<Fr, Error>::default();
+        let pchip = MockEccChip::<G1Affine, Error>::default();
+        let ctx = &mut MockChipCtx::default();
+
+        let mut single = Transcript::new(&proof[..], ctx, &nchip, 8, 33).unwrap();
+        let points = (0..n)
+            .map(|_| single.read_point(ctx, &nchip, &schip, &pchip))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let scalars = (0..n)
+            .map(|_| single.read_scalar(ctx, &nchip, &schip))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let mut batch = Transcript::new(&proof[..], ctx, &nchip, 8, 33).unwrap();
+        assert_eq!(
+            batch.read_n_points(ctx, &nchip, &schip, &pchip, n).unwrap(),
+            points
+        );
+        assert_eq!(
+            batch.read_n_scalars(ctx, &nchip, &schip, n).unwrap(),
+            scalars
+        );
+
+        // the transcript absorbed the same values, in the same order
+        assert_eq!(
+            single
+                .squeeze_challenge_scalar(ctx, &nchip, &schip)
+                .unwrap(),
+            batch.squeeze_challenge_scalar(ctx, &nchip, &schip).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_batch_read_rejects_invalid_point() {
+        let mut proof = proof(4);
+        // an x of the second point above the modulus
+        proof[32..64].iter_mut().for_each(|b| *b = 0xff);
+        let nchip = MockFieldChip::<Fr, Error>::default();
+        let schip = MockFieldChip::<Fr, Error>::default();
+        let pchip = MockEccChip::<G1Affine, Error>::default();
+        let ctx = &mut MockChipCtx::default();
+
+        let mut batch = Transcript::new(&proof[..], ctx, &nchip, 8, 33).unwrap();
+        assert!(matches!(
+            batch.read_n_points(ctx, &nchip, &schip, &pchip, 4),
+            Err(Error::Transcript(_))
+        ));
+
+        let mut batch = Transcript::new(&proof[..], ctx, &nchip, 8, 33).unwrap();
+        batch.expect("4 advice commitments for proof 0".to_owned());
+        match batch.read_n_points(ctx, &nchip, &schip, &pchip, 4) {
+            Err(Error::Transcript(e)) => assert_eq!(
+                e.to_string(),
+                "expected 4 advice commitments for proof 0, got an invalid point encoding at \
+                 byte 32"
+            ),
+            _ => panic!("the invalid point is read"),
+        }
+    }
+
+    #[test]
+    fn test_read_reports_eof_offset() {
+        let proof = proof(2);
+        let nchip = MockFieldChip::<Fr, Error>::default();
+        let schip = MockFieldChip::<Fr, Error>::default();
+        let pchip = MockEccChip::<G1Affine, Error>::default();
+        let ctx = &mut MockChipCtx::default();
+
+        // the second scalar is cut after 5 bytes
+        let truncated = &proof[..3 * 32 + 5];
+        let mut transcript = Transcript::new(truncated, ctx, &nchip, 8, 33).unwrap();
+        transcript
+            .read_n_points(ctx, &nchip, &schip, &pchip, 2)
+            .unwrap();
+        transcript.expect("2 instance evals for proof 1".to_owned());
+        match transcript.read_n_scalars(ctx, &nchip, &schip, 2) {
+            Err(Error::Transcript(e)) => {
+                assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+                assert_eq!(
+                    e.to_string(),
+                    "expected 2 instance evals for proof 1, got EOF at byte 96"
+                );
+            }
+            _ => panic!("the proof is truncated"),
+        }
+    }
+}
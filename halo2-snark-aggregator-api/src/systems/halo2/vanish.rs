@@ -4,7 +4,7 @@ use crate::{
     arith_ast,
 };
 
-use super::evaluation::{CommitQuery, EvaluationQuery, EvaluationQuerySchema};
+use super::evaluation::{CommitQuery, EvaluationQuery, EvaluationQuerySchema, QueryItem};
 
 pub struct Evaluated<'a, A: ArithEccChip> {
     key: String,
@@ -36,7 +36,8 @@ impl<'a, A: ArithEccChip> Evaluated<'a, A> {
             .enumerate()
             .map(|(i, c)| {
                 EvaluationQuerySchema::Commitment(CommitQuery {
-                    key: format!("{}_h_commitment{}", key.clone(), i),
+                    key: format!("{}_h_commitment{}", key, i).into(),
+                    item: QueryItem::Vanish,
                     commitment: Some(c.clone()),
                     eval: None as Option<A::AssignedScalar>,
                 })
@@ -53,16 +54,17 @@ impl<'a, A: ArithEccChip> Evaluated<'a, A> {
         })
     }
 
-    pub fn queries(&self, x: &A::AssignedScalar) -> Vec<EvaluationQuery<A>> {
+    // Consumes the evaluated argument, h_commitment is moved into its query.
+    pub fn queries(self, x: &A::AssignedScalar) -> Vec<EvaluationQuery<A>> {
         vec![
             EvaluationQuery::new_from_query(
                 0,
                 x.clone(),
-                self.h_commitment.clone()
-                    + EvaluationQuerySchema::Scalar(self.expected_h_eval.clone()),
+                self.h_commitment + EvaluationQuerySchema::Scalar(self.expected_h_eval),
             ),
             EvaluationQuery::new(
                 0,
+                QueryItem::Vanish,
                 format!("{}_random_commitment", self.key),
                 x.clone(),
                 self.random_commitment.clone(),
@@ -71,3 +73,74 @@ impl<'a, A: ArithEccChip> Evaluated<'a, A> {
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arith::common::ArithCommonChip;
+    use crate::mock::trace::{TracingContext, TracingEccChip, TracingFieldChip};
+    use group::{Curve, Group};
+    use halo2_proofs::{arithmetic::Field, pairing::bn256::Fr as Fp, plonk::Error};
+    use pairing_bn256::bn256::{G1Affine, G1};
+
+    type A = TracingEccChip<G1Affine, Error>;
+
+    // h(X) split in the pieces g and 2g, expressions 3 and 4 folded by y = 5 and divided by
+    // xn - 1 = 6. Returns the opened point, the expected h eval and the node of xn - 1.
+    fn run(ctx: &mut TracingContext) -> (G1, Fp, usize) {
+        let schip = TracingFieldChip::<Fp, Error>::default();
+        let pchip = A::default();
+        let s = |ctx: &mut TracingContext, v: u64| schip.assign_var(ctx, Fp::from(v)).unwrap();
+
+        let (one, y, xn, x) = (s(ctx, 1), s(ctx, 5), s(ctx, 7), s(ctx, 9));
+        let expressions = vec![s(ctx, 3), s(ctx, 4)];
+        let h = vec![
+            pchip.assign_var(ctx, G1::generator().to_affine()).unwrap(),
+            pchip
+                .assign_var(ctx, (G1::generator() * Fp::from(2)).to_affine())
+                .unwrap(),
+        ];
+        let random_commitment = pchip.assign_one(ctx).unwrap();
+        let random_eval = s(ctx, 0);
+
+        let evaluated = Evaluated::<A>::new(
+            ctx,
+            &schip,
+            expressions,
+            &y,
+            &xn,
+            &random_commitment,
+            &random_eval,
+            &h,
+            &one,
+            "p0".to_owned(),
+        )
+        .unwrap();
+        let xn_minus_one = ctx.find("sub", &[xn.id, one.id]).unwrap();
+        let expected_h_eval = evaluated.expected_h_eval.v;
+
+        let query = evaluated.queries(&x).remove(0);
+        let (point, _) = query.s.eval::<_, A>(ctx, &schip, &pchip, &one).unwrap();
+        (point.v, expected_h_eval, xn_minus_one)
+    }
+
+    #[test]
+    fn test_evaluated_folds_h_and_expressions() {
+        let (point, expected_h_eval, _) = run(&mut TracingContext::default());
+        // h_0 + xn * h_1
+        assert_eq!(point, G1::generator() * Fp::from(1 + 7 * 2));
+        let fold = Fp::from(3) * Fp::from(5) + Fp::from(4);
+        assert_eq!(expected_h_eval, fold * Fp::from(6).invert().unwrap());
+    }
+
+    #[test]
+    fn test_evaluated_divides_by_a_scripted_vanishing_eval() {
+        let (_, _, xn_minus_one) = run(&mut TracingContext::default());
+        let ctx = &mut TracingContext::default();
+        ctx.script(xn_minus_one, Fp::from(2));
+        let (_, expected_h_eval, _) = run(ctx);
+
+        let fold = Fp::from(3) * Fp::from(5) + Fp::from(4);
+        assert_eq!(expected_h_eval, fold * Fp::from(2).invert().unwrap());
+    }
+}
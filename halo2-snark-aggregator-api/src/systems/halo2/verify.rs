@@ -1,12 +1,12 @@
-use super::evaluation::EvaluationQuerySchema;
+use super::evaluation::Accumulator;
+use super::instance::{validate_instances, InstanceError};
 use super::multiopen::MultiOpenProof;
-use super::params::{PlonkCommonSetup, VerifierParams};
+use super::params::{self, PlonkCommonSetup, VerifierParams};
 use super::{
     lookup::{self, PermutationCommitments},
     permutation,
 };
 use crate::arith::{common::ArithCommonChip, ecc::ArithEccChip, field::ArithFieldChip};
-use crate::scalar;
 use crate::transcript::read::TranscriptRead;
 use group::prime::PrimeCurveAffine;
 use halo2_proofs::arithmetic::{Field, FieldExt};
@@ -16,14 +16,94 @@ use halo2_proofs::{
     plonk::{Expression, VerifyingKey},
     poly::commitment::ParamsVerifier,
 };
+use std::fmt::Display;
 use std::marker::PhantomData;
+use std::rc::Rc;
 use std::vec;
 
+// Stages of VerifierParamsBuilder::build_params, a front-end with the values of a stage at
+// hand, e.g. precomputed witnesses, builds the struct itself and skips the stage with
+// VerifierParamsBuilder::with_stage.
+
+// Commitments of the proofs, outer vectors by proof, with the challenges squeezed between
+// them.
+pub struct ProofCommitments<A: ArithEccChip> {
+    pub advice_commitments: Vec<Vec<A::AssignedPoint>>,
+    pub theta: A::AssignedScalar,
+    pub lookups_permuted: Vec<Vec<PermutationCommitments<A::AssignedPoint>>>,
+    pub beta: A::AssignedScalar,
+    pub gamma: A::AssignedScalar,
+    pub permutations_committed: Vec<Vec<A::AssignedPoint>>,
+    pub lookups_committed: Vec<Vec<A::AssignedPoint>>,
+    pub random_commitment: A::AssignedPoint,
+    pub y: A::AssignedScalar,
+    pub h_commitments: Vec<A::AssignedPoint>,
+}
+
+// x and its rotations.
+pub struct EvaluationPoint<A: ArithEccChip> {
+    pub x: A::AssignedScalar,
+    pub x_next: A::AssignedScalar,
+    pub x_last: A::AssignedScalar,
+    pub x_inv: A::AssignedScalar,
+    pub xn: A::AssignedScalar,
+}
+
+// Evals of a permutation product at x, x_next and, but for the last product, x_last.
+pub struct PermutationProductEvals<A: ArithEccChip> {
+    pub eval: A::AssignedScalar,
+    pub next_eval: A::AssignedScalar,
+    pub last_eval: Option<A::AssignedScalar>,
+}
+
+pub struct LookupEvals<A: ArithEccChip> {
+    pub product_eval: A::AssignedScalar,
+    pub product_next_eval: A::AssignedScalar,
+    pub permuted_input_eval: A::AssignedScalar,
+    pub permuted_input_inv_eval: A::AssignedScalar,
+    pub permuted_table_eval: A::AssignedScalar,
+}
+
+// Evals of the proofs, outer vectors by proof, and the multiopen challenges and points read
+// after them.
+pub struct ProofEvals<A: ArithEccChip> {
+    pub instance_evals: Vec<Vec<A::AssignedScalar>>,
+    pub advice_evals: Vec<Vec<A::AssignedScalar>>,
+    pub fixed_evals: Vec<A::AssignedScalar>,
+    pub random_eval: A::AssignedScalar,
+    pub permutation_evals: Vec<A::AssignedScalar>,
+    pub permutation_product_evals: Vec<Vec<PermutationProductEvals<A>>>,
+    pub lookup_evals: Vec<Vec<LookupEvals<A>>>,
+    pub v: A::AssignedScalar,
+    pub u: A::AssignedScalar,
+    pub w: Vec<A::AssignedPoint>,
+}
+
+// States of VerifierParamsBuilder: each stage consumes the builder and returns it in the next
+// state with the values read so far, so the stages run once and in transcript order.
+pub struct Start;
+
+pub struct Committed<A: ArithEccChip> {
+    pub commitments: ProofCommitments<A>,
+}
+
+pub struct Challenged<A: ArithEccChip> {
+    pub commitments: ProofCommitments<A>,
+    pub point: EvaluationPoint<A>,
+}
+
+pub struct Evaluated<A: ArithEccChip> {
+    pub commitments: ProofCommitments<A>,
+    pub point: EvaluationPoint<A>,
+    pub evals: ProofEvals<A>,
+}
+
 pub struct VerifierParamsBuilder<
     'a,
     E: MultiMillerLoop,
     A: ArithEccChip<Point = E::G1Affine>,
     T: TranscriptRead<A>,
+    S = Start,
 > {
     ctx: &'a mut A::Context,
     nchip: &'a A::NativeChip,
@@ -34,6 +114,7 @@ pub struct VerifierParamsBuilder<
     params: &'a ParamsVerifier<E>,
     transcript: &'a mut T,
     key: String,
+    stage: S,
 }
 
 // Follow the sequence of official halo2
@@ -46,8 +127,185 @@ impl<
             Native = <E::G1Affine as CurveAffine>::ScalarExt,
         >,
         T: TranscriptRead<A>,
-    > VerifierParamsBuilder<'a, E, A, T>
+    > VerifierParamsBuilder<'a, E, A, T, Start>
+{
+    pub fn new(
+        ctx: &'a mut A::Context,
+        nchip: &'a A::NativeChip,
+        schip: &'a A::ScalarChip,
+        pchip: &'a A,
+        assigned_instances: Vec<Vec<A::AssignedPoint>>,
+        vk: &'a VerifyingKey<E::G1Affine>,
+        params: &'a ParamsVerifier<E>,
+        transcript: &'a mut T,
+        key: String,
+    ) -> Self {
+        VerifierParamsBuilder {
+            ctx,
+            nchip,
+            schip,
+            pchip,
+            assigned_instances,
+            vk,
+            params,
+            transcript,
+            key,
+            stage: Start,
+        }
+    }
+
+    // Stage 1: hashes the vk and the instance commitments, then reads the commitments of the
+    // proofs up to the quotient pieces. theta, beta, gamma and y are squeezed between them as
+    // halo2 does, the transcript fixes that order.
+    pub fn read_commitments(
+        mut self,
+    ) -> Result<VerifierParamsBuilder<'a, E, A, T, Committed<A>>, A::Error> {
+        self.init_transcript()?;
+        self.squeeze_instance_commitment()?;
+
+        let num_proofs = self.assigned_instances.len();
+
+        let advice_commitments = self.load_n_m_points(
+            num_proofs,
+            self.vk.cs.num_advice_columns,
+            "advice commitments",
+        )?;
+
+        let theta = self.squeeze_challenge_scalar()?;
+
+        let lookups_permuted = (0..num_proofs)
+            .map(|i| {
+                (0..self.vk.cs.lookups.len())
+                    .map(|j| {
+                        self.expect(format!(
+                            "the permuted commitments of lookup {} for proof {}",
+                            j, i
+                        ));
+                        let permuted_input_commitment = self.load_point()?;
+                        let permuted_table_commitment = self.load_point()?;
+
+                        Ok(PermutationCommitments {
+                            permuted_input_commitment,
+                            permuted_table_commitment,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<Vec<_>>, _>>()?;
+
+        let beta = self.squeeze_challenge_scalar()?;
+        let gamma = self.squeeze_challenge_scalar()?;
+
+        let permutations_committed = self.load_n_m_points(
+            num_proofs,
+            self.permutation_chunks(),
+            "permutation product commitments",
+        )?;
+
+        let lookups_committed = lookups_permuted
+            .iter()
+            .enumerate()
+            .map(|(i, lookups)| {
+                self.expect(format!(
+                    "{} lookup product commitments for proof {}",
+                    lookups.len(),
+                    i
+                ));
+                // Hash each lookup product commitment
+                lookups
+                    .into_iter()
+                    .map(|_| self.load_point())
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.expect("the random commitment".to_owned());
+        let random_commitment = self.load_point()?;
+
+        let y = self.squeeze_challenge_scalar()?;
+        let pieces = self.vk.domain.get_quotient_poly_degree();
+        self.expect(format!("{} quotient commitments", pieces));
+        let h_commitments = self.load_n_points(pieces)?;
+
+        let commitments = ProofCommitments {
+            advice_commitments,
+            theta,
+            lookups_permuted,
+            beta,
+            gamma,
+            permutations_committed,
+            lookups_committed,
+            random_commitment,
+            y,
+            h_commitments,
+        };
+        Ok(self.with_stage(Committed { commitments }))
+    }
+
+    pub fn build_params(self) -> Result<VerifierParams<A>, A::Error> {
+        self.read_commitments()?
+            .squeeze_challenges()?
+            .read_evals()?
+            .assemble()
+    }
+}
+
+impl<
+        'a,
+        E: MultiMillerLoop,
+        A: ArithEccChip<
+            Point = E::G1Affine,
+            Scalar = <E::G1Affine as CurveAffine>::ScalarExt,
+            Native = <E::G1Affine as CurveAffine>::ScalarExt,
+        >,
+        T: TranscriptRead<A>,
+        S,
+    > VerifierParamsBuilder<'a, E, A, T, S>
 {
+    // The builder in the given state, skipping the stages up to it.
+    pub fn with_stage<N>(self, stage: N) -> VerifierParamsBuilder<'a, E, A, T, N> {
+        VerifierParamsBuilder {
+            ctx: self.ctx,
+            nchip: self.nchip,
+            schip: self.schip,
+            pchip: self.pchip,
+            assigned_instances: self.assigned_instances,
+            vk: self.vk,
+            params: self.params,
+            transcript: self.transcript,
+            key: self.key,
+            stage,
+        }
+    }
+
+    // The builder back in the Start state and the values of its stage.
+    fn split_stage(self) -> (VerifierParamsBuilder<'a, E, A, T, Start>, S) {
+        let VerifierParamsBuilder {
+            ctx,
+            nchip,
+            schip,
+            pchip,
+            assigned_instances,
+            vk,
+            params,
+            transcript,
+            key,
+            stage,
+        } = self;
+        let builder = VerifierParamsBuilder::new(
+            ctx,
+            nchip,
+            schip,
+            pchip,
+            assigned_instances,
+            vk,
+            params,
+            transcript,
+            key,
+        );
+        (builder, stage)
+    }
+
     fn init_transcript(&mut self) -> Result<(), A::Error> {
         let mut hasher = blake2b_simd::Params::new()
             .hash_length(64)
@@ -86,27 +344,30 @@ impl<
         Ok(())
     }
 
+    // Names the items read next for the errors of the transcript, see TranscriptRead::expect.
+    fn expect(&mut self, items: String) {
+        self.transcript.expect(items);
+    }
+
     fn load_point(&mut self) -> Result<A::AssignedPoint, A::Error> {
         self.transcript
             .read_point(self.ctx, self.nchip, self.schip, self.pchip)
     }
 
     fn load_n_points(&mut self, n: usize) -> Result<Vec<A::AssignedPoint>, A::Error> {
-        let mut ret = vec![];
-        for _ in 0..n {
-            let p = self.load_point()?;
-            ret.push(p);
-        }
-        Ok(ret)
+        self.transcript
+            .read_n_points(self.ctx, self.nchip, self.schip, self.pchip, n)
     }
 
     fn load_n_m_points(
         &mut self,
         n: usize,
         m: usize,
+        items: &str,
     ) -> Result<Vec<Vec<A::AssignedPoint>>, A::Error> {
         let mut ret = vec![];
-        for _ in 0..n {
+        for i in 0..n {
+            self.expect(format!("{} {} for proof {}", m, items, i));
             let p = self.load_n_points(m)?;
             ret.push(p);
         }
@@ -119,21 +380,19 @@ impl<
     }
 
     fn load_n_scalars(&mut self, n: usize) -> Result<Vec<A::AssignedScalar>, A::Error> {
-        let mut ret = vec![];
-        for _ in 0..n {
-            let p = self.load_scalar()?;
-            ret.push(p);
-        }
-        Ok(ret)
+        self.transcript
+            .read_n_scalars(self.ctx, self.nchip, self.schip, n)
     }
 
     fn load_n_m_scalars(
         &mut self,
         n: usize,
         m: usize,
+        items: &str,
     ) -> Result<Vec<Vec<A::AssignedScalar>>, A::Error> {
         let mut ret = vec![];
-        for _ in 0..n {
+        for i in 0..n {
+            self.expect(format!("{} {} for proof {}", m, items, i));
             let p = self.load_n_scalars(m)?;
             ret.push(p);
         }
@@ -151,14 +410,7 @@ impl<
         omega: A::Scalar,
         at: i32,
     ) -> Result<A::AssignedScalar, A::Error> {
-        let (base, exp) = if at < 0 {
-            (omega.invert().unwrap(), [(-at) as u64, 0, 0, 0])
-        } else {
-            (omega, [at as u64, 0, 0, 0])
-        };
-        let omega_at = base.pow_vartime(exp);
-        self.schip
-            .sum_with_coeff_and_constant(self.ctx, vec![(x, omega_at)], A::Scalar::zero())
+        params::rotate_omega::<A>(self.ctx, self.schip, x, omega, at)
     }
 
     fn convert_expression(
@@ -217,36 +469,29 @@ impl<
         &mut self,
         x: &<A as ArithEccChip>::AssignedScalar,
         permutations_committed: Vec<Vec<<A as ArithEccChip>::AssignedPoint>>,
+        permutation_product_evals: Vec<Vec<PermutationProductEvals<A>>>,
         advice_evals: &Vec<Vec<<A as ArithEccChip>::AssignedScalar>>,
         instance_evals: &Vec<Vec<<A as ArithEccChip>::AssignedScalar>>,
         fixed_evals: &Vec<<A as ArithEccChip>::AssignedScalar>,
-    ) -> Result<Vec<permutation::Evaluated<A>>, A::Error> {
+    ) -> Vec<permutation::Evaluated<A>> {
         let permutation_evaluated_sets = permutations_committed
             .into_iter()
-            .map(|permutation| {
-                let mut sets = vec![];
-
-                let mut iter = permutation.into_iter();
-                while let Some(permutation_product_commitment) = iter.next() {
-                    let permutation_product_eval = self.load_scalar()?;
-                    let permutation_product_next_eval = self.load_scalar()?;
-                    let permutation_product_last_eval = if iter.len() > 0 {
-                        Some(self.load_scalar()?)
-                    } else {
-                        None
-                    };
-
-                    sets.push(permutation::EvaluatedSet {
-                        permutation_product_commitment,
-                        permutation_product_eval,
-                        permutation_product_next_eval,
-                        permutation_product_last_eval,
-                    });
-                }
-
-                Ok(sets)
+            .zip(permutation_product_evals.into_iter())
+            .map(|(permutation, evals)| {
+                permutation
+                    .into_iter()
+                    .zip(evals.into_iter())
+                    .map(
+                        |(permutation_product_commitment, evals)| permutation::EvaluatedSet {
+                            permutation_product_commitment,
+                            permutation_product_eval: evals.eval,
+                            permutation_product_next_eval: evals.next_eval,
+                            permutation_product_last_eval: evals.last_eval,
+                        },
+                    )
+                    .collect::<Vec<_>>()
             })
-            .collect::<Result<Vec<_>, _>>()?;
+            .collect::<Vec<_>>();
 
         let permutation_evaluated_evals: Vec<Vec<<A as ArithEccChip>::AssignedScalar>> =
             advice_evals
@@ -279,7 +524,7 @@ impl<
                 })
                 .collect::<Vec<_>>();
 
-        let permutation_evaluated = permutation_evaluated_sets
+        permutation_evaluated_sets
             .into_iter()
             .zip(permutation_evaluated_evals.into_iter())
             .enumerate()
@@ -295,142 +540,97 @@ impl<
                     }
                 },
             )
-            .collect();
-
-        Ok(permutation_evaluated)
+            .collect()
     }
 
     fn build_lookup_evaluated(
         &mut self,
         lookups_permuted: Vec<Vec<PermutationCommitments<<A as ArithEccChip>::AssignedPoint>>>,
         lookups_committed: Vec<Vec<<A as ArithEccChip>::AssignedPoint>>,
+        lookup_evals: Vec<Vec<LookupEvals<A>>>,
     ) -> Result<Vec<Vec<lookup::Evaluated<A>>>, A::Error> {
+        // The lookup expressions only depend on the vk, convert them once for all proofs.
+        let lookup_expressions = self
+            .vk
+            .cs
+            .lookups
+            .iter()
+            .map(|argument| {
+                let input_expressions = argument
+                    .input_expressions
+                    .iter()
+                    .map(|expr| self.convert_expression(expr.clone()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let table_expressions = argument
+                    .table_expressions
+                    .iter()
+                    .map(|expr| self.convert_expression(expr.clone()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((Rc::new(input_expressions), Rc::new(table_expressions)))
+            })
+            .collect::<Result<Vec<_>, A::Error>>()?;
+
         let lookup_evaluated = lookups_permuted
             .into_iter()
             .zip(lookups_committed.into_iter())
+            .zip(lookup_evals.into_iter())
             .enumerate()
-            .map(|(i, (permuted, product_commitment))| {
+            .map(|(i, ((permuted, product_commitment), evals))| {
                 permuted
                     .into_iter()
                     .zip(product_commitment.into_iter())
-                    .zip(self.vk.cs.lookups.iter())
+                    .zip(evals.into_iter())
+                    .zip(lookup_expressions.iter())
                     .enumerate()
-                    .map(|(j, ((permuted, product_commitment), argument))| {
-                        let product_eval = self.load_scalar()?;
-                        let product_next_eval = self.load_scalar()?;
-                        let permuted_input_eval = self.load_scalar()?;
-                        let permuted_input_inv_eval = self.load_scalar()?;
-                        let permuted_table_eval = self.load_scalar()?;
-                        Ok(lookup::Evaluated {
-                            input_expressions: argument
-                                .input_expressions
-                                .iter()
-                                .map(|expr| self.convert_expression(expr.clone()))
-                                .collect::<Result<Vec<_>, _>>()?,
-                            table_expressions: argument
-                                .table_expressions
-                                .iter()
-                                .map(|expr| self.convert_expression(expr.clone()))
-                                .collect::<Result<Vec<_>, _>>()?,
-                            committed: lookup::Committed {
-                                permuted,
-                                product_commitment,
-                            },
-                            product_eval,
-                            product_next_eval,
-                            permuted_input_eval,
-                            permuted_input_inv_eval,
-                            permuted_table_eval,
-                            key: format!("{}_{}_{}", self.key.clone(), i, j),
-                        })
-                    })
-                    .collect::<Result<Vec<_>, _>>()
+                    .map(
+                        |(j, (((permuted, product_commitment), evals), expressions))| {
+                            lookup::Evaluated {
+                                input_expressions: expressions.0.clone(),
+                                table_expressions: expressions.1.clone(),
+                                committed: lookup::Committed {
+                                    permuted,
+                                    product_commitment,
+                                },
+                                product_eval: evals.product_eval,
+                                product_next_eval: evals.product_next_eval,
+                                permuted_input_eval: evals.permuted_input_eval,
+                                permuted_input_inv_eval: evals.permuted_input_inv_eval,
+                                permuted_table_eval: evals.permuted_table_eval,
+                                key: format!("{}_{}_{}", self.key.clone(), i, j),
+                            }
+                        },
+                    )
+                    .collect::<Vec<_>>()
             })
-            .collect::<Result<Vec<_>, _>>()?;
+            .collect::<Vec<_>>();
 
         Ok(lookup_evaluated)
     }
 
-    pub fn build_params(mut self) -> Result<VerifierParams<A>, A::Error> {
-        self.init_transcript()?;
-
-        self.squeeze_instance_commitment()?;
-        let instance_commitments = &self.assigned_instances;
-
-        let num_proofs = instance_commitments.len();
-
-        let advice_commitments = self.load_n_m_points(num_proofs, self.vk.cs.num_advice_columns)?;
-
-        let theta = self.squeeze_challenge_scalar()?;
-
-        let lookups_permuted = (0..num_proofs)
-            .map(|_| {
-                (0..self.vk.cs.lookups.len())
-                    .map(|_| {
-                        let permuted_input_commitment = self.load_point()?;
-                        let permuted_table_commitment = self.load_point()?;
-
-                        Ok(PermutationCommitments {
-                            permuted_input_commitment,
-                            permuted_table_commitment,
-                        })
-                    })
-                    .collect::<Result<Vec<_>, _>>()
-            })
-            .collect::<Result<Vec<Vec<_>>, _>>()?;
+    fn assemble_params(
+        mut self,
+        commitments: ProofCommitments<A>,
+        point: EvaluationPoint<A>,
+        evals: ProofEvals<A>,
+    ) -> Result<VerifierParams<A>, A::Error> {
+        let common = self.common();
+        let n = common.n;
 
-        let beta = self.squeeze_challenge_scalar()?;
-        let gamma = self.squeeze_challenge_scalar()?;
-
-        let permutations_committed = self.load_n_m_points(
-            num_proofs,
-            self.vk
-                .cs
-                .permutation
-                .columns
-                .chunks(self.vk.cs.degree() - 2)
-                .len(),
-        )?;
-
-        let lookups_committed = lookups_permuted
-            .iter()
-            .map(|lookups| {
-                // Hash each lookup product commitment
-                lookups
-                    .into_iter()
-                    .map(|_| self.load_point())
-                    .collect::<Result<Vec<_>, _>>()
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let random_commitment = self.load_point()?;
-
-        let y = self.squeeze_challenge_scalar()?;
-        let h_commitments = self.load_n_points(self.vk.domain.get_quotient_poly_degree())?;
-        let l = self.vk.cs.blinding_factors() as u32 + 1;
-        let n = self.params.n as u32;
-        let omega = self.vk.domain.get_omega();
-
-        let x = self.squeeze_challenge_scalar()?;
-
-        let instance_evals =
-            self.load_n_m_scalars(num_proofs, self.vk.cs.instance_queries.len())?;
-        let advice_evals = self.load_n_m_scalars(num_proofs, self.vk.cs.advice_queries.len())?;
-        let fixed_evals = self.load_n_scalars(self.vk.cs.fixed_queries.len())?;
-
-        let random_eval = self.load_scalar()?;
-
-        let permutation_evals = self.load_n_scalars(self.vk.permutation.commitments.len())?;
         let permutation_evaluated = self.build_permutation_evaluated(
-            &x,
-            permutations_committed,
-            &advice_evals,
-            &instance_evals,
-            &fixed_evals,
+            &point.x,
+            commitments.permutations_committed,
+            evals.permutation_product_evals,
+            &evals.advice_evals,
+            &evals.instance_evals,
+            &evals.fixed_evals,
+        );
+
+        let lookup_evaluated = self.build_lookup_evaluated(
+            commitments.lookups_permuted,
+            commitments.lookups_committed,
+            evals.lookup_evals,
         )?;
 
-        let lookup_evaluated = self.build_lookup_evaluated(lookups_permuted, lookups_committed)?;
-
         let fixed_commitments = self
             .vk
             .fixed_commitments
@@ -438,19 +638,6 @@ impl<
             .map(|&affine| self.pchip.assign_const(self.ctx, affine))
             .collect::<Result<Vec<_>, _>>()?;
 
-        let v = self.squeeze_challenge_scalar()?;
-        let u = self.squeeze_challenge_scalar()?;
-
-        let mut w = vec![];
-        while let Ok(p) = self.load_point() {
-            w.push(p);
-        }
-
-        let x_next = self.rotate_omega(&x, omega, 1)?;
-        let x_last = self.rotate_omega(&x, omega, -(l as i32))?;
-        let x_inv = self.rotate_omega(&x, omega, -1)?;
-        let xn = self.schip.pow_constant(self.ctx, &x, n)?;
-
         Ok(VerifierParams {
             key: self.key.clone(),
             gates: self
@@ -465,11 +652,11 @@ impl<
                         .collect::<Result<Vec<_>, _>>()
                 })
                 .collect::<Result<Vec<_>, _>>()?,
-            common: PlonkCommonSetup { l, n },
+            common,
             lookup_evaluated,
             permutation_evaluated,
             instance_commitments: self.assigned_instances,
-            instance_evals,
+            instance_evals: evals.instance_evals,
             instance_queries: self
                 .vk
                 .cs
@@ -477,8 +664,8 @@ impl<
                 .iter()
                 .map(|column| (column.0.index, column.1 .0 as i32))
                 .collect(),
-            advice_commitments,
-            advice_evals,
+            advice_commitments: commitments.advice_commitments,
+            advice_evals: evals.advice_evals,
             advice_queries: self
                 .vk
                 .cs
@@ -487,7 +674,7 @@ impl<
                 .map(|column| (column.0.index, column.1 .0 as i32))
                 .collect(),
             fixed_commitments,
-            fixed_evals,
+            fixed_evals: evals.fixed_evals,
             fixed_queries: self
                 .vk
                 .cs
@@ -502,29 +689,29 @@ impl<
                 .iter()
                 .map(|commit| self.pchip.assign_const(self.ctx, *commit))
                 .collect::<Result<Vec<_>, _>>()?,
-            permutation_evals,
-            vanish_commitments: h_commitments,
-            random_commitment,
-            random_eval,
-            beta,
-            gamma,
-            theta,
+            permutation_evals: evals.permutation_evals,
+            vanish_commitments: commitments.h_commitments,
+            random_commitment: commitments.random_commitment,
+            random_eval: evals.random_eval,
+            beta: commitments.beta,
+            gamma: commitments.gamma,
+            theta: commitments.theta,
             delta: self.schip.assign_const(
                 self.ctx,
                 <<E::G1Affine as CurveAffine>::ScalarExt as FieldExt>::DELTA,
             )?,
-            x,
-            x_next,
-            x_last,
-            x_inv,
-            xn,
-            y,
-            u,
-            v,
+            x: point.x,
+            x_next: point.x_next,
+            x_last: point.x_last,
+            x_inv: point.x_inv,
+            xn: point.xn,
+            y: commitments.y,
+            u: evals.u,
+            v: evals.v,
             omega: self
                 .schip
                 .assign_const(self.ctx, self.vk.domain.get_omega())?,
-            w,
+            w: evals.w,
             zero: self
                 .schip
                 .assign_const(self.ctx, <E::G1Affine as CurveAffine>::ScalarExt::zero())?,
@@ -537,6 +724,184 @@ impl<
             )?,
         })
     }
+
+    fn permutation_chunks(&self) -> usize {
+        self.vk
+            .cs
+            .permutation
+            .columns
+            .chunks(self.vk.cs.degree() - 2)
+            .len()
+    }
+
+    fn common(&self) -> PlonkCommonSetup {
+        PlonkCommonSetup::new(self.vk.cs.blinding_factors() as u32, self.params.n as u32)
+    }
+}
+
+impl<
+        'a,
+        E: MultiMillerLoop,
+        A: ArithEccChip<
+            Point = E::G1Affine,
+            Scalar = <E::G1Affine as CurveAffine>::ScalarExt,
+            Native = <E::G1Affine as CurveAffine>::ScalarExt,
+        >,
+        T: TranscriptRead<A>,
+    > VerifierParamsBuilder<'a, E, A, T, Committed<A>>
+{
+    // Stage 2: squeezes x and rotates it to the points the queries open at.
+    pub fn squeeze_challenges(
+        mut self,
+    ) -> Result<VerifierParamsBuilder<'a, E, A, T, Challenged<A>>, A::Error> {
+        let common = self.common();
+        let omega = self.vk.domain.get_omega();
+
+        let x = self.squeeze_challenge_scalar()?;
+        let x_next = self.rotate_omega(&x, omega, 1)?;
+        let x_last = self.rotate_omega(&x, omega, common.last_rotation())?;
+        let x_inv = self.rotate_omega(&x, omega, -1)?;
+        let xn = self.schip.pow_constant(self.ctx, &x, common.n)?;
+
+        let point = EvaluationPoint {
+            x,
+            x_next,
+            x_last,
+            x_inv,
+            xn,
+        };
+        let (builder, Committed { commitments }) = self.split_stage();
+        Ok(builder.with_stage(Challenged { commitments, point }))
+    }
+}
+
+impl<
+        'a,
+        E: MultiMillerLoop,
+        A: ArithEccChip<
+            Point = E::G1Affine,
+            Scalar = <E::G1Affine as CurveAffine>::ScalarExt,
+            Native = <E::G1Affine as CurveAffine>::ScalarExt,
+        >,
+        T: TranscriptRead<A>,
+    > VerifierParamsBuilder<'a, E, A, T, Challenged<A>>
+{
+    // Stage 3: reads the evals at x, then squeezes v and u and reads the points w of the
+    // multiopen proof up to the end of the transcript.
+    pub fn read_evals(
+        mut self,
+    ) -> Result<VerifierParamsBuilder<'a, E, A, T, Evaluated<A>>, A::Error> {
+        let num_proofs = self.assigned_instances.len();
+        let chunks = self.permutation_chunks();
+
+        let instance_evals = self.load_n_m_scalars(
+            num_proofs,
+            self.vk.cs.instance_queries.len(),
+            "instance evals",
+        )?;
+        let advice_evals =
+            self.load_n_m_scalars(num_proofs, self.vk.cs.advice_queries.len(), "advice evals")?;
+        self.expect(format!("{} fixed evals", self.vk.cs.fixed_queries.len()));
+        let fixed_evals = self.load_n_scalars(self.vk.cs.fixed_queries.len())?;
+
+        self.expect("the random eval".to_owned());
+        let random_eval = self.load_scalar()?;
+
+        let permutation_commitments = self.vk.permutation.commitments.len();
+        self.expect(format!("{} permutation evals", permutation_commitments));
+        let permutation_evals = self.load_n_scalars(permutation_commitments)?;
+        let permutation_product_evals = (0..num_proofs)
+            .map(|proof| {
+                self.expect(format!("the permutation product evals for proof {}", proof));
+                (0..chunks)
+                    .map(|i| {
+                        let eval = self.load_scalar()?;
+                        let next_eval = self.load_scalar()?;
+                        let last_eval = if i + 1 < chunks {
+                            Some(self.load_scalar()?)
+                        } else {
+                            None
+                        };
+                        Ok(PermutationProductEvals {
+                            eval,
+                            next_eval,
+                            last_eval,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let lookup_evals = (0..num_proofs)
+            .map(|i| {
+                self.expect(format!("the lookup evals for proof {}", i));
+                (0..self.vk.cs.lookups.len())
+                    .map(|_| {
+                        Ok(LookupEvals {
+                            product_eval: self.load_scalar()?,
+                            product_next_eval: self.load_scalar()?,
+                            permuted_input_eval: self.load_scalar()?,
+                            permuted_input_inv_eval: self.load_scalar()?,
+                            permuted_table_eval: self.load_scalar()?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let v = self.squeeze_challenge_scalar()?;
+        let u = self.squeeze_challenge_scalar()?;
+
+        self.expect("the multiopen points".to_owned());
+        let mut w = vec![];
+        while let Ok(p) = self.load_point() {
+            w.push(p);
+        }
+
+        let evals = ProofEvals {
+            instance_evals,
+            advice_evals,
+            fixed_evals,
+            random_eval,
+            permutation_evals,
+            permutation_product_evals,
+            lookup_evals,
+            v,
+            u,
+            w,
+        };
+        let (builder, Challenged { commitments, point }) = self.split_stage();
+        Ok(builder.with_stage(Evaluated {
+            commitments,
+            point,
+            evals,
+        }))
+    }
+}
+
+impl<
+        'a,
+        E: MultiMillerLoop,
+        A: ArithEccChip<
+            Point = E::G1Affine,
+            Scalar = <E::G1Affine as CurveAffine>::ScalarExt,
+            Native = <E::G1Affine as CurveAffine>::ScalarExt,
+        >,
+        T: TranscriptRead<A>,
+    > VerifierParamsBuilder<'a, E, A, T, Evaluated<A>>
+{
+    // Stage 4: builds the arguments of the proofs and assigns the vk constants, reads nothing.
+    pub fn assemble(self) -> Result<VerifierParams<A>, A::Error> {
+        let (
+            builder,
+            Evaluated {
+                commitments,
+                point,
+                evals,
+            },
+        ) = self.split_stage();
+        builder.assemble_params(commitments, point, evals)
+    }
 }
 
 pub fn assign_instance_commitment<
@@ -553,12 +918,13 @@ pub fn assign_instance_commitment<
     instances: &[&[&[E::Scalar]]],
     vk: &VerifyingKey<E::G1Affine>,
     params: &ParamsVerifier<E>,
-) -> Result<(Vec<A::AssignedScalar>, Vec<Vec<A::AssignedPoint>>), A::Error> {
+) -> Result<(Vec<A::AssignedScalar>, Vec<Vec<A::AssignedPoint>>), A::Error>
+where
+    A::Error: From<InstanceError>,
+{
     let mut plain_assigned_instances = vec![];
 
-    for instances in instances.iter() {
-        assert!(instances.len() == vk.cs.num_instance_columns)
-    }
+    validate_instances(vk, params, instances)?;
 
     let instances = instances
         .iter()
@@ -566,8 +932,6 @@ pub fn assign_instance_commitment<
             instance
                 .iter()
                 .map(|instance| {
-                    assert!(instance.len() <= params.n as usize - (vk.cs.blinding_factors() + 1));
-
                     let mut assigned_scalars = vec![];
                     for instance in instance.iter() {
                         let s = schip.assign_var(ctx, instance.clone())?;
@@ -618,6 +982,83 @@ pub fn assign_instance_commitment<
     Ok((plain_assigned_instances, commitments))
 }
 
+// assign_instance_commitment with the native commitments of each proof given, e.g. taken from
+// an InstanceCommitmentCache. The commitments are assigned as constants and not constrained to
+// the instances: for the mock chips of the native final pair only, never in a circuit.
+pub fn assign_instances_with_commitments<
+    E: MultiMillerLoop,
+    A: ArithEccChip<
+        Point = E::G1Affine,
+        Scalar = <E::G1Affine as CurveAffine>::ScalarExt,
+        Native = <E::G1Affine as CurveAffine>::ScalarExt,
+    >,
+>(
+    ctx: &mut A::Context,
+    schip: &A::ScalarChip,
+    pchip: &A,
+    instances: &[&[&[E::Scalar]]],
+    commitments: &[Vec<E::G1Affine>],
+    vk: &VerifyingKey<E::G1Affine>,
+    params: &ParamsVerifier<E>,
+) -> Result<(Vec<A::AssignedScalar>, Vec<Vec<A::AssignedPoint>>), A::Error>
+where
+    A::Error: From<InstanceError>,
+{
+    validate_instances(vk, params, instances)?;
+
+    let mut plain_assigned_instances = vec![];
+    for instance in instances.iter().flat_map(|columns| columns.iter()) {
+        for instance in instance.iter() {
+            plain_assigned_instances.push(schip.assign_var(ctx, instance.clone())?);
+        }
+    }
+
+    let commitments = commitments
+        .iter()
+        .map(|columns| {
+            columns
+                .iter()
+                .map(|c| pchip.assign_const(ctx, *c))
+                .collect::<Result<Vec<_>, A::Error>>()
+        })
+        .collect::<Result<Vec<Vec<_>>, A::Error>>()?;
+
+    Ok((plain_assigned_instances, commitments))
+}
+
+pub fn build_verifier_params<
+    E: MultiMillerLoop,
+    A: ArithEccChip<
+        Point = E::G1Affine,
+        Scalar = <E::G1Affine as CurveAffine>::ScalarExt,
+        Native = <E::G1Affine as CurveAffine>::ScalarExt,
+    >,
+    T: TranscriptRead<A>,
+>(
+    ctx: &mut A::Context,
+    nchip: &A::NativeChip,
+    schip: &A::ScalarChip,
+    pchip: &A,
+    assigned_instances: Vec<Vec<A::AssignedPoint>>,
+    vk: &VerifyingKey<E::G1Affine>,
+    params: &ParamsVerifier<E>,
+    transcript: &mut T,
+    key: String,
+) -> Result<VerifierParams<A>, A::Error> {
+    VerifierParamsBuilder::new(
+        ctx,
+        nchip,
+        schip,
+        pchip,
+        assigned_instances,
+        vk,
+        params,
+        transcript,
+        key,
+    )
+    .build_params()
+}
+
 pub fn verify_single_proof_no_eval<
     E: MultiMillerLoop,
     A: ArithEccChip<
@@ -637,7 +1078,7 @@ pub fn verify_single_proof_no_eval<
     transcript: &mut T,
     key: String,
 ) -> Result<(MultiOpenProof<A>, Vec<<A as ArithEccChip>::AssignedPoint>), A::Error> {
-    let params_builder = VerifierParamsBuilder {
+    let chip_params = build_verifier_params(
         ctx,
         nchip,
         schip,
@@ -647,9 +1088,7 @@ pub fn verify_single_proof_no_eval<
         params,
         transcript,
         key,
-    };
-
-    let chip_params = params_builder.build_params()?;
+    )?;
     let advice_commitments = chip_params.advice_commitments.clone();
     Ok((
         chip_params.batch_multi_open_proofs(ctx, schip)?,
@@ -671,11 +1110,11 @@ fn evaluate_multiopen_proof<
     schip: &A::ScalarChip,
     pchip: &A,
     proof: MultiOpenProof<A>,
-    //params: &ParamsVerifier<E>,  only for debugging purpose
 ) -> Result<(A::AssignedPoint, A::AssignedPoint), A::Error> {
     let one = schip.assign_one(ctx)?;
 
-    println!("debug context before evaluate multiopen proof: {}", ctx);
+    tracing::debug!("debug context before evaluate multiopen proof: {}", ctx);
+    let _span = tracing::info_span!("msm").entered();
     let (left_s, left_e) = proof.w_x.eval::<_, A>(ctx, schip, pchip, &one)?;
     let (right_s, right_e) = proof.w_g.eval::<_, A>(ctx, schip, pchip, &one)?;
 
@@ -695,24 +1134,19 @@ fn evaluate_multiopen_proof<
         }
     };
 
-    /* FIXME: only for debugging purpose
-
-    let left_v = pchip.to_value(&left)?;
-    let right_v = pchip.to_value(&right)?;
+    tracing::debug!("debug context after evaluate multiopen proof: {}", ctx);
 
-    let s_g2_prepared = E::G2Prepared::from(params.s_g2);
-    let n_g2_prepared = E::G2Prepared::from(-params.g2);
-    let success = bool::from(
-        E::multi_miller_loop(&[(&left_v, &s_g2_prepared), (&right_v, &n_g2_prepared)])
-            .final_exponentiation()
-            .is_identity(),
-    );
-    assert!(success);
+    Ok((left, right))
+}
 
-    */
-    println!("debug context after evaluate multiopen proof: {}", ctx);
+// Target of the events marking the row each step of the aggregation starts at, for chips with
+// a current_row. The circuit crate renders them next to the circuit layout.
+pub const LAYOUT_TARGET: &str = "halo2_snark_aggregator::layout";
 
-    Ok((left, right))
+pub fn mark_layout_step<A: ArithCommonChip>(ctx: &A::Context, chip: &A, step: impl Display) {
+    if let Some(row) = chip.current_row(ctx) {
+        tracing::trace!(target: LAYOUT_TARGET, row = row as u64, step = %step);
+    }
 }
 
 pub struct ProofData<
@@ -770,6 +1204,8 @@ pub fn verify_single_proof_in_chip<
         Vec<A::AssignedPoint>, // advice commitments
     ),
     A::Error>
+where
+    A::Error: From<InstanceError>,
   {
 
     let instances1: Vec<Vec<&[E::Scalar]>> = circuit.proofs[0]
@@ -799,9 +1235,8 @@ pub fn verify_single_proof_in_chip<
         "".to_owned(),
     )?;
 
-    print!("get single proof {}", circuit.name);
-    let (w_x, w_g) =
-        evaluate_multiopen_proof::<E, A, T>(ctx, schip, pchip, proof /*, circuit.params*/)?;
+    tracing::debug!(circuit = %circuit.name, "get single proof");
+    let (w_x, w_g) = evaluate_multiopen_proof::<E, A, T>(ctx, schip, pchip, proof)?;
     Ok((w_x, w_g, plain_assigned_instances, advice_commitments))
 }
 
@@ -813,12 +1248,84 @@ pub fn verify_aggregation_proofs_in_chip<
         Native = <E::G1Affine as CurveAffine>::ScalarExt,
     >,
     T: TranscriptRead<A>,
+>(
+    ctx: &mut A::Context,
+    nchip: &A::NativeChip,
+    schip: &A::ScalarChip,
+    pchip: &A,
+    circuits: Vec<CircuitProof<E, A, T>>,
+    transcript: &mut T,
+) -> Result<
+    (
+        A::AssignedPoint,           // w_x
+        A::AssignedPoint,           // w_g
+        Vec<A::AssignedScalar>,     // plain assigned instance
+        Vec<Vec<A::AssignedPoint>>, // advice commitments
+    ),
+    A::Error,
+>
+where
+    A::Error: From<InstanceError>,
+{
+    verify_aggregation_proofs(ctx, nchip, schip, pchip, circuits, None, transcript)
+}
+
+// verify_aggregation_proofs_in_chip with the native instance commitments of each proof of each
+// circuit given, see assign_instances_with_commitments: for the mock chips only.
+pub fn verify_aggregation_proofs_with_instance_commitments<
+    E: MultiMillerLoop,
+    A: ArithEccChip<
+        Point = E::G1Affine,
+        Scalar = <E::G1Affine as CurveAffine>::ScalarExt,
+        Native = <E::G1Affine as CurveAffine>::ScalarExt,
+    >,
+    T: TranscriptRead<A>,
+>(
+    ctx: &mut A::Context,
+    nchip: &A::NativeChip,
+    schip: &A::ScalarChip,
+    pchip: &A,
+    circuits: Vec<CircuitProof<E, A, T>>,
+    instance_commitments: &[Vec<Vec<E::G1Affine>>],
+    transcript: &mut T,
+) -> Result<
+    (
+        A::AssignedPoint,           // w_x
+        A::AssignedPoint,           // w_g
+        Vec<A::AssignedScalar>,     // plain assigned instance
+        Vec<Vec<A::AssignedPoint>>, // advice commitments
+    ),
+    A::Error,
+>
+where
+    A::Error: From<InstanceError>,
+{
+    verify_aggregation_proofs(
+        ctx,
+        nchip,
+        schip,
+        pchip,
+        circuits,
+        Some(instance_commitments),
+        transcript,
+    )
+}
+
+fn verify_aggregation_proofs<
+    E: MultiMillerLoop,
+    A: ArithEccChip<
+        Point = E::G1Affine,
+        Scalar = <E::G1Affine as CurveAffine>::ScalarExt,
+        Native = <E::G1Affine as CurveAffine>::ScalarExt,
+    >,
+    T: TranscriptRead<A>,
 >(
     ctx: &mut A::Context,
     nchip: &A::NativeChip,
     schip: &A::ScalarChip,
     pchip: &A,
     mut circuits: Vec<CircuitProof<E, A, T>>,
+    instance_commitments: Option<&[Vec<Vec<E::G1Affine>>]>,
     transcript: &mut T,
 ) -> Result<
     (
@@ -828,17 +1335,21 @@ pub fn verify_aggregation_proofs_in_chip<
         Vec<Vec<A::AssignedPoint>>, // advice commitments
     ),
     A::Error>
+where
+    A::Error: From<InstanceError>,
   {
     let mut plain_assigned_instances = vec![];
 
     let multiopen_proofs: Vec<Vec<(MultiOpenProof<A>, Vec<A::AssignedPoint>)>> = circuits
     //let multiopen_proofs: Vec<Vec<MultiOpenProof<A>>> = circuits
         .iter_mut()
-        .map(|circuit_proof| {
+        .enumerate()
+        .map(|(ci, circuit_proof)| {
             let r = circuit_proof
                 .proofs
                 .iter_mut()
-                .map(|proof| {
+                .enumerate()
+                .map(|(pi, proof)| {
                     let instances1: Vec<Vec<&[E::Scalar]>> = proof
                         .instances
                         .iter()
@@ -847,39 +1358,57 @@ pub fn verify_aggregation_proofs_in_chip<
                     let instances2: Vec<&[&[E::Scalar]]> =
                         instances1.iter().map(|x| &x[..]).collect();
 
+                    let step = format!("{} {}", circuit_proof.name, proof.key);
+                    mark_layout_step(ctx, schip, format!("{} instances", step));
                     let (assigned_instances, assigned_instance_commitments) =
-                        assign_instance_commitment(
-                            ctx,
-                            schip,
-                            pchip,
-                            &instances2[..],
-                            circuit_proof.vk,
-                            circuit_proof.params,
-                        )?;
+                        match instance_commitments {
+                            None => assign_instance_commitment(
+                                ctx,
+                                schip,
+                                pchip,
+                                &instances2[..],
+                                circuit_proof.vk,
+                                circuit_proof.params,
+                            )?,
+                            Some(commitments) => assign_instances_with_commitments(
+                                ctx,
+                                schip,
+                                pchip,
+                                &instances2[..],
+                                &commitments[ci][pi..pi + 1],
+                                circuit_proof.vk,
+                                circuit_proof.params,
+                            )?,
+                        };
 
                     for assigned_instance in assigned_instances {
                         plain_assigned_instances.push(assigned_instance)
                     }
 
-                    let (p, c) = verify_single_proof_no_eval(
-                        ctx,
-                        nchip,
-                        schip,
-                        pchip,
-                        assigned_instance_commitments,
-                        circuit_proof.vk,
-                        circuit_proof.params,
-                        &mut proof.transcript,
-                        proof.key.clone(),
-                    )?;
-
-                    println!("get proof {} {}", circuit_proof.name, p);
+                    mark_layout_step(ctx, schip, format!("{} proof", step));
+                    let (p, c) = tracing::info_span!("transcript_parsing", key = %proof.key)
+                        .in_scope(|| {
+                            verify_single_proof_no_eval(
+                                ctx,
+                                nchip,
+                                schip,
+                                pchip,
+                                assigned_instance_commitments,
+                                circuit_proof.vk,
+                                circuit_proof.params,
+                                &mut proof.transcript,
+                                proof.key.clone(),
+                            )
+                        })?;
+
+                    tracing::debug!("get proof {} {}", circuit_proof.name, p);
 
                     Ok((p,c))
                 })
                 .collect::<Result<Vec<(MultiOpenProof<A>, Vec<A::AssignedPoint>)>, A::Error>>();
 
             /* update aggregation challenge */
+            mark_layout_step(ctx, schip, format!("{} challenges", circuit_proof.name));
             for p in circuit_proof.proofs.iter_mut() {
                 let scalar = p.transcript.squeeze_challenge_scalar(ctx, nchip, schip)?;
                 transcript.common_scalar(ctx, nchip, schip, &scalar)?;
@@ -894,24 +1423,184 @@ pub fn verify_aggregation_proofs_in_chip<
         .flatten()
         .collect::<Vec<(MultiOpenProof<A>, Vec<A::AssignedPoint>)>>();
 
+    mark_layout_step(ctx, schip, "aggregation challenge");
     let aggregation_challenge = transcript.squeeze_challenge_scalar(ctx, nchip, schip)?;
 
-    let mut acc: Option<MultiOpenProof<A>> = None;
+    let mut acc: Option<Accumulator<_>> = None;
     let mut commits: Vec<Vec<A::AssignedPoint>> = vec![];
     for (proof, c) in proofs.into_iter() {
         acc = match acc {
-            None => Some(proof),
-            Some(acc) => {
-                Some(MultiOpenProof {
-                    w_x: acc.w_x * scalar!(aggregation_challenge) + proof.w_x,
-                    w_g: acc.w_g * scalar!(aggregation_challenge) + proof.w_g,
-                })
-            }
+            None => Some(proof.into()),
+            Some(acc) => Some(acc.fold_schema(proof.into(), &aggregation_challenge)),
         };
         commits.push(c)
     }
-    let aggregated_proof = acc.unwrap();
+    let aggregated_proof: MultiOpenProof<A> = acc.unwrap().into();
 
+    mark_layout_step(ctx, schip, "msm");
     evaluate_multiopen_proof::<E, A, T>(ctx, schip, pchip, aggregated_proof)
         .map(|pair| (pair.0, pair.1, plain_assigned_instances, commits))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock::{
+            arith::{
+                ecc::MockEccChip,
+                field::{MockChipCtx, MockFieldChip},
+            },
+            transcript_encode::PoseidonEncode,
+        },
+        systems::halo2::transcript::PoseidonTranscriptRead,
+        tests::systems::halo2::add_mul_test::test_circuit::test_circuit_builder,
+    };
+    use halo2_proofs::{
+        pairing::bn256::Fr as Fp,
+        plonk::{create_proof, keygen_pk, keygen_vk, Error},
+        poly::commitment::Params,
+        transcript::{Challenge255, PoseidonWrite},
+    };
+    use pairing_bn256::bn256::{Bn256, G1Affine};
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+
+    type A = MockEccChip<G1Affine, Error>;
+    type Transcript<'a> = PoseidonTranscriptRead<&'a [u8], G1Affine, A, PoseidonEncode, 9, 8>;
+
+    #[test]
+    fn test_stages_match_build_params() {
+        let (a, b) = (Fp::from(3), Fp::from(5));
+        let c = Fp::from(7) * a.square() * b.square();
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(10);
+        let vk = keygen_vk(&params, &test_circuit_builder(a, b)).unwrap();
+        let pk = keygen_pk(&params, vk, &test_circuit_builder(a, b)).unwrap();
+        let instances: &[&[&[_]]] = &[&[&[c]]];
+
+        let mut writer = PoseidonWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            &[test_circuit_builder(a, b)],
+            instances,
+            Pcg32::seed_from_u64(0),
+            &mut writer,
+        )
+        .unwrap();
+        let proof = writer.finalize();
+        let params_verifier: ParamsVerifier<Bn256> = params.verifier(1).unwrap();
+
+        let nchip = MockFieldChip::<Fp, Error>::default();
+        let schip = MockFieldChip::<Fp, Error>::default();
+        let pchip = A::default();
+        let ctx = &mut MockChipCtx::default();
+        let (_, assigned_instances) = assign_instance_commitment::<Bn256, _>(
+            ctx,
+            &schip,
+            &pchip,
+            instances,
+            pk.get_vk(),
+            &params_verifier,
+        )
+        .unwrap();
+
+        let mut transcript = Transcript::new(&proof[..], ctx, &nchip, 8usize, 33usize).unwrap();
+        let expected = build_verifier_params(
+            ctx,
+            &nchip,
+            &schip,
+            &pchip,
+            assigned_instances.clone(),
+            pk.get_vk(),
+            &params_verifier,
+            &mut transcript,
+            "p0".to_owned(),
+        )
+        .unwrap();
+
+        let mut transcript = Transcript::new(&proof[..], ctx, &nchip, 8usize, 33usize).unwrap();
+        let builder = VerifierParamsBuilder::new(
+            ctx,
+            &nchip,
+            &schip,
+            &pchip,
+            assigned_instances,
+            pk.get_vk(),
+            &params_verifier,
+            &mut transcript,
+            "p0".to_owned(),
+        );
+        let evaluated = builder
+            .read_commitments()
+            .unwrap()
+            .squeeze_challenges()
+            .unwrap()
+            .read_evals()
+            .unwrap();
+        let (builder, stage) = evaluated.split_stage();
+        // the values of the stages handed back to a fresh builder, as a front-end with them at
+        // hand does
+        let staged = builder.with_stage(stage).assemble().unwrap();
+
+        assert_eq!(staged.dump(), expected.dump());
+    }
+
+    #[test]
+    fn test_assign_instance_commitment_rejects_instances() {
+        let (a, b) = (Fp::from(3), Fp::from(5));
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(10);
+        let vk = keygen_vk(&params, &test_circuit_builder(a, b)).unwrap();
+        let params_verifier: ParamsVerifier<Bn256> = params.verifier(1).unwrap();
+
+        let schip = MockFieldChip::<Fp, Error>::default();
+        let pchip = A::default();
+        let ctx = &mut MockChipCtx::default();
+        let instances: &[&[&[_]]] = &[&[&[a], &[b]]];
+        let res = assign_instance_commitment::<Bn256, _>(
+            ctx,
+            &schip,
+            &pchip,
+            instances,
+            &vk,
+            &params_verifier,
+        );
+        assert!(matches!(res, Err(Error::InvalidInstances)));
+    }
+
+    #[test]
+    fn test_assign_instances_with_commitments() {
+        use super::super::instance::instance_commitments;
+
+        let (a, b) = (Fp::from(3), Fp::from(5));
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(10);
+        let vk = keygen_vk(&params, &test_circuit_builder(a, b)).unwrap();
+        let params_verifier: ParamsVerifier<Bn256> = params.verifier(1).unwrap();
+
+        let schip = MockFieldChip::<Fp, Error>::default();
+        let pchip = A::default();
+        let ctx = &mut MockChipCtx::default();
+        let instances: &[&[&[_]]] = &[&[&[a]], &[&[b]]];
+        let expected = assign_instance_commitment::<Bn256, _>(
+            ctx,
+            &schip,
+            &pchip,
+            instances,
+            &vk,
+            &params_verifier,
+        )
+        .unwrap();
+        let commitments = instance_commitments(&params_verifier, instances);
+        let assigned = assign_instances_with_commitments::<Bn256, _>(
+            ctx,
+            &schip,
+            &pchip,
+            instances,
+            &commitments,
+            &vk,
+            &params_verifier,
+        )
+        .unwrap();
+        assert_eq!(assigned, expected);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::arith::{common::ArithCommonChip, ecc::ArithEccChip};
+use group::{prime::PrimeCurveAffine, Curve};
+use halo2_proofs::arithmetic::MultiMillerLoop;
+
+// n-pair final check, the proof is valid iff prod(e(g1_i, g2_i)) == 1.
+// The G1 sides are assigned in context, the G2 sides stay native
+// and have to be handled by whoever consumes the final pairing.
+pub struct MultiPairingCheck<E: MultiMillerLoop, A: ArithEccChip<Point = E::G1Affine>> {
+    pub pairs: Vec<(A::AssignedPoint, E::G2Affine)>,
+}
+
+impl<E: MultiMillerLoop, A: ArithEccChip<Point = E::G1Affine>> Default for MultiPairingCheck<E, A> {
+    fn default() -> Self {
+        Self { pairs: vec![] }
+    }
+}
+
+impl<E: MultiMillerLoop, A: ArithEccChip<Point = E::G1Affine>> MultiPairingCheck<E, A> {
+    // The kzg pair of a halo2 multiopen proof: e(w_x, [s]) * e(w_g, -[1]) == 1.
+    pub fn from_final_pair(
+        w_x: A::AssignedPoint,
+        w_g: A::AssignedPoint,
+        s_g2: E::G2Affine,
+        g2: E::G2Affine,
+    ) -> Self {
+        Self {
+            pairs: vec![(w_x, s_g2), (w_g, (-g2.to_curve()).to_affine())],
+        }
+    }
+
+    pub fn push(&mut self, g1: A::AssignedPoint, g2: E::G2Affine) {
+        self.pairs.push((g1, g2));
+    }
+
+    pub fn extend(&mut self, other: Self) {
+        self.pairs.extend(other.pairs);
+    }
+
+    pub fn to_native(&self, pchip: &A) -> Result<Vec<(E::G1Affine, E::G2Affine)>, A::Error> {
+        self.pairs
+            .iter()
+            .map(|(p, q)| Ok((pchip.to_value(p)?, *q)))
+            .collect()
+    }
+
+    pub fn check_native(&self, pchip: &A) -> Result<bool, A::Error> {
+        Ok(multi_pairing_native::<E>(&self.to_native(pchip)?[..]))
+    }
+}
+
+pub fn multi_pairing_native<E: MultiMillerLoop>(pairs: &[(E::G1Affine, E::G2Affine)]) -> bool {
+    let g2s = pairs
+        .iter()
+        .map(|(_, q)| E::G2Prepared::from(*q))
+        .collect::<Vec<_>>();
+    let terms = pairs
+        .iter()
+        .zip(g2s.iter())
+        .map(|((p, _), q)| (p, q))
+        .collect::<Vec<_>>();
+
+    bool::from(
+        E::multi_miller_loop(&terms[..])
+            .final_exponentiation()
+            .is_identity(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::arith::{ecc::MockEccChip, field::MockChipCtx};
+    use halo2_proofs::{arithmetic::Field, pairing::bn256::Fr as Fp, plonk::Error};
+    use pairing_bn256::bn256::{Bn256, G1Affine, G2Affine};
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    #[test]
+    fn test_multi_pairing_check() {
+        let pchip = MockEccChip::<G1Affine, Error>::default();
+        let ctx = &mut MockChipCtx::default();
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        let (g1, g2) = (G1Affine::generator(), G2Affine::generator());
+
+        // w_g = [s] * w_x
+        let s = Fp::random(&mut rng);
+        let a = Fp::random(&mut rng);
+        let w_x = pchip.assign_var(ctx, (g1 * a).to_affine()).unwrap();
+        let w_g = pchip.assign_var(ctx, (g1 * (a * s)).to_affine()).unwrap();
+        let s_g2 = (g2 * s).to_affine();
+
+        let mut check = MultiPairingCheck::<Bn256, MockEccChip<G1Affine, Error>>::from_final_pair(
+            w_x, w_g, s_g2, g2,
+        );
+        assert!(check.check_native(&pchip).unwrap());
+
+        // e([b], [c]) * e(-[b * c], [1]) cancels out, so extra terms keep the check valid
+        let b = Fp::random(&mut rng);
+        let c = Fp::random(&mut rng);
+        check.push(
+            pchip.assign_var(ctx, (g1 * b).to_affine()).unwrap(),
+            (g2 * c).to_affine(),
+        );
+        check.push(
+            pchip.assign_var(ctx, (g1 * -(b * c)).to_affine()).unwrap(),
+            g2,
+        );
+        assert_eq!(check.pairs.len(), 4);
+        assert!(check.check_native(&pchip).unwrap());
+
+        check.push(pchip.assign_var(ctx, g1).unwrap(), g2);
+        assert!(!check.check_native(&pchip).unwrap());
+    }
+}
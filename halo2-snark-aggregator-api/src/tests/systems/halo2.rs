@@ -1,5 +1,9 @@
 pub mod add_mul_test;
+#[cfg(all(test, feature = "json"))]
+mod golden_test;
 pub mod lookup_test;
+pub mod random_test;
+pub mod shape_test;
 
 #[cfg(test)]
 pub mod zkevm_test;
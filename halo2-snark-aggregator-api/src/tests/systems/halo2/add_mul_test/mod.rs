@@ -1,3 +1,12 @@
 pub(crate) mod test_circuit;
 pub mod verify_aggregation;
 pub mod verify_single;
+
+#[cfg(feature = "test-support")]
+use halo2_proofs::{pairing::bn256::Fr as Fp, plonk::Circuit};
+
+// The add mul test circuit for the tests of the other crates of the workspace.
+#[cfg(feature = "test-support")]
+pub fn test_circuit_builder(a: Fp, b: Fp) -> impl Circuit<Fp> {
+    test_circuit::test_circuit_builder(a, b)
+}
@@ -152,4 +152,25 @@ mod tests {
             PoseidonEncode,
         >(&nchip, &schip, &pchip, ctx);
     }
+
+    #[test]
+    fn test_verify_single_proof_in_chip_trace() {
+        use crate::mock::trace::{TracingContext, TracingEccChip, TracingFieldChip};
+
+        let nchip = TracingFieldChip::default();
+        let schip = TracingFieldChip::default();
+        let pchip = TracingEccChip::default();
+        let ctx = &mut TracingContext::default();
+        test_verify_single_proof_in_chip::<
+            TracingFieldChip<Fp, Error>,
+            TracingFieldChip<Fp, Error>,
+            TracingEccChip<G1Affine, Error>,
+            PoseidonEncode,
+        >(&nchip, &schip, &pchip, ctx);
+
+        assert!(ctx.nodes.len() > 0);
+        assert!(ctx.nodes.iter().any(|node| node.op == "scalar_mul"));
+        let path = std::env::temp_dir().join("add_mul_verify_single.trace");
+        ctx.dump(&path).unwrap();
+    }
 }
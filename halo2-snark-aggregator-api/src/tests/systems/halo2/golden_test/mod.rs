@@ -0,0 +1,209 @@
+// Golden vectors of the transcript parsing: fixed proofs of the add_mul and lookup test circuits
+// under vectors/, next to the json dump of the VerifierParams that build_verifier_params parses
+// from them. A change of the transcript order or of a challenge derivation changes the dump and
+// fails here instead of as an unverifiable proof downstream.
+//
+// A missing vector fails the test like a differing one. `GOLDEN_UPDATE=1 cargo test golden` is
+// the only way to write them, after an intended change; commit the result.
+use crate::{
+    mock::{
+        arith::{
+            ecc::MockEccChip,
+            field::{MockChipCtx, MockFieldChip},
+        },
+        transcript_encode::PoseidonEncode,
+    },
+    systems::halo2::{
+        params::VerifierParamsDump,
+        transcript::PoseidonTranscriptRead,
+        verify::{assign_instance_commitment, build_verifier_params},
+    },
+    tests::systems::halo2::{add_mul_test, lookup_test},
+};
+use group::{ff::PrimeField, GroupEncoding};
+use halo2_proofs::{
+    arithmetic::Field,
+    pairing::bn256::Fr as Fp,
+    plonk::{create_proof, keygen_pk, keygen_vk, Circuit, Error, ProvingKey},
+    poly::commitment::{Params, ParamsVerifier},
+    transcript::{Challenge255, PoseidonWrite},
+};
+use pairing_bn256::bn256::{Bn256, G1Affine};
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+use rand_xorshift::XorShiftRng;
+use std::path::PathBuf;
+
+fn vectors_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/tests/systems/halo2/golden_test/vectors")
+}
+
+fn parse(
+    pk: &ProvingKey<G1Affine>,
+    params: &ParamsVerifier<Bn256>,
+    instances: &[&[&[Fp]]],
+    proof: &[u8],
+) -> VerifierParamsDump {
+    let nchip = MockFieldChip::<Fp, Error>::default();
+    let schip = MockFieldChip::<Fp, Error>::default();
+    let pchip = MockEccChip::<G1Affine, Error>::default();
+    let ctx = &mut MockChipCtx::default();
+
+    let (_, assigned_instances) =
+        assign_instance_commitment::<Bn256, _>(ctx, &schip, &pchip, instances, pk.get_vk(), params)
+            .unwrap();
+    let mut transcript =
+        PoseidonTranscriptRead::<_, G1Affine, _, PoseidonEncode, 9usize, 8usize>::new(
+            proof, ctx, &nchip, 8usize, 33usize,
+        )
+        .unwrap();
+    build_verifier_params(
+        ctx,
+        &nchip,
+        &schip,
+        &pchip,
+        assigned_instances,
+        pk.get_vk(),
+        params,
+        &mut transcript,
+        "p0".to_owned(),
+    )
+    .unwrap()
+    .dump()
+}
+
+fn check_golden<ConcreteCircuit: Circuit<Fp>>(
+    name: &str,
+    k: u32,
+    circuit: ConcreteCircuit,
+    instances: &[Fp],
+) {
+    let params = Params::<G1Affine>::unsafe_setup::<Bn256>(k);
+    let vk = keygen_vk(&params, &circuit).unwrap();
+    let pk = keygen_pk(&params, vk, &circuit).unwrap();
+    let params_verifier = params.verifier(instances.len()).unwrap();
+    let instances: &[&[&[Fp]]] = &[&[instances]];
+
+    let proof_path = vectors_dir().join(format!("{}.proof", name));
+    let json_path = vectors_dir().join(format!("{}.json", name));
+    let update = std::env::var("GOLDEN_UPDATE").map_or(false, |v| v == "1");
+    if !update && !(proof_path.exists() && json_path.exists()) {
+        panic!(
+            "{} has no golden vector under {}, write it with GOLDEN_UPDATE=1 and commit it",
+            name,
+            vectors_dir().display()
+        );
+    }
+
+    let proof = if update {
+        let mut transcript = PoseidonWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            &[circuit],
+            instances,
+            Pcg32::seed_from_u64(0),
+            &mut transcript,
+        )
+        .unwrap();
+        transcript.finalize()
+    } else {
+        std::fs::read(&proof_path).unwrap()
+    };
+    let json =
+        serde_json::to_string_pretty(&parse(&pk, &params_verifier, instances, &proof[..])).unwrap();
+
+    if update {
+        std::fs::create_dir_all(vectors_dir()).unwrap();
+        std::fs::write(&proof_path, &proof).unwrap();
+        std::fs::write(&json_path, &json).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&json_path).unwrap();
+    if let Some((line, (got, want))) = json
+        .lines()
+        .zip(expected.lines())
+        .enumerate()
+        .find(|(_, (got, want))| got != want)
+    {
+        panic!(
+            "{} parses differently from its golden vector at line {}:\n  got  {}\n  want {}\n\
+             rerun with GOLDEN_UPDATE=1 if the change is intended",
+            name,
+            line + 1,
+            got.trim(),
+            want.trim()
+        );
+    }
+    assert_eq!(json.lines().count(), expected.lines().count(), "{}", name);
+}
+
+#[test]
+fn test_golden_add_mul() {
+    let mut rng = XorShiftRng::seed_from_u64(0);
+    let a = Fp::random(&mut rng);
+    let b = Fp::random(&mut rng);
+    let c = Fp::from(7) * a.square() * b.square();
+    check_golden(
+        "add_mul",
+        10,
+        add_mul_test::test_circuit::test_circuit_builder(a, b),
+        &[c],
+    );
+}
+
+#[test]
+fn test_golden_lookup() {
+    let odd_lookup = [1u64, 3, 5, 7, 9].map(Fp::from);
+    check_golden(
+        "lookup",
+        6,
+        lookup_test::test_circuit::test_circuit_builder(),
+        &odd_lookup[..],
+    );
+}
+
+#[test]
+fn test_dump_json_round_trip() {
+    let circuit = lookup_test::test_circuit::test_circuit_builder();
+    let params = Params::<G1Affine>::unsafe_setup::<Bn256>(6);
+    let vk = keygen_vk(&params, &circuit).unwrap();
+    let pk = keygen_pk(&params, vk, &circuit).unwrap();
+    let odd_lookup = [1u64, 3, 5, 7, 9].map(Fp::from);
+    let instances: &[&[&[Fp]]] = &[&[&odd_lookup[..]]];
+
+    let mut transcript = PoseidonWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(
+        &params,
+        &pk,
+        &[circuit],
+        instances,
+        Pcg32::seed_from_u64(0),
+        &mut transcript,
+    )
+    .unwrap();
+    let proof = transcript.finalize();
+    let dump = parse(&pk, &params.verifier(5).unwrap(), instances, &proof[..]);
+
+    let json = serde_json::to_string(&dump).unwrap();
+    assert_eq!(
+        serde_json::from_str::<VerifierParamsDump>(&json).unwrap(),
+        dump
+    );
+
+    // the hex decodes back to the points and scalars
+    let bytes = |hex: &str| {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect::<Vec<_>>()
+    };
+    let mut repr = <G1Affine as GroupEncoding>::Repr::default();
+    repr.as_mut().copy_from_slice(&bytes(&dump.w[0]));
+    assert!(bool::from(G1Affine::from_bytes(&repr).is_some()));
+    let mut repr = <Fp as PrimeField>::Repr::default();
+    repr.as_mut().copy_from_slice(&bytes(&dump.x));
+    assert!(bool::from(Fp::from_repr(repr).is_some()));
+    assert_eq!(dump.lookup_evaluated[0].len(), 1);
+}
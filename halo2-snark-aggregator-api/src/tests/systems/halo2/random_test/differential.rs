@@ -0,0 +1,207 @@
+use super::test_circuit::RandomCircuit;
+use crate::{
+    mock::{
+        arith::{
+            ecc::MockEccChip,
+            field::{MockChipCtx, MockFieldChip},
+        },
+        transcript_encode::PoseidonEncode,
+    },
+    systems::{
+        halo2::{
+            diff::{first_query_mismatch, native_queries, replay_native},
+            transcript::PoseidonTranscriptRead,
+            verify::{
+                assign_instance_commitment, build_verifier_params, verify_single_proof_in_chip,
+                CircuitProof, ProofData,
+            },
+        },
+        pairing::multi_pairing_native,
+    },
+};
+use group::Curve;
+use halo2_proofs::{
+    pairing::bn256::Fr as Fp,
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Error, SingleVerifier},
+    poly::commitment::{Params, ParamsVerifier},
+    transcript::{Challenge255, PoseidonRead, PoseidonWrite},
+};
+use pairing_bn256::bn256::{Bn256, G1Affine};
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+use std::collections::BTreeSet;
+use std::marker::PhantomData;
+
+const K: u32 = 6;
+
+type Transcript<'a> =
+    PoseidonTranscriptRead<&'a [u8], G1Affine, MockEccChip<G1Affine, Error>, PoseidonEncode, 9, 8>;
+
+fn transcript<'a>(
+    proof: &'a [u8],
+    ctx: &mut MockChipCtx,
+    nchip: &MockFieldChip<Fp, Error>,
+) -> Transcript<'a> {
+    Transcript::new(proof, ctx, nchip, 8usize, 33usize).unwrap()
+}
+
+// Checks the api verifier against halo2's verify_proof on the random circuit of SEED: the
+// transcript values and evaluations, the rotations of the query set and the accumulator.
+pub fn test_random_circuit_against_native<const SEED: u64>() {
+    let params = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+    let vk = keygen_vk(&params, &RandomCircuit::<SEED>).unwrap();
+    let pk = keygen_pk(&params, vk, &RandomCircuit::<SEED>).unwrap();
+    let vk = pk.get_vk();
+    let instances = RandomCircuit::<SEED>::instances();
+    let instances: &[&[&[Fp]]] = &[&[&instances[..]]];
+
+    let mut writer = PoseidonWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(
+        &params,
+        &pk,
+        &[RandomCircuit::<SEED>],
+        instances,
+        Pcg32::seed_from_u64(SEED),
+        &mut writer,
+    )
+    .unwrap();
+    let proof = writer.finalize();
+    let params_verifier: ParamsVerifier<Bn256> = params.verifier(instances[0][0].len()).unwrap();
+
+    // the proof is valid for halo2 itself
+    let strategy = SingleVerifier::new(&params_verifier);
+    let mut reader = PoseidonRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    verify_proof(&params_verifier, vk, strategy, instances, &mut reader).unwrap();
+
+    let nchip = MockFieldChip::<Fp, Error>::default();
+    let schip = MockFieldChip::<Fp, Error>::default();
+    let pchip = MockEccChip::<G1Affine, Error>::default();
+    let ctx = &mut MockChipCtx::default();
+
+    // every transcript value and evaluation, in the read order of halo2's verifier
+    let (_, assigned_instances) = assign_instance_commitment::<Bn256, _>(
+        ctx,
+        &schip,
+        &pchip,
+        instances,
+        vk,
+        &params_verifier,
+    )
+    .unwrap();
+    let chip_params = build_verifier_params(
+        ctx,
+        &nchip,
+        &schip,
+        &pchip,
+        assigned_instances,
+        vk,
+        &params_verifier,
+        &mut transcript(&proof[..], ctx, &nchip),
+        "p0".to_owned(),
+    )
+    .unwrap();
+    let native = replay_native(vk, &params_verifier, instances, &proof[..]).unwrap();
+    if let Some(divergence) = native.first_divergence(&chip_params.diff_trace()) {
+        panic!("seed {}: {}", SEED, divergence);
+    }
+
+    // the rotations halo2 opens: the queried cells, the permutation products at the next
+    // and, when chained, the last row, the lookup products at the next row and the
+    // permuted inputs at the previous one
+    let cs = &vk.cs;
+    let sets = cs.permutation.columns.chunks(cs.degree() - 2).len();
+    let mut native_rotations = cs
+        .instance_queries
+        .iter()
+        .map(|(_, r)| r.0)
+        .chain(cs.advice_queries.iter().map(|(_, r)| r.0))
+        .chain(cs.fixed_queries.iter().map(|(_, r)| r.0))
+        .chain([0])
+        .collect::<BTreeSet<_>>();
+    if sets > 0 || !cs.lookups.is_empty() {
+        native_rotations.insert(1);
+    }
+    if sets > 1 {
+        native_rotations.insert(-(cs.blinding_factors() as i32 + 1));
+    }
+    if !cs.lookups.is_empty() {
+        native_rotations.insert(-1);
+    }
+    let rotations = chip_params
+        .queries(ctx, &schip)
+        .unwrap()
+        .iter()
+        .map(|q| q.rotation)
+        .collect::<BTreeSet<_>>();
+    assert_eq!(rotations, native_rotations, "seed {}", SEED);
+
+    // and the whole ordered query list, commitments and evals included
+    let native_queries = native_queries(vk, &params_verifier, instances, &proof[..]).unwrap();
+    let in_chip_queries = chip_params.query_snapshot(ctx, &schip).unwrap();
+    assert_eq!(
+        first_query_mismatch(&native_queries, &in_chip_queries),
+        None,
+        "seed {}",
+        SEED
+    );
+
+    // the accumulator passes the pairing check halo2 runs on its own
+    let owned_instances = vec![vec![instances[0][0].to_vec()]];
+    let pdata = ProofData {
+        instances: &owned_instances,
+        transcript: transcript(&proof[..], ctx, &nchip),
+        key: "p0".to_owned(),
+        _phantom: PhantomData,
+    };
+    let mut proof_transcript = transcript(&proof[..], ctx, &nchip);
+    let (w_x, w_g, _, _) = verify_single_proof_in_chip(
+        ctx,
+        &nchip,
+        &schip,
+        &pchip,
+        &mut CircuitProof {
+            name: format!("random_circuit_{}", SEED),
+            vk,
+            params: &params_verifier,
+            proofs: vec![pdata],
+        },
+        &mut proof_transcript,
+    )
+    .unwrap();
+    assert!(
+        multi_pairing_native::<Bn256>(&[
+            (w_x.to_affine(), params_verifier.s_g2),
+            (w_g.to_affine(), -params_verifier.g2),
+        ]),
+        "seed {}",
+        SEED
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // one test per seed, the seed is a const parameter of the circuit
+    macro_rules! random_circuit_tests {
+        ($($name:ident: $seed:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    test_random_circuit_against_native::<$seed>();
+                }
+            )*
+        };
+    }
+
+    random_circuit_tests! {
+        test_random_circuit_0: 0,
+        test_random_circuit_1: 1,
+        test_random_circuit_2: 2,
+        test_random_circuit_3: 3,
+        test_random_circuit_4: 4,
+        test_random_circuit_5: 5,
+        test_random_circuit_6: 6,
+        test_random_circuit_7: 7,
+    }
+}
@@ -0,0 +1,3 @@
+#[cfg(feature = "differential")]
+pub mod differential;
+pub mod test_circuit;
@@ -0,0 +1,247 @@
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner},
+    pairing::bn256::Fr as Fp,
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector,
+    },
+    poly::Rotation,
+};
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+// Values of the lookup table, 0..TABLE_SIZE.
+const TABLE_SIZE: u64 = 16;
+
+// A gate q * (k_ab * a * b + k_a * a + k_b * b + k_f * f + k - c) = 0, with a queried at the
+// next row when rotate_a, enabled on rows rows of its own region.
+#[derive(Clone, Debug)]
+pub struct RandomGate {
+    pub coeffs: [Fp; 5],
+    pub rotate_a: bool,
+    pub rows: usize,
+}
+
+// Everything of a random circuit derives from its seed, the gates and the witness alike,
+// since halo2 configures a circuit without it.
+#[derive(Clone, Debug)]
+pub struct RandomShape {
+    pub gates: Vec<RandomGate>,
+    // lookup of advice a into a fixed table of 0..TABLE_SIZE
+    pub lookup: usize,
+    // copies the c of a gate into the a of the next one, equality on a, b and f too
+    pub permutation: bool,
+    rng: XorShiftRng,
+}
+
+impl RandomShape {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = XorShiftRng::seed_from_u64(seed);
+        let gates = (0..rng.gen_range(1..=3))
+            .map(|_| RandomGate {
+                coeffs: [(); 5].map(|_| Fp::random(&mut rng)),
+                rotate_a: rng.gen(),
+                rows: rng.gen_range(1..=4),
+            })
+            .collect();
+        let lookup = rng.gen_range(0..=4);
+        let permutation = rng.gen();
+        RandomShape {
+            gates,
+            lookup,
+            permutation,
+            rng,
+        }
+    }
+
+    fn value(&mut self) -> Fp {
+        Fp::random(&mut self.rng)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RandomConfig {
+    a: Column<Advice>,
+    b: Column<Advice>,
+    c: Column<Advice>,
+    f: Column<Fixed>,
+    table: Column<Fixed>,
+    instance: Column<Instance>,
+    gates: Vec<Selector>,
+    q_lookup: Selector,
+}
+
+// The random circuit of the seed SEED, a const parameter so that configure can see it.
+#[derive(Default)]
+pub struct RandomCircuit<const SEED: u64>;
+
+// (a, b, c, f) of the rows of a gate, and the a of the row after them when the gate
+// rotates it.
+struct GateWitness {
+    rows: Vec<(Fp, Fp, Fp, Fp)>,
+    next_a: Option<Fp>,
+}
+
+impl<const SEED: u64> RandomCircuit<SEED> {
+    // The c of the last row of each gate, exposed in that order.
+    pub fn instances() -> Vec<Fp> {
+        Self::witness()
+            .iter()
+            .map(|gate| gate.rows.last().unwrap().2)
+            .collect()
+    }
+
+    fn witness() -> Vec<GateWitness> {
+        let mut shape = RandomShape::new(SEED);
+        let mut last_c = None;
+        shape
+            .gates
+            .clone()
+            .iter()
+            .map(|gate| {
+                let [k_ab, k_a, k_b, k_f, k] = gate.coeffs;
+                let extra = gate.rotate_a as usize;
+                let mut a = (0..gate.rows + extra)
+                    .map(|_| shape.value())
+                    .collect::<Vec<_>>();
+                if let (true, Some(c)) = (shape.permutation, last_c) {
+                    a[0] = c;
+                }
+                let rows = (0..gate.rows)
+                    .map(|i| {
+                        let (b, f) = (shape.value(), shape.value());
+                        let a_q = a[i + extra];
+                        (
+                            a[i],
+                            b,
+                            k_ab * a_q * b + k_a * a_q + k_b * b + k_f * f + k,
+                            f,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                last_c = Some(rows.last().unwrap().2);
+                GateWitness {
+                    rows,
+                    next_a: (extra == 1).then(|| a[gate.rows]),
+                }
+            })
+            .collect()
+    }
+}
+
+impl<const SEED: u64> Circuit<Fp> for RandomCircuit<SEED> {
+    type Config = RandomConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let shape = RandomShape::new(SEED);
+        let config = RandomConfig {
+            a: meta.advice_column(),
+            b: meta.advice_column(),
+            c: meta.advice_column(),
+            f: meta.fixed_column(),
+            table: meta.fixed_column(),
+            instance: meta.instance_column(),
+            gates: shape.gates.iter().map(|_| meta.selector()).collect(),
+            q_lookup: meta.complex_selector(),
+        };
+
+        meta.enable_equality(config.c);
+        meta.enable_equality(config.instance);
+        if shape.permutation {
+            meta.enable_equality(config.a);
+            meta.enable_equality(config.b);
+            meta.enable_equality(config.f);
+        }
+
+        for (gate, selector) in shape.gates.iter().zip(config.gates.iter()) {
+            meta.create_gate("random gate", |meta| {
+                let q = meta.query_selector(*selector);
+                let rotation = if gate.rotate_a {
+                    Rotation::next()
+                } else {
+                    Rotation::cur()
+                };
+                let a = meta.query_advice(config.a, rotation);
+                let b = meta.query_advice(config.b, Rotation::cur());
+                let c = meta.query_advice(config.c, Rotation::cur());
+                let f = meta.query_fixed(config.f, Rotation::cur());
+                let [k_ab, k_a, k_b, k_f, k] = gate.coeffs.map(Expression::Constant);
+                vec![q * (k_ab * a.clone() * b.clone() + k_a * a + k_b * b + k_f * f + k - c)]
+            });
+        }
+
+        if shape.lookup > 0 {
+            meta.lookup_any("random lookup", |meta| {
+                let q = meta.query_selector(config.q_lookup);
+                let a = meta.query_advice(config.a, Rotation::cur());
+                let table = meta.query_fixed(config.table, Rotation::cur());
+                vec![(q * a, table)]
+            });
+        }
+
+        config
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let mut shape = RandomShape::new(SEED);
+        let witness = Self::witness();
+        let mut last_c: Option<AssignedCell<Fp, Fp>> = None;
+
+        for (i, (gate, selector)) in witness.iter().zip(config.gates.iter()).enumerate() {
+            let cell = layouter.assign_region(
+                || "random gate",
+                |mut region| {
+                    let mut c_cells = vec![];
+                    for (row, (a, b, c, f)) in gate.rows.iter().enumerate() {
+                        selector.enable(&mut region, row)?;
+                        let a_cell = region.assign_advice(|| "a", config.a, row, || Ok(*a))?;
+                        region.assign_advice(|| "b", config.b, row, || Ok(*b))?;
+                        c_cells.push(region.assign_advice(|| "c", config.c, row, || Ok(*c))?);
+                        region.assign_fixed(|| "f", config.f, row, || Ok(*f))?;
+                        if let (0, true, Some(c)) = (row, shape.permutation, last_c.as_ref()) {
+                            region.constrain_equal(a_cell.cell(), c.cell())?;
+                        }
+                    }
+                    if let Some(a) = gate.next_a {
+                        region.assign_advice(|| "a", config.a, gate.rows.len(), || Ok(a))?;
+                    }
+                    Ok(c_cells.pop().unwrap())
+                },
+            )?;
+            layouter.constrain_instance(cell.cell(), config.instance, i)?;
+            last_c = Some(cell);
+        }
+
+        if shape.lookup > 0 {
+            let lookup = shape.lookup;
+            let values = (0..lookup)
+                .map(|_| Fp::from(shape.rng.gen_range(0..TABLE_SIZE)))
+                .collect::<Vec<_>>();
+            layouter.assign_region(
+                || "random lookup",
+                |mut region| {
+                    for offset in 0..TABLE_SIZE as usize {
+                        let value = || Ok(Fp::from(offset as u64));
+                        region.assign_fixed(|| "table", config.table, offset, value)?;
+                    }
+                    for (offset, value) in values.iter().enumerate() {
+                        config.q_lookup.enable(&mut region, offset)?;
+                        region.assign_advice(|| "a", config.a, offset, || Ok(*value))?;
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,2 @@
+pub mod test_circuit;
+pub mod verify_aggregation;
@@ -0,0 +1,151 @@
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Layouter, SimpleFloorPlanner},
+    pairing::bn256::Fr as Fp,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+
+// Values of the lookup table, 0..TABLE_SIZE.
+const TABLE_SIZE: u64 = 16;
+
+// Rows of the lookup region.
+const LOOKUP_ROWS: usize = 4;
+
+#[derive(Clone, Debug)]
+pub struct ShapeConfig {
+    advice: Vec<Column<Advice>>,
+    table: Column<Fixed>,
+    instance: Option<Column<Instance>>,
+    q: Selector,
+    q_lookup: Selector,
+}
+
+// A circuit of a given shape: ADVICE advice columns, LOOKUPS lookups of the advice columns into
+// a fixed table, one gate q * (a_0^(DEGREE - 1) + a_1 + ... + a_{ADVICE - 1} - a_0[next]) = 0 of
+// degree DEGREE, and the a_0 of the next row exposed on an instance column when INSTANCE.
+#[derive(Default)]
+pub struct ShapeCircuit<
+    const LOOKUPS: usize,
+    const ADVICE: usize,
+    const DEGREE: usize,
+    const INSTANCE: bool,
+>;
+
+impl<const LOOKUPS: usize, const ADVICE: usize, const DEGREE: usize, const INSTANCE: bool>
+    ShapeCircuit<LOOKUPS, ADVICE, DEGREE, INSTANCE>
+{
+    // a_0, ..., a_{ADVICE - 1} of the gate row and the a_0 of the next one.
+    fn witness() -> (Vec<Fp>, Fp) {
+        let row = (0..ADVICE as u64)
+            .map(|i| if i == 0 { Fp::from(2) } else { Fp::from(i) })
+            .collect::<Vec<_>>();
+        let power = row[0].pow(&[DEGREE as u64 - 1, 0, 0, 0]);
+        let next = row[1..].iter().fold(power, |acc, a| acc + a);
+        (row, next)
+    }
+
+    // The instance columns of one proof, empty without INSTANCE.
+    pub fn instances() -> Vec<Vec<Fp>> {
+        if INSTANCE {
+            vec![vec![Self::witness().1]]
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl<const LOOKUPS: usize, const ADVICE: usize, const DEGREE: usize, const INSTANCE: bool>
+    Circuit<Fp> for ShapeCircuit<LOOKUPS, ADVICE, DEGREE, INSTANCE>
+{
+    type Config = ShapeConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        assert!(ADVICE >= 1 && DEGREE >= 2);
+        let config = ShapeConfig {
+            advice: (0..ADVICE).map(|_| meta.advice_column()).collect(),
+            table: meta.fixed_column(),
+            instance: INSTANCE.then(|| meta.instance_column()),
+            q: meta.selector(),
+            q_lookup: meta.complex_selector(),
+        };
+
+        if let Some(instance) = config.instance {
+            meta.enable_equality(config.advice[0]);
+            meta.enable_equality(instance);
+        }
+
+        meta.create_gate("shape gate", |meta| {
+            let q = meta.query_selector(config.q);
+            let a = config
+                .advice
+                .iter()
+                .map(|column| meta.query_advice(*column, Rotation::cur()))
+                .collect::<Vec<_>>();
+            let next = meta.query_advice(config.advice[0], Rotation::next());
+            let power = (1..DEGREE - 1).fold(a[0].clone(), |acc, _| acc * a[0].clone());
+            let sum = a[1..].iter().fold(power, |acc, a| acc + a.clone());
+            vec![q * (sum - next)]
+        });
+
+        for i in 0..LOOKUPS {
+            let column = config.advice[i % ADVICE];
+            meta.lookup_any("shape lookup", |meta| {
+                let q = meta.query_selector(config.q_lookup);
+                let a = meta.query_advice(column, Rotation::cur());
+                let table = meta.query_fixed(config.table, Rotation::cur());
+                vec![(q * a, table)]
+            });
+        }
+
+        config
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let (row, next) = Self::witness();
+        let cell = layouter.assign_region(
+            || "shape gate",
+            |mut region| {
+                config.q.enable(&mut region, 0)?;
+                for (column, value) in config.advice.iter().zip(row.iter()) {
+                    region.assign_advice(|| "a", *column, 0, || Ok(*value))?;
+                }
+                region.assign_advice(|| "next", config.advice[0], 1, || Ok(next))
+            },
+        )?;
+        if let Some(instance) = config.instance {
+            layouter.constrain_instance(cell.cell(), instance, 0)?;
+        }
+
+        if LOOKUPS > 0 {
+            layouter.assign_region(
+                || "shape lookup",
+                |mut region| {
+                    for offset in 0..TABLE_SIZE as usize {
+                        let value = || Ok(Fp::from(offset as u64));
+                        region.assign_fixed(|| "table", config.table, offset, value)?;
+                    }
+                    for offset in 0..LOOKUP_ROWS {
+                        config.q_lookup.enable(&mut region, offset)?;
+                        for (i, column) in config.advice.iter().enumerate() {
+                            let value = Fp::from(((offset + i) as u64) % TABLE_SIZE);
+                            region.assign_advice(|| "a", *column, offset, || Ok(value))?;
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use std::marker::PhantomData;
+
+use crate::{
+    arith::{common::ArithCommonChip, ecc::ArithEccChip, field::ArithFieldChip},
+    systems::{
+        halo2::{
+            transcript::PoseidonTranscriptRead,
+            verify::{verify_aggregation_proofs_in_chip, CircuitProof, ProofData},
+        },
+        pairing::multi_pairing_native,
+    },
+    tests::systems::halo2::shape_test::test_circuit::ShapeCircuit,
+    transcript::encode::Encode,
+};
+use halo2_proofs::arithmetic::CurveAffine;
+use halo2_proofs::{
+    pairing::bn256::Fr as Fp,
+    plonk::{create_proof, keygen_pk, keygen_vk},
+    poly::commitment::{Params, ParamsVerifier},
+    transcript::{Challenge255, PoseidonWrite},
+};
+use pairing_bn256::bn256::{Bn256, G1Affine};
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+
+const K: u32 = 6;
+const NPROOFS: usize = 2usize;
+
+// Aggregates NPROOFS proofs of the shape circuit and checks the pairing of the accumulator.
+pub fn test_verify_shape_aggregation_in_chip<
+    ScalarChip,
+    NativeChip,
+    EccChip,
+    EncodeChip: Encode<EccChip>,
+    const LOOKUPS: usize,
+    const ADVICE: usize,
+    const DEGREE: usize,
+    const INSTANCE: bool,
+>(
+    nchip: &NativeChip,
+    schip: &ScalarChip,
+    pchip: &EccChip,
+    ctx: &mut <EccChip as ArithCommonChip>::Context,
+) where
+    NativeChip: ArithFieldChip<Field = <G1Affine as CurveAffine>::ScalarExt>,
+    ScalarChip: ArithFieldChip<Field = <G1Affine as CurveAffine>::ScalarExt>,
+    EccChip: ArithEccChip<
+        Point = G1Affine,
+        Scalar = ScalarChip::Field,
+        Native = NativeChip::Field,
+        NativeChip = NativeChip,
+        ScalarChip = ScalarChip,
+        Error = halo2_proofs::plonk::Error,
+    >,
+{
+    let name = format!("shape_{}_{}_{}_{}", LOOKUPS, ADVICE, DEGREE, INSTANCE);
+    let circuit = ShapeCircuit::<LOOKUPS, ADVICE, DEGREE, INSTANCE>::default();
+    let params = Params::<G1Affine>::unsafe_setup::<Bn256>(K);
+    let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk, &circuit).expect("keygen_pk should not fail");
+
+    let instances = vec![ShapeCircuit::<LOOKUPS, ADVICE, DEGREE, INSTANCE>::instances()];
+    let columns: Vec<&[Fp]> = instances[0].iter().map(|column| &column[..]).collect();
+    let public_inputs_size = instances[0].iter().map(|column| column.len()).sum();
+    let params_verifier: &ParamsVerifier<Bn256> = &params.verifier(public_inputs_size).unwrap();
+
+    let n_proof = (0..NPROOFS)
+        .map(|i| {
+            let mut transcript = PoseidonWrite::<_, _, Challenge255<_>>::init(vec![]);
+            create_proof(
+                &params,
+                &pk,
+                &[ShapeCircuit::<LOOKUPS, ADVICE, DEGREE, INSTANCE>::default()],
+                &[&columns[..]],
+                Pcg32::seed_from_u64(i as u64),
+                &mut transcript,
+            )
+            .expect("proof generation should not fail");
+            transcript.finalize()
+        })
+        .collect::<Vec<_>>();
+
+    let proof_data_list = n_proof
+        .iter()
+        .enumerate()
+        .map(|(i, proof)| ProofData {
+            instances: &instances,
+            transcript: PoseidonTranscriptRead::<_, G1Affine, _, EncodeChip, 9usize, 8usize>::new(
+                &proof[..],
+                ctx,
+                nchip,
+                8usize,
+                33usize,
+            )
+            .unwrap(),
+            key: format!("p{}", i),
+            _phantom: PhantomData,
+        })
+        .collect::<Vec<ProofData<Bn256, _, _>>>();
+
+    let empty_vec = vec![];
+    let mut transcript = PoseidonTranscriptRead::<_, G1Affine, _, EncodeChip, 9usize, 8usize>::new(
+        &empty_vec[..],
+        ctx,
+        nchip,
+        8usize,
+        33usize,
+    )
+    .unwrap();
+    let (w_x, w_g, _, _) = verify_aggregation_proofs_in_chip(
+        ctx,
+        nchip,
+        schip,
+        pchip,
+        vec![CircuitProof {
+            name: name.clone(),
+            vk: pk.get_vk(),
+            params: &params_verifier,
+            proofs: proof_data_list,
+        }],
+        &mut transcript,
+    )
+    .unwrap();
+
+    assert!(
+        multi_pairing_native::<Bn256>(&[
+            (pchip.to_value(&w_x).unwrap(), params_verifier.s_g2),
+            (pchip.to_value(&w_g).unwrap(), -params_verifier.g2),
+        ]),
+        "{}",
+        name
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{
+        arith::{
+            ecc::MockEccChip,
+            field::{MockChipCtx, MockFieldChip},
+        },
+        transcript_encode::PoseidonEncode,
+    };
+    use halo2_proofs::plonk::Error;
+
+    fn run<const LOOKUPS: usize, const ADVICE: usize, const DEGREE: usize, const INSTANCE: bool>() {
+        let nchip = MockFieldChip::default();
+        let schip = MockFieldChip::default();
+        let pchip = MockEccChip::default();
+        let ctx = &mut MockChipCtx::default();
+        test_verify_shape_aggregation_in_chip::<
+            MockFieldChip<Fp, Error>,
+            MockFieldChip<Fp, Error>,
+            MockEccChip<G1Affine, Error>,
+            PoseidonEncode,
+            LOOKUPS,
+            ADVICE,
+            DEGREE,
+            INSTANCE,
+        >(&nchip, &schip, &pchip, ctx);
+    }
+
+    // One test per row of the shape matrix, name: (lookups, advice columns, gate degree, instance
+    // column), each running $run::<lookups, advice, degree, instance>().
+    macro_rules! shape_matrix_tests {
+        ($run:ident; $($name:ident: ($lookups:literal, $advice:literal, $degree:literal,
+            $instance:literal),)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    $run::<$lookups, $advice, $degree, $instance>();
+                }
+            )*
+        };
+    }
+
+    shape_matrix_tests! { run;
+        test_shape_0_lookups_1_advice_degree_2: (0, 1, 2, true),
+        test_shape_0_lookups_1_advice_degree_2_no_instance: (0, 1, 2, false),
+        test_shape_0_lookups_1_advice_degree_5: (0, 1, 5, true),
+        test_shape_0_lookups_1_advice_degree_5_no_instance: (0, 1, 5, false),
+        test_shape_0_lookups_5_advice_degree_2: (0, 5, 2, true),
+        test_shape_0_lookups_5_advice_degree_2_no_instance: (0, 5, 2, false),
+        test_shape_0_lookups_5_advice_degree_5: (0, 5, 5, true),
+        test_shape_0_lookups_5_advice_degree_5_no_instance: (0, 5, 5, false),
+        test_shape_1_lookup_1_advice_degree_2: (1, 1, 2, true),
+        test_shape_1_lookup_1_advice_degree_2_no_instance: (1, 1, 2, false),
+        test_shape_1_lookup_1_advice_degree_5: (1, 1, 5, true),
+        test_shape_1_lookup_1_advice_degree_5_no_instance: (1, 1, 5, false),
+        test_shape_1_lookup_5_advice_degree_2: (1, 5, 2, true),
+        test_shape_1_lookup_5_advice_degree_2_no_instance: (1, 5, 2, false),
+        test_shape_1_lookup_5_advice_degree_5: (1, 5, 5, true),
+        test_shape_1_lookup_5_advice_degree_5_no_instance: (1, 5, 5, false),
+        test_shape_3_lookups_1_advice_degree_2: (3, 1, 2, true),
+        test_shape_3_lookups_1_advice_degree_2_no_instance: (3, 1, 2, false),
+        test_shape_3_lookups_1_advice_degree_5: (3, 1, 5, true),
+        test_shape_3_lookups_1_advice_degree_5_no_instance: (3, 1, 5, false),
+        test_shape_3_lookups_5_advice_degree_2: (3, 5, 2, true),
+        test_shape_3_lookups_5_advice_degree_2_no_instance: (3, 5, 2, false),
+        test_shape_3_lookups_5_advice_degree_5: (3, 5, 5, true),
+        test_shape_3_lookups_5_advice_degree_5_no_instance: (3, 5, 5, false),
+    }
+}
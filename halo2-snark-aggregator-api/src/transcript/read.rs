@@ -1,6 +1,10 @@
 use crate::arith::ecc::ArithEccChip;
 
 pub trait TranscriptRead<A: ArithEccChip> {
+    // Describes the items read next, e.g. "7 advice commitments for proof 2", for the errors
+    // of a reader that reports them.
+    fn expect(&mut self, _items: String) {}
+
     fn read_point(
         &mut self,
         ctx: &mut A::Context,
@@ -15,6 +19,31 @@ pub trait TranscriptRead<A: ArithEccChip> {
         schip: &A::ScalarChip,
     ) -> Result<A::AssignedScalar, A::Error>;
 
+    // n points in a row, a reader can decode them all before assigning any
+    fn read_n_points(
+        &mut self,
+        ctx: &mut A::Context,
+        nchip: &A::NativeChip,
+        schip: &A::ScalarChip,
+        pchip: &A,
+        n: usize,
+    ) -> Result<Vec<A::AssignedPoint>, A::Error> {
+        (0..n)
+            .map(|_| self.read_point(ctx, nchip, schip, pchip))
+            .collect()
+    }
+    fn read_n_scalars(
+        &mut self,
+        ctx: &mut A::Context,
+        nchip: &A::NativeChip,
+        schip: &A::ScalarChip,
+        n: usize,
+    ) -> Result<Vec<A::AssignedScalar>, A::Error> {
+        (0..n)
+            .map(|_| self.read_scalar(ctx, nchip, schip))
+            .collect()
+    }
+
     fn read_constant_point(
         &mut self,
         ctx: &mut A::Context,
@@ -4,7 +4,7 @@ use halo2_proofs::arithmetic::BaseExt;
 use halo2_proofs::arithmetic::Coordinates;
 use halo2_proofs::arithmetic::CurveAffine;
 use halo2_proofs::arithmetic::Field;
-use halo2_proofs::transcript::Challenge255;
+use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::transcript::EncodedChallenge;
 use halo2_proofs::transcript::Transcript;
 use halo2_proofs::transcript::TranscriptRead;
@@ -20,6 +20,27 @@ const SHA_PREFIX_POINT: u8 = 1;
 const SHA_PREFIX_SCALAR: u8 = 2;
 ///
 
+/// A challenge keeping only the low 128 bits of the digest, for the native sha
+/// transcripts only: the in-circuit poseidon transcript and the generated
+/// contracts still squeeze `Challenge255`.
+#[derive(Copy, Clone, Debug)]
+pub struct Challenge128<C: CurveAffine>(u128, PhantomData<C>);
+
+impl<C: CurveAffine> EncodedChallenge<C> for Challenge128<C> {
+    type Input = [u8; 64];
+
+    fn new(challenge_input: &[u8; 64]) -> Self {
+        Challenge128(
+            u128::from_le_bytes(challenge_input[..16].try_into().unwrap()),
+            PhantomData,
+        )
+    }
+
+    fn get_scalar(&self) -> C::Scalar {
+        C::Scalar::from_u128(self.0)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ShaRead<R: Read, C: CurveAffine, E: EncodedChallenge<C>, D: Digest> {
     state: D,
@@ -38,8 +59,36 @@ impl<R: Read, C: CurveAffine, E: EncodedChallenge<C>, D: Digest> ShaRead<R, C, E
     }
 }
 
-impl<R: Read, C: CurveAffine, D: Digest + Clone> TranscriptRead<C, Challenge255<C>>
-    for ShaRead<R, C, Challenge255<C>, D>
+/// Scalar absorbed first by a transcript initialized with a deployment label, so that proofs
+/// made for one deployment do not verify against the contract of another.
+pub fn label_scalar<C: CurveAffine>(label: &[u8]) -> C::Scalar {
+    let mut hasher = blake2b_simd::Params::new()
+        .hash_length(64)
+        .personal(b"H2AG-Trans-Label")
+        .to_state();
+    hasher.update(&(label.len() as u64).to_le_bytes());
+    hasher.update(label);
+    C::Scalar::from_bytes_wide(hasher.finalize().as_array())
+}
+
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>, D: Digest + Clone>
+    ShaRead<R, C, E, D>
+{
+    /// Initialize a transcript given an input buffer, absorbing `label_scalar(label)` first
+    /// when a label is given.
+    pub fn init_with_label(reader: R, label: Option<&[u8]>) -> Self {
+        let mut transcript = Self::init(reader);
+        if let Some(label) = label {
+            transcript
+                .common_scalar(label_scalar::<C>(label))
+                .expect("absorbing a scalar does not fail");
+        }
+        transcript
+    }
+}
+
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>, D: Digest + Clone>
+    TranscriptRead<C, E> for ShaRead<R, C, E, D>
 {
     fn read_point(&mut self) -> io::Result<C> {
         // let mut compressed = C::Repr::default();
@@ -69,10 +118,10 @@ impl<R: Read, C: CurveAffine, D: Digest + Clone> TranscriptRead<C, Challenge255<
     }
 }
 
-impl<R: Read, C: CurveAffine, D: Digest + Clone> Transcript<C, Challenge255<C>>
-    for ShaRead<R, C, Challenge255<C>, D>
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>, D: Digest + Clone>
+    Transcript<C, E> for ShaRead<R, C, E, D>
 {
-    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+    fn squeeze_challenge(&mut self) -> E {
         self.state.update(&[SHA_PREFIX_CHALLENGE]);
         let hasher = self.state.clone();
         let result: [u8; 32] = hasher.finalize().as_slice().try_into().unwrap();
@@ -82,7 +131,7 @@ impl<R: Read, C: CurveAffine, D: Digest + Clone> Transcript<C, Challenge255<C>>
 
         let mut bytes = result.to_vec();
         bytes.resize(64, 0u8);
-        Challenge255::<C>::new(&bytes.try_into().unwrap())
+        E::new(&bytes.try_into().unwrap())
     }
 
     fn common_point(&mut self, point: C) -> io::Result<()> {
@@ -146,8 +195,24 @@ impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>, D: Digest> ShaWrite<W, C,
     }
 }
 
-impl<W: Write, C: CurveAffine, D: Digest + Clone> TranscriptWrite<C, Challenge255<C>>
-    for ShaWrite<W, C, Challenge255<C>, D>
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>, D: Digest + Clone>
+    ShaWrite<W, C, E, D>
+{
+    /// Initialize a transcript given an output buffer, absorbing `label_scalar(label)` first
+    /// when a label is given.
+    pub fn init_with_label(writer: W, label: Option<&[u8]>) -> Self {
+        let mut transcript = Self::init(writer);
+        if let Some(label) = label {
+            transcript
+                .common_scalar(label_scalar::<C>(label))
+                .expect("absorbing a scalar does not fail");
+        }
+        transcript
+    }
+}
+
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>, D: Digest + Clone>
+    TranscriptWrite<C, E> for ShaWrite<W, C, E, D>
 {
     fn write_point(&mut self, point: C) -> io::Result<()> {
         self.common_point(point)?;
@@ -176,10 +241,10 @@ impl<W: Write, C: CurveAffine, D: Digest + Clone> TranscriptWrite<C, Challenge25
     }
 }
 
-impl<W: Write, C: CurveAffine, D: Digest + Clone> Transcript<C, Challenge255<C>>
-    for ShaWrite<W, C, Challenge255<C>, D>
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>, D: Digest + Clone>
+    Transcript<C, E> for ShaWrite<W, C, E, D>
 {
-    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+    fn squeeze_challenge(&mut self) -> E {
         self.state.update(&[SHA_PREFIX_CHALLENGE]);
         let hasher = self.state.clone();
         let result: [u8; 32] = hasher.finalize().as_slice().try_into().unwrap();
@@ -189,7 +254,7 @@ impl<W: Write, C: CurveAffine, D: Digest + Clone> Transcript<C, Challenge255<C>>
 
         let mut bytes = result.to_vec();
         bytes.resize(64, 0u8);
-        Challenge255::<C>::new(&bytes.try_into().unwrap())
+        E::new(&bytes.try_into().unwrap())
     }
 
     fn common_point(&mut self, point: C) -> io::Result<()> {
@@ -228,3 +293,73 @@ impl<W: Write, C: CurveAffine, D: Digest + Clone> Transcript<C, Challenge255<C>>
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::transcript::Challenge255;
+    use pairing_bn256::bn256::{Fr, G1Affine};
+    use pairing_bn256::group::prime::PrimeCurveAffine;
+
+    fn roundtrip<E: EncodedChallenge<G1Affine, Input = [u8; 64]>>() -> (Fr, Fr) {
+        let point = G1Affine::generator();
+        let scalar = Fr::from(7u64);
+
+        let mut writer = ShaWrite::<_, G1Affine, E, sha2::Sha256>::init(vec![]);
+        writer.write_point(point).unwrap();
+        writer.write_scalar(scalar).unwrap();
+        let written = writer.squeeze_challenge().get_scalar();
+        let proof = writer.finalize();
+
+        let mut reader = ShaRead::<_, G1Affine, E, sha2::Sha256>::init(&proof[..]);
+        assert_eq!(reader.read_point().unwrap(), point);
+        assert_eq!(reader.read_scalar().unwrap(), scalar);
+        let read = reader.squeeze_challenge().get_scalar();
+
+        (written, read)
+    }
+
+    #[test]
+    fn test_sha_transcript_challenge_encodings() {
+        let (written, read) = roundtrip::<Challenge255<G1Affine>>();
+        assert_eq!(written, read);
+
+        let (written, read) = roundtrip::<Challenge128<G1Affine>>();
+        assert_eq!(written, read);
+        let bits = written.to_repr();
+        assert!(bits.as_ref()[16..].iter().all(|b| *b == 0));
+    }
+    type LabelledWrite = ShaWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>, sha2::Sha256>;
+    type LabelledRead<'a> = ShaRead<&'a [u8], G1Affine, Challenge255<G1Affine>, sha2::Sha256>;
+
+    fn labelled_challenge(writer_label: Option<&[u8]>, reader_label: Option<&[u8]>) -> (Fr, Fr) {
+        let scalar = Fr::from(7u64);
+
+        let mut writer = LabelledWrite::init_with_label(vec![], writer_label);
+        writer.write_scalar(scalar).unwrap();
+        let written = writer.squeeze_challenge().get_scalar();
+        let proof = writer.finalize();
+
+        let mut reader = LabelledRead::init_with_label(&proof[..], reader_label);
+        assert_eq!(reader.read_scalar().unwrap(), scalar);
+        let read = reader.squeeze_challenge().get_scalar();
+
+        (written, read)
+    }
+
+    #[test]
+    fn test_sha_transcript_label() {
+        let (written, read) = labelled_challenge(Some(b"mainnet"), Some(b"mainnet"));
+        assert_eq!(written, read);
+
+        let (_, other) = labelled_challenge(Some(b"mainnet"), Some(b"testnet"));
+        assert_ne!(written, other);
+
+        let (unlabelled, _) = labelled_challenge(None, None);
+        assert_ne!(written, unlabelled);
+
+        let mut writer = LabelledWrite::init(vec![]);
+        writer.write_scalar(Fr::from(7u64)).unwrap();
+        assert_eq!(writer.squeeze_challenge().get_scalar(), unlabelled);
+    }
+}
@@ -0,0 +1,165 @@
+// Proving and verification time and proof size of the verify circuit proof written with
+// the sha256, keccak256 and poseidon transcripts, aggregating one proof of the add_mul test
+// circuit of the api crate.
+//
+// The transcript only changes how the proof of the verify circuit is written: the target
+// proofs are read in circuit with the poseidon transcript whatever it is, so the verify
+// circuit has the same rows, 2^VERIFY_CIRCUIT_K at most, for the three of them.
+//
+// cargo bench -p halo2-snark-aggregator-circuit --features benches --bench transcript
+use criterion::{criterion_group, criterion_main, Criterion};
+use halo2_proofs::arithmetic::Field;
+use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, SingleVerifier};
+use halo2_proofs::plonk::{ProvingKey, VerifyingKey};
+use halo2_proofs::poly::commitment::{Params, ParamsVerifier};
+use halo2_proofs::transcript::{Challenge255, PoseidonRead, PoseidonWrite, TranscriptRead};
+use halo2_snark_aggregator_api::tests::systems::halo2::add_mul_test::test_circuit_builder;
+use halo2_snark_aggregator_api::transcript::sha::{ShaRead, ShaWrite};
+use halo2_snark_aggregator_circuit::verify_circuit::{
+    CreateProof, MultiCircuitsCreateProof, MultiCircuitsSetup, Setup, SingleProofPair,
+    VerifyCircuitTranscript,
+};
+use pairing_bn256::bn256::{Bn256, Fr, G1Affine};
+use rand_core::OsRng;
+use std::io::Cursor;
+use std::rc::Rc;
+
+type Sha256Write = ShaWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>, sha2::Sha256>;
+type Sha256Read = ShaRead<Cursor<Vec<u8>>, G1Affine, Challenge255<G1Affine>, sha2::Sha256>;
+type KeccakWrite = ShaWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>, sha3::Keccak256>;
+type KeccakRead = ShaRead<Cursor<Vec<u8>>, G1Affine, Challenge255<G1Affine>, sha3::Keccak256>;
+type PoseidonTranscriptWrite = PoseidonWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>;
+type PoseidonTranscriptRead = PoseidonRead<Cursor<Vec<u8>>, G1Affine, Challenge255<G1Affine>>;
+
+const TARGET_CIRCUIT_K: u32 = 10;
+// HALO2_PARAMS_22 caches its params as for the cli
+const VERIFY_CIRCUIT_K: u32 = 22;
+
+struct Fixture {
+    target_params: Rc<Params<G1Affine>>,
+    target_vk: Rc<VerifyingKey<G1Affine>>,
+    target_proof: SingleProofPair<Bn256>,
+    verify_params: Params<G1Affine>,
+    verify_vk: VerifyingKey<G1Affine>,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let a = Fr::random(OsRng);
+        let b = Fr::random(OsRng);
+        let c = Fr::from(7) * a.square() * b.square();
+
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(TARGET_CIRCUIT_K);
+        let vk = keygen_vk(&params, &test_circuit_builder(a, b)).unwrap();
+        let pk = keygen_pk(&params, vk, &test_circuit_builder(a, b)).unwrap();
+        let mut transcript = PoseidonWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            &[test_circuit_builder(a, b)],
+            &[&[&[c]]],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+        let target_proof = SingleProofPair::<Bn256> {
+            instances: vec![vec![vec![c]]],
+            transcript: transcript.finalize(),
+        };
+        let target_params = Rc::new(params);
+        let target_vk = Rc::new(pk.get_vk().clone());
+
+        let (verify_params, verify_vk) = MultiCircuitsSetup::<_, _, 1> {
+            setups: [Setup {
+                name: "add_mul".to_owned(),
+                target_circuit_params: target_params.clone(),
+                target_circuit_vk: target_vk.clone(),
+                proofs: vec![target_proof.clone()],
+                nproofs: 1,
+            }],
+            coherent: vec![],
+        }
+        .call(VERIFY_CIRCUIT_K);
+
+        Fixture {
+            target_params,
+            target_vk,
+            target_proof,
+            verify_params,
+            verify_vk,
+        }
+    }
+
+    // keygen_pk of the verify circuit and its proof written with T
+    fn prove<T: VerifyCircuitTranscript<G1Affine>>(
+        &self,
+    ) -> (ProvingKey<G1Affine>, Vec<Fr>, Vec<u8>) {
+        let (pk, _, instances, proof) = MultiCircuitsCreateProof::<_, _, 1> {
+            target_circuit_proofs: [CreateProof {
+                name: "add_mul".to_owned(),
+                target_circuit_params: self.target_params.clone(),
+                target_circuit_vk: self.target_vk.clone(),
+                template_proofs: vec![self.target_proof.clone()],
+                proofs: vec![self.target_proof.clone()],
+                nproofs: 1,
+            }],
+            verify_circuit_params: &self.verify_params,
+            verify_circuit_vk: self.verify_vk.clone(),
+            coherent: vec![],
+            transcript_label: None,
+        }
+        .call_with_transcript::<T>();
+        (pk, instances, proof)
+    }
+}
+
+fn bench_transcript<W, R>(
+    c: &mut Criterion,
+    fixture: &Fixture,
+    name: &str,
+    read: impl Fn(Cursor<Vec<u8>>) -> R,
+) where
+    W: VerifyCircuitTranscript<G1Affine>,
+    R: TranscriptRead<G1Affine, Challenge255<G1Affine>>,
+{
+    let (_, instances, proof) = fixture.prove::<W>();
+    println!("{}: verify circuit proof of {} bytes", name, proof.len());
+
+    let params: ParamsVerifier<Bn256> = fixture.verify_params.verifier(instances.len()).unwrap();
+    let instances: &[&[&[Fr]]] = &[&[&instances[..]]];
+
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    group.bench_function("prove", |b| b.iter(|| fixture.prove::<W>()));
+    group.bench_function("verify", |b| {
+        b.iter(|| {
+            let strategy = SingleVerifier::new(&params);
+            let mut transcript = read(Cursor::new(proof.clone()));
+            verify_proof(
+                &params,
+                &fixture.verify_vk,
+                strategy,
+                instances,
+                &mut transcript,
+            )
+            .unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn transcripts(c: &mut Criterion) {
+    let fixture = Fixture::new();
+
+    bench_transcript::<Sha256Write, _>(c, &fixture, "sha256", Sha256Read::init);
+    bench_transcript::<KeccakWrite, _>(c, &fixture, "keccak256", KeccakRead::init);
+    bench_transcript::<PoseidonTranscriptWrite, _>(
+        c,
+        &fixture,
+        "poseidon",
+        PoseidonTranscriptRead::init,
+    );
+}
+
+criterion_group!(benches, transcripts);
+criterion_main!(benches);
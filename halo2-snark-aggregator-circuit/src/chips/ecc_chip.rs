@@ -125,6 +125,28 @@ impl<'a, 'b, C: CurveAffine> ArithEccChip for EccChip<'a, 'b, C> {
         self.chip.constant_mul(ctx, rhs.to_curve(), lhs)
     }
 
+    // Only for a point of assign_const: the table is built from the value it was assigned with
+    // and its multiples are assigned as constants, so the point cells themselves are not used.
+    // Off by default until fixed_base_mul measures fewer rows than the multi_exp, see the
+    // fixed_base_mul_rows test of the ecc lib.
+    #[cfg(feature = "fixed-base-tables")]
+    fn scalar_mul_fixed_base(
+        &self,
+        ctx: &mut Self::Context,
+        lhs: &Self::AssignedScalar,
+        rhs: &Self::AssignedPoint,
+    ) -> Result<Option<Self::AssignedPoint>, Self::Error> {
+        use halo2_ecc_circuit_lib::chips::ecc_chip::{FixedBaseTable, FIXED_BASE_WINDOW_SIZE};
+
+        match rhs.constant() {
+            Some(base) => {
+                let table = FixedBaseTable::<C, FIXED_BASE_WINDOW_SIZE>::new(base);
+                Ok(Some(self.chip.fixed_base_mul(ctx, &table, lhs)?))
+            }
+            None => Ok(None),
+        }
+    }
+
     fn multi_exp(
         &self,
         ctx: &mut Self::Context,
@@ -133,4 +155,15 @@ impl<'a, 'b, C: CurveAffine> ArithEccChip for EccChip<'a, 'b, C> {
     ) -> Result<Self::AssignedPoint, Self::Error> {
         self.chip.shamir(ctx, &mut points, &scalars)
     }
+
+    fn select(
+        &self,
+        ctx: &mut Self::Context,
+        cond: &Self::AssignedScalar,
+        a: &Self::AssignedPoint,
+        b: &Self::AssignedPoint,
+    ) -> Result<Self::AssignedPoint, Self::Error> {
+        let cond = self.chip.base_gate().assert_condition(ctx, cond)?;
+        self.chip.bisec_point(ctx, &cond, a, b)
+    }
 }
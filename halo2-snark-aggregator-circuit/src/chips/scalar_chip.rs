@@ -74,6 +74,10 @@ impl<'a, 'b, N: FieldExt> ArithCommonChip for ScalarChip<'a, 'b, N> {
     ) -> Result<Self::AssignedValue, Self::Error> {
         Ok(v.clone())
     }
+
+    fn current_row(&self, ctx: &Self::Context) -> Option<usize> {
+        Some(*ctx.offset)
+    }
 }
 
 impl<'a, 'b, N: FieldExt> ArithFieldChip for ScalarChip<'a, 'b, N> {
@@ -124,4 +128,15 @@ impl<'a, 'b, N: FieldExt> ArithFieldChip for ScalarChip<'a, 'b, N> {
     ) -> Result<Self::AssignedField, Self::Error> {
         self.0.mul_add_constant(ctx, a, b, c)
     }
+
+    fn select(
+        &self,
+        ctx: &mut Self::Context,
+        cond: &Self::AssignedField,
+        a: &Self::AssignedField,
+        b: &Self::AssignedField,
+    ) -> Result<Self::AssignedField, Self::Error> {
+        let cond = self.0.assert_condition(ctx, cond)?;
+        self.0.bisec(ctx, &cond, a, b)
+    }
 }
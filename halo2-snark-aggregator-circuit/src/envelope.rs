@@ -0,0 +1,222 @@
+// Self-describing envelope of a proof, so that every consumer (fs, the solidity
+// fixtures, the wasm and ffi verifiers) can check that a proof is for its curve,
+// transcript, vk and instance layout before parsing it. Little endian layout:
+//
+//   magic "H2AG" | version u16 | curve u8 | transcript u8 | vk hash [u8; 32]
+//   | instance columns u32 | rows u32 per column | proof length u32 | proof
+use halo2_proofs::arithmetic::CurveAffine;
+use halo2_proofs::plonk::VerifyingKey;
+
+pub use crate::vk::vk_hash;
+
+pub const ENVELOPE_MAGIC: [u8; 4] = *b"H2AG";
+// 2 since the vk hash is the canonical one of vk::vk_hash, envelopes of version 1 carry
+// a hash of the Debug repr of the vk
+pub const ENVELOPE_VERSION: u16 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvelopeCurve {
+    Bn256 = 1,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvelopeTranscript {
+    // target circuit proofs, see sample_circuit
+    Poseidon = 1,
+    // verify circuit proofs, see Halo2VerifierCircuit
+    Sha256 = 2,
+    Keccak256 = 3,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvelopeError {
+    UnexpectedEnd,
+    TrailingBytes,
+    InvalidMagic,
+    UnsupportedVersion(u16),
+    UnknownCurve(u8),
+    UnknownTranscript(u8),
+    CurveMismatch,
+    TranscriptMismatch,
+    VkHashMismatch,
+    InstanceLayoutMismatch,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofEnvelope {
+    pub curve: EnvelopeCurve,
+    pub transcript: EnvelopeTranscript,
+    pub vk_hash: [u8; 32],
+    // rows of each instance column
+    pub instance_layout: Vec<u32>,
+    pub proof: Vec<u8>,
+}
+
+// Rows of each instance column of a proof, from its instances as load_instances returns them.
+pub fn instance_layout<F>(instances: &[Vec<Vec<F>>]) -> Vec<u32> {
+    instances
+        .iter()
+        .flatten()
+        .map(|column| column.len() as u32)
+        .collect()
+}
+
+// Whether the bytes start as an envelope, raw proofs are still accepted where this is false.
+pub fn is_envelope(bytes: &[u8]) -> bool {
+    bytes.starts_with(&ENVELOPE_MAGIC)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], EnvelopeError> {
+        if self.bytes.len() < n {
+            return Err(EnvelopeError::UnexpectedEnd);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, EnvelopeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, EnvelopeError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, EnvelopeError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+impl ProofEnvelope {
+    // Envelope of a target circuit proof, instance_layout being the rows of each of its
+    // instance columns.
+    pub fn target_circuit<C: CurveAffine>(
+        vk: &VerifyingKey<C>,
+        instance_layout: Vec<u32>,
+        proof: Vec<u8>,
+    ) -> Self {
+        ProofEnvelope {
+            curve: EnvelopeCurve::Bn256,
+            transcript: EnvelopeTranscript::Poseidon,
+            vk_hash: vk_hash(vk),
+            instance_layout,
+            proof,
+        }
+    }
+
+    // Envelope of a verify circuit proof with instances instances of one column.
+    pub fn verify_circuit<C: CurveAffine>(
+        vk: &VerifyingKey<C>,
+        instances: usize,
+        proof: Vec<u8>,
+    ) -> Self {
+        ProofEnvelope {
+            curve: EnvelopeCurve::Bn256,
+            transcript: EnvelopeTranscript::Sha256,
+            vk_hash: vk_hash(vk),
+            instance_layout: vec![instances as u32],
+            proof,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = ENVELOPE_MAGIC.to_vec();
+        bytes.extend(ENVELOPE_VERSION.to_le_bytes());
+        bytes.push(self.curve as u8);
+        bytes.push(self.transcript as u8);
+        bytes.extend(self.vk_hash);
+        bytes.extend((self.instance_layout.len() as u32).to_le_bytes());
+        for rows in self.instance_layout.iter() {
+            bytes.extend(rows.to_le_bytes());
+        }
+        bytes.extend((self.proof.len() as u32).to_le_bytes());
+        bytes.extend(self.proof.iter());
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, EnvelopeError> {
+        let mut reader = Reader { bytes };
+        if reader.take(4)? != ENVELOPE_MAGIC {
+            return Err(EnvelopeError::InvalidMagic);
+        }
+        let version = reader.u16()?;
+        if version != ENVELOPE_VERSION {
+            return Err(EnvelopeError::UnsupportedVersion(version));
+        }
+        let curve = match reader.u8()? {
+            1 => EnvelopeCurve::Bn256,
+            c => return Err(EnvelopeError::UnknownCurve(c)),
+        };
+        let transcript = match reader.u8()? {
+            1 => EnvelopeTranscript::Poseidon,
+            2 => EnvelopeTranscript::Sha256,
+            3 => EnvelopeTranscript::Keccak256,
+            t => return Err(EnvelopeError::UnknownTranscript(t)),
+        };
+        let vk_hash = reader.take(32)?.try_into().unwrap();
+        let columns = reader.u32()?;
+        let instance_layout = (0..columns)
+            .map(|_| reader.u32())
+            .collect::<Result<Vec<_>, _>>()?;
+        let proof_len = reader.u32()? as usize;
+        let proof = reader.take(proof_len)?.to_vec();
+        if !reader.bytes.is_empty() {
+            return Err(EnvelopeError::TrailingBytes);
+        }
+
+        Ok(ProofEnvelope {
+            curve,
+            transcript,
+            vk_hash,
+            instance_layout,
+            proof,
+        })
+    }
+
+    // Checks the envelope is for the proofs the consumer reads.
+    pub fn check(&self, expected: &ProofEnvelope) -> Result<(), EnvelopeError> {
+        if self.curve != expected.curve {
+            return Err(EnvelopeError::CurveMismatch);
+        }
+        if self.transcript != expected.transcript {
+            return Err(EnvelopeError::TranscriptMismatch);
+        }
+        if self.vk_hash != expected.vk_hash {
+            return Err(EnvelopeError::VkHashMismatch);
+        }
+        if self.instance_layout != expected.instance_layout {
+            return Err(EnvelopeError::InstanceLayoutMismatch);
+        }
+        Ok(())
+    }
+
+    // Envelope in bytes, once checked against the verify circuit vk and its number of
+    // instances.
+    pub fn open_verify_circuit<C: CurveAffine>(
+        bytes: &[u8],
+        vk: &VerifyingKey<C>,
+        instances: usize,
+    ) -> Result<Self, EnvelopeError> {
+        let envelope = Self::decode(bytes)?;
+        envelope.check(&Self::verify_circuit(vk, instances, vec![]))?;
+        Ok(envelope)
+    }
+
+    // Envelope in bytes, once checked against the target circuit vk and the rows of each
+    // of its instance columns.
+    pub fn open_target_circuit<C: CurveAffine>(
+        bytes: &[u8],
+        vk: &VerifyingKey<C>,
+        instance_layout: Vec<u32>,
+    ) -> Result<Self, EnvelopeError> {
+        let envelope = Self::decode(bytes)?;
+        envelope.check(&Self::target_circuit(vk, instance_layout, vec![]))?;
+        Ok(envelope)
+    }
+}
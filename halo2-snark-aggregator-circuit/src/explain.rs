@@ -0,0 +1,69 @@
+// Labelled transcripts of the proofs of a folder, for the sdk `explain_proof` command.
+use crate::envelope::instance_layout;
+use crate::fs::{
+    load_target_circuit_instance, load_target_circuit_params, load_target_circuit_proof_checked,
+    load_target_circuit_vk, load_transcript_label, load_verify_circuit_instance,
+    load_verify_circuit_params, load_verify_circuit_proof, load_verify_circuit_vk,
+};
+use crate::sample_circuit::TargetCircuit;
+use halo2_proofs::transcript::{Challenge255, PoseidonRead};
+use halo2_snark_aggregator_api::systems::halo2::explain::{explain_proof, ProofEntry};
+use halo2_snark_aggregator_api::transcript::sha::ShaRead;
+use pairing_bn256::bn256::{Bn256, G1Affine};
+use std::io;
+use std::path::PathBuf;
+
+// The index-th proof of the target circuit, read with the poseidon transcript of
+// sample_circuit.
+pub fn explain_target_circuit_proof<Circuit: TargetCircuit<G1Affine, Bn256>>(
+    folder: &PathBuf,
+    index: usize,
+) -> io::Result<Vec<ProofEntry<G1Affine>>> {
+    let params = load_target_circuit_params::<G1Affine, Bn256, Circuit>(&mut folder.clone());
+    let vk = load_target_circuit_vk::<G1Affine, Bn256, Circuit>(&mut folder.clone(), &params);
+    let instances = Circuit::load_instances(&load_target_circuit_instance::<Circuit>(
+        &mut folder.clone(),
+        index,
+    ));
+    let proof = load_target_circuit_proof_checked::<Circuit>(
+        &mut folder.clone(),
+        index,
+        &vk,
+        instance_layout(&instances),
+    );
+    let instances = instances
+        .iter()
+        .map(|x| x.iter().map(|y| &y[..]).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let instances = instances.iter().map(|x| &x[..]).collect::<Vec<_>>();
+
+    let params = params
+        .verifier::<Bn256>(Circuit::PUBLIC_INPUT_SIZE)
+        .unwrap();
+    let mut transcript = PoseidonRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    explain_proof(&vk, &params, &instances[..], &mut transcript)
+}
+
+// verify_circuit_proof.data, read with the sha256 transcript of Halo2VerifierCircuit.
+pub fn explain_verify_circuit_proof(
+    folder: &PathBuf,
+    public_input_size: usize,
+) -> io::Result<Vec<ProofEntry<G1Affine>>> {
+    let params = load_verify_circuit_params(&mut folder.clone());
+    let vk = load_verify_circuit_vk(&mut folder.clone());
+    let instances = load_verify_circuit_instance(&mut folder.clone());
+    let instances = instances
+        .iter()
+        .map(|x| x.iter().map(|y| &y[..]).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let instances = instances.iter().map(|x| &x[..]).collect::<Vec<_>>();
+    let proof = load_verify_circuit_proof(&mut folder.clone());
+    let label = load_transcript_label(&mut folder.clone());
+
+    let params = params.verifier::<Bn256>(public_input_size).unwrap();
+    let mut transcript = ShaRead::<_, _, Challenge255<_>, sha2::Sha256>::init_with_label(
+        &proof[..],
+        label.as_deref(),
+    );
+    explain_proof(&vk, &params, &instances[..], &mut transcript)
+}
@@ -5,13 +5,24 @@ use halo2_proofs::{
 };
 use pairing_bn256::bn256::{Bn256, Fr, G1Affine};
 
+use crate::envelope::ProofEnvelope;
+use crate::signing::{artifact_key, sign_artifact, signature_filename, verify_artifact};
 use crate::{sample_circuit::TargetCircuit, verify_circuit::Halo2VerifierCircuit};
+use halo2_snark_aggregator_api::systems::halo2::instance::{
+    instances_from_repr, InstanceCommitmentCache,
+};
+use pairing_bn256::group::ff::PrimeField;
 use std::{
-    io::{Cursor, Read, Write},
+    io::{self, Cursor, Read, Write},
     path::PathBuf,
 };
 
 pub fn read_file(folder: &mut PathBuf, filename: &str) -> Vec<u8> {
+    read_signed_file(folder, filename, artifact_key().as_deref())
+}
+
+// Reads the file and, with a key, checks it against its signature file.
+pub fn read_signed_file(folder: &mut PathBuf, filename: &str, key: Option<&[u8]>) -> Vec<u8> {
     let mut buf = vec![];
 
     folder.push(filename);
@@ -19,15 +30,57 @@ pub fn read_file(folder: &mut PathBuf, filename: &str) -> Vec<u8> {
     folder.pop();
 
     fd.read_to_end(&mut buf).unwrap();
+
+    if let Some(key) = key {
+        folder.push(signature_filename(filename));
+        let signature = std::fs::read(folder.as_path());
+        folder.pop();
+
+        let signature = signature.unwrap_or_else(|_| panic!("{} is not signed", filename));
+        assert!(
+            verify_artifact(key, &buf, &signature),
+            "signature of {} does not match",
+            filename
+        );
+    }
     buf
 }
 
 pub fn write_file(folder: &mut PathBuf, filename: &str, buf: &Vec<u8>) {
+    write_signed_file(folder, filename, buf, artifact_key().as_deref())
+}
+
+// Writes the file and, with a key, its signature file.
+pub fn write_signed_file(folder: &mut PathBuf, filename: &str, buf: &[u8], key: Option<&[u8]>) {
     folder.push(filename);
     let mut fd = std::fs::File::create(folder.as_path()).unwrap();
     folder.pop();
 
-    fd.write(buf).unwrap();
+    fd.write_all(buf).unwrap();
+
+    if let Some(key) = key {
+        folder.push(signature_filename(filename));
+        std::fs::write(folder.as_path(), sign_artifact(key, buf)).unwrap();
+        folder.pop();
+    }
+}
+
+// Largest k of the params read from bytes.
+pub const MAX_PARAMS_K: u32 = 28;
+
+// Params::read of bytes that may not be params: Params::read goes through 2^k points for
+// the k of the first 4 bytes, a k out of range or bytes too short for it are an error first.
+pub fn try_read_params<C: CurveAffine>(buf: &[u8]) -> io::Result<Params<C>> {
+    let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let k = buf
+        .get(..4)
+        .map(|k| u32::from_le_bytes(k.try_into().unwrap()))
+        .ok_or_else(|| invalid("params too short"))?;
+    let point_size = C::Repr::default().as_ref().len();
+    if k > MAX_PARAMS_K || buf.len() < 4 + (1usize << k) * point_size {
+        return Err(invalid("params k out of range"));
+    }
+    Params::<C>::read(Cursor::new(buf))
 }
 
 pub fn read_target_circuit_params<
@@ -50,9 +103,9 @@ pub fn load_target_circuit_params<
 >(
     folder: &mut PathBuf,
 ) -> Params<C> {
-    Params::<C>::read(Cursor::new(&read_target_circuit_params::<C, E, Circuit>(
+    try_read_params::<C>(&read_target_circuit_params::<C, E, Circuit>(
         &mut folder.clone(),
-    )))
+    ))
     .unwrap()
 }
 
@@ -113,15 +166,31 @@ pub fn load_target_circuit_proof<Circuit: TargetCircuit<G1Affine, Bn256>>(
     )
 }
 
+// Proof of sample_circuit_proof_<NAME><index>.envelope once checked against the target
+// circuit vk and the rows of each instance column, or of the .data file for folders
+// without one.
+pub fn load_target_circuit_proof_checked<Circuit: TargetCircuit<G1Affine, Bn256>>(
+    folder: &mut PathBuf,
+    index: usize,
+    vk: &VerifyingKey<G1Affine>,
+    instance_layout: Vec<u32>,
+) -> Vec<u8> {
+    let filename = format!("sample_circuit_proof_{}{}.envelope", Circuit::NAME, index);
+    if !folder.join(&filename).exists() {
+        return load_target_circuit_proof::<Circuit>(folder, index);
+    }
+    let envelope = read_file(folder, &filename);
+    ProofEnvelope::open_target_circuit(&envelope, vk, instance_layout)
+        .unwrap_or_else(|e| panic!("incompatible {}: {:?}", filename, e))
+        .proof
+}
+
 pub fn read_verify_circuit_params(folder: &mut PathBuf) -> Vec<u8> {
     read_file(folder, "verify_circuit.params")
 }
 
 pub fn load_verify_circuit_params(folder: &mut PathBuf) -> Params<G1Affine> {
-    Params::<G1Affine>::read(Cursor::new(&read_verify_circuit_params(
-        &mut folder.clone(),
-    )))
-    .unwrap()
+    try_read_params::<G1Affine>(&read_verify_circuit_params(&mut folder.clone())).unwrap()
 }
 
 pub fn read_verify_circuit_vk(folder: &mut PathBuf) -> Vec<u8> {
@@ -140,7 +209,7 @@ pub fn read_verify_circuit_instance(folder: &mut PathBuf) -> Vec<u8> {
     read_file(folder, "verify_circuit_instance.data")
 }
 
-fn load_instances<E: MultiMillerLoop>(buf: &[u8]) -> Vec<Vec<Vec<E::Scalar>>> {
+pub fn load_instances<E: MultiMillerLoop>(buf: &[u8]) -> Vec<Vec<Vec<E::Scalar>>> {
     let mut ret = vec![];
     let cursor = &mut std::io::Cursor::new(buf);
 
@@ -151,6 +220,27 @@ fn load_instances<E: MultiMillerLoop>(buf: &[u8]) -> Vec<Vec<Vec<E::Scalar>>> {
     vec![vec![ret]]
 }
 
+// load_instances of bytes that may not be instances: a trailing partial scalar or a non
+// canonical one is an error, where load_instances stops reading.
+pub fn try_load_instances<E: MultiMillerLoop>(buf: &[u8]) -> io::Result<Vec<Vec<Vec<E::Scalar>>>> {
+    let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let size = <E::Scalar as PrimeField>::Repr::default().as_ref().len();
+    if buf.len() % size != 0 {
+        return Err(invalid("trailing bytes in instances"));
+    }
+    let reprs = buf
+        .chunks(size)
+        .map(|chunk| {
+            let mut repr = <E::Scalar as PrimeField>::Repr::default();
+            repr.as_mut().copy_from_slice(chunk);
+            repr
+        })
+        .collect();
+
+    instances_from_repr::<E::Scalar>(&[vec![reprs]])
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
 pub fn load_verify_circuit_instance(folder: &mut PathBuf) -> Vec<Vec<Vec<Fr>>> {
     let instances = read_verify_circuit_instance(&mut folder.clone());
     load_instances::<Bn256>(&instances)
@@ -160,52 +250,255 @@ pub fn load_verify_circuit_proof(folder: &mut PathBuf) -> Vec<u8> {
     read_file(folder, "verify_circuit_proof.data")
 }
 
-pub fn write_verify_circuit_params(folder: &mut PathBuf, verify_circuit_params: &Params<G1Affine>) {
-    folder.push("verify_circuit.params");
-    let mut fd = std::fs::File::create(folder.as_path()).unwrap();
-    folder.pop();
+// Deployment label of the transcript of the verify circuit proof, if the folder has one.
+pub fn load_transcript_label(folder: &mut PathBuf) -> Option<Vec<u8>> {
+    if !folder.join("transcript_label").exists() {
+        return None;
+    }
+    Some(read_file(folder, "transcript_label"))
+}
+
+const INSTANCE_COMMITMENT_CACHE: &str = "instance_commitments.cache";
 
-    verify_circuit_params.write(&mut fd).unwrap();
+// Instance commitments of the target proofs already aggregated in the folder, empty when there
+// is no cache yet or it does not read.
+pub fn load_instance_commitment_cache(folder: &mut PathBuf) -> InstanceCommitmentCache<G1Affine> {
+    if !folder.join(INSTANCE_COMMITMENT_CACHE).exists() {
+        return InstanceCommitmentCache::default();
+    }
+    let buf = read_file(folder, INSTANCE_COMMITMENT_CACHE);
+    InstanceCommitmentCache::read(&mut &buf[..]).unwrap_or_default()
+}
+
+pub fn write_instance_commitment_cache(
+    folder: &mut PathBuf,
+    cache: &InstanceCommitmentCache<G1Affine>,
+) {
+    let mut buf = vec![];
+    cache.write(&mut buf).unwrap();
+    write_file(folder, INSTANCE_COMMITMENT_CACHE, &buf)
+}
+
+pub fn load_verify_circuit_final_pair(folder: &mut PathBuf) -> Vec<u8> {
+    read_file(folder, "verify_circuit_final_pair.data")
+}
+
+pub fn write_verify_circuit_params(folder: &mut PathBuf, verify_circuit_params: &Params<G1Affine>) {
+    let mut buf = vec![];
+    verify_circuit_params.write(&mut buf).unwrap();
+    write_file(folder, "verify_circuit.params", &buf)
 }
 
 pub fn write_verify_circuit_vk(folder: &mut PathBuf, verify_circuit_vk: &VerifyingKey<G1Affine>) {
-    folder.push("verify_circuit.vkey");
-    let mut fd = std::fs::File::create(folder.as_path()).unwrap();
-    folder.pop();
+    let mut buf = vec![];
+    verify_circuit_vk.write(&mut buf).unwrap();
+    write_file(folder, "verify_circuit.vkey", &buf)
+}
 
-    verify_circuit_vk.write(&mut fd).unwrap();
+// Content of verify_circuit_instance.data.
+pub fn encode_verify_circuit_instance(buf: &Vec<<G1Affine as CurveAffine>::ScalarExt>) -> Vec<u8> {
+    let mut bytes = vec![];
+    buf.iter().for_each(|x| x.write(&mut bytes).unwrap());
+    bytes
 }
 
 pub fn write_verify_circuit_instance(
     folder: &mut PathBuf,
     buf: &Vec<<G1Affine as CurveAffine>::ScalarExt>,
 ) {
-    folder.push("verify_circuit_instance.data");
-    let mut fd = std::fs::File::create(folder.as_path()).unwrap();
-    folder.pop();
-
-    buf.iter().for_each(|x| x.write(&mut fd).unwrap());
+    write_file(
+        folder,
+        "verify_circuit_instance.data",
+        &encode_verify_circuit_instance(buf),
+    )
 }
 
-pub fn write_verify_circuit_final_pair(folder: &mut PathBuf, pair: &(G1Affine, G1Affine, Vec<Fr>)) {
-    folder.push("verify_circuit_final_pair.data");
-    let mut fd = std::fs::File::create(folder.as_path()).unwrap();
-    folder.pop();
-
-    pair.0.x.write(&mut fd).unwrap();
-    pair.0.y.write(&mut fd).unwrap();
-    pair.1.x.write(&mut fd).unwrap();
-    pair.1.y.write(&mut fd).unwrap();
+// Content of verify_circuit_final_pair.data.
+pub fn encode_verify_circuit_final_pair(pair: &(G1Affine, G1Affine, Vec<Fr>)) -> Vec<u8> {
+    let mut bytes = vec![];
+    pair.0.x.write(&mut bytes).unwrap();
+    pair.0.y.write(&mut bytes).unwrap();
+    pair.1.x.write(&mut bytes).unwrap();
+    pair.1.y.write(&mut bytes).unwrap();
 
     pair.2.iter().for_each(|scalar| {
-        scalar.write(&mut fd).unwrap();
-    })
+        scalar.write(&mut bytes).unwrap();
+    });
+    bytes
+}
+
+// verify_circuit_final_pair.data back into (w_x, w_g, target circuit instances).
+pub fn decode_verify_circuit_final_pair(buf: &[u8]) -> io::Result<(G1Affine, G1Affine, Vec<Fr>)> {
+    let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+    if buf.len() < 128 || buf.len() % 32 != 0 {
+        return Err(invalid("final pair is not two points and scalars"));
+    }
+    let cursor = &mut Cursor::new(buf);
+    let mut point = || -> io::Result<G1Affine> {
+        let x = <G1Affine as CurveAffine>::Base::read(cursor)?;
+        let y = <G1Affine as CurveAffine>::Base::read(cursor)?;
+        Option::from(G1Affine::from_xy(x, y)).ok_or_else(|| invalid("final pair point off curve"))
+    };
+    let (w_x, w_g) = (point()?, point()?);
+    let instances = try_load_instances::<Bn256>(&buf[128..])?
+        .remove(0)
+        .remove(0);
+    Ok((w_x, w_g, instances))
+}
+
+pub fn write_verify_circuit_final_pair(folder: &mut PathBuf, pair: &(G1Affine, G1Affine, Vec<Fr>)) {
+    write_file(
+        folder,
+        "verify_circuit_final_pair.data",
+        &encode_verify_circuit_final_pair(pair),
+    )
 }
 
 pub fn write_verify_circuit_proof(folder: &mut PathBuf, buf: &Vec<u8>) {
     write_file(folder, "verify_circuit_proof.data", buf)
 }
 
+// verify_circuit_proof.envelope, the proof of verify_circuit_proof.data in its envelope.
+pub fn write_verify_circuit_proof_envelope(folder: &mut PathBuf, envelope: &ProofEnvelope) {
+    write_file(folder, "verify_circuit_proof.envelope", &envelope.encode())
+}
+
+// verify_circuit_proof.envelope once checked against the verify circuit vk and its number
+// of instances, or verify_circuit_proof.data in an envelope for folders without one.
+pub fn load_verify_circuit_proof_envelope(
+    folder: &mut PathBuf,
+    vk: &VerifyingKey<G1Affine>,
+    instances: usize,
+) -> ProofEnvelope {
+    if !folder.join("verify_circuit_proof.envelope").exists() {
+        let proof = load_verify_circuit_proof(folder);
+        return ProofEnvelope::verify_circuit(vk, instances, proof);
+    }
+    let envelope = read_file(folder, "verify_circuit_proof.envelope");
+    ProofEnvelope::open_verify_circuit(&envelope, vk, instances)
+        .unwrap_or_else(|e| panic!("incompatible verify_circuit_proof.envelope: {:?}", e))
+}
+
+// Proof of verify_circuit_proof.envelope once checked against the verify circuit vk and
+// its number of instances, or of verify_circuit_proof.data for folders without one.
+pub fn load_verify_circuit_proof_checked(
+    folder: &mut PathBuf,
+    vk: &VerifyingKey<G1Affine>,
+    instances: usize,
+) -> Vec<u8> {
+    load_verify_circuit_proof_envelope(folder, vk, instances).proof
+}
+
 pub fn write_verify_circuit_solidity(folder: &mut PathBuf, buf: &Vec<u8>) {
     write_file(folder, "verifier.sol", buf)
 }
+
+// Calldata of verify_compressed, see halo2_snark_aggregator_solidity::CalldataLayout::compress.
+pub fn write_verify_circuit_compressed_calldata(folder: &mut PathBuf, buf: &Vec<u8>) {
+    write_file(folder, "verify_circuit_compressed_calldata.data", buf)
+}
+
+// Calldata encoder of verifier.sol, see halo2_snark_aggregator_solidity::render_calldata_encoder.
+pub fn write_verify_circuit_encoder(folder: &mut PathBuf, buf: &Vec<u8>) {
+    write_file(folder, "encoder.ts", buf)
+}
+
+// Vyper version of verifier.sol, see halo2_snark_aggregator_solidity::CodegenBackend::Vyper.
+pub fn write_verify_circuit_vyper(folder: &mut PathBuf, buf: &Vec<u8>) {
+    write_file(folder, "verifier.vy", buf)
+}
+
+// Yul object version of verifier.sol, see halo2_snark_aggregator_solidity::CodegenBackend::Yul.
+pub fn write_verify_circuit_yul(folder: &mut PathBuf, buf: &Vec<u8>) {
+    write_file(folder, "verifier.yul", buf)
+}
+
+// Aptos move version of verifier.sol, see halo2_snark_aggregator_solidity::CodegenBackend::Move.
+pub fn write_verify_circuit_move(folder: &mut PathBuf, buf: &Vec<u8>) {
+    write_file(folder, "verifier.move", buf)
+}
+
+// BatchVerifier next to the verifier.sol it imports.
+pub fn write_verify_circuit_batch_solidity(folder: &mut PathBuf, buf: &Vec<u8>) {
+    write_file(folder, "batch_verifier.sol", buf)
+}
+
+// Gas estimate of verifier.sol, see halo2_snark_aggregator_solidity::GasReport.
+pub fn write_verify_circuit_gas_report(folder: &mut PathBuf, buf: &Vec<u8>) {
+    write_file(folder, "gas_report.txt", buf)
+}
+
+// Markdown audit report of verifier.sol, see call_with_audit_report of the solidity crate.
+pub fn write_verify_circuit_audit_report(folder: &mut PathBuf, buf: &Vec<u8>) {
+    write_file(folder, "verifier_audit.md", buf)
+}
+
+// foundry.toml and test/Verifier.t.sol next to verifier.sol, `forge test` runs from the folder.
+pub fn write_verify_circuit_foundry_suite(folder: &mut PathBuf, config: &Vec<u8>, test: &Vec<u8>) {
+    write_file(folder, "foundry.toml", config);
+
+    folder.push("test");
+    std::fs::create_dir_all(folder.as_path()).unwrap();
+    write_file(folder, "Verifier.t.sol", test);
+    folder.pop();
+}
+
+// Writes the (path, content) files of a generated project under folder/name.
+fn write_project(folder: &mut PathBuf, name: &str, files: &[(String, String)]) {
+    folder.push(name);
+    for (path, content) in files {
+        folder.push(path);
+        std::fs::create_dir_all(folder.parent().unwrap()).unwrap();
+        folder.pop();
+        write_file(folder, path, &Vec::<u8>::from(content.as_bytes()));
+    }
+    folder.pop();
+}
+
+pub fn write_verify_circuit_hardhat_project(folder: &mut PathBuf, files: &[(String, String)]) {
+    write_project(folder, "hardhat", files)
+}
+
+// CosmWasm contract crate of halo2_snark_aggregator_solidity::render_cosmwasm_project.
+pub fn write_verify_circuit_cosmwasm_project(folder: &mut PathBuf, files: &[(String, String)]) {
+    write_project(folder, "cosmwasm", files)
+}
+
+// NEAR contract crate of halo2_snark_aggregator_solidity::render_near_project.
+pub fn write_verify_circuit_near_project(folder: &mut PathBuf, files: &[(String, String)]) {
+    write_project(folder, "near", files)
+}
+
+// Solana program crate of halo2_snark_aggregator_solidity::render_solana_project.
+pub fn write_verify_circuit_solana_project(folder: &mut PathBuf, files: &[(String, String)]) {
+    write_project(folder, "solana", files)
+}
+
+// verify_circuit_malformed.json of malformed::malformed_proofs_json, read by the foundry suite.
+pub fn write_verify_circuit_malformed_fixtures(folder: &mut PathBuf, json: &serde_json::Value) {
+    write_file(
+        folder,
+        "verify_circuit_malformed.json",
+        &serde_json::to_string_pretty(json).unwrap().into_bytes(),
+    )
+}
+
+// verification_key.json, proof.json and public.json of the snarkjs module.
+pub fn write_verify_circuit_snarkjs(
+    folder: &mut PathBuf,
+    verification_key: &serde_json::Value,
+    proof: &serde_json::Value,
+    public: &serde_json::Value,
+) {
+    for (filename, json) in [
+        ("verification_key.json", verification_key),
+        ("proof.json", proof),
+        ("public.json", public),
+    ] {
+        write_file(
+            folder,
+            filename,
+            &serde_json::to_string_pretty(json).unwrap().into_bytes(),
+        );
+    }
+}
@@ -0,0 +1,122 @@
+// Layout of the verify circuit with halo2's dev-graph, next to a strip of the steps of the
+// aggregation: the instances, proof and challenges of every sub-verifier, then the msm. The
+// steps are read from the LAYOUT_TARGET events of the verifier, in rows of the aggregation
+// region. V1 places that region at row 0, the range tables being in columns of their own.
+use halo2_proofs::{arithmetic::FieldExt, dev::CircuitLayout, plonk::Circuit};
+use halo2_snark_aggregator_api::systems::halo2::verify::LAYOUT_TARGET;
+use plotters::prelude::*;
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::Registry;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayoutStep {
+    pub name: String,
+    pub rows: Range<usize>,
+}
+
+#[derive(Default)]
+struct StepVisitor {
+    row: Option<u64>,
+    step: Option<String>,
+}
+
+impl Visit for StepVisitor {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "row" {
+            self.row = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "step" {
+            self.step = Some(format!("{:?}", value));
+        }
+    }
+}
+
+struct StepCollector(Arc<Mutex<Vec<(String, usize)>>>);
+
+impl<S: Subscriber> Layer<S> for StepCollector {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if event.metadata().target() != LAYOUT_TARGET {
+            return;
+        }
+        let mut visitor = StepVisitor::default();
+        event.record(&mut visitor);
+        if let (Some(row), Some(step)) = (visitor.row, visitor.step) {
+            let mut marks = self.0.lock().unwrap();
+            // the floor planner synthesizes the region again, from its first row
+            if marks
+                .last()
+                .map_or(false, |(_, last)| (row as usize) < *last)
+            {
+                marks.clear();
+            }
+            marks.push((step, row as usize));
+        }
+    }
+}
+
+// Steps between consecutive marks, the last mark only ends the previous step.
+fn steps(marks: Vec<(String, usize)>) -> Vec<LayoutStep> {
+    marks
+        .windows(2)
+        .filter(|w| w[0].1 < w[1].1)
+        .map(|w| LayoutStep {
+            name: w[0].0.clone(),
+            rows: w[0].1..w[1].1,
+        })
+        .collect()
+}
+
+// Runs f, with the steps it marks in its last synthesis pass.
+pub fn collect_steps<R>(f: impl FnOnce() -> R) -> (R, Vec<LayoutStep>) {
+    let marks = Arc::new(Mutex::new(vec![]));
+    let subscriber = Registry::default().with(StepCollector(marks.clone()));
+    let r = tracing::subscriber::with_default(subscriber, f);
+    let marks = std::mem::take(&mut *marks.lock().unwrap());
+    (r, steps(marks))
+}
+
+// Renders the layout of circuit on 2^k rows to the png at path, returns the steps drawn.
+pub fn render_layout<F: FieldExt, ConcreteCircuit: Circuit<F>>(
+    k: u32,
+    circuit: &ConcreteCircuit,
+    path: &Path,
+) -> Result<Vec<LayoutStep>, Box<dyn Error>> {
+    let root = BitMapBackend::new(path, (1600, 1600)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let (layout, strip) = root.split_horizontally(1280);
+
+    let (rendered, steps) = collect_steps(|| CircuitLayout::default().render(k, circuit, &layout));
+    rendered?;
+
+    // same title and margin as the layout, for the rows to line up
+    let strip = strip.titled("Steps", ("sans-serif", 60))?;
+    let mut chart = ChartBuilder::on(&strip)
+        .margin(5)
+        .build_cartesian_2d(0f64..1f64, (1usize << k)..0)?;
+    chart.draw_series(steps.iter().enumerate().map(|(i, step)| {
+        Rectangle::new(
+            [(0.0, step.rows.start), (1.0, step.rows.end)],
+            Palette99::pick(i).mix(0.5).filled(),
+        )
+    }))?;
+    chart.draw_series(steps.iter().map(|step| {
+        Text::new(
+            format!("{} ({} rows)", step.name, step.rows.len()),
+            (0.02, step.rows.start),
+            ("sans-serif", 12),
+        )
+    }))?;
+
+    root.present()?;
+    Ok(steps)
+}
@@ -1,7 +1,16 @@
 pub mod chips;
+pub mod envelope;
+pub mod explain;
 pub mod fs;
+#[cfg(feature = "dev-graph")]
+pub mod layout;
+pub mod malformed;
+pub mod pse;
 pub mod sample_circuit;
+pub mod signing;
+pub mod snarkjs;
 pub mod verify_circuit;
+pub mod vk;
 
 #[cfg(test)]
 mod tests;
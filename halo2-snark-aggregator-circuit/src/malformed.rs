@@ -0,0 +1,227 @@
+// Systematically corrupted variants of a valid verify circuit proof, each labelled with
+// the failure it must hit, as fixtures for the negative tests of the native verifier and
+// of the generated contracts.
+use crate::fs::{decode_verify_circuit_final_pair, encode_verify_circuit_final_pair};
+use crate::verify_circuit::final_pair_to_instances;
+use halo2_ecc_circuit_lib::utils::field_to_bn;
+use halo2_proofs::arithmetic::{BaseExt, CurveAffine, Field};
+use halo2_proofs::plonk::{verify_proof, Error, SingleVerifier, VerifyingKey};
+use halo2_proofs::poly::commitment::ParamsVerifier;
+use halo2_proofs::transcript::Challenge255;
+use halo2_snark_aggregator_api::systems::halo2::explain::{explain_proof, ProofValue};
+use halo2_snark_aggregator_api::transcript::sha::ShaRead;
+use pairing_bn256::bn256::{Bn256, Fq, Fr, G1Affine};
+use pairing_bn256::group::Curve;
+use serde_json::{json, Value};
+use std::io::{self, Cursor};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExpectedFailure {
+    // the transcript does not decode: a commitment off the curve, Error::Transcript natively
+    InvalidPoint,
+    // the proof decodes but the pairing check fails, Error::ConstraintSystemFailure natively
+    VerificationFailure,
+}
+
+impl ExpectedFailure {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExpectedFailure::InvalidPoint => "invalid_point",
+            ExpectedFailure::VerificationFailure => "verification_failure",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MalformedProof {
+    pub label: String,
+    pub expected_failure: ExpectedFailure,
+    pub proof: Vec<u8>,
+    pub final_pair: Vec<u8>,
+    pub instances: Vec<Fr>,
+}
+
+// Size in the sha256 transcript of the verify circuit proofs: points are written as
+// (x, y), scalars as their repr, challenges and instance commitments are not written.
+fn encoded_size(value: &ProofValue<G1Affine>) -> usize {
+    match value {
+        ProofValue::Commitment(_) => 64,
+        ProofValue::Evaluation(_) => 32,
+        ProofValue::Challenge(_) => 0,
+    }
+}
+
+fn decodes_to_point(bytes: &[u8]) -> bool {
+    let cursor = &mut Cursor::new(bytes);
+    match (Fq::read(cursor), Fq::read(cursor)) {
+        (Ok(x), Ok(y)) => bool::from(G1Affine::from_xy(x, y).is_some()),
+        _ => false,
+    }
+}
+
+// Corruptions of the transcript of a proof of vk for instances: the low bit of the x of
+// every commitment flipped, and every two consecutive distinct evaluations swapped.
+// final_pair is passed through, it is the contract side of instances.
+pub fn malformed_proofs(
+    vk: &VerifyingKey<G1Affine>,
+    params: &ParamsVerifier<Bn256>,
+    instances: &[Fr],
+    proof: &[u8],
+    final_pair: &[u8],
+) -> io::Result<Vec<MalformedProof>> {
+    let mut transcript = ShaRead::<_, _, Challenge255<_>, sha2::Sha256>::init(proof);
+    let entries = explain_proof(vk, params, &[&[instances]], &mut transcript)?;
+
+    // (label, offset) of the values written in the proof, in order
+    let mut offset = 0;
+    let mut commitments = vec![];
+    let mut evaluations = vec![];
+    for entry in entries.iter().skip_while(|e| {
+        matches!(e.value, ProofValue::Commitment(_)) && e.label.starts_with("instance[")
+    }) {
+        match entry.value {
+            ProofValue::Commitment(_) => commitments.push((entry.label.clone(), offset)),
+            ProofValue::Evaluation(_) => evaluations.push((entry.label.clone(), offset)),
+            ProofValue::Challenge(_) => (),
+        }
+        offset += encoded_size(&entry.value);
+    }
+
+    let malformed = |label: String, proof: Vec<u8>, expected_failure| MalformedProof {
+        label,
+        expected_failure,
+        proof,
+        final_pair: final_pair.to_vec(),
+        instances: instances.to_vec(),
+    };
+    let mut ret = vec![];
+
+    for (label, offset) in commitments {
+        let mut corrupted = proof.to_vec();
+        corrupted[offset] ^= 1;
+        let expected_failure = if decodes_to_point(&corrupted[offset..offset + 64]) {
+            ExpectedFailure::VerificationFailure
+        } else {
+            ExpectedFailure::InvalidPoint
+        };
+        ret.push(malformed(
+            format!("flip {}", label),
+            corrupted,
+            expected_failure,
+        ));
+    }
+
+    for pair in evaluations.windows(2) {
+        let ((a, i), (b, j)) = (&pair[0], &pair[1]);
+        if proof[*i..*i + 32] == proof[*j..*j + 32] {
+            continue;
+        }
+        let mut corrupted = proof.to_vec();
+        corrupted.copy_within(*j..*j + 32, *i);
+        corrupted[*j..*j + 32].copy_from_slice(&proof[*i..*i + 32]);
+        ret.push(malformed(
+            format!("swap {} and {}", a, b),
+            corrupted,
+            ExpectedFailure::VerificationFailure,
+        ));
+    }
+
+    Ok(ret)
+}
+
+// malformed_proofs of the verify circuit proof and final pair of a run, with the instances
+// recomputed from the final pair, and the proof checked against a wrong final pair: w_x
+// moved by the generator and each target circuit instance incremented.
+pub fn malformed_verify_circuit_proofs(
+    vk: &VerifyingKey<G1Affine>,
+    params: &ParamsVerifier<Bn256>,
+    proof: &[u8],
+    final_pair: &[u8],
+) -> io::Result<Vec<MalformedProof>> {
+    let pair = decode_verify_circuit_final_pair(final_pair)?;
+    let instances = final_pair_to_instances::<G1Affine, Bn256>(&pair);
+    let mut ret = malformed_proofs(vk, params, &instances, proof, final_pair)?;
+
+    let mut wrong_pairs = vec![];
+    let mut pair_w_x = pair.clone();
+    pair_w_x.0 = (pair.0 + G1Affine::generator()).to_affine();
+    wrong_pairs.push(("wrong w_x".to_owned(), pair_w_x));
+    for i in 0..pair.2.len() {
+        let mut pair_instance = pair.clone();
+        pair_instance.2[i] += Fr::one();
+        wrong_pairs.push((format!("wrong instance[{}]", i), pair_instance));
+    }
+
+    for (label, wrong_pair) in wrong_pairs {
+        ret.push(MalformedProof {
+            label,
+            expected_failure: ExpectedFailure::VerificationFailure,
+            proof: proof.to_vec(),
+            final_pair: encode_verify_circuit_final_pair(&wrong_pair),
+            instances: final_pair_to_instances::<G1Affine, Bn256>(&wrong_pair),
+        });
+    }
+
+    Ok(ret)
+}
+
+// Runs the native verifier on a malformed proof, with the transcript label of the run, Ok when
+// it fails as expected.
+pub fn check_malformed_proof(
+    vk: &VerifyingKey<G1Affine>,
+    params: &ParamsVerifier<Bn256>,
+    label: Option<&[u8]>,
+    malformed: &MalformedProof,
+) -> Result<(), String> {
+    let mut transcript = ShaRead::<_, _, Challenge255<_>, sha2::Sha256>::init_with_label(
+        &malformed.proof[..],
+        label,
+    );
+    let strategy = SingleVerifier::new(params);
+    let instances: &[&[&[Fr]]] = &[&[&malformed.instances[..]]];
+    match (
+        verify_proof(params, vk, strategy, instances, &mut transcript),
+        malformed.expected_failure,
+    ) {
+        (Err(Error::Transcript(_)), ExpectedFailure::InvalidPoint)
+        | (Err(Error::ConstraintSystemFailure), ExpectedFailure::VerificationFailure) => Ok(()),
+        (result, expected) => Err(format!(
+            "{}: expected {}, got {:?}",
+            malformed.label,
+            expected.as_str(),
+            result
+        )),
+    }
+}
+
+fn to_hex(buf: &[u8]) -> String {
+    let hex = buf.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    format!("0x{}", hex)
+}
+
+// verify_circuit_malformed.json: the proof and final pair as 0x prefixed hex of the bytes
+// of their files, for the foundry suite, and the verify circuit instances as decimal
+// strings, for the native verifier.
+pub fn malformed_proofs_json(proofs: &[MalformedProof]) -> Value {
+    let fixtures = proofs
+        .iter()
+        .map(|p| {
+            json!({
+                "label": p.label,
+                "expected_failure": p.expected_failure.as_str(),
+                "proof": to_hex(&p.proof),
+                "final_pair": to_hex(&p.final_pair),
+                "instances": p
+                    .instances
+                    .iter()
+                    .map(|f| field_to_bn(f).to_str_radix(10))
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "count": fixtures.len(),
+        "fixtures": fixtures,
+    })
+}
@@ -0,0 +1,89 @@
+// Conversions between the artifacts of the PSE snark-verifier aggregator and those of
+// this crate, to migrate proofs and cross check the two aggregators.
+//
+// snark-verifier exposes its KZG accumulator (lhs, rhs) as the first instances of the
+// aggregation circuit, each coordinate in PSE_LIMBS limbs of PSE_BITS bits, and decides
+// it with e(lhs, g2) == e(rhs, s_g2). The final pair (w_x, w_g) here is decided with
+// e(w_x, s_g2) == e(w_g, g2), so w_x is rhs and w_g is lhs.
+//
+// Target proofs are copied as they are. Their transcript must have been written with
+// the PoseidonWrite transcript of sample_circuit: the poseidon transcript of
+// snark-verifier has other parameters and its proofs do not verify here.
+use crate::verify_circuit::SingleProofPair;
+use halo2_ecc_circuit_lib::five::integer_chip::FiveColumnIntegerChipHelper;
+use halo2_ecc_circuit_lib::utils::{bn_to_field, field_to_bn};
+use halo2_proofs::arithmetic::{CurveAffine, Field};
+use num_bigint::BigUint;
+use pairing_bn256::bn256::{Bn256, Fq, Fr, G1Affine};
+
+pub const PSE_LIMBS: usize = 4;
+pub const PSE_BITS: usize = 68;
+// lhs.x, lhs.y, rhs.x and rhs.y
+pub const PSE_ACCUMULATOR_SIZE: usize = 4 * PSE_LIMBS;
+
+fn limbs_to_base(limbs: &[Fr]) -> Option<Fq> {
+    let mut bn = BigUint::from(0u64);
+    for limb in limbs.iter().rev() {
+        let limb = field_to_bn(limb);
+        if limb.bits() as usize > PSE_BITS {
+            return None;
+        }
+        bn = (bn << PSE_BITS) + limb;
+    }
+    if bn >= field_to_bn(&-Fq::one()) + 1u64 {
+        return None;
+    }
+    Some(bn_to_field(&bn))
+}
+
+fn point_from_limbs(limbs: &[Fr]) -> Option<G1Affine> {
+    let x = limbs_to_base(&limbs[..PSE_LIMBS])?;
+    let y = limbs_to_base(&limbs[PSE_LIMBS..2 * PSE_LIMBS])?;
+    Option::from(G1Affine::from_xy(x, y))
+}
+
+fn point_to_limbs(point: &G1Affine, limbs: &mut Vec<Fr>) {
+    // same limbs as the integers of the verify circuit
+    let helper = FiveColumnIntegerChipHelper::<Fq, Fr>::new();
+    let coordinates = point.coordinates().unwrap();
+    limbs.extend(helper.w_to_limb_n_le(coordinates.x()));
+    limbs.extend(helper.w_to_limb_n_le(coordinates.y()));
+}
+
+// (w_x, w_g) of the accumulator in the first PSE_ACCUMULATOR_SIZE instances of a
+// snark-verifier aggregation proof, None if a limb is out of range or a point is not
+// on the curve.
+pub fn pse_accumulator_to_final_pair(instances: &[Fr]) -> Option<(G1Affine, G1Affine)> {
+    if instances.len() < PSE_ACCUMULATOR_SIZE {
+        return None;
+    }
+    let lhs = point_from_limbs(&instances[..2 * PSE_LIMBS])?;
+    let rhs = point_from_limbs(&instances[2 * PSE_LIMBS..PSE_ACCUMULATOR_SIZE])?;
+    Some((rhs, lhs))
+}
+
+// Accumulator instances of snark-verifier for the final pair (w_x, w_g).
+pub fn final_pair_to_pse_accumulator(pair: &(G1Affine, G1Affine)) -> Vec<Fr> {
+    let mut instances = vec![];
+    point_to_limbs(&pair.1, &mut instances);
+    point_to_limbs(&pair.0, &mut instances);
+    instances
+}
+
+// Final pair of this crate, as written to verify_circuit_final_pair.data, of the
+// instances of a snark-verifier aggregation proof: the accumulator, then the
+// instances it exposes of the aggregated proofs.
+pub fn pse_instances_to_final_pair(instances: &[Fr]) -> Option<(G1Affine, G1Affine, Vec<Fr>)> {
+    let (w_x, w_g) = pse_accumulator_to_final_pair(instances)?;
+    Some((w_x, w_g, instances[PSE_ACCUMULATOR_SIZE..].to_vec()))
+}
+
+// Target proof of a snark-verifier Snark, its instance columns and proof bytes, to be
+// aggregated by this crate.
+pub fn pse_snark_to_proof_pair(instances: Vec<Vec<Fr>>, proof: Vec<u8>) -> SingleProofPair<Bn256> {
+    SingleProofPair {
+        // one proof of the circuit
+        instances: vec![instances],
+        transcript: proof,
+    }
+}
@@ -9,10 +9,19 @@ use halo2_proofs::{
     poly::commitment::Params,
 };
 use rand_core::OsRng;
-use std::io::Write;
 
+use crate::envelope::ProofEnvelope;
 use crate::fs::load_target_circuit_params;
 use crate::fs::load_target_circuit_vk;
+use crate::fs::write_file;
+
+pub use halo2_snark_aggregator_derive::TargetCircuit;
+
+// Paths of the code #[derive(TargetCircuit)] generates.
+#[doc(hidden)]
+pub mod __private {
+    pub use pairing_bn256::bn256::{Bn256, Fr, G1Affine};
+}
 
 pub trait TargetCircuit<C: CurveAffine, E: MultiMillerLoop<G1Affine = C>> {
     const TARGET_CIRCUIT_K: u32;
@@ -28,6 +37,27 @@ pub trait TargetCircuit<C: CurveAffine, E: MultiMillerLoop<G1Affine = C>> {
     fn load_instances(buf: &Vec<u8>) -> Vec<Vec<Vec<C::ScalarExt>>>;
 }
 
+// The instances of a circuit with one instance column, read from buf until its end.
+pub fn load_single_column_instances<F: BaseExt>(buf: &Vec<u8>) -> Vec<Vec<Vec<F>>> {
+    let mut ret = vec![];
+    let cursor = &mut std::io::Cursor::new(buf);
+
+    while let Ok(a) = F::read(cursor) {
+        ret.push(a);
+    }
+
+    vec![vec![ret]]
+}
+
+// size zero public inputs in one instance column, no instance column for size 0.
+pub fn zero_instances<F: BaseExt>(size: usize) -> Vec<Vec<F>> {
+    if size == 0 {
+        vec![]
+    } else {
+        vec![vec![F::zero(); size]]
+    }
+}
+
 pub fn sample_circuit_setup<
     C: CurveAffine,
     E: MultiMillerLoop<G1Affine = C>,
@@ -42,17 +72,17 @@ pub fn sample_circuit_setup<
     let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
 
     {
-        folder.push(format!("sample_circuit_{}.params", CIRCUIT::PARAMS_NAME));
-        let mut fd = std::fs::File::create(folder.as_path()).unwrap();
-        folder.pop();
-        params.write(&mut fd).unwrap();
+        let mut buf = vec![];
+        params.write(&mut buf).unwrap();
+        let filename = format!("sample_circuit_{}.params", CIRCUIT::PARAMS_NAME);
+        write_file(&mut folder, &filename, &buf);
     }
 
     {
-        folder.push(format!("sample_circuit_{}.vkey", CIRCUIT::PARAMS_NAME));
-        let mut fd = std::fs::File::create(folder.as_path()).unwrap();
-        folder.pop();
-        vk.write(&mut fd).unwrap();
+        let mut buf = vec![];
+        vk.write(&mut buf).unwrap();
+        let filename = format!("sample_circuit_{}.vkey", CIRCUIT::PARAMS_NAME);
+        write_file(&mut folder, &filename, &buf);
     }
 }
 
@@ -71,6 +101,7 @@ pub fn sample_circuit_random_run<
     let vk = load_target_circuit_vk::<C, E, CIRCUIT>(&mut folder, &params);
     let pk = keygen_pk(&params, vk, &circuit).expect("keygen_pk should not fail");
 
+    let instance_layout = instances.iter().map(|column| column.len() as u32).collect();
     // let instances: &[&[&[C::Scalar]]] = &[&[&[constant * a.square() * b.square()]]];
     let instances: &[&[&[_]]] = &[instances];
     let mut transcript = PoseidonWrite::<_, _, Challenge255<_>>::init(vec![]);
@@ -79,31 +110,27 @@ pub fn sample_circuit_random_run<
     let proof = transcript.finalize();
 
     {
-        folder.push(format!(
-            "sample_circuit_proof_{}{}.data",
-            CIRCUIT::NAME,
-            index
-        ));
-        let mut fd = std::fs::File::create(folder.as_path()).unwrap();
-        folder.pop();
-        fd.write_all(&proof).unwrap();
+        let filename = format!("sample_circuit_proof_{}{}.data", CIRCUIT::NAME, index);
+        write_file(&mut folder, &filename, &proof);
+    }
+
+    {
+        let envelope = ProofEnvelope::target_circuit(pk.get_vk(), instance_layout, proof.clone());
+        let filename = format!("sample_circuit_proof_{}{}.envelope", CIRCUIT::NAME, index);
+        write_file(&mut folder, &filename, &envelope.encode());
     }
 
     {
-        folder.push(format!(
-            "sample_circuit_instance_{}{}.data",
-            CIRCUIT::NAME,
-            index
-        ));
-        let mut fd = std::fs::File::create(folder.as_path()).unwrap();
-        folder.pop();
+        let mut buf = vec![];
         instances.iter().for_each(|l1| {
             l1.iter().for_each(|l2| {
                 l2.iter().for_each(|c: &C::ScalarExt| {
-                    c.write(&mut fd).unwrap();
+                    c.write(&mut buf).unwrap();
                 })
             })
         });
+        let filename = format!("sample_circuit_instance_{}{}.data", CIRCUIT::NAME, index);
+        write_file(&mut folder, &filename, &buf);
     }
 
     let params = params.verifier::<E>(CIRCUIT::PUBLIC_INPUT_SIZE).unwrap();
@@ -0,0 +1,37 @@
+// Optional HMAC-SHA256 signatures of the artifact files written by fs. When the
+// ARTIFACT_KEY_ENV variable is set, each file is written with a <file>.sig next to it
+// and is checked against it on read, so that params, vkeys and proofs swapped in a
+// shared artifact store are rejected before solidity generation. Without the key the
+// files are written and read as they are.
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+pub const ARTIFACT_KEY_ENV: &str = "AGGREGATOR_ARTIFACT_KEY";
+pub const SIGNATURE_EXTENSION: &str = "sig";
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Key of ARTIFACT_KEY_ENV, None if it is unset or empty.
+pub fn artifact_key() -> Option<Vec<u8>> {
+    std::env::var(ARTIFACT_KEY_ENV)
+        .ok()
+        .filter(|key| !key.is_empty())
+        .map(|key| key.into_bytes())
+}
+
+pub fn signature_filename(filename: &str) -> String {
+    format!("{}.{}", filename, SIGNATURE_EXTENSION)
+}
+
+pub fn sign_artifact(key: &[u8], content: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("hmac takes keys of any size");
+    mac.update(content);
+    mac.finalize().into_bytes().to_vec()
+}
+
+// Constant time check of the signature of content.
+pub fn verify_artifact(key: &[u8], content: &[u8], signature: &[u8]) -> bool {
+    let mut mac = HmacSha256::new_from_slice(key).expect("hmac takes keys of any size");
+    mac.update(content);
+    mac.verify_slice(signature).is_ok()
+}
@@ -0,0 +1,91 @@
+// verification_key.json, proof.json and public.json in the style of snarkjs, for the
+// tooling and explorers reading them: field elements are decimal strings, G1 points
+// [x, y, "1"] and G2 points [[x.c0, x.c1], [y.c0, y.c1], ["1", "0"]]. The protocol is
+// "halo2", they are meant to be displayed and sanity checked, not verified by snarkjs.
+use halo2_ecc_circuit_lib::utils::field_to_bn;
+use halo2_proofs::arithmetic::{BaseExt, CurveAffine};
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::commitment::Params;
+use num_bigint::BigUint;
+use pairing_bn256::bn256::{Bn256, Fq, Fr, G1Affine, G2Affine};
+use serde_json::{json, Value};
+use std::io::Cursor;
+
+fn fe_to_dec<F: BaseExt>(f: &F) -> String {
+    field_to_bn(f).to_str_radix(10)
+}
+
+fn g1_to_json(point: &G1Affine) -> Value {
+    match Option::<_>::from(point.coordinates()) {
+        Some(coordinates) => json!([fe_to_dec(coordinates.x()), fe_to_dec(coordinates.y()), "1"]),
+        None => json!(["0", "1", "0"]),
+    }
+}
+
+fn fq2_to_dec<F: BaseExt>(f: &F) -> [String; 2] {
+    let mut bytes = vec![];
+    f.write(&mut bytes).unwrap();
+    [
+        BigUint::from_bytes_le(&bytes[..32]).to_str_radix(10),
+        BigUint::from_bytes_le(&bytes[32..64]).to_str_radix(10),
+    ]
+}
+
+fn g2_to_json(point: &G2Affine) -> Value {
+    match Option::<_>::from(point.coordinates()) {
+        Some(coordinates) => json!([
+            fq2_to_dec(coordinates.x()),
+            fq2_to_dec(coordinates.y()),
+            ["1", "0"]
+        ]),
+        None => json!([["0", "0"], ["1", "0"], ["0", "0"]]),
+    }
+}
+
+// verification_key.json of the verify circuit, n_public is its public inputs size.
+pub fn verification_key_json(
+    params: &Params<G1Affine>,
+    vk: &VerifyingKey<G1Affine>,
+    n_public: usize,
+) -> Value {
+    let verifier = params.verifier::<Bn256>(0).unwrap();
+    json!({
+        "protocol": "halo2",
+        "curve": "bn128",
+        "nPublic": n_public,
+        "power": params.k,
+        "w": fe_to_dec(&vk.domain.get_omega()),
+        "fixed": vk.fixed_commitments.iter().map(g1_to_json).collect::<Vec<_>>(),
+        "permutation": vk.permutation.commitments.iter().map(g1_to_json).collect::<Vec<_>>(),
+        "X_2": g2_to_json(&verifier.s_g2),
+        "G_2": g2_to_json(&verifier.g2),
+    })
+}
+
+// proof.json of verify_circuit_proof.data, as its 32 bytes little endian words, and of
+// the points (w_x, w_g) of verify_circuit_final_pair.data.
+pub fn proof_json(proof: &[u8], final_pair: &[u8]) -> Value {
+    let cursor = &mut Cursor::new(final_pair);
+    let mut point = || {
+        let x = Fq::read(cursor).unwrap();
+        let y = Fq::read(cursor).unwrap();
+        G1Affine::from_xy(x, y).unwrap()
+    };
+    let (w_x, w_g) = (point(), point());
+
+    json!({
+        "protocol": "halo2",
+        "curve": "bn128",
+        "proof": proof
+            .chunks(32)
+            .map(|word| BigUint::from_bytes_le(word).to_str_radix(10))
+            .collect::<Vec<_>>(),
+        "w_x": g1_to_json(&w_x),
+        "w_g": g1_to_json(&w_g),
+    })
+}
+
+// public.json of verify_circuit_instance.data.
+pub fn public_json(instances: &[Fr]) -> Value {
+    json!(instances.iter().map(fe_to_dec).collect::<Vec<_>>())
+}
@@ -3,3 +3,24 @@ mod mul_add;
 
 #[cfg(test)]
 mod lookup;
+
+#[cfg(test)]
+mod pse;
+
+#[cfg(test)]
+mod snarkjs;
+
+#[cfg(test)]
+mod signing;
+
+#[cfg(test)]
+mod envelope;
+
+#[cfg(test)]
+mod malformed;
+
+#[cfg(test)]
+mod vk;
+
+#[cfg(all(test, feature = "dev-graph"))]
+mod layout;
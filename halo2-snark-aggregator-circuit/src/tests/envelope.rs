@@ -0,0 +1,85 @@
+use crate::envelope::*;
+
+fn envelope() -> ProofEnvelope {
+    ProofEnvelope {
+        curve: EnvelopeCurve::Bn256,
+        transcript: EnvelopeTranscript::Sha256,
+        vk_hash: [7u8; 32],
+        instance_layout: vec![4],
+        proof: vec![1, 2, 3],
+    }
+}
+
+#[test]
+fn test_envelope_roundtrip() {
+    let bytes = envelope().encode();
+    assert!(is_envelope(&bytes));
+    assert!(!is_envelope(&envelope().proof));
+    assert_eq!(ProofEnvelope::decode(&bytes), Ok(envelope()));
+    // magic, version, curve, transcript, vk hash, 1 column of rows, proof length, proof
+    assert_eq!(bytes.len(), 4 + 2 + 1 + 1 + 32 + 4 + 4 + 4 + 3);
+}
+
+#[test]
+fn test_envelope_decode_errors() {
+    let bytes = envelope().encode();
+
+    let mut magic = bytes.clone();
+    magic[0] = b'X';
+    assert_eq!(
+        ProofEnvelope::decode(&magic),
+        Err(EnvelopeError::InvalidMagic)
+    );
+
+    // version 1 envelopes carry the vk hash of before vk::vk_hash
+    let mut version = bytes.clone();
+    version[4] = 1;
+    assert_eq!(
+        ProofEnvelope::decode(&version),
+        Err(EnvelopeError::UnsupportedVersion(1))
+    );
+
+    let mut transcript = bytes.clone();
+    transcript[7] = 9;
+    assert_eq!(
+        ProofEnvelope::decode(&transcript),
+        Err(EnvelopeError::UnknownTranscript(9))
+    );
+
+    let truncated = &bytes[..bytes.len() - 1];
+    assert_eq!(
+        ProofEnvelope::decode(truncated),
+        Err(EnvelopeError::UnexpectedEnd)
+    );
+
+    let mut trailing = bytes;
+    trailing.push(0);
+    assert_eq!(
+        ProofEnvelope::decode(&trailing),
+        Err(EnvelopeError::TrailingBytes)
+    );
+}
+
+#[test]
+fn test_envelope_check() {
+    let expected = envelope();
+    assert_eq!(envelope().check(&expected), Ok(()));
+
+    let mut other = envelope();
+    other.transcript = EnvelopeTranscript::Poseidon;
+    assert_eq!(
+        other.check(&expected),
+        Err(EnvelopeError::TranscriptMismatch)
+    );
+
+    let mut other = envelope();
+    other.vk_hash[0] = 0;
+    assert_eq!(other.check(&expected), Err(EnvelopeError::VkHashMismatch));
+
+    let mut other = envelope();
+    other.instance_layout = vec![4, 1];
+    assert_eq!(
+        other.check(&expected),
+        Err(EnvelopeError::InstanceLayoutMismatch)
+    );
+}
@@ -0,0 +1,50 @@
+use crate::layout::*;
+use halo2_proofs::arithmetic::Field;
+use halo2_snark_aggregator_api::systems::halo2::verify::LAYOUT_TARGET;
+use halo2_snark_aggregator_api::tests::systems::halo2::add_mul_test;
+use pairing_bn256::bn256::Fr;
+use rand_core::OsRng;
+
+fn mark(row: u64, step: &str) {
+    tracing::trace!(target: LAYOUT_TARGET, row, step = %step);
+}
+
+#[test]
+fn test_collect_steps_of_the_last_pass() {
+    let (_, steps) = collect_steps(|| {
+        // a measurement pass, then the assignment pass from row 0 again
+        mark(0, "c0p0 instances");
+        mark(40, "end");
+        mark(0, "c0p0 instances");
+        mark(10, "empty");
+        mark(10, "c0p0 proof");
+        mark(30, "msm");
+        mark(50, "end");
+        tracing::trace!(row = 60u64, step = "not a layout event");
+    });
+
+    let step = |name: &str, rows| LayoutStep {
+        name: name.to_owned(),
+        rows,
+    };
+    assert_eq!(
+        steps,
+        vec![
+            step("c0p0 instances", 0..10),
+            step("c0p0 proof", 10..30),
+            step("msm", 30..50)
+        ]
+    );
+}
+
+#[test]
+fn test_render_layout_writes_a_png() {
+    let path = std::env::temp_dir().join("halo2_snark_aggregator_layout_test.png");
+    let circuit = add_mul_test::test_circuit_builder(Fr::random(OsRng), Fr::one());
+
+    let steps = render_layout(10, &circuit, &path).unwrap();
+    // the add_mul circuit marks no steps
+    assert!(steps.is_empty());
+    assert!(std::fs::metadata(&path).unwrap().len() > 0);
+    std::fs::remove_file(&path).unwrap();
+}
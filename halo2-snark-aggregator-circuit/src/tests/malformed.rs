@@ -0,0 +1,66 @@
+use crate::fs::{decode_verify_circuit_final_pair, encode_verify_circuit_final_pair};
+use crate::malformed::*;
+use halo2_proofs::arithmetic::Field;
+use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk};
+use halo2_proofs::poly::commitment::{Params, ParamsVerifier};
+use halo2_proofs::transcript::Challenge255;
+use halo2_snark_aggregator_api::tests::systems::halo2::add_mul_test::test_circuit_builder;
+use halo2_snark_aggregator_api::transcript::sha::ShaWrite;
+use pairing_bn256::bn256::{Bn256, Fr, G1Affine, G1};
+use pairing_bn256::group::{Curve, Group};
+use rand_core::OsRng;
+
+#[test]
+fn test_malformed_proofs_fail_as_labelled() {
+    let a = Fr::random(OsRng);
+    let b = Fr::random(OsRng);
+    let c = Fr::from(7) * a.square() * b.square();
+
+    let params = Params::<G1Affine>::unsafe_setup::<Bn256>(10);
+    let vk = keygen_vk(&params, &test_circuit_builder(a, b)).unwrap();
+    let pk = keygen_pk(&params, vk, &test_circuit_builder(a, b)).unwrap();
+    let instances: &[&[&[Fr]]] = &[&[&[c]]];
+
+    let mut transcript = ShaWrite::<_, _, Challenge255<_>, sha2::Sha256>::init(vec![]);
+    create_proof(
+        &params,
+        &pk,
+        &[test_circuit_builder(a, b)],
+        instances,
+        OsRng,
+        &mut transcript,
+    )
+    .unwrap();
+    let proof = transcript.finalize();
+    let params_verifier: ParamsVerifier<Bn256> = params.verifier(1).unwrap();
+
+    let malformed = malformed_proofs(pk.get_vk(), &params_verifier, &[c], &proof, &[]).unwrap();
+    assert!(malformed.iter().any(|m| m.label == "flip advice[0]"));
+    assert!(malformed.iter().any(|m| m.label.starts_with("swap ")));
+    for m in malformed.iter() {
+        assert_ne!(m.proof, proof);
+        check_malformed_proof(pk.get_vk(), &params_verifier, None, m).unwrap();
+    }
+
+    let json = malformed_proofs_json(&malformed);
+    assert_eq!(json["count"], malformed.len());
+    assert_eq!(json["fixtures"][0]["label"], "flip advice[0]");
+    assert_eq!(json["fixtures"][0]["expected_failure"], "invalid_point");
+    assert!(json["fixtures"][0]["proof"]
+        .as_str()
+        .unwrap()
+        .starts_with("0x"));
+}
+
+#[test]
+fn test_final_pair_round_trip() {
+    let pair = (
+        G1::random(OsRng).to_affine(),
+        G1::random(OsRng).to_affine(),
+        vec![Fr::from(3), -Fr::one()],
+    );
+    let bytes = encode_verify_circuit_final_pair(&pair);
+    assert_eq!(decode_verify_circuit_final_pair(&bytes).unwrap(), pair);
+    assert!(decode_verify_circuit_final_pair(&bytes[..bytes.len() - 1]).is_err());
+    assert!(decode_verify_circuit_final_pair(&bytes[..64]).is_err());
+}
@@ -0,0 +1,61 @@
+use crate::pse::*;
+use crate::verify_circuit::final_pair_to_instances;
+use halo2_proofs::arithmetic::{CurveAffine, Field};
+use pairing_bn256::bn256::{Bn256, Fr, G1Affine, G1};
+use pairing_bn256::group::{Curve, Group};
+use rand_core::OsRng;
+
+#[test]
+fn test_pse_accumulator_round_trip() {
+    let w_x = G1::random(OsRng).to_affine();
+    let w_g = G1::random(OsRng).to_affine();
+
+    let accumulator = final_pair_to_pse_accumulator(&(w_x, w_g));
+    assert_eq!(accumulator.len(), PSE_ACCUMULATOR_SIZE);
+    assert_eq!(
+        pse_accumulator_to_final_pair(&accumulator),
+        Some((w_x, w_g))
+    );
+
+    // lhs comes first
+    let mut lhs_first = final_pair_to_pse_accumulator(&(w_g, w_g));
+    lhs_first.truncate(2 * PSE_LIMBS);
+    assert_eq!(&accumulator[..2 * PSE_LIMBS], &lhs_first[..]);
+
+    let mut instances = accumulator.clone();
+    instances.extend([Fr::from(1), Fr::from(2)]);
+    let final_pair = pse_instances_to_final_pair(&instances).unwrap();
+    assert_eq!(final_pair, (w_x, w_g, vec![Fr::from(1), Fr::from(2)]));
+    // same public inputs as an aggregation of this crate
+    assert_eq!(
+        final_pair_to_instances::<G1Affine, Bn256>(&final_pair).len(),
+        4 + 2
+    );
+}
+
+#[test]
+fn test_pse_accumulator_rejects() {
+    let w = G1Affine::generator();
+    let accumulator = final_pair_to_pse_accumulator(&(w, w));
+    assert_eq!(
+        pse_accumulator_to_final_pair(&accumulator[..PSE_ACCUMULATOR_SIZE - 1]),
+        None
+    );
+
+    // limb over PSE_BITS bits
+    let mut wide = accumulator.clone();
+    wide[0] = Fr::from(1u64 << 34).square().double();
+    assert_eq!(pse_accumulator_to_final_pair(&wide), None);
+
+    // point off the curve
+    let mut off_curve = accumulator.clone();
+    off_curve[PSE_LIMBS] += Fr::one();
+    assert_eq!(pse_accumulator_to_final_pair(&off_curve), None);
+}
+
+#[test]
+fn test_pse_snark_to_proof_pair() {
+    let pair = pse_snark_to_proof_pair(vec![vec![Fr::from(3)]], vec![1, 2]);
+    assert_eq!(pair.instances, vec![vec![vec![Fr::from(3)]]]);
+    assert_eq!(pair.transcript, vec![1, 2]);
+}
@@ -0,0 +1,38 @@
+use crate::fs::{read_signed_file, write_signed_file};
+use crate::signing::*;
+
+#[test]
+fn test_sign_artifact() {
+    let content = b"verify_circuit.vkey".to_vec();
+    let signature = sign_artifact(b"key", &content);
+    assert_eq!(signature.len(), 32);
+
+    assert!(verify_artifact(b"key", &content, &signature));
+    assert!(!verify_artifact(b"other key", &content, &signature));
+    assert!(!verify_artifact(b"key", b"swapped vkey", &signature));
+    assert!(!verify_artifact(b"key", &content, &signature[..31]));
+}
+
+#[test]
+fn test_signed_files() {
+    let mut folder = std::env::temp_dir().join(format!("signed_files_{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+
+    let key = Some(&b"key"[..]);
+    let content = vec![1u8, 2, 3];
+    write_signed_file(&mut folder, "artifact.data", &content, key);
+    assert!(folder.join(signature_filename("artifact.data")).exists());
+    assert_eq!(read_signed_file(&mut folder, "artifact.data", key), content);
+
+    // a swapped file no longer matches its signature, an unsigned one is rejected
+    std::fs::write(folder.join("artifact.data"), [3u8, 2, 1]).unwrap();
+    let swapped =
+        std::panic::catch_unwind(|| read_signed_file(&mut folder.clone(), "artifact.data", key));
+    assert!(swapped.is_err());
+    write_signed_file(&mut folder, "unsigned.data", &content, None);
+    let unsigned =
+        std::panic::catch_unwind(|| read_signed_file(&mut folder.clone(), "unsigned.data", key));
+    assert!(unsigned.is_err());
+
+    std::fs::remove_dir_all(&folder).unwrap();
+}
@@ -0,0 +1,48 @@
+use crate::snarkjs::*;
+use halo2_proofs::arithmetic::{BaseExt, Field};
+use halo2_proofs::plonk::keygen_vk;
+use halo2_proofs::poly::commitment::Params;
+use halo2_snark_aggregator_api::tests::systems::halo2::lookup_test;
+use pairing_bn256::bn256::{Bn256, Fq, Fr, G1Affine};
+
+#[test]
+fn test_verification_key_json() {
+    let params = Params::<G1Affine>::unsafe_setup::<Bn256>(6);
+    let vk = keygen_vk(&params, &lookup_test::test_circuit::test_circuit_builder()).unwrap();
+    let json = verification_key_json(&params, &vk, 4);
+
+    assert_eq!(json["protocol"], "halo2");
+    assert_eq!(json["nPublic"], 4);
+    assert_eq!(json["power"], 6);
+    assert_eq!(
+        json["fixed"].as_array().unwrap().len(),
+        vk.fixed_commitments.len()
+    );
+    assert_eq!(json["fixed"][0][2], "1");
+    assert_eq!(json["X_2"][2], serde_json::json!(["1", "0"]));
+}
+
+#[test]
+fn test_proof_json() {
+    let mut final_pair = vec![];
+    // the generator (1, 2) twice, then a scalar
+    for v in [1u64, 2, 1, 2] {
+        Fq::from(v).write(&mut final_pair).unwrap();
+    }
+    Fr::from(7).write(&mut final_pair).unwrap();
+    let mut proof = vec![0u8; 64];
+    proof[32] = 0xff;
+
+    let json = proof_json(&proof, &final_pair);
+    assert_eq!(json["proof"], serde_json::json!(["0", "255"]));
+    assert_eq!(json["w_x"], serde_json::json!(["1", "2", "1"]));
+    assert_eq!(json["w_g"], json["w_x"]);
+
+    assert_eq!(
+        public_json(&[Fr::from(3), -Fr::one()]),
+        serde_json::json!([
+            "3",
+            "21888242871839275222246405745257275088548364400416034343698204186575808495616"
+        ])
+    );
+}
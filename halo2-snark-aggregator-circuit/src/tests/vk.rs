@@ -0,0 +1,48 @@
+use crate::vk::*;
+use halo2_proofs::arithmetic::{Field, FieldExt};
+use halo2_proofs::plonk::keygen_vk;
+use halo2_proofs::poly::commitment::Params;
+use halo2_snark_aggregator_api::tests::systems::halo2::{add_mul_test, lookup_test};
+use pairing_bn256::bn256::{Bn256, Fr, G1Affine};
+use rand_core::OsRng;
+
+#[test]
+fn test_vk_hash_is_canonical() {
+    let params = Params::<G1Affine>::unsafe_setup::<Bn256>(10);
+    let add_mul = |a, b| keygen_vk(&params, &add_mul_test::test_circuit_builder(a, b));
+    let vk = add_mul(Fr::random(OsRng), Fr::random(OsRng)).unwrap();
+
+    // the vk does not depend on the witness, nor its hash
+    let other = add_mul(Fr::random(OsRng), Fr::random(OsRng)).unwrap();
+    assert_eq!(vk_hash(&vk), vk_hash(&other));
+    assert_eq!(vk_hash_hex(&vk).len(), 64);
+
+    let preimage = vk_hash_preimage(&vk);
+    assert!(preimage.starts_with(VK_HASH_DOMAIN));
+    assert_eq!(preimage[VK_HASH_DOMAIN.len()], VK_HASH_VERSION);
+    let k = &preimage[VK_HASH_DOMAIN.len() + 1..VK_HASH_DOMAIN.len() + 5];
+    assert_eq!(k, 10u32.to_le_bytes());
+
+    // another circuit, or the same one on another domain, hashes differently
+    let lookup = keygen_vk(&params, &lookup_test::test_circuit::test_circuit_builder()).unwrap();
+    assert_ne!(vk_hash(&vk), vk_hash(&lookup));
+    let params = Params::<G1Affine>::unsafe_setup::<Bn256>(11);
+    let a = Fr::random(OsRng);
+    let larger = keygen_vk(&params, &add_mul_test::test_circuit_builder(a, a));
+    assert_ne!(vk_hash(&vk), vk_hash(&larger.unwrap()));
+}
+
+#[test]
+fn test_vk_hash_instances() {
+    let mut hash = [0u8; 32];
+    hash[15] = 1;
+    hash[31] = 2;
+    assert_eq!(vk_hash_instances::<Fr>(&hash), [Fr::from(1), Fr::from(2)]);
+
+    // both halves stay under 2^128, the hash is their concatenation
+    let hash = [0xff; 32];
+    let shift = Fr::from_u128(u128::MAX) + Fr::one();
+    let [high, low] = vk_hash_instances::<Fr>(&hash);
+    assert_eq!(high, Fr::from_u128(u128::MAX));
+    assert_eq!(high * shift + low, shift * shift - Fr::one());
+}
@@ -1,9 +1,11 @@
+use crate::envelope::instance_layout;
 use crate::fs::{
-    load_target_circuit_instance, load_target_circuit_params, load_target_circuit_proof,
-    load_target_circuit_vk, load_verify_circuit_instance, load_verify_circuit_params,
-    load_verify_circuit_proof, load_verify_circuit_vk,
+    load_target_circuit_instance, load_target_circuit_params, load_target_circuit_proof_checked,
+    load_target_circuit_vk, load_transcript_label, load_verify_circuit_instance,
+    load_verify_circuit_params, load_verify_circuit_proof, load_verify_circuit_vk,
 };
 use crate::sample_circuit::TargetCircuit;
+use crate::vk::{vk_hash, vk_hash_instances};
 
 use super::chips::{ecc_chip::EccChip, encode_chip::PoseidonEncodeChip, scalar_chip::ScalarChip};
 use halo2_ecc_circuit_lib::chips::integer_chip::IntegerChipOps;
@@ -33,7 +35,7 @@ use halo2_proofs::{
 };
 use halo2_proofs::{
     plonk::{keygen_pk, verify_proof, SingleVerifier},
-    transcript::Challenge255,
+    transcript::{Challenge255, PoseidonWrite, TranscriptWrite},
 };
 use halo2_snark_aggregator_api::mock::arith::{
     ecc::MockEccChip,
@@ -41,20 +43,28 @@ use halo2_snark_aggregator_api::mock::arith::{
 };
 use halo2_snark_aggregator_api::mock::transcript_encode::PoseidonEncode;
 use halo2_snark_aggregator_api::systems::halo2::verify::{
-    verify_aggregation_proofs_in_chip, CircuitProof,
+    mark_layout_step, verify_aggregation_proofs_in_chip,
+    verify_aggregation_proofs_with_instance_commitments, CircuitProof,
 };
 use halo2_snark_aggregator_api::systems::halo2::{
-    transcript::PoseidonTranscriptRead, verify::ProofData,
+    instance::{validate_instances, InstanceError},
+    transcript::PoseidonTranscriptRead,
+    verify::ProofData,
 };
-use halo2_snark_aggregator_api::transcript::sha::{ShaRead, ShaWrite};
-use log::info;
+use halo2_snark_aggregator_api::systems::pairing::MultiPairingCheck;
+// the cache of MultiCircuitsCreateProof::call_with_cache, for the crates of zkaggregate!
+pub use halo2_snark_aggregator_api::systems::halo2::instance::InstanceCommitmentCache;
+use halo2_snark_aggregator_api::transcript::sha::{label_scalar, ShaRead, ShaWrite};
 use pairing_bn256::bn256::{Bn256, Fr, G1Affine};
-use pairing_bn256::group::Curve;
+use pairing_bn256::group::{prime::PrimeCurveAffine, Curve};
 use rand_core::OsRng;
+use sha2::Digest;
 use std::env::var;
+use std::fmt;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::{io::Read, marker::PhantomData};
+use tracing::{info, info_span};
 
 const COMMON_RANGE_BITS: usize = 17usize;
 
@@ -107,6 +117,23 @@ impl<
     > Halo2CircuitInstances<'a, E, N>
 {
     pub fn calc_verify_circuit_final_pair(&self) -> (C, C, Vec<<C as CurveAffine>::ScalarExt>) {
+        self.final_pair(None)
+    }
+
+    // calc_verify_circuit_final_pair with the instance commitments of the target proofs taken
+    // from cache, and those of proofs not in it added to it.
+    pub fn calc_verify_circuit_final_pair_with_cache(
+        &self,
+        cache: &mut InstanceCommitmentCache<C>,
+    ) -> (C, C, Vec<<C as CurveAffine>::ScalarExt>) {
+        self.final_pair(Some(cache))
+    }
+
+    fn final_pair(
+        &self,
+        cache: Option<&mut InstanceCommitmentCache<C>>,
+    ) -> (C, C, Vec<<C as CurveAffine>::ScalarExt>) {
+        let _span = info_span!("final_pair").entered();
         let nchip = MockFieldChip::<C::ScalarExt, Error>::default();
         let schip = MockFieldChip::<C::ScalarExt, Error>::default();
         let pchip = MockEccChip::<C, Error>::default();
@@ -157,15 +184,44 @@ impl<
             )
             .unwrap();
 
-        let (w_x, w_g, instances, _) = verify_aggregation_proofs_in_chip(
-            ctx,
-            &nchip,
-            &schip,
-            &pchip,
-            circuit_proofs,
-            &mut transcript,
-        )
+        let (w_x, w_g, mut instances, _) = match cache {
+            None => verify_aggregation_proofs_in_chip(
+                ctx,
+                &nchip,
+                &schip,
+                &pchip,
+                circuit_proofs,
+                &mut transcript,
+            ),
+            Some(cache) => {
+                let commitments = self
+                    .0
+                    .iter()
+                    .map(|instance| {
+                        let instances = instance
+                            .n_instances
+                            .iter()
+                            .map(|x| x.iter().map(|y| &y[..]).collect::<Vec<_>>())
+                            .collect::<Vec<_>>();
+                        let instances = instances.iter().map(|x| &x[..]).collect::<Vec<_>>();
+                        cache.commitments(instance.params, &instances[..])
+                    })
+                    .collect::<Vec<_>>();
+                verify_aggregation_proofs_with_instance_commitments(
+                    ctx,
+                    &nchip,
+                    &schip,
+                    &pchip,
+                    circuit_proofs,
+                    &commitments[..],
+                    &mut transcript,
+                )
+            }
+        }
         .unwrap();
+        for instance in self.0.iter() {
+            instances.extend(vk_hash_instances::<C::ScalarExt>(&vk_hash(instance.vk)));
+        }
 
         (w_x.to_affine(), w_g.to_affine(), instances)
     }
@@ -224,6 +280,12 @@ impl<
         let mut res = self.synthesize_proof(&base_gate, &range_gate, &mut layouter)?;
 
         let integer_chip = FiveColumnIntegerChip::new(&range_gate);
+        // the vk hash of each target circuit follows its instances, constants of the circuit
+        let vk_hashes = self
+            .circuits
+            .iter()
+            .flat_map(|circuit| vk_hash_instances::<C::ScalarExt>(&vk_hash(circuit.vk)))
+            .collect::<Vec<_>>();
 
         let mut x0_low = None;
         let mut x0_high = None;
@@ -317,7 +379,11 @@ impl<
                 x0_high = Some(x0_high_);
                 x1_low = Some(x1_low_);
                 x1_high = Some(x1_high_);
-                instances = Some(res.2.clone());
+                let mut instances_ = res.2.clone();
+                for limb in vk_hashes.iter() {
+                    instances_.push(base_gate.assign_constant(ctx, *limb)?);
+                }
+                instances = Some(instances_);
                 Ok(())
             },
         )?;
@@ -359,8 +425,11 @@ impl<
         ),
         Error,
     > {
+        let _span = info_span!("witness_synthesis").entered();
         let integer_chip = FiveColumnIntegerChip::new(range_gate);
         let ecc_chip = NativeEccChip::new(&integer_chip);
+        // one set of range tables for the whole circuit, every sub-verifier below looks up the
+        // same table columns
         range_gate
             .init_table(layouter, &integer_chip.helper.integer_modulus)
             .unwrap();
@@ -453,6 +522,7 @@ impl<
                     &mut transcript,
                 )?;
 
+                mark_layout_step(ctx, schip, "coherent");
                 for coherent in &self.coherent {
                     ecc_chip.assert_equal(
                         ctx,
@@ -463,6 +533,7 @@ impl<
 
                 base_gate.assert_false(ctx, &p1.z)?;
                 base_gate.assert_false(ctx, &p2.z)?;
+                mark_layout_step(ctx, schip, "end");
                 r = Some((p1, p2, v));
                 Ok(())
             },
@@ -562,7 +633,20 @@ impl Setup<G1Affine, Bn256> {
     where
         L: Fn(&Vec<u8>) -> Vec<Vec<Vec<Fr>>>,
     {
-        let target_circuit_instances = (0..SingleCircuit::N_PROOFS)
+        Self::with_nproofs::<SingleCircuit, L>(folder, load_instances, SingleCircuit::N_PROOFS)
+    }
+
+    // The first nproofs proofs of the folder, for a proof count given at the aggregation site
+    // instead of the N_PROOFS of the circuit.
+    pub fn with_nproofs<SingleCircuit: TargetCircuit<G1Affine, Bn256>, L>(
+        folder: &PathBuf,
+        load_instances: L,
+        nproofs: usize,
+    ) -> Setup<G1Affine, Bn256>
+    where
+        L: Fn(&Vec<u8>) -> Vec<Vec<Vec<Fr>>>,
+    {
+        let target_circuit_instances = (0..nproofs)
             .map(|index| {
                 load_instances(&load_target_circuit_instance::<SingleCircuit>(
                     &mut folder.clone(),
@@ -571,8 +655,22 @@ impl Setup<G1Affine, Bn256> {
             })
             .collect::<Vec<_>>();
 
-        let proofs = (0..SingleCircuit::N_PROOFS)
-            .map(|index| load_target_circuit_proof::<SingleCircuit>(&mut folder.clone(), index))
+        let target_circuit_params =
+            load_target_circuit_params::<G1Affine, Bn256, SingleCircuit>(&mut folder.clone());
+        let target_circuit_vk = load_target_circuit_vk::<G1Affine, Bn256, SingleCircuit>(
+            &mut folder.clone(),
+            &target_circuit_params,
+        );
+
+        let proofs = (0..nproofs)
+            .map(|index| {
+                load_target_circuit_proof_checked::<SingleCircuit>(
+                    &mut folder.clone(),
+                    index,
+                    &target_circuit_vk,
+                    instance_layout(&target_circuit_instances[index]),
+                )
+            })
             .collect::<Vec<_>>();
 
         let single_proof_witness = target_circuit_instances
@@ -584,19 +682,12 @@ impl Setup<G1Affine, Bn256> {
             })
             .collect::<Vec<_>>();
 
-        let target_circuit_params =
-            load_target_circuit_params::<G1Affine, Bn256, SingleCircuit>(&mut folder.clone());
-        let target_circuit_vk = load_target_circuit_vk::<G1Affine, Bn256, SingleCircuit>(
-            &mut folder.clone(),
-            &target_circuit_params,
-        );
-
         Setup {
             name: format!("{:?}", folder),
             target_circuit_params: Rc::new(target_circuit_params),
             target_circuit_vk: Rc::new(target_circuit_vk),
             proofs: single_proof_witness,
-            nproofs: SingleCircuit::N_PROOFS,
+            nproofs,
         }
     }
 }
@@ -677,16 +768,16 @@ impl<C: CurveAffine, E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>, co
             _ => None,
         };
 
-        println!("params path: {:?}", path);
+        info!("params path: {:?}", path);
         if path.is_some() && Path::exists(&path.unwrap()) {
-            println!("read params from {:?}", path.unwrap());
+            info!("read params from {:?}", path.unwrap());
             let mut fd = std::fs::File::open(&path.unwrap()).unwrap();
             Params::<C>::read(&mut fd).unwrap()
         } else {
             let params = Params::<C>::unsafe_setup::<E>(k);
 
             if let Some(path) = path {
-                println!("write params to {:?}", path);
+                info!("write params to {:?}", path);
 
                 let mut fd = std::fs::File::create(path).unwrap();
 
@@ -724,12 +815,45 @@ impl<C: CurveAffine, E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>, co
         let verify_circuit_params = Self::get_params_cached(verify_circuit_k);
         info!("setup params done");
 
-        let verify_circuit_vk =
-            keygen_vk(&verify_circuit_params, &verify_circuit).expect("keygen_vk should not fail");
+        let verify_circuit_vk = info_span!("keygen_vk").in_scope(|| {
+            keygen_vk(&verify_circuit_params, &verify_circuit).expect("keygen_vk should not fail")
+        });
         info!("setup vkey done");
 
         (verify_circuit_params, verify_circuit_vk)
     }
+
+    // Renders the layout of the verify circuit on 2^verify_circuit_k rows to the png at path,
+    // with the rows of every sub-verifier step.
+    #[cfg(feature = "dev-graph")]
+    pub fn render_layout(
+        &self,
+        verify_circuit_k: u32,
+        path: &Path,
+    ) -> Result<Vec<crate::layout::LayoutStep>, Box<dyn std::error::Error>> {
+        let setup_outcome = self.new_verify_circuit_info(true);
+
+        let verify_circuit = verify_circuit_builder(
+            from_0_to_n::<N>().map(|i| Halo2VerifierCircuit {
+                name: setup_outcome[i].name.clone(),
+                params: &setup_outcome[i].params_verifier,
+                vk: &setup_outcome[i].vk,
+                proofs: setup_outcome[i]
+                    .instances
+                    .iter()
+                    .zip(setup_outcome[i].proofs.iter())
+                    .map(|(instances, transcript)| SingleProofWitness {
+                        instances,
+                        transcript,
+                    })
+                    .collect(),
+                nproofs: setup_outcome[i].nproofs,
+            }),
+            self.coherent.clone(),
+        );
+
+        crate::layout::render_layout(verify_circuit_k, &verify_circuit, path)
+    }
 }
 
 pub fn final_pair_to_instances<
@@ -808,7 +932,20 @@ impl CreateProof<G1Affine, Bn256> {
     where
         L: Fn(&Vec<u8>) -> Vec<Vec<Vec<Fr>>>,
     {
-        let instances = (0..SingleCircuit::N_PROOFS)
+        Self::with_nproofs::<SingleCircuit, L>(folder, load_instances, SingleCircuit::N_PROOFS)
+    }
+
+    // The first nproofs proofs of the folder, for a proof count given at the aggregation site
+    // instead of the N_PROOFS of the circuit.
+    pub fn with_nproofs<SingleCircuit: TargetCircuit<G1Affine, Bn256>, L>(
+        folder: &PathBuf,
+        load_instances: L,
+        nproofs: usize,
+    ) -> CreateProof<G1Affine, Bn256>
+    where
+        L: Fn(&Vec<u8>) -> Vec<Vec<Vec<Fr>>>,
+    {
+        let instances = (0..nproofs)
             .map(|index| {
                 load_instances(&load_target_circuit_instance::<SingleCircuit>(
                     &mut folder.clone(),
@@ -817,8 +954,22 @@ impl CreateProof<G1Affine, Bn256> {
             })
             .collect::<Vec<_>>();
 
-        let proofs = (0..SingleCircuit::N_PROOFS)
-            .map(|index| load_target_circuit_proof::<SingleCircuit>(&mut folder.clone(), index))
+        let target_circuit_params =
+            load_target_circuit_params::<G1Affine, Bn256, SingleCircuit>(&mut folder.clone());
+        let target_circuit_vk = load_target_circuit_vk::<G1Affine, Bn256, SingleCircuit>(
+            &mut folder.clone(),
+            &target_circuit_params,
+        );
+
+        let proofs = (0..nproofs)
+            .map(|index| {
+                load_target_circuit_proof_checked::<SingleCircuit>(
+                    &mut folder.clone(),
+                    index,
+                    &target_circuit_vk,
+                    instance_layout(&instances[index]),
+                )
+            })
             .collect::<Vec<_>>();
 
         let single_proof_witness = instances
@@ -830,20 +981,13 @@ impl CreateProof<G1Affine, Bn256> {
             })
             .collect::<Vec<_>>();
 
-        let target_circuit_params =
-            load_target_circuit_params::<G1Affine, Bn256, SingleCircuit>(&mut folder.clone());
-        let target_circuit_vk = load_target_circuit_vk::<G1Affine, Bn256, SingleCircuit>(
-            &mut folder.clone(),
-            &target_circuit_params,
-        );
-
         CreateProof {
             name: format!("{:?}", folder),
             target_circuit_params: Rc::new(target_circuit_params),
             target_circuit_vk: Rc::new(target_circuit_vk),
             template_proofs: single_proof_witness.clone(),
             proofs: single_proof_witness,
-            nproofs: SingleCircuit::N_PROOFS,
+            nproofs,
         }
     }
 }
@@ -858,6 +1002,50 @@ pub struct MultiCircuitsCreateProof<
     pub verify_circuit_params: &'a Params<C>,
     pub verify_circuit_vk: VerifyingKey<C>,
     pub coherent: Vec<[(usize, usize); 2]>,
+    // deployment label absorbed first in the transcript of the verify circuit proof
+    pub transcript_label: Option<Vec<u8>>,
+}
+
+// Transcript the proof of the verify circuit is written with. Sha256 is the one of the
+// cli and the contracts, the others are compared against it in benches/transcript.rs.
+pub trait VerifyCircuitTranscript<C: CurveAffine>: TranscriptWrite<C, Challenge255<C>> {
+    fn init() -> Self;
+    fn finalize(self) -> Vec<u8>;
+
+    fn init_with_label(label: Option<&[u8]>) -> Self
+    where
+        Self: Sized,
+    {
+        let mut transcript = Self::init();
+        if let Some(label) = label {
+            transcript
+                .common_scalar(label_scalar::<C>(label))
+                .expect("absorbing a scalar does not fail");
+        }
+        transcript
+    }
+}
+
+impl<C: CurveAffine, D: Digest + Clone> VerifyCircuitTranscript<C>
+    for ShaWrite<Vec<u8>, C, Challenge255<C>, D>
+{
+    fn init() -> Self {
+        ShaWrite::init(vec![])
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        ShaWrite::finalize(self)
+    }
+}
+
+impl<C: CurveAffine> VerifyCircuitTranscript<C> for PoseidonWrite<Vec<u8>, C, Challenge255<C>> {
+    fn init() -> Self {
+        PoseidonWrite::init(vec![])
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        PoseidonWrite::finalize(self)
+    }
 }
 
 impl<C: CurveAffine, E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>, const N: usize>
@@ -870,6 +1058,45 @@ impl<C: CurveAffine, E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>, co
         (C, C, Vec<C::ScalarExt>),
         Vec<C::ScalarExt>,
         Vec<u8>,
+    ) {
+        self.call_with_transcript::<ShaWrite<Vec<u8>, C, Challenge255<C>, sha2::Sha256>>()
+    }
+
+    // call with the instance commitments of the final pair taken from cache, see
+    // Halo2CircuitInstances::calc_verify_circuit_final_pair_with_cache. Those of the verify
+    // circuit proof itself are computed by halo2's create_proof.
+    pub fn call_with_cache(
+        self,
+        cache: &mut InstanceCommitmentCache<C>,
+    ) -> (
+        ProvingKey<C>,
+        (C, C, Vec<C::ScalarExt>),
+        Vec<C::ScalarExt>,
+        Vec<u8>,
+    ) {
+        self.prove::<ShaWrite<Vec<u8>, C, Challenge255<C>, sha2::Sha256>>(Some(cache))
+    }
+
+    // call with the proof of the verify circuit written with the transcript T.
+    pub fn call_with_transcript<T: VerifyCircuitTranscript<C>>(
+        self,
+    ) -> (
+        ProvingKey<C>,
+        (C, C, Vec<C::ScalarExt>),
+        Vec<C::ScalarExt>,
+        Vec<u8>,
+    ) {
+        self.prove::<T>(None)
+    }
+
+    fn prove<T: VerifyCircuitTranscript<C>>(
+        self,
+        cache: Option<&mut InstanceCommitmentCache<C>>,
+    ) -> (
+        ProvingKey<C>,
+        (C, C, Vec<C::ScalarExt>),
+        Vec<C::ScalarExt>,
+        Vec<u8>,
     ) {
         let setup = MultiCircuitsSetup {
             setups: self.target_circuit_proofs.map(|target_circuit| Setup {
@@ -927,43 +1154,51 @@ impl<C: CurveAffine, E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>, co
         let setup_outcome = setup.new_verify_circuit_info(false);
 
         let verify_circuit_final_pair = {
-            Halo2CircuitInstances(from_0_to_n::<N>().map(|i| Halo2CircuitInstance {
-                name: setup_outcome[i].name.clone(),
-                params: &setup_outcome[i].params_verifier,
-                vk: &setup_outcome[i].vk,
-                n_instances: &setup_outcome[i].instances,
-                n_transcript: &setup_outcome[i].proofs,
-            }))
-            .calc_verify_circuit_final_pair()
+            let instances =
+                Halo2CircuitInstances(from_0_to_n::<N>().map(|i| Halo2CircuitInstance {
+                    name: setup_outcome[i].name.clone(),
+                    params: &setup_outcome[i].params_verifier,
+                    vk: &setup_outcome[i].vk,
+                    n_instances: &setup_outcome[i].instances,
+                    n_transcript: &setup_outcome[i].proofs,
+                }));
+            match cache {
+                None => instances.calc_verify_circuit_final_pair(),
+                Some(cache) => instances.calc_verify_circuit_final_pair_with_cache(cache),
+            }
         };
 
         let verify_circuit_instances = final_pair_to_instances::<C, E>(&verify_circuit_final_pair);
 
-        let verify_circuit_pk = keygen_pk(
-            &self.verify_circuit_params,
-            self.verify_circuit_vk,
-            &verify_circuit,
-        )
-        .expect("keygen_pk should not fail");
+        let verify_circuit_pk = info_span!("keygen_pk").in_scope(|| {
+            keygen_pk(
+                &self.verify_circuit_params,
+                self.verify_circuit_vk,
+                &verify_circuit,
+            )
+            .expect("keygen_pk should not fail")
+        });
 
         let elapsed_time = now.elapsed();
         info!("Running keygen_pk took {} seconds.", elapsed_time.as_secs());
 
         let instances: &[&[&[C::ScalarExt]]] = &[&[&verify_circuit_instances[..]]];
-        let mut transcript = ShaWrite::<_, _, Challenge255<_>, sha2::Sha256>::init(vec![]);
-        create_proof(
-            &self.verify_circuit_params,
-            &verify_circuit_pk,
-            &[verify_circuit],
-            instances,
-            OsRng,
-            &mut transcript,
-        )
-        .expect("proof generation should not fail");
+        let mut transcript = T::init_with_label(self.transcript_label.as_deref());
+        info_span!("proving").in_scope(|| {
+            create_proof(
+                &self.verify_circuit_params,
+                &verify_circuit_pk,
+                &[verify_circuit],
+                instances,
+                OsRng,
+                &mut transcript,
+            )
+            .expect("proof generation should not fail")
+        });
         let proof = transcript.finalize();
 
         let elapsed_time = now.elapsed();
-        println!(
+        info!(
             "Running create proof took {} seconds.",
             elapsed_time.as_secs()
         );
@@ -977,12 +1212,42 @@ impl<C: CurveAffine, E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>, co
     }
 }
 
+// Why VerifyCheck::call rejected the verify circuit proof, the instance shape error is kept
+// rather than folded into Error::InvalidInstances.
+#[derive(Debug)]
+pub enum VerifyCheckError {
+    Instances(InstanceError),
+    Proof(Error),
+}
+
+impl fmt::Display for VerifyCheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyCheckError::Instances(e) => write!(f, "invalid instances: {}", e),
+            VerifyCheckError::Proof(e) => write!(f, "proof rejected: {:?}", e),
+        }
+    }
+}
+
+impl From<InstanceError> for VerifyCheckError {
+    fn from(e: InstanceError) -> Self {
+        VerifyCheckError::Instances(e)
+    }
+}
+
+impl From<Error> for VerifyCheckError {
+    fn from(e: Error) -> Self {
+        VerifyCheckError::Proof(e)
+    }
+}
+
 pub struct VerifyCheck<C: CurveAffine> {
     pub verify_params: Rc<Params<C>>,
     pub verify_vk: Rc<VerifyingKey<C>>,
     pub verify_instance: Vec<Vec<Vec<C::ScalarExt>>>,
     pub verify_public_inputs_size: usize,
     pub verify_proof: Vec<u8>,
+    pub transcript_label: Option<Vec<u8>>,
 }
 
 impl VerifyCheck<G1Affine> {
@@ -993,6 +1258,7 @@ impl VerifyCheck<G1Affine> {
             verify_instance: load_verify_circuit_instance(&mut folder.clone()),
             verify_proof: load_verify_circuit_proof(&mut folder.clone()),
             verify_public_inputs_size,
+            transcript_label: load_transcript_label(&mut folder.clone()),
         }
     }
 }
@@ -1000,7 +1266,8 @@ impl VerifyCheck<G1Affine> {
 impl<C: CurveAffine> VerifyCheck<C> {
     pub fn call<E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>>(
         &self,
-    ) -> Result<(), Error> {
+    ) -> Result<(), VerifyCheckError> {
+        let _span = info_span!("verify_check").entered();
         let params = self
             .verify_params
             .verifier::<E>(self.verify_public_inputs_size)
@@ -1014,9 +1281,12 @@ impl<C: CurveAffine> VerifyCheck<C> {
             .collect();
         let verify_circuit_instance2: Vec<&[&[E::Scalar]]> =
             verify_circuit_instance1.iter().map(|x| &x[..]).collect();
+        validate_instances(&self.verify_vk, &params, &verify_circuit_instance2[..])?;
 
-        let mut transcript =
-            ShaRead::<_, _, Challenge255<_>, sha2::Sha256>::init(&self.verify_proof[..]);
+        let mut transcript = ShaRead::<_, _, Challenge255<_>, sha2::Sha256>::init_with_label(
+            &self.verify_proof[..],
+            self.transcript_label.as_deref(),
+        );
 
         verify_proof(
             &params,
@@ -1024,6 +1294,35 @@ impl<C: CurveAffine> VerifyCheck<C> {
             strategy,
             &verify_circuit_instance2[..],
             &mut transcript,
-        )
+        )?;
+        Ok(())
+    }
+
+    // Native final check of the aggregation: the verify circuit instances start with the encoding
+    // of final_pair, and e(w_x, [s]) * e(w_g, -[1]) == 1 in the setup of the target circuits.
+    pub fn check_final_pair<E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>>(
+        &self,
+        target_params: &ParamsVerifier<E>,
+        final_pair: &(C, C, Vec<C::ScalarExt>),
+    ) -> Result<(), Error> {
+        let _span = info_span!("final_pair_check").entered();
+        let expected = final_pair_to_instances::<C, E>(final_pair);
+        let column = self.verify_instance.get(0).and_then(|proof| proof.get(0));
+        if !column.map_or(false, |column| column.starts_with(&expected[..])) {
+            return Err(Error::InvalidInstances);
+        }
+
+        let pchip = MockEccChip::<C, Error>::default();
+        let check = MultiPairingCheck::<E, MockEccChip<C, Error>>::from_final_pair(
+            final_pair.0.to_curve(),
+            final_pair.1.to_curve(),
+            target_params.s_g2,
+            target_params.g2,
+        );
+        if check.check_native(&pchip)? {
+            Ok(())
+        } else {
+            Err(Error::ConstraintSystemFailure)
+        }
     }
 }
@@ -0,0 +1,178 @@
+// Canonical digest of a vk, the vk hash of the proof envelopes, of the contracts pinning a vk
+// and of the sdk `vk_hash` command. It is the keccak256 of an explicit little endian encoding
+// of the parts of the vk the verifier depends on, so that it does not move with the Debug
+// output halo2 hashes into its own transcript repr:
+//
+//   "halo2-vk" | version u8 | k u32 | quotient degree u32 | cs | fixed commitments
+//   | permutation commitments
+//
+// where cs is the column counts, the queries, the permutation columns, the gates and the
+// lookups, and points are (x, y), the identity as (0, 0).
+use halo2_proofs::arithmetic::{CurveAffine, FieldExt};
+use halo2_proofs::plonk::{Any, Column, ConstraintSystem, Expression, VerifyingKey};
+use halo2_proofs::poly::Rotation;
+use pairing_bn256::group::ff::PrimeField;
+use sha3::{Digest, Keccak256};
+
+pub const VK_HASH_DOMAIN: &[u8] = b"halo2-vk";
+pub const VK_HASH_VERSION: u8 = 1;
+
+struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn u32(&mut self, v: usize) {
+        self.0.extend((v as u32).to_le_bytes());
+    }
+
+    fn field<F: PrimeField>(&mut self, f: &F) {
+        self.0.extend(f.to_repr().as_ref());
+    }
+
+    fn point<C: CurveAffine>(&mut self, p: &C) {
+        match Option::from(p.coordinates()) {
+            Some(c) => {
+                self.field(c.x());
+                self.field(c.y());
+            }
+            None => {
+                self.field(&C::Base::zero());
+                self.field(&C::Base::zero());
+            }
+        }
+    }
+
+    fn query(&mut self, index: usize, rotation: &Rotation) {
+        self.u32(index);
+        self.0.extend(rotation.0.to_le_bytes());
+    }
+
+    fn column(&mut self, column: &Column<Any>) {
+        self.0.push(match column.column_type() {
+            Any::Advice => 0,
+            Any::Fixed => 1,
+            Any::Instance => 2,
+        });
+        self.u32(column.index());
+    }
+
+    // prefix notation, one tag byte per node
+    fn expression<F: FieldExt>(&mut self, e: &Expression<F>) {
+        match e {
+            Expression::Constant(c) => {
+                self.0.push(0);
+                self.field(c);
+            }
+            // selectors are compiled to fixed columns by keygen, a vk has none left
+            Expression::Selector(_) => self.0.push(1),
+            Expression::Fixed {
+                column_index,
+                rotation,
+                ..
+            } => {
+                self.0.push(2);
+                self.query(*column_index, rotation);
+            }
+            Expression::Advice {
+                column_index,
+                rotation,
+                ..
+            } => {
+                self.0.push(3);
+                self.query(*column_index, rotation);
+            }
+            Expression::Instance {
+                column_index,
+                rotation,
+                ..
+            } => {
+                self.0.push(4);
+                self.query(*column_index, rotation);
+            }
+            Expression::Negated(a) => {
+                self.0.push(5);
+                self.expression(a);
+            }
+            Expression::Sum(a, b) => {
+                self.0.push(6);
+                self.expression(a);
+                self.expression(b);
+            }
+            Expression::Product(a, b) => {
+                self.0.push(7);
+                self.expression(a);
+                self.expression(b);
+            }
+            Expression::Scaled(a, f) => {
+                self.0.push(8);
+                self.expression(a);
+                self.field(f);
+            }
+        }
+    }
+
+    fn expressions<F: FieldExt>(&mut self, es: &[Expression<F>]) {
+        self.u32(es.len());
+        es.iter().for_each(|e| self.expression(e));
+    }
+
+    fn constraint_system<F: FieldExt>(&mut self, cs: &ConstraintSystem<F>) {
+        self.u32(cs.num_fixed_columns);
+        self.u32(cs.num_advice_columns);
+        self.u32(cs.num_instance_columns);
+        self.u32(cs.degree());
+
+        for queries in [&cs.fixed_queries, &cs.advice_queries, &cs.instance_queries] {
+            self.u32(queries.len());
+            queries
+                .iter()
+                .for_each(|(column, rotation)| self.query(column.index(), rotation));
+        }
+
+        self.u32(cs.permutation.columns.len());
+        cs.permutation.columns.iter().for_each(|c| self.column(c));
+
+        self.u32(cs.gates.len());
+        cs.gates
+            .iter()
+            .for_each(|gate| self.expressions(&gate.polys));
+
+        self.u32(cs.lookups.len());
+        cs.lookups.iter().for_each(|lookup| {
+            self.expressions(&lookup.input_expressions);
+            self.expressions(&lookup.table_expressions);
+        });
+    }
+}
+
+// Bytes hashed by vk_hash.
+pub fn vk_hash_preimage<C: CurveAffine>(vk: &VerifyingKey<C>) -> Vec<u8> {
+    let mut e = Encoder(VK_HASH_DOMAIN.to_vec());
+    e.0.push(VK_HASH_VERSION);
+    e.u32(vk.domain.k() as usize);
+    e.u32(vk.domain.get_quotient_poly_degree());
+    e.constraint_system(&vk.cs);
+
+    e.u32(vk.fixed_commitments.len());
+    vk.fixed_commitments.iter().for_each(|p| e.point(p));
+    e.u32(vk.permutation.commitments.len());
+    vk.permutation.commitments.iter().for_each(|p| e.point(p));
+    e.0
+}
+
+pub fn vk_hash<C: CurveAffine>(vk: &VerifyingKey<C>) -> [u8; 32] {
+    Keccak256::digest(&vk_hash_preimage(vk)).into()
+}
+
+pub fn vk_hash_hex<C: CurveAffine>(vk: &VerifyingKey<C>) -> String {
+    vk_hash(vk).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Number of verify circuit instances of the vk hash of each target circuit.
+pub const VK_HASH_INSTANCES: usize = 2;
+
+// The vk hash as verify circuit instances, its high and low 128 bits: the hash read as a big
+// endian number, as a contract reads a bytes32, does not fit the scalar field.
+pub fn vk_hash_instances<F: FieldExt>(hash: &[u8; 32]) -> [F; VK_HASH_INSTANCES] {
+    let half = |bytes: &[u8]| F::from_u128(u128::from_be_bytes(bytes.try_into().unwrap()));
+    [half(&hash[..16]), half(&hash[16..])]
+}
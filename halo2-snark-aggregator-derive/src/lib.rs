@@ -0,0 +1,261 @@
+// #[derive(TargetCircuit)], re-exported by halo2_snark_aggregator_circuit::sample_circuit next to
+// the trait. The impl is for the bn256 curve the sdk aggregates on:
+//
+//     #[derive(TargetCircuit)]
+//     #[target_circuit(k = 7, public_input_size = 1, n_proofs = 2, name = "simple_example",
+//         circuit = "MyCircuit<Fr>", instance_builder = "instance_builder")]
+//     pub struct TestCircuit;
+//
+// params_name defaults to name and readable_vkey to false. Without instance_builder the sample
+// run proves the default circuit on zero public inputs, without load_instances the instances
+// are read as one column of scalars.
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Attribute, DeriveInput, Error, ExprPath, Lit, Meta, MetaNameValue,
+    NestedMeta, Result, Type,
+};
+
+#[proc_macro_derive(TargetCircuit, attributes(target_circuit))]
+pub fn derive_target_circuit(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+#[derive(Default)]
+struct Attrs {
+    k: Option<u32>,
+    public_input_size: Option<usize>,
+    n_proofs: Option<usize>,
+    name: Option<String>,
+    params_name: Option<String>,
+    readable_vkey: Option<bool>,
+    circuit: Option<Type>,
+    instance_builder: Option<ExprPath>,
+    load_instances: Option<ExprPath>,
+}
+
+fn parse_attr(attrs: &mut Attrs, nv: MetaNameValue) -> Result<()> {
+    let key = nv
+        .path
+        .get_ident()
+        .map(|ident| ident.to_string())
+        .unwrap_or_default();
+    let duplicate = || Error::new_spanned(&nv.path, format!("duplicate `{}`", key));
+
+    match (key.as_str(), &nv.lit) {
+        ("k", Lit::Int(v)) if attrs.k.is_none() => attrs.k = Some(v.base10_parse()?),
+        ("public_input_size", Lit::Int(v)) if attrs.public_input_size.is_none() => {
+            attrs.public_input_size = Some(v.base10_parse()?)
+        }
+        ("n_proofs", Lit::Int(v)) if attrs.n_proofs.is_none() => {
+            attrs.n_proofs = Some(v.base10_parse()?)
+        }
+        ("name", Lit::Str(v)) if attrs.name.is_none() => attrs.name = Some(v.value()),
+        ("params_name", Lit::Str(v)) if attrs.params_name.is_none() => {
+            attrs.params_name = Some(v.value())
+        }
+        ("readable_vkey", Lit::Bool(v)) if attrs.readable_vkey.is_none() => {
+            attrs.readable_vkey = Some(v.value)
+        }
+        ("circuit", Lit::Str(v)) if attrs.circuit.is_none() => attrs.circuit = Some(v.parse()?),
+        ("instance_builder", Lit::Str(v)) if attrs.instance_builder.is_none() => {
+            attrs.instance_builder = Some(v.parse()?)
+        }
+        ("load_instances", Lit::Str(v)) if attrs.load_instances.is_none() => {
+            attrs.load_instances = Some(v.parse()?)
+        }
+        ("k" | "public_input_size" | "n_proofs", Lit::Int(_))
+        | (
+            "name" | "params_name" | "circuit" | "instance_builder" | "load_instances",
+            Lit::Str(_),
+        )
+        | ("readable_vkey", Lit::Bool(_)) => return Err(duplicate()),
+        ("k" | "public_input_size" | "n_proofs", lit) => {
+            return Err(Error::new_spanned(
+                lit,
+                format!("`{}` takes an integer", key),
+            ))
+        }
+        ("name" | "params_name" | "circuit" | "instance_builder" | "load_instances", lit) => {
+            return Err(Error::new_spanned(lit, format!("`{}` takes a string", key)))
+        }
+        ("readable_vkey", lit) => {
+            return Err(Error::new_spanned(lit, "`readable_vkey` takes a bool"))
+        }
+        _ => return Err(Error::new_spanned(&nv.path, format!("unknown `{}`", key))),
+    }
+    Ok(())
+}
+
+fn parse_attrs(attrs: &[Attribute]) -> Result<Attrs> {
+    let mut parsed = Attrs::default();
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("target_circuit"))
+    {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => {
+                return Err(Error::new_spanned(
+                    meta,
+                    "expected #[target_circuit(key = value, ..)]",
+                ))
+            }
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(nv)) => parse_attr(&mut parsed, nv)?,
+                nested => return Err(Error::new_spanned(nested, "expected `key = value`")),
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "TargetCircuit can't be derived for a generic type, implement it by hand",
+        ));
+    }
+
+    let attrs = parse_attrs(&input.attrs)?;
+    let ident = &input.ident;
+    let missing = |key: &str| {
+        Error::new_spanned(ident, format!("missing `{}` in #[target_circuit(..)]", key))
+    };
+
+    let k = attrs.k.ok_or_else(|| missing("k"))?;
+    let public_input_size = attrs
+        .public_input_size
+        .ok_or_else(|| missing("public_input_size"))?;
+    let n_proofs = attrs.n_proofs.ok_or_else(|| missing("n_proofs"))?;
+    let name = attrs.name.ok_or_else(|| missing("name"))?;
+    let params_name = attrs.params_name.unwrap_or_else(|| name.clone());
+    let readable_vkey = attrs.readable_vkey.unwrap_or(false);
+    let circuit = attrs.circuit.ok_or_else(|| missing("circuit"))?;
+
+    let krate = quote!(::halo2_snark_aggregator_circuit::sample_circuit);
+    let instance_builder = match attrs.instance_builder {
+        Some(f) => quote!(#f()),
+        None => quote! {
+            (
+                ::core::default::Default::default(),
+                #krate::zero_instances(#public_input_size),
+            )
+        },
+    };
+    let load_instances = match attrs.load_instances {
+        Some(f) => quote!(#f(buf)),
+        None => quote!(#krate::load_single_column_instances(buf)),
+    };
+
+    Ok(quote! {
+        impl #krate::TargetCircuit<#krate::__private::G1Affine, #krate::__private::Bn256>
+            for #ident
+        {
+            const TARGET_CIRCUIT_K: u32 = #k;
+            const PUBLIC_INPUT_SIZE: usize = #public_input_size;
+            const N_PROOFS: usize = #n_proofs;
+            const NAME: &'static str = #name;
+            const PARAMS_NAME: &'static str = #params_name;
+            const READABLE_VKEY: bool = #readable_vkey;
+
+            type Circuit = #circuit;
+
+            fn instance_builder() -> (Self::Circuit, Vec<Vec<#krate::__private::Fr>>) {
+                #instance_builder
+            }
+
+            fn load_instances(buf: &Vec<u8>) -> Vec<Vec<Vec<#krate::__private::Fr>>> {
+                #load_instances
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn expand_err(input: DeriveInput) -> String {
+        expand(input).err().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_expand_defaults() {
+        let input: DeriveInput = parse_quote! {
+            #[target_circuit(k = 7, public_input_size = 1, n_proofs = 2, name = "simple")]
+            #[target_circuit(circuit = "MyCircuit<Fr>")]
+            struct TestCircuit;
+        };
+        let out = expand(input).unwrap().to_string();
+
+        assert!(out.contains("const TARGET_CIRCUIT_K : u32 = 7u32"));
+        assert!(out.contains("const N_PROOFS : usize = 2usize"));
+        assert_eq!(out.matches("\"simple\"").count(), 2);
+        assert!(out.contains("const READABLE_VKEY : bool = false"));
+        assert!(out.contains("zero_instances (1usize)"));
+        assert!(out.contains("load_single_column_instances (buf)"));
+    }
+
+    #[test]
+    fn test_expand_hooks() {
+        let input: DeriveInput = parse_quote! {
+            #[target_circuit(k = 7, public_input_size = 1, n_proofs = 2, name = "simple",
+                params_name = "shared", readable_vkey = true, circuit = "MyCircuit<Fr>",
+                instance_builder = "builders::random", load_instances = "load")]
+            struct TestCircuit;
+        };
+        let out = expand(input).unwrap().to_string();
+
+        assert!(out.contains("\"shared\""));
+        assert!(out.contains("const READABLE_VKEY : bool = true"));
+        assert!(out.contains("builders :: random"));
+        assert!(out.contains("load (buf)"));
+        assert!(!out.contains("zero_instances"));
+    }
+
+    #[test]
+    fn test_expand_errors() {
+        assert_eq!(
+            expand_err(parse_quote! {
+                #[target_circuit(public_input_size = 1, n_proofs = 2, name = "a", circuit = "C")]
+                struct TestCircuit;
+            }),
+            "missing `k` in #[target_circuit(..)]"
+        );
+        assert_eq!(
+            expand_err(parse_quote! {
+                #[target_circuit(k = 7, k = 8)]
+                struct TestCircuit;
+            }),
+            "duplicate `k`"
+        );
+        assert_eq!(
+            expand_err(parse_quote! {
+                #[target_circuit(k = "7")]
+                struct TestCircuit;
+            }),
+            "`k` takes an integer"
+        );
+        assert_eq!(
+            expand_err(parse_quote! {
+                #[target_circuit(degree = 7)]
+                struct TestCircuit;
+            }),
+            "unknown `degree`"
+        );
+        assert!(expand_err(parse_quote! {
+            #[target_circuit(k = 7)]
+            struct TestCircuit<F>(F);
+        })
+        .starts_with("TargetCircuit can't be derived for a generic type"));
+    }
+}
@@ -0,0 +1,323 @@
+// Two target circuits of different shapes, aggregated together by the heterogeneous example
+// and checked by tests/heterogeneous.rs: LookupCircuit decomposes values into bytes range
+// checked by lookups, PermutationCircuit accumulates a sum copied across many columns.
+use halo2_proofs::{
+    arithmetic::{BaseExt, Field},
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+use halo2_snark_aggregator_circuit::sample_circuit::TargetCircuit;
+use pairing_bn256::bn256::{Bn256, Fr, G1Affine};
+use rand_core::{OsRng, RngCore};
+
+fn read_instances(buf: &[u8]) -> Vec<Vec<Vec<Fr>>> {
+    let mut ret = vec![];
+    let cursor = &mut std::io::Cursor::new(buf);
+
+    while let Ok(a) = <Fr as BaseExt>::read(cursor) {
+        ret.push(a);
+    }
+
+    vec![vec![ret]]
+}
+
+// u32 values of LookupCircuit, each exposed as an instance.
+pub const LOOKUP_VALUES: usize = 4;
+// bytes of a value, one lookup into the byte table each
+const BYTES: usize = 4;
+
+#[derive(Clone, Debug)]
+pub struct LookupConfig {
+    value: Column<Advice>,
+    bytes: [Column<Advice>; BYTES],
+    table: Column<Fixed>,
+    instance: Column<Instance>,
+    q_range: Selector,
+}
+
+// value = bytes[0] + 2^8 bytes[1] + 2^16 bytes[2] + 2^24 bytes[3], every byte in 0..256.
+#[derive(Clone, Debug)]
+pub struct LookupCircuit {
+    pub values: Vec<Option<u32>>,
+}
+
+// keygen runs on the default circuit, it has the rows of the proofs without their values
+impl Default for LookupCircuit {
+    fn default() -> Self {
+        LookupCircuit {
+            values: vec![None; LOOKUP_VALUES],
+        }
+    }
+}
+
+impl Circuit<Fr> for LookupCircuit {
+    type Config = LookupConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let config = LookupConfig {
+            value: meta.advice_column(),
+            bytes: [(); BYTES].map(|_| meta.advice_column()),
+            table: meta.fixed_column(),
+            instance: meta.instance_column(),
+            q_range: meta.complex_selector(),
+        };
+        meta.enable_equality(config.value);
+        meta.enable_equality(config.instance);
+
+        meta.create_gate("byte decomposition", |meta| {
+            let q = meta.query_selector(config.q_range);
+            let value = meta.query_advice(config.value, Rotation::cur());
+            let sum = config.bytes.iter().rev().fold(None, |acc, byte| {
+                let byte = meta.query_advice(*byte, Rotation::cur());
+                Some(match acc {
+                    None => byte,
+                    Some(acc) => acc * Fr::from(256) + byte,
+                })
+            });
+            vec![q * (sum.unwrap() - value)]
+        });
+
+        for byte in config.bytes {
+            meta.lookup_any("byte range", |meta| {
+                let q = meta.query_selector(config.q_range);
+                let byte = meta.query_advice(byte, Rotation::cur());
+                let table = meta.query_fixed(config.table, Rotation::cur());
+                vec![(q * byte, table)]
+            });
+        }
+
+        config
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "byte table",
+            |mut region| {
+                for byte in 0..256 {
+                    let value = || Ok(Fr::from(byte as u64));
+                    region.assign_fixed(|| "byte", config.table, byte, value)?;
+                }
+                Ok(())
+            },
+        )?;
+
+        let cells = layouter.assign_region(
+            || "values",
+            |mut region| {
+                let mut cells = vec![];
+                for (row, value) in self.values.iter().enumerate() {
+                    config.q_range.enable(&mut region, row)?;
+                    let cell = region.assign_advice(
+                        || "value",
+                        config.value,
+                        row,
+                        || value.map(|v| Fr::from(v as u64)).ok_or(Error::Synthesis),
+                    )?;
+                    for (i, column) in config.bytes.iter().enumerate() {
+                        let byte = value.map(|v| Fr::from(((v >> (8 * i)) & 0xff) as u64));
+                        region.assign_advice(
+                            || "byte",
+                            *column,
+                            row,
+                            || byte.ok_or(Error::Synthesis),
+                        )?;
+                    }
+                    cells.push(cell);
+                }
+                Ok(cells)
+            },
+        )?;
+
+        for (row, cell) in cells.iter().enumerate() {
+            layouter.constrain_instance(cell.cell(), config.instance, row)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct CircuitA;
+
+impl TargetCircuit<G1Affine, Bn256> for CircuitA {
+    const TARGET_CIRCUIT_K: u32 = 9;
+    const PUBLIC_INPUT_SIZE: usize = LOOKUP_VALUES;
+    const N_PROOFS: usize = 1;
+    const NAME: &'static str = "lookup_circuit";
+    const PARAMS_NAME: &'static str = "lookup_circuit";
+    const READABLE_VKEY: bool = true;
+
+    type Circuit = LookupCircuit;
+
+    fn instance_builder() -> (Self::Circuit, Vec<Vec<Fr>>) {
+        let values = (0..LOOKUP_VALUES)
+            .map(|_| OsRng.next_u32())
+            .collect::<Vec<_>>();
+        let instances = vec![values.iter().map(|v| Fr::from(*v as u64)).collect()];
+        let circuit = LookupCircuit {
+            values: values.into_iter().map(Some).collect(),
+        };
+        (circuit, instances)
+    }
+
+    fn load_instances(buf: &Vec<u8>) -> Vec<Vec<Vec<Fr>>> {
+        read_instances(buf)
+    }
+}
+
+// Terms summed by PermutationCircuit, one row each.
+pub const PERMUTATION_TERMS: usize = 8;
+// columns every term is copied through, each with equality enabled
+const COPIES: usize = 6;
+
+#[derive(Clone, Debug)]
+pub struct PermutationConfig {
+    term: Column<Advice>,
+    acc: Column<Advice>,
+    next: Column<Advice>,
+    copies: [Column<Advice>; COPIES],
+    instance: Column<Instance>,
+    q_add: Selector,
+}
+
+// acc of the row i + 1 = acc of the row i + term i, acc starting at 0, every acc copied
+// from the next of the row above and every term copied through all the copies columns.
+// The first term and the sum are exposed.
+#[derive(Clone, Debug)]
+pub struct PermutationCircuit {
+    pub terms: Vec<Option<Fr>>,
+}
+
+impl Default for PermutationCircuit {
+    fn default() -> Self {
+        PermutationCircuit {
+            terms: vec![None; PERMUTATION_TERMS],
+        }
+    }
+}
+
+impl Circuit<Fr> for PermutationCircuit {
+    type Config = PermutationConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let config = PermutationConfig {
+            term: meta.advice_column(),
+            acc: meta.advice_column(),
+            next: meta.advice_column(),
+            copies: [(); COPIES].map(|_| meta.advice_column()),
+            instance: meta.instance_column(),
+            q_add: meta.selector(),
+        };
+        let constant = meta.fixed_column();
+        meta.enable_constant(constant);
+        for column in [config.term, config.acc, config.next]
+            .iter()
+            .chain(config.copies.iter())
+        {
+            meta.enable_equality(*column);
+        }
+        meta.enable_equality(config.instance);
+
+        meta.create_gate("accumulate", |meta| {
+            let q = meta.query_selector(config.q_add);
+            let term = meta.query_advice(config.term, Rotation::cur());
+            let acc = meta.query_advice(config.acc, Rotation::cur());
+            let next = meta.query_advice(config.next, Rotation::cur());
+            vec![q * (acc + term - next)]
+        });
+
+        config
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let (first, sum) = layouter.assign_region(
+            || "accumulate",
+            |mut region| {
+                let mut acc = Some(Fr::zero());
+                let mut cells: Option<(AssignedCell<Fr, Fr>, AssignedCell<Fr, Fr>)> = None;
+                for (row, term) in self.terms.iter().enumerate() {
+                    config.q_add.enable(&mut region, row)?;
+                    let term_cell = region.assign_advice(
+                        || "term",
+                        config.term,
+                        row,
+                        || term.ok_or(Error::Synthesis),
+                    )?;
+                    match &cells {
+                        Some((_, next)) => {
+                            next.copy_advice(|| "acc", &mut region, config.acc, row)?;
+                        }
+                        None => {
+                            let zero = Fr::zero();
+                            region.assign_advice_from_constant(|| "acc", config.acc, row, zero)?;
+                        }
+                    }
+                    let mut copy = term_cell.clone();
+                    for column in config.copies.iter() {
+                        copy = copy.copy_advice(|| "copy", &mut region, *column, row)?;
+                    }
+
+                    acc = acc.zip(*term).map(|(acc, term)| acc + term);
+                    let next = region.assign_advice(
+                        || "next",
+                        config.next,
+                        row,
+                        || acc.ok_or(Error::Synthesis),
+                    )?;
+                    let first = cells.take().map_or(term_cell, |(first, _)| first);
+                    cells = Some((first, next));
+                }
+                Ok(cells.unwrap())
+            },
+        )?;
+
+        layouter.constrain_instance(first.cell(), config.instance, 0)?;
+        layouter.constrain_instance(sum.cell(), config.instance, 1)
+    }
+}
+
+pub struct CircuitB;
+
+impl TargetCircuit<G1Affine, Bn256> for CircuitB {
+    const TARGET_CIRCUIT_K: u32 = 6;
+    const PUBLIC_INPUT_SIZE: usize = 2;
+    const N_PROOFS: usize = 1;
+    const NAME: &'static str = "permutation_circuit";
+    const PARAMS_NAME: &'static str = "permutation_circuit";
+    const READABLE_VKEY: bool = true;
+
+    type Circuit = PermutationCircuit;
+
+    fn instance_builder() -> (Self::Circuit, Vec<Vec<Fr>>) {
+        let terms = (0..PERMUTATION_TERMS)
+            .map(|_| Fr::random(OsRng))
+            .collect::<Vec<_>>();
+        let sum = terms.iter().fold(Fr::zero(), |acc, term| acc + term);
+        let instances = vec![vec![terms[0], sum]];
+        let circuit = PermutationCircuit {
+            terms: terms.into_iter().map(Some).collect(),
+        };
+        (circuit, instances)
+    }
+
+    fn load_instances(buf: &Vec<u8>) -> Vec<Vec<Vec<Fr>>> {
+        read_instances(buf)
+    }
+}
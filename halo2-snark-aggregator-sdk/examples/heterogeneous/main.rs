@@ -0,0 +1,13 @@
+// Aggregates a lookup heavy and a permutation heavy circuit in one verify circuit, the
+// multi-circuit path of zkaggregate! with circuits of different shapes.
+mod circuits;
+
+use circuits::{CircuitA, CircuitB};
+use halo2_snark_aggregator_sdk::zkaggregate;
+
+zkaggregate! { CircuitA: 1, CircuitB: 1 }
+
+pub fn main() {
+    let builder = zkcli::builder(22);
+    builder.run()
+}
@@ -1,11 +1,12 @@
 use halo2_proofs::{
-    arithmetic::{BaseExt, CurveAffine, Field, FieldExt, MultiMillerLoop},
+    arithmetic::{Field, FieldExt},
     circuit::{AssignedCell, Chip, Layouter, Region, SimpleFloorPlanner},
     plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance},
     poly::Rotation,
 };
 use halo2_snark_aggregator_circuit::sample_circuit::TargetCircuit;
 use halo2_snark_aggregator_sdk::zkaggregate;
+use pairing_bn256::bn256::Fr;
 use rand_core::OsRng;
 use std::marker::PhantomData;
 
@@ -325,43 +326,29 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     }
 }
 
+#[derive(TargetCircuit)]
+#[target_circuit(
+    k = 7,
+    public_input_size = 1,
+    n_proofs = 2,
+    name = "simple_example",
+    readable_vkey = true,
+    circuit = "MyCircuit<Fr>",
+    instance_builder = "instance_builder"
+)]
 pub struct TestCircuit;
 
-impl<C: CurveAffine, E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>> TargetCircuit<C, E>
-    for TestCircuit
-{
-    const TARGET_CIRCUIT_K: u32 = 7;
-    const PUBLIC_INPUT_SIZE: usize = 1;
-    const N_PROOFS: usize = 2;
-    const NAME: &'static str = "simple_example";
-    const PARAMS_NAME: &'static str = "simple_example";
-    const READABLE_VKEY: bool = true;
-
-    type Circuit = MyCircuit<C::ScalarExt>;
-
-    fn instance_builder() -> (Self::Circuit, Vec<Vec<C::ScalarExt>>) {
-        let constant = C::Scalar::from(7);
-        let a = C::Scalar::random(OsRng);
-        let b = C::Scalar::random(OsRng);
-        let circuit = MyCircuit {
-            constant,
-            a: Some(a),
-            b: Some(b),
-        };
-        let instances = vec![vec![constant * a.square() * b.square()]];
-        (circuit, instances)
-    }
-
-    fn load_instances(buf: &Vec<u8>) -> Vec<Vec<Vec<<C as CurveAffine>::ScalarExt>>> {
-        let mut ret = vec![];
-        let cursor = &mut std::io::Cursor::new(buf);
-
-        while let Ok(a) = <E::Scalar as BaseExt>::read(cursor) {
-            ret.push(a);
-        }
-
-        vec![vec![ret]]
-    }
+fn instance_builder() -> (MyCircuit<Fr>, Vec<Vec<Fr>>) {
+    let constant = Fr::from(7);
+    let a = Fr::random(OsRng);
+    let b = Fr::random(OsRng);
+    let circuit = MyCircuit {
+        constant,
+        a: Some(a),
+        b: Some(b),
+    };
+    let instances = vec![vec![constant * a.square() * b.square()]];
+    (circuit, instances)
 }
 
 zkaggregate! {1, vec![], TestCircuit}
@@ -0,0 +1,50 @@
+// C api of a cdylib expanding zkaggregate_ffi!, see the sdk README.
+//
+// Functions returning int32_t return 0 on success and -1 on an error: a circuit index
+// out of range, malformed bytes, a missing step or a panic of the aggregator. The
+// buffers have the format of the files the cli reads and writes, and the buffers the
+// aggregator returns are released with zkagg_buffer_free. A handle is not thread safe.
+#ifndef ZKAGGREGATE_H
+#define ZKAGGREGATE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+typedef struct ZkAggregator ZkAggregator;
+
+typedef struct {
+    uint8_t *ptr;
+    size_t len;
+} ZkBuffer;
+
+ZkAggregator *zkagg_new(uint32_t verify_circuit_k);
+void zkagg_free(ZkAggregator *agg);
+void zkagg_buffer_free(ZkBuffer buf);
+
+// sample_circuit_<name>.params and .vkey of the circuit at index circuit
+int32_t zkagg_set_target_circuit(ZkAggregator *agg, size_t circuit, const uint8_t *params,
+                                 size_t params_len, const uint8_t *vk, size_t vk_len);
+// one of the instances and transcripts of the circuit at index circuit, up to its proof count
+int32_t zkagg_add_target_proof(ZkAggregator *agg, size_t circuit, const uint8_t *instances,
+                               size_t instances_len, const uint8_t *proof, size_t proof_len);
+
+// verify_setup: verify_circuit.params and verify_circuit.vkey
+int32_t zkagg_setup(ZkAggregator *agg, ZkBuffer *params_out, ZkBuffer *vk_out);
+// the output of an earlier zkagg_setup, instead of running it again
+int32_t zkagg_load_verify_circuit(ZkAggregator *agg, const uint8_t *params, size_t params_len,
+                                  const uint8_t *vk, size_t vk_len);
+
+// transcript_label: the deployment label of the verify circuit proofs, NULL unsets it
+int32_t zkagg_set_transcript_label(ZkAggregator *agg, const uint8_t *label, size_t label_len);
+
+// verify_run: verify_circuit_proof.data, verify_circuit_instance.data and
+// verify_circuit_final_pair.data
+int32_t zkagg_create_proof(ZkAggregator *agg, ZkBuffer *proof_out, ZkBuffer *instances_out,
+                           ZkBuffer *final_pair_out);
+
+// verify_check: 1 if the proof is valid, 0 if not, -1 on an error. The proof may be
+// verify_circuit_proof.envelope, -1 if it is not for the loaded vk and the instances.
+int32_t zkagg_verify(ZkAggregator *agg, const uint8_t *instances, size_t instances_len,
+                     const uint8_t *proof, size_t proof_len);
+
+#endif
@@ -0,0 +1,389 @@
+// C ABI of the aggregator for the target circuits given, to be expanded in a cdylib
+// so that sequencers written in Go or C++ embed the aggregator instead of spawning
+// the cli. ffi/zkaggregate.h declares the functions and their ownership rules, the
+// byte buffers have the format of the files the cli reads and writes.
+#[macro_export]
+macro_rules! zkaggregate_ffi {
+    (@impl $n:expr, $coherent:expr, $( $x:ident : $np:expr ),+ ) => {
+        mod zkffi {
+            $(
+                use crate::$x;
+            )*
+            use halo2_proofs::arithmetic::BaseExt;
+            use halo2_proofs::plonk::{keygen_vk, VerifyingKey};
+            use halo2_proofs::poly::commitment::Params;
+            use halo2_snark_aggregator_circuit::envelope::{is_envelope, ProofEnvelope};
+            use halo2_snark_aggregator_circuit::fs::{try_load_instances, try_read_params};
+            use halo2_snark_aggregator_circuit::sample_circuit::TargetCircuit;
+            use halo2_snark_aggregator_circuit::verify_circuit::{
+                CreateProof, Halo2VerifierCircuit, InstanceCommitmentCache,
+                MultiCircuitsCreateProof, MultiCircuitsSetup, Setup, SingleProofPair, VerifyCheck,
+            };
+            use pairing_bn256::bn256::{Bn256, Fr, G1Affine};
+            use std::io::Cursor;
+            use std::panic::{catch_unwind, AssertUnwindSafe};
+            use std::rc::Rc;
+
+            // Buffer allocated by the aggregator, released with zkagg_buffer_free.
+            #[repr(C)]
+            pub struct ZkBuffer {
+                pub ptr: *mut u8,
+                pub len: usize,
+            }
+
+            impl ZkBuffer {
+                fn new(buf: Vec<u8>) -> Self {
+                    let buf = Box::leak(buf.into_boxed_slice());
+                    ZkBuffer {
+                        ptr: buf.as_mut_ptr(),
+                        len: buf.len(),
+                    }
+                }
+            }
+
+            struct TargetCircuitData {
+                params: Rc<Params<G1Affine>>,
+                vk: Rc<VerifyingKey<G1Affine>>,
+                proofs: Vec<SingleProofPair<Bn256>>,
+            }
+
+            // Opaque handle of the C api.
+            pub struct ZkAggregator {
+                verify_circuit_k: u32,
+                targets: Vec<Option<TargetCircuitData>>,
+                verify_params: Option<Rc<Params<G1Affine>>>,
+                verify_vk: Option<Rc<VerifyingKey<G1Affine>>>,
+                transcript_label: Option<Vec<u8>>,
+                // instance commitments of the target proofs aggregated so far, for retries
+                instance_commitments: InstanceCommitmentCache<G1Affine>,
+            }
+
+            fn read_vk<SingleCircuit: TargetCircuit<G1Affine, Bn256>>(
+                params: &Params<G1Affine>,
+                vk: &[u8],
+            ) -> VerifyingKey<G1Affine> {
+                if SingleCircuit::READABLE_VKEY {
+                    VerifyingKey::<G1Affine>::read::<_, SingleCircuit::Circuit>(
+                        &mut Cursor::new(vk),
+                        params,
+                    )
+                    .unwrap()
+                } else {
+                    keygen_vk(params, &SingleCircuit::Circuit::default())
+                        .expect("keygen_vk should not fail")
+                }
+            }
+
+            const NAMES: [&str; $n] = [$(<$x as TargetCircuit<G1Affine, Bn256>>::NAME,)*];
+            const N_PROOFS: [usize; $n] = [$($np,)*];
+            const READ_VK: [fn(&Params<G1Affine>, &[u8]) -> VerifyingKey<G1Affine>; $n] =
+                [$(read_vk::<$x>,)*];
+            const LOAD_INSTANCES: [fn(&Vec<u8>) -> Vec<Vec<Vec<Fr>>>; $n] =
+                [$(<$x as TargetCircuit<G1Affine, Bn256>>::load_instances,)*];
+
+            impl ZkAggregator {
+                // Every circuit with its count of proofs, None until they are all given.
+                fn targets(&self) -> Option<Vec<(String, &TargetCircuitData)>> {
+                    self.targets
+                        .iter()
+                        .enumerate()
+                        .map(|(i, target)| {
+                            target
+                                .as_ref()
+                                .filter(|target| target.proofs.len() == N_PROOFS[i])
+                                .map(|target| (NAMES[i].to_owned(), target))
+                        })
+                        .collect()
+                }
+            }
+
+            fn slice<'a>(ptr: *const u8, len: usize) -> &'a [u8] {
+                if len == 0 {
+                    &[]
+                } else {
+                    unsafe { std::slice::from_raw_parts(ptr, len) }
+                }
+            }
+
+            // 0 on success, -1 if f fails or panics, no panic unwinds into the caller.
+            fn status(f: impl FnOnce() -> Option<()>) -> i32 {
+                match catch_unwind(AssertUnwindSafe(f)) {
+                    Ok(Some(())) => 0,
+                    _ => -1,
+                }
+            }
+
+            fn write_scalars(scalars: &[Fr], buf: &mut Vec<u8>) {
+                scalars.iter().for_each(|s| s.write(buf).unwrap());
+            }
+
+            #[no_mangle]
+            pub extern "C" fn zkagg_new(verify_circuit_k: u32) -> *mut ZkAggregator {
+                Box::into_raw(Box::new(ZkAggregator {
+                    verify_circuit_k,
+                    targets: (0..$n).map(|_| None).collect(),
+                    verify_params: None,
+                    verify_vk: None,
+                    transcript_label: None,
+                    instance_commitments: InstanceCommitmentCache::default(),
+                }))
+            }
+
+            #[no_mangle]
+            pub unsafe extern "C" fn zkagg_free(agg: *mut ZkAggregator) {
+                if !agg.is_null() {
+                    drop(Box::from_raw(agg));
+                }
+            }
+
+            #[no_mangle]
+            pub unsafe extern "C" fn zkagg_buffer_free(buf: ZkBuffer) {
+                if !buf.ptr.is_null() {
+                    drop(Box::from_raw(std::slice::from_raw_parts_mut(buf.ptr, buf.len)));
+                }
+            }
+
+            // Params and vk of the target circuit at index circuit, drops its proofs.
+            #[no_mangle]
+            pub unsafe extern "C" fn zkagg_set_target_circuit(
+                agg: *mut ZkAggregator,
+                circuit: usize,
+                params: *const u8,
+                params_len: usize,
+                vk: *const u8,
+                vk_len: usize,
+            ) -> i32 {
+                let agg = &mut *agg;
+                status(|| {
+                    let target_params =
+                        try_read_params::<G1Affine>(slice(params, params_len)).ok()?;
+                    let target_vk = READ_VK.get(circuit)?(&target_params, slice(vk, vk_len));
+                    *agg.targets.get_mut(circuit)? = Some(TargetCircuitData {
+                        params: Rc::new(target_params),
+                        vk: Rc::new(target_vk),
+                        proofs: vec![],
+                    });
+                    Some(())
+                })
+            }
+
+            // Adds a proof of the target circuit at index circuit, with its instances.
+            #[no_mangle]
+            pub unsafe extern "C" fn zkagg_add_target_proof(
+                agg: *mut ZkAggregator,
+                circuit: usize,
+                instances: *const u8,
+                instances_len: usize,
+                proof: *const u8,
+                proof_len: usize,
+            ) -> i32 {
+                let agg = &mut *agg;
+                status(|| {
+                    let instances =
+                        LOAD_INSTANCES.get(circuit)?(&slice(instances, instances_len).to_vec());
+                    let target = agg.targets.get_mut(circuit)?.as_mut()?;
+                    if target.proofs.len() == N_PROOFS[circuit] {
+                        return None;
+                    }
+                    target.proofs.push(SingleProofPair::<Bn256> {
+                        instances,
+                        transcript: slice(proof, proof_len).to_vec(),
+                    });
+                    Some(())
+                })
+            }
+
+            // Sets the verify circuit up from the target circuits, returns its params and vk.
+            #[no_mangle]
+            pub unsafe extern "C" fn zkagg_setup(
+                agg: *mut ZkAggregator,
+                params_out: *mut ZkBuffer,
+                vk_out: *mut ZkBuffer,
+            ) -> i32 {
+                let agg = &mut *agg;
+                status(|| {
+                    let setups = agg
+                        .targets()?
+                        .into_iter()
+                        .map(|(name, target)| Setup {
+                            name,
+                            target_circuit_params: target.params.clone(),
+                            target_circuit_vk: target.vk.clone(),
+                            proofs: target.proofs.clone(),
+                            nproofs: target.proofs.len(),
+                        })
+                        .collect::<Vec<_>>();
+                    let request = MultiCircuitsSetup::<_, _, $n> {
+                        setups: setups.try_into().ok()?,
+                        coherent: $coherent,
+                    };
+                    let (params, vk) = request.call(agg.verify_circuit_k);
+
+                    let mut params_buf = vec![];
+                    params.write(&mut params_buf).unwrap();
+                    let mut vk_buf = vec![];
+                    vk.write(&mut vk_buf).unwrap();
+                    *params_out = ZkBuffer::new(params_buf);
+                    *vk_out = ZkBuffer::new(vk_buf);
+                    agg.verify_params = Some(Rc::new(params));
+                    agg.verify_vk = Some(Rc::new(vk));
+                    Some(())
+                })
+            }
+
+            // Verify circuit params and vk of an earlier zkagg_setup.
+            #[no_mangle]
+            pub unsafe extern "C" fn zkagg_load_verify_circuit(
+                agg: *mut ZkAggregator,
+                params: *const u8,
+                params_len: usize,
+                vk: *const u8,
+                vk_len: usize,
+            ) -> i32 {
+                let agg = &mut *agg;
+                status(|| {
+                    let params = try_read_params::<G1Affine>(slice(params, params_len)).ok()?;
+                    let vk = VerifyingKey::<G1Affine>::read::<_, Halo2VerifierCircuit<'_, Bn256>>(
+                        &mut Cursor::new(slice(vk, vk_len)),
+                        &params,
+                    )
+                    .ok()?;
+                    agg.verify_params = Some(Rc::new(params));
+                    agg.verify_vk = Some(Rc::new(vk));
+                    Some(())
+                })
+            }
+
+            // Deployment label of the verify circuit proof transcript, as the transcript_label
+            // file of the cli. A null label unsets it.
+            #[no_mangle]
+            pub unsafe extern "C" fn zkagg_set_transcript_label(
+                agg: *mut ZkAggregator,
+                label: *const u8,
+                label_len: usize,
+            ) -> i32 {
+                let agg = &mut *agg;
+                agg.transcript_label = if label.is_null() {
+                    None
+                } else {
+                    Some(slice(label, label_len).to_vec())
+                };
+                0
+            }
+
+            // Aggregates the target proofs, returns the verify circuit proof, its instances
+            // and the final pair, as verify_circuit_proof.data, verify_circuit_instance.data
+            // and verify_circuit_final_pair.data.
+            #[no_mangle]
+            pub unsafe extern "C" fn zkagg_create_proof(
+                agg: *mut ZkAggregator,
+                proof_out: *mut ZkBuffer,
+                instances_out: *mut ZkBuffer,
+                final_pair_out: *mut ZkBuffer,
+            ) -> i32 {
+                let agg = &mut *agg;
+                status(|| {
+                    let target_circuit_proofs = agg
+                        .targets()?
+                        .into_iter()
+                        .map(|(name, target)| CreateProof {
+                            name,
+                            target_circuit_params: target.params.clone(),
+                            target_circuit_vk: target.vk.clone(),
+                            template_proofs: target.proofs.clone(),
+                            proofs: target.proofs.clone(),
+                            nproofs: target.proofs.len(),
+                        })
+                        .collect::<Vec<_>>();
+                    let request = MultiCircuitsCreateProof::<_, _, $n> {
+                        target_circuit_proofs: target_circuit_proofs.try_into().ok()?,
+                        verify_circuit_params: agg.verify_params.as_ref()?,
+                        verify_circuit_vk: (**agg.verify_vk.as_ref()?).clone(),
+                        coherent: $coherent,
+                        transcript_label: agg.transcript_label.clone(),
+                    };
+                    let (_, final_pair, instances, proof) =
+                        request.call_with_cache(&mut agg.instance_commitments);
+
+                    let mut instances_buf = vec![];
+                    write_scalars(&instances, &mut instances_buf);
+                    let mut final_pair_buf = vec![];
+                    for c in [final_pair.0.x, final_pair.0.y, final_pair.1.x, final_pair.1.y] {
+                        c.write(&mut final_pair_buf).unwrap();
+                    }
+                    write_scalars(&final_pair.2, &mut final_pair_buf);
+                    *proof_out = ZkBuffer::new(proof);
+                    *instances_out = ZkBuffer::new(instances_buf);
+                    *final_pair_out = ZkBuffer::new(final_pair_buf);
+                    Some(())
+                })
+            }
+
+            // 1 if the verify circuit proof is valid for the instances, 0 if not, -1 on error.
+            // The proof may be a proof envelope, -1 if it is not for the vk and instances.
+            #[no_mangle]
+            pub unsafe extern "C" fn zkagg_verify(
+                agg: *mut ZkAggregator,
+                instances: *const u8,
+                instances_len: usize,
+                proof: *const u8,
+                proof_len: usize,
+            ) -> i32 {
+                let agg = &*agg;
+                let (params, vk) = match (&agg.verify_params, &agg.verify_vk) {
+                    (Some(params), Some(vk)) => (params.clone(), vk.clone()),
+                    _ => return -1,
+                };
+                let verify_instance =
+                    match try_load_instances::<Bn256>(slice(instances, instances_len)) {
+                        Ok(verify_instance) => verify_instance,
+                        Err(_) => return -1,
+                    };
+                let proof = slice(proof, proof_len);
+                let verify_proof = if is_envelope(proof) {
+                    let instances = verify_instance[0][0].len();
+                    match ProofEnvelope::open_verify_circuit(proof, &vk, instances) {
+                        Ok(envelope) => envelope.proof,
+                        Err(_) => return -1,
+                    }
+                } else {
+                    proof.to_vec()
+                };
+                let check = VerifyCheck::<G1Affine> {
+                    verify_params: params,
+                    verify_vk: vk,
+                    verify_public_inputs_size: verify_instance[0][0].len(),
+                    verify_instance,
+                    verify_proof,
+                    transcript_label: agg.transcript_label.clone(),
+                };
+                match catch_unwind(AssertUnwindSafe(|| check.call::<Bn256>().is_ok())) {
+                    Ok(true) => 1,
+                    Ok(false) => 0,
+                    Err(_) => -1,
+                }
+            }
+        }
+    };
+
+    // `zkaggregate_ffi! { CircuitA: 4, CircuitB: 1 }`, the proof counts of zkaggregate!.
+    ( $( $x:ident : $np:expr ),+ $(,)? ) => {
+        $crate::zkaggregate_ffi! {
+            @impl 0 $( + $crate::zkaggregate!(@one $x) )+, vec![], $( $x: $np ),+
+        }
+    };
+
+    // Same with the coherent constraints after a `;`.
+    ( $( $x:ident : $np:expr ),+ ; $coherent:expr ) => {
+        $crate::zkaggregate_ffi! {
+            @impl 0 $( + $crate::zkaggregate!(@one $x) )+, $coherent, $( $x: $np ),+
+        }
+    };
+
+    // `zkaggregate_ffi! { n, coherent, CircuitA, CircuitB }`, the proof counts read from N_PROOFS.
+    ( $n:expr, $coherent:expr, $( $x:ident ),+ ) => {
+        $crate::zkaggregate_ffi! {
+            @impl $n, $coherent, $( $x: <$x as TargetCircuit<G1Affine, Bn256>>::N_PROOFS ),+
+        }
+    };
+}
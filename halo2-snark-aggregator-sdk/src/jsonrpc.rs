@@ -0,0 +1,475 @@
+// JSON-RPC 2.0 interface of the `serve` command: aggregation jobs are submitted over
+// http POST, queued and run one at a time by the aggregator, and polled by id.
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{channel, sync_channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const UNKNOWN_JOB: i64 = -32001;
+const JOB_NOT_DONE: i64 = -32002;
+
+// Larger request bodies are rejected with 413 before being read, the hex proofs of a job
+// are far below it.
+const MAX_BODY_SIZE: usize = 64 << 20;
+const MAX_HEADER_SIZE: u64 = 16 << 10;
+// Connections are handled by a fixed set of workers, the listener blocks once the backlog
+// is full, and a stalled client gives its worker back after the read timeout.
+const CONNECTION_WORKERS: usize = 8;
+const CONNECTION_BACKLOG: usize = 64;
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+// Done and failed jobs are dropped this long after they finish, then are unknown.
+const JOB_TTL: Duration = Duration::from_secs(60 * 60);
+
+// Instances and transcript of a target circuit proof, as the sample_circuit_*.data files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TargetProof {
+    pub instances: Vec<u8>,
+    pub transcript: Vec<u8>,
+}
+
+// Proofs of an aggregation, N_PROOFS of them for each target circuit, in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Job {
+    pub proofs: Vec<Vec<TargetProof>>,
+}
+
+// Outputs of the aggregation, as the verify_circuit_*.data files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JobResult {
+    pub proof: Vec<u8>,
+    pub instances: Vec<u8>,
+    pub final_pair: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Done(JobResult),
+    Failed(String),
+}
+
+fn to_hex(buf: &[u8]) -> String {
+    format!(
+        "0x{}",
+        buf.iter().map(|b| format!("{:02x}", b)).collect::<String>()
+    )
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn error(id: &Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+#[derive(Default)]
+struct Jobs {
+    next_id: u64,
+    statuses: HashMap<u64, JobStatus>,
+    // finish time of the done and failed jobs, oldest first
+    finished: VecDeque<(Instant, u64)>,
+}
+
+impl Jobs {
+    fn expire(&mut self, ttl: Duration) {
+        while let Some(&(finished_at, id)) = self.finished.front() {
+            if finished_at.elapsed() < ttl {
+                break;
+            }
+            self.statuses.remove(&id);
+            self.finished.pop_front();
+        }
+    }
+}
+
+struct Rpc {
+    n_proofs: Vec<usize>,
+    jobs: Mutex<Jobs>,
+    queue: Mutex<Sender<(u64, Job)>>,
+    job_ttl: Duration,
+}
+
+impl Rpc {
+    fn new(n_proofs: Vec<usize>, queue: Sender<(u64, Job)>, job_ttl: Duration) -> Self {
+        Rpc {
+            n_proofs,
+            jobs: Mutex::new(Jobs::default()),
+            queue: Mutex::new(queue),
+            job_ttl,
+        }
+    }
+
+    fn parse_job(&self, params: &Value) -> Option<Job> {
+        let circuits = params.get("proofs")?.as_array()?;
+        if circuits.len() != self.n_proofs.len() {
+            return None;
+        }
+        let proofs = circuits
+            .iter()
+            .zip(self.n_proofs.iter())
+            .map(|(proofs, n)| {
+                let proofs = proofs.as_array().filter(|proofs| proofs.len() == *n)?;
+                proofs
+                    .iter()
+                    .map(|proof| {
+                        Some(TargetProof {
+                            instances: from_hex(proof.get("instances")?.as_str()?)?,
+                            transcript: from_hex(proof.get("transcript")?.as_str()?)?,
+                        })
+                    })
+                    .collect()
+            })
+            .collect::<Option<_>>()?;
+        Some(Job { proofs })
+    }
+
+    fn submit_proofs(&self, params: &Value) -> Result<Value, (i64, &'static str)> {
+        let job = self.parse_job(params).ok_or((
+            INVALID_PARAMS,
+            "expected the hex instances and transcript of N_PROOFS proofs per circuit",
+        ))?;
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.expire(self.job_ttl);
+        let id = jobs.next_id;
+        jobs.next_id += 1;
+        jobs.statuses.insert(id, JobStatus::Pending);
+        self.queue.lock().unwrap().send((id, job)).unwrap();
+        Ok(json!({ "job_id": id }))
+    }
+
+    fn job(&self, params: &Value) -> Result<JobStatus, (i64, &'static str)> {
+        let id = params
+            .get("job_id")
+            .and_then(Value::as_u64)
+            .ok_or((INVALID_PARAMS, "expected a job_id"))?;
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.expire(self.job_ttl);
+        jobs.statuses
+            .get(&id)
+            .cloned()
+            .ok_or((UNKNOWN_JOB, "unknown job"))
+    }
+
+    fn job_status(&self, params: &Value) -> Result<Value, (i64, &'static str)> {
+        Ok(match self.job(params)? {
+            JobStatus::Pending => json!({ "status": "pending" }),
+            JobStatus::Running => json!({ "status": "running" }),
+            JobStatus::Done(_) => json!({ "status": "done" }),
+            JobStatus::Failed(e) => json!({ "status": "failed", "error": e }),
+        })
+    }
+
+    fn get_result(&self, params: &Value) -> Result<Value, (i64, &'static str)> {
+        match self.job(params)? {
+            JobStatus::Done(result) => Ok(json!({
+                "proof": to_hex(&result.proof),
+                "instances": to_hex(&result.instances),
+                "final_pair": to_hex(&result.final_pair),
+            })),
+            _ => Err((JOB_NOT_DONE, "job is not done")),
+        }
+    }
+
+    fn call(&self, request: &Value) -> Option<Value> {
+        let id = request.get("id").cloned();
+        let response_id = id.clone().unwrap_or(Value::Null);
+        let method = match (request.get("jsonrpc"), request.get("method")) {
+            (Some(version), Some(Value::String(method))) if version == "2.0" => method,
+            _ => return Some(error(&response_id, INVALID_REQUEST, "invalid request")),
+        };
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        let result = match method.as_str() {
+            "submit_proofs" => self.submit_proofs(&params),
+            "job_status" => self.job_status(&params),
+            "get_result" => self.get_result(&params),
+            _ => Err((METHOD_NOT_FOUND, "method not found")),
+        };
+        // notifications, without id, have no response
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error(&id, code, message),
+        })
+    }
+
+    // Response body of an http request body, a single request or a batch.
+    fn handle(&self, body: &str) -> Option<String> {
+        let response = match serde_json::from_str::<Value>(body) {
+            Err(_) => Some(error(&Value::Null, PARSE_ERROR, "parse error")),
+            Ok(Value::Array(batch)) if !batch.is_empty() => {
+                let responses = batch
+                    .iter()
+                    .filter_map(|request| self.call(request))
+                    .collect::<Vec<_>>();
+                (!responses.is_empty()).then(|| Value::Array(responses))
+            }
+            Ok(request) => self.call(&request),
+        };
+        response.map(|response| response.to_string())
+    }
+
+    fn set_status(&self, id: u64, status: JobStatus) {
+        let mut jobs = self.jobs.lock().unwrap();
+        if let JobStatus::Done(_) | JobStatus::Failed(_) = status {
+            jobs.finished.push_back((Instant::now(), id));
+        }
+        jobs.statuses.insert(id, status);
+        jobs.expire(self.job_ttl);
+    }
+}
+
+fn handle_connection(rpc: &Rpc, stream: TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut stream = stream;
+
+    let mut content_length = 0;
+    let mut headers = (&mut reader).take(MAX_HEADER_SIZE);
+    loop {
+        let mut line = String::new();
+        if headers.read_line(&mut line)? == 0 {
+            if headers.limit() == 0 {
+                return write!(
+                    stream,
+                    "HTTP/1.1 431 Request Header Fields Too Large\r\n\r\n"
+                );
+            }
+            break;
+        }
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        return write!(stream, "HTTP/1.1 413 Payload Too Large\r\n\r\n");
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body)?;
+
+    match rpc.handle(&String::from_utf8_lossy(&body)) {
+        Some(response) => write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            response.len(),
+            response
+        ),
+        None => write!(stream, "HTTP/1.1 204 No Content\r\n\r\n"),
+    }
+}
+
+// Serves the JSON-RPC methods on addr and runs the submitted jobs on the calling
+// thread, which owns the aggregator. n_proofs is N_PROOFS of each target circuit.
+// Panics of run fail the job instead of the server.
+pub fn serve<F>(addr: &str, n_proofs: Vec<usize>, mut run: F)
+where
+    F: FnMut(Job) -> JobResult,
+{
+    let (sender, receiver) = channel();
+    let rpc = Arc::new(Rpc::new(n_proofs, sender, JOB_TTL));
+
+    let listener = TcpListener::bind(addr).unwrap();
+    tracing::info!("serving json-rpc on {}", addr);
+    let (connections, pending) = sync_channel::<TcpStream>(CONNECTION_BACKLOG);
+    let pending = Arc::new(Mutex::new(pending));
+    for _ in 0..CONNECTION_WORKERS {
+        let server = rpc.clone();
+        let pending = pending.clone();
+        std::thread::spawn(move || loop {
+            // the lock is released before the connection is handled
+            let stream = match pending.lock().unwrap().recv() {
+                Ok(stream) => stream,
+                Err(_) => return,
+            };
+            if let Err(e) = handle_connection(&server, stream) {
+                tracing::warn!("json-rpc connection failed: {}", e);
+            }
+        });
+    }
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if connections.send(stream).is_err() {
+                return;
+            }
+        }
+    });
+
+    for (id, job) in receiver {
+        rpc.set_status(id, JobStatus::Running);
+        let status = match catch_unwind(AssertUnwindSafe(|| run(job))) {
+            Ok(result) => JobStatus::Done(result),
+            Err(e) => JobStatus::Failed(
+                e.downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| e.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "aggregation panicked".to_owned()),
+            ),
+        };
+        tracing::info!("json-rpc job {} finished", id);
+        rpc.set_status(id, status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::Receiver;
+
+    fn rpc(job_ttl: Duration) -> (Rpc, Receiver<(u64, Job)>) {
+        let (sender, receiver) = channel();
+        (Rpc::new(vec![2], sender, job_ttl), receiver)
+    }
+
+    fn call(rpc: &Rpc, method: &str, params: Value) -> Value {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        serde_json::from_str(&rpc.handle(&request.to_string()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(to_hex(&[0, 0xab]), "0x00ab");
+        assert_eq!(from_hex("0x00ab"), Some(vec![0, 0xab]));
+        assert_eq!(from_hex("00ab"), Some(vec![0, 0xab]));
+        assert_eq!(from_hex("0x0"), None);
+        assert_eq!(from_hex("0xzz"), None);
+    }
+
+    #[test]
+    fn test_jobs() {
+        let (rpc, receiver) = rpc(JOB_TTL);
+        let proof = json!({ "instances": "0x01", "transcript": "0x0203" });
+
+        let response = call(&rpc, "submit_proofs", json!({ "proofs": [[proof]] }));
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        let response = call(&rpc, "submit_proofs", json!({ "proofs": [[proof, proof]] }));
+        assert_eq!(response["result"]["job_id"], 0);
+        let (id, job) = receiver.try_recv().unwrap();
+        assert_eq!(id, 0);
+        assert_eq!(
+            job.proofs[0][1],
+            TargetProof {
+                instances: vec![1],
+                transcript: vec![2, 3],
+            }
+        );
+
+        let job = json!({ "job_id": 0 });
+        assert_eq!(
+            call(&rpc, "job_status", job.clone())["result"]["status"],
+            "pending"
+        );
+        assert_eq!(
+            call(&rpc, "get_result", job.clone())["error"]["code"],
+            JOB_NOT_DONE
+        );
+
+        rpc.set_status(
+            0,
+            JobStatus::Done(JobResult {
+                proof: vec![4],
+                instances: vec![5],
+                final_pair: vec![6],
+            }),
+        );
+        assert_eq!(
+            call(&rpc, "job_status", job.clone())["result"]["status"],
+            "done"
+        );
+        assert_eq!(
+            call(&rpc, "get_result", job)["result"]["final_pair"],
+            "0x06"
+        );
+        assert_eq!(
+            call(&rpc, "job_status", json!({ "job_id": 1 }))["error"]["code"],
+            UNKNOWN_JOB
+        );
+    }
+
+    #[test]
+    fn test_requests() {
+        let (rpc, _receiver) = rpc(JOB_TTL);
+        let parse =
+            |body: &str| -> Value { serde_json::from_str(&rpc.handle(body).unwrap()).unwrap() };
+
+        assert_eq!(parse("{")["error"]["code"], PARSE_ERROR);
+        assert_eq!(
+            parse(r#"{"id": 1, "method": "job_status"}"#)["error"]["code"],
+            INVALID_REQUEST
+        );
+        assert_eq!(
+            parse(r#"{"jsonrpc": "2.0", "id": 1, "method": "prove"}"#)["error"]["code"],
+            METHOD_NOT_FOUND
+        );
+        // notification
+        assert_eq!(
+            rpc.handle(r#"{"jsonrpc": "2.0", "method": "job_status", "params": {"job_id": 0}}"#),
+            None
+        );
+
+        let batch = parse(
+            r#"[{"jsonrpc": "2.0", "id": 1, "method": "job_status", "params": {"job_id": 0}},
+                {"jsonrpc": "2.0", "id": 2, "method": "prove"}]"#,
+        );
+        assert_eq!(batch.as_array().unwrap().len(), 2);
+        assert_eq!(batch[1]["id"], 2);
+    }
+    #[test]
+    fn test_expiry() {
+        let (rpc, _receiver) = rpc(Duration::from_secs(0));
+        let proof = json!({ "instances": "0x01", "transcript": "0x0203" });
+        call(&rpc, "submit_proofs", json!({ "proofs": [[proof, proof]] }));
+
+        // pending and running jobs never expire
+        rpc.set_status(0, JobStatus::Running);
+        let job = json!({ "job_id": 0 });
+        assert_eq!(
+            call(&rpc, "job_status", job.clone())["result"]["status"],
+            "running"
+        );
+
+        rpc.set_status(0, JobStatus::Failed("aggregation panicked".to_owned()));
+        assert_eq!(call(&rpc, "job_status", job)["error"]["code"], UNKNOWN_JOB);
+        assert!(rpc.jobs.lock().unwrap().finished.is_empty());
+    }
+
+    #[test]
+    fn test_body_size() {
+        let (rpc, _receiver) = rpc(JOB_TTL);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        write!(
+            client,
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY_SIZE + 1
+        )
+        .unwrap();
+
+        let (stream, _) = listener.accept().unwrap();
+        handle_connection(&rpc, stream).unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 413 "));
+    }
+}
@@ -1,10 +1,37 @@
 #[cfg(feature = "benches")]
 #[cfg(test)]
 mod benches;
+mod ffi;
+pub mod jsonrpc;
+pub mod orchestrator;
+mod python;
+pub mod trace;
+
+// Expands to its input with the dev-graph feature of the sdk, for the verify_layout command of
+// zkaggregate!, whose cfg would otherwise test the features of the crate expanding it.
+#[cfg(feature = "dev-graph")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __dev_graph {
+    ($($t:tt)*) => {
+        $($t)*
+    };
+}
+
+#[cfg(not(feature = "dev-graph"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __dev_graph {
+    ($($t:tt)*) => {};
+}
 
 #[macro_export]
 macro_rules! zkaggregate {
-    ( $n:expr, $coherent:expr, $( $x:ident ),+ ) => {
+    (@one $x:ident) => {
+        1usize
+    };
+
+    (@impl $n:expr, $coherent:expr, $( $x:ident : $np:expr ),+ ) => {
         mod zkcli {
             $(
                 use crate::$x;
@@ -13,7 +40,15 @@ macro_rules! zkaggregate {
             use halo2_proofs::arithmetic::{BaseExt, CurveAffine, MultiMillerLoop};
             use halo2_proofs::plonk::{Circuit, VerifyingKey};
             use halo2_proofs::poly::commitment::Params;
+            use halo2_snark_aggregator_circuit::envelope::ProofEnvelope;
+            use halo2_snark_aggregator_circuit::explain::{
+                explain_target_circuit_proof, explain_verify_circuit_proof,
+            };
             use halo2_snark_aggregator_circuit::fs::*;
+            use halo2_snark_aggregator_circuit::malformed::{
+                check_malformed_proof, malformed_proofs_json, malformed_verify_circuit_proofs,
+            };
+            use halo2_snark_aggregator_circuit::vk::{vk_hash_hex, VK_HASH_INSTANCES};
             use halo2_snark_aggregator_circuit::sample_circuit::{
                 sample_circuit_random_run, sample_circuit_setup, TargetCircuit,
             };
@@ -21,8 +56,17 @@ macro_rules! zkaggregate {
                 CreateProof, Halo2VerifierCircuit, MultiCircuitsCreateProof,
                 MultiCircuitsSetup, Setup, SingleProofWitness, VerifyCheck, SingleProofPair,
             };
-            use halo2_snark_aggregator_solidity::{SolidityGenerate, MultiCircuitSolidityGenerate};
-            use log::info;
+            use halo2_snark_aggregator_sdk::jsonrpc::{serve, Job, JobResult};
+            use halo2_snark_aggregator_sdk::orchestrator::{
+                prepare_shards, run_shards, ShardedCircuit,
+            };
+            use halo2_snark_aggregator_solidity::{
+                render_batch_verifier, render_calldata_encoder, render_foundry_suite,
+                render_cosmwasm_project, render_hardhat_project, render_near_project,
+                render_solana_project, verifier_steps, vk_hash, CodegenBackend, GeneratedVerifier,
+                MultiCircuitSolidityGenerate, SolidityGenerate, SolidityOptions, TranscriptHash,
+            };
+            use tracing::{info, info_span};
             use pairing_bn256::bn256::{Bn256, Fr, G1Affine};
             use std::io::{Cursor, Read, Write};
             use std::marker::PhantomData;
@@ -40,6 +84,25 @@ macro_rules! zkaggregate {
                 folder_path: std::path::PathBuf,
                 #[clap(short, long, parse(from_os_str))]
                 template_path: Option<std::path::PathBuf>,
+                // verify_solidity also adds verify_compressed(bytes) to the contract
+                #[clap(long)]
+                compressed: bool,
+                // address of the json-rpc server of the serve command
+                #[clap(long, default_value = "127.0.0.1:8545")]
+                addr: String,
+                // chrome trace of the spans of the command, for chrome://tracing or perfetto
+                #[clap(long, parse(from_os_str))]
+                trace_output: Option<PathBuf>,
+                // verify_run processes of the orchestrate command run at a time
+                #[clap(long, default_value = "2")]
+                jobs: usize,
+                // runs again of a failed verify_run process of the orchestrate command
+                #[clap(long, default_value = "1")]
+                retries: usize,
+                // proof of the explain_proof command: verify_circuit, or the name of a target
+                // circuit followed by the index of its proof, as in its proof file name
+                #[clap(long, default_value = "verify_circuit")]
+                proof: String,
             }
 
             paste! {
@@ -48,22 +111,23 @@ macro_rules! zkaggregate {
                     folder: PathBuf,
                     template_folder: Option<PathBuf>,
                     verify_circuit_k: u32,
+                    _trace_guard: Option<halo2_snark_aggregator_sdk::trace::FlushGuard>,
                 }
             }
 
-            fn env_init() {
-                env_logger::init();
+            fn env_init(args: &Cli) -> Option<halo2_snark_aggregator_sdk::trace::FlushGuard> {
+                let guard = halo2_snark_aggregator_sdk::trace::init(args.trace_output.as_deref());
                 rayon::ThreadPoolBuilder::new()
                     .num_threads(24)
                     .build_global()
                     .unwrap();
+                guard
             }
 
             paste! {
                 pub fn builder(verify_circuit_k: u32) -> CliBuilder {
-                    env_init();
-
                     let args = Cli::parse();
+                    let _trace_guard = env_init(&args);
                     let folder = args.folder_path.clone();
                     let template_folder = args.template_path.clone();
 
@@ -72,6 +136,7 @@ macro_rules! zkaggregate {
                         folder,
                         template_folder,
                         verify_circuit_k,
+                        _trace_guard,
                     }
                 }
             }
@@ -80,7 +145,8 @@ macro_rules! zkaggregate {
                 fn compute_verify_public_input_size(&self) -> usize {
                     4
                     $(
-                        + <$x as TargetCircuit<G1Affine, Bn256>>::N_PROOFS * <$x as TargetCircuit<G1Affine, Bn256>>::PUBLIC_INPUT_SIZE
+                        + $np * <$x as TargetCircuit<G1Affine, Bn256>>::PUBLIC_INPUT_SIZE
+                        + VK_HASH_INSTANCES
                     )*
                 }
 
@@ -90,8 +156,11 @@ macro_rules! zkaggregate {
                     )*
                 }
 
-                fn sample_run_one_circuit<SingleCircuit: TargetCircuit<G1Affine, Bn256>>(&self) {
-                    for i in 0..SingleCircuit::N_PROOFS {
+                fn sample_run_one_circuit<SingleCircuit: TargetCircuit<G1Affine, Bn256>>(
+                    &self,
+                    nproofs: usize,
+                ) {
+                    for i in 0..nproofs {
                         let (circuit, instances) = SingleCircuit::instance_builder();
 
                         sample_circuit_random_run::<G1Affine, Bn256, SingleCircuit>(
@@ -108,21 +177,29 @@ macro_rules! zkaggregate {
 
                 fn dispatch_sample_run(&self) {
                     $(
-                        self.sample_run_one_circuit::<$x>();
+                        self.sample_run_one_circuit::<$x>($np);
                     )*
                 }
 
-                fn dispatch_verify_setup(&self) {
+                fn multi_circuits_setup(&self) -> MultiCircuitsSetup<G1Affine, Bn256, { $n }> {
                     let setup: [Setup<_, _>; $n] = [
                         $(
-                            Setup::new::<$x, _>(&self.folder, &<$x as TargetCircuit<G1Affine, Bn256>>::load_instances),
+                            Setup::with_nproofs::<$x, _>(
+                                &self.folder,
+                                &<$x as TargetCircuit<G1Affine, Bn256>>::load_instances,
+                                $np,
+                            ),
                         )*
                     ];
 
-                    let request = MultiCircuitsSetup::<_, _, $n> {
+                    MultiCircuitsSetup::<_, _, { $n }> {
                         setups: setup,
                         coherent: $coherent
-                    };
+                    }
+                }
+
+                fn dispatch_verify_setup(&self) {
+                    let request = self.multi_circuits_setup();
 
                     let (params, vk) = request.call(self.verify_circuit_k);
 
@@ -133,58 +210,503 @@ macro_rules! zkaggregate {
                 fn dispatch_verify_run(&self) {
                     let target_circuit_proofs: [CreateProof<_, _>; $n] = [
                         $(
-                            CreateProof::new::<$x, _>(&self.folder, &<$x as TargetCircuit<G1Affine, Bn256>>::load_instances),
+                            CreateProof::with_nproofs::<$x, _>(
+                                &self.folder,
+                                &<$x as TargetCircuit<G1Affine, Bn256>>::load_instances,
+                                $np,
+                            ),
                         )*
                     ];
 
-                    let request = MultiCircuitsCreateProof::<_, _, $n> {
+                    let request = MultiCircuitsCreateProof::<_, _, { $n }> {
                         target_circuit_proofs,
                         verify_circuit_params: &load_verify_circuit_params(&mut self.folder.clone()),
                         verify_circuit_vk: load_verify_circuit_vk(&mut self.folder.clone()),
-                        coherent: $coherent
+                        coherent: $coherent,
+                        transcript_label: load_transcript_label(&mut self.folder.clone()),
                     };
 
-                    let (_, final_pair, instance, proof) = request.call();
+                    let mut cache = load_instance_commitment_cache(&mut self.folder.clone());
+                    let cached = cache.len();
+                    let (pk, final_pair, instance, proof) = request.call_with_cache(&mut cache);
+                    if cache.len() != cached {
+                        write_instance_commitment_cache(&mut self.folder.clone(), &cache);
+                    }
 
                     write_verify_circuit_instance(&mut self.folder.clone(), &instance);
                     write_verify_circuit_proof(&mut self.folder.clone(), &proof);
+                    write_verify_circuit_proof_envelope(
+                        &mut self.folder.clone(),
+                        &ProofEnvelope::verify_circuit(pk.get_vk(), instance.len(), proof),
+                    );
                     write_verify_circuit_final_pair(&mut self.folder.clone(), &final_pair);
                 }
 
+                // Runs the aggregation jobs submitted over json-rpc, against the target
+                // circuits and verify circuit of the folder, loaded once.
+                fn dispatch_serve(&self) {
+                    let targets: [(Rc<Params<G1Affine>>, Rc<VerifyingKey<G1Affine>>); $n] = [
+                        $(
+                            {
+                                let params = load_target_circuit_params::<G1Affine, Bn256, $x>(
+                                    &mut self.folder.clone(),
+                                );
+                                let vk = load_target_circuit_vk::<G1Affine, Bn256, $x>(
+                                    &mut self.folder.clone(),
+                                    &params,
+                                );
+                                (Rc::new(params), Rc::new(vk))
+                            },
+                        )*
+                    ];
+                    let names = [$(<$x as TargetCircuit<G1Affine, Bn256>>::NAME,)*];
+                    let load_instances: [fn(&Vec<u8>) -> Vec<Vec<Vec<Fr>>>; $n] = [
+                        $(
+                            <$x as TargetCircuit<G1Affine, Bn256>>::load_instances,
+                        )*
+                    ];
+                    let verify_params = load_verify_circuit_params(&mut self.folder.clone());
+                    let verify_vk = load_verify_circuit_vk(&mut self.folder.clone());
+                    let transcript_label = load_transcript_label(&mut self.folder.clone());
+                    let mut cache = load_instance_commitment_cache(&mut self.folder.clone());
+
+                    let n_proofs = vec![$($np,)*];
+                    serve(&self.args.addr, n_proofs, |job: Job| {
+                        let target_circuit_proofs = job
+                            .proofs
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, proofs)| {
+                                let proofs = proofs
+                                    .into_iter()
+                                    .map(|proof| SingleProofPair::<Bn256> {
+                                        instances: load_instances[i](&proof.instances),
+                                        transcript: proof.transcript,
+                                    })
+                                    .collect::<Vec<_>>();
+                                CreateProof {
+                                    name: names[i].to_owned(),
+                                    target_circuit_params: targets[i].0.clone(),
+                                    target_circuit_vk: targets[i].1.clone(),
+                                    template_proofs: proofs.clone(),
+                                    nproofs: proofs.len(),
+                                    proofs,
+                                }
+                            })
+                            .collect::<Vec<_>>();
+
+                        let request = MultiCircuitsCreateProof::<_, _, { $n }> {
+                            target_circuit_proofs: target_circuit_proofs
+                                .try_into()
+                                .unwrap_or_else(|_| unreachable!()),
+                            verify_circuit_params: &verify_params,
+                            verify_circuit_vk: verify_vk.clone(),
+                            coherent: $coherent,
+                            transcript_label: transcript_label.clone(),
+                        };
+                        let cached = cache.len();
+                        let (_, final_pair, instances, proof) = request.call_with_cache(&mut cache);
+                        if cache.len() != cached {
+                            write_instance_commitment_cache(&mut self.folder.clone(), &cache);
+                        }
+
+                        JobResult {
+                            proof,
+                            instances: encode_verify_circuit_instance(&instances),
+                            final_pair: encode_verify_circuit_final_pair(&final_pair),
+                        }
+                    });
+                }
+
                 fn dispatch_verify_check(&self) {
                     let request = VerifyCheck::<G1Affine>::new(&self.folder, self.compute_verify_public_input_size());
                     request.call::<Bn256>().unwrap();
 
+                    // the final pair is checked against the setup of the first target circuit,
+                    // all of them share the [s] of the pairing
+                    let target_params = [
+                        $(
+                            load_target_circuit_params::<G1Affine, Bn256, $x>(
+                                &mut self.folder.clone(),
+                            ),
+                        )*
+                    ];
+                    let target_params = target_params[0].verifier::<Bn256>(0).unwrap();
+                    let final_pair = decode_verify_circuit_final_pair(
+                        &load_verify_circuit_final_pair(&mut self.folder.clone()),
+                    )
+                    .unwrap();
+                    request.check_final_pair(&target_params, &final_pair).unwrap();
+
                     info!("verify check succeed")
                 }
 
-                fn dispatch_verify_solidity(&self) {
+                // verify_circuit_malformed.json, corrupted variants of the proof of the run,
+                // each checked to fail the native verifier the way its label says.
+                fn dispatch_verify_malformed(&self) {
+                    let verify_params = load_verify_circuit_params(&mut self.folder.clone());
+                    let verify_vk = load_verify_circuit_vk(&mut self.folder.clone());
+                    let params = verify_params
+                        .verifier::<Bn256>(self.compute_verify_public_input_size())
+                        .unwrap();
+                    let proof = load_verify_circuit_proof_checked(
+                        &mut self.folder.clone(),
+                        &verify_vk,
+                        self.compute_verify_public_input_size(),
+                    );
+                    let malformed = malformed_verify_circuit_proofs(
+                        &verify_vk,
+                        &params,
+                        &proof,
+                        &load_verify_circuit_final_pair(&mut self.folder.clone()),
+                    )
+                    .expect("could not read the proof");
+                    let label = load_transcript_label(&mut self.folder.clone());
+                    for m in malformed.iter() {
+                        check_malformed_proof(&verify_vk, &params, label.as_deref(), m).unwrap();
+                    }
+
+                    write_verify_circuit_malformed_fixtures(
+                        &mut self.folder.clone(),
+                        &malformed_proofs_json(&malformed),
+                    );
+                    info!("{} malformed proofs written", malformed.len())
+                }
+
+                fn solidity_generate_request<'a>(
+                    &self,
+                    verify_params: &'a Params<G1Affine>,
+                    verify_vk: &'a VerifyingKey<G1Affine>,
+                ) -> MultiCircuitSolidityGenerate<'a, G1Affine, $n> {
                     let target_circuits_params: [SolidityGenerate<_>; $n] = [
                         $(
-                            SolidityGenerate::new::<$x>(&self.folder),
+                            SolidityGenerate::with_nproofs::<$x>(&self.folder, $np),
                         )*
                     ];
 
-                    let request = MultiCircuitSolidityGenerate::<G1Affine, $n> {
+                    MultiCircuitSolidityGenerate::<G1Affine, $n> {
                         target_circuits_params,
-                        verify_params: &load_verify_circuit_params(&mut self.folder.clone()),
-                        verify_vk: &load_verify_circuit_vk(&mut self.folder.clone()),
-                        verify_circuit_instance: load_verify_circuit_instance(
-                            &mut self.folder.clone(),
-                        ),
-                        proof: load_verify_circuit_proof(&mut self.folder.clone()),
+                        verify_params,
+                        verify_vk,
                         verify_public_inputs_size: self.compute_verify_public_input_size(),
-                    };
+                        // see Halo2VerifierCircuit's transcript
+                        transcript_hash: TranscriptHash::Sha256,
+                        transcript_label: load_transcript_label(&mut self.folder.clone()),
+                        solidity_options: self.solidity_options(),
+                    }
+                }
 
-                    let sol = request.call::<Bn256>(self.template_folder.clone().unwrap());
+                // Options of every solidity contract the sdk writes.
+                fn solidity_options(&self) -> SolidityOptions {
+                    SolidityOptions {
+                        compressed: self.args.compressed,
+                        ..SolidityOptions::default()
+                    }
+                }
+
+                fn dispatch_verify_solidity(&self) {
+                    let verify_params = load_verify_circuit_params(&mut self.folder.clone());
+                    let verify_vk = load_verify_circuit_vk(&mut self.folder.clone());
+                    let request = self.solidity_generate_request(&verify_params, &verify_vk);
+                    let proof = load_verify_circuit_proof_checked(
+                        &mut self.folder.clone(),
+                        &verify_vk,
+                        self.compute_verify_public_input_size(),
+                    );
+                    let GeneratedVerifier {
+                        verifier: sol,
+                        gas_report,
+                        layout,
+                        audit_report,
+                        ..
+                    } = request.call_with_artifacts::<Bn256>(self.template_folder.clone());
+                    if self.args.compressed {
+                        write_verify_circuit_compressed_calldata(
+                            &mut self.folder.clone(),
+                            &layout.compress(
+                                &proof,
+                                &load_verify_circuit_final_pair(&mut self.folder.clone()),
+                            ),
+                        );
+                    }
+                    let encoder = render_calldata_encoder(self.template_folder.clone(), &layout);
+                    write_verify_circuit_encoder(
+                        &mut self.folder.clone(),
+                        &Vec::<u8>::from(encoder.as_bytes()),
+                    );
 
                     write_verify_circuit_solidity(
                         &mut self.folder.clone(),
                         &Vec::<u8>::from(sol.as_bytes()),
                     );
+                    let batch_sol = render_batch_verifier(
+                        self.template_folder.clone(),
+                        load_verify_circuit_final_pair(&mut self.folder.clone()).len() / 32,
+                        &request.solidity_options,
+                        verifier_steps(&sol, &request.solidity_options.contract_name),
+                    );
+                    write_verify_circuit_batch_solidity(
+                        &mut self.folder.clone(),
+                        &Vec::<u8>::from(batch_sol.as_bytes()),
+                    );
+                    write_verify_circuit_gas_report(
+                        &mut self.folder.clone(),
+                        &Vec::<u8>::from(gas_report.to_string().as_bytes()),
+                    );
+                    write_verify_circuit_audit_report(
+                        &mut self.folder.clone(),
+                        &Vec::<u8>::from(audit_report.as_bytes()),
+                    );
+
+                    let (foundry_config, foundry_test) = render_foundry_suite(
+                        self.template_folder.clone(),
+                        &proof,
+                        &load_verify_circuit_final_pair(&mut self.folder.clone()),
+                        &request.solidity_options,
+                        verifier_steps(&sol, &request.solidity_options.contract_name),
+                    )
+                    .unwrap_or_else(|e| panic!("failed to render the foundry suite: {}", e));
+                    write_verify_circuit_foundry_suite(
+                        &mut self.folder.clone(),
+                        &Vec::<u8>::from(foundry_config.as_bytes()),
+                        &Vec::<u8>::from(foundry_test.as_bytes()),
+                    );
+                }
+
+                // verification_key.json, proof.json and public.json of the verify circuit,
+                // in the style of snarkjs.
+                fn dispatch_verify_snarkjs(&self) {
+                    let verify_params = load_verify_circuit_params(&mut self.folder.clone());
+                    let verify_vk = load_verify_circuit_vk(&mut self.folder.clone());
+                    let instances = load_verify_circuit_instance(&mut self.folder.clone());
+
+                    write_verify_circuit_snarkjs(
+                        &mut self.folder.clone(),
+                        &halo2_snark_aggregator_circuit::snarkjs::verification_key_json(
+                            &verify_params,
+                            &verify_vk,
+                            self.compute_verify_public_input_size(),
+                        ),
+                        &halo2_snark_aggregator_circuit::snarkjs::proof_json(
+                            &load_verify_circuit_proof(&mut self.folder.clone()),
+                            &load_verify_circuit_final_pair(&mut self.folder.clone()),
+                        ),
+                        &halo2_snark_aggregator_circuit::snarkjs::public_json(&instances[0][0]),
+                    );
+                }
+
+                // Same verifier as verify_solidity, as the yul object in verifier.yul.
+                fn dispatch_verify_yul(&self) {
+                    let verify_params = load_verify_circuit_params(&mut self.folder.clone());
+                    let verify_vk = load_verify_circuit_vk(&mut self.folder.clone());
+                    let yul = self
+                        .solidity_generate_request(&verify_params, &verify_vk)
+                        .call_with_backend::<Bn256>(
+                            self.template_folder.clone(),
+                            CodegenBackend::Yul,
+                        );
+                    write_verify_circuit_yul(
+                        &mut self.folder.clone(),
+                        &Vec::<u8>::from(yul.as_bytes()),
+                    );
+                }
+
+                // Same verifier as verify_solidity, written in vyper to verifier.vy.
+                fn dispatch_verify_vyper(&self) {
+                    let verify_params = load_verify_circuit_params(&mut self.folder.clone());
+                    let verify_vk = load_verify_circuit_vk(&mut self.folder.clone());
+                    let vy = self
+                        .solidity_generate_request(&verify_params, &verify_vk)
+                        .call_with_backend::<Bn256>(
+                            self.template_folder.clone(),
+                            CodegenBackend::Vyper,
+                        );
+                    write_verify_circuit_vyper(
+                        &mut self.folder.clone(),
+                        &Vec::<u8>::from(vy.as_bytes()),
+                    );
+                }
+
+                // Same verifier as verify_solidity, as an aptos move module in verifier.move.
+                fn dispatch_verify_move(&self) {
+                    let verify_params = load_verify_circuit_params(&mut self.folder.clone());
+                    let verify_vk = load_verify_circuit_vk(&mut self.folder.clone());
+                    let module = self
+                        .solidity_generate_request(&verify_params, &verify_vk)
+                        .call_with_backend::<Bn256>(
+                            self.template_folder.clone(),
+                            CodegenBackend::Move,
+                        );
+                    write_verify_circuit_move(
+                        &mut self.folder.clone(),
+                        &Vec::<u8>::from(module.as_bytes()),
+                    );
+                }
+
+                // Same verifier as verify_solidity, wrapped in a hardhat project under hardhat/.
+                fn dispatch_verify_hardhat(&self) {
+                    self.dispatch_verify_solidity();
+
+                    let files = render_hardhat_project(
+                        self.template_folder.clone(),
+                        &String::from_utf8(read_file(&mut self.folder.clone(), "verifier.sol")).unwrap(),
+                        &load_verify_circuit_proof_envelope(
+                            &mut self.folder.clone(),
+                            &load_verify_circuit_vk(&mut self.folder.clone()),
+                            self.compute_verify_public_input_size(),
+                        ),
+                        &load_verify_circuit_final_pair(&mut self.folder.clone()),
+                        &self.solidity_options(),
+                    )
+                    .unwrap_or_else(|e| panic!("failed to render the hardhat project: {}", e));
+                    write_verify_circuit_hardhat_project(&mut self.folder.clone(), &files);
+                }
+
+                // Contract crates settling the final pair of the run against the setup of the
+                // first target circuit and the verify circuit vk, under cosmwasm/, near/ or
+                // solana/.
+                fn render_settlement_project(
+                    &self,
+                    render: fn(
+                        Option<PathBuf>,
+                        &SolidityGenerate<G1Affine>,
+                        &[u8; 32],
+                        &[u8],
+                    ) -> Vec<(String, String)>,
+                ) -> Vec<(String, String)> {
+                    let verify_vk = load_verify_circuit_vk(&mut self.folder.clone());
+                    let targets: [SolidityGenerate<_>; $n] = [
+                        $(
+                            SolidityGenerate::with_nproofs::<$x>(&self.folder, $np),
+                        )*
+                    ];
+                    render(
+                        self.template_folder.clone(),
+                        &targets[0],
+                        &vk_hash(&verify_vk),
+                        &load_verify_circuit_final_pair(&mut self.folder.clone()),
+                    )
+                }
+
+                fn dispatch_verify_cosmwasm(&self) {
+                    let files = self.render_settlement_project(render_cosmwasm_project::<_, Bn256>);
+                    write_verify_circuit_cosmwasm_project(&mut self.folder.clone(), &files);
+                }
+
+                fn dispatch_verify_near(&self) {
+                    let files = self.render_settlement_project(render_near_project::<_, Bn256>);
+                    write_verify_circuit_near_project(&mut self.folder.clone(), &files);
+                }
+
+                fn dispatch_verify_solana(&self) {
+                    let files = self.render_settlement_project(render_solana_project::<_, Bn256>);
+                    write_verify_circuit_solana_project(&mut self.folder.clone(), &files);
+                }
+
+                halo2_snark_aggregator_sdk::__dev_graph! {
+                    // Renders the verify circuit layout to folder/verify_circuit_layout.png and
+                    // prints the rows of every step of the aggregation.
+                    fn dispatch_verify_layout(&self) {
+                        let path = self.folder.join("verify_circuit_layout.png");
+                        let steps = self
+                            .multi_circuits_setup()
+                            .render_layout(self.verify_circuit_k, &path)
+                            .unwrap();
+                        for step in steps {
+                            println!("{:>8} rows  {}", step.rows.len(), step.name);
+                        }
+                        info!("layout written to {:?}", path);
+                    }
+                }
+
+                // Aggregates the target proofs of the folder by shards of the declared proof
+                // count per circuit, each proved by a verify_run process in folder/shards/<i>.
+                fn dispatch_orchestrate(&self) {
+                    let circuits = [
+                        $(
+                            ShardedCircuit {
+                                name: <$x as TargetCircuit<G1Affine, Bn256>>::NAME,
+                                params_name: <$x as TargetCircuit<G1Affine, Bn256>>::PARAMS_NAME,
+                                n_proofs: $np,
+                            },
+                        )*
+                    ];
+                    let shards = prepare_shards(&self.folder, &circuits).unwrap();
+                    info!("{} shards", shards.len());
+
+                    let exe = std::env::current_exe().unwrap();
+                    let results = run_shards(&shards, self.args.jobs, self.args.retries, |shard| {
+                        let mut command = std::process::Command::new(&exe);
+                        command
+                            .arg("--command")
+                            .arg("verify_run")
+                            .arg("--folder-path")
+                            .arg(&shard.folder);
+                        command
+                    });
+
+                    let failed = shards
+                        .iter()
+                        .zip(results.iter())
+                        .filter_map(|(shard, result)| result.as_ref().err().map(|e| (shard, e)))
+                        .map(|(shard, e)| format!("{}: {}", shard.folder.display(), e))
+                        .collect::<Vec<_>>();
+                    assert!(failed.is_empty(), "failed shards: {}", failed.join(", "));
+                }
+
+                // Prints the labelled commitments, challenges and evaluations of the proof
+                // transcript, to find where a proof that fails to aggregate goes wrong.
+                fn dispatch_explain_proof(&self) {
+                    let proof = self.args.proof.as_str();
+                    let entries = if proof == "verify_circuit" {
+                        explain_verify_circuit_proof(
+                            &self.folder,
+                            self.compute_verify_public_input_size(),
+                        )
+                    } else {
+                        let index = |name: &str| {
+                            proof.strip_prefix(name).and_then(|i| i.parse::<usize>().ok())
+                        };
+                        None
+                        $(
+                            .or_else(|| {
+                                index(<$x as TargetCircuit<G1Affine, Bn256>>::NAME).map(|i| {
+                                    explain_target_circuit_proof::<$x>(&self.folder, i)
+                                })
+                            })
+                        )*
+                        .unwrap_or_else(|| panic!("unknown proof {}", proof))
+                    };
+
+                    for entry in entries.expect("could not read the proof") {
+                        println!("{}", entry);
+                    }
+                }
+
+                // Prints the canonical vk hash of the verify circuit and of every target
+                // circuit, the hash the envelopes and the generated contracts pin.
+                fn dispatch_vk_hash(&self) {
+                    let verify_vk = load_verify_circuit_vk(&mut self.folder.clone());
+                    println!("verify_circuit {}", vk_hash_hex(&verify_vk));
+                    $(
+                        let params = load_target_circuit_params::<G1Affine, Bn256, $x>(
+                            &mut self.folder.clone(),
+                        );
+                        let vk = load_target_circuit_vk::<G1Affine, Bn256, $x>(
+                            &mut self.folder.clone(),
+                            &params,
+                        );
+                        println!(
+                            "{} {}",
+                            <$x as TargetCircuit<G1Affine, Bn256>>::NAME,
+                            vk_hash_hex(&vk)
+                        );
+                    )*
                 }
 
                 pub fn run(&self) {
+                    let _span = info_span!("command", command = %self.args.command).entered();
+
                     if self.args.command == "sample_setup" {
                         self.dispatch_sample_setup();
                     }
@@ -201,15 +723,95 @@ macro_rules! zkaggregate {
                         self.dispatch_verify_run();
                     }
 
+                    if self.args.command == "orchestrate" {
+                        self.dispatch_orchestrate();
+                    }
+
+                    if self.args.command == "serve" {
+                        self.dispatch_serve();
+                    }
+
                     if self.args.command == "verify_check" {
                         self.dispatch_verify_check();
                     }
 
+                    if self.args.command == "verify_malformed" {
+                        self.dispatch_verify_malformed();
+                    }
+
                     if self.args.command == "verify_solidity" {
                         self.dispatch_verify_solidity();
                     }
+
+                    if self.args.command == "verify_snarkjs" {
+                        self.dispatch_verify_snarkjs();
+                    }
+
+                    if self.args.command == "verify_yul" {
+                        self.dispatch_verify_yul();
+                    }
+
+                    if self.args.command == "verify_vyper" {
+                        self.dispatch_verify_vyper();
+                    }
+
+                    if self.args.command == "verify_move" {
+                        self.dispatch_verify_move();
+                    }
+
+                    if self.args.command == "verify_cosmwasm" {
+                        self.dispatch_verify_cosmwasm();
+                    }
+
+                    if self.args.command == "verify_near" {
+                        self.dispatch_verify_near();
+                    }
+
+                    if self.args.command == "verify_solana" {
+                        self.dispatch_verify_solana();
+                    }
+
+                    if self.args.command == "verify_hardhat" {
+                        self.dispatch_verify_hardhat();
+                    }
+
+                    if self.args.command == "explain_proof" {
+                        self.dispatch_explain_proof();
+                    }
+
+                    if self.args.command == "vk_hash" {
+                        self.dispatch_vk_hash();
+                    }
+
+                    halo2_snark_aggregator_sdk::__dev_graph! {
+                        if self.args.command == "verify_layout" {
+                            self.dispatch_verify_layout();
+                        }
+                    }
                 }
             }
         }
     };
+
+    // `zkaggregate! { CircuitA: 4, CircuitB: 1 }`, the proof count of every circuit given at the
+    // aggregation site.
+    ( $( $x:ident : $np:expr ),+ $(,)? ) => {
+        $crate::zkaggregate! {
+            @impl 0 $( + $crate::zkaggregate!(@one $x) )+, vec![], $( $x: $np ),+
+        }
+    };
+
+    // Same with the coherent constraints after a `;`.
+    ( $( $x:ident : $np:expr ),+ ; $coherent:expr ) => {
+        $crate::zkaggregate! {
+            @impl 0 $( + $crate::zkaggregate!(@one $x) )+, $coherent, $( $x: $np ),+
+        }
+    };
+
+    // `zkaggregate! { n, coherent, CircuitA, CircuitB }`, the proof counts read from N_PROOFS.
+    ( $n:expr, $coherent:expr, $( $x:ident ),+ ) => {
+        $crate::zkaggregate! {
+            @impl $n, $coherent, $( $x: <$x as TargetCircuit<G1Affine, Bn256>>::N_PROOFS ),+
+        }
+    };
 }
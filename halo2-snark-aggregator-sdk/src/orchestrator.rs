@@ -0,0 +1,244 @@
+// Sharding of the `orchestrate` command: the target proofs of a folder are split into
+// aggregations of N_PROOFS proofs per circuit, each run as a `verify_run` child process
+// in its own shard folder, so that the memory of a proof is released with its process.
+use halo2_snark_aggregator_circuit::signing::SIGNATURE_EXTENSION;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+// A target circuit of the aggregation, its TargetCircuit constants.
+#[derive(Clone, Copy, Debug)]
+pub struct ShardedCircuit {
+    pub name: &'static str,
+    pub params_name: &'static str,
+    pub n_proofs: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shard {
+    pub index: usize,
+    pub folder: PathBuf,
+}
+
+// Hard links the file when possible, the params are large.
+fn link_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if to.exists() {
+        std::fs::remove_file(to)?;
+    }
+    std::fs::hard_link(from, to).or_else(|_| std::fs::copy(from, to).map(|_| ()))
+}
+
+// Links the file with its signature, if it is signed.
+fn link(from: &Path, to: &Path) -> std::io::Result<()> {
+    link_file(from, to)?;
+    let signature = |path: &Path| {
+        let mut path = path.as_os_str().to_owned();
+        path.push(".");
+        path.push(SIGNATURE_EXTENSION);
+        PathBuf::from(path)
+    };
+    if signature(from).exists() {
+        link_file(&signature(from), &signature(to))?;
+    }
+    Ok(())
+}
+
+// The envelope of a proof is optional, it is linked along with it when there is one.
+fn proof_envelope(circuit: &ShardedCircuit, index: usize) -> String {
+    format!("sample_circuit_proof_{}{}.envelope", circuit.name, index)
+}
+
+fn proof_files(circuit: &ShardedCircuit, index: usize) -> [String; 2] {
+    [
+        format!("sample_circuit_instance_{}{}.data", circuit.name, index),
+        format!("sample_circuit_proof_{}{}.data", circuit.name, index),
+    ]
+}
+
+// Number of consecutive proofs of the circuit in the folder, from index 0.
+fn count_proofs(folder: &Path, circuit: &ShardedCircuit) -> usize {
+    (0..)
+        .take_while(|i| {
+            proof_files(circuit, *i)
+                .iter()
+                .all(|f| folder.join(f).exists())
+        })
+        .count()
+}
+
+// Shard folders under folder/shards, each with the params and vkeys of the folder and
+// the proofs of its aggregation renumbered from 0. Proofs left over by the last full
+// shard are not aggregated.
+pub fn prepare_shards(folder: &Path, circuits: &[ShardedCircuit]) -> std::io::Result<Vec<Shard>> {
+    let n_shards = circuits
+        .iter()
+        .map(|circuit| count_proofs(folder, circuit) / circuit.n_proofs)
+        .min()
+        .unwrap_or(0);
+
+    (0..n_shards)
+        .map(|index| {
+            let shard = folder.join("shards").join(index.to_string());
+            std::fs::create_dir_all(&shard)?;
+            for file in ["verify_circuit.params", "verify_circuit.vkey"] {
+                link(&folder.join(file), &shard.join(file))?;
+            }
+            for circuit in circuits {
+                for ext in ["params", "vkey"] {
+                    let file = format!("sample_circuit_{}.{}", circuit.params_name, ext);
+                    link(&folder.join(&file), &shard.join(&file))?;
+                }
+                for i in 0..circuit.n_proofs {
+                    let from = proof_files(circuit, index * circuit.n_proofs + i);
+                    for (from, to) in from.iter().zip(proof_files(circuit, i).iter()) {
+                        link(&folder.join(from), &shard.join(to))?;
+                    }
+                    let envelope =
+                        folder.join(proof_envelope(circuit, index * circuit.n_proofs + i));
+                    if envelope.exists() {
+                        link(&envelope, &shard.join(proof_envelope(circuit, i)))?;
+                    }
+                }
+            }
+            Ok(Shard {
+                index,
+                folder: shard,
+            })
+        })
+        .collect()
+}
+
+fn run_shard(
+    shard: &Shard,
+    retries: usize,
+    command: &impl Fn(&Shard) -> Command,
+) -> Result<(), String> {
+    let mut error = String::new();
+    for attempt in 0..=retries {
+        match command(shard).status() {
+            Ok(status) if status.success() => return Ok(()),
+            Ok(status) => error = format!("{}", status),
+            Err(e) => error = e.to_string(),
+        }
+        tracing::warn!(
+            "shard {} attempt {} failed: {}",
+            shard.index,
+            attempt,
+            error
+        );
+    }
+    Err(error)
+}
+
+// Runs the command of each shard in up to jobs processes at a time, a failed shard is
+// run again up to retries times. Returns the outcome of each shard, in order.
+pub fn run_shards(
+    shards: &[Shard],
+    jobs: usize,
+    retries: usize,
+    command: impl Fn(&Shard) -> Command + Sync,
+) -> Vec<Result<(), String>> {
+    let queue = Mutex::new(shards.iter().collect::<VecDeque<_>>());
+    let results = Mutex::new(vec![Ok(()); shards.len()]);
+
+    // a scope on a pool of its own: the global pool may have fewer threads than jobs, and
+    // std::thread::scope is not in the pinned toolchain
+    let jobs = jobs.max(1);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .unwrap();
+    pool.scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|_| loop {
+                let shard = match queue.lock().unwrap().pop_front() {
+                    Some(shard) => shard,
+                    None => break,
+                };
+                let result = run_shard(shard, retries, &command);
+                results.lock().unwrap()[shard.index] = result;
+            });
+        }
+    });
+    results.into_inner().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_folder(name: &str) -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&folder);
+        std::fs::create_dir_all(&folder).unwrap();
+        folder
+    }
+
+    #[test]
+    fn test_prepare_shards() {
+        let folder = temp_folder("orchestrator_prepare");
+        let circuit = ShardedCircuit {
+            name: "c",
+            params_name: "p",
+            n_proofs: 2,
+        };
+        let files = ["verify_circuit.params", "verify_circuit.vkey"]
+            .iter()
+            .map(|f| f.to_string())
+            .chain([
+                "sample_circuit_p.params".into(),
+                "sample_circuit_p.vkey".into(),
+            ])
+            .chain((0..5).flat_map(|i| proof_files(&circuit, i)))
+            .collect::<Vec<_>>();
+        for file in files.iter() {
+            std::fs::write(folder.join(file), file).unwrap();
+        }
+        std::fs::write(folder.join(proof_envelope(&circuit, 3)), "envelope").unwrap();
+
+        // 5 proofs make 2 shards of 2
+        let shards = prepare_shards(&folder, &[circuit]).unwrap();
+        assert_eq!(shards.len(), 2);
+        let proof = std::fs::read_to_string(shards[1].folder.join("sample_circuit_proof_c1.data"));
+        assert_eq!(proof.unwrap(), "sample_circuit_proof_c3.data");
+        assert!(shards[1].folder.join("verify_circuit.params").exists());
+        assert!(shards[1].folder.join(proof_envelope(&circuit, 1)).exists());
+        assert!(!shards[0].folder.join(proof_envelope(&circuit, 1)).exists());
+        assert!(!shards[1]
+            .folder
+            .join("sample_circuit_proof_c2.data")
+            .exists());
+
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn test_run_shards() {
+        let folder = temp_folder("orchestrator_run");
+        let shards = (0..3)
+            .map(|index| Shard {
+                index,
+                folder: folder.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        // shard 1 fails once, then succeeds on its retry; shard 2 always fails
+        let results = run_shards(&shards, 2, 1, |shard| {
+            let mut command = Command::new("sh");
+            let marker = folder.join("retried");
+            let script = match shard.index {
+                1 => format!("test -e {0} || {{ touch {0}; exit 1; }}", marker.display()),
+                2 => "exit 3".to_owned(),
+                _ => "true".to_owned(),
+            };
+            command.arg("-c").arg(script);
+            command
+        });
+        assert_eq!(results[0], Ok(()));
+        assert_eq!(results[1], Ok(()));
+        assert!(results[2].is_err());
+
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+}
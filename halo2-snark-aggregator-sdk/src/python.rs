@@ -0,0 +1,196 @@
+// Python module of the aggregator for the target circuits given, to be expanded in a
+// cdylib built with maturin. It runs the steps of the cli on the files of a folder,
+// so that batch aggregation and monitoring can be scripted from notebooks.
+#[macro_export]
+macro_rules! zkaggregate_py {
+    (@impl $n:expr, $coherent:expr, $( $x:ident : $np:expr ),+ ) => {
+        mod zkpy {
+            $(
+                use crate::$x;
+            )*
+            use halo2_snark_aggregator_circuit::envelope::ProofEnvelope;
+            use halo2_snark_aggregator_circuit::fs::*;
+            use halo2_snark_aggregator_circuit::sample_circuit::TargetCircuit;
+            use halo2_snark_aggregator_circuit::verify_circuit::{
+                CreateProof, MultiCircuitsCreateProof, MultiCircuitsSetup, Setup, VerifyCheck,
+            };
+            use halo2_snark_aggregator_circuit::vk::VK_HASH_INSTANCES;
+            use halo2_snark_aggregator_solidity::encode_calldata;
+            use pairing_bn256::bn256::{Bn256, G1Affine};
+            use pyo3::prelude::*;
+            use pyo3::types::PyBytes;
+            use std::path::PathBuf;
+            use std::rc::Rc;
+
+            // Aggregation artifacts of a folder, with the file names of the cli. Panics of
+            // the aggregator, as a missing file, are raised as PanicException.
+            #[pyclass(unsendable)]
+            pub struct Aggregator {
+                folder: PathBuf,
+                verify_circuit_k: u32,
+            }
+
+            impl Aggregator {
+                fn read(&self, filename: &str, bytes: Option<&[u8]>) -> Vec<u8> {
+                    match bytes {
+                        Some(bytes) => bytes.to_vec(),
+                        None => read_file(&mut self.folder.clone(), filename),
+                    }
+                }
+            }
+
+            #[pymethods]
+            impl Aggregator {
+                #[new]
+                fn new(folder: PathBuf, verify_circuit_k: u32) -> Self {
+                    Aggregator {
+                        folder,
+                        verify_circuit_k,
+                    }
+                }
+
+                // Names of the target circuits, in the order of the aggregation.
+                #[staticmethod]
+                fn target_circuits() -> Vec<&'static str> {
+                    vec![$(<$x as TargetCircuit<G1Affine, Bn256>>::NAME,)*]
+                }
+
+                #[getter]
+                fn verify_public_inputs_size(&self) -> usize {
+                    4
+                    $(
+                        + $np * <$x as TargetCircuit<G1Affine, Bn256>>::PUBLIC_INPUT_SIZE
+                        + VK_HASH_INSTANCES
+                    )*
+                }
+
+                // verify_setup, writes verify_circuit.params and verify_circuit.vkey.
+                fn setup(&self) {
+                    let setups: [Setup<_, _>; $n] = [
+                        $(
+                            Setup::with_nproofs::<$x, _>(
+                                &self.folder,
+                                &<$x as TargetCircuit<G1Affine, Bn256>>::load_instances,
+                                $np,
+                            ),
+                        )*
+                    ];
+                    let request = MultiCircuitsSetup::<_, _, $n> {
+                        setups,
+                        coherent: $coherent,
+                    };
+                    let (params, vk) = request.call(self.verify_circuit_k);
+
+                    write_verify_circuit_params(&mut self.folder.clone(), &params);
+                    write_verify_circuit_vk(&mut self.folder.clone(), &vk);
+                }
+
+                // verify_run, aggregates the target proofs of the folder and writes the
+                // outputs of the cli. Returns them as (proof, instances, final_pair) bytes.
+                fn aggregate(&self, py: Python) -> (PyObject, PyObject, PyObject) {
+                    let target_circuit_proofs: [CreateProof<_, _>; $n] = [
+                        $(
+                            CreateProof::with_nproofs::<$x, _>(
+                                &self.folder,
+                                &<$x as TargetCircuit<G1Affine, Bn256>>::load_instances,
+                                $np,
+                            ),
+                        )*
+                    ];
+                    let request = MultiCircuitsCreateProof::<_, _, $n> {
+                        target_circuit_proofs,
+                        verify_circuit_params: &load_verify_circuit_params(
+                            &mut self.folder.clone(),
+                        ),
+                        verify_circuit_vk: load_verify_circuit_vk(&mut self.folder.clone()),
+                        coherent: $coherent,
+                        transcript_label: load_transcript_label(&mut self.folder.clone()),
+                    };
+                    let mut cache = load_instance_commitment_cache(&mut self.folder.clone());
+                    let cached = cache.len();
+                    let (pk, final_pair, instances, proof) = request.call_with_cache(&mut cache);
+                    if cache.len() != cached {
+                        write_instance_commitment_cache(&mut self.folder.clone(), &cache);
+                    }
+
+                    write_verify_circuit_instance(&mut self.folder.clone(), &instances);
+                    write_verify_circuit_proof(&mut self.folder.clone(), &proof);
+                    write_verify_circuit_proof_envelope(
+                        &mut self.folder.clone(),
+                        &ProofEnvelope::verify_circuit(pk.get_vk(), instances.len(), proof.clone()),
+                    );
+                    write_verify_circuit_final_pair(&mut self.folder.clone(), &final_pair);
+
+                    let bytes = |buf: Vec<u8>| PyBytes::new(py, &buf).into();
+                    (
+                        bytes(proof),
+                        bytes(read_verify_circuit_instance(&mut self.folder.clone())),
+                        bytes(load_verify_circuit_final_pair(&mut self.folder.clone())),
+                    )
+                }
+
+                // verify_check of the instances and proof, those of the folder if not given.
+                #[args(instances = "None", proof = "None")]
+                fn verify(&self, instances: Option<&[u8]>, proof: Option<&[u8]>) -> bool {
+                    let verify_instance = load_instances::<Bn256>(
+                        &self.read("verify_circuit_instance.data", instances),
+                    );
+                    let check = VerifyCheck::<G1Affine> {
+                        verify_params: Rc::new(load_verify_circuit_params(
+                            &mut self.folder.clone(),
+                        )),
+                        verify_vk: Rc::new(load_verify_circuit_vk(&mut self.folder.clone())),
+                        verify_instance,
+                        verify_public_inputs_size: self.verify_public_inputs_size(),
+                        verify_proof: self.read("verify_circuit_proof.data", proof),
+                        transcript_label: load_transcript_label(&mut self.folder.clone()),
+                    };
+                    check.call::<Bn256>().is_ok()
+                }
+
+                // abi encoded verify(uint256[], uint256[]) of the verifier contract, for the
+                // proof and final pair given or those of the folder.
+                #[args(proof = "None", final_pair = "None")]
+                fn calldata(
+                    &self,
+                    py: Python,
+                    proof: Option<&[u8]>,
+                    final_pair: Option<&[u8]>,
+                ) -> PyObject {
+                    let calldata = encode_calldata(
+                        &self.read("verify_circuit_proof.data", proof),
+                        &self.read("verify_circuit_final_pair.data", final_pair),
+                    );
+                    PyBytes::new(py, &calldata).into()
+                }
+            }
+
+            #[pymodule]
+            fn halo2_snark_aggregator(_py: Python, m: &PyModule) -> PyResult<()> {
+                m.add_class::<Aggregator>()?;
+                Ok(())
+            }
+        }
+    };
+
+    // `zkaggregate_py! { CircuitA: 4, CircuitB: 1 }`, the proof counts of zkaggregate!.
+    ( $( $x:ident : $np:expr ),+ $(,)? ) => {
+        $crate::zkaggregate_py! {
+            @impl 0 $( + $crate::zkaggregate!(@one $x) )+, vec![], $( $x: $np ),+
+        }
+    };
+
+    // Same with the coherent constraints after a `;`.
+    ( $( $x:ident : $np:expr ),+ ; $coherent:expr ) => {
+        $crate::zkaggregate_py! {
+            @impl 0 $( + $crate::zkaggregate!(@one $x) )+, $coherent, $( $x: $np ),+
+        }
+    };
+
+    // `zkaggregate_py! { n, coherent, CircuitA, CircuitB }`, the proof counts read from N_PROOFS.
+    ( $n:expr, $coherent:expr, $( $x:ident ),+ ) => {
+        $crate::zkaggregate_py! {
+            @impl $n, $coherent, $( $x: <$x as TargetCircuit<G1Affine, Bn256>>::N_PROOFS ),+
+        }
+    };
+}
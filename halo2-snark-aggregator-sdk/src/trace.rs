@@ -0,0 +1,31 @@
+// Tracing subscriber of the cli. Events go to stderr, filtered by RUST_LOG as with
+// env_logger, and with --trace-output the spans are also written as a Chrome trace,
+// to open in chrome://tracing or perfetto, or to fold into a flamegraph.
+use std::path::Path;
+use tracing_chrome::ChromeLayerBuilder;
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+pub use tracing_chrome::FlushGuard;
+
+// The trace is completed when the guard is dropped, it must outlive the command.
+pub fn init(trace_output: Option<&Path>) -> Option<FlushGuard> {
+    let (chrome, guard) = match trace_output {
+        Some(path) => {
+            let (layer, guard) = ChromeLayerBuilder::new()
+                .file(path)
+                .include_args(true)
+                .build();
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+    tracing_subscriber::registry()
+        .with(
+            fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_filter(EnvFilter::from_default_env()),
+        )
+        .with(chrome)
+        .init();
+    guard
+}
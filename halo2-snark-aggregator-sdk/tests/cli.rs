@@ -0,0 +1,109 @@
+// The cli of simple-example end to end in a temp folder, so a regression of the dispatcher
+// zkaggregate! generates or of the fs paths fails `cargo test`. The sample commands run on the
+// k 7 sample circuit; the chain through the verify circuit sets up a k 22 one and is ignored
+// by default.
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::Path;
+use tempfile::TempDir;
+
+// SimpleCircuit of simple-example, N_PROOFS = 2.
+const NAME: &str = "simple_example";
+const N_PROOFS: usize = 2;
+
+fn cli(folder: &Path, command: &str) -> Command {
+    let mut cmd = Command::new(env!("CARGO"));
+    cmd.current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["run", "--release", "--example", "simple-example", "--"])
+        .args(["--command", command, "--folder-path"])
+        .arg(folder);
+    cmd
+}
+
+fn assert_files(folder: &Path, files: &[String]) {
+    for file in files {
+        assert!(folder.join(file).exists(), "{} missing", file);
+    }
+}
+
+fn sample_files() -> Vec<String> {
+    let mut files = vec![
+        format!("sample_circuit_{}.params", NAME),
+        format!("sample_circuit_{}.vkey", NAME),
+    ];
+    for i in 0..N_PROOFS {
+        files.push(format!("sample_circuit_proof_{}{}.data", NAME, i));
+        files.push(format!("sample_circuit_proof_{}{}.envelope", NAME, i));
+        files.push(format!("sample_circuit_instance_{}{}.data", NAME, i));
+    }
+    files
+}
+
+#[test]
+fn test_cli_sample_commands() {
+    let folder = TempDir::new().unwrap();
+
+    cli(folder.path(), "sample_setup").assert().success();
+    cli(folder.path(), "sample_run").assert().success();
+    assert_files(folder.path(), &sample_files());
+}
+
+#[test]
+fn test_cli_fails_on_an_empty_folder() {
+    let folder = TempDir::new().unwrap();
+
+    // no params nor vkey to prove with
+    cli(folder.path(), "sample_run").assert().failure();
+    cli(folder.path(), "verify_check").assert().failure();
+}
+
+// Run it with `cargo test --release --test cli -- --ignored`.
+#[test]
+#[ignore]
+fn test_cli_end_to_end() {
+    let folder = TempDir::new().unwrap();
+    let path = folder.path();
+
+    for command in ["sample_setup", "sample_run", "verify_setup", "verify_run"] {
+        cli(path, command).assert().success();
+    }
+    assert_files(
+        path,
+        &[
+            "verify_circuit.params",
+            "verify_circuit.vkey",
+            "verify_circuit_instance.data",
+            "verify_circuit_proof.data",
+            "verify_circuit_final_pair.data",
+        ]
+        .map(String::from),
+    );
+
+    cli(path, "verify_check").assert().success();
+
+    // the dump of the verify circuit vk and of each target circuit vk
+    cli(path, "vk_hash")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("verify_circuit "))
+        .stdout(predicate::str::contains(format!("{} ", NAME)));
+
+    let templates =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../halo2-snark-aggregator-solidity/templates");
+    cli(path, "verify_solidity")
+        .arg("--template-path")
+        .arg(&templates)
+        .assert()
+        .success();
+    assert_files(
+        path,
+        &["verifier.sol", "gas_report.txt", "encoder.ts"].map(String::from),
+    );
+
+    // a tampered proof fails the check
+    let proof = path.join("verify_circuit_proof.data");
+    let mut bytes = std::fs::read(&proof).unwrap();
+    bytes[0] ^= 1;
+    std::fs::write(&proof, bytes).unwrap();
+    cli(path, "verify_check").assert().failure();
+}
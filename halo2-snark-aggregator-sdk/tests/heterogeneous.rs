@@ -0,0 +1,98 @@
+// The heterogeneous example end to end: the circuits under the mock prover, and the cli
+// through setup, proving and verification of both circuits in one verify circuit.
+#[path = "../examples/heterogeneous/circuits.rs"]
+mod circuits;
+
+use circuits::*;
+use halo2_proofs::dev::MockProver;
+use halo2_proofs::plonk::{keygen_vk, Circuit, ConstraintSystem};
+use halo2_proofs::poly::commitment::Params;
+use halo2_snark_aggregator_circuit::sample_circuit::TargetCircuit;
+use halo2_snark_aggregator_circuit::vk::VK_HASH_INSTANCES;
+use pairing_bn256::bn256::{Bn256, Fr, G1Affine};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn mock_prove<T: TargetCircuit<G1Affine, Bn256>>() {
+    let (circuit, instances) = T::instance_builder();
+    let prover = MockProver::run(T::TARGET_CIRCUIT_K, &circuit, instances.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // a wrong instance is caught
+    let mut instances = instances;
+    instances[0][0] += Fr::from(1);
+    let prover = MockProver::run(T::TARGET_CIRCUIT_K, &circuit, instances).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_lookup_circuit() {
+    mock_prove::<CircuitA>();
+}
+
+#[test]
+fn test_permutation_circuit() {
+    mock_prove::<CircuitB>();
+}
+
+#[test]
+fn test_circuits_have_different_shapes() {
+    let mut lookup = ConstraintSystem::<Fr>::default();
+    LookupCircuit::configure(&mut lookup);
+    let mut permutation = ConstraintSystem::<Fr>::default();
+    PermutationCircuit::configure(&mut permutation);
+
+    assert_eq!(lookup.lookups.len(), 4);
+    assert!(permutation.lookups.is_empty());
+    // one permutation product per column with the degree 3 gate of the accumulator
+    assert!(permutation.permutation.columns.len() > 2 * lookup.permutation.columns.len());
+
+    // the vks of the two circuits differ, so the verify circuit holds two sub-verifiers
+    let vk_a = keygen_vk(
+        &Params::<G1Affine>::unsafe_setup::<Bn256>(CircuitA::TARGET_CIRCUIT_K),
+        &LookupCircuit::default(),
+    )
+    .unwrap();
+    let vk_b = keygen_vk(
+        &Params::<G1Affine>::unsafe_setup::<Bn256>(CircuitB::TARGET_CIRCUIT_K),
+        &PermutationCircuit::default(),
+    )
+    .unwrap();
+    assert_ne!(vk_a.cs.num_advice_columns, vk_b.cs.num_advice_columns);
+}
+
+fn run_example(folder: &Path, command: &str) {
+    let status = Command::new(env!("CARGO"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["run", "--release", "--example", "heterogeneous", "--"])
+        .args(["--command", command, "--folder-path"])
+        .arg(folder)
+        .status()
+        .unwrap();
+    assert!(status.success(), "{} failed", command);
+}
+
+// Generates the params of a k 22 verify circuit, run it with
+// `cargo test --release --test heterogeneous -- --ignored`.
+#[test]
+#[ignore]
+fn test_heterogeneous_aggregation() {
+    let folder = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("heterogeneous");
+    std::fs::create_dir_all(&folder).unwrap();
+
+    for command in [
+        "sample_setup",
+        "sample_run",
+        "verify_setup",
+        "verify_run",
+        "verify_check",
+    ] {
+        run_example(&folder, command);
+    }
+    // the accumulator limbs, the instances of both circuits, then their vk hashes
+    let instances = std::fs::read(folder.join("verify_circuit_instance.data")).unwrap();
+    assert_eq!(
+        instances.len(),
+        32 * (4 + LOOKUP_VALUES + 2 + 2 * VK_HASH_INSTANCES)
+    );
+}
@@ -1,3 +1,17 @@
 pub(crate) mod aggregate;
+pub(crate) mod audit;
+pub(crate) mod calldata;
+pub(crate) mod const_fold;
 pub(crate) mod ctx;
+pub(crate) mod gas;
+pub(crate) mod huff;
 pub(crate) mod linear_scan;
+pub(crate) mod move_lang;
+pub(crate) mod msm_loop;
+pub(crate) mod pairing;
+pub(crate) mod proof_parsing;
+pub(crate) mod spill;
+pub(crate) mod split;
+pub(crate) mod ssa;
+pub(crate) mod vk_table;
+pub(crate) mod vyper;
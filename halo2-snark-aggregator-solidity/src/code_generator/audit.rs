@@ -0,0 +1,286 @@
+use super::ctx::{CodeGeneratorCtx, Expression, G2Point, Statement, Type};
+use super::pairing::{collect_pairs, PairingG1};
+use crate::TranscriptHash;
+use num_bigint::BigUint;
+use std::fmt::Write;
+use std::rc::Rc;
+
+// Part of the halo2 verifier a statement of get_wx_wg implements. The equation is
+// told from the shape of the statement, it locates the code of each equation for
+// a reviewer rather than proving the code implements it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Equation {
+    Absorb,
+    Challenge,
+    Lagrange,
+    Evaluation,
+    Multiopen,
+}
+
+impl Equation {
+    fn title(&self) -> &'static str {
+        match self {
+            Equation::Absorb => "transcript absorption",
+            Equation::Challenge => "challenge derivation",
+            Equation::Lagrange => "lagrange evaluation",
+            Equation::Evaluation => "expression evaluation",
+            Equation::Multiopen => "multiopen accumulation",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Equation::Absorb => {
+                "Commitments and evaluations of the proof written to the absorbing buffer, \
+                 in the order the halo2 verifier reads them from the transcript."
+            }
+            Equation::Challenge => {
+                "Fiat-Shamir challenge squeezed from the hash of the absorbing buffer, \
+                 in the order the halo2 verifier squeezes them (theta, beta, gamma, y, x, \
+                 then the multiopen challenges)."
+            }
+            Equation::Lagrange => {
+                "Powers and inverses of the evaluation point: x^n, the vanishing \
+                 polynomial x^n - 1 and the lagrange evaluations \
+                 l_i(x) = omega^i (x^n - 1) / (n (x - omega^i))."
+            }
+            Equation::Evaluation => {
+                "Field arithmetic over the evaluations: custom gates, permutation and \
+                 lookup constraints folded with y, checked against the quotient, and the \
+                 scalar coefficients of the multiopen argument."
+            }
+            Equation::Multiopen => {
+                "Multi-scalar multiplications accumulating the commitments into w_x and \
+                 w_g, the verify circuit points of the final pairing."
+            }
+        }
+    }
+}
+
+fn children(e: &Expression) -> Vec<&Rc<Expression>> {
+    match e {
+        Expression::Add(l, r, _)
+        | Expression::Sub(l, r, _)
+        | Expression::Mul(l, r, _)
+        | Expression::Div(l, r, _) => vec![l, r],
+        Expression::MulAdd(a, b, c, _) => vec![a, b, c],
+        Expression::Pow(base, _, _) => vec![base],
+        Expression::MulAddPM(target, _, _) => vec![target],
+        Expression::MulAddTable(target, entries) => {
+            let mut c = vec![target];
+            c.extend(entries.iter().map(|(p, _)| p));
+            c
+        }
+        _ => vec![],
+    }
+}
+
+fn any(e: &Expression, f: &impl Fn(&Expression) -> bool) -> bool {
+    f(e) || children(e).into_iter().any(|c| any(c, f))
+}
+
+fn collect_constants(e: &Expression, constants: &mut Vec<(String, String)>) {
+    let constant = match e {
+        Expression::Scalar(s) => Some(("scalar", s.to_string())),
+        Expression::Point(x, y) => Some(("point", format!("({}, {})", x, y))),
+        _ => None,
+    };
+    if let Some((kind, value)) = constant {
+        if !constants.iter().any(|(_, v)| *v == value) {
+            constants.push((kind.to_owned(), value));
+        }
+    }
+    for c in children(e) {
+        collect_constants(c, constants);
+    }
+}
+
+fn classify(s: &Statement) -> Equation {
+    match s {
+        Statement::UpdateHash(..) | Statement::For { .. } => Equation::Absorb,
+        Statement::ForMMMMul { .. } => Equation::Evaluation,
+        Statement::Assign(l, r, _) => {
+            if matches!(r, Expression::Hash(_)) {
+                Equation::Challenge
+            } else if l.get_type() == Type::Point {
+                Equation::Multiopen
+            } else if any(r, &|e| {
+                matches!(e, Expression::Pow(..) | Expression::Div(..))
+            }) {
+                Equation::Lagrange
+            } else {
+                Equation::Evaluation
+            }
+        }
+    }
+}
+
+fn g2_string(p: &G2Point) -> String {
+    format!("(({}, {}), ({}, {}))", p.x.0, p.x.1, p.y.0, p.y.1)
+}
+
+// Markdown report of the solidity verifier generated from args, listing its
+// constants and its code grouped by the verifier equation each block implements.
+pub(crate) fn audit_report(args: &CodeGeneratorCtx, transcript_hash: TranscriptHash) -> String {
+    let mut report = String::new();
+    let hash = match transcript_hash {
+        TranscriptHash::Sha256 => "sha256",
+        TranscriptHash::Keccak256 => "keccak256",
+    };
+
+    writeln!(report, "# Verifier audit report\n").unwrap();
+    writeln!(
+        report,
+        "Generated with the verifier, it maps the code of `get_wx_wg` and `verify` to \
+         the halo2 verifier equations. The transcript hash is `{}`, memory holds {} words \
+         and the final pair array {} words.\n",
+        hash, args.memory_size, args.instance_size
+    )
+    .unwrap();
+
+    writeln!(report, "## Constants\n").unwrap();
+    writeln!(report, "| constant | kind | value |").unwrap();
+    writeln!(report, "| --- | --- | --- |").unwrap();
+    for (name, point) in [
+        ("verify circuit s_g2", &args.verify_circuit_s_g2),
+        ("verify circuit -g2", &args.verify_circuit_n_g2),
+        ("target circuit s_g2", &args.target_circuit_s_g2),
+        ("target circuit -g2", &args.target_circuit_n_g2),
+    ] {
+        writeln!(report, "| {} | g2 point | {} |", name, g2_string(point)).unwrap();
+    }
+    let mut constants = vec![];
+    for s in args.assignments.iter() {
+        match s {
+            Statement::Assign(_, r, _) => collect_constants(r, &mut constants),
+            Statement::UpdateHash(e, _) => collect_constants(e, &mut constants),
+            _ => {}
+        }
+    }
+    collect_constants(&args.wx, &mut constants);
+    collect_constants(&args.wg, &mut constants);
+    for (i, (kind, value)) in constants.iter().enumerate() {
+        writeln!(report, "| c{} | {} | {} |", i, kind, value).unwrap();
+    }
+
+    writeln!(report, "\n## get_wx_wg\n").unwrap();
+    let mut opcodes = vec![];
+    let mut incremental_ident = 0u64;
+    let mut challenges = 0;
+    let mut i = 0;
+    while i < args.assignments.len() {
+        let equation = classify(&args.assignments[i]);
+        let mut end = i + 1;
+        while end < args.assignments.len() && classify(&args.assignments[end]) == equation {
+            end += 1;
+        }
+
+        let title = if equation == Equation::Challenge {
+            challenges += end - i;
+            format!("{} {}", equation.title(), challenges)
+        } else {
+            equation.title().to_owned()
+        };
+        writeln!(report, "### Statements {}..{}: {}\n", i, end, title).unwrap();
+        writeln!(report, "{}\n", equation.description()).unwrap();
+        writeln!(report, "```solidity").unwrap();
+        for s in args.assignments[i..end].iter() {
+            for line in s.to_solidity_string(&mut opcodes, &mut incremental_ident) {
+                writeln!(report, "{}", line).unwrap();
+            }
+        }
+        for line in Statement::opcodes_to_solidity_string(&mut opcodes) {
+            writeln!(report, "{}", line).unwrap();
+        }
+        opcodes.clear();
+        writeln!(report, "```\n").unwrap();
+        i = end;
+    }
+
+    writeln!(report, "## Pairing\n").unwrap();
+    writeln!(
+        report,
+        "`verify` checks e(w_x, s_g2) e(w_g, -g2) == 1 for the verify circuit and the same \
+         on the final pair for the target circuit, in a single call to the pairing \
+         precompile. The final pair terms are multiplied by r, the keccak of the \
+         unscaled precompile input.\n"
+    )
+    .unwrap();
+    writeln!(report, "| term | g1 | g2 | scaled by r |").unwrap();
+    writeln!(report, "| --- | --- | --- | --- |").unwrap();
+    for (i, term) in collect_pairs(args).iter().enumerate() {
+        let g1 = match term.g1 {
+            PairingG1::Wx => "w_x".to_owned(),
+            PairingG1::Wg => "w_g".to_owned(),
+            PairingG1::FinalPair(w) => format!("final pair words {}, {}", w, w + 1),
+        };
+        writeln!(
+            report,
+            "| {} | {} | {} | {} |",
+            i,
+            g1,
+            g2_string(term.g2),
+            if term.scaled { "yes" } else { "no" }
+        )
+        .unwrap();
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn g2() -> G2Point {
+        G2Point {
+            x: (BigUint::from(1u64), BigUint::from(2u64)),
+            y: (BigUint::from(3u64), BigUint::from(4u64)),
+        }
+    }
+
+    #[test]
+    fn test_audit_report() {
+        let m = |idx, t| Rc::new(Expression::Memory(idx, t));
+        let proof = Rc::new(Expression::TransciprtOffset(0, Type::Scalar));
+        let args = CodeGeneratorCtx {
+            wx: Expression::Memory(3, Type::Point),
+            wg: Expression::Memory(3, Type::Point),
+            target_circuit_s_g2: g2(),
+            target_circuit_n_g2: g2(),
+            verify_circuit_s_g2: g2(),
+            verify_circuit_n_g2: g2(),
+            assignments: vec![
+                Statement::UpdateHash(proof.clone(), 0),
+                Statement::Assign(m(0, Type::Scalar), Expression::Hash(0), vec![]),
+                Statement::Assign(
+                    m(1, Type::Scalar),
+                    Expression::Pow(m(0, Type::Scalar), 4, Type::Scalar),
+                    vec![],
+                ),
+                Statement::Assign(
+                    m(2, Type::Scalar),
+                    Expression::Mul(m(1, Type::Scalar), proof, Type::Scalar),
+                    vec![],
+                ),
+                Statement::Assign(
+                    m(3, Type::Point),
+                    Expression::Point(BigUint::from(1u64), BigUint::from(2u64)),
+                    vec![],
+                ),
+            ],
+            memory_size: 5,
+            instance_size: 4,
+            absorbing_length: 1,
+        };
+
+        let report = audit_report(&args, TranscriptHash::Sha256);
+        assert!(report.contains("### Statements 0..1: transcript absorption"));
+        assert!(report.contains("### Statements 1..2: challenge derivation 1"));
+        assert!(report.contains("### Statements 2..3: lagrange evaluation"));
+        assert!(report.contains("### Statements 3..4: expression evaluation"));
+        assert!(report.contains("### Statements 4..5: multiopen accumulation"));
+        assert!(report.contains("| c0 | point | (1, 2) |"));
+        assert!(report.contains("| 3 | final pair words 2, 3 |"));
+    }
+}
@@ -0,0 +1,186 @@
+use super::ctx::{CodeGeneratorCtx, Expression, Statement, Type};
+use super::gas::opcode_len;
+use num_bigint::BigUint;
+use std::collections::BTreeSet;
+
+// Flag of a compressed identity point, x is below 2^254 otherwise.
+const IDENTITY_FLAG_BIT: u64 = 254;
+// Parity of y in a compressed point.
+const Y_ODD_FLAG_BIT: u64 = 255;
+
+// Positions of the points in the verify() calldata, used to pack it into the
+// blob taken by verify_compressed(): points are 32 bytes (x with the parity of y
+// in the top bit), scalars 32 bytes, without abi offsets, lengths or padding.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CalldataLayout {
+    // number of words of the proof
+    pub proof_len: usize,
+    // word offsets of the x coordinates of the proof points
+    pub proof_points: Vec<usize>,
+    // number of words of the final pair, the first 4 words are two points
+    pub instance_size: usize,
+}
+
+fn add_expression_points(e: &Expression, points: &mut BTreeSet<usize>) {
+    match e {
+        Expression::TransciprtOffset(offset, Type::Point) => {
+            points.insert(*offset);
+        }
+        Expression::Add(l, r, _)
+        | Expression::Sub(l, r, _)
+        | Expression::Mul(l, r, _)
+        | Expression::Div(l, r, _) => {
+            add_expression_points(l, points);
+            add_expression_points(r, points);
+        }
+        Expression::MulAdd(a, b, c, _) => {
+            add_expression_points(a, points);
+            add_expression_points(b, points);
+            add_expression_points(c, points);
+        }
+        Expression::Pow(base, _, _) => add_expression_points(base, points),
+        // (proof point, memory scalar) byte pairs
+        Expression::MulAddPM(target, opcode, t) => {
+            if *t == Type::Point {
+                let bytes = opcode.to_bytes_le();
+                for i in 0..opcode_len(opcode, 2) as usize {
+                    points.insert(*bytes.get(i * 2).unwrap_or(&0) as usize);
+                }
+            }
+            add_expression_points(target, points);
+        }
+        Expression::MulAddTable(target, entries) => {
+            for (p, _) in entries.iter() {
+                add_expression_points(p, points);
+            }
+            add_expression_points(target, points);
+        }
+        _ => {}
+    }
+}
+
+impl CalldataLayout {
+    pub(crate) fn new(args: &CodeGeneratorCtx, proof_len: usize) -> Self {
+        let mut points = BTreeSet::new();
+        for s in args.assignments.iter() {
+            match s {
+                Statement::Assign(_, r, _) => add_expression_points(r, &mut points),
+                Statement::UpdateHash(e, _) => add_expression_points(e, &mut points),
+                Statement::For {
+                    memory_start,
+                    memory_end,
+                    memory_step,
+                    t: Type::Point,
+                    ..
+                } => {
+                    for offset in (*memory_start..=*memory_end).step_by(*memory_step) {
+                        points.insert(offset);
+                    }
+                }
+                Statement::For { .. } | Statement::ForMMMMul { .. } => {}
+            }
+        }
+        add_expression_points(&args.wx, &mut points);
+        add_expression_points(&args.wg, &mut points);
+
+        CalldataLayout {
+            proof_len,
+            proof_points: points.into_iter().collect(),
+            instance_size: args.instance_size,
+        }
+    }
+
+    pub fn compressed_length(&self) -> usize {
+        (self.proof_len - self.proof_points.len() + self.instance_size - 2) * 32
+    }
+
+    // Bitmap of the proof points as rendered in the contract, bit i of byte i / 8 for word i.
+    pub(crate) fn proof_points_bitmap(&self) -> String {
+        let mut bitmap = vec![0u8; (self.proof_len + 7) / 8];
+        for offset in self.proof_points.iter() {
+            bitmap[offset / 8] |= 1 << (offset % 8);
+        }
+        bitmap.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    // Packs the serialized proof and final pair (little endian 32 bytes words)
+    // into the blob taken by verify_compressed().
+    pub fn compress(&self, proof: &[u8], final_pair: &[u8]) -> Vec<u8> {
+        let to_words = |buf: &[u8]| {
+            buf.chunks(32)
+                .map(BigUint::from_bytes_le)
+                .collect::<Vec<_>>()
+        };
+        let encode_word = |v: &BigUint| {
+            let bytes = v.to_bytes_be();
+            let mut word = vec![0u8; 32 - bytes.len()];
+            word.extend(bytes);
+            word
+        };
+        let compress_words = |words: Vec<BigUint>, points: &[usize], blob: &mut Vec<u8>| {
+            let mut i = 0;
+            while i < words.len() {
+                if points.contains(&i) {
+                    let (x, y) = (&words[i], &words[i + 1]);
+                    let word = if *x == BigUint::from(0u64) && *y == BigUint::from(0u64) {
+                        BigUint::from(1u64) << IDENTITY_FLAG_BIT
+                    } else {
+                        x + ((y & BigUint::from(1u64)) << Y_ODD_FLAG_BIT)
+                    };
+                    blob.extend(encode_word(&word));
+                    i += 2;
+                } else {
+                    blob.extend(encode_word(&words[i]));
+                    i += 1;
+                }
+            }
+        };
+
+        let proof = to_words(proof);
+        let final_pair = to_words(final_pair);
+        assert_eq!(proof.len(), self.proof_len);
+        assert_eq!(final_pair.len(), self.instance_size);
+
+        let mut blob = vec![];
+        compress_words(proof, &self.proof_points[..], &mut blob);
+        compress_words(final_pair, &[0, 2], &mut blob);
+        assert_eq!(blob.len(), self.compressed_length());
+        blob
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_calldata() {
+        let layout = CalldataLayout {
+            proof_len: 4,
+            proof_points: vec![1],
+            instance_size: 5,
+        };
+        let word = |v: u64| {
+            let mut bytes = v.to_le_bytes().to_vec();
+            bytes.resize(32, 0);
+            bytes
+        };
+        let proof = [word(7), word(2), word(3), word(9)].concat();
+        let final_pair = [word(1), word(2), word(0), word(0), word(5)].concat();
+
+        let blob = layout.compress(&proof, &final_pair);
+        assert_eq!(blob.len(), layout.compressed_length());
+        assert_eq!(blob.len(), 6 * 32);
+        // scalar, point (2, 3) with odd y, scalar
+        assert_eq!(blob[31], 7);
+        assert_eq!(blob[32], 0x80);
+        assert_eq!(blob[63], 2);
+        assert_eq!(blob[95], 9);
+        // point (1, 2) with even y, identity point, scalar
+        assert_eq!(blob[96], 0);
+        assert_eq!(blob[127], 1);
+        assert_eq!(blob[128], 0x40);
+        assert_eq!(blob[191], 5);
+        assert_eq!(layout.proof_points_bitmap(), "02");
+    }
+}
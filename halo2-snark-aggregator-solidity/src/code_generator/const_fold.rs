@@ -0,0 +1,196 @@
+use super::ctx::{CodeGeneratorCtx, Expression, Statement, Type};
+use num_bigint::BigUint;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+fn q_mod() -> BigUint {
+    BigUint::parse_bytes(
+        b"30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001",
+        16,
+    )
+    .unwrap()
+}
+
+// Memory words assigned a literal, by offset.
+struct ConstFolder {
+    q: BigUint,
+    consts: HashMap<usize, Expression>,
+}
+
+impl ConstFolder {
+    fn fold(&self, e: &Expression) -> Expression {
+        let fold = |e: &Rc<Expression>| Rc::new(self.fold(e));
+        let scalar = |e: &Expression| match e {
+            Expression::Scalar(s) => Some(s.clone()),
+            _ => None,
+        };
+        let q = &self.q;
+
+        match e {
+            Expression::Memory(idx, _) => {
+                self.consts.get(idx).cloned().unwrap_or_else(|| e.clone())
+            }
+            Expression::Add(l, r, Type::Scalar) => {
+                let (l, r) = (fold(l), fold(r));
+                match (scalar(&l), scalar(&r)) {
+                    (Some(l), Some(r)) => Expression::Scalar((l + r) % q),
+                    _ => Expression::Add(l, r, Type::Scalar),
+                }
+            }
+            Expression::Sub(l, r, Type::Scalar) => {
+                let (l, r) = (fold(l), fold(r));
+                match (scalar(&l), scalar(&r)) {
+                    (Some(l), Some(r)) => Expression::Scalar((l + q - r) % q),
+                    _ => Expression::Sub(l, r, Type::Scalar),
+                }
+            }
+            Expression::Mul(l, r, Type::Scalar) => {
+                let (l, r) = (fold(l), fold(r));
+                match (scalar(&l), scalar(&r)) {
+                    (Some(l), Some(r)) => Expression::Scalar((l * r) % q),
+                    _ => Expression::Mul(l, r, Type::Scalar),
+                }
+            }
+            Expression::Div(l, r, Type::Scalar) => {
+                let (l, r) = (fold(l), fold(r));
+                match (scalar(&l), scalar(&r)) {
+                    // a zero divisor is left to revert at runtime
+                    (Some(l), Some(r)) if r != BigUint::from(0u64) => {
+                        Expression::Scalar(l * r.modpow(&(q - 2u64), q) % q)
+                    }
+                    _ => Expression::Div(l, r, Type::Scalar),
+                }
+            }
+            Expression::MulAdd(a, b, c, Type::Scalar) => {
+                let (a, b, c) = (fold(a), fold(b), fold(c));
+                match (scalar(&a), scalar(&b), scalar(&c)) {
+                    (Some(a), Some(b), Some(c)) => Expression::Scalar((a * b + c) % q),
+                    _ => Expression::MulAdd(a, b, c, Type::Scalar),
+                }
+            }
+            Expression::Pow(base, exp, Type::Scalar) => {
+                let base = fold(base);
+                match scalar(&base) {
+                    Some(base) => Expression::Scalar(base.modpow(&BigUint::from(*exp), q)),
+                    None => Expression::Pow(base, *exp, Type::Scalar),
+                }
+            }
+            // point arithmetic on constants is folded by SolidityEccChip already
+            Expression::Add(l, r, t) => Expression::Add(fold(l), fold(r), t.clone()),
+            Expression::Sub(l, r, t) => Expression::Sub(fold(l), fold(r), t.clone()),
+            Expression::Mul(l, r, t) => Expression::Mul(fold(l), fold(r), t.clone()),
+            Expression::Div(l, r, t) => Expression::Div(fold(l), fold(r), t.clone()),
+            Expression::MulAdd(a, b, c, t) => {
+                Expression::MulAdd(fold(a), fold(b), fold(c), t.clone())
+            }
+            Expression::Pow(base, exp, t) => Expression::Pow(fold(base), *exp, t.clone()),
+            _ => e.clone(),
+        }
+    }
+}
+
+// Evaluates the scalar expressions over constants only (omega powers, lagrange
+// denominators at fixed rotations...) at codegen time. The memory words they
+// are assigned to are replaced with the literal and their assignment removed,
+// so it runs before memory_optimize, while every word is assigned once.
+pub(crate) fn fold_constants(mut args: CodeGeneratorCtx) -> CodeGeneratorCtx {
+    let mut folder = ConstFolder {
+        q: q_mod(),
+        consts: HashMap::new(),
+    };
+
+    let mut statements = vec![];
+    for s in args.assignments.iter() {
+        match s {
+            Statement::Assign(l, r, samples) => {
+                let r = folder.fold(r);
+                match (l.as_ref(), &r) {
+                    (Expression::Memory(idx, _), Expression::Scalar(_))
+                    | (Expression::Memory(idx, _), Expression::Point(..)) => {
+                        folder.consts.insert(*idx, r);
+                    }
+                    _ => statements.push(Statement::Assign(l.clone(), r, samples.clone())),
+                }
+            }
+            Statement::UpdateHash(e, offset) => {
+                statements.push(Statement::UpdateHash(Rc::new(folder.fold(e)), *offset))
+            }
+            _ => statements.push(s.clone()),
+        }
+    }
+
+    args.assignments = statements;
+    args.wx = folder.fold(&args.wx);
+    args.wg = folder.fold(&args.wg);
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_generator::ctx::G2Point;
+
+    fn g2() -> G2Point {
+        G2Point {
+            x: (BigUint::from(1u64), BigUint::from(2u64)),
+            y: (BigUint::from(3u64), BigUint::from(4u64)),
+        }
+    }
+
+    #[test]
+    fn test_fold_constants() {
+        let m = |idx| Rc::new(Expression::Memory(idx, Type::Scalar));
+        let c = |v: u64| Rc::new(Expression::Scalar(BigUint::from(v)));
+        let proof = Rc::new(Expression::TransciprtOffset(0, Type::Scalar));
+        let args = CodeGeneratorCtx {
+            wx: Expression::Memory(3, Type::Point),
+            wg: Expression::Memory(3, Type::Point),
+            target_circuit_s_g2: g2(),
+            target_circuit_n_g2: g2(),
+            verify_circuit_s_g2: g2(),
+            verify_circuit_n_g2: g2(),
+            assignments: vec![
+                // m0 = 2^3, m1 = (m0 - 2) / 3, m2 = m1 * proof[0]
+                Statement::Assign(m(0), Expression::Pow(c(2), 3, Type::Scalar), vec![]),
+                Statement::Assign(
+                    m(1),
+                    Expression::Div(
+                        Rc::new(Expression::Sub(m(0), c(2), Type::Scalar)),
+                        c(3),
+                        Type::Scalar,
+                    ),
+                    vec![],
+                ),
+                Statement::Assign(
+                    m(2),
+                    Expression::Mul(m(1), proof.clone(), Type::Scalar),
+                    vec![],
+                ),
+            ],
+            memory_size: 5,
+            instance_size: 4,
+            absorbing_length: 1,
+        };
+
+        let args = fold_constants(args);
+        assert_eq!(args.assignments.len(), 1);
+        match &args.assignments[0] {
+            Statement::Assign(l, r, _) => {
+                assert_eq!(**l, *m(2));
+                assert_eq!(*r, Expression::Mul(c(2), proof, Type::Scalar));
+            }
+            _ => unreachable!(),
+        }
+
+        // division of constants in the field
+        let folder = ConstFolder {
+            q: q_mod(),
+            consts: HashMap::new(),
+        };
+        let third = folder.fold(&Expression::Div(c(1), c(3), Type::Scalar));
+        assert_eq!(
+            folder.fold(&Expression::Mul(Rc::new(third), c(3), Type::Scalar)),
+            *c(1)
+        );
+    }
+}
@@ -1,6 +1,16 @@
+use super::msm_loop::encode_table;
 use num_bigint::BigUint;
 use std::{collections::HashMap, rc::Rc};
 
+// The yul backend keeps m[i] at YUL_MEMORY_BASE + 32 * i, points are passed
+// around as pointers to (x, y) since a yul expression yields a single value.
+pub(crate) const YUL_MEMORY_BASE: usize = 0x80;
+const YUL_Q_MOD: &str = "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+
+pub(crate) fn yul_memory_ptr(idx: usize) -> String {
+    format!("{:#x}", YUL_MEMORY_BASE + idx * 0x20)
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Type {
     Scalar,
@@ -30,6 +40,8 @@ pub enum Expression {
     InstanceOffset(usize, Type),
     // TODO: Remove
     TmpBufOffset(usize, Type),
+    // Offset in the constants table of the VerifyingKey contract, see vk_table.rs.
+    VkOffset(usize, Type),
     Point(BigUint, BigUint),
     Scalar(BigUint),
     Add(Rc<Expression>, Rc<Expression>, Type),
@@ -39,6 +51,8 @@ pub enum Expression {
     MulAdd(Rc<Expression>, Rc<Expression>, Rc<Expression>, Type),
     MulAddPM(Rc<Expression>, BigUint, Type),
     MulAddMT(usize, BigUint),
+    // (point, scalar slot) entries looped over by ecc_mul_add_table, see msm_loop.rs.
+    MulAddTable(Rc<Expression>, Vec<(Rc<Expression>, usize)>),
     Pow(Rc<Expression>, usize, Type),
     Hash(usize),
     Temp(Type),
@@ -82,6 +96,7 @@ impl Expression {
             | Expression::TransciprtOffset(_, t)
             | Expression::InstanceOffset(_, t)
             | Expression::TmpBufOffset(_, t)
+            | Expression::VkOffset(_, t)
             | Expression::Add(_, _, t)
             | Expression::Sub(_, _, t)
             | Expression::Mul(_, _, t)
@@ -94,6 +109,7 @@ impl Expression {
             Expression::Temp(t) => (*t).clone(),
             Expression::MulAddPM(_, _, t) => (*t).clone(),
             Expression::MulAddMT(_, _) => Type::Scalar,
+            Expression::MulAddTable(..) => Type::Point,
         }
     }
 
@@ -230,6 +246,10 @@ impl Expression {
             Expression::TmpBufOffset(offset, t) => {
                 format!("{}_from_bytes(vars, {})", t.to_libstring(), offset)
             }
+            Expression::VkOffset(offset, t) => match t {
+                Type::Scalar => format!("vk[{}]", offset),
+                Type::Point => format!("vk[{}], vk[{}]", offset, offset + 1),
+            },
             Expression::Hash(offset) => {
                 format!("squeeze_challenge(absorbing, {})", offset)
             }
@@ -250,6 +270,93 @@ impl Expression {
             Expression::MulAddMT(m, opcode) => {
                 format!("fr_mul_add_mt(m, m[{}], {}, t0)", m, opcode)
             }
+            Expression::MulAddTable(target, entries) => format!(
+                "ecc_mul_add_table(m, proof, hex\"{}\", {})",
+                encode_table(entries),
+                target.to_typed_string()
+            ),
+        }
+    }
+
+    pub fn to_yul_string(&self) -> String {
+        match self {
+            Expression::Scalar(s) => s.to_string(),
+            Expression::Point(x, y) => format!("point_const({}, {})", x, y),
+            Expression::Memory(idx, Type::Scalar) => format!("mload({})", yul_memory_ptr(*idx)),
+            Expression::Memory(idx, Type::Point) => yul_memory_ptr(*idx),
+            Expression::Add(l, r, Type::Point) => {
+                format!("ecc_add({}, {})", l.to_yul_string(), r.to_yul_string())
+            }
+            Expression::Add(l, r, Type::Scalar) => format!(
+                "addmod({}, {}, {})",
+                l.to_yul_string(),
+                r.to_yul_string(),
+                YUL_Q_MOD
+            ),
+            Expression::Sub(l, r, Type::Point) => {
+                format!("ecc_sub({}, {})", l.to_yul_string(), r.to_yul_string())
+            }
+            Expression::Sub(l, r, Type::Scalar) => format!(
+                "addmod({}, sub({}, {}), {})",
+                l.to_yul_string(),
+                YUL_Q_MOD,
+                r.to_yul_string(),
+                YUL_Q_MOD
+            ),
+            Expression::Mul(s, p, Type::Point) => {
+                format!("ecc_mul({}, {})", p.to_yul_string(), s.to_yul_string())
+            }
+            Expression::Mul(l, r, Type::Scalar) => format!(
+                "mulmod({}, {}, {})",
+                l.to_yul_string(),
+                r.to_yul_string(),
+                YUL_Q_MOD
+            ),
+            Expression::Div(l, r, t) => format!(
+                "{}_div({}, {})",
+                t.to_libstring(),
+                l.to_yul_string(),
+                r.to_yul_string()
+            ),
+            Expression::MulAdd(l, r, c, t) => format!(
+                "{}_mul_add({}, {}, {})",
+                t.to_libstring(),
+                l.to_yul_string(),
+                r.to_yul_string(),
+                c.to_yul_string()
+            ),
+            Expression::TransciprtOffset(offset, Type::Scalar) => format!("proof({})", offset),
+            Expression::TransciprtOffset(offset, Type::Point) => {
+                format!("proof_point({})", offset)
+            }
+            Expression::InstanceOffset(offset, Type::Scalar) => {
+                format!("mload(instance_ptr({}))", offset)
+            }
+            Expression::InstanceOffset(offset, Type::Point) => {
+                format!("instance_ptr({})", offset)
+            }
+            Expression::TmpBufOffset(..) => unreachable!(),
+            // the yul backend keeps its constants inline
+            Expression::VkOffset(..) => unreachable!(),
+            Expression::Hash(offset) => format!("squeeze_challenge({})", offset),
+            Expression::Pow(base, exp, t) => {
+                assert_eq!(*t, Type::Scalar);
+                format!("fr_pow({}, {})", base.to_yul_string(), exp)
+            }
+            Expression::Temp(_) => "t0".to_owned(),
+            Expression::MulAddPM(target, opcode, t) => format!(
+                "{}_mul_add_pm({}, {})",
+                t.to_libstring(),
+                opcode,
+                target.to_yul_string()
+            ),
+            Expression::MulAddMT(m, opcode) => format!(
+                "fr_mul_add_mt(mload({}), {}, t0)",
+                yul_memory_ptr(*m),
+                opcode
+            ),
+            // solidity only, see loop_msm
+            Expression::MulAddTable(..) => unreachable!(),
         }
     }
 
@@ -552,6 +659,84 @@ impl Statement {
         ret
     }
 
+    pub fn to_yul_string(&self) -> Vec<String> {
+        match self {
+            Statement::Assign(l, r, _) => match l.as_ref() {
+                Expression::Memory(idx, Type::Scalar) => vec![format!(
+                    "mstore({}, {})",
+                    yul_memory_ptr(*idx),
+                    r.to_yul_string()
+                )],
+                Expression::Memory(idx, Type::Point) => vec![format!(
+                    "copy_point({}, {})",
+                    yul_memory_ptr(*idx),
+                    r.to_yul_string()
+                )],
+                Expression::Temp(_) => vec![format!("t0 := {}", r.to_yul_string())],
+                _ => unreachable!(),
+            },
+            Statement::UpdateHash(e, offset) => vec![format!(
+                "update_hash_{}({}, {})",
+                match e.get_type() {
+                    Type::Scalar => "scalar",
+                    Type::Point => "point",
+                },
+                e.to_yul_string(),
+                offset
+            )],
+            Statement::For {
+                memory_start,
+                memory_end,
+                memory_step,
+                absorbing_start,
+                absorbing_step,
+                t,
+            } => {
+                let proof_offset = format!("add({}, mul(i, {}))", memory_start, memory_step);
+                let absorbing_offset =
+                    format!("add({}, mul(i, {}))", absorbing_start, absorbing_step);
+                let update = match *t {
+                    Type::Scalar => format!(
+                        "    update_hash_scalar(proof({}), {})",
+                        proof_offset, absorbing_offset
+                    ),
+                    Type::Point => format!(
+                        "    update_hash_xy(proof({}), proof(add({}, 1)), {})",
+                        proof_offset, proof_offset, absorbing_offset
+                    ),
+                };
+                vec![
+                    format!(
+                        "for {{ let i := 0 }} lt(i, {}) {{ i := add(i, 1) }} {{",
+                        (memory_end - memory_start) / memory_step + 1
+                    ),
+                    update,
+                    "}".to_owned(),
+                ]
+            }
+            Statement::ForMMMMul { start, step, n, t } => {
+                assert_eq!(*t, Type::Scalar);
+                let ptr = |start: usize, step: usize| {
+                    format!(
+                        "add({:#x}, mul(add({}, mul(i, {})), 0x20))",
+                        YUL_MEMORY_BASE, start, step
+                    )
+                };
+                vec![
+                    format!("for {{ let i := 0 }} lt(i, {}) {{ i := add(i, 1) }} {{", n),
+                    format!(
+                        "    mstore({}, mulmod(mload({}), mload({}), {}))",
+                        ptr(start.0, step.0),
+                        ptr(start.1, step.1),
+                        ptr(start.2, step.2),
+                        YUL_Q_MOD
+                    ),
+                    "}".to_owned(),
+                ]
+            }
+        }
+    }
+
     pub fn substitute(&self, lookup: &HashMap<usize, usize>) -> Statement {
         match self {
             Statement::Assign(l, r, s) => Statement::Assign(
@@ -718,3 +903,69 @@ pub(crate) struct CodeGeneratorCtx {
     pub(crate) instance_size: usize,
     pub(crate) absorbing_length: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scalar(idx: usize) -> Rc<Expression> {
+        Rc::new(Expression::Memory(idx, Type::Scalar))
+    }
+
+    fn point(idx: usize) -> Rc<Expression> {
+        Rc::new(Expression::Memory(idx, Type::Point))
+    }
+
+    // Both backends must read the same slots and apply the same operations in the same order,
+    // the whole contracts are run side by side in evm::tests.
+    #[test]
+    fn test_yul_matches_solidity() {
+        let statements = vec![
+            Statement::Assign(
+                scalar(2),
+                Expression::Sub(scalar(0), scalar(1), Type::Scalar),
+                vec![],
+            ),
+            Statement::Assign(
+                point(3),
+                Expression::MulAdd(
+                    point(5),
+                    Rc::new(Expression::TransciprtOffset(4, Type::Scalar)),
+                    point(7),
+                    Type::Point,
+                ),
+                vec![],
+            ),
+            Statement::UpdateHash(point(3), 6),
+        ];
+
+        let solidity = statements
+            .iter()
+            .flat_map(|s| s.to_solidity_string(&mut vec![], &mut 0))
+            .collect::<Vec<_>>();
+        let yul = statements
+            .iter()
+            .flat_map(|s| s.to_yul_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            solidity,
+            vec![
+                "m[2] = (addmod(m[0], q_mod - m[1], q_mod));",
+                "(m[3], m[4]) = (ecc_mul_add(m[5], m[6], proof[4], m[7], m[8]));",
+                "update_hash_point(m[3], m[4], absorbing, 6);",
+            ]
+        );
+        assert_eq!(
+            yul,
+            vec![
+                format!(
+                    "mstore(0xc0, addmod(mload(0x80), sub({q}, mload(0xa0)), {q}))",
+                    q = YUL_Q_MOD
+                ),
+                "copy_point(0xe0, ecc_mul_add(0x120, proof(4), 0x160))".to_owned(),
+                "update_hash_point(0xe0, 6)".to_owned(),
+            ]
+        );
+    }
+}
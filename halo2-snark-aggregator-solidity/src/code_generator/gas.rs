@@ -0,0 +1,206 @@
+use super::ctx::{CodeGeneratorCtx, Expression, Statement, Type};
+use super::pairing::{collect_pairs, TERM_WORDS};
+use crate::TranscriptHash;
+use num_bigint::BigUint;
+use std::fmt;
+
+const TX_BASE_GAS: u64 = 21000;
+const CALLDATA_ZERO_BYTE_GAS: u64 = 4;
+const CALLDATA_NONZERO_BYTE_GAS: u64 = 16;
+const ECADD_GAS: u64 = 150;
+const ECMUL_GAS: u64 = 6000;
+const PAIRING_BASE_GAS: u64 = 45000;
+const PAIRING_PER_PAIR_GAS: u64 = 34000;
+const SHA256_BASE_GAS: u64 = 60;
+const SHA256_WORD_GAS: u64 = 12;
+const KECCAK256_BASE_GAS: u64 = 30;
+const KECCAK256_WORD_GAS: u64 = 6;
+const Q_MOD_BITS: u64 = 254;
+
+// EIP-2565 price of a modexp with 32 bytes base and modulus.
+fn modexp_gas(exp_bits: u64) -> u64 {
+    std::cmp::max(200, 16 * exp_bits.saturating_sub(1) / 3)
+}
+
+// Number of entries in a packed mul_add opcode, it ends at the first 0xff byte.
+pub(crate) fn opcode_len(opcode: &BigUint, width: usize) -> u64 {
+    let bytes = opcode.to_bytes_le();
+    (0..32 / width)
+        .take_while(|i| bytes.get(i * width).map_or(0, |b| *b) != 0xff)
+        .count() as u64
+}
+
+// Gas estimate of a verify() call from the operations of the generated code,
+// only precompiles and calldata are priced, the interpreter overhead is not.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GasReport {
+    pub calldata: u64,
+    // ecAdd and ecMul precompiles
+    pub msm: u64,
+    pub pairing: u64,
+    // sha256 precompile or keccak256 opcode squeezing the transcript challenges
+    pub transcript_hash: u64,
+    // modexp precompile for field inversions and powers
+    pub modexp: u64,
+}
+
+impl GasReport {
+    pub fn total(&self) -> u64 {
+        TX_BASE_GAS + self.calldata + self.msm + self.pairing + self.transcript_hash + self.modexp
+    }
+
+    fn add_expression(&mut self, e: &Expression, hash: TranscriptHash) {
+        match e {
+            Expression::Add(l, r, t) | Expression::Sub(l, r, t) => {
+                if *t == Type::Point {
+                    self.msm += ECADD_GAS;
+                }
+                self.add_expression(l, hash);
+                self.add_expression(r, hash);
+            }
+            Expression::Mul(l, r, t) => {
+                if *t == Type::Point {
+                    self.msm += ECMUL_GAS;
+                }
+                self.add_expression(l, hash);
+                self.add_expression(r, hash);
+            }
+            Expression::Div(l, r, _) => {
+                self.modexp += modexp_gas(Q_MOD_BITS);
+                self.add_expression(l, hash);
+                self.add_expression(r, hash);
+            }
+            Expression::MulAdd(a, b, c, t) => {
+                if *t == Type::Point {
+                    self.msm += ECMUL_GAS + ECADD_GAS;
+                }
+                self.add_expression(a, hash);
+                self.add_expression(b, hash);
+                self.add_expression(c, hash);
+            }
+            Expression::MulAddPM(target, opcode, t) => {
+                if *t == Type::Point {
+                    self.msm += opcode_len(opcode, 2) * (ECMUL_GAS + ECADD_GAS);
+                }
+                self.add_expression(target, hash);
+            }
+            Expression::MulAddTable(target, entries) => {
+                self.msm += entries.len() as u64 * (ECMUL_GAS + ECADD_GAS);
+                self.add_expression(target, hash);
+            }
+            Expression::Pow(base, exp, _) => {
+                let bits = (usize::BITS - exp.leading_zeros()) as u64;
+                self.modexp += modexp_gas(bits);
+                self.add_expression(base, hash);
+            }
+            // absorbing[0..length] plus the trailing byte
+            Expression::Hash(length) => {
+                let (base, word) = match hash {
+                    TranscriptHash::Sha256 => (SHA256_BASE_GAS, SHA256_WORD_GAS),
+                    TranscriptHash::Keccak256 => (KECCAK256_BASE_GAS, KECCAK256_WORD_GAS),
+                };
+                self.transcript_hash += base + word * (*length as u64 + 1);
+            }
+            _ => {}
+        }
+    }
+
+    fn add_statement(&mut self, s: &Statement, hash: TranscriptHash) {
+        match s {
+            Statement::Assign(_, r, _) => self.add_expression(r, hash),
+            Statement::UpdateHash(e, _) => self.add_expression(e, hash),
+            Statement::For { .. } | Statement::ForMMMMul { .. } => {}
+        }
+    }
+}
+
+// The final pair and instances are not known at codegen time and priced as non-zero words.
+pub(crate) fn estimate_gas(
+    args: &CodeGeneratorCtx,
+    proof: &[u8],
+    hash: TranscriptHash,
+) -> GasReport {
+    let mut report = GasReport::default();
+
+    // selector, two offsets and two lengths
+    let abi_overhead = 4 + 4 * 32;
+    let zeros = proof.iter().filter(|b| **b == 0).count() as u64;
+    report.calldata = abi_overhead * CALLDATA_NONZERO_BYTE_GAS
+        + zeros * CALLDATA_ZERO_BYTE_GAS
+        + (proof.len() as u64 - zeros) * CALLDATA_NONZERO_BYTE_GAS
+        + (args.instance_size as u64 * 32) * CALLDATA_NONZERO_BYTE_GAS;
+
+    for s in args.assignments.iter() {
+        report.add_statement(s, hash);
+    }
+    report.add_expression(&args.wx, hash);
+    report.add_expression(&args.wg, hash);
+
+    // verify circuit and target circuit checks in a single call, the target circuit
+    // pairs scaled by the keccak of the input, see code_generator::pairing
+    let terms = collect_pairs(args);
+    report.pairing = PAIRING_BASE_GAS
+        + terms.len() as u64 * PAIRING_PER_PAIR_GAS
+        + KECCAK256_BASE_GAS
+        + (terms.len() * TERM_WORDS) as u64 * KECCAK256_WORD_GAS;
+    report.msm += terms.iter().filter(|t| t.scaled).count() as u64 * ECMUL_GAS;
+
+    report
+}
+
+impl fmt::Display for GasReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "calldata:        {}", self.calldata)?;
+        writeln!(f, "msm:             {}", self.msm)?;
+        writeln!(f, "pairing:         {}", self.pairing)?;
+        writeln!(f, "transcript hash: {}", self.transcript_hash)?;
+        writeln!(f, "modexp:          {}", self.modexp)?;
+        write!(f, "total:           {}", self.total())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_gas_report() {
+        let point = |i| Rc::new(Expression::Memory(i, Type::Point));
+        let scalar = |i| Rc::new(Expression::Memory(i, Type::Scalar));
+        // two (proof point, memory scalar) pairs, terminated by 0xffff
+        let opcode = (BigUint::from(0xffffu64) << 32u8) + BigUint::from(0x0302_0100u64);
+        let statements = vec![
+            Statement::Assign(
+                Rc::new(Expression::Temp(Type::Point)),
+                Expression::MulAddPM(point(0), opcode, Type::Point),
+                vec![],
+            ),
+            Statement::Assign(scalar(2), Expression::Hash(3), vec![]),
+            Statement::Assign(
+                scalar(3),
+                Expression::Div(scalar(0), scalar(1), Type::Scalar),
+                vec![],
+            ),
+        ];
+
+        let report = GasReport::default();
+        let report = statements.iter().fold(report, |mut report, s| {
+            report.add_statement(s, TranscriptHash::Sha256);
+            report
+        });
+        assert_eq!(report.msm, 2 * (ECMUL_GAS + ECADD_GAS));
+        assert_eq!(
+            report.transcript_hash,
+            SHA256_BASE_GAS + 4 * SHA256_WORD_GAS
+        );
+        assert_eq!(report.modexp, modexp_gas(Q_MOD_BITS));
+
+        let mut report = GasReport::default();
+        report.add_statement(&statements[1], TranscriptHash::Keccak256);
+        assert_eq!(
+            report.transcript_hash,
+            KECCAK256_BASE_GAS + 4 * KECCAK256_WORD_GAS
+        );
+    }
+}
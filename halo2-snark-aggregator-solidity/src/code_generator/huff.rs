@@ -0,0 +1,255 @@
+use super::ctx::{yul_memory_ptr, Expression, Statement, Type};
+use num_bigint::BigUint;
+
+// The huff backend shares the memory layout of the yul one: m[i] at
+// YUL_MEMORY_BASE + 32 * i, points passed around as pointers to (x, y).
+// Every macro of templates/verifier.huff takes its first argument on top of
+// the stack, so arguments are pushed last to first.
+
+fn push_usize(v: usize) -> String {
+    format!("{:#x}", v)
+}
+
+fn push_biguint(v: &BigUint) -> String {
+    format!("0x{}", v.to_str_radix(16))
+}
+
+fn libmacro(t: &Type, name: &str) -> String {
+    match t {
+        Type::Scalar => format!("FR_{}()", name),
+        Type::Point => format!("ECC_{}()", name),
+    }
+}
+
+// Pushes the value of e, a scalar or a point pointer.
+fn expression_to_huff(e: &Expression, out: &mut Vec<String>) {
+    match e {
+        Expression::Scalar(s) => out.push(push_biguint(s)),
+        Expression::Point(x, y) => {
+            out.push(push_biguint(y));
+            out.push(push_biguint(x));
+            out.push("POINT_CONST()".to_owned());
+        }
+        Expression::Memory(idx, Type::Scalar) => {
+            out.push(format!("{} mload", yul_memory_ptr(*idx)));
+        }
+        Expression::Memory(idx, Type::Point) => out.push(yul_memory_ptr(*idx)),
+        Expression::Add(l, r, Type::Point) => {
+            expression_to_huff(r, out);
+            expression_to_huff(l, out);
+            out.push("ECC_ADD()".to_owned());
+        }
+        Expression::Add(l, r, Type::Scalar) => {
+            out.push("[Q_MOD]".to_owned());
+            expression_to_huff(r, out);
+            expression_to_huff(l, out);
+            out.push("addmod".to_owned());
+        }
+        Expression::Sub(l, r, Type::Point) => {
+            expression_to_huff(r, out);
+            expression_to_huff(l, out);
+            out.push("ECC_SUB()".to_owned());
+        }
+        Expression::Sub(l, r, Type::Scalar) => {
+            out.push("[Q_MOD]".to_owned());
+            expression_to_huff(r, out);
+            out.push("[Q_MOD] sub".to_owned());
+            expression_to_huff(l, out);
+            out.push("addmod".to_owned());
+        }
+        Expression::Mul(s, p, Type::Point) => {
+            expression_to_huff(s, out);
+            expression_to_huff(p, out);
+            out.push("ECC_MUL()".to_owned());
+        }
+        Expression::Mul(l, r, Type::Scalar) => {
+            out.push("[Q_MOD]".to_owned());
+            expression_to_huff(r, out);
+            expression_to_huff(l, out);
+            out.push("mulmod".to_owned());
+        }
+        Expression::Div(l, r, t) => {
+            assert_eq!(*t, Type::Scalar);
+            expression_to_huff(r, out);
+            expression_to_huff(l, out);
+            out.push("FR_DIV()".to_owned());
+        }
+        Expression::MulAdd(l, r, c, t) => {
+            expression_to_huff(c, out);
+            expression_to_huff(r, out);
+            expression_to_huff(l, out);
+            out.push(libmacro(t, "MUL_ADD"));
+        }
+        Expression::TransciprtOffset(offset, Type::Scalar) => {
+            out.push(format!("{} PROOF()", push_usize(*offset)));
+        }
+        Expression::TransciprtOffset(offset, Type::Point) => {
+            out.push(format!("{} PROOF_POINT()", push_usize(*offset)));
+        }
+        Expression::InstanceOffset(offset, Type::Scalar) => {
+            out.push(format!("{} INSTANCE_PTR() mload", push_usize(*offset)));
+        }
+        Expression::InstanceOffset(offset, Type::Point) => {
+            out.push(format!("{} INSTANCE_PTR()", push_usize(*offset)));
+        }
+        Expression::TmpBufOffset(..) | Expression::VkOffset(..) | Expression::MulAddTable(..) => {
+            unreachable!()
+        }
+        Expression::Hash(offset) => {
+            out.push(format!("{} SQUEEZE_CHALLENGE()", push_usize(*offset)));
+        }
+        Expression::Pow(base, exp, t) => {
+            assert_eq!(*t, Type::Scalar);
+            out.push(push_usize(*exp));
+            expression_to_huff(base, out);
+            out.push("FR_POW()".to_owned());
+        }
+        Expression::Temp(_) => out.push("[T0_PTR] mload".to_owned()),
+        Expression::MulAddPM(target, opcode, t) => {
+            expression_to_huff(target, out);
+            out.push(push_biguint(opcode));
+            out.push(libmacro(t, "MUL_ADD_PM"));
+        }
+        Expression::MulAddMT(m, opcode) => {
+            out.push("[T0_PTR] mload".to_owned());
+            out.push(push_biguint(opcode));
+            out.push(format!("{} mload", yul_memory_ptr(*m)));
+            out.push("FR_MUL_ADD_MT()".to_owned());
+        }
+    }
+}
+
+pub(crate) fn expression_to_huff_string(e: &Expression) -> String {
+    let mut out = vec![];
+    expression_to_huff(e, &mut out);
+    out.join(" ")
+}
+
+// label is a counter keeping the labels of the emitted loops unique.
+pub(crate) fn statement_to_huff(s: &Statement, label: &mut usize) -> Vec<String> {
+    match s {
+        Statement::Assign(l, r, _) => {
+            let value = expression_to_huff_string(r);
+            match l.as_ref() {
+                Expression::Memory(idx, Type::Scalar) => {
+                    vec![format!("{} {} mstore", value, yul_memory_ptr(*idx))]
+                }
+                Expression::Memory(idx, Type::Point) => {
+                    vec![format!("{} {} COPY_POINT()", value, yul_memory_ptr(*idx))]
+                }
+                Expression::Temp(_) => vec![format!("{} [T0_PTR] mstore", value)],
+                _ => unreachable!(),
+            }
+        }
+        Statement::UpdateHash(e, offset) => vec![format!(
+            "{} {} UPDATE_HASH_{}()",
+            push_usize(*offset),
+            expression_to_huff_string(e),
+            match e.get_type() {
+                Type::Scalar => "SCALAR",
+                Type::Point => "POINT",
+            }
+        )],
+        Statement::For {
+            memory_start,
+            memory_end,
+            memory_step,
+            absorbing_start,
+            absorbing_step,
+            t,
+        } => {
+            *label += 1;
+            let n = (memory_end - memory_start) / memory_step + 1;
+            // [i] -> [start + i * step]
+            let offset = |start: usize, step: usize| {
+                format!("{} mul {} add", push_usize(step), push_usize(start))
+            };
+            let pos = format!("dup1 {}", offset(*absorbing_start, *absorbing_step));
+            let update = match *t {
+                Type::Scalar => vec![
+                    format!("    {}", pos),
+                    format!("    dup2 {} PROOF()", offset(*memory_start, *memory_step)),
+                    "    UPDATE_HASH_SCALAR()".to_owned(),
+                ],
+                Type::Point => vec![
+                    format!("    {}", pos),
+                    format!(
+                        "    dup2 {} 0x01 add PROOF()",
+                        offset(*memory_start, *memory_step)
+                    ),
+                    format!("    dup3 {} PROOF()", offset(*memory_start, *memory_step)),
+                    "    UPDATE_HASH_XY()".to_owned(),
+                ],
+            };
+            [
+                vec![
+                    "0x00".to_owned(),
+                    format!("loop_{}:", label),
+                    format!("    {} dup2 lt iszero end_{} jumpi", push_usize(n), label),
+                ],
+                update,
+                vec![
+                    format!("    0x01 add loop_{} jump", label),
+                    format!("end_{}:", label),
+                    "    pop".to_owned(),
+                ],
+            ]
+            .concat()
+        }
+        Statement::ForMMMMul { start, step, n, t } => {
+            assert_eq!(*t, Type::Scalar);
+            *label += 1;
+            // [.., i] -> [.., ptr(start + i * step)], i is the nth word
+            let ptr = |dup: usize, start: usize, step: usize| {
+                format!(
+                    "dup{} {} mul {} add 0x20 mul {} add",
+                    dup,
+                    push_usize(step),
+                    push_usize(start),
+                    push_usize(super::ctx::YUL_MEMORY_BASE)
+                )
+            };
+            vec![
+                "0x00".to_owned(),
+                format!("loop_{}:", label),
+                format!("    {} dup2 lt iszero end_{} jumpi", push_usize(*n), label),
+                "    [Q_MOD]".to_owned(),
+                format!("    {} mload", ptr(2, start.2, step.2)),
+                format!("    {} mload", ptr(3, start.1, step.1)),
+                "    mulmod".to_owned(),
+                format!("    {} mstore", ptr(2, start.0, step.0)),
+                format!("    0x01 add loop_{} jump", label),
+                format!("end_{}:", label),
+                "    pop".to_owned(),
+            ]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_statement_to_huff() {
+        let m = |idx| Rc::new(Expression::Memory(idx, Type::Scalar));
+        let s = Statement::Assign(m(0), Expression::Sub(m(1), m(2), Type::Scalar), vec![]);
+        assert_eq!(
+            statement_to_huff(&s, &mut 0),
+            vec!["[Q_MOD] 0xc0 mload [Q_MOD] sub 0xa0 mload addmod 0x80 mstore"]
+        );
+
+        let mut label = 0;
+        let s = Statement::ForMMMMul {
+            start: (0, 1, 2),
+            step: (3, 3, 3),
+            n: 4,
+            t: Type::Scalar,
+        };
+        let lines = statement_to_huff(&s, &mut label);
+        assert_eq!(label, 1);
+        assert_eq!(lines[1], "loop_1:");
+        assert_eq!(lines.last().unwrap(), "    pop");
+    }
+}
@@ -8,7 +8,7 @@ use self::{
     optimize::optimize,
 };
 use super::ctx::{CodeGeneratorCtx, Expression, Statement, Type};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 fn linear_scan(
     intervals: &mut Vec<Interval>,
@@ -16,7 +16,9 @@ fn linear_scan(
     expressions: &mut Vec<Expression>,
     pool: &mut MemoryPool,
 ) -> usize {
-    let active = &mut HashSet::<Interval>::new();
+    // kept in allocation order, iterating a hash set here would make the
+    // generated code depend on the hasher seed
+    let active = &mut Vec::<Interval>::new();
 
     intervals.into_iter().for_each(|i| {
         expire_old_intervals(active, &i, pool);
@@ -57,20 +59,20 @@ fn linear_scan(
 
         if mem_block.pos != 0xdeadbeaf {
             i.mem_block = Some(mem_block);
-            active.insert(i.clone());
+            active.push(i.clone());
         }
     });
 
     pool.capability
 }
 
-fn expire_old_intervals(active: &mut HashSet<Interval>, i: &Interval, pool: &mut MemoryPool) {
-    let active_vec = &mut active.clone().into_iter().collect::<Vec<_>>();
-
-    active_vec.iter().for_each(|j| {
+fn expire_old_intervals(active: &mut Vec<Interval>, i: &Interval, pool: &mut MemoryPool) {
+    active.retain(|j| {
         if j.end <= i.start {
             pool.free(j.mem_block.clone().unwrap());
-            active.remove(j);
+            false
+        } else {
+            true
         }
     })
 }
@@ -91,3 +93,62 @@ pub(crate) fn memory_optimize(mut ctx: CodeGeneratorCtx) -> CodeGeneratorCtx {
 
     ctx
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_generator::ctx::G2Point;
+    use num_bigint::BigUint;
+    use std::rc::Rc;
+
+    fn g2() -> G2Point {
+        G2Point {
+            x: (BigUint::from(0u64), BigUint::from(0u64)),
+            y: (BigUint::from(0u64), BigUint::from(0u64)),
+        }
+    }
+
+    fn scalar(i: usize) -> Rc<Expression> {
+        Rc::new(Expression::Memory(i, Type::Scalar))
+    }
+
+    fn ctx() -> CodeGeneratorCtx {
+        // values with overlapping lifetimes, so that several intervals expire at once
+        let mut assignments = vec![];
+        for i in 0..64 {
+            let r = if i < 4 {
+                Expression::TransciprtOffset(i, Type::Scalar)
+            } else {
+                Expression::Mul(scalar(i - 4), scalar(i - 1), Type::Scalar)
+            };
+            assignments.push(Statement::Assign(scalar(i), r, vec![]));
+            if i % 3 == 0 {
+                assignments.push(Statement::UpdateHash(scalar(i), i));
+            }
+        }
+
+        CodeGeneratorCtx {
+            wx: Expression::Memory(63, Type::Scalar),
+            wg: Expression::Memory(62, Type::Scalar),
+            target_circuit_s_g2: g2(),
+            target_circuit_n_g2: g2(),
+            verify_circuit_s_g2: g2(),
+            verify_circuit_n_g2: g2(),
+            assignments,
+            memory_size: 64,
+            instance_size: 0,
+            absorbing_length: 64,
+        }
+    }
+
+    #[test]
+    fn test_memory_optimize_is_deterministic() {
+        let expected = format!("{:?}", memory_optimize(ctx()).assignments);
+        for _ in 0..8 {
+            assert_eq!(
+                format!("{:?}", memory_optimize(ctx()).assignments),
+                expected
+            );
+        }
+    }
+}
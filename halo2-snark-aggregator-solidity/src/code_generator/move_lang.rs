@@ -0,0 +1,231 @@
+use super::ctx::{Expression, Statement, Type};
+
+// Words are u256 values in move as in vyper, points Point { x, y } structs. The field
+// and curve operations are the helpers of templates/verifier.move, on top of the bn254
+// structures of aptos_std::crypto_algebra. Vectors are indexed with the move 2 syntax.
+
+fn point(x: String, y: String) -> String {
+    format!("Point {{ x: {}, y: {} }}", x, y)
+}
+
+fn libcall(t: &Type, name: &str) -> String {
+    match t {
+        Type::Scalar => format!("fr_{}", name),
+        Type::Point => format!("ecc_{}", name),
+    }
+}
+
+fn binary(t: &Type, name: &str, l: &Expression, r: &Expression) -> String {
+    format!(
+        "{}({}, {})",
+        libcall(t, name),
+        expression_to_move(l),
+        expression_to_move(r)
+    )
+}
+
+pub(crate) fn expression_to_move(e: &Expression) -> String {
+    match e {
+        Expression::Scalar(s) => s.to_string(),
+        Expression::Point(x, y) => point(x.to_string(), y.to_string()),
+        Expression::Memory(idx, Type::Scalar) => format!("m[{}]", idx),
+        Expression::Memory(idx, Type::Point) => {
+            point(format!("m[{}]", idx), format!("m[{}]", idx + 1))
+        }
+        Expression::TransciprtOffset(offset, Type::Scalar) => format!("proof[{}]", offset),
+        Expression::TransciprtOffset(offset, Type::Point) => point(
+            format!("proof[{}]", offset),
+            format!("proof[{}]", offset + 1),
+        ),
+        Expression::InstanceOffset(offset, Type::Scalar) => format!("instances[{}]", offset),
+        Expression::InstanceOffset(offset, Type::Point) => point(
+            format!("instances[{}]", offset),
+            format!("instances[{}]", offset + 1),
+        ),
+        Expression::Add(l, r, t) => binary(t, "add", l, r),
+        Expression::Sub(l, r, t) => binary(t, "sub", l, r),
+        Expression::Mul(s, p, Type::Point) => binary(&Type::Point, "mul", p, s),
+        Expression::Mul(l, r, Type::Scalar) => binary(&Type::Scalar, "mul", l, r),
+        Expression::Div(l, r, t) => binary(t, "div", l, r),
+        Expression::MulAdd(l, r, c, t) => format!(
+            "{}({}, {}, {})",
+            libcall(t, "mul_add"),
+            expression_to_move(l),
+            expression_to_move(r),
+            expression_to_move(c)
+        ),
+        Expression::Pow(base, exp, t) => {
+            assert_eq!(*t, Type::Scalar);
+            format!("fr_pow({}, {})", expression_to_move(base), exp)
+        }
+        Expression::Temp(Type::Scalar) => "t0".to_owned(),
+        Expression::Temp(Type::Point) => point("t0".to_owned(), "t1".to_owned()),
+        Expression::MulAddPM(target, opcode, t) => format!(
+            "{}(&m, &proof, {}, {})",
+            libcall(t, "mul_add_pm"),
+            opcode,
+            expression_to_move(target)
+        ),
+        Expression::MulAddMT(m, opcode) => {
+            format!("fr_mul_add_mt(&m, m[{}], {}, t0)", m, opcode)
+        }
+        // squeeze_challenge updates absorbing, see statement_to_move
+        Expression::Hash(_) => unreachable!(),
+        Expression::TmpBufOffset(..) | Expression::VkOffset(..) | Expression::MulAddTable(..) => {
+            unreachable!()
+        }
+    }
+}
+
+// Statements of the body of verify, p is a Point local for point assignments.
+pub(crate) fn statement_to_move(s: &Statement) -> Vec<String> {
+    match s {
+        Statement::Assign(l, r, _) => {
+            let (mut lines, value) = match r {
+                Expression::Hash(offset) => (
+                    vec![
+                        format!("absorbing[{}] = 0;", offset),
+                        format!("h = transcript_hash(&absorbing, {});", offset * 32 + 1),
+                        "absorbing[0] = word_of(h);".to_owned(),
+                    ],
+                    "to_scalar(h)".to_owned(),
+                ),
+                _ => (vec![], expression_to_move(r)),
+            };
+            match (l.as_ref(), r.get_type()) {
+                (Expression::Memory(idx, _), Type::Scalar) => {
+                    lines.push(format!("m[{}] = {};", idx, value))
+                }
+                (Expression::Memory(idx, _), Type::Point) => {
+                    lines.push(format!("p = {};", value));
+                    lines.push(format!("m[{}] = p.x;", idx));
+                    lines.push(format!("m[{}] = p.y;", idx + 1));
+                }
+                (Expression::Temp(_), Type::Scalar) => lines.push(format!("t0 = {};", value)),
+                (Expression::Temp(_), Type::Point) => {
+                    lines.push(format!("p = {};", value));
+                    lines.push("t0 = p.x;".to_owned());
+                    lines.push("t1 = p.y;".to_owned());
+                }
+                _ => unreachable!(),
+            }
+            lines
+        }
+        Statement::UpdateHash(e, offset) => match e.get_type() {
+            Type::Scalar => vec![
+                format!("absorbing[{}] = 2;", offset),
+                format!("absorbing[{}] = {};", offset + 1, expression_to_move(e)),
+            ],
+            Type::Point => vec![
+                format!("p = {};", expression_to_move(e)),
+                format!("absorbing[{}] = 1;", offset),
+                format!("absorbing[{}] = p.x;", offset + 1),
+                format!("absorbing[{}] = p.y;", offset + 2),
+            ],
+        },
+        Statement::For {
+            memory_start,
+            memory_end,
+            memory_step,
+            absorbing_start,
+            absorbing_step,
+            t,
+        } => {
+            let n = (memory_end - memory_start) / memory_step + 1;
+            let proof =
+                |delta: usize| format!("proof[{} + i * {}]", memory_start + delta, memory_step);
+            let absorbing = |delta: usize| {
+                format!(
+                    "absorbing[{} + i * {}]",
+                    absorbing_start + delta,
+                    absorbing_step
+                )
+            };
+            let mut lines = vec![format!("for (i in 0..{}) {{", n)];
+            match *t {
+                Type::Scalar => {
+                    lines.push(format!("    {} = 2;", absorbing(0)));
+                    lines.push(format!("    {} = {};", absorbing(1), proof(0)));
+                }
+                Type::Point => {
+                    lines.push(format!("    {} = 1;", absorbing(0)));
+                    lines.push(format!("    {} = {};", absorbing(1), proof(0)));
+                    lines.push(format!("    {} = {};", absorbing(2), proof(1)));
+                }
+            }
+            lines.push("};".to_owned());
+            lines
+        }
+        Statement::ForMMMMul { start, step, n, t } => {
+            assert_eq!(*t, Type::Scalar);
+            let m = |start: usize, step: usize| format!("m[{} + i * {}]", start, step);
+            vec![
+                format!("for (i in 0..{}) {{", n),
+                format!(
+                    "    {} = fr_mul({}, {});",
+                    m(start.0, step.0),
+                    m(start.1, step.1),
+                    m(start.2, step.2)
+                ),
+                "};".to_owned(),
+            ]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_statement_to_move() {
+        let s = Statement::Assign(
+            Rc::new(Expression::Memory(0, Type::Point)),
+            Expression::Add(
+                Rc::new(Expression::Memory(2, Type::Point)),
+                Rc::new(Expression::TransciprtOffset(4, Type::Point)),
+                Type::Point,
+            ),
+            vec![],
+        );
+        assert_eq!(
+            statement_to_move(&s),
+            vec![
+                "p = ecc_add(Point { x: m[2], y: m[3] }, Point { x: proof[4], y: proof[5] });",
+                "m[0] = p.x;",
+                "m[1] = p.y;",
+            ]
+        );
+
+        let s = Statement::Assign(
+            Rc::new(Expression::Memory(0, Type::Scalar)),
+            Expression::Hash(3),
+            vec![],
+        );
+        assert_eq!(
+            statement_to_move(&s),
+            vec![
+                "absorbing[3] = 0;",
+                "h = transcript_hash(&absorbing, 97);",
+                "absorbing[0] = word_of(h);",
+                "m[0] = to_scalar(h);",
+            ]
+        );
+
+        let s = Statement::ForMMMMul {
+            start: (0, 1, 2),
+            step: (3, 3, 3),
+            n: 4,
+            t: Type::Scalar,
+        };
+        assert_eq!(
+            statement_to_move(&s),
+            vec![
+                "for (i in 0..4) {",
+                "    m[0 + i * 3] = fr_mul(m[1 + i * 3], m[2 + i * 3]);",
+                "};",
+            ]
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use super::ctx::{CodeGeneratorCtx, Expression, Statement, Type};
+use super::gas::opcode_len;
+use num_bigint::BigUint;
+use std::rc::Rc;
+
+// Flag of a proof point in a table entry, the point is in memory otherwise.
+const PROOF_POINT_FLAG: usize = 0x8000;
+
+type Entries = Vec<(Rc<Expression>, usize)>;
+
+// The (point, scalar slot) pairs accumulated into the temp by s, and the
+// expression it accumulates into.
+fn extract_entries(s: &Statement) -> Option<(Rc<Expression>, Entries)> {
+    match s {
+        Statement::Assign(l, Expression::MulAdd(p, m, target, Type::Point), _)
+            if l.is_temp() && (p.is_memory() || p.is_transcript()) && m.is_memory() =>
+        {
+            let (p_offset, m_offset) = (p.try_get_offset()?, m.try_get_offset()?);
+            if p_offset >= PROOF_POINT_FLAG || m_offset > 0xffff {
+                return None;
+            }
+            Some((target.clone(), vec![(p.clone(), m_offset)]))
+        }
+        // (proof point, memory scalar) byte pairs
+        Statement::Assign(l, Expression::MulAddPM(target, opcode, Type::Point), _)
+            if l.is_temp() =>
+        {
+            let bytes = opcode.to_bytes_le();
+            let byte = |i: usize| *bytes.get(i).unwrap_or(&0) as usize;
+            let entries = (0..opcode_len(opcode, 2) as usize)
+                .map(|i| {
+                    (
+                        Rc::new(Expression::TransciprtOffset(byte(i * 2), Type::Point)),
+                        byte(i * 2 + 1),
+                    )
+                })
+                .collect();
+            Some((target.clone(), entries))
+        }
+        _ => None,
+    }
+}
+
+// 4 bytes per entry: the point offset, with PROOF_POINT_FLAG for a proof point,
+// then the scalar slot, both big endian.
+pub(crate) fn encode_table(entries: &[(Rc<Expression>, usize)]) -> String {
+    entries
+        .iter()
+        .map(|(p, m)| {
+            let offset = p.try_get_offset().unwrap();
+            let flag = if p.is_transcript() {
+                PROOF_POINT_FLAG
+            } else {
+                0
+            };
+            format!("{:04x}{:04x}", offset | flag, m)
+        })
+        .collect()
+}
+
+struct Run {
+    target: Rc<Expression>,
+    entries: Entries,
+    statements: Vec<Statement>,
+}
+
+impl Run {
+    fn flush(self, out: &mut Vec<Statement>) {
+        if self.statements.len() > 1 {
+            let samples = match self.statements.last() {
+                Some(Statement::Assign(_, _, samples)) => samples.clone(),
+                _ => unreachable!(),
+            };
+            out.push(Statement::Assign(
+                Rc::new(Expression::Temp(Type::Point)),
+                Expression::MulAddTable(self.target, self.entries),
+                samples,
+            ));
+        } else {
+            out.extend(self.statements);
+        }
+    }
+}
+
+// Merges each chain of point multiply-adds into the temp into one
+// ecc_mul_add_table call looping over a constant table of (point, scalar slot)
+// entries, instead of a call per term (or per 16 terms with ecc_mul_add_pm).
+// Runs after aggregate, the memory offsets are final.
+pub(crate) fn loop_msm(mut args: CodeGeneratorCtx) -> CodeGeneratorCtx {
+    let mut statements = vec![];
+    let mut run: Option<Run> = None;
+
+    for s in args.assignments.iter() {
+        match extract_entries(s) {
+            // the temp is read right after its assignment, extend the chain
+            Some((target, mut entries))
+                if run.is_some() && *target == Expression::Temp(Type::Point) =>
+            {
+                let r = run.as_mut().unwrap();
+                r.entries.append(&mut entries);
+                r.statements.push(s.clone());
+            }
+            Some((target, entries)) => {
+                if let Some(r) = run.take() {
+                    r.flush(&mut statements);
+                }
+                run = Some(Run {
+                    target,
+                    entries,
+                    statements: vec![s.clone()],
+                });
+            }
+            None => {
+                if let Some(r) = run.take() {
+                    r.flush(&mut statements);
+                }
+                statements.push(s.clone());
+            }
+        }
+    }
+    if let Some(r) = run.take() {
+        r.flush(&mut statements);
+    }
+
+    args.assignments = statements;
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_generator::ctx::G2Point;
+
+    fn g2() -> G2Point {
+        G2Point {
+            x: (BigUint::from(1u64), BigUint::from(2u64)),
+            y: (BigUint::from(3u64), BigUint::from(4u64)),
+        }
+    }
+
+    #[test]
+    fn test_loop_msm() {
+        let temp = Rc::new(Expression::Temp(Type::Point));
+        let scalar = |idx| Rc::new(Expression::Memory(idx, Type::Scalar));
+        let point = |idx| Rc::new(Expression::Memory(idx, Type::Point));
+        // (proof[4], m[1]), (proof[6], m[2]) terminated by 0xffff
+        let opcode = (BigUint::from(0xffffu64) << 32u8) + BigUint::from(0x0206_0104u64);
+        let args = CodeGeneratorCtx {
+            wx: Expression::Temp(Type::Point),
+            wg: Expression::Temp(Type::Point),
+            target_circuit_s_g2: g2(),
+            target_circuit_n_g2: g2(),
+            verify_circuit_s_g2: g2(),
+            verify_circuit_n_g2: g2(),
+            assignments: vec![
+                Statement::Assign(
+                    temp.clone(),
+                    Expression::MulAdd(point(8), scalar(0), point(10), Type::Point),
+                    vec![],
+                ),
+                Statement::Assign(
+                    temp.clone(),
+                    Expression::MulAddPM(temp.clone(), opcode, Type::Point),
+                    vec![],
+                ),
+                Statement::Assign(point(12), (*temp).clone(), vec![]),
+            ],
+            memory_size: 14,
+            instance_size: 4,
+            absorbing_length: 1,
+        };
+
+        let args = loop_msm(args);
+        assert_eq!(args.assignments.len(), 2);
+        match &args.assignments[0] {
+            Statement::Assign(l, Expression::MulAddTable(target, entries), _) => {
+                assert!(l.is_temp());
+                assert_eq!(*target, point(10));
+                assert_eq!(encode_table(entries), "000800008004000180060002");
+            }
+            _ => unreachable!(),
+        }
+    }
+}
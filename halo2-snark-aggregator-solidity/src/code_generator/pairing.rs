@@ -0,0 +1,131 @@
+use super::ctx::{CodeGeneratorCtx, G2Point};
+use num_bigint::BigUint;
+
+// Words of a term in the input of the pairing precompile: g1 x, y then g2 x0, x1, y0, y1.
+pub(crate) const TERM_WORDS: usize = 6;
+
+// G1 side of a term of the final pairing check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PairingG1 {
+    Wx,
+    Wg,
+    // point at this word of target_circuit_final_pair
+    FinalPair(usize),
+}
+
+// e(g1, g2), the check passes if the product of all the terms is 1.
+pub(crate) struct PairingTerm<'a> {
+    pub(crate) g1: PairingG1,
+    pub(crate) g2: &'a G2Point,
+    // g1 is multiplied by the coefficient r before the call
+    pub(crate) scaled: bool,
+}
+
+// The verify circuit check e(w_x, s) e(w_g, -g) == 1 and the target circuit one
+// on the final pair, as the 4 terms of a single call to the pairing precompile.
+// The target circuit terms are scaled by r, the keccak of the unscaled input, so
+// the product can't be 1 unless both checks pass, except with negligible probability.
+pub(crate) fn collect_pairs(args: &CodeGeneratorCtx) -> Vec<PairingTerm<'_>> {
+    vec![
+        PairingTerm {
+            g1: PairingG1::Wx,
+            g2: &args.verify_circuit_s_g2,
+            scaled: false,
+        },
+        PairingTerm {
+            g1: PairingG1::Wg,
+            g2: &args.verify_circuit_n_g2,
+            scaled: false,
+        },
+        PairingTerm {
+            g1: PairingG1::FinalPair(0),
+            g2: &args.target_circuit_s_g2,
+            scaled: true,
+        },
+        PairingTerm {
+            g1: PairingG1::FinalPair(2),
+            g2: &args.target_circuit_n_g2,
+            scaled: true,
+        },
+    ]
+}
+
+// Statements writing the unscaled terms into the precompile input: store_g1 writes a
+// G1 point at a word of the input, store_word a G2 coordinate.
+pub(crate) fn pairing_input(
+    terms: &[PairingTerm],
+    store_g1: impl Fn(PairingG1, usize) -> Vec<String>,
+    store_word: impl Fn(&BigUint, usize) -> String,
+) -> Vec<String> {
+    let mut statements = vec![];
+    for (i, term) in terms.iter().enumerate() {
+        let base = i * TERM_WORDS;
+        statements.append(&mut store_g1(term.g1, base));
+        for (j, w) in [&term.g2.x.0, &term.g2.x.1, &term.g2.y.0, &term.g2.y.1]
+            .into_iter()
+            .enumerate()
+        {
+            statements.push(store_word(w, base + 2 + j));
+        }
+    }
+    statements
+}
+
+// Statements replacing the G1 point at each word given to scale by r times it.
+pub(crate) fn pairing_scale(
+    terms: &[PairingTerm],
+    scale: impl Fn(usize) -> Vec<String>,
+) -> Vec<String> {
+    terms
+        .iter()
+        .enumerate()
+        .filter(|(_, term)| term.scaled)
+        .flat_map(|(i, _)| scale(i * TERM_WORDS))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_generator::ctx::{Expression, Type};
+
+    fn g2(v: u64) -> G2Point {
+        G2Point {
+            x: (BigUint::from(v), BigUint::from(v + 1)),
+            y: (BigUint::from(v + 2), BigUint::from(v + 3)),
+        }
+    }
+
+    #[test]
+    fn test_pairing_input() {
+        let args = CodeGeneratorCtx {
+            wx: Expression::Memory(0, Type::Point),
+            wg: Expression::Memory(1, Type::Point),
+            target_circuit_s_g2: g2(30),
+            target_circuit_n_g2: g2(40),
+            verify_circuit_s_g2: g2(10),
+            verify_circuit_n_g2: g2(20),
+            assignments: vec![],
+            memory_size: 2,
+            instance_size: 4,
+            absorbing_length: 1,
+        };
+        let terms = collect_pairs(&args);
+
+        let input = pairing_input(
+            &terms,
+            |g1, w| vec![format!("{:?}@{}", g1, w)],
+            |v, w| format!("{}@{}", v, w),
+        );
+        assert_eq!(input.len(), 4 * 5);
+        assert_eq!(input[0], "Wx@0");
+        assert_eq!(input[1..5], ["10@2", "11@3", "12@4", "13@5"]);
+        assert_eq!(input[5], "Wg@6");
+        assert_eq!(input[10], "FinalPair(0)@12");
+        assert_eq!(input[15], "FinalPair(2)@18");
+        assert_eq!(input[19], "43@23");
+
+        let scale = pairing_scale(&terms, |w| vec![w.to_string()]);
+        assert_eq!(scale, ["12", "18"]);
+    }
+}
@@ -0,0 +1,136 @@
+use super::ctx::{CodeGeneratorCtx, Expression, Statement, Type};
+use super::gas::opcode_len;
+
+// proof(i) of the yul verifier: calldataload of the proof offset, two adds and the
+// calldataload of the word
+const CALLDATA_READ_GAS: u64 = 12;
+// push of the word address and mload
+const MEMORY_READ_GAS: u64 = 6;
+// calldatacopy of the proof: base cost of the call, then the copy and the memory
+// expansion of each word
+const COPY_BASE_GAS: u64 = 12;
+const COPY_WORD_GAS: u64 = 6;
+
+// Where the yul verifier reads the proof words from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ProofParsing {
+    // each read loads the word from the calldata, cheaper when words are read once
+    Calldata,
+    // the proof is copied to memory once, cheaper when words are read many times
+    Memory,
+}
+
+fn words(t: &Type) -> u64 {
+    match t {
+        Type::Scalar => 1,
+        Type::Point => 2,
+    }
+}
+
+fn expression_reads(e: &Expression) -> u64 {
+    match e {
+        Expression::TransciprtOffset(_, t) => words(t),
+        Expression::Add(l, r, _)
+        | Expression::Sub(l, r, _)
+        | Expression::Mul(l, r, _)
+        | Expression::Div(l, r, _) => expression_reads(l) + expression_reads(r),
+        Expression::MulAdd(a, b, c, _) => {
+            expression_reads(a) + expression_reads(b) + expression_reads(c)
+        }
+        Expression::Pow(base, _, _) => expression_reads(base),
+        // a proof word per (proof, memory) entry
+        Expression::MulAddPM(target, opcode, t) => {
+            opcode_len(opcode, 2) * words(t) + expression_reads(target)
+        }
+        _ => 0,
+    }
+}
+
+// Proof words read by the statements, counting each read of a word.
+fn proof_reads(args: &CodeGeneratorCtx) -> u64 {
+    let reads = args
+        .assignments
+        .iter()
+        .map(|s| match s {
+            Statement::Assign(_, r, _) => expression_reads(r),
+            Statement::UpdateHash(e, _) => expression_reads(e),
+            Statement::For {
+                memory_start,
+                memory_end,
+                memory_step,
+                t,
+                ..
+            } => ((memory_end - memory_start) / memory_step) as u64 * words(t),
+            Statement::ForMMMMul { .. } => 0,
+        })
+        .sum::<u64>();
+    reads + expression_reads(&args.wx) + expression_reads(&args.wg)
+}
+
+// Copies the proof to memory when the reads it saves pay for the copy.
+pub(crate) fn choose_proof_parsing(args: &CodeGeneratorCtx, proof_len: usize) -> ProofParsing {
+    let reads = proof_reads(args);
+    let copy = COPY_BASE_GAS + COPY_WORD_GAS * proof_len as u64;
+    if reads * (CALLDATA_READ_GAS - MEMORY_READ_GAS) > copy {
+        ProofParsing::Memory
+    } else {
+        ProofParsing::Calldata
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_generator::ctx::G2Point;
+    use num_bigint::BigUint;
+    use std::rc::Rc;
+
+    fn g2() -> G2Point {
+        G2Point {
+            x: (BigUint::from(1u64), BigUint::from(2u64)),
+            y: (BigUint::from(3u64), BigUint::from(4u64)),
+        }
+    }
+
+    fn ctx(assignments: Vec<Statement>) -> CodeGeneratorCtx {
+        CodeGeneratorCtx {
+            wx: Expression::Memory(0, Type::Point),
+            wg: Expression::Memory(0, Type::Point),
+            target_circuit_s_g2: g2(),
+            target_circuit_n_g2: g2(),
+            verify_circuit_s_g2: g2(),
+            verify_circuit_n_g2: g2(),
+            assignments,
+            memory_size: 2,
+            instance_size: 4,
+            absorbing_length: 4,
+        }
+    }
+
+    #[test]
+    fn test_choose_proof_parsing() {
+        let proof = |offset| Rc::new(Expression::TransciprtOffset(offset, Type::Scalar));
+        let m = |idx| Rc::new(Expression::Memory(idx, Type::Scalar));
+
+        // each of the 4 words read once
+        let once = (0..4)
+            .map(|i| Statement::UpdateHash(proof(i), i))
+            .collect::<Vec<_>>();
+        assert_eq!(proof_reads(&ctx(once.clone())), 4);
+        assert_eq!(choose_proof_parsing(&ctx(once), 4), ProofParsing::Calldata);
+
+        // then read again in 8 products
+        let mut many = (0..4)
+            .map(|i| Statement::UpdateHash(proof(i), i))
+            .collect::<Vec<_>>();
+        many.extend((0..8).map(|i| {
+            Statement::Assign(
+                m(1),
+                Expression::Mul(proof(i % 4), proof((i + 1) % 4), Type::Scalar),
+                vec![],
+            )
+        }));
+        assert_eq!(proof_reads(&ctx(many.clone())), 20);
+        assert_eq!(choose_proof_parsing(&ctx(many), 4), ProofParsing::Memory);
+    }
+}
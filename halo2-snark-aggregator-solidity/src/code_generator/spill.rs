@@ -0,0 +1,261 @@
+use super::ctx::{CodeGeneratorCtx, Expression, Statement, Type};
+use std::rc::Rc;
+
+// Stack slots an expression may use in get_wx_wg without "stack too deep" on
+// plain solc: the 16 reachable slots minus the locals (m, absorbing, proof,
+// instances, t0, t1) and the return address.
+const MAX_EXPRESSION_SLOTS: usize = 8;
+
+fn width(t: &Type) -> usize {
+    match t {
+        Type::Scalar => 1,
+        Type::Point => 2,
+    }
+}
+
+fn children(e: &Expression) -> Vec<Rc<Expression>> {
+    match e {
+        Expression::Add(l, r, _)
+        | Expression::Sub(l, r, _)
+        | Expression::Mul(l, r, _)
+        | Expression::Div(l, r, _) => vec![l.clone(), r.clone()],
+        Expression::MulAdd(a, b, c, _) => vec![a.clone(), b.clone(), c.clone()],
+        Expression::Pow(base, _, _) => vec![base.clone()],
+        Expression::MulAddPM(target, _, _) | Expression::MulAddTable(target, _) => {
+            vec![target.clone()]
+        }
+        _ => vec![],
+    }
+}
+
+fn with_children(e: &Expression, mut c: Vec<Rc<Expression>>) -> Expression {
+    match e {
+        Expression::Add(_, _, t) => Expression::Add(c.remove(0), c.remove(0), t.clone()),
+        Expression::Sub(_, _, t) => Expression::Sub(c.remove(0), c.remove(0), t.clone()),
+        Expression::Mul(_, _, t) => Expression::Mul(c.remove(0), c.remove(0), t.clone()),
+        Expression::Div(_, _, t) => Expression::Div(c.remove(0), c.remove(0), t.clone()),
+        Expression::MulAdd(_, _, _, t) => {
+            Expression::MulAdd(c.remove(0), c.remove(0), c.remove(0), t.clone())
+        }
+        Expression::Pow(_, exp, t) => Expression::Pow(c.remove(0), *exp, t.clone()),
+        Expression::MulAddPM(_, opcode, t) => {
+            Expression::MulAddPM(c.remove(0), opcode.clone(), t.clone())
+        }
+        Expression::MulAddTable(_, entries) => {
+            Expression::MulAddTable(c.remove(0), entries.clone())
+        }
+        _ => e.clone(),
+    }
+}
+
+// Arguments pushed before the children: m, proof (offset and length) and the opcode
+// of *_mul_add_pm or the table of ecc_mul_add_table, the exponent of fr_pow.
+fn extra_slots(e: &Expression) -> usize {
+    match e {
+        Expression::MulAddPM(..) | Expression::MulAddTable(..) => 4,
+        Expression::Pow(..) => 1,
+        _ => 0,
+    }
+}
+
+// Stack slots used to evaluate e: each argument stays on the stack while the
+// next ones are evaluated.
+fn stack_slots(e: &Expression) -> usize {
+    match e {
+        Expression::MulAddMT(..) => 5,
+        Expression::Hash(..) => 2,
+        _ => {
+            let mut used = extra_slots(e);
+            let mut max = used.max(width(&e.get_type()));
+            for c in children(e) {
+                max = max.max(used + stack_slots(&c));
+                used += width(&c.get_type());
+            }
+            max.max(used)
+        }
+    }
+}
+
+fn find_temp(e: &Expression) -> Option<Type> {
+    match e {
+        Expression::Temp(t) => Some(t.clone()),
+        _ => children(e).iter().find_map(|c| find_temp(c)),
+    }
+}
+
+fn replace_temp(e: &Expression, m: &Rc<Expression>) -> Expression {
+    if e.is_temp() {
+        (**m).clone()
+    } else {
+        let c = children(e)
+            .iter()
+            .map(|c| Rc::new(replace_temp(c, m)))
+            .collect::<Vec<_>>();
+        with_children(e, c)
+    }
+}
+
+// Scratch memory after the memory of the statements, reused by each statement.
+struct Spiller {
+    base: usize,
+    next: usize,
+    max: usize,
+    statements: Vec<Statement>,
+}
+
+impl Spiller {
+    fn assign(&mut self, e: Expression) -> Rc<Expression> {
+        let t = e.get_type();
+        let m = Rc::new(Expression::Memory(self.base + self.next, t.clone()));
+        self.next += width(&t);
+        self.max = self.max.max(self.next);
+        self.statements
+            .push(Statement::Assign(m.clone(), e, vec![]));
+        m
+    }
+
+    fn spill(&mut self, e: &Expression) -> Expression {
+        let mut c = children(e)
+            .iter()
+            .map(|c| Rc::new(self.spill(c)))
+            .collect::<Vec<_>>();
+        let mut e = with_children(e, c.clone());
+        // spill the deepest argument until the expression fits
+        while stack_slots(&e) > MAX_EXPRESSION_SLOTS {
+            let (i, _) = c
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| !children(c).is_empty())
+                .max_by_key(|(i, c)| (stack_slots(c), std::cmp::Reverse(*i)))
+                .expect("expression too deep with leaf arguments only");
+            c[i] = self.assign((*c[i]).clone());
+            e = with_children(&e, c.clone());
+        }
+        e
+    }
+
+    // Returns e split into the scratch assignments and the expression reading them,
+    // the scratch words used start at start.
+    // A temp must be read right after its assignment (see split_statements),
+    // so it is copied first if e reads it.
+    fn spill_expression(&mut self, e: &Expression, start: usize) -> (Vec<Statement>, Expression) {
+        self.next = start;
+        self.statements = vec![];
+        if stack_slots(e) <= MAX_EXPRESSION_SLOTS {
+            return (vec![], e.clone());
+        }
+
+        let e = match find_temp(e) {
+            Some(t) => {
+                let m = self.assign(Expression::Temp(t));
+                replace_temp(e, &m)
+            }
+            None => e.clone(),
+        };
+        let e = self.spill(&e);
+        (std::mem::take(&mut self.statements), e)
+    }
+}
+
+// Moves the arguments of expressions deeper than MAX_EXPRESSION_SLOTS into
+// scratch memory, so the verifier compiles without via-ir.
+pub(crate) fn spill_expressions(mut args: CodeGeneratorCtx) -> CodeGeneratorCtx {
+    let mut spiller = Spiller {
+        base: args.memory_size,
+        next: 0,
+        max: 0,
+        statements: vec![],
+    };
+
+    let mut statements = vec![];
+    for s in args.assignments.iter() {
+        match s {
+            Statement::Assign(l, r, samples) => {
+                let (mut spilled, r) = spiller.spill_expression(r, 0);
+                statements.append(&mut spilled);
+                statements.push(Statement::Assign(l.clone(), r, samples.clone()));
+            }
+            Statement::UpdateHash(e, offset) => {
+                let (mut spilled, e) = spiller.spill_expression(e, 0);
+                statements.append(&mut spilled);
+                statements.push(Statement::UpdateHash(Rc::new(e), *offset));
+            }
+            _ => statements.push(s.clone()),
+        }
+    }
+
+    // wx and wg are evaluated together in the return statement,
+    // so the scratch words of wg follow the ones of wx
+    let (mut spilled, wx) = spiller.spill_expression(&args.wx, 0);
+    statements.append(&mut spilled);
+    let (mut spilled, wg) = spiller.spill_expression(&args.wg, spiller.next);
+    statements.append(&mut spilled);
+
+    args.assignments = statements;
+    args.wx = wx;
+    args.wg = wg;
+    args.memory_size += spiller.max;
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_generator::ctx::G2Point;
+    use num_bigint::BigUint;
+
+    fn g2() -> G2Point {
+        G2Point {
+            x: (BigUint::from(1u64), BigUint::from(2u64)),
+            y: (BigUint::from(3u64), BigUint::from(4u64)),
+        }
+    }
+
+    fn point(idx: usize) -> Rc<Expression> {
+        Rc::new(Expression::Memory(idx, Type::Point))
+    }
+
+    #[test]
+    fn test_spill_expressions() {
+        let s = Rc::new(Expression::TransciprtOffset(0, Type::Scalar));
+        // ecc_add(ecc_add(ecc_add(ecc_add(p, q), q), q), q) keeps a point per level
+        let mut deep = point(0);
+        for _ in 0..4 {
+            deep = Rc::new(Expression::Add(point(2), deep, Type::Point));
+        }
+        let deep = Expression::MulAdd(deep, s.clone(), point(4), Type::Point);
+        assert!(stack_slots(&deep) > MAX_EXPRESSION_SLOTS);
+
+        let args = CodeGeneratorCtx {
+            wx: (*point(0)).clone(),
+            wg: (*point(2)).clone(),
+            target_circuit_s_g2: g2(),
+            target_circuit_n_g2: g2(),
+            verify_circuit_s_g2: g2(),
+            verify_circuit_n_g2: g2(),
+            assignments: vec![Statement::Assign(point(6), deep, vec![])],
+            memory_size: 8,
+            instance_size: 4,
+            absorbing_length: 1,
+        };
+
+        let args = spill_expressions(args);
+        assert!(args.assignments.len() > 1);
+        for s in args.assignments.iter() {
+            match s {
+                Statement::Assign(l, r, _) => {
+                    assert!(stack_slots(r) <= MAX_EXPRESSION_SLOTS);
+                    if let Expression::Memory(idx, _) = l.as_ref() {
+                        assert!(*idx < args.memory_size);
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+        // the original assignment stays last
+        match args.assignments.last() {
+            Some(Statement::Assign(l, _, _)) => assert_eq!(**l, *point(6)),
+            _ => unreachable!(),
+        }
+    }
+}
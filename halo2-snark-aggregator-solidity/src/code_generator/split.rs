@@ -0,0 +1,181 @@
+use super::ctx::{Expression, Statement, Type};
+
+// EIP-170 limit on the size of deployed bytecode.
+pub(crate) const EIP170_CODE_SIZE_LIMIT: usize = 24576;
+// Estimated bytecode of the fr/ecc helpers, pairing and verify(),
+// every contract of a split verifier carries (part of) them. Only the first
+// split uses it, with solc the steps are sized again from their compiled code.
+const BASE_CODE_SIZE: usize = 9000;
+// Headroom for the estimate being off.
+pub(crate) const CODE_SIZE_MARGIN: usize = 2048;
+
+// Estimated bytes of statements a step can hold before it is compiled.
+pub(crate) const ESTIMATED_BUDGET: usize =
+    EIP170_CODE_SIZE_LIMIT - CODE_SIZE_MARGIN - BASE_CODE_SIZE;
+
+fn width(t: &Type) -> usize {
+    match t {
+        Type::Scalar => 1,
+        Type::Point => 2,
+    }
+}
+
+// Rough bytecode size of an expression, leaves are a few pushes and
+// a load, operations add a call into one of the helpers.
+fn estimate_expression_size(e: &Expression) -> usize {
+    match e {
+        Expression::Memory(_, t)
+        | Expression::TransciprtOffset(_, t)
+        | Expression::InstanceOffset(_, t)
+        | Expression::TmpBufOffset(_, t)
+        | Expression::VkOffset(_, t)
+        | Expression::Temp(t) => 12 * width(t),
+        Expression::Scalar(_) => 34,
+        Expression::Point(_, _) => 68,
+        Expression::Add(l, r, t)
+        | Expression::Sub(l, r, t)
+        | Expression::Mul(l, r, t)
+        | Expression::Div(l, r, t) => {
+            20 * width(t) + estimate_expression_size(l) + estimate_expression_size(r)
+        }
+        Expression::MulAdd(a, b, c, t) => {
+            24 * width(t)
+                + estimate_expression_size(a)
+                + estimate_expression_size(b)
+                + estimate_expression_size(c)
+        }
+        Expression::MulAddPM(e, _, t) => 56 * width(t) + estimate_expression_size(e),
+        Expression::MulAddMT(_, _) => 56,
+        Expression::MulAddTable(e, entries) => 64 + 4 * entries.len() + estimate_expression_size(e),
+        Expression::Pow(base, _, _) => 40 + estimate_expression_size(base),
+        Expression::Hash(_) => 24,
+    }
+}
+
+pub(crate) fn estimate_code_size(s: &Statement) -> usize {
+    match s {
+        Statement::Assign(l, r, _) => 16 * width(&l.get_type()) + estimate_expression_size(r),
+        Statement::UpdateHash(e, _) => 24 + estimate_expression_size(e),
+        Statement::For { .. } | Statement::ForMMMMul { .. } => 96,
+    }
+}
+
+// Splits the statements of get_wx_wg so that the estimated size of each part is under budget,
+// returns a single part if the whole verifier is.
+// A temp is only read by the statement right after its assignment,
+// so an assignment to a temp is moved along with its reader.
+pub(crate) fn split_statements(statements: &[Statement], budget: usize) -> Vec<Vec<Statement>> {
+    let total = statements.iter().map(estimate_code_size).sum::<usize>();
+    if total <= budget {
+        return vec![statements.to_vec()];
+    }
+
+    let mut parts = vec![];
+    let mut part = vec![];
+    let mut size = 0;
+    for s in statements {
+        let s_size = estimate_code_size(s);
+        if size + s_size > budget && !part.is_empty() {
+            let carried = if ends_with_temp(&part) {
+                part.pop()
+            } else {
+                None
+            };
+            if !part.is_empty() {
+                parts.push(part);
+            }
+            part = carried.into_iter().collect();
+            size = part.iter().map(estimate_code_size).sum();
+        }
+        part.push(s.clone());
+        size += s_size;
+    }
+    if !part.is_empty() {
+        parts.push(part);
+    }
+    parts
+}
+
+// Budget of the next split from the compiled code size of each part of the last one, as
+// (estimated size, code size). The code size is fitted as base + scale * estimate, one part
+// only gives the scale over the estimated base.
+pub(crate) fn fit_budget(sizes: &[(usize, usize)]) -> Option<usize> {
+    let target = (EIP170_CODE_SIZE_LIMIT - CODE_SIZE_MARGIN) as f64;
+    let min = sizes.iter().min_by_key(|(estimate, _)| *estimate)?;
+    let max = sizes.iter().max_by_key(|(estimate, _)| *estimate)?;
+    let (base, scale) = match (*min, *max) {
+        ((e0, s0), (e1, s1)) if e1 > e0 && s1 > s0 => {
+            let scale = (s1 - s0) as f64 / (e1 - e0) as f64;
+            (s1 as f64 - scale * e1 as f64, scale)
+        }
+        (_, (e1, s1)) if e1 > 0 && s1 > BASE_CODE_SIZE => (
+            BASE_CODE_SIZE as f64,
+            (s1 - BASE_CODE_SIZE) as f64 / e1 as f64,
+        ),
+        _ => return None,
+    };
+    Some(((target - base.max(0.0)) / scale).max(0.0) as usize)
+}
+
+fn ends_with_temp(part: &[Statement]) -> bool {
+    matches!(part.last(), Some(Statement::Assign(l, _, _)) if l.is_temp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    fn mul_add(i: usize) -> Statement {
+        Statement::Assign(
+            Rc::new(Expression::Memory(i, Type::Point)),
+            Expression::MulAdd(
+                Rc::new(Expression::Memory(i + 2, Type::Point)),
+                Rc::new(Expression::TransciprtOffset(i, Type::Scalar)),
+                Rc::new(Expression::Memory(i + 4, Type::Point)),
+                Type::Point,
+            ),
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_split_statements() {
+        let small = (0..10).map(mul_add).collect::<Vec<_>>();
+        assert_eq!(split_statements(&small, ESTIMATED_BUDGET).len(), 1);
+
+        let mut large = vec![];
+        for i in 0..2000 {
+            large.push(Statement::Assign(
+                Rc::new(Expression::Temp(Type::Scalar)),
+                Expression::Scalar(i.into()),
+                vec![],
+            ));
+            large.push(mul_add(i));
+        }
+        let parts = split_statements(&large, ESTIMATED_BUDGET);
+        assert!(parts.len() > 1);
+        assert_eq!(parts.iter().map(|p| p.len()).sum::<usize>(), large.len());
+        for part in parts.iter() {
+            assert!(!ends_with_temp(part));
+            let size = part.iter().map(estimate_code_size).sum::<usize>();
+            assert!(size + BASE_CODE_SIZE + CODE_SIZE_MARGIN <= EIP170_CODE_SIZE_LIMIT);
+        }
+
+        let smaller = split_statements(&large, ESTIMATED_BUDGET / 2);
+        assert!(smaller.len() > parts.len());
+    }
+
+    #[test]
+    fn test_fit_budget() {
+        // code size 10000 + 2 * estimate
+        let sizes = [(4000, 18000), (6000, 22000)];
+        let target = EIP170_CODE_SIZE_LIMIT - CODE_SIZE_MARGIN;
+        assert_eq!(fit_budget(&sizes), Some((target - 10000) / 2));
+        assert_eq!(fit_budget(&[]), None);
+
+        let budget = fit_budget(&[(ESTIMATED_BUDGET, 30000)]).unwrap();
+        assert!(budget < ESTIMATED_BUDGET);
+        assert!(BASE_CODE_SIZE + budget * (30000 - BASE_CODE_SIZE) / ESTIMATED_BUDGET <= target);
+    }
+}
@@ -0,0 +1,241 @@
+use super::const_fold::fold_constants;
+use super::ctx::{CodeGeneratorCtx, Expression, Statement, Type};
+use num_bigint::BigUint;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+// IR of the field and curve operations recorded by the chips, before memory_optimize
+// reuses the memory slots: every slot is assigned once, so a slot names the value
+// of the instruction defining it. The passes below rewrite the instructions, then
+// they are lowered back to statements for the passes and backends downstream.
+enum Inst {
+    // m[value] = expression, with the samples of the statement
+    Def(usize, Type, Expression, Vec<BigUint>),
+    // absorbs the expression at this offset of the absorbing buffer
+    Absorb(Rc<Expression>, usize),
+}
+
+pub(crate) struct Ssa {
+    insts: Vec<Inst>,
+    wx: Expression,
+    wg: Expression,
+}
+
+fn uses(e: &Expression, values: &mut Vec<usize>) {
+    match e {
+        Expression::Memory(idx, _) => values.push(*idx),
+        Expression::Add(l, r, _)
+        | Expression::Sub(l, r, _)
+        | Expression::Mul(l, r, _)
+        | Expression::Div(l, r, _) => {
+            uses(l, values);
+            uses(r, values);
+        }
+        Expression::MulAdd(a, b, c, _) => {
+            uses(a, values);
+            uses(b, values);
+            uses(c, values);
+        }
+        Expression::Pow(base, _, _) => uses(base, values),
+        Expression::TransciprtOffset(..)
+        | Expression::InstanceOffset(..)
+        | Expression::TmpBufOffset(..)
+        | Expression::Point(..)
+        | Expression::Scalar(..)
+        | Expression::Hash(..) => {}
+        // built by aggregate and later passes only
+        _ => unreachable!(),
+    }
+}
+
+// Squeezing a challenge reads and updates the transcript state, every other
+// expression only depends on the values it uses.
+fn is_pure(e: &Expression) -> bool {
+    !matches!(e, Expression::Hash(_))
+}
+
+impl Ssa {
+    pub(crate) fn build(args: &CodeGeneratorCtx) -> Ssa {
+        let mut defined = HashSet::new();
+        let insts = args
+            .assignments
+            .iter()
+            .map(|s| match s {
+                Statement::Assign(l, r, samples) => match l.as_ref() {
+                    Expression::Memory(idx, t) => {
+                        assert!(defined.insert(*idx), "m[{}] is assigned twice", idx);
+                        Inst::Def(*idx, t.clone(), r.clone(), samples.clone())
+                    }
+                    _ => unreachable!(),
+                },
+                Statement::UpdateHash(e, offset) => Inst::Absorb(e.clone(), *offset),
+                _ => unreachable!(),
+            })
+            .collect();
+
+        Ssa {
+            insts,
+            wx: args.wx.clone(),
+            wg: args.wg.clone(),
+        }
+    }
+
+    // Common subexpression elimination: a pure definition computing the same
+    // expression as an earlier one is replaced by the earlier value.
+    pub(crate) fn cse(&mut self) {
+        let mut replaced = HashMap::new();
+        let mut seen = HashMap::new();
+        let mut insts = vec![];
+        for inst in self.insts.drain(..) {
+            match inst {
+                Inst::Def(value, t, e, samples) => {
+                    let e = e.substitute(&replaced);
+                    if is_pure(&e) {
+                        if let Some(first) = seen.get(&e) {
+                            replaced.insert(value, *first);
+                            continue;
+                        }
+                        seen.insert(e.clone(), value);
+                    }
+                    insts.push(Inst::Def(value, t, e, samples));
+                }
+                Inst::Absorb(e, offset) => {
+                    insts.push(Inst::Absorb(Rc::new(e.substitute(&replaced)), offset))
+                }
+            }
+        }
+        self.insts = insts;
+        self.wx = self.wx.substitute(&replaced);
+        self.wg = self.wg.substitute(&replaced);
+    }
+
+    // Dead code elimination: drops the pure definitions neither absorbed, used by
+    // w_x and w_g nor by a live definition.
+    pub(crate) fn dce(&mut self) {
+        let mut live = vec![];
+        uses(&self.wx, &mut live);
+        uses(&self.wg, &mut live);
+        let mut live: HashSet<usize> = live.into_iter().collect();
+
+        let mut insts = vec![];
+        for inst in self.insts.drain(..).rev() {
+            let mut values = vec![];
+            match &inst {
+                Inst::Def(value, _, e, _) => {
+                    if !live.contains(value) && is_pure(e) {
+                        continue;
+                    }
+                    uses(e, &mut values);
+                }
+                Inst::Absorb(e, _) => uses(e, &mut values),
+            }
+            live.extend(values);
+            insts.push(inst);
+        }
+        insts.reverse();
+        self.insts = insts;
+    }
+
+    pub(crate) fn lower(self, args: CodeGeneratorCtx) -> CodeGeneratorCtx {
+        let assignments = self
+            .insts
+            .into_iter()
+            .map(|inst| match inst {
+                Inst::Def(value, t, e, samples) => {
+                    Statement::Assign(Rc::new(Expression::Memory(value, t)), e, samples)
+                }
+                Inst::Absorb(e, offset) => Statement::UpdateHash(e, offset),
+            })
+            .collect();
+        CodeGeneratorCtx {
+            wx: self.wx,
+            wg: self.wg,
+            assignments,
+            ..args
+        }
+    }
+}
+
+// Constant folding, then CSE and DCE on the SSA form of the statements.
+pub(crate) fn optimize(args: CodeGeneratorCtx) -> CodeGeneratorCtx {
+    let args = fold_constants(args);
+    let mut ssa = Ssa::build(&args);
+    ssa.cse();
+    ssa.dce();
+    ssa.lower(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_generator::ctx::G2Point;
+
+    fn g2() -> G2Point {
+        G2Point {
+            x: (BigUint::from(1u64), BigUint::from(2u64)),
+            y: (BigUint::from(3u64), BigUint::from(4u64)),
+        }
+    }
+
+    #[test]
+    fn test_optimize() {
+        let m = |idx| Rc::new(Expression::Memory(idx, Type::Scalar));
+        let proof = |offset| Rc::new(Expression::TransciprtOffset(offset, Type::Scalar));
+        let args = CodeGeneratorCtx {
+            wx: Expression::Memory(4, Type::Point),
+            wg: Expression::Memory(4, Type::Point),
+            target_circuit_s_g2: g2(),
+            target_circuit_n_g2: g2(),
+            verify_circuit_s_g2: g2(),
+            verify_circuit_n_g2: g2(),
+            assignments: vec![
+                Statement::UpdateHash(proof(0), 0),
+                // squeezes stay even when unused
+                Statement::Assign(m(0), Expression::Hash(0), vec![]),
+                Statement::Assign(
+                    m(1),
+                    Expression::Mul(proof(0), proof(1), Type::Scalar),
+                    vec![],
+                ),
+                // same as m1, replaced by it
+                Statement::Assign(
+                    m(2),
+                    Expression::Mul(proof(0), proof(1), Type::Scalar),
+                    vec![],
+                ),
+                // dead
+                Statement::Assign(m(3), Expression::Add(m(2), m(0), Type::Scalar), vec![]),
+                Statement::Assign(
+                    Rc::new(Expression::Memory(4, Type::Point)),
+                    Expression::MulAdd(
+                        Rc::new(Expression::TransciprtOffset(2, Type::Point)),
+                        m(2),
+                        Rc::new(Expression::TransciprtOffset(4, Type::Point)),
+                        Type::Point,
+                    ),
+                    vec![],
+                ),
+            ],
+            memory_size: 6,
+            instance_size: 4,
+            absorbing_length: 1,
+        };
+
+        let args = optimize(args);
+        let defined = args
+            .assignments
+            .iter()
+            .filter_map(|s| match s {
+                Statement::Assign(l, _, _) => l.try_get_offset(),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(defined, [0, 1, 4]);
+        match &args.assignments[3] {
+            Statement::Assign(_, Expression::MulAdd(_, s, _, _), _) => {
+                assert_eq!(**s, Expression::Memory(1, Type::Scalar))
+            }
+            _ => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use super::ctx::{CodeGeneratorCtx, Expression, Statement, Type};
+use num_bigint::BigUint;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Constants of the generated code (fixed commitments, omega powers, the
+// transcript initial state...) moved out of the verifier into a table
+// deployed as the code of a separate VerifyingKey contract, so verifiers of
+// circuits with the same shape share the same logic bytecode.
+#[derive(Default)]
+struct VkTable {
+    words: Vec<BigUint>,
+    lookup: HashMap<Vec<BigUint>, usize>,
+}
+
+impl VkTable {
+    fn offset(&mut self, words: Vec<BigUint>) -> usize {
+        match self.lookup.get(&words) {
+            Some(offset) => *offset,
+            None => {
+                let offset = self.words.len();
+                self.words.extend(words.iter().cloned());
+                self.lookup.insert(words, offset);
+                offset
+            }
+        }
+    }
+
+    fn replace(&mut self, e: &Expression) -> Expression {
+        match e {
+            Expression::Scalar(s) => {
+                Expression::VkOffset(self.offset(vec![s.clone()]), Type::Scalar)
+            }
+            Expression::Point(x, y) => {
+                Expression::VkOffset(self.offset(vec![x.clone(), y.clone()]), Type::Point)
+            }
+            Expression::Add(l, r, t) => Expression::Add(
+                Rc::new(self.replace(l)),
+                Rc::new(self.replace(r)),
+                t.clone(),
+            ),
+            Expression::Sub(l, r, t) => Expression::Sub(
+                Rc::new(self.replace(l)),
+                Rc::new(self.replace(r)),
+                t.clone(),
+            ),
+            Expression::Mul(l, r, t) => Expression::Mul(
+                Rc::new(self.replace(l)),
+                Rc::new(self.replace(r)),
+                t.clone(),
+            ),
+            Expression::Div(l, r, t) => Expression::Div(
+                Rc::new(self.replace(l)),
+                Rc::new(self.replace(r)),
+                t.clone(),
+            ),
+            Expression::MulAdd(a, b, c, t) => Expression::MulAdd(
+                Rc::new(self.replace(a)),
+                Rc::new(self.replace(b)),
+                Rc::new(self.replace(c)),
+                t.clone(),
+            ),
+            Expression::Pow(base, exp, t) => {
+                Expression::Pow(Rc::new(self.replace(base)), *exp, t.clone())
+            }
+            // Expression::map doesn't visit the target
+            Expression::MulAddPM(target, opcode, t) => {
+                Expression::MulAddPM(Rc::new(self.replace(target)), opcode.clone(), t.clone())
+            }
+            Expression::MulAddTable(target, entries) => {
+                Expression::MulAddTable(Rc::new(self.replace(target)), entries.clone())
+            }
+            _ => e.clone(),
+        }
+    }
+}
+
+// Replaces the constants of args with Expression::VkOffset, and returns the
+// table they index, one word per scalar and two per point.
+pub(crate) fn extract_vk_table(mut args: CodeGeneratorCtx) -> (CodeGeneratorCtx, Vec<BigUint>) {
+    let mut table = VkTable::default();
+
+    args.assignments = args
+        .assignments
+        .iter()
+        .map(|s| match s {
+            Statement::Assign(l, r, samples) => {
+                Statement::Assign(l.clone(), table.replace(r), samples.clone())
+            }
+            Statement::UpdateHash(e, offset) => {
+                Statement::UpdateHash(Rc::new(table.replace(e)), *offset)
+            }
+            _ => s.clone(),
+        })
+        .collect();
+    args.wx = table.replace(&args.wx);
+    args.wg = table.replace(&args.wg);
+
+    (args, table.words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_generator::ctx::G2Point;
+
+    fn g2() -> G2Point {
+        G2Point {
+            x: (BigUint::from(1u64), BigUint::from(2u64)),
+            y: (BigUint::from(3u64), BigUint::from(4u64)),
+        }
+    }
+
+    #[test]
+    fn test_extract_vk_table() {
+        let m0 = Rc::new(Expression::Memory(0, Type::Scalar));
+        let m1 = Rc::new(Expression::Memory(1, Type::Point));
+        let c = Rc::new(Expression::Scalar(BigUint::from(7u64)));
+        let p = Rc::new(Expression::Point(BigUint::from(8u64), BigUint::from(9u64)));
+        let args = CodeGeneratorCtx {
+            wx: Expression::MulAddPM(p.clone(), BigUint::from(1u64), Type::Point),
+            wg: (*m1).clone(),
+            target_circuit_s_g2: g2(),
+            target_circuit_n_g2: g2(),
+            verify_circuit_s_g2: g2(),
+            verify_circuit_n_g2: g2(),
+            assignments: vec![
+                Statement::Assign(
+                    m0.clone(),
+                    Expression::Mul(m0.clone(), c.clone(), Type::Scalar),
+                    vec![],
+                ),
+                Statement::UpdateHash(c.clone(), 0),
+                Statement::Assign(m1.clone(), (*p).clone(), vec![]),
+            ],
+            memory_size: 3,
+            instance_size: 4,
+            absorbing_length: 1,
+        };
+
+        let (args, table) = extract_vk_table(args);
+        assert_eq!(
+            table,
+            vec![7u64, 8, 9]
+                .into_iter()
+                .map(BigUint::from)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            args.assignments[0].to_solidity_string(&mut vec![], &mut 0)[0],
+            "m[0] = (mulmod(m[0], vk[0], q_mod));"
+        );
+        match &args.assignments[1] {
+            Statement::UpdateHash(e, _) => assert_eq!(e.to_typed_string(), "vk[0]"),
+            _ => unreachable!(),
+        }
+        match &args.wx {
+            Expression::MulAddPM(target, _, _) => {
+                assert_eq!(target.to_typed_string(), "vk[1], vk[2]")
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(args.wg.to_typed_string(), "m[1], m[2]");
+    }
+}
@@ -0,0 +1,238 @@
+use super::ctx::{Expression, Statement, Type};
+
+// Points are uint256[2] values in vyper, so the ecadd and ecmul builtins take
+// them as is. The helpers are the ones of templates/verifier.vy.
+
+fn point(x: String, y: String) -> String {
+    format!("[{}, {}]", x, y)
+}
+
+fn libcall(t: &Type, name: &str) -> String {
+    match t {
+        Type::Scalar => format!("self.fr_{}", name),
+        Type::Point => format!("self.ecc_{}", name),
+    }
+}
+
+pub(crate) fn expression_to_vyper(e: &Expression) -> String {
+    match e {
+        Expression::Scalar(s) => s.to_string(),
+        Expression::Point(x, y) => point(x.to_string(), y.to_string()),
+        Expression::Memory(idx, Type::Scalar) => format!("m[{}]", idx),
+        Expression::Memory(idx, Type::Point) => {
+            point(format!("m[{}]", idx), format!("m[{}]", idx + 1))
+        }
+        Expression::TransciprtOffset(offset, Type::Scalar) => format!("proof[{}]", offset),
+        Expression::TransciprtOffset(offset, Type::Point) => point(
+            format!("proof[{}]", offset),
+            format!("proof[{}]", offset + 1),
+        ),
+        Expression::InstanceOffset(offset, Type::Scalar) => format!("instances[{}]", offset),
+        Expression::InstanceOffset(offset, Type::Point) => point(
+            format!("instances[{}]", offset),
+            format!("instances[{}]", offset + 1),
+        ),
+        Expression::Add(l, r, Type::Point) => format!(
+            "ecadd({}, {})",
+            expression_to_vyper(l),
+            expression_to_vyper(r)
+        ),
+        Expression::Add(l, r, Type::Scalar) => format!(
+            "uint256_addmod({}, {}, Q_MOD)",
+            expression_to_vyper(l),
+            expression_to_vyper(r)
+        ),
+        Expression::Sub(l, r, Type::Point) => format!(
+            "self.ecc_sub({}, {})",
+            expression_to_vyper(l),
+            expression_to_vyper(r)
+        ),
+        Expression::Sub(l, r, Type::Scalar) => format!(
+            "uint256_addmod({}, Q_MOD - {}, Q_MOD)",
+            expression_to_vyper(l),
+            expression_to_vyper(r)
+        ),
+        Expression::Mul(s, p, Type::Point) => format!(
+            "ecmul({}, {})",
+            expression_to_vyper(p),
+            expression_to_vyper(s)
+        ),
+        Expression::Mul(l, r, Type::Scalar) => format!(
+            "uint256_mulmod({}, {}, Q_MOD)",
+            expression_to_vyper(l),
+            expression_to_vyper(r)
+        ),
+        Expression::Div(l, r, t) => format!(
+            "{}({}, {})",
+            libcall(t, "div"),
+            expression_to_vyper(l),
+            expression_to_vyper(r)
+        ),
+        Expression::MulAdd(l, r, c, t) => format!(
+            "{}({}, {}, {})",
+            libcall(t, "mul_add"),
+            expression_to_vyper(l),
+            expression_to_vyper(r),
+            expression_to_vyper(c)
+        ),
+        Expression::Pow(base, exp, t) => {
+            assert_eq!(*t, Type::Scalar);
+            format!("self.fr_pow({}, {})", expression_to_vyper(base), exp)
+        }
+        Expression::Temp(Type::Scalar) => "t0".to_owned(),
+        Expression::Temp(Type::Point) => point("t0".to_owned(), "t1".to_owned()),
+        Expression::MulAddPM(target, opcode, t) => format!(
+            "{}(m, proof, {}, {})",
+            libcall(t, "mul_add_pm"),
+            opcode,
+            expression_to_vyper(target)
+        ),
+        Expression::MulAddMT(m, opcode) => {
+            format!("self.fr_mul_add_mt(m, m[{}], {}, t0)", m, opcode)
+        }
+        // squeeze_challenge updates absorbing, see statement_to_vyper
+        Expression::Hash(_) => unreachable!(),
+        Expression::TmpBufOffset(..) | Expression::VkOffset(..) | Expression::MulAddTable(..) => {
+            unreachable!()
+        }
+    }
+}
+
+// Statements of the body of verify, p is a uint256[2] local for point assignments.
+pub(crate) fn statement_to_vyper(s: &Statement) -> Vec<String> {
+    match s {
+        Statement::Assign(l, r, _) => {
+            let (mut lines, value) = match r {
+                Expression::Hash(offset) => (
+                    vec![
+                        format!("absorbing[{}] = 0", offset),
+                        format!(
+                            "h = self.transcript_hash(slice(_abi_encode(absorbing), 0, {}))",
+                            offset * 32 + 1
+                        ),
+                        "absorbing[0] = convert(h, uint256)".to_owned(),
+                    ],
+                    "self.to_scalar(h)".to_owned(),
+                ),
+                _ => (vec![], expression_to_vyper(r)),
+            };
+            match (l.as_ref(), r.get_type()) {
+                (Expression::Memory(idx, _), Type::Scalar) => {
+                    lines.push(format!("m[{}] = {}", idx, value))
+                }
+                (Expression::Memory(idx, _), Type::Point) => {
+                    lines.push(format!("p = {}", value));
+                    lines.push(format!("m[{}] = p[0]", idx));
+                    lines.push(format!("m[{}] = p[1]", idx + 1));
+                }
+                (Expression::Temp(_), Type::Scalar) => lines.push(format!("t0 = {}", value)),
+                (Expression::Temp(_), Type::Point) => {
+                    lines.push(format!("p = {}", value));
+                    lines.push("t0 = p[0]".to_owned());
+                    lines.push("t1 = p[1]".to_owned());
+                }
+                _ => unreachable!(),
+            }
+            lines
+        }
+        Statement::UpdateHash(e, offset) => match e.get_type() {
+            Type::Scalar => vec![
+                format!("absorbing[{}] = 2", offset),
+                format!("absorbing[{}] = {}", offset + 1, expression_to_vyper(e)),
+            ],
+            Type::Point => vec![
+                format!("p = {}", expression_to_vyper(e)),
+                format!("absorbing[{}] = 1", offset),
+                format!("absorbing[{}] = p[0]", offset + 1),
+                format!("absorbing[{}] = p[1]", offset + 2),
+            ],
+        },
+        Statement::For {
+            memory_start,
+            memory_end,
+            memory_step,
+            absorbing_start,
+            absorbing_step,
+            t,
+        } => {
+            let n = (memory_end - memory_start) / memory_step + 1;
+            let proof =
+                |delta: usize| format!("proof[{} + i * {}]", memory_start + delta, memory_step);
+            let absorbing = |delta: usize| {
+                format!(
+                    "absorbing[{} + i * {}]",
+                    absorbing_start + delta,
+                    absorbing_step
+                )
+            };
+            let mut lines = vec![format!("for i in range({}):", n)];
+            match *t {
+                Type::Scalar => {
+                    lines.push(format!("    {} = 2", absorbing(0)));
+                    lines.push(format!("    {} = {}", absorbing(1), proof(0)));
+                }
+                Type::Point => {
+                    lines.push(format!("    {} = 1", absorbing(0)));
+                    lines.push(format!("    {} = {}", absorbing(1), proof(0)));
+                    lines.push(format!("    {} = {}", absorbing(2), proof(1)));
+                }
+            }
+            lines
+        }
+        Statement::ForMMMMul { start, step, n, t } => {
+            assert_eq!(*t, Type::Scalar);
+            let m = |start: usize, step: usize| format!("m[{} + i * {}]", start, step);
+            vec![
+                format!("for i in range({}):", n),
+                format!(
+                    "    {} = uint256_mulmod({}, {}, Q_MOD)",
+                    m(start.0, step.0),
+                    m(start.1, step.1),
+                    m(start.2, step.2)
+                ),
+            ]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_statement_to_vyper() {
+        let s = Statement::Assign(
+            Rc::new(Expression::Memory(0, Type::Point)),
+            Expression::Add(
+                Rc::new(Expression::Memory(2, Type::Point)),
+                Rc::new(Expression::TransciprtOffset(4, Type::Point)),
+                Type::Point,
+            ),
+            vec![],
+        );
+        assert_eq!(
+            statement_to_vyper(&s),
+            vec![
+                "p = ecadd([m[2], m[3]], [proof[4], proof[5]])",
+                "m[0] = p[0]",
+                "m[1] = p[1]",
+            ]
+        );
+
+        let s = Statement::Assign(
+            Rc::new(Expression::Memory(0, Type::Scalar)),
+            Expression::Hash(3),
+            vec![],
+        );
+        assert_eq!(
+            statement_to_vyper(&s),
+            vec![
+                "absorbing[3] = 0",
+                "h = self.transcript_hash(slice(_abi_encode(absorbing), 0, 97))",
+                "absorbing[0] = convert(h, uint256)",
+                "m[0] = self.to_scalar(h)",
+            ]
+        );
+    }
+}
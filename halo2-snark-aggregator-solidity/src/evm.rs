@@ -0,0 +1,259 @@
+pub use crate::encode_calldata;
+use revm::{CreateScheme, InMemoryDB, Return, TransactOut, TransactTo, EVM};
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+// Deployment bytecode of the `Verifier` contract of verifier.sol or verifier_split.sol,
+// preceded by that of its `VerifierStep*` contracts, in order, when it is split.
+// Needs solc on the PATH.
+pub fn compile_solidity(source: &str) -> Vec<Vec<u8>> {
+    let mut cmd = Command::new("solc")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .arg("--bin")
+        .arg("--optimize")
+        .arg("-")
+        .spawn()
+        .expect("failed to spawn solc");
+    cmd.stdin
+        .take()
+        .unwrap()
+        .write_all(source.as_bytes())
+        .unwrap();
+    let output = cmd.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "solc failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let binary = |name: &str| {
+        let header = format!("======= <stdin>:{} =", name);
+        let binary = stdout
+            .lines()
+            .skip_while(|line| !line.starts_with(&header))
+            .skip_while(|line| !line.starts_with("Binary:"))
+            .nth(1)
+            .unwrap_or_else(|| panic!("no {} binary in solc output", name));
+        hex_decode(binary.trim())
+    };
+    let steps = crate::verifier_steps(source, "Verifier");
+    let mut codes = (0..steps)
+        .map(|i| binary(&format!("VerifierStep{}", i)))
+        .collect::<Vec<_>>();
+    codes.push(binary("Verifier"));
+    codes
+}
+
+// Deployment bytecode of verifier.vy, needs vyper on the PATH.
+pub fn compile_vyper(source: &str) -> Vec<Vec<u8>> {
+    let path = std::env::temp_dir().join(format!("verifier_{}.vy", std::process::id()));
+    std::fs::write(&path, source).unwrap();
+    let output = Command::new("vyper")
+        .arg("-f")
+        .arg("bytecode")
+        .arg(&path)
+        .output()
+        .expect("failed to spawn vyper");
+    std::fs::remove_file(&path).unwrap();
+    assert!(
+        output.status.success(),
+        "vyper failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    vec![hex_decode(stdout.trim().trim_start_matches("0x"))]
+}
+
+// Deployment bytecode of the `Verifier` object of verifier.yul, needs solc on the PATH.
+pub fn compile_yul(source: &str) -> Vec<Vec<u8>> {
+    let path = std::env::temp_dir().join(format!("verifier_{}.yul", std::process::id()));
+    std::fs::write(&path, source).unwrap();
+    let output = Command::new("solc")
+        .arg("--strict-assembly")
+        .arg("--optimize")
+        .arg("--bin")
+        .arg(&path)
+        .output()
+        .expect("failed to spawn solc");
+    std::fs::remove_file(&path).unwrap();
+    assert!(
+        output.status.success(),
+        "solc failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let binary = stdout
+        .lines()
+        .skip_while(|line| !line.starts_with("Binary representation:"))
+        .nth(1)
+        .expect("no binary in solc output");
+    vec![hex_decode(binary.trim())]
+}
+
+fn hex_decode(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+// Deploys the contracts in a fresh in-memory evm and calls the last one, whose constructor
+// takes the addresses of the others, as the split verifier does its steps. Returns the gas
+// used by the call or the reason it failed.
+pub fn deploy_and_call(deployment_codes: &[Vec<u8>], calldata: Vec<u8>) -> Result<u64, String> {
+    let mut evm = EVM::new();
+    evm.database(InMemoryDB::default());
+    evm.env.tx.gas_limit = u64::MAX;
+
+    let mut deploy = |code: Vec<u8>| {
+        evm.env.tx.transact_to = TransactTo::Create(CreateScheme::Create);
+        evm.env.tx.data = code.into();
+        match evm.transact_commit() {
+            (Return::Return | Return::Stop, TransactOut::Create(_, Some(contract)), ..) => {
+                Ok(contract)
+            }
+            (exit, out, ..) => Err(format!("deployment failed: {:?} {:?}", exit, out)),
+        }
+    };
+    let (verifier, steps) = deployment_codes
+        .split_last()
+        .expect("no contract to deploy");
+    let mut code = verifier.clone();
+    for step in steps {
+        // abi encoded address[N] constructor argument, one left padded word each
+        let address = deploy(step.clone())?;
+        code.extend([0u8; 12]);
+        code.extend(address.as_bytes());
+    }
+    let contract = deploy(code)?;
+
+    evm.env.tx.transact_to = TransactTo::Call(contract);
+    evm.env.tx.data = calldata.into();
+    match evm.transact_commit() {
+        (Return::Return | Return::Stop, _, gas_used, ..) => Ok(gas_used),
+        (exit, out, ..) => Err(format!("call failed: {:?} {:?}", exit, out)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_snark_aggregator_circuit::fs::{
+        load_verify_circuit_final_pair, load_verify_circuit_proof, read_file,
+    };
+    use std::path::PathBuf;
+
+    const BLOCK_GAS_LIMIT: u64 = 30_000_000;
+
+    // The folder with the output of the sdk, taken from VERIFIER_OUTPUT. The tests reading it
+    // are ignored, run them with `--ignored` once the sdk commands have written it.
+    fn output_folder() -> PathBuf {
+        std::env::var("VERIFIER_OUTPUT")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                    .join("../halo2-snark-aggregator-sdk/output")
+            })
+    }
+
+    // Runs against the output of the sdk `verify_solidity` command.
+    #[test]
+    #[ignore]
+    fn test_generated_verifier_in_revm() {
+        let mut folder = output_folder();
+        let sol = String::from_utf8(read_file(&mut folder, "verifier.sol")).unwrap();
+        check_verifier(&mut folder, compile_solidity(&sol));
+    }
+
+    // Runs against the output of the sdk `verify_vyper` command.
+    #[test]
+    #[ignore]
+    fn test_generated_vyper_verifier_in_revm() {
+        let mut folder = output_folder();
+        let vy = String::from_utf8(read_file(&mut folder, "verifier.vy")).unwrap();
+        check_verifier(&mut folder, compile_vyper(&vy));
+    }
+
+    // Runs against the output of the sdk `verify_yul` command.
+    #[test]
+    #[ignore]
+    fn test_generated_yul_verifier_in_revm() {
+        let mut folder = output_folder();
+        let yul = String::from_utf8(read_file(&mut folder, "verifier.yul")).unwrap();
+        check_verifier(&mut folder, compile_yul(&yul));
+    }
+
+    // The yul object and the solidity verifier generated from the same statements accept
+    // and reject the same calldata, run against the output of `verify_solidity` and
+    // `verify_yul`.
+    #[test]
+    #[ignore]
+    fn test_yul_and_solidity_verifiers_agree_in_revm() {
+        let mut folder = output_folder();
+        let sol = String::from_utf8(read_file(&mut folder, "verifier.sol")).unwrap();
+        let yul = String::from_utf8(read_file(&mut folder, "verifier.yul")).unwrap();
+        let (sol, yul) = (compile_solidity(&sol), compile_yul(&yul));
+
+        let proof = load_verify_circuit_proof(&mut folder);
+        let final_pair = load_verify_circuit_final_pair(&mut folder);
+        let mut calldata = vec![encode_calldata(&proof, &final_pair)];
+        for i in [0, proof.len() / 2, proof.len() - 1] {
+            let mut mutated_proof = proof.clone();
+            mutated_proof[i] ^= 1;
+            calldata.push(encode_calldata(&mutated_proof, &final_pair));
+        }
+        // one flip in each word of the final pair
+        for i in (0..final_pair.len()).step_by(32) {
+            let mut wrong_final_pair = final_pair.clone();
+            wrong_final_pair[i] ^= 1;
+            calldata.push(encode_calldata(&proof, &wrong_final_pair));
+        }
+
+        for (i, calldata) in calldata.into_iter().enumerate() {
+            let sol_result = deploy_and_call(&sol, calldata.clone());
+            let yul_result = deploy_and_call(&yul, calldata);
+            assert_eq!(
+                sol_result.is_ok(),
+                yul_result.is_ok(),
+                "calldata {}: solidity {:?}, yul {:?}",
+                i,
+                sol_result,
+                yul_result
+            );
+            assert_eq!(sol_result.is_ok(), i == 0, "calldata {}", i);
+        }
+    }
+
+    // Accepts the proof of folder, rejects it mutated or with a wrong final pair.
+    fn check_verifier(folder: &mut PathBuf, deployment_codes: Vec<Vec<u8>>) {
+        let proof = load_verify_circuit_proof(folder);
+        let final_pair = load_verify_circuit_final_pair(folder);
+
+        let gas_used =
+            deploy_and_call(&deployment_codes, encode_calldata(&proof, &final_pair)).unwrap();
+        assert!(gas_used < BLOCK_GAS_LIMIT, "gas used {}", gas_used);
+
+        let mut mutated_proof = proof.clone();
+        *mutated_proof.last_mut().unwrap() ^= 1;
+        assert!(deploy_and_call(
+            &deployment_codes,
+            encode_calldata(&mutated_proof, &final_pair)
+        )
+        .is_err());
+
+        let mut wrong_final_pair = final_pair.clone();
+        *wrong_final_pair.last_mut().unwrap() ^= 1;
+        assert!(deploy_and_call(
+            &deployment_codes,
+            encode_calldata(&proof, &wrong_final_pair)
+        )
+        .is_err());
+    }
+}
@@ -2,17 +2,38 @@ pub(crate) mod chips;
 pub(crate) mod code_generator;
 pub(crate) mod transcript;
 
+#[cfg(feature = "revm")]
+pub mod evm;
+
+#[cfg(feature = "solc")]
+pub mod solc;
+
 use std::path::PathBuf;
 
 use crate::chips::{
     ecc_chip::SolidityEccChip, encode_chip::PoseidonEncode, scalar_chip::SolidityFieldChip,
 };
 use crate::code_generator::aggregate::aggregate;
+use crate::code_generator::audit::audit_report;
 use crate::code_generator::ctx::SolidityCodeGeneratorContext;
+use crate::code_generator::gas::estimate_gas;
+use crate::code_generator::huff::{expression_to_huff_string, statement_to_huff};
 use crate::code_generator::linear_scan::memory_optimize;
+use crate::code_generator::move_lang::{expression_to_move, statement_to_move};
+use crate::code_generator::msm_loop::loop_msm;
+use crate::code_generator::pairing::{collect_pairs, pairing_input, pairing_scale, PairingG1};
+use crate::code_generator::proof_parsing::{choose_proof_parsing, ProofParsing};
+use crate::code_generator::spill::spill_expressions;
+#[cfg(feature = "solc")]
+use crate::code_generator::split::{estimate_code_size, fit_budget};
+use crate::code_generator::split::{split_statements, ESTIMATED_BUDGET};
+use crate::code_generator::ssa::optimize;
+use crate::code_generator::vk_table::extract_vk_table;
+use crate::code_generator::vyper::{expression_to_vyper, statement_to_vyper};
 use crate::transcript::codegen::CodegenTranscriptRead;
-use code_generator::ctx::{CodeGeneratorCtx, G2Point, Statement};
-use halo2_proofs::arithmetic::{BaseExt, Field};
+use crate::transcript::symbolic::SymbolicTranscriptRead;
+use code_generator::ctx::{CodeGeneratorCtx, G2Point, Statement, YUL_MEMORY_BASE};
+use halo2_proofs::arithmetic::{BaseExt, Field, FieldExt};
 use halo2_proofs::arithmetic::{CurveAffine, MultiMillerLoop};
 use halo2_proofs::plonk::VerifyingKey;
 use halo2_proofs::poly::commitment::Params;
@@ -20,35 +41,562 @@ use halo2_snark_aggregator_api::arith::{common::ArithCommonChip, ecc::ArithEccCh
 use halo2_snark_aggregator_api::systems::halo2::verify::{
     assign_instance_commitment, verify_single_proof_no_eval,
 };
+use halo2_snark_aggregator_api::transcript::read::TranscriptRead;
+use halo2_snark_aggregator_api::transcript::sha::label_scalar;
+use halo2_snark_aggregator_circuit::envelope::{ProofEnvelope, ENVELOPE_VERSION};
 use halo2_snark_aggregator_circuit::fs::{load_target_circuit_params, load_target_circuit_vk};
 use halo2_snark_aggregator_circuit::sample_circuit::TargetCircuit;
-use log::info;
 use num_bigint::BigUint;
 use pairing_bn256::bn256::{Bn256, G1Affine};
+use sha3::Digest;
 use tera::{Context, Tera};
+use tracing::{info, info_span};
 
-fn render_verifier_sol_template<C: CurveAffine>(
+pub use crate::code_generator::calldata::CalldataLayout;
+pub use crate::code_generator::gas::GasReport;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodegenBackend {
+    Solidity,
+    // Standalone yul object, see templates/verifier.yul for its memory layout.
+    Yul,
+    // Experimental huff contract with the memory layout of the yul one,
+    // see templates/verifier.huff.
+    Huff,
+    // Vyper contract with the abi of the solidity one, see templates/verifier.vy.
+    Vyper,
+    // Aptos move module on the native bn254 structures, see templates/verifier.move.
+    Move,
+}
+
+// Digest of the sha transcript the verify circuit proof is made with, the
+// generated contract squeezes the challenges with the same hash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranscriptHash {
+    // sha256 precompile, the default of the verify circuit
+    Sha256,
+    // keccak256 opcode, cheaper
+    Keccak256,
+}
+
+// Custom errors need solidity 0.8.4 or later.
+pub const DEFAULT_SOLIDITY_PRAGMA: &str = "^0.8.4";
+
+// Header and naming of the generated verifier and verifying key contracts, so
+// they drop into an existing project without post-processing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolidityOptions {
+    // name of the verifier contract, the split verifier names its steps after it
+    pub contract_name: String,
+    // SPDX license identifier
+    pub license: String,
+    // must allow custom errors, i.e. 0.8.4 or later
+    pub pragma: String,
+    // written as comments under the pragma, e.g. the solc optimizer runs the
+    // contract was sized for
+    pub optimizer_hints: Vec<String>,
+    // verify() emits ProofVerified(keccak256(abi.encodePacked(final_pair))) and
+    // is no longer view
+    pub emit_event: bool,
+    // verify() reverts with ProofInvalid(step) on an invalid proof, or returns
+    // false when unset, for malformed public inputs as well.
+    pub revert_on_failure: bool,
+    // The options below are for the solidity backend only, the others ignore them.
+    // adds verify_compressed(bytes) taking the calldata packed by CalldataLayout::compress
+    pub compressed: bool,
+    // moves the constants of the verifier into a VerifyingKey contract, returned in
+    // GeneratedVerifier::vk, whose address is passed to the verifier constructor
+    pub vk_contract: bool,
+    // the verifier imports ./verifier_pairing.sol (see render_pairing_library) and
+    // calls its precompile helpers instead of embedding them
+    pub shared_library: bool,
+    // each chain of point multiply-adds is a loop over a constant table of
+    // (point, scalar slot) entries instead of a call per term
+    pub msm_loop: bool,
+}
+
+impl Default for SolidityOptions {
+    fn default() -> Self {
+        SolidityOptions {
+            contract_name: "Verifier".to_owned(),
+            license: "GPL-3.0".to_owned(),
+            pragma: DEFAULT_SOLIDITY_PRAGMA.to_owned(),
+            optimizer_hints: vec![],
+            emit_event: false,
+            revert_on_failure: true,
+            compressed: false,
+            vk_contract: false,
+            shared_library: false,
+            msm_loop: false,
+        }
+    }
+}
+
+fn insert_solidity_options(ctx: &mut Context, options: &SolidityOptions) {
+    ctx.insert("contract_name", &options.contract_name);
+    ctx.insert("license", &options.license);
+    ctx.insert("pragma", &options.pragma);
+    ctx.insert("optimizer_hints", &options.optimizer_hints);
+    ctx.insert("emit_event", &options.emit_event);
+    ctx.insert("revert_on_failure", &options.revert_on_failure);
+    ctx.insert("check_fn", check_fn(options));
+}
+
+// Name of the reverting view check of the verifier, it keeps the name verify unless
+// the calling convention changes.
+fn check_fn(options: &SolidityOptions) -> &'static str {
+    if options.emit_event || !options.revert_on_failure {
+        "check"
+    } else {
+        "verify"
+    }
+}
+
+// Templates built into the crate, so it works without the templates folder
+// when used as a dependency.
+const EMBEDDED_TEMPLATES: [(&str, &str); 30] = [
+    ("verifier.sol", include_str!("../templates/verifier.sol")),
+    (
+        "verifier_batch.sol",
+        include_str!("../templates/verifier_batch.sol"),
+    ),
+    (
+        "verifier_deferred.sol",
+        include_str!("../templates/verifier_deferred.sol"),
+    ),
+    (
+        "verifier_epoch.sol",
+        include_str!("../templates/verifier_epoch.sol"),
+    ),
+    (
+        "verifier_interface.sol",
+        include_str!("../templates/verifier_interface.sol"),
+    ),
+    (
+        "verifier_lib.sol",
+        include_str!("../templates/verifier_lib.sol"),
+    ),
+    (
+        "verifier_pairing.sol",
+        include_str!("../templates/verifier_pairing.sol"),
+    ),
+    (
+        "verifier_verify.sol",
+        include_str!("../templates/verifier_verify.sol"),
+    ),
+    (
+        "verifier_split.sol",
+        include_str!("../templates/verifier_split.sol"),
+    ),
+    (
+        "verifier_uups.sol",
+        include_str!("../templates/verifier_uups.sol"),
+    ),
+    (
+        "verifier_vk.sol",
+        include_str!("../templates/verifier_vk.sol"),
+    ),
+    ("verifier.yul", include_str!("../templates/verifier.yul")),
+    ("verifier.huff", include_str!("../templates/verifier.huff")),
+    ("verifier.vy", include_str!("../templates/verifier.vy")),
+    ("verifier.move", include_str!("../templates/verifier.move")),
+    ("encoder.ts", include_str!("../templates/encoder.ts")),
+    ("foundry.toml", include_str!("../templates/foundry.toml")),
+    (
+        "Verifier.t.sol",
+        include_str!("../templates/Verifier.t.sol"),
+    ),
+    (
+        "hardhat.package.json",
+        include_str!("../templates/hardhat.package.json"),
+    ),
+    (
+        "hardhat.config.ts",
+        include_str!("../templates/hardhat.config.ts"),
+    ),
+    (
+        "hardhat.deploy.ts",
+        include_str!("../templates/hardhat.deploy.ts"),
+    ),
+    (
+        "hardhat.call.ts",
+        include_str!("../templates/hardhat.call.ts"),
+    ),
+    (
+        "cosmwasm.Cargo.toml",
+        include_str!("../templates/cosmwasm.Cargo.toml"),
+    ),
+    (
+        "cosmwasm.lib.rs",
+        include_str!("../templates/cosmwasm.lib.rs"),
+    ),
+    (
+        "cosmwasm.msg.rs",
+        include_str!("../templates/cosmwasm.msg.rs"),
+    ),
+    (
+        "cosmwasm.schema.rs",
+        include_str!("../templates/cosmwasm.schema.rs"),
+    ),
+    (
+        "near.Cargo.toml",
+        include_str!("../templates/near.Cargo.toml"),
+    ),
+    ("near.lib.rs", include_str!("../templates/near.lib.rs")),
+    (
+        "solana.Cargo.toml",
+        include_str!("../templates/solana.Cargo.toml"),
+    ),
+    ("solana.lib.rs", include_str!("../templates/solana.lib.rs")),
+];
+
+// Templates found in template_folder override the embedded ones with the same name.
+fn load_templates(template_folder: &Option<PathBuf>) -> Tera {
+    try_load_templates(template_folder).expect("failed to load templates")
+}
+
+// The embedded templates, overridden by those of template_folder when given. A folder that
+// does not exist or a template that does not parse is an error.
+fn try_load_templates(template_folder: &Option<PathBuf>) -> Result<Tera, tera::Error> {
+    let mut embedded = Tera::default();
+    embedded.add_raw_templates(EMBEDDED_TEMPLATES.to_vec())?;
+
+    match template_folder {
+        None => Ok(embedded),
+        Some(template_folder) => {
+            let folder = template_folder.as_path().canonicalize().map_err(|e| {
+                tera::Error::msg(format!(
+                    "template folder {}: {}",
+                    template_folder.display(),
+                    e
+                ))
+            })?;
+            let mut tera = Tera::new(&format!("{}/*", folder.display()))?;
+            tera.extend(&embedded)?;
+            Ok(tera)
+        }
+    }
+}
+
+fn insert_g2_points(ctx: &mut Context, args: &CodeGeneratorCtx, fmt: impl Fn(&BigUint) -> String) {
+    for (name, point) in [
+        ("target_circuit_s_g2", &args.target_circuit_s_g2),
+        ("target_circuit_n_g2", &args.target_circuit_n_g2),
+        ("verify_circuit_s_g2", &args.verify_circuit_s_g2),
+        ("verify_circuit_n_g2", &args.verify_circuit_n_g2),
+    ] {
+        ctx.insert(format!("{}_x0", name), &fmt(&point.x.0));
+        ctx.insert(format!("{}_x1", name), &fmt(&point.x.1));
+        ctx.insert(format!("{}_y0", name), &fmt(&point.y.0));
+        ctx.insert(format!("{}_y1", name), &fmt(&point.y.1));
+    }
+}
+
+fn render_verifier_yul_template(
     args: CodeGeneratorCtx,
-    template_folder: std::path::PathBuf,
+    template_folder: Option<PathBuf>,
+    transcript_hash: TranscriptHash,
+    proof_len: usize,
 ) -> String {
-    let path = format!(
-        "{}/*",
-        template_folder
-            .as_path()
-            .canonicalize()
-            .unwrap()
-            .to_str()
-            .unwrap()
+    let tera = load_templates(&template_folder);
+    let mut ctx = Context::new();
+
+    let statements = args
+        .assignments
+        .iter()
+        .flat_map(|s| s.to_yul_string())
+        .collect::<Vec<_>>();
+    let instance_assign = (4..args.instance_size)
+        .map(|i| format!("mstore(instance_ptr({}), final_pair({}))", i, i))
+        .collect::<Vec<_>>();
+
+    let absorbing_base = YUL_MEMORY_BASE + args.memory_size * 0x20;
+    let instance_base = absorbing_base + (args.absorbing_length + 1) * 0x20;
+    let proof_base = instance_base + args.instance_size * 0x20;
+    let proof_in_memory = choose_proof_parsing(&args, proof_len) == ProofParsing::Memory;
+    let heap_base = if proof_in_memory {
+        proof_base + proof_len * 0x20
+    } else {
+        proof_base
+    };
+
+    ctx.insert("wx", &args.wx.to_yul_string());
+    ctx.insert("wg", &args.wg.to_yul_string());
+    let terms = collect_pairs(&args);
+    let input = pairing_input(
+        &terms,
+        |g1, w| {
+            let point = match g1 {
+                PairingG1::Wx => "w_x".to_owned(),
+                PairingG1::Wg => "w_g".to_owned(),
+                PairingG1::FinalPair(i) => {
+                    format!("point_const(final_pair({}), final_pair({}))", i, i + 1)
+                }
+            };
+            vec![format!(
+                "copy_point(add(input, {:#x}), {})",
+                w * 0x20,
+                point
+            )]
+        },
+        |v, w| {
+            format!(
+                "mstore(add(input, {:#x}), {})",
+                w * 0x20,
+                v.to_str_radix(10)
+            )
+        },
     );
-    let tera = Tera::new(&path).unwrap();
+    let scale = pairing_scale(&terms, |w| {
+        vec![format!(
+            "copy_point(add(input, {:#x}), ecc_mul(add(input, {:#x}), r))",
+            w * 0x20,
+            w * 0x20
+        )]
+    });
+    ctx.insert("pairing_input", &input);
+    ctx.insert("pairing_scale", &scale);
+    ctx.insert("statements", &statements);
+    ctx.insert("instance_assign", &instance_assign);
+    insert_g2_points(&mut ctx, &args, |v| v.to_str_radix(10));
+    ctx.insert("memory_size", &args.memory_size);
+    ctx.insert("instance_size", &args.instance_size);
+    ctx.insert("absorbing_length", &args.absorbing_length);
+    ctx.insert("memory_base", &format!("{:#x}", YUL_MEMORY_BASE));
+    ctx.insert("absorbing_base", &format!("{:#x}", absorbing_base));
+    ctx.insert("instance_base", &format!("{:#x}", instance_base));
+    ctx.insert("proof_in_memory", &proof_in_memory);
+    ctx.insert("proof_base", &format!("{:#x}", proof_base));
+    ctx.insert("proof_size", &format!("{:#x}", proof_len * 0x20));
+    ctx.insert("heap_base", &format!("{:#x}", heap_base));
+    ctx.insert("keccak", &(transcript_hash == TranscriptHash::Keccak256));
+    tera.render("verifier.yul", &ctx)
+        .expect("failed to render template")
+}
+
+fn render_verifier_huff_template(
+    args: CodeGeneratorCtx,
+    template_folder: Option<PathBuf>,
+    transcript_hash: TranscriptHash,
+) -> String {
+    let tera = load_templates(&template_folder);
+    let mut ctx = Context::new();
+
+    let mut label = 0;
+    let statements = args
+        .assignments
+        .iter()
+        .flat_map(|s| statement_to_huff(s, &mut label))
+        .collect::<Vec<_>>();
+    let instance_check = (0..args.instance_size)
+        .map(|i| {
+            format!(
+                "{:#x} FINAL_PAIR() [{}] swap1 lt REQUIRE()",
+                i,
+                if i < 4 { "P_MOD" } else { "Q_MOD" }
+            )
+        })
+        .collect::<Vec<_>>();
+    let instance_assign = (4..args.instance_size)
+        .map(|i| format!("{:#x} FINAL_PAIR() {:#x} INSTANCE_PTR() mstore", i, i))
+        .collect::<Vec<_>>();
+
+    let absorbing_base = YUL_MEMORY_BASE + args.memory_size * 0x20;
+    let instance_base = absorbing_base + (args.absorbing_length + 1) * 0x20;
+    let heap_base = instance_base + args.instance_size * 0x20;
+
+    let terms = collect_pairs(&args);
+    // each statement takes and leaves [input] on the stack
+    let input = pairing_input(
+        &terms,
+        |g1, w| {
+            let point = match g1 {
+                PairingG1::Wx => expression_to_huff_string(&args.wx),
+                PairingG1::Wg => expression_to_huff_string(&args.wg),
+                PairingG1::FinalPair(i) => format!(
+                    "{:#x} FINAL_PAIR() {:#x} FINAL_PAIR() POINT_CONST()",
+                    i + 1,
+                    i
+                ),
+            };
+            vec![format!("{} dup2 {:#x} add COPY_POINT()", point, w * 0x20)]
+        },
+        |v, w| format!("0x{} dup2 {:#x} add mstore", v.to_str_radix(16), w * 0x20),
+    );
+    // [r, input] on the stack
+    let scale = pairing_scale(&terms, |w| {
+        vec![format!(
+            "dup1 dup3 {:#x} add ECC_MUL() dup3 {:#x} add COPY_POINT()",
+            w * 0x20,
+            w * 0x20
+        )]
+    });
+    ctx.insert("pairing_input", &input);
+    ctx.insert("pairing_scale", &scale);
+    ctx.insert("statements", &statements);
+    ctx.insert("instance_check", &instance_check);
+    ctx.insert("instance_assign", &instance_assign);
+    insert_g2_points(&mut ctx, &args, |v| format!("0x{}", v.to_str_radix(16)));
+    ctx.insert("memory_size", &args.memory_size);
+    ctx.insert("instance_size", &args.instance_size);
+    ctx.insert("instance_size_hex", &format!("{:#x}", args.instance_size));
+    ctx.insert("absorbing_length", &args.absorbing_length);
+    ctx.insert("memory_base", &format!("{:#x}", YUL_MEMORY_BASE));
+    ctx.insert("absorbing_base", &format!("{:#x}", absorbing_base));
+    ctx.insert("instance_base", &format!("{:#x}", instance_base));
+    ctx.insert("heap_base", &format!("{:#x}", heap_base));
+    ctx.insert("keccak", &(transcript_hash == TranscriptHash::Keccak256));
+    tera.render("verifier.huff", &ctx)
+        .expect("failed to render template")
+}
+
+fn render_verifier_vyper_template(
+    args: CodeGeneratorCtx,
+    template_folder: Option<PathBuf>,
+    transcript_hash: TranscriptHash,
+    proof_len: usize,
+) -> String {
+    let tera = load_templates(&template_folder);
+    let mut ctx = Context::new();
+
+    let statements = args
+        .assignments
+        .iter()
+        .flat_map(statement_to_vyper)
+        .collect::<Vec<_>>();
+    let instance_assign = (4..args.instance_size)
+        .map(|i| format!("instances[{}] = target_circuit_final_pair[{}]", i, i))
+        .collect::<Vec<_>>();
+
+    ctx.insert("wx", &expression_to_vyper(&args.wx));
+    ctx.insert("wg", &expression_to_vyper(&args.wg));
+    let terms = collect_pairs(&args);
+    let input = pairing_input(
+        &terms,
+        |g1, w| {
+            let (x, y) = match g1 {
+                PairingG1::Wx => ("wx[0]".to_owned(), "wx[1]".to_owned()),
+                PairingG1::Wg => ("wg[0]".to_owned(), "wg[1]".to_owned()),
+                PairingG1::FinalPair(i) => (
+                    format!("target_circuit_final_pair[{}]", i),
+                    format!("target_circuit_final_pair[{}]", i + 1),
+                ),
+            };
+            vec![
+                format!("pairs[{}] = {}", w, x),
+                format!("pairs[{}] = {}", w + 1, y),
+            ]
+        },
+        |v, w| format!("pairs[{}] = {}", w, v.to_str_radix(10)),
+    );
+    let scale = pairing_scale(&terms, |w| {
+        vec![
+            format!("p = ecmul([pairs[{}], pairs[{}]], r)", w, w + 1),
+            format!("pairs[{}] = p[0]", w),
+            format!("pairs[{}] = p[1]", w + 1),
+        ]
+    });
+    ctx.insert("pairing_input", &input);
+    ctx.insert("pairing_scale", &scale);
+    ctx.insert("statements", &statements);
+    ctx.insert("instance_assign", &instance_assign);
+    insert_g2_points(&mut ctx, &args, |v| v.to_str_radix(10));
+    ctx.insert("memory_size", &args.memory_size);
+    ctx.insert("instance_size", &args.instance_size);
+    ctx.insert("absorbing_length", &args.absorbing_length);
+    ctx.insert("proof_len", &proof_len);
+    ctx.insert("keccak", &(transcript_hash == TranscriptHash::Keccak256));
+    tera.render("verifier.vy", &ctx)
+        .expect("failed to render template")
+}
+
+fn render_verifier_move_template(
+    args: CodeGeneratorCtx,
+    template_folder: Option<PathBuf>,
+    transcript_hash: TranscriptHash,
+) -> String {
+    let tera = load_templates(&template_folder);
+    let mut ctx = Context::new();
+
+    let statements = args
+        .assignments
+        .iter()
+        .flat_map(statement_to_move)
+        .collect::<Vec<_>>();
+    let instance_assign = (4..args.instance_size)
+        .map(|i| format!("instances[{}] = target_circuit_final_pair[{}];", i, i))
+        .collect::<Vec<_>>();
+
+    ctx.insert("wx", &expression_to_move(&args.wx));
+    ctx.insert("wg", &expression_to_move(&args.wg));
+    let terms = collect_pairs(&args);
+    let input = pairing_input(
+        &terms,
+        |g1, w| {
+            let (x, y) = match g1 {
+                PairingG1::Wx => ("wx.x".to_owned(), "wx.y".to_owned()),
+                PairingG1::Wg => ("wg.x".to_owned(), "wg.y".to_owned()),
+                PairingG1::FinalPair(i) => (
+                    format!("target_circuit_final_pair[{}]", i),
+                    format!("target_circuit_final_pair[{}]", i + 1),
+                ),
+            };
+            vec![
+                format!("pairs[{}] = {};", w, x),
+                format!("pairs[{}] = {};", w + 1, y),
+            ]
+        },
+        |v, w| format!("pairs[{}] = {};", w, v.to_str_radix(10)),
+    );
+    let scale = pairing_scale(&terms, |w| {
+        vec![
+            format!(
+                "p = ecc_mul(Point {{ x: pairs[{}], y: pairs[{}] }}, r);",
+                w,
+                w + 1
+            ),
+            format!("pairs[{}] = p.x;", w),
+            format!("pairs[{}] = p.y;", w + 1),
+        ]
+    });
+    ctx.insert("pairing_input", &input);
+    ctx.insert("pairing_scale", &scale);
+    ctx.insert("statements", &statements);
+    ctx.insert("instance_assign", &instance_assign);
+    ctx.insert("memory_size", &args.memory_size);
+    ctx.insert("instance_size", &args.instance_size);
+    ctx.insert("absorbing_length", &args.absorbing_length);
+    ctx.insert("keccak", &(transcript_hash == TranscriptHash::Keccak256));
+    tera.render("verifier.move", &ctx)
+        .expect("failed to render template")
+}
+
+// Renders verifier.sol, or verifier_split.sol when the statements don't fit in budget, see
+// code_generator::split.
+fn render_verifier_sol_template<C: CurveAffine>(
+    args: &CodeGeneratorCtx,
+    template_folder: Option<PathBuf>,
+    options: &SolidityOptions,
+    layout: &CalldataLayout,
+    vk_size: usize,
+    transcript_hash: TranscriptHash,
+    budget: usize,
+) -> String {
+    let tera = load_templates(&template_folder);
     let mut ctx = Context::new();
-    let mut opcodes = vec![];
     let mut incremental_ident = 0u64;
-    let mut equations = vec![];
-    for s in args.assignments {
-        equations.append(&mut s.to_solidity_string(&mut opcodes, &mut incremental_ident));
-    }
-    equations.append(&mut Statement::opcodes_to_solidity_string(&mut opcodes));
+    let steps = split_statements(&args.assignments, budget)
+        .iter()
+        .map(|statements| {
+            let mut opcodes = vec![];
+            let mut equations = vec![];
+            for s in statements.iter() {
+                equations.append(&mut s.to_solidity_string(&mut opcodes, &mut incremental_ident));
+            }
+            equations.append(&mut Statement::opcodes_to_solidity_string(&mut opcodes));
+            equations
+        })
+        .collect::<Vec<_>>();
 
     let mut instance_assign = vec![];
     for i in 4..args.instance_size {
@@ -60,79 +608,616 @@ fn render_verifier_sol_template<C: CurveAffine>(
 
     ctx.insert("wx", &(args.wx).to_typed_string());
     ctx.insert("wg", &(args.wg).to_typed_string());
-    ctx.insert("statements", &equations);
-    ctx.insert("instance_assign", &instance_assign);
-    ctx.insert(
-        "target_circuit_s_g2_x0",
-        &args.target_circuit_s_g2.x.0.to_str_radix(10),
-    );
-    ctx.insert(
-        "target_circuit_s_g2_x1",
-        &args.target_circuit_s_g2.x.1.to_str_radix(10),
-    );
-    ctx.insert(
-        "target_circuit_s_g2_y0",
-        &args.target_circuit_s_g2.y.0.to_str_radix(10),
-    );
-    ctx.insert(
-        "target_circuit_s_g2_y1",
-        &args.target_circuit_s_g2.y.1.to_str_radix(10),
-    );
-    ctx.insert(
-        "target_circuit_n_g2_x0",
-        &args.target_circuit_n_g2.x.0.to_str_radix(10),
-    );
-    ctx.insert(
-        "target_circuit_n_g2_x1",
-        &args.target_circuit_n_g2.x.1.to_str_radix(10),
-    );
-    ctx.insert(
-        "target_circuit_n_g2_y0",
-        &args.target_circuit_n_g2.y.0.to_str_radix(10),
-    );
-    ctx.insert(
-        "target_circuit_n_g2_y1",
-        &args.target_circuit_n_g2.y.1.to_str_radix(10),
-    );
-    ctx.insert(
-        "verify_circuit_s_g2_x0",
-        &args.verify_circuit_s_g2.x.0.to_str_radix(10),
-    );
-    ctx.insert(
-        "verify_circuit_s_g2_x1",
-        &args.verify_circuit_s_g2.x.1.to_str_radix(10),
-    );
-    ctx.insert(
-        "verify_circuit_s_g2_y0",
-        &args.verify_circuit_s_g2.y.0.to_str_radix(10),
-    );
-    ctx.insert(
-        "verify_circuit_s_g2_y1",
-        &args.verify_circuit_s_g2.y.1.to_str_radix(10),
-    );
-    ctx.insert(
-        "verify_circuit_n_g2_x0",
-        &args.verify_circuit_n_g2.x.0.to_str_radix(10),
-    );
-    ctx.insert(
-        "verify_circuit_n_g2_x1",
-        &args.verify_circuit_n_g2.x.1.to_str_radix(10),
+    let terms = collect_pairs(args);
+    let input = pairing_input(
+        &terms,
+        |g1, w| {
+            let point = match g1 {
+                PairingG1::Wx => "(x0, y0)".to_owned(),
+                PairingG1::Wg => "(x1, y1)".to_owned(),
+                PairingG1::FinalPair(i) => format!(
+                    "(target_circuit_final_pair[{}], target_circuit_final_pair[{}])",
+                    i,
+                    i + 1
+                ),
+            };
+            vec![format!("(input[{}], input[{}]) = {};", w, w + 1, point)]
+        },
+        |v, w| format!("input[{}] = {};", w, v.to_str_radix(10)),
     );
+    let scale = pairing_scale(&terms, |w| {
+        vec![format!(
+            "(input[{}], input[{}]) = ecc_mul_add(input[{}], input[{}], r, 0, 0);",
+            w,
+            w + 1,
+            w,
+            w + 1
+        )]
+    });
+    ctx.insert("pairing_input", &input);
+    ctx.insert("pairing_scale", &scale);
+    ctx.insert("instance_assign", &instance_assign);
+    insert_g2_points(&mut ctx, args, |v| v.to_str_radix(10));
+    ctx.insert("memory_size", &args.memory_size);
+    ctx.insert("instance_size", &args.instance_size);
+    ctx.insert("absorbing_length", &args.absorbing_length);
+    insert_solidity_options(&mut ctx, options);
+    ctx.insert("compressed", &options.compressed);
+    // verify_compressed decompresses into memory, the other entries pass calldata on
+    let data_location = if options.compressed {
+        "memory"
+    } else {
+        "calldata"
+    };
+    ctx.insert("data_location", data_location);
+    ctx.insert("vk_size", &vk_size);
+    ctx.insert("shared_lib", &options.shared_library);
+    ctx.insert("keccak", &(transcript_hash == TranscriptHash::Keccak256));
+    if options.compressed {
+        ctx.insert("proof_len", &layout.proof_len);
+        ctx.insert("proof_points", &layout.proof_points_bitmap());
+        ctx.insert("compressed_length", &layout.compressed_length());
+    }
+    let template = if steps.len() == 1 {
+        ctx.insert("statements", &steps[0]);
+        "verifier.sol"
+    } else {
+        info!(
+            "verifier exceeds the EIP-170 code size limit, split into {} steps",
+            steps.len()
+        );
+        ctx.insert("steps", &steps);
+        "verifier_split.sol"
+    };
+    tera.render(template, &ctx)
+        .expect("failed to render template")
+}
+
+// Renders the solidity verifier split on the estimated statement sizes, then compiles it with
+// the other sources (pairing library, vk contract) and splits it again on a budget fitted to
+// the compiled size of its steps, see split::fit_budget: a smaller one while a step is over
+// the EIP-170 limit, a larger one while it saves a step. Returns the last rendering whose
+// contracts all fit.
+#[cfg(feature = "solc")]
+fn fit_split_verifier(
+    assignments: &[Statement],
+    options: &SolidityOptions,
+    sources: &[(&str, &str)],
+    render: impl Fn(usize) -> String,
+) -> String {
+    const ROUNDS: usize = 4;
+    let mut budget = ESTIMATED_BUDGET;
+    let mut sol = render(budget);
+    let mut fitting = None;
+    for _ in 0..ROUNDS {
+        let parts = split_statements(assignments, budget);
+        let mut all_sources = vec![("verifier.sol", sol.as_str())];
+        all_sources.extend_from_slice(sources);
+        let contracts = solc::compile(&all_sources)
+            .unwrap_or_else(|e| panic!("generated verifier rejected: {}", e));
+        let code_size = |name: String| {
+            let name = format!("verifier.sol:{}", name);
+            contracts
+                .iter()
+                .find(|c| c.name == name)
+                .map_or(0, |c| c.code_size)
+        };
+        // an unsplit verifier has its statements in the verifier contract itself
+        let sizes = parts
+            .iter()
+            .enumerate()
+            .map(|(i, part)| {
+                let estimate = part.iter().map(estimate_code_size).sum::<usize>();
+                let name = if parts.len() == 1 {
+                    options.contract_name.clone()
+                } else {
+                    format!("{}Step{}", options.contract_name, i)
+                };
+                (estimate, code_size(name))
+            })
+            .collect::<Vec<_>>();
+
+        let oversized = sizes.iter().any(|(_, size)| *size > solc::CODE_SIZE_LIMIT);
+        if !oversized {
+            if parts.len() == 1 {
+                return sol;
+            }
+            fitting = Some(sol.clone());
+        }
+        let next = match fit_budget(&sizes) {
+            Some(fitted) if oversized => fitted.min(budget * 9 / 10),
+            Some(fitted) => fitted,
+            None => budget * 9 / 10,
+        };
+        if !oversized && split_statements(assignments, next).len() >= parts.len() {
+            break;
+        }
+        info!(
+            "verifier steps compile to {:?} bytes, split again with a budget of {}",
+            sizes.iter().map(|(_, size)| size).collect::<Vec<_>>(),
+            next
+        );
+        budget = next;
+        sol = render(budget);
+    }
+    fitting.unwrap_or(sol)
+}
+
+// Without solc the split is only sized on the estimated statement sizes.
+#[cfg(not(feature = "solc"))]
+fn fit_split_verifier(
+    _: &[Statement],
+    _: &SolidityOptions,
+    _: &[(&str, &str)],
+    render: impl Fn(usize) -> String,
+) -> String {
+    render(ESTIMATED_BUDGET)
+}
+
+// VerifyingKey contract holding the constants extracted by extract_vk_table.
+fn render_vk_contract(
+    template_folder: Option<PathBuf>,
+    table: &[BigUint],
+    options: &SolidityOptions,
+) -> String {
+    let tera = load_templates(&template_folder);
+    let mut ctx = Context::new();
+    let table = table
+        .iter()
+        .map(|w| format!("{:0>64}", w.to_str_radix(16)))
+        .collect::<String>();
+    ctx.insert("table", &table);
+    insert_solidity_options(&mut ctx, options);
+    tera.render("verifier_vk.sol", &ctx)
+        .expect("failed to render template")
+}
+
+// VerifierPairing library with the precompile helpers, deployed once and linked
+// into the verifiers generated with SolidityOptions::shared_library.
+pub fn render_pairing_library(template_folder: Option<PathBuf>, pragma: &str) -> String {
+    let tera = load_templates(&template_folder);
+    let mut ctx = Context::new();
+    ctx.insert("pragma", pragma);
+    tera.render("verifier_pairing.sol", &ctx)
+        .expect("failed to render template")
+}
+
+// BatchVerifier contract, it imports ./verifier.sol and must be deployed instead of it.
+// instance_size is the number of words of a target circuit final pair, options those
+// the verifier was generated with.
+pub fn render_batch_verifier(
+    template_folder: Option<PathBuf>,
+    instance_size: usize,
+    options: &SolidityOptions,
+    steps: usize,
+) -> String {
+    let tera = load_templates(&template_folder);
+    let mut ctx = Context::new();
+    ctx.insert("instance_size", &instance_size);
+    ctx.insert("steps", &steps);
+    insert_solidity_options(&mut ctx, options);
+    tera.render("verifier_batch.sol", &ctx)
+        .expect("failed to render template")
+}
+
+// Hash of a vk pinned by UpgradeableVerifier, the one of the proof envelopes.
+pub use halo2_snark_aggregator_circuit::envelope::vk_hash;
+
+// UpgradeableVerifier (UUPS implementation extending Verifier, it imports
+// ./verifier.sol) and VerifierProxy contracts. The implementation pins vk_hash,
+// upgrades must name the vk hash of the new implementation. steps is the number of
+// steps of a split Verifier, see verifier_steps, the implementation passes their
+// addresses on to its constructor. options are those the verifier was generated with.
+pub fn render_upgradeable_verifier(
+    template_folder: Option<PathBuf>,
+    vk_hash: &[u8; 32],
+    options: &SolidityOptions,
+    steps: usize,
+) -> String {
+    let tera = load_templates(&template_folder);
+    let mut ctx = Context::new();
+    ctx.insert("steps", &steps);
+    let vk_hash = vk_hash
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    ctx.insert("vk_hash", &vk_hash);
+    insert_solidity_options(&mut ctx, options);
+    tera.render("verifier_uups.sol", &ctx)
+        .expect("failed to render template")
+}
+
+// DeferredPairingVerifier contract, checking only the final pair of an aggregation
+// proof verified off-chain with the g2 points of the target circuit setup. The
+// caller passes the vk hash the final pair was accumulated for, it must be vk_hash.
+pub fn render_deferred_pairing_contract<
+    C: CurveAffine,
+    E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>,
+>(
+    template_folder: Option<PathBuf>,
+    target: &SolidityGenerate<C>,
+    vk_hash: &[u8; 32],
+    pragma: &str,
+) -> String {
+    let target_params = target
+        .target_circuit_params
+        .verifier::<E>(target.target_circuit_vk.cs.num_instance_columns)
+        .unwrap();
+    let words = |p: G2Point| [p.x.0, p.x.1, p.y.0, p.y.1].map(|w| w.to_string());
+
+    let tera = load_templates(&template_folder);
+    let mut ctx = Context::new();
+    let vk_hash = vk_hash
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    ctx.insert("vk_hash", &vk_hash);
+    ctx.insert("s_g2", &words(get_xy_from_g2point::<E>(target_params.s_g2)));
+    ctx.insert("n_g2", &words(get_xy_from_g2point::<E>(-target_params.g2)));
+    ctx.insert("pragma", pragma);
+    tera.render("verifier_deferred.sol", &ctx)
+        .expect("failed to render template")
+}
+
+// Context of the contracts settling final pairs outside the evm: the vk hash in hex
+// and the (x.c0, x.c1, y.c0, y.c1) words of s_g2 and -g2 of the target circuit setup.
+fn settlement_context<C: CurveAffine, E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>>(
+    target: &SolidityGenerate<C>,
+    vk_hash: &[u8; 32],
+) -> Context {
+    let target_params = target
+        .target_circuit_params
+        .verifier::<E>(target.target_circuit_vk.cs.num_instance_columns)
+        .unwrap();
+    // G2Point holds the precompile order (c1, c0)
+    let words = |p: G2Point| [p.x.1, p.x.0, p.y.1, p.y.0].map(|w| w.to_string());
+
+    let mut ctx = Context::new();
+    let vk_hash = vk_hash
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    ctx.insert("vk_hash", &vk_hash);
+    ctx.insert("s_g2", &words(get_xy_from_g2point::<E>(target_params.s_g2)));
+    ctx.insert("n_g2", &words(get_xy_from_g2point::<E>(-target_params.g2)));
+    ctx
+}
+
+// {"final_pair": [..]} of the 4 first words of the final pair, in decimal.
+fn settlement_args(final_pair: &[u8]) -> String {
+    let final_pair = final_pair[..128]
+        .chunks(32)
+        .map(|chunk| format!("\"{}\"", BigUint::from_bytes_le(chunk)))
+        .collect::<Vec<_>>();
+    format!("{{\"final_pair\": [{}]}}", final_pair.join(", "))
+}
+
+// CosmWasm contract crate settling final pairs like DeferredPairingVerifier, as
+// (path, content) files: Cargo.toml, the contract, its messages, the schema binary
+// and the verify message of the final pair of the run as a fixture. vk_hash is only
+// recorded in the contract, the final pair is not bound to it.
+pub fn render_cosmwasm_project<
+    C: CurveAffine,
+    E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>,
+>(
+    template_folder: Option<PathBuf>,
+    target: &SolidityGenerate<C>,
+    vk_hash: &[u8; 32],
+    final_pair: &[u8],
+) -> Vec<(String, String)> {
+    let tera = load_templates(&template_folder);
+    let ctx = settlement_context::<C, E>(target, vk_hash);
+    let render = |name: &str| tera.render(name, &ctx).expect("failed to render template");
+    let verify_msg = format!("{{\"verify\": {}}}\n", settlement_args(final_pair));
+
+    vec![
+        ("Cargo.toml".to_owned(), render("cosmwasm.Cargo.toml")),
+        ("src/lib.rs".to_owned(), render("cosmwasm.lib.rs")),
+        ("src/msg.rs".to_owned(), render("cosmwasm.msg.rs")),
+        ("src/bin/schema.rs".to_owned(), render("cosmwasm.schema.rs")),
+        ("fixtures/verify.json".to_owned(), verify_msg),
+    ]
+}
+
+// NEAR contract crate settling final pairs with the alt_bn128 host functions, as
+// (path, content) files: Cargo.toml, the contract and the arguments of its verify
+// method for the final pair of the run as a fixture. vk_hash is only recorded in the
+// contract, as for CosmWasm.
+pub fn render_near_project<
+    C: CurveAffine,
+    E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>,
+>(
+    template_folder: Option<PathBuf>,
+    target: &SolidityGenerate<C>,
+    vk_hash: &[u8; 32],
+    final_pair: &[u8],
+) -> Vec<(String, String)> {
+    let tera = load_templates(&template_folder);
+    let ctx = settlement_context::<C, E>(target, vk_hash);
+    let render = |name: &str| tera.render(name, &ctx).expect("failed to render template");
+
+    vec![
+        ("Cargo.toml".to_owned(), render("near.Cargo.toml")),
+        ("src/lib.rs".to_owned(), render("near.lib.rs")),
+        (
+            "fixtures/verify.json".to_owned(),
+            format!("{}\n", settlement_args(final_pair)),
+        ),
+    ]
+}
+
+// Solana program crate settling final pairs with the alt_bn128 syscalls, as (path,
+// content) files: Cargo.toml, the program and, as a fixture, the hex instruction data
+// of the final pair array of the run (final pair and target circuit instances).
+pub fn render_solana_project<
+    C: CurveAffine,
+    E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>,
+>(
+    template_folder: Option<PathBuf>,
+    target: &SolidityGenerate<C>,
+    vk_hash: &[u8; 32],
+    final_pair: &[u8],
+) -> Vec<(String, String)> {
+    let target_params = target
+        .target_circuit_params
+        .verifier::<E>(target.target_circuit_vk.cs.num_instance_columns)
+        .unwrap();
+    let be_word = |w: &BigUint| {
+        let mut bytes = w.to_bytes_le();
+        bytes.resize(32, 0);
+        bytes.reverse();
+        bytes
+    };
+    let byte_list = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .map(|b| format!("{:#04x}", b))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    // the pairing syscall takes the input of the evm precompile
+    let g2_bytes = |p: G2Point| {
+        byte_list(
+            &[p.x.0, p.x.1, p.y.0, p.y.1]
+                .iter()
+                .flat_map(be_word)
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    let tera = load_templates(&template_folder);
+    let mut ctx = Context::new();
+    ctx.insert("vk_hash_bytes", &byte_list(vk_hash));
     ctx.insert(
-        "verify_circuit_n_g2_y0",
-        &args.verify_circuit_n_g2.y.0.to_str_radix(10),
+        "s_g2_bytes",
+        &g2_bytes(get_xy_from_g2point::<E>(target_params.s_g2)),
     );
     ctx.insert(
-        "verify_circuit_n_g2_y1",
-        &args.verify_circuit_n_g2.y.1.to_str_radix(10),
+        "n_g2_bytes",
+        &g2_bytes(get_xy_from_g2point::<E>(-target_params.g2)),
     );
-    ctx.insert("memory_size", &args.memory_size);
-    ctx.insert("instance_size", &args.instance_size);
-    ctx.insert("absorbing_length", &args.absorbing_length);
-    tera.render("verifier.sol", &ctx)
+    let render = |name: &str| tera.render(name, &ctx).expect("failed to render template");
+
+    let instruction_data = vk_hash
+        .iter()
+        .cloned()
+        .chain(
+            final_pair
+                .chunks(32)
+                .flat_map(|chunk| chunk.iter().rev().cloned().collect::<Vec<_>>()),
+        )
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+
+    vec![
+        ("Cargo.toml".to_owned(), render("solana.Cargo.toml")),
+        ("src/lib.rs".to_owned(), render("solana.lib.rs")),
+        (
+            "fixtures/instruction_data.hex".to_owned(),
+            format!("{}\n", instruction_data),
+        ),
+    ]
+}
+
+// Words of the final pair array (final pair followed by the target circuit
+// instances) holding the state transition checked by EpochVerifier.
+#[derive(Clone, Copy, Debug)]
+pub struct EpochInstances {
+    pub prev_root: usize,
+    pub new_root: usize,
+    pub epoch: usize,
+}
+
+// EpochVerifier contract wrapping a deployed verifier with an epoch counter and a
+// state root, it imports IVerifier from ./verifier.sol.
+pub fn render_epoch_contract(
+    template_folder: Option<PathBuf>,
+    instances: &EpochInstances,
+    pragma: &str,
+) -> String {
+    let indices = [instances.prev_root, instances.new_root, instances.epoch];
+    // the first 4 words are the final pair points
+    assert!(indices.iter().all(|i| *i >= 4));
+    assert!(indices[0] != indices[1] && indices[1] != indices[2] && indices[0] != indices[2]);
+
+    let tera = load_templates(&template_folder);
+    let mut ctx = Context::new();
+    ctx.insert("prev_root_index", &instances.prev_root);
+    ctx.insert("new_root_index", &instances.new_root);
+    ctx.insert("epoch_index", &instances.epoch);
+    ctx.insert("max_index", indices.iter().max().unwrap());
+    ctx.insert("pragma", pragma);
+    tera.render("verifier_epoch.sol", &ctx)
         .expect("failed to render template")
 }
 
+// Number of step contracts of a verifier rendered from verifier_split.sol, 0 when it isn't
+// split. They are deployed before the verifier, whose constructor takes their addresses.
+pub fn verifier_steps(sol: &str, contract_name: &str) -> usize {
+    (0..)
+        .take_while(|i| sol.contains(&format!("contract {}Step{} is ", contract_name, i)))
+        .count()
+}
+
+// Renders (foundry.toml, test/Verifier.t.sol) with the proof and final pair
+// of the current run as fixtures for the verifier generated with options, which
+// has the given number of steps, see verifier_steps.
+pub fn render_foundry_suite(
+    template_folder: Option<PathBuf>,
+    proof: &[u8],
+    final_pair: &[u8],
+    options: &SolidityOptions,
+    steps: usize,
+) -> Result<(String, String), tera::Error> {
+    let tera = try_load_templates(&template_folder)?;
+    let to_hex = |buf: &[u8]| buf.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+
+    let mut ctx = Context::new();
+    ctx.insert("proof", &to_hex(proof));
+    ctx.insert("final_pair", &to_hex(final_pair));
+    ctx.insert("steps", &steps);
+    insert_solidity_options(&mut ctx, options);
+
+    let config = tera.render("foundry.toml", &Context::new())?;
+    let test = tera.render("Verifier.t.sol", &ctx)?;
+    Ok((config, test))
+}
+
+pub const VERIFIER_ABI: &str = r#"[
+  {
+    "inputs": [
+      { "internalType": "uint256[]", "name": "proof", "type": "uint256[]" },
+      { "internalType": "uint256[]", "name": "target_circuit_final_pair", "type": "uint256[]" }
+    ],
+    "name": "verify",
+    "outputs": [],
+    "stateMutability": "view",
+    "type": "function"
+  }
+]
+"#;
+
+// VERIFIER_ABI for the reverting view check of a verifier generated with options.
+pub fn verifier_abi(options: &SolidityOptions) -> String {
+    VERIFIER_ABI.replace("\"verify\"", &format!("\"{}\"", check_fn(options)))
+}
+
+// abi encoded verify(uint256[] proof, uint256[] target_circuit_final_pair),
+// words are little endian 32 bytes chunks of the serialized proof and final pair.
+pub fn encode_calldata(proof: &[u8], final_pair: &[u8]) -> Vec<u8> {
+    let to_words = |buf: &[u8]| {
+        buf.chunks(32)
+            .map(BigUint::from_bytes_le)
+            .collect::<Vec<_>>()
+    };
+    let encode_word = |v: &BigUint| {
+        let bytes = v.to_bytes_be();
+        let mut word = vec![0u8; 32 - bytes.len()];
+        word.extend(bytes);
+        word
+    };
+    let proof = to_words(proof);
+    let final_pair = to_words(final_pair);
+
+    let mut calldata = sha3::Keccak256::digest(b"verify(uint256[],uint256[])")[..4].to_vec();
+    calldata.extend(encode_word(&BigUint::from(0x40u64)));
+    calldata.extend(encode_word(&BigUint::from(0x60 + proof.len() * 0x20)));
+    for words in [proof, final_pair] {
+        calldata.extend(encode_word(&BigUint::from(words.len())));
+        for word in words.iter() {
+            calldata.extend(encode_word(word));
+        }
+    }
+    calldata
+}
+
+// ABI of the IVerifier interface implemented by every generated verifier.
+pub const IVERIFIER_ABI: &str = r#"[
+  {
+    "inputs": [
+      { "internalType": "bytes", "name": "proof", "type": "bytes" },
+      { "internalType": "uint256[]", "name": "instances", "type": "uint256[]" }
+    ],
+    "name": "verify",
+    "outputs": [{ "internalType": "bool", "name": "", "type": "bool" }],
+    "stateMutability": "view",
+    "type": "function"
+  }
+]
+"#;
+
+// TypeScript module (ethers v6) encoding the proof and final pair written by the sdk
+// into the calldata of verify, IVerifier.verify and verify_compressed, for the
+// verifier whose calldata layout is given.
+pub fn render_calldata_encoder(
+    template_folder: Option<PathBuf>,
+    layout: &CalldataLayout,
+) -> String {
+    let tera = load_templates(&template_folder);
+    let mut ctx = Context::new();
+    ctx.insert("proof_len", &layout.proof_len);
+    ctx.insert("proof_points", &layout.proof_points);
+    ctx.insert("instance_size", &layout.instance_size);
+    tera.render("encoder.ts", &ctx)
+        .expect("failed to render template")
+}
+
+// Files of a hardhat project around the verifier sol generated with options, as
+// (relative path, content). The proof fixture is the encoded envelope, in hex, that
+// the call script checks and opens. The bytecode is left to `npx hardhat compile`,
+// which writes it under artifacts/.
+pub fn render_hardhat_project(
+    template_folder: Option<PathBuf>,
+    sol: &str,
+    envelope: &ProofEnvelope,
+    final_pair: &[u8],
+    options: &SolidityOptions,
+) -> Result<Vec<(String, String)>, tera::Error> {
+    let tera = try_load_templates(&template_folder)?;
+    let render = |name: &str| tera.render(name, &Context::new());
+    let mut ctx = Context::new();
+    ctx.insert("steps", &verifier_steps(sol, &options.contract_name));
+    insert_solidity_options(&mut ctx, options);
+    ctx.insert("envelope_version", &ENVELOPE_VERSION);
+    let abi_path = format!("abi/{}.json", options.contract_name);
+    let envelope_hex = envelope
+        .encode()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    let to_words_json = |buf: &[u8]| {
+        let words = buf
+            .chunks(32)
+            .map(|chunk| format!("\"{}\"", BigUint::from_bytes_le(chunk)))
+            .collect::<Vec<_>>();
+        format!("[\n  {}\n]\n", words.join(",\n  "))
+    };
+
+    Ok(vec![
+        ("package.json".to_owned(), render("hardhat.package.json")?),
+        ("hardhat.config.ts".to_owned(), render("hardhat.config.ts")?),
+        ("contracts/Verifier.sol".to_owned(), sol.to_owned()),
+        (abi_path, verifier_abi(options)),
+        ("abi/IVerifier.json".to_owned(), IVERIFIER_ABI.to_owned()),
+        (
+            "scripts/deploy.ts".to_owned(),
+            tera.render("hardhat.deploy.ts", &ctx)?,
+        ),
+        (
+            "scripts/call.ts".to_owned(),
+            tera.render("hardhat.call.ts", &ctx)?,
+        ),
+        (
+            "fixtures/proof.envelope".to_owned(),
+            format!("0x{}\n", envelope_hex),
+        ),
+        (
+            "fixtures/final_pair.json".to_owned(),
+            to_words_json(final_pair),
+        ),
+    ])
+}
+
 pub fn g2field_to_bn<F: BaseExt>(f: &F) -> (BigUint, BigUint) {
     let mut bytes: Vec<u8> = Vec::new();
     f.write(&mut bytes).unwrap();
@@ -165,6 +1250,14 @@ pub struct SolidityGenerate<C: CurveAffine> {
 impl SolidityGenerate<G1Affine> {
     pub fn new<SingleCircuit: TargetCircuit<G1Affine, Bn256>>(
         folder: &PathBuf,
+    ) -> SolidityGenerate<G1Affine> {
+        Self::with_nproofs::<SingleCircuit>(folder, SingleCircuit::N_PROOFS)
+    }
+
+    // For a proof count given at the aggregation site instead of the N_PROOFS of the circuit.
+    pub fn with_nproofs<SingleCircuit: TargetCircuit<G1Affine, Bn256>>(
+        folder: &PathBuf,
+        nproofs: usize,
     ) -> SolidityGenerate<G1Affine> {
         let target_circuit_params =
             load_target_circuit_params::<G1Affine, Bn256, SingleCircuit>(&mut folder.clone());
@@ -176,34 +1269,114 @@ impl SolidityGenerate<G1Affine> {
         SolidityGenerate {
             target_circuit_params,
             target_circuit_vk,
-            nproofs: SingleCircuit::N_PROOFS,
+            nproofs,
         }
     }
 }
 
+// Everything generated for a verifier, see MultiCircuitSolidityGenerate::call_with_artifacts.
+pub struct GeneratedVerifier {
+    pub verifier: String,
+    // gas estimate of a verify() call
+    pub gas_report: GasReport,
+    // packs the calldata of verify_compressed with SolidityOptions::compressed
+    pub layout: CalldataLayout,
+    // VerifyingKey contract with SolidityOptions::vk_contract
+    pub vk: Option<String>,
+    // markdown report mapping the constants and code of the contract to the
+    // verifier equations, for third party audits
+    pub audit_report: String,
+}
+
 pub struct MultiCircuitSolidityGenerate<'a, C: CurveAffine, const N: usize> {
     pub target_circuits_params: [SolidityGenerate<C>; N],
     pub verify_params: &'a Params<C>,
     pub verify_vk: &'a VerifyingKey<C>,
-    // serialized instance
-    pub verify_circuit_instance: Vec<Vec<Vec<C::ScalarExt>>>,
-    // serialized proof
-    pub proof: Vec<u8>,
+    // rows of each instance column of the verify circuit, the contract is
+    // generated from the vk, params and this shape without a proof
     pub verify_public_inputs_size: usize,
+    pub transcript_hash: TranscriptHash,
+    // deployment label the verify circuit proof transcript starts with, see
+    // ShaRead::init_with_label; the contract absorbs it as a constant
+    pub transcript_label: Option<Vec<u8>>,
+    pub solidity_options: SolidityOptions,
 }
 
 impl<'a, C: CurveAffine, const N: usize> MultiCircuitSolidityGenerate<'a, C, N> {
     pub fn call<E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>>(
         &self,
-        template_folder: std::path::PathBuf,
+        template_folder: Option<PathBuf>,
     ) -> String {
-        /*
-        for i in self.target_circuits_params.iter() {
-            let v = i.target_circuit_params.verifier::<E>(i.target_circuit_vk.cs.num_instance_columns).unwrap();
-            println!("g2 {:?}", v.s_g2);
-        }
-        */
+        self.call_with_backend::<E>(template_folder, CodegenBackend::Solidity)
+    }
+
+    // Also returns the gas estimate of a verify() call on the generated contract.
+    pub fn call_with_gas_report<E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>>(
+        &self,
+        template_folder: Option<PathBuf>,
+    ) -> (String, GasReport) {
+        let generated = self.call_with_artifacts::<E>(template_folder);
+        (generated.verifier, generated.gas_report)
+    }
 
+    // Also returns the gas report and a markdown report mapping the constants and
+    // code of the contract to the verifier equations, for third party audits.
+    pub fn call_with_audit_report<E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>>(
+        &self,
+        template_folder: Option<PathBuf>,
+    ) -> (String, GasReport, String) {
+        let generated = self.call_with_artifacts::<E>(template_folder);
+        (
+            generated.verifier,
+            generated.gas_report,
+            generated.audit_report,
+        )
+    }
+
+    // The solidity verifier with everything generated along with it, as selected by
+    // solidity_options.
+    pub fn call_with_artifacts<E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>>(
+        &self,
+        template_folder: Option<PathBuf>,
+    ) -> GeneratedVerifier {
+        self.generate::<E>(
+            template_folder,
+            CodegenBackend::Solidity,
+            &self.solidity_options,
+        )
+    }
+
+    pub fn call_with_backend<E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>>(
+        &self,
+        template_folder: Option<PathBuf>,
+        backend: CodegenBackend,
+    ) -> String {
+        self.generate::<E>(template_folder, backend, &self.solidity_options)
+            .verifier
+    }
+
+    // Overrides the pragma of solidity_options, it must allow custom errors,
+    // i.e. 0.8.4 or later.
+    pub fn call_with_pragma<E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>>(
+        &self,
+        template_folder: Option<PathBuf>,
+        pragma: &str,
+    ) -> String {
+        let options = SolidityOptions {
+            pragma: pragma.to_owned(),
+            ..self.solidity_options.clone()
+        };
+        self.generate::<E>(template_folder, CodegenBackend::Solidity, &options)
+            .verifier
+    }
+
+    fn generate<E: MultiMillerLoop<G1Affine = C, Scalar = C::ScalarExt>>(
+        &self,
+        template_folder: Option<PathBuf>,
+        backend: CodegenBackend,
+        options: &SolidityOptions,
+    ) -> GeneratedVerifier {
+        let _span = info_span!("generate_verifier", backend = ?backend).entered();
         let target_params = self.target_circuits_params[0]
             .target_circuit_params
             .verifier::<E>(
@@ -226,9 +1399,10 @@ impl<'a, C: CurveAffine, const N: usize> MultiCircuitSolidityGenerate<'a, C, N>
         let pchip = &SolidityEccChip::new();
         let ctx = &mut SolidityCodeGeneratorContext::new();
 
+        // symbolic walk of the transcript, see SymbolicTranscriptRead
         let mut transcript =
             CodegenTranscriptRead::<_, C, _, PoseidonEncode<_>, 9usize, 8usize>::new(
-                &self.proof[..],
+                SymbolicTranscriptRead::<C>::new(self.transcript_hash),
                 ctx,
                 schip,
                 8usize,
@@ -236,13 +1410,17 @@ impl<'a, C: CurveAffine, const N: usize> MultiCircuitSolidityGenerate<'a, C, N>
             )
             .unwrap();
 
-        let verify_circuit_instance1: Vec<Vec<&[E::Scalar]>> = self
-            .verify_circuit_instance
-            .iter()
-            .map(|x| x.iter().map(|y| &y[..]).collect())
-            .collect();
-        let verify_circuit_instance2: Vec<&[&[E::Scalar]]> =
-            verify_circuit_instance1.iter().map(|x| &x[..]).collect();
+        // instance values are read from the calldata, only their shape matters
+        let verify_circuit_instance: Vec<Vec<E::Scalar>> =
+            vec![
+                (1..=self.verify_public_inputs_size)
+                    .map(|i| E::Scalar::from_u128(i as u128))
+                    .collect();
+                self.verify_vk.cs.num_instance_columns
+            ];
+        let verify_circuit_instance1: Vec<&[E::Scalar]> =
+            verify_circuit_instance.iter().map(|x| &x[..]).collect();
+        let verify_circuit_instance2: Vec<&[&[E::Scalar]]> = vec![&verify_circuit_instance1[..]];
 
         ctx.enter_instance();
         let (_, assigned_instances) = assign_instance_commitment(
@@ -256,6 +1434,11 @@ impl<'a, C: CurveAffine, const N: usize> MultiCircuitSolidityGenerate<'a, C, N>
         .unwrap();
         ctx.exit_instance();
 
+        if let Some(label) = &self.transcript_label {
+            let label = schip.assign_const(ctx, label_scalar::<C>(label)).unwrap();
+            transcript.common_scalar(ctx, nchip, schip, &label).unwrap();
+        }
+
         let (proof, _) = verify_single_proof_no_eval(
             ctx,
             nchip,
@@ -310,12 +1493,506 @@ impl<'a, C: CurveAffine, const N: usize> MultiCircuitSolidityGenerate<'a, C, N>
             },
         };
 
+        let sol_ctx: CodeGeneratorCtx = optimize(sol_ctx);
         let sol_ctx: CodeGeneratorCtx = memory_optimize(sol_ctx);
         let sol_ctx: CodeGeneratorCtx = aggregate(sol_ctx);
+        let solidity = backend == CodegenBackend::Solidity;
+        let sol_ctx: CodeGeneratorCtx = if solidity && options.msm_loop {
+            loop_msm(sol_ctx)
+        } else {
+            sol_ctx
+        };
+        let sol_ctx: CodeGeneratorCtx = spill_expressions(sol_ctx);
+
+        let symbolic_proof = transcript.finalize().finalize();
+        let gas_report = estimate_gas(&sol_ctx, &symbolic_proof[..], self.transcript_hash);
+        info!("estimated verify gas:\n{}", gas_report);
+        let layout = CalldataLayout::new(&sol_ctx, (symbolic_proof.len() + 31) / 32);
+
+        let audit_report = audit_report(&sol_ctx, self.transcript_hash);
+
+        let (sol_ctx, vk_table, vk) = if solidity && options.vk_contract {
+            let (sol_ctx, vk_table) = extract_vk_table(sol_ctx);
+            let vk = render_vk_contract(template_folder.clone(), &vk_table, options);
+            (sol_ctx, vk_table, Some(vk))
+        } else {
+            (sol_ctx, vec![], None)
+        };
+
+        let template = match backend {
+            CodegenBackend::Solidity => {
+                let pairing_lib = options
+                    .shared_library
+                    .then(|| render_pairing_library(template_folder.clone(), &options.pragma));
+                let mut sources = vec![];
+                if let Some(lib) = &pairing_lib {
+                    sources.push(("verifier_pairing.sol", lib.as_str()));
+                }
+                if let Some(vk) = &vk {
+                    sources.push(("verifier_vk.sol", vk.as_str()));
+                }
+                fit_split_verifier(&sol_ctx.assignments, options, &sources, |budget| {
+                    render_verifier_sol_template::<C>(
+                        &sol_ctx,
+                        template_folder.clone(),
+                        options,
+                        &layout,
+                        vk_table.len(),
+                        self.transcript_hash,
+                        budget,
+                    )
+                })
+            }
+            CodegenBackend::Yul => render_verifier_yul_template(
+                sol_ctx,
+                template_folder.clone(),
+                self.transcript_hash,
+                layout.proof_len,
+            ),
+            CodegenBackend::Huff => render_verifier_huff_template(
+                sol_ctx,
+                template_folder.clone(),
+                self.transcript_hash,
+            ),
+            CodegenBackend::Vyper => render_verifier_vyper_template(
+                sol_ctx,
+                template_folder.clone(),
+                self.transcript_hash,
+                layout.proof_len,
+            ),
+            CodegenBackend::Move => render_verifier_move_template(
+                sol_ctx,
+                template_folder.clone(),
+                self.transcript_hash,
+            ),
+        };
+        info!("generate {:?} verifier succeeds", backend);
+
+        #[cfg(feature = "solc")]
+        if backend == CodegenBackend::Solidity {
+            let pairing_lib = options
+                .shared_library
+                .then(|| render_pairing_library(template_folder.clone(), &options.pragma));
+            let mut sources = vec![("verifier.sol", template.as_str())];
+            if let Some(lib) = &pairing_lib {
+                sources.push(("verifier_pairing.sol", lib.as_str()));
+            }
+            if let Some(vk) = &vk {
+                sources.push(("verifier_vk.sol", vk.as_str()));
+            }
+            if let Err(e) = solc::check_contracts(&sources) {
+                panic!("generated verifier rejected: {}", e);
+            }
+        }
+
+        GeneratedVerifier {
+            verifier: template,
+            gas_report,
+            layout,
+            vk,
+            audit_report,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::plonk::{keygen_vk, Circuit};
+    use halo2_snark_aggregator_api::tests::systems::halo2::{add_mul_test, lookup_test};
+    use halo2_snark_aggregator_circuit::envelope::{EnvelopeCurve, EnvelopeTranscript};
+    use pairing_bn256::bn256::Fr;
+
+    fn envelope(proof: Vec<u8>) -> ProofEnvelope {
+        ProofEnvelope {
+            curve: EnvelopeCurve::Bn256,
+            transcript: EnvelopeTranscript::Sha256,
+            vk_hash: [0xab; 32],
+            instance_layout: vec![4],
+            proof,
+        }
+    }
+
+    // Numbers of 20 digits or more depend on the random setup of unsafe_setup, they
+    // are replaced so that the snapshots only change with the generated code.
+    fn redact(code: &str) -> String {
+        let mut redacted = String::new();
+        let mut token = String::new();
+        let flush = |token: &mut String, redacted: &mut String| {
+            let digits = token.strip_prefix("0x").unwrap_or(token.as_str());
+            if digits.len() >= 20 && digits.chars().all(|c| c.is_ascii_hexdigit()) {
+                redacted.push_str(if token.starts_with("0x") {
+                    "0x<N>"
+                } else {
+                    "<N>"
+                });
+            } else {
+                redacted.push_str(token);
+            }
+            token.clear();
+        };
+        for c in code.chars() {
+            if c.is_ascii_alphanumeric() {
+                token.push(c);
+            } else {
+                flush(&mut token, &mut redacted);
+                redacted.push(c);
+            }
+        }
+        flush(&mut token, &mut redacted);
+        redacted
+    }
+
+    // Contract generated for the vk of circuit, used as both the target and the
+    // verify circuit.
+    fn generate_for<ConcreteCircuit: Circuit<Fr>>(
+        k: u32,
+        circuit: &ConcreteCircuit,
+        backend: CodegenBackend,
+    ) -> String {
+        redact(&generate_with_label(k, circuit, backend, None))
+    }
+
+    fn generate_with_label<ConcreteCircuit: Circuit<Fr>>(
+        k: u32,
+        circuit: &ConcreteCircuit,
+        backend: CodegenBackend,
+        transcript_label: Option<Vec<u8>>,
+    ) -> String {
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(k);
+        let vk = keygen_vk(&params, circuit).unwrap();
+        let target_circuit_params = Params::<G1Affine>::unsafe_setup::<Bn256>(k);
+        let target_circuit_vk = keygen_vk(&target_circuit_params, circuit).unwrap();
+        let generate = MultiCircuitSolidityGenerate {
+            target_circuits_params: [SolidityGenerate {
+                target_circuit_params,
+                target_circuit_vk,
+                nproofs: 1,
+            }],
+            verify_params: &params,
+            verify_vk: &vk,
+            verify_public_inputs_size: 4,
+            transcript_hash: TranscriptHash::Sha256,
+            transcript_label,
+            solidity_options: SolidityOptions::default(),
+        };
+
+        let first = generate.call_with_backend::<Bn256>(None, backend);
+        // the code generation only depends on its inputs
+        let second = generate.call_with_backend::<Bn256>(None, backend);
+        assert_eq!(first, second, "code generation is not deterministic");
+        first
+    }
+
+    #[test]
+    fn test_redact() {
+        let code = "m[0] = 12345678901234567890;\nmstore(0x80, 0x0123456789abcdef0123) // 123";
+        assert_eq!(redact(code), "m[0] = <N>;\nmstore(0x80, 0x<N>) // 123");
+        assert_eq!(redact("1234567890123456789"), "1234567890123456789");
+    }
+
+    // Snapshots of the generated contracts, recorded with `cargo insta test --accept`.
+    // The lookup circuit has a lookup argument and no custom gate, the add mul one a
+    // degree 3 gate, copy constraints and no lookup.
+    #[test]
+    fn test_snapshot_lookup_circuit() {
+        let circuit = lookup_test::test_circuit::test_circuit_builder();
+        insta::assert_snapshot!(
+            "lookup_solidity",
+            generate_for(6, &circuit, CodegenBackend::Solidity)
+        );
+        insta::assert_snapshot!("lookup_yul", generate_for(6, &circuit, CodegenBackend::Yul));
+    }
+
+    #[test]
+    fn test_snapshot_add_mul_circuit() {
+        let circuit = add_mul_test::test_circuit_builder(Fr::from(2), Fr::from(3));
+        insta::assert_snapshot!(
+            "add_mul_solidity",
+            generate_for(10, &circuit, CodegenBackend::Solidity)
+        );
+        insta::assert_snapshot!(
+            "add_mul_yul",
+            generate_for(10, &circuit, CodegenBackend::Yul)
+        );
+    }
+
+    // The label is absorbed as one more constant scalar before the vk hash.
+    #[test]
+    fn test_transcript_label() {
+        let circuit = lookup_test::test_circuit::test_circuit_builder();
+        let generate = |label: Option<&[u8]>| {
+            generate_with_label(
+                6,
+                &circuit,
+                CodegenBackend::Solidity,
+                label.map(|l| l.to_vec()),
+            )
+        };
+        let unlabelled = generate(None);
+        let mainnet = generate(Some(b"mainnet"));
+        assert_ne!(unlabelled, mainnet);
+        assert_ne!(mainnet, generate(Some(b"testnet")));
+    }
+
+    #[test]
+    fn test_embedded_templates() {
+        let tera = load_templates(&None);
+        for (name, _) in EMBEDDED_TEMPLATES.iter() {
+            assert!(tera.get_template_names().any(|n| n == *name));
+        }
+
+        let options = SolidityOptions::default();
+        let (_, test) = render_foundry_suite(None, &[1, 2], &[0xab], &options, 0).unwrap();
+        assert!(test.contains("0102"));
+        assert!(test.contains("ab"));
+
+        let folder = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("templates");
+        let tera = load_templates(&Some(folder));
+        assert!(tera.get_template_names().any(|n| n == "verifier.sol"));
+
+        let missing = Some(PathBuf::from("/nonexistent/templates"));
+        assert!(try_load_templates(&missing).is_err());
+        assert!(render_foundry_suite(missing.clone(), &[1], &[2], &options, 0).is_err());
+        assert!(render_hardhat_project(missing, "", &envelope(vec![1]), &[2], &options).is_err());
+    }
+
+    #[test]
+    fn test_render_pairing_library() {
+        let sol = render_pairing_library(None, DEFAULT_SOLIDITY_PRAGMA);
+        assert!(sol.contains("library VerifierPairing"));
+        assert!(!sol.contains("{{"));
+    }
+
+    #[test]
+    fn test_render_calldata_encoder() {
+        let layout = CalldataLayout {
+            proof_len: 10,
+            proof_points: vec![0, 4],
+            instance_size: 6,
+        };
+        let ts = render_calldata_encoder(None, &layout);
+        assert!(ts.contains("export const PROOF_LEN = 10;"));
+        assert!(ts.contains("export const INSTANCE_SIZE = 6;"));
+        assert!(ts.contains("PROOF_POINTS: readonly number[] = [0, 4];"));
+    }
+
+    #[test]
+    fn test_render_batch_verifier() {
+        let sol = render_batch_verifier(None, 8, &SolidityOptions::default(), 0);
+        assert!(sol.contains("pragma solidity ^0.8.4;"));
+        assert!(sol.contains("contract BatchVerifier is Verifier"));
+        assert!(sol.contains("uint256[8] memory instances"));
+
+        let options = SolidityOptions {
+            contract_name: "AggregationVerifier".to_owned(),
+            license: "MIT".to_owned(),
+            ..SolidityOptions::default()
+        };
+        let sol = render_batch_verifier(None, 8, &options, 2);
+        assert!(sol.starts_with("// SPDX-License-Identifier: MIT\n"));
+        assert!(sol.contains("contract BatchVerifier is AggregationVerifier"));
+        assert!(sol.contains("AggregationVerifier(steps) {}"));
+    }
+
+    #[test]
+    fn test_render_epoch_contract() {
+        let instances = EpochInstances {
+            prev_root: 4,
+            new_root: 5,
+            epoch: 6,
+        };
+        let sol = render_epoch_contract(None, &instances, DEFAULT_SOLIDITY_PRAGMA);
+        assert!(sol.contains("contract EpochVerifier"));
+        assert!(sol.contains("if (instances.length <= 6)"));
+        assert!(sol.contains("stateRoot = instances[5];"));
+        assert!(!sol.contains("{{"));
+    }
+
+    #[test]
+    fn test_render_upgradeable_verifier() {
+        let options = SolidityOptions::default();
+        let sol = render_upgradeable_verifier(None, &[0xab; 32], &options, 0);
+        assert!(sol.contains("contract UpgradeableVerifier is Verifier"));
+        assert!(sol.contains("contract VerifierProxy"));
+        assert!(sol.contains(&format!("VK_HASH = 0x{};", "ab".repeat(32))));
+        assert!(!sol.contains("constructor(address["));
+
+        let sol = render_upgradeable_verifier(None, &[0xab; 32], &options, 3);
+        assert!(sol.contains("constructor(address[3] memory steps) Verifier(steps) {}"));
+    }
+
+    #[test]
+    fn test_verifier_steps() {
+        let sol = "contract VerifierStep0 is VerifierBase {}\n\
+                   contract VerifierStep1 is VerifierBase {}";
+        assert_eq!(verifier_steps(sol, "Verifier"), 2);
+        assert_eq!(verifier_steps(sol, "Other"), 0);
+        let unsplit = "contract Verifier is IVerifier {}";
+        assert_eq!(verifier_steps(unsplit, "Verifier"), 0);
+
+        let options = SolidityOptions::default();
+        let (_, test) = render_foundry_suite(None, &[1], &[2], &options, 2).unwrap();
+        assert!(test.contains("steps[1] = address(new VerifierStep1());"));
+        assert!(test.contains("verifier = new Verifier(steps);"));
+    }
+
+    #[test]
+    fn test_render_projects_with_options() {
+        let options = SolidityOptions {
+            contract_name: "AggregationVerifier".to_owned(),
+            revert_on_failure: false,
+            ..SolidityOptions::default()
+        };
+        let (_, test) = render_foundry_suite(None, &[1], &[2], &options, 1).unwrap();
+        assert!(test.contains("steps[0] = address(new AggregationVerifierStep0());"));
+        assert!(test.contains("verifier = new AggregationVerifier(steps);"));
+        assert!(test.contains("verifier.check(to_words(PROOF), to_words(FINAL_PAIR));"));
+        assert!(!test.contains("{{"));
+
+        let sol = "contract AggregationVerifierStep0 is AggregationVerifierBase {}";
+        let files = render_hardhat_project(None, sol, &envelope(vec![1]), &[2], &options).unwrap();
+        let file = |path: &str| &files.iter().find(|(p, _)| p == path).unwrap().1;
+        let fixture = file("fixtures/proof.envelope").trim();
+        let bytes = (2..fixture.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&fixture[i..i + 2], 16).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ProofEnvelope::decode(&bytes), Ok(envelope(vec![1])));
+        let call = file("scripts/call.ts");
+        assert!(call.contains(&format!("!== {}", ENVELOPE_VERSION)));
+        assert!(file("abi/AggregationVerifier.json").contains("\"name\": \"check\""));
+        let deploy = file("scripts/deploy.ts");
+        assert!(deploy.contains("i < 1;"));
+        assert!(deploy.contains("getContractFactory(\"AggregationVerifier\")"));
+        assert!(file("scripts/call.ts").contains("verifier.check(proof, finalPair)"));
+    }
+
+    #[test]
+    fn test_render_deferred_pairing_contract() {
+        let circuit = lookup_test::test_circuit::test_circuit_builder();
+        let target_circuit_params = Params::<G1Affine>::unsafe_setup::<Bn256>(6);
+        let target = SolidityGenerate {
+            target_circuit_vk: keygen_vk(&target_circuit_params, &circuit).unwrap(),
+            target_circuit_params,
+            nproofs: 1,
+        };
+        let sol = render_deferred_pairing_contract::<_, Bn256>(
+            None,
+            &target,
+            &[0xab; 32],
+            DEFAULT_SOLIDITY_PRAGMA,
+        );
+        assert!(sol.contains("contract DeferredPairingVerifier"));
+        assert!(sol.contains(&format!("VK_HASH = 0x{};", "ab".repeat(32))));
+        assert!(sol.contains("input[5] = "));
+        assert!(sol.contains("input[11] = "));
+        assert!(!sol.contains("{{"));
+    }
+
+    #[test]
+    fn test_render_cosmwasm_project() {
+        let circuit = lookup_test::test_circuit::test_circuit_builder();
+        let target_circuit_params = Params::<G1Affine>::unsafe_setup::<Bn256>(6);
+        let target = SolidityGenerate {
+            target_circuit_vk: keygen_vk(&target_circuit_params, &circuit).unwrap(),
+            target_circuit_params,
+            nproofs: 1,
+        };
+        let mut final_pair = vec![0u8; 160];
+        final_pair[0] = 1;
+        let files = render_cosmwasm_project::<_, Bn256>(None, &target, &[0xab; 32], &final_pair);
+
+        let file = |path: &str| &files.iter().find(|(p, _)| p == path).unwrap().1;
+        assert!(file("Cargo.toml").contains("name = \"aggregation-verifier\""));
+        let contract = file("src/lib.rs");
+        assert!(contract.contains(&format!("VK_HASH: &str = \"{}\";", "ab".repeat(32))));
+        assert!(contract.contains("const S_G2: [&str; 4] = [\""));
+        assert!(!contract.contains("{{"));
+        assert!(file("src/msg.rs").contains("pub enum ExecuteMsg"));
+        assert!(file("src/bin/schema.rs").contains("write_api!"));
+        // the instances after the 4 words of the final pair are left out
+        let verify_msg = file("fixtures/verify.json");
+        assert_eq!(
+            verify_msg,
+            "{\"verify\": {\"final_pair\": [\"1\", \"0\", \"0\", \"0\"]}}\n"
+        );
+    }
+
+    #[test]
+    fn test_render_near_project() {
+        let circuit = lookup_test::test_circuit::test_circuit_builder();
+        let target_circuit_params = Params::<G1Affine>::unsafe_setup::<Bn256>(6);
+        let target = SolidityGenerate {
+            target_circuit_vk: keygen_vk(&target_circuit_params, &circuit).unwrap(),
+            target_circuit_params,
+            nproofs: 1,
+        };
+        let files = render_near_project::<_, Bn256>(None, &target, &[0xab; 32], &[0u8; 128]);
+
+        let file = |path: &str| &files.iter().find(|(p, _)| p == path).unwrap().1;
+        assert!(file("Cargo.toml").contains("near-sdk"));
+        let contract = file("src/lib.rs");
+        assert!(contract.contains(&format!("VK_HASH: &str = \"{}\";", "ab".repeat(32))));
+        assert!(contract.contains("env::alt_bn128_pairing_check"));
+        assert!(!contract.contains("{{"));
+        assert!(file("fixtures/verify.json").starts_with("{\"final_pair\": [\"0\", "));
+    }
+
+    #[test]
+    fn test_render_solana_project() {
+        let circuit = lookup_test::test_circuit::test_circuit_builder();
+        let target_circuit_params = Params::<G1Affine>::unsafe_setup::<Bn256>(6);
+        let target = SolidityGenerate {
+            target_circuit_vk: keygen_vk(&target_circuit_params, &circuit).unwrap(),
+            target_circuit_params,
+            nproofs: 1,
+        };
+        let mut final_pair = vec![0u8; 160];
+        final_pair[128] = 7;
+        let files = render_solana_project::<_, Bn256>(None, &target, &[0xab; 32], &final_pair);
+
+        let file = |path: &str| &files.iter().find(|(p, _)| p == path).unwrap().1;
+        assert!(file("Cargo.toml").contains("solana-program"));
+        let program = file("src/lib.rs");
+        assert!(program.contains("VK_HASH: [u8; 32] = [0xab, 0xab,"));
+        assert!(program.contains("alt_bn128_pairing(&input)"));
+        assert!(!program.contains("{{"));
+        // vk hash, then the final pair array in big endian words
+        let data = file("fixtures/instruction_data.hex");
+        assert_eq!(data.len(), 2 * (32 + 160) + 1);
+        assert!(data.starts_with(&"ab".repeat(32)));
+        assert!(data.trim_end().ends_with(&format!("{}07", "0".repeat(62))));
+    }
+
+    #[test]
+    fn test_render_vk_contract_options() {
+        let options = SolidityOptions {
+            license: "MIT".to_owned(),
+            pragma: "0.8.19".to_owned(),
+            optimizer_hints: vec!["solc --optimize --optimize-runs 200".to_owned()],
+            ..SolidityOptions::default()
+        };
+        let sol = render_vk_contract(None, &[BigUint::from(1u64)], &options);
+        assert!(sol.starts_with("// SPDX-License-Identifier: MIT\npragma solidity 0.8.19;\n"));
+        assert!(sol.contains("// solc --optimize --optimize-runs 200\n"));
+        assert!(sol.contains(&format!("hex\"00{:0>64}\"", 1)));
+    }
 
-        let template = render_verifier_sol_template::<C>(sol_ctx, template_folder.clone());
-        info!("generate solidity succeeds");
+    #[test]
+    fn test_encode_calldata() {
+        let mut proof = vec![0u8; 64];
+        proof[0] = 1;
+        let final_pair = vec![0u8; 32 * 4];
+        let calldata = encode_calldata(&proof, &final_pair);
 
-        template
+        assert_eq!(calldata.len(), 4 + 32 * (2 + 1 + 2 + 1 + 4));
+        let word = |i: usize| &calldata[4 + 32 * i..4 + 32 * (i + 1)];
+        assert_eq!(word(0)[31], 0x40);
+        assert_eq!(word(1)[31], 0xa0);
+        // little endian words of the proof become big endian abi words
+        assert_eq!(word(2)[31], 2);
+        assert_eq!(word(3)[31], 1);
+        assert_eq!(word(5)[31], 4);
     }
 }
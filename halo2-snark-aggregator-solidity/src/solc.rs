@@ -0,0 +1,170 @@
+use std::fmt;
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// EIP-170 limit on the size of deployed bytecode.
+pub const CODE_SIZE_LIMIT: usize = 24576;
+// EIP-3860 limit on the size of the creation bytecode.
+pub const INITCODE_SIZE_LIMIT: usize = 2 * CODE_SIZE_LIMIT;
+
+#[derive(Debug)]
+pub enum SolcError {
+    // solc could not be run, it must be on the PATH
+    Spawn(std::io::Error),
+    // solc rejected the sources, with its error output
+    Compile(String),
+    CodeSize { contract: String, size: usize },
+    InitcodeSize { contract: String, size: usize },
+}
+
+impl fmt::Display for SolcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolcError::Spawn(e) => write!(f, "failed to run solc: {}", e),
+            SolcError::Compile(stderr) => write!(f, "solc failed: {}", stderr),
+            SolcError::CodeSize { contract, size } => write!(
+                f,
+                "{} has {} bytes of code, over the {} bytes limit",
+                contract, size, CODE_SIZE_LIMIT
+            ),
+            SolcError::InitcodeSize { contract, size } => write!(
+                f,
+                "{} has {} bytes of creation code, over the {} bytes limit",
+                contract, size, INITCODE_SIZE_LIMIT
+            ),
+        }
+    }
+}
+
+// Bytecode sizes of a compiled contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompiledContract {
+    // source file and contract name, as `verifier.sol:Verifier`
+    pub name: String,
+    pub initcode_size: usize,
+    pub code_size: usize,
+}
+
+// Splits the `--bin --bin-runtime` output of solc by contract, interfaces and
+// abstract contracts have empty binaries.
+fn parse_output(stdout: &str) -> Vec<CompiledContract> {
+    let mut contracts: Vec<CompiledContract> = vec![];
+    let mut lines = stdout.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if let Some(name) = line
+            .strip_prefix("======= ")
+            .and_then(|l| l.strip_suffix(" ======="))
+        {
+            contracts.push(CompiledContract {
+                name: name.to_owned(),
+                initcode_size: 0,
+                code_size: 0,
+            });
+            continue;
+        }
+        let size = |lines: &mut std::str::Lines| lines.next().unwrap_or("").trim().len() / 2;
+        match (line, contracts.last_mut()) {
+            ("Binary:", Some(contract)) => contract.initcode_size = size(&mut lines),
+            ("Binary of the runtime part:", Some(contract)) => {
+                contract.code_size = size(&mut lines)
+            }
+            _ => {}
+        }
+    }
+    contracts
+}
+
+// Compiles the sources, as (file name, content), with `solc --optimize`. They are
+// written to a temporary directory so that they can import each other by file name.
+pub fn compile(sources: &[(&str, &str)]) -> Result<Vec<CompiledContract>, SolcError> {
+    static RUN: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "solc_{}_{}",
+        std::process::id(),
+        RUN.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    for (name, content) in sources {
+        std::fs::write(dir.join(name), content).unwrap();
+    }
+
+    let output = Command::new("solc")
+        .current_dir(&dir)
+        .arg("--optimize")
+        .arg("--bin")
+        .arg("--bin-runtime")
+        .args(sources.iter().map(|(name, _)| name))
+        .output();
+    std::fs::remove_dir_all(&dir).unwrap();
+    let output = output.map_err(SolcError::Spawn)?;
+    if !output.status.success() {
+        return Err(SolcError::Compile(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ));
+    }
+
+    Ok(parse_output(&String::from_utf8_lossy(&output.stdout)))
+}
+
+// Compiles the sources and checks every contract fits under the EIP-170 and
+// EIP-3860 limits, so an oversized contract fails at generation, not at deployment.
+pub fn check_contracts(sources: &[(&str, &str)]) -> Result<Vec<CompiledContract>, SolcError> {
+    let contracts = compile(sources)?;
+    for contract in contracts.iter() {
+        if contract.code_size > CODE_SIZE_LIMIT {
+            return Err(SolcError::CodeSize {
+                contract: contract.name.clone(),
+                size: contract.code_size,
+            });
+        }
+        if contract.initcode_size > INITCODE_SIZE_LIMIT {
+            return Err(SolcError::InitcodeSize {
+                contract: contract.name.clone(),
+                size: contract.initcode_size,
+            });
+        }
+    }
+    Ok(contracts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{render_pairing_library, DEFAULT_SOLIDITY_PRAGMA};
+
+    #[test]
+    fn test_parse_output() {
+        let stdout = "\n======= verifier.sol:IVerifier =======\nBinary:\n\n\
+                      Binary of the runtime part:\n\n\
+                      \n======= verifier.sol:Verifier =======\nBinary:\n60806040\n\
+                      Binary of the runtime part:\n6080\n";
+        let contracts = parse_output(stdout);
+        assert_eq!(contracts.len(), 2);
+        assert_eq!(contracts[0].code_size, 0);
+        assert_eq!(
+            contracts[1],
+            CompiledContract {
+                name: "verifier.sol:Verifier".to_owned(),
+                initcode_size: 4,
+                code_size: 2,
+            }
+        );
+    }
+
+    // needs solc on the PATH
+    #[test]
+    fn test_check_contracts() {
+        let lib = render_pairing_library(None, DEFAULT_SOLIDITY_PRAGMA);
+        let contracts = check_contracts(&[("verifier_pairing.sol", &lib)]).unwrap();
+        assert!(contracts
+            .iter()
+            .any(|c| c.name == "verifier_pairing.sol:VerifierPairing" && c.code_size > 0));
+
+        let broken = format!("{}\ncontract Broken {{", lib);
+        assert!(matches!(
+            check_contracts(&[("broken.sol", &broken)]),
+            Err(SolcError::Compile(_))
+        ));
+    }
+}
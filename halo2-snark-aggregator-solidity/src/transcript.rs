@@ -1 +1,2 @@
 pub(crate) mod codegen;
+pub(crate) mod symbolic;
@@ -6,17 +6,16 @@ use halo2_proofs::transcript::EncodedChallenge;
 use halo2_proofs::transcript::{Challenge255, Transcript, TranscriptRead};
 use halo2_proofs::{arithmetic::CurveAffine, plonk::Error};
 use halo2_snark_aggregator_api::arith::common::ArithCommonChip;
-use halo2_snark_aggregator_api::transcript::sha::ShaRead;
 use halo2_snark_aggregator_api::{
     arith::ecc::ArithEccChip,
     hash::poseidon::PoseidonChip,
     transcript::{encode::Encode, read::TranscriptRead as APITranscriptRead},
 };
 use pairing_bn256::group::Curve;
-use std::{io, marker::PhantomData};
+use std::marker::PhantomData;
 
 pub struct CodegenTranscriptRead<
-    R: io::Read,
+    R: TranscriptRead<C, Challenge255<C>>,
     C: CurveAffine,
     A: ArithEccChip<
         Point = C,
@@ -31,12 +30,12 @@ pub struct CodegenTranscriptRead<
     const RATE: usize,
 > {
     hash: PoseidonChip<A::NativeChip, T, RATE>,
-    reader: ShaRead<R, C, Challenge255<C>, sha3::Keccak256>,
+    reader: R,
     _phantom: PhantomData<E>,
 }
 
 impl<
-        R: io::Read,
+        R: TranscriptRead<C, Challenge255<C>>,
         C: CurveAffine,
         A: ArithEccChip<
             Point = C,
@@ -60,11 +59,15 @@ impl<
     ) -> Result<CodegenTranscriptRead<R, C, A, E, T, RATE>, A::Error> {
         Ok(CodegenTranscriptRead {
             hash: PoseidonChip::new(ctx, schip, r_f, r_p)?,
-            reader: ShaRead::init(reader),
+            reader,
             _phantom: PhantomData,
         })
     }
 
+    pub fn finalize(self) -> R {
+        self.reader
+    }
+
     fn _common_point(
         &mut self,
         ctx: &mut A::Context,
@@ -97,7 +100,7 @@ impl<
 }
 
 impl<
-        R: io::Read,
+        R: TranscriptRead<C, Challenge255<C>>,
         C: CurveAffine,
         A: ArithEccChip<
             Point = C,
@@ -116,7 +119,7 @@ impl<
 }
 
 impl<
-        R: io::Read,
+        R: TranscriptRead<C, Challenge255<C>>,
         C: CurveAffine,
         A: ArithEccChip<
             Point = C,
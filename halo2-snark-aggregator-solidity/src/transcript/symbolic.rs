@@ -0,0 +1,107 @@
+use crate::TranscriptHash;
+use halo2_proofs::arithmetic::{CurveAffine, Field, FieldExt};
+use halo2_proofs::transcript::{Challenge255, Transcript, TranscriptRead, TranscriptWrite};
+use halo2_snark_aggregator_api::transcript::sha::ShaWrite;
+use pairing_bn256::group::{prime::PrimeCurveAffine, Curve};
+use std::io;
+
+// Transcript reader for codegen without a proof: it makes up the points and
+// scalars read (multiples of the generator, so none is the identity) and writes
+// them to a sha transcript, so the challenges are squeezed as for a real proof
+// and the bytes written are a proof of the right shape.
+pub(crate) struct SymbolicTranscriptRead<C: CurveAffine> {
+    writer: SymbolicWriter<C>,
+    seed: C::Scalar,
+}
+
+enum SymbolicWriter<C: CurveAffine> {
+    Sha256(ShaWrite<Vec<u8>, C, Challenge255<C>, sha2::Sha256>),
+    Keccak256(ShaWrite<Vec<u8>, C, Challenge255<C>, sha3::Keccak256>),
+}
+
+macro_rules! dispatch {
+    ($writer:expr, $w:ident => $e:expr) => {
+        match $writer {
+            SymbolicWriter::Sha256($w) => $e,
+            SymbolicWriter::Keccak256($w) => $e,
+        }
+    };
+}
+
+impl<C: CurveAffine> SymbolicTranscriptRead<C> {
+    pub(crate) fn new(hash: TranscriptHash) -> Self {
+        SymbolicTranscriptRead {
+            writer: match hash {
+                TranscriptHash::Sha256 => SymbolicWriter::Sha256(ShaWrite::init(vec![])),
+                TranscriptHash::Keccak256 => SymbolicWriter::Keccak256(ShaWrite::init(vec![])),
+            },
+            seed: C::Scalar::from_u128(0x5eed),
+        }
+    }
+
+    fn next_scalar(&mut self) -> C::Scalar {
+        self.seed = self.seed.square() + C::Scalar::from_u128(7);
+        self.seed
+    }
+
+    // The made up proof, used for its length and calldata gas.
+    pub(crate) fn finalize(self) -> Vec<u8> {
+        dispatch!(self.writer, w => w.finalize())
+    }
+}
+
+impl<C: CurveAffine> Transcript<C, Challenge255<C>> for SymbolicTranscriptRead<C> {
+    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+        dispatch!(&mut self.writer, w => w.squeeze_challenge())
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        dispatch!(&mut self.writer, w => w.common_point(point))
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        dispatch!(&mut self.writer, w => w.common_scalar(scalar))
+    }
+}
+
+impl<C: CurveAffine> TranscriptRead<C, Challenge255<C>> for SymbolicTranscriptRead<C> {
+    fn read_point(&mut self) -> io::Result<C> {
+        let point = (C::generator() * self.next_scalar()).to_affine();
+        dispatch!(&mut self.writer, w => w.write_point(point))?;
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let scalar = self.next_scalar();
+        dispatch!(&mut self.writer, w => w.write_scalar(scalar))?;
+        Ok(scalar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::transcript::EncodedChallenge;
+    use halo2_snark_aggregator_api::transcript::sha::ShaRead;
+    use pairing_bn256::bn256::G1Affine;
+
+    #[test]
+    fn test_symbolic_transcript_read() {
+        let mut symbolic = SymbolicTranscriptRead::<G1Affine>::new(TranscriptHash::Sha256);
+        let point = symbolic.read_point().unwrap();
+        let scalar = symbolic.read_scalar().unwrap();
+        let challenge = symbolic.squeeze_challenge();
+        let proof = symbolic.finalize();
+        assert_eq!(proof.len(), 64 + 32);
+
+        // the made up proof reads back to the same values and challenges
+        let mut reader =
+            ShaRead::<_, G1Affine, Challenge255<G1Affine>, sha2::Sha256>::init(&proof[..]);
+        assert_eq!(reader.read_point().unwrap(), point);
+        assert_eq!(reader.read_scalar().unwrap(), scalar);
+        assert_eq!(
+            reader.squeeze_challenge().get_scalar(),
+            challenge.get_scalar()
+        );
+    }
+}
@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: GPL-3.0
+pragma solidity ^0.8.0;
+
+import "forge-std/Test.sol";
+import "../verifier.sol";
+
+// Fixtures are the little endian proof and final pair of the run the verifier was generated from.
+// The tests call the reverting view check {{ check_fn }}, whatever the convention of verify.
+contract VerifierTest is Test {
+    bytes constant PROOF = hex"{{ proof }}";
+    bytes constant FINAL_PAIR = hex"{{ final_pair }}";
+
+    {{ contract_name }} verifier;
+
+    function setUp() public {
+        {%- if steps > 0 %}
+        // the steps of the split verifier are deployed on their own
+        address[{{ steps }}] memory steps;
+        {%- for i in range(end=steps) %}
+        steps[{{ i }}] = address(new {{ contract_name }}Step{{ i }}());
+        {%- endfor %}
+        verifier = new {{ contract_name }}(steps);
+        {%- else %}
+        verifier = new {{ contract_name }}();
+        {%- endif %}
+    }
+
+    function to_words(bytes memory buf) internal pure returns (uint256[] memory words) {
+        words = new uint256[](buf.length / 32);
+        for (uint256 i = 0; i < words.length; i++) {
+            uint256 v = 0;
+            for (uint256 j = 32; j > 0; j--) {
+                v = (v << 8) | uint8(buf[i * 32 + j - 1]);
+            }
+            words[i] = v;
+        }
+    }
+
+    function test_valid_proof() public view {
+        verifier.{{ check_fn }}(to_words(PROOF), to_words(FINAL_PAIR));
+    }
+
+    function test_iverifier() public {
+        IVerifier v = IVerifier(address(verifier));
+        bytes memory proof = abi.encodePacked(to_words(PROOF));
+        assertTrue(v.verify(proof, to_words(FINAL_PAIR)));
+        proof[proof.length - 1] ^= 0x01;
+        vm.expectRevert();
+        v.verify(proof, to_words(FINAL_PAIR));
+        assertTrue(verifier.supportsInterface(type(IVerifier).interfaceId));
+    }
+
+    function test_mutated_proof() public {
+        uint256[] memory proof = to_words(PROOF);
+        proof[proof.length - 1] ^= 1;
+        vm.expectRevert();
+        verifier.{{ check_fn }}(proof, to_words(FINAL_PAIR));
+    }
+
+    function test_wrong_instance() public {
+        uint256[] memory final_pair = to_words(FINAL_PAIR);
+        final_pair[final_pair.length - 1] ^= 1;
+        vm.expectRevert();
+        verifier.{{ check_fn }}(to_words(PROOF), final_pair);
+    }
+
+    // Corrupted proofs and final pairs of the verify_malformed command, all must revert.
+    // Skipped when verify_circuit_malformed.json has not been written.
+    function test_malformed_fixtures() public {
+        string memory json;
+        try vm.readFile("verify_circuit_malformed.json") returns (string memory content) {
+            json = content;
+        } catch {
+            return;
+        }
+        uint256 count = vm.parseJsonUint(json, ".count");
+        for (uint256 i = 0; i < count; i++) {
+            string memory key = string.concat(".fixtures[", vm.toString(i), "]");
+            bytes memory proof = vm.parseJsonBytes(json, string.concat(key, ".proof"));
+            bytes memory final_pair = vm.parseJsonBytes(json, string.concat(key, ".final_pair"));
+            (bool ok, ) = address(verifier).call(
+                // by signature, verify is overloaded by IVerifier
+                abi.encodeWithSignature(
+                    "{{ check_fn }}(uint256[],uint256[])",
+                    to_words(proof),
+                    to_words(final_pair)
+                )
+            );
+            assertFalse(ok, vm.parseJsonString(json, string.concat(key, ".label")));
+        }
+    }
+}
@@ -0,0 +1,99 @@
+// Settles the final pair of an aggregation proof verified off-chain, as the
+// DeferredPairingVerifier contract does. The final pair (w_x, w_g) accumulates the
+// target circuit proofs, it is valid if e(w_x, s_g2) e(w_g, -g2) == 1 on the g2 points
+// of the target circuit setup. The pairing runs in wasm with pairing_bn256.
+//
+// Only the final pair is checked: the aggregation proof isn't verified, so nothing binds
+// the final pair to the verify circuit or its instances. VK_HASH names the verify circuit
+// the contract was rendered for and is informational, the caller has to know the final
+// pair came from a proof of it.
+pub mod msg;
+
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use cosmwasm_std::{
+    entry_point, to_json_binary, Binary, Deps, DepsMut, Env, HexBinary, MessageInfo, Response,
+    StdError, StdResult, Uint256,
+};
+use cw_storage_plus::Map;
+use pairing_bn256::arithmetic::{CurveAffine, MillerLoopResult, MultiMillerLoop};
+use pairing_bn256::bn256::{Bn256, Fq, Fq2, G1Affine, G2Affine, G2Prepared, Gt};
+use pairing_bn256::group::Group;
+use std::str::FromStr;
+
+// hash of the verify circuit vk the contract was rendered for, not checked against anything
+const VK_HASH: &str = "{{ vk_hash }}";
+// (x.c0, x.c1, y.c0, y.c1) of s_g2 and -g2 of the target circuit setup
+const S_G2: [&str; 4] = [{% for word in s_g2 %}"{{ word }}", {% endfor %}];
+const N_G2: [&str; 4] = [{% for word in n_g2 %}"{{ word }}", {% endfor %}];
+
+// final pairs recorded by ExecuteMsg::Verify, by their little endian words
+const VERIFIED: Map<&[u8], bool> = Map::new("verified");
+
+fn fq(word: &Uint256) -> Option<Fq> {
+    Option::from(Fq::from_bytes(&word.to_le_bytes()))
+}
+
+fn g1(x: &Uint256, y: &Uint256) -> StdResult<G1Affine> {
+    fq(x)
+        .zip(fq(y))
+        .and_then(|(x, y)| Option::from(G1Affine::from_xy(x, y)))
+        .ok_or_else(|| StdError::generic_err("InvalidFinalPair"))
+}
+
+fn g2(words: &[&str; 4]) -> G2Prepared {
+    let fq = |i: usize| fq(&Uint256::from_str(words[i]).unwrap()).unwrap();
+    G2Prepared::from(G2Affine {
+        x: Fq2 { c0: fq(0), c1: fq(1) },
+        y: Fq2 { c0: fq(2), c1: fq(3) },
+    })
+}
+
+fn check_final_pair(final_pair: &[Uint256; 4]) -> StdResult<bool> {
+    let w_x = g1(&final_pair[0], &final_pair[1])?;
+    let w_g = g1(&final_pair[2], &final_pair[3])?;
+    let (s_g2, n_g2) = (g2(&S_G2), g2(&N_G2));
+    let result = Bn256::multi_miller_loop(&[(&w_x, &s_g2), (&w_g, &n_g2)]);
+    Ok(result.final_exponentiation() == Gt::identity())
+}
+
+fn final_pair_key(final_pair: &[Uint256; 4]) -> Vec<u8> {
+    final_pair.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+#[entry_point]
+pub fn instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: InstantiateMsg,
+) -> StdResult<Response> {
+    Ok(Response::new().add_attribute("vk_hash", VK_HASH))
+}
+
+#[entry_point]
+pub fn execute(deps: DepsMut, _env: Env, _info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::Verify { final_pair } => {
+            if !check_final_pair(&final_pair)? {
+                return Err(StdError::generic_err("PairingFailed"));
+            }
+            let key = final_pair_key(&final_pair);
+            VERIFIED.save(deps.storage, &key, &true)?;
+            Ok(Response::new()
+                .add_attribute("action", "verify")
+                .add_attribute("final_pair", HexBinary::from(key).to_hex()))
+        }
+    }
+}
+
+#[entry_point]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Verify { final_pair } => to_json_binary(&check_final_pair(&final_pair)?),
+        QueryMsg::IsVerified { final_pair } => {
+            let key = final_pair_key(&final_pair);
+            to_json_binary(&VERIFIED.has(deps.storage, &key))
+        }
+        QueryMsg::VkHash {} => to_json_binary(&HexBinary::from_hex(VK_HASH)?),
+    }
+}
@@ -0,0 +1,27 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{HexBinary, Uint256};
+
+#[cw_serde]
+pub struct InstantiateMsg {}
+
+// final_pair is (w_x.x, w_x.y, w_g.x, w_g.y), the first 4 words of
+// verify_circuit_final_pair.data.
+#[cw_serde]
+pub enum ExecuteMsg {
+    // records the final pair as verified, fails if its pairing check fails
+    Verify { final_pair: [Uint256; 4] },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    // pairing check of the final pair, without recording it
+    #[returns(bool)]
+    Verify { final_pair: [Uint256; 4] },
+    // whether Verify was executed on the final pair
+    #[returns(bool)]
+    IsVerified { final_pair: [Uint256; 4] },
+    // the verify circuit vk hash the contract was rendered for, informational
+    #[returns(HexBinary)]
+    VkHash {},
+}
@@ -0,0 +1,108 @@
+import { Interface, concat, getBytes, toBeHex, toBigInt } from "ethers";
+
+// Calldata encoder (ethers v6) of the verifier generated with this module: it
+// encodes the proof and final pair written by the sdk into the calldata of the
+// verifier entry points.
+
+// words of the proof and of the final pair, the final pair starts with two points
+export const PROOF_LEN = {{ proof_len }};
+export const INSTANCE_SIZE = {{ instance_size }};
+// word offsets of the x coordinates of the proof points
+export const PROOF_POINTS: readonly number[] = [{{ proof_points | join(sep=", ") }}];
+const FINAL_PAIR_POINTS: readonly number[] = [0, 2];
+
+const IDENTITY_FLAG = 1n << 254n;
+const Y_ODD_FLAG = 1n << 255n;
+
+export const verifierInterface = new Interface([
+  "function verify(uint256[] proof, uint256[] target_circuit_final_pair)",
+  "function verify(bytes proof, uint256[] instances) view returns (bool)",
+  "function verify_compressed(bytes blob)",
+]);
+
+// Words of verify_circuit_proof.data or verify_circuit_final_pair.data, 32 bytes
+// little endian each.
+export function wordsFromFile(data: Uint8Array): bigint[] {
+  if (data.length % 32 != 0) {
+    throw new Error(`length ${data.length} is not a multiple of 32`);
+  }
+  const words: bigint[] = [];
+  for (let i = 0; i < data.length; i += 32) {
+    words.push(toBigInt(Uint8Array.from(data.subarray(i, i + 32)).reverse()));
+  }
+  return words;
+}
+
+// Words of a json array of decimal or 0x prefixed strings, as the hardhat fixtures.
+export function wordsFromJson(json: string): bigint[] {
+  const words: unknown = JSON.parse(json);
+  if (!Array.isArray(words)) {
+    throw new Error("expected a json array of words");
+  }
+  return words.map((w) => BigInt(w as string | number));
+}
+
+function checkLengths(proof: bigint[], finalPair: bigint[]) {
+  if (proof.length != PROOF_LEN) {
+    throw new Error(`proof has ${proof.length} words, expected ${PROOF_LEN}`);
+  }
+  if (finalPair.length != INSTANCE_SIZE) {
+    throw new Error(`final pair has ${finalPair.length} words, expected ${INSTANCE_SIZE}`);
+  }
+}
+
+function packWords(words: bigint[]): string {
+  return concat(words.map((w) => toBeHex(w, 32)));
+}
+
+// verify(uint256[] proof, uint256[] target_circuit_final_pair)
+export function encodeVerify(proof: bigint[], finalPair: bigint[]): string {
+  checkLengths(proof, finalPair);
+  return verifierInterface.encodeFunctionData("verify(uint256[],uint256[])", [proof, finalPair]);
+}
+
+// IVerifier.verify(bytes proof, uint256[] instances), the proof packed as 32 bytes
+// big endian words.
+export function encodeIVerifier(proof: bigint[], finalPair: bigint[]): string {
+  checkLengths(proof, finalPair);
+  return verifierInterface.encodeFunctionData("verify(bytes,uint256[])", [
+    packWords(proof),
+    finalPair,
+  ]);
+}
+
+function compressWords(words: bigint[], points: readonly number[]): bigint[] {
+  const compressed: bigint[] = [];
+  let i = 0;
+  while (i < words.length) {
+    if (points.includes(i)) {
+      const [x, y] = [words[i], words[i + 1]];
+      compressed.push(x == 0n && y == 0n ? IDENTITY_FLAG : x + ((y & 1n) == 1n ? Y_ODD_FLAG : 0n));
+      i += 2;
+    } else {
+      compressed.push(words[i]);
+      i += 1;
+    }
+  }
+  return compressed;
+}
+
+// Blob of verify_compressed(bytes blob), only in a verifier generated with
+// compression: the points are packed to their x coordinate with the parity of y
+// in the top bit, see CalldataLayout::compress.
+export function compressCalldata(proof: bigint[], finalPair: bigint[]): Uint8Array {
+  checkLengths(proof, finalPair);
+  return getBytes(
+    packWords([
+      ...compressWords(proof, PROOF_POINTS),
+      ...compressWords(finalPair, FINAL_PAIR_POINTS),
+    ])
+  );
+}
+
+// verify_compressed(bytes blob)
+export function encodeVerifyCompressed(proof: bigint[], finalPair: bigint[]): string {
+  return verifierInterface.encodeFunctionData("verify_compressed", [
+    compressCalldata(proof, finalPair),
+  ]);
+}
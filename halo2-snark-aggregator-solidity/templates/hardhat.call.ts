@@ -0,0 +1,42 @@
+import { ethers } from "hardhat";
+import fs from "fs";
+
+// Proof words of a proof envelope (see halo2_snark_aggregator_circuit::envelope): magic,
+// version u16, curve u8, transcript u8, vk hash, instance columns u32, rows u32 per column,
+// proof length u32 and proof, little endian. The proof is made of 32 bytes little endian words.
+function openEnvelope(hex: string): string[] {
+  const bytes = ethers.utils.arrayify(hex.trim());
+  const view = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+  if (ethers.utils.toUtf8String(bytes.slice(0, 4)) !== "H2AG") {
+    throw new Error("not a proof envelope");
+  }
+  if (view.getUint16(4, true) !== {{ envelope_version }}) {
+    throw new Error(`unsupported proof envelope version ${view.getUint16(4, true)}`);
+  }
+  const columns = view.getUint32(40, true);
+  const offset = 44 + 4 * columns;
+  const proof = bytes.slice(offset + 4, offset + 4 + view.getUint32(offset, true));
+  const words: string[] = [];
+  for (let i = 0; i < proof.length; i += 32) {
+    words.push(ethers.BigNumber.from(proof.slice(i, i + 32).reverse()).toString());
+  }
+  return words;
+}
+
+// Calls {{ check_fn }} with the proof and final pair of the run the verifier was generated from.
+async function main() {
+  const abi = JSON.parse(fs.readFileSync("abi/{{ contract_name }}.json", "utf8"));
+  const { address } = JSON.parse(fs.readFileSync("deployment.json", "utf8"));
+  const proof = openEnvelope(fs.readFileSync("fixtures/proof.envelope", "utf8"));
+  const finalPair: string[] = JSON.parse(fs.readFileSync("fixtures/final_pair.json", "utf8"));
+
+  const verifier = new ethers.Contract(address, abi, ethers.provider);
+  const gas = await verifier.estimateGas.{{ check_fn }}(proof, finalPair);
+  await verifier.{{ check_fn }}(proof, finalPair);
+  console.log("proof verified, gas", gas.toString());
+}
+
+main().catch((error) => {
+  console.error(error);
+  process.exitCode = 1;
+});
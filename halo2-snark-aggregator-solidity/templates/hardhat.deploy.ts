@@ -0,0 +1,25 @@
+import { ethers } from "hardhat";
+import fs from "fs";
+
+async function main() {
+  // the steps of a split verifier are deployed on their own, in order
+  const steps: string[] = [];
+  for (let i = 0; i < {{ steps }}; i++) {
+    const Step = await ethers.getContractFactory(`{{ contract_name }}Step${i}`);
+    const step = await Step.deploy();
+    await step.deployed();
+    steps.push(step.address);
+  }
+
+  const Verifier = await ethers.getContractFactory("{{ contract_name }}");
+  const verifier = steps.length > 0 ? await Verifier.deploy(steps) : await Verifier.deploy();
+  await verifier.deployed();
+
+  console.log("{{ contract_name }} deployed to", verifier.address);
+  fs.writeFileSync("deployment.json", JSON.stringify({ address: verifier.address }, null, 2));
+}
+
+main().catch((error) => {
+  console.error(error);
+  process.exitCode = 1;
+});
@@ -0,0 +1,95 @@
+// Settles the final pair of an aggregation proof verified off-chain, as the
+// DeferredPairingVerifier contract does. The final pair (w_x, w_g) accumulates the
+// target circuit proofs, it is valid if e(w_x, s_g2) e(w_g, -g2) == 1 on the g2 points
+// of the target circuit setup. The pairing is the alt_bn128 host function of NEAR.
+//
+// Only the final pair is checked: the aggregation proof isn't verified, so nothing binds
+// the final pair to the verify circuit or its instances. VK_HASH names the verify circuit
+// the contract was rendered for and is informational, the caller has to know the final
+// pair came from a proof of it.
+use near_sdk::store::LookupSet;
+use near_sdk::{env, near, require, NearToken, Promise};
+
+// hash of the verify circuit vk the contract was rendered for, not checked against anything
+const VK_HASH: &str = "{{ vk_hash }}";
+// (x.c0, x.c1, y.c0, y.c1) of s_g2 and -g2 of the target circuit setup
+const S_G2: [&str; 4] = [{% for word in s_g2 %}"{{ word }}", {% endfor %}];
+const N_G2: [&str; 4] = [{% for word in n_g2 %}"{{ word }}", {% endfor %}];
+
+// 32 bytes little endian of a decimal word, as the host functions take them
+fn word(decimal: &str) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for c in decimal.bytes() {
+        require!(c.is_ascii_digit(), "InvalidFinalPair");
+        let mut carry = (c - b'0') as u16;
+        for byte in bytes.iter_mut() {
+            let v = *byte as u16 * 10 + carry;
+            *byte = v as u8;
+            carry = v >> 8;
+        }
+        require!(carry == 0, "InvalidFinalPair");
+    }
+    bytes
+}
+
+// keys of the verified final pairs, the keccak of their words so that each costs
+// the storage of 32 bytes
+#[near(contract_state)]
+pub struct Verifier {
+    verified: LookupSet<[u8; 32]>,
+}
+
+impl Default for Verifier {
+    fn default() -> Self {
+        Self {
+            verified: LookupSet::new(b"v"),
+        }
+    }
+}
+
+fn final_pair_key(final_pair: &[String; 4]) -> [u8; 32] {
+    let words = final_pair.iter().flat_map(|w| word(w)).collect::<Vec<_>>();
+    env::keccak256_array(&words)
+}
+
+#[near]
+impl Verifier {
+    // final_pair is (w_x.x, w_x.y, w_g.x, w_g.y) in decimal, the first 4 words of
+    // verify_circuit_final_pair.data.
+    pub fn check(&self, final_pair: [String; 4]) -> bool {
+        let mut input = vec![];
+        for (g1, g2) in [(&final_pair[..2], &S_G2), (&final_pair[2..], &N_G2)] {
+            g1.iter().for_each(|w| input.extend(word(w)));
+            g2.iter().for_each(|w| input.extend(word(w)));
+        }
+        env::alt_bn128_pairing_check(&input)
+    }
+
+    // Records the final pair as verified, panics if its pairing check fails. The
+    // caller attaches the deposit for the storage of its key, the rest is refunded.
+    #[payable]
+    pub fn verify(&mut self, final_pair: [String; 4]) {
+        require!(self.check(final_pair.clone()), "PairingFailed");
+
+        let usage = env::storage_usage();
+        self.verified.insert(final_pair_key(&final_pair));
+        self.verified.flush();
+        let cost = env::storage_byte_cost()
+            .saturating_mul((env::storage_usage().saturating_sub(usage)) as u128);
+        let deposit = env::attached_deposit();
+        require!(deposit >= cost, "InsufficientDeposit");
+
+        let refund = deposit.saturating_sub(cost);
+        if refund > NearToken::from_yoctonear(0) {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+    }
+
+    pub fn is_verified(&self, final_pair: [String; 4]) -> bool {
+        self.verified.contains(&final_pair_key(&final_pair))
+    }
+
+    pub fn vk_hash(&self) -> String {
+        VK_HASH.to_owned()
+    }
+}
@@ -0,0 +1,79 @@
+// Settles the final pair of an aggregation proof verified off-chain, as the
+// DeferredPairingVerifier contract does, with the alt_bn128 syscalls of Solana. The
+// final pair (w_x, w_g) accumulates the target circuit proofs, it is valid if
+// e(w_x, s_g2) e(w_g, -g2) == 1 on the g2 points of the target circuit setup.
+//
+// The instruction data is the vk hash, then the final pair array of verify(): w_x,
+// w_g and the target circuit instances, 32 bytes big endian words as
+// abi.encodePacked(final_pair). On success the program logs and returns the keccak
+// of the final pair array, the instancesHash of the ProofVerified event.
+use solana_program::account_info::AccountInfo;
+use solana_program::alt_bn128::prelude::alt_bn128_pairing;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::keccak;
+use solana_program::log::sol_log_data;
+use solana_program::program::set_return_data;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+// error codes, after the custom errors of verifier.sol
+pub const INVALID_INSTRUCTION_DATA: u32 = 1;
+pub const VK_HASH_MISMATCH: u32 = 2;
+pub const INVALID_PUBLIC_INPUT: u32 = 3;
+pub const PRECOMPILE_FAILED: u32 = 4;
+pub const PAIRING_FAILED: u32 = 5;
+
+// hash of the vk the final pairs are accumulated for
+pub const VK_HASH: [u8; 32] = [{{ vk_hash_bytes }}];
+// s_g2 and -g2 of the target circuit setup, in the input layout of the pairing syscall
+const S_G2: [u8; 128] = [{{ s_g2_bytes }}];
+const N_G2: [u8; 128] = [{{ n_g2_bytes }}];
+
+const P_MOD: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+const Q_MOD: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if data.len() < 32 + 4 * 32 || data.len() % 32 != 0 {
+        return Err(ProgramError::Custom(INVALID_INSTRUCTION_DATA));
+    }
+    let (vk_hash, final_pair) = data.split_at(32);
+    if vk_hash != VK_HASH {
+        return Err(ProgramError::Custom(VK_HASH_MISMATCH));
+    }
+    // big endian words compare as byte strings
+    for (i, word) in final_pair.chunks(32).enumerate() {
+        let modulus = if i < 4 { &P_MOD } else { &Q_MOD };
+        if word >= &modulus[..] {
+            return Err(ProgramError::Custom(INVALID_PUBLIC_INPUT));
+        }
+    }
+
+    let mut input = Vec::with_capacity(2 * 192);
+    input.extend_from_slice(&final_pair[..64]);
+    input.extend_from_slice(&S_G2);
+    input.extend_from_slice(&final_pair[64..128]);
+    input.extend_from_slice(&N_G2);
+    let result =
+        alt_bn128_pairing(&input).map_err(|_| ProgramError::Custom(PRECOMPILE_FAILED))?;
+    if result.last() != Some(&1) {
+        return Err(ProgramError::Custom(PAIRING_FAILED));
+    }
+
+    let instances_hash = keccak::hash(final_pair).to_bytes();
+    sol_log_data(&[&instances_hash]);
+    set_return_data(&instances_hash);
+    Ok(())
+}
@@ -0,0 +1,264 @@
+// SPDX-License-Identifier: GPL-3.0
+// Move version of verifier.sol for Aptos (move 2), on the bn254 structures of
+// aptos_std::crypto_algebra. verify aborts with the code of the matching custom
+// error of verifier.sol, see the E_ constants.
+module verifier::verifier {
+    use std::bcs;
+    use std::hash;
+    use std::option;
+    use std::vector;
+    use aptos_std::aptos_hash;
+    use aptos_std::bn254_algebra::{
+        FormatFrLsb, FormatG1Uncompr, FormatG2Uncompr, Fr, G1, G2, Gt,
+    };
+    use aptos_std::crypto_algebra::{
+        add, deserialize, eq, field_add, field_div, field_mul, field_sub, multi_pairing, one,
+        scalar_mul, serialize, sub, zero, Element,
+    };
+    use aptos_std::from_bcs;
+
+    const Q_MOD: u256 = 21888242871839275222246405745257275088548364400416034343698204186575808495617;
+    const P_MOD: u256 = 21888242871839275222246405745257275088696311157297823662689037894645226208583;
+
+    const E_INVALID_PUBLIC_INPUT_LENGTH: u64 = 1;
+    const E_INVALID_PUBLIC_INPUT: u64 = 2;
+    const E_DIVISION_BY_ZERO: u64 = 3;
+    const E_INVALID_POINT: u64 = 4;
+    const E_PROOF_INVALID: u64 = 5;
+
+    struct Point has copy, drop {
+        x: u256,
+        y: u256,
+    }
+
+    fun zeros(n: u64): vector<u256> {
+        let v = vector[];
+        for (i in 0..n) {
+            vector::push_back(&mut v, 0);
+        };
+        v
+    }
+
+    fun fr(a: u256): Element<Fr> {
+        let e = deserialize<Fr, FormatFrLsb>(&bcs::to_bytes(&(a % Q_MOD)));
+        option::extract(&mut e)
+    }
+
+    fun fr_word(e: &Element<Fr>): u256 {
+        from_bcs::to_u256(serialize<Fr, FormatFrLsb>(e))
+    }
+
+    fun fr_add(a: u256, b: u256): u256 {
+        fr_word(&field_add(&fr(a), &fr(b)))
+    }
+
+    fun fr_sub(a: u256, b: u256): u256 {
+        fr_word(&field_sub(&fr(a), &fr(b)))
+    }
+
+    fun fr_mul(a: u256, b: u256): u256 {
+        fr_word(&field_mul(&fr(a), &fr(b)))
+    }
+
+    fun fr_div(a: u256, b: u256): u256 {
+        let r = field_div(&fr(a), &fr(b));
+        assert!(option::is_some(&r), E_DIVISION_BY_ZERO);
+        fr_word(&option::extract(&mut r))
+    }
+
+    fun fr_pow(a: u256, power: u64): u256 {
+        let base = fr(a);
+        let r = one<Fr>();
+        let e = power;
+        while (e > 0) {
+            if (e & 1 == 1) {
+                r = field_mul(&r, &base);
+            };
+            base = field_mul(&base, &base);
+            e = e >> 1;
+        };
+        fr_word(&r)
+    }
+
+    fun fr_mul_add(a: u256, b: u256, c: u256): u256 {
+        fr_add(fr_mul(a, b), c)
+    }
+
+    fun fr_mul_add_pm(m: &vector<u256>, proof: &vector<u256>, opcode: u256, t: u256): u256 {
+        let op = opcode;
+        let r = t;
+        for (i in 0..16) {
+            let a = ((op & 255) as u64);
+            if (a == 255) break;
+            op = op >> 8;
+            let b = ((op & 255) as u64);
+            op = op >> 8;
+            r = fr_mul_add(proof[a], m[b], r);
+        };
+        r
+    }
+
+    fun fr_mul_add_mt(m: &vector<u256>, base: u256, opcode: u256, t: u256): u256 {
+        let op = opcode;
+        let r = t;
+        for (i in 0..32) {
+            let a = ((op & 255) as u64);
+            if (a == 255) break;
+            op = op >> 8;
+            r = fr_mul_add(base, r, m[a]);
+        };
+        r
+    }
+
+    // (0, 0) is the point at infinity, as for the ecc precompiles
+    fun g1(p: Point): Element<G1> {
+        if (p.x == 0 && p.y == 0) return zero<G1>();
+        let bytes = bcs::to_bytes(&p.x);
+        vector::append(&mut bytes, bcs::to_bytes(&p.y));
+        let e = deserialize<G1, FormatG1Uncompr>(&bytes);
+        assert!(option::is_some(&e), E_INVALID_POINT);
+        option::extract(&mut e)
+    }
+
+    fun g1_point(e: &Element<G1>): Point {
+        if (eq(e, &zero<G1>())) return Point { x: 0, y: 0 };
+        let x = serialize<G1, FormatG1Uncompr>(e);
+        let y = vector::trim(&mut x, 32);
+        Point { x: from_bcs::to_u256(x), y: from_bcs::to_u256(y) }
+    }
+
+    fun ecc_add(a: Point, b: Point): Point {
+        g1_point(&add(&g1(a), &g1(b)))
+    }
+
+    fun ecc_sub(a: Point, b: Point): Point {
+        g1_point(&sub(&g1(a), &g1(b)))
+    }
+
+    fun ecc_mul(p: Point, s: u256): Point {
+        g1_point(&scalar_mul(&g1(p), &fr(s)))
+    }
+
+    fun ecc_mul_add(p: Point, s: u256, q: Point): Point {
+        ecc_add(ecc_mul(p, s), q)
+    }
+
+    fun ecc_mul_add_pm(m: &vector<u256>, proof: &vector<u256>, opcode: u256, t: Point): Point {
+        let op = opcode;
+        let r = t;
+        for (i in 0..16) {
+            let a = ((op & 255) as u64);
+            if (a == 255) break;
+            op = op >> 8;
+            let b = ((op & 255) as u64);
+            op = op >> 8;
+            r = ecc_add(ecc_mul(Point { x: proof[a], y: proof[a + 1] }, m[b]), r);
+        };
+        r
+    }
+
+    // 32 bytes big endian per word, as abi.encode
+    fun words_bytes(words: &vector<u256>): vector<u8> {
+        let bytes = vector[];
+        for (i in 0..vector::length(words)) {
+            let word = bcs::to_bytes(&words[i]);
+            vector::reverse(&mut word);
+            vector::append(&mut bytes, word);
+        };
+        bytes
+    }
+
+    fun transcript_hash(absorbing: &vector<u256>, len: u64): vector<u8> {
+        let data = words_bytes(absorbing);
+        vector::trim(&mut data, len);
+        {%- if keccak %}
+        aptos_hash::keccak256(data)
+        {%- else %}
+        hash::sha2_256(data)
+        {%- endif %}
+    }
+
+    // the digest as a big endian word
+    fun word_of(h: vector<u8>): u256 {
+        vector::reverse(&mut h);
+        from_bcs::to_u256(h)
+    }
+
+    // the digest as a little endian word, fr_reverse of verifier.sol
+    fun to_scalar(h: vector<u8>): u256 {
+        from_bcs::to_u256(h) % Q_MOD
+    }
+
+    // precompile order x1, x0, y1, y0 to x0, x1, y0, y1 little endian
+    fun g2(pairs: &vector<u256>, w: u64): Element<G2> {
+        let bytes = vector[];
+        for (j in 0..4) {
+            let word = pairs[w + 2 + (j ^ 1)];
+            vector::append(&mut bytes, bcs::to_bytes(&word));
+        };
+        let e = deserialize<G2, FormatG2Uncompr>(&bytes);
+        assert!(option::is_some(&e), E_INVALID_POINT);
+        option::extract(&mut e)
+    }
+
+    // (g1 x, g1 y, g2 x1, g2 x0, g2 y1, g2 y0) for each pair, Gt is written additively
+    fun pairing(pairs: &vector<u256>): bool {
+        let g1s = vector[];
+        let g2s = vector[];
+        for (i in 0..4) {
+            let w = i * 6;
+            vector::push_back(&mut g1s, g1(Point { x: pairs[w], y: pairs[w + 1] }));
+            vector::push_back(&mut g2s, g2(pairs, w));
+        };
+        eq(&multi_pairing<G1, G2, Gt>(&g1s, &g2s), &zero<Gt>())
+    }
+
+    public entry fun verify(proof: vector<u256>, target_circuit_final_pair: vector<u256>) {
+        // final pair coordinates followed by the aggregated target circuit instances
+        assert!(
+            vector::length(&target_circuit_final_pair) == {{ instance_size }},
+            E_INVALID_PUBLIC_INPUT_LENGTH
+        );
+        for (i in 0..4) {
+            assert!(target_circuit_final_pair[i] < P_MOD, E_INVALID_PUBLIC_INPUT);
+        };
+        {%- if instance_size > 4 %}
+        for (i in 4..{{ instance_size }}) {
+            assert!(target_circuit_final_pair[i] < Q_MOD, E_INVALID_PUBLIC_INPUT);
+        };
+        {%- endif %}
+
+        let instances = zeros({{ instance_size }});
+        instances[0] = target_circuit_final_pair[0] & 0xffffffffffffffffffffffffffffffffff;
+        instances[1] = (target_circuit_final_pair[0] >> 136) + ((target_circuit_final_pair[1] & 1) << 136);
+        instances[2] = target_circuit_final_pair[2] & 0xffffffffffffffffffffffffffffffffff;
+        instances[3] = (target_circuit_final_pair[2] >> 136) + ((target_circuit_final_pair[3] & 1) << 136);
+        {%- for statement in instance_assign %}
+        {{statement}}
+        {%- endfor %}
+
+        let m = zeros({{ memory_size }});
+        let absorbing = zeros({{ absorbing_length + 1 }});
+        let t0: u256 = 0;
+        let t1: u256 = 0;
+        let p = Point { x: 0, y: 0 };
+        let h: vector<u8> = vector[];
+        {% for statement in statements %}
+        {{statement}}
+        {%- endfor %}
+
+        let wx = {{ wx }};
+        let wg = {{ wg }};
+
+        // both pairing checks in a single call, see code_generator::pairing
+        let pairs = zeros(24);
+        {%- for statement in pairing_input %}
+        {{statement}}
+        {%- endfor %}
+        let r = word_of(aptos_hash::keccak256(words_bytes(&pairs))) % Q_MOD;
+        {%- for statement in pairing_scale %}
+        {{statement}}
+        {%- endfor %}
+        assert!(pairing(&pairs), E_PROOF_INVALID);
+    }
+}
@@ -0,0 +1,193 @@
+# @version ^0.3.10
+# SPDX-License-Identifier: GPL-3.0
+# Vyper version of verifier.sol, with the same
+# verify(uint256[] proof, uint256[] target_circuit_final_pair) abi.
+# Reverts carry the name of the matching custom error of verifier.sol as reason.
+
+Q_MOD: constant(uint256) = 21888242871839275222246405745257275088548364400416034343698204186575808495617
+P_MOD: constant(uint256) = 21888242871839275222246405745257275088696311157297823662689037894645226208583
+WORD: constant(uint256) = 32
+MODEXP: constant(address) = 0x0000000000000000000000000000000000000005
+PAIRING: constant(address) = 0x0000000000000000000000000000000000000008
+
+
+@internal
+@view
+def fr_pow(a: uint256, power: uint256) -> uint256:
+    res: Bytes[32] = raw_call(
+        MODEXP,
+        _abi_encode(WORD, WORD, WORD, a, power, Q_MOD),
+        max_outsize=32,
+        is_static_call=True,
+    )
+    return extract32(res, 0, output_type=uint256)
+
+
+@internal
+@view
+def fr_div(a: uint256, b: uint256) -> uint256:
+    assert b != 0, "DivisionByZero"
+    return uint256_mulmod(a, self.fr_pow(b, Q_MOD - 2), Q_MOD)
+
+
+@internal
+@pure
+def fr_mul_add(a: uint256, b: uint256, c: uint256) -> uint256:
+    return uint256_addmod(uint256_mulmod(a, b, Q_MOD), c, Q_MOD)
+
+
+@internal
+@pure
+def fr_mul_add_pm(
+    m: uint256[{{ memory_size }}],
+    proof: DynArray[uint256, {{ proof_len }}],
+    opcode: uint256,
+    t: uint256,
+) -> uint256:
+    op: uint256 = opcode
+    r: uint256 = t
+    for i in range(16):
+        a: uint256 = op & 255
+        if a == 255:
+            break
+        op = op >> 8
+        b: uint256 = op & 255
+        op = op >> 8
+        r = uint256_addmod(uint256_mulmod(proof[a], m[b], Q_MOD), r, Q_MOD)
+    return r
+
+
+@internal
+@pure
+def fr_mul_add_mt(m: uint256[{{ memory_size }}], base: uint256, opcode: uint256, t: uint256) -> uint256:
+    op: uint256 = opcode
+    r: uint256 = t
+    for i in range(32):
+        a: uint256 = op & 255
+        if a == 255:
+            break
+        op = op >> 8
+        r = uint256_addmod(uint256_mulmod(base, r, Q_MOD), m[a], Q_MOD)
+    return r
+
+
+@internal
+@pure
+def fr_reverse(input: uint256) -> uint256:
+    v: uint256 = input
+    # swap bytes
+    v = ((v & 0xFF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00) >> 8) | ((v & 0x00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF) << 8)
+    # swap 2-byte long pairs
+    v = ((v & 0xFFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000) >> 16) | ((v & 0x0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF) << 16)
+    # swap 4-byte long pairs
+    v = ((v & 0xFFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000) >> 32) | ((v & 0x00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF) << 32)
+    # swap 8-byte long pairs
+    v = ((v & 0xFFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF0000000000000000) >> 64) | ((v & 0x0000000000000000FFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF) << 64)
+    # swap 16-byte long pairs
+    return (v >> 128) | (v << 128)
+
+
+@internal
+@view
+def transcript_hash(data: Bytes[{{ (absorbing_length + 1) * 32 }}]) -> bytes32:
+    {%- if keccak %}
+    return keccak256(data)
+    {%- else %}
+    return sha256(data)
+    {%- endif %}
+
+
+@internal
+@pure
+def to_scalar(h: bytes32) -> uint256:
+    return self.fr_reverse(convert(h, uint256)) % Q_MOD
+
+
+@internal
+@view
+def ecc_sub(a: uint256[2], b: uint256[2]) -> uint256[2]:
+    return ecadd(a, [b[0], P_MOD - b[1]])
+
+
+@internal
+@view
+def ecc_mul_add(p: uint256[2], s: uint256, q: uint256[2]) -> uint256[2]:
+    return ecadd(ecmul(p, s), q)
+
+
+@internal
+@view
+def ecc_mul_add_pm(
+    m: uint256[{{ memory_size }}],
+    proof: DynArray[uint256, {{ proof_len }}],
+    opcode: uint256,
+    t: uint256[2],
+) -> uint256[2]:
+    op: uint256 = opcode
+    r: uint256[2] = t
+    for i in range(16):
+        a: uint256 = op & 255
+        if a == 255:
+            break
+        op = op >> 8
+        b: uint256 = op & 255
+        op = op >> 8
+        r = ecadd(ecmul([proof[a], proof[a + 1]], m[b]), r)
+    return r
+
+
+# (g1 x, g1 y, g2 x[0], g2 x[1], g2 y[0], g2 y[1]) for each pair
+@internal
+@view
+def pairing(input: uint256[24]) -> bool:
+    res: Bytes[32] = raw_call(PAIRING, _abi_encode(input), max_outsize=32, is_static_call=True)
+    return extract32(res, 0, output_type=uint256) == 1
+
+
+@external
+@view
+def verify(
+    proof: DynArray[uint256, {{ proof_len }}],
+    target_circuit_final_pair: DynArray[uint256, {{ instance_size }}],
+):
+    # final pair coordinates followed by the aggregated target circuit instances
+    assert len(target_circuit_final_pair) == {{ instance_size }}, "InvalidPublicInputLength"
+    for i in range(4):
+        assert target_circuit_final_pair[i] < P_MOD, "InvalidPublicInput"
+    {%- if instance_size > 4 %}
+    for i in range(4, {{ instance_size }}):
+        assert target_circuit_final_pair[i] < Q_MOD, "InvalidPublicInput"
+    {%- endif %}
+
+    instances: uint256[{{ instance_size }}] = empty(uint256[{{ instance_size }}])
+    instances[0] = target_circuit_final_pair[0] & 0xffffffffffffffffffffffffffffffffff
+    instances[1] = (target_circuit_final_pair[0] >> 136) + ((target_circuit_final_pair[1] & 1) << 136)
+    instances[2] = target_circuit_final_pair[2] & 0xffffffffffffffffffffffffffffffffff
+    instances[3] = (target_circuit_final_pair[2] >> 136) + ((target_circuit_final_pair[3] & 1) << 136)
+    {%- for statement in instance_assign %}
+    {{statement}}
+    {%- endfor %}
+
+    m: uint256[{{ memory_size }}] = empty(uint256[{{ memory_size }}])
+    absorbing: uint256[{{ absorbing_length + 1 }}] = empty(uint256[{{ absorbing_length + 1 }}])
+    t0: uint256 = 0
+    t1: uint256 = 0
+    p: uint256[2] = empty(uint256[2])
+    h: bytes32 = empty(bytes32)
+    {% for statement in statements %}
+    {{statement}}
+    {%- endfor %}
+
+    wx: uint256[2] = {{ wx }}
+    wg: uint256[2] = {{ wg }}
+
+    # both pairing checks in a single call, see code_generator::pairing
+    pairs: uint256[24] = empty(uint256[24])
+    {%- for statement in pairing_input %}
+    {{statement}}
+    {%- endfor %}
+    r: uint256 = convert(keccak256(_abi_encode(pairs)), uint256) % Q_MOD
+    {%- for statement in pairing_scale %}
+    {{statement}}
+    {%- endfor %}
+    assert self.pairing(pairs), "ProofInvalid(0)"
@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: {{ license }}
+pragma solidity {{ pragma }};
+{%- for hint in optimizer_hints %}
+// {{ hint }}
+{%- endfor %}
+
+import "./verifier.sol";
+
+// Verifies several aggregated proofs with a single pairing check. The pairs of each
+// proof are combined with coefficients derived from the keccak of the whole batch,
+// so the batch passes only if every proof does, except with negligible probability.
+contract BatchVerifier is {{ contract_name }} {
+    error EmptyBatch();
+    error BatchLengthMismatch(uint256 proofs, uint256 final_pairs);
+{%- if steps > 0 %}
+
+    constructor(address[{{ steps }}] memory steps) {{ contract_name }}(steps) {}
+{%- endif %}
+
+    // acc holds r * w_x, r * w_g and r times the two final pair points, summed over the batch.
+    function accumulate(
+        uint256[8] memory acc,
+        uint256[] calldata proof,
+        uint256[] calldata target_circuit_final_pair,
+        uint256 r
+    ) internal view {
+        uint256[{{instance_size}}] memory instances = load_instances(target_circuit_final_pair);
+        (uint256 x0, uint256 y0, uint256 x1, uint256 y1) = get_wx_wg(proof, instances);
+
+        (acc[0], acc[1]) = ecc_mul_add(x0, y0, r, acc[0], acc[1]);
+        (acc[2], acc[3]) = ecc_mul_add(x1, y1, r, acc[2], acc[3]);
+        (acc[4], acc[5]) = ecc_mul_add(
+            target_circuit_final_pair[0],
+            target_circuit_final_pair[1],
+            r,
+            acc[4],
+            acc[5]
+        );
+        (acc[6], acc[7]) = ecc_mul_add(
+            target_circuit_final_pair[2],
+            target_circuit_final_pair[3],
+            r,
+            acc[6],
+            acc[7]
+        );
+    }
+
+    function verify_batch(
+        uint256[][] calldata proofs,
+        uint256[][] calldata target_circuit_final_pairs
+    ) public view {
+        if (proofs.length == 0) revert EmptyBatch();
+        if (proofs.length != target_circuit_final_pairs.length) {
+            revert BatchLengthMismatch(proofs.length, target_circuit_final_pairs.length);
+        }
+
+        bytes32 seed = keccak256(abi.encode(proofs, target_circuit_final_pairs));
+        // (0, 0) is the identity for the ecAdd precompile
+        uint256[8] memory acc;
+        for (uint256 i = 0; i < proofs.length; i++) {
+            uint256 r = uint256(keccak256(abi.encodePacked(seed, i))) % q_mod;
+            accumulate(acc, proofs[i], target_circuit_final_pairs[i], r);
+        }
+
+        G1Point[] memory g1_points = new G1Point[](4);
+        G2Point[] memory g2_points = new G2Point[](4);
+        for (uint256 i = 0; i < 4; i++) {
+            g1_points[i].x = acc[i * 2];
+            g1_points[i].y = acc[i * 2 + 1];
+        }
+        g2_points[0] = get_verify_circuit_g2_s();
+        g2_points[1] = get_verify_circuit_g2_n();
+        g2_points[2] = get_target_circuit_g2_s();
+        g2_points[3] = get_target_circuit_g2_n();
+
+        if (!pairing(g1_points, g2_points)) revert ProofInvalid(2);
+    }
+}
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: GPL-3.0
+pragma solidity {{ pragma }};
+
+// Settles the final pair of an aggregation proof verified off-chain. The final pair
+// (w_x, w_g) accumulates the target circuit proofs, it is valid if
+// e(w_x, s_g2) e(w_g, -g2) == 1 on the g2 points of the target circuit setup.
+contract DeferredPairingVerifier {
+    error PrecompileFailed(uint256 precompile);
+    error InvalidFinalPair(uint256 index);
+    error VkHashMismatch(bytes32 vkHash);
+    error PairingFailed();
+
+    uint256 constant p_mod =
+        21888242871839275222246405745257275088696311157297823662689037894645226208583;
+
+    // hash of the vk the final pair was accumulated for
+    bytes32 public constant VK_HASH = 0x{{ vk_hash }};
+
+    // final pair coordinates (x0, y0, x1, y1), as the first 4 words of the final pair
+    // array of the full verifier
+    function verify(uint256[4] calldata finalPair, bytes32 vkHash) public view {
+        if (vkHash != VK_HASH) revert VkHashMismatch(vkHash);
+        for (uint256 i = 0; i < 4; i++) {
+            if (finalPair[i] >= p_mod) revert InvalidFinalPair(i);
+        }
+
+        uint256[12] memory input;
+        input[0] = finalPair[0];
+        input[1] = finalPair[1];
+        {%- for word in s_g2 %}
+        input[{{ loop.index + 1 }}] = {{ word }};
+        {%- endfor %}
+        input[6] = finalPair[2];
+        input[7] = finalPair[3];
+        {%- for word in n_g2 %}
+        input[{{ loop.index + 7 }}] = {{ word }};
+        {%- endfor %}
+
+        uint256[1] memory result;
+        bool ret;
+        assembly {
+            ret := staticcall(gas(), 8, input, 0x180, result, 0x20)
+        }
+        if (!ret) revert PrecompileFailed(8);
+        if (result[0] == 0) revert PairingFailed();
+    }
+}
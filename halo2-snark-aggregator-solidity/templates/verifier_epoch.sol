@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: GPL-3.0
+pragma solidity {{ pragma }};
+
+import {IVerifier} from "./verifier.sol";
+
+// Submission scaffold around a deployed verifier: each accepted aggregation proof
+// moves the contract from one state root to the next and bumps the epoch. The
+// instances must commit to the current root, the new root and the next epoch, so
+// an old proof can't be replayed once the epoch has moved on.
+// Anyone with a valid proof may submit, add access control on top if needed.
+contract EpochVerifier {
+    error InvalidPublicInputLength(uint256 length);
+    error InvalidEpoch(uint256 epoch);
+    error InvalidStateRoot(uint256 root);
+    error ProofRejected(uint256 epoch);
+
+    event EpochSubmitted(uint256 indexed epoch, uint256 stateRoot);
+
+    IVerifier public immutable verifier;
+    uint256 public epoch;
+    uint256 public stateRoot;
+
+    constructor(IVerifier verifier_, uint256 genesisStateRoot) {
+        verifier = verifier_;
+        stateRoot = genesisStateRoot;
+    }
+
+    // proof and instances as taken by IVerifier.verify, instances being the final pair
+    // followed by the aggregated target circuit instances.
+    function submit(bytes calldata proof, uint256[] calldata instances) external {
+        if (instances.length <= {{ max_index }}) revert InvalidPublicInputLength(instances.length);
+
+        uint256 next = epoch + 1;
+        if (instances[{{ epoch_index }}] != next) revert InvalidEpoch(instances[{{ epoch_index }}]);
+        if (instances[{{ prev_root_index }}] != stateRoot) {
+            revert InvalidStateRoot(instances[{{ prev_root_index }}]);
+        }
+        if (!verifier.verify(proof, instances)) revert ProofRejected(next);
+
+        epoch = next;
+        stateRoot = instances[{{ new_root_index }}];
+        emit EpochSubmitted(next, stateRoot);
+    }
+}
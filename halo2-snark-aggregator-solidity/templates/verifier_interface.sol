@@ -0,0 +1,12 @@
+interface IERC165 {
+    function supportsInterface(bytes4 interfaceId) external view returns (bool);
+}
+
+// Stable interface of the generated verifiers, callers can swap verifiers without changes.
+// proof is the concatenation of the 32 bytes big endian proof words, instances the final pair.
+interface IVerifier {
+    function verify(bytes calldata proof, uint256[] calldata instances)
+        external
+        view
+        returns (bool);
+}
@@ -0,0 +1,420 @@
+    // ProofInvalid(0) is the pairing of verify(), checking the verify circuit and target
+    // circuit pairs at once, and ProofInvalid(2) the batched pairing of BatchVerifier.
+    error ProofInvalid(uint256 step);
+    // Address of the precompile whose call failed.
+    error PrecompileFailed(uint256 precompile);
+    error InvalidPublicInputLength(uint256 length);
+    error InvalidPublicInput(uint256 index);
+    error DivisionByZero();
+
+    function pairing(G1Point[] memory p1, G2Point[] memory p2)
+        internal
+        view
+        returns (bool)
+    {
+        uint256[] memory input = new uint256[](p1.length * 6);
+
+        assert(p1.length == p2.length);
+
+        unchecked {
+            for (uint256 i = 0; i < p1.length; i++) {
+                input[0 + i * 6] = p1[i].x;
+                input[1 + i * 6] = p1[i].y;
+                input[2 + i * 6] = p2[i].x[0];
+                input[3 + i * 6] = p2[i].x[1];
+                input[4 + i * 6] = p2[i].y[0];
+                input[5 + i * 6] = p2[i].y[1];
+            }
+        }
+
+        return pairing_call(input);
+    }
+
+    // input is the (g1 x, g1 y, g2 x0, g2 x1, g2 y0, g2 y1) words of each pair.
+    function pairing_call(uint256[] memory input) internal view returns (bool) {
+        {%- if shared_lib %}
+        return VerifierPairing.pairing(input);
+        {%- else %}
+        uint256 length = input.length;
+        uint256[1] memory result;
+        bool ret;
+
+        assembly {
+            ret := staticcall(
+                gas(),
+                8,
+                add(input, 0x20),
+                mul(length, 0x20),
+                result,
+                0x20
+            )
+        }
+        if (!ret) revert PrecompileFailed(8);
+        return result[0] != 0;
+        {%- endif %}
+    }
+
+    uint256 constant q_mod =
+        21888242871839275222246405745257275088548364400416034343698204186575808495617;
+
+    function fr_invert(uint256 a) internal view returns (uint256) {
+        return fr_pow(a, q_mod - 2);
+    }
+
+    function fr_pow(uint256 a, uint256 power) internal view returns (uint256) {
+        {%- if shared_lib %}
+        return VerifierPairing.fr_pow(a, power);
+        {%- else %}
+        uint256[6] memory input;
+        uint256[1] memory result;
+        bool ret;
+
+        input[0] = 32;
+        input[1] = 32;
+        input[2] = 32;
+        input[3] = a;
+        input[4] = power;
+        input[5] = q_mod;
+
+        assembly {
+            ret := staticcall(gas(), 0x05, input, 0xc0, result, 0x20)
+        }
+        if (!ret) revert PrecompileFailed(5);
+
+        return result[0];
+        {%- endif %}
+    }
+
+    function fr_div(uint256 a, uint256 b) internal view returns (uint256) {
+        if (b == 0) revert DivisionByZero();
+        return mulmod(a, fr_invert(b), q_mod);
+    }
+
+    function fr_mul_add(
+        uint256 a,
+        uint256 b,
+        uint256 c
+    ) internal pure returns (uint256) {
+        return addmod(mulmod(a, b, q_mod), c, q_mod);
+    }
+
+    function fr_mul_add_pm(
+        uint256[{{memory_size}}] memory m,
+        uint256[] calldata proof,
+        uint256 opcode,
+        uint256 t
+    ) internal pure returns (uint256) {
+        unchecked {
+            for (uint256 i = 0; i < 32; i += 2) {
+                uint256 a = opcode & 0xff;
+                if (a != 0xff) {
+                    opcode >>= 8;
+                    uint256 b = opcode & 0xff;
+                    opcode >>= 8;
+                    t = addmod(mulmod(proof[a], m[b], q_mod), t, q_mod);
+                } else {
+                    break;
+                }
+            }
+        }
+
+        return t;
+    }
+
+    function fr_mul_add_mt(
+        uint256[{{memory_size}}] memory m,
+        uint256 base,
+        uint256 opcode,
+        uint256 t
+    ) internal pure returns (uint256) {
+        unchecked {
+            for (uint256 i = 0; i < 32; i += 1) {
+                uint256 a = opcode & 0xff;
+                if (a != 0xff) {
+                    opcode >>= 8;
+                    t = addmod(mulmod(base, t, q_mod), m[a], q_mod);
+                } else {
+                    break;
+                }
+            }
+        }
+
+        return t;
+    }
+
+    function fr_reverse(uint256 input) internal pure returns (uint256 v) {
+        v = input;
+
+        // swap bytes
+        v = ((v & 0xFF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00) >> 8) |
+            ((v & 0x00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF) << 8);
+
+        // swap 2-byte long pairs
+        v = ((v & 0xFFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000) >> 16) |
+            ((v & 0x0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF) << 16);
+
+        // swap 4-byte long pairs
+        v = ((v & 0xFFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000) >> 32) |
+            ((v & 0x00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF) << 32);
+
+        // swap 8-byte long pairs
+        v = ((v & 0xFFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF0000000000000000) >> 64) |
+            ((v & 0x0000000000000000FFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF) << 64);
+
+        // swap 16-byte long pairs
+        v = (v >> 128) | (v << 128);
+    }
+
+    uint256 constant p_mod =
+        21888242871839275222246405745257275088696311157297823662689037894645226208583;
+
+    struct G1Point {
+        uint256 x;
+        uint256 y;
+    }
+
+    struct G2Point {
+        uint256[2] x;
+        uint256[2] y;
+    }
+
+    function ecc_from(uint256 x, uint256 y)
+        internal
+        pure
+        returns (G1Point memory r)
+    {
+        r.x = x;
+        r.y = y;
+    }
+
+    function ecc_add(uint256 ax, uint256 ay, uint256 bx, uint256 by)
+        internal
+        view
+        returns (uint256, uint256)
+    {
+        {%- if shared_lib %}
+        return VerifierPairing.ecc_add(ax, ay, bx, by);
+        {%- else %}
+        bool ret = false;
+        G1Point memory r;
+        uint256[4] memory input_points;
+
+        input_points[0] = ax;
+        input_points[1] = ay;
+        input_points[2] = bx;
+        input_points[3] = by;
+
+        assembly {
+            ret := staticcall(gas(), 6, input_points, 0x80, r, 0x40)
+        }
+        if (!ret) revert PrecompileFailed(6);
+
+        return (r.x, r.y);
+        {%- endif %}
+    }
+
+    function ecc_sub(uint256 ax, uint256 ay, uint256 bx, uint256 by)
+        internal
+        view
+        returns (uint256, uint256)
+    {
+        return ecc_add(ax, ay, bx, p_mod - by);
+    }
+
+    function ecc_mul(uint256 px, uint256 py, uint256 s)
+        internal
+        view
+        returns (uint256, uint256)
+    {
+        {%- if shared_lib %}
+        return VerifierPairing.ecc_mul(px, py, s);
+        {%- else %}
+        uint256[3] memory input;
+        bool ret = false;
+        G1Point memory r;
+
+        input[0] = px;
+        input[1] = py;
+        input[2] = s;
+
+        assembly {
+            ret := staticcall(gas(), 7, input, 0x60, r, 0x40)
+        }
+        if (!ret) revert PrecompileFailed(7);
+
+        return (r.x, r.y);
+        {%- endif %}
+    }
+
+    function _ecc_mul_add(uint256[5] memory input)
+        internal
+        view
+    {
+        {%- if shared_lib %}
+        (input[3], input[4]) = VerifierPairing.ecc_mul_add(input[0], input[1], input[2], input[3], input[4]);
+        {%- else %}
+        bool ret = false;
+
+        assembly {
+            ret := staticcall(gas(), 7, input, 0x60, add(input, 0x20), 0x40)
+        }
+        if (!ret) revert PrecompileFailed(7);
+
+        assembly {
+            ret := staticcall(gas(), 6, add(input, 0x20), 0x80, add(input, 0x60), 0x40)
+        }
+        if (!ret) revert PrecompileFailed(6);
+        {%- endif %}
+    }
+
+    function ecc_mul_add(uint256 px, uint256 py, uint256 s, uint256 qx, uint256 qy)
+        internal
+        view
+        returns (uint256, uint256)
+    {
+        uint256[5] memory input;
+        input[0] = px;
+        input[1] = py;
+        input[2] = s;
+        input[3] = qx;
+        input[4] = qy;
+
+        _ecc_mul_add(input);
+
+        return (input[3], input[4]);
+    }
+    
+    function ecc_mul_add_pm(
+        uint256[{{memory_size}}] memory m,
+        uint256[] calldata proof,
+        uint256 opcode,
+        uint256 t0,
+        uint256 t1
+    ) internal view returns (uint256, uint256) {
+        uint256[5] memory input;
+        input[3] = t0;
+        input[4] = t1;
+        unchecked {
+            for (uint256 i = 0; i < 32; i += 2) {
+                uint256 a = opcode & 0xff;
+                if (a != 0xff) {
+                    opcode >>= 8;
+                    uint256 b = opcode & 0xff;
+                    opcode >>= 8;
+                    input[0] = proof[a];
+                    input[1] = proof[a + 1];
+                    input[2] = m[b];
+                    _ecc_mul_add(input);
+                } else {
+                    break;
+                }
+            }
+        }
+
+        return (input[3], input[4]);
+    }
+
+    // 4 bytes per entry: the offset of the point, in the proof if the top bit is set
+    // and in m otherwise, and the slot of the scalar in m.
+    function ecc_mul_add_table(
+        uint256[{{memory_size}}] memory m,
+        uint256[] calldata proof,
+        bytes memory table,
+        uint256 t0,
+        uint256 t1
+    ) internal view returns (uint256, uint256) {
+        uint256[5] memory input;
+        input[3] = t0;
+        input[4] = t1;
+        unchecked {
+            for (uint256 i = 0; i < table.length; i += 4) {
+                uint256 entry;
+                assembly {
+                    entry := shr(224, mload(add(add(table, 0x20), i)))
+                }
+                uint256 p = entry >> 16;
+                if (p & 0x8000 != 0) {
+                    p &= 0x7fff;
+                    input[0] = proof[p];
+                    input[1] = proof[p + 1];
+                } else {
+                    input[0] = m[p];
+                    input[1] = m[p + 1];
+                }
+                input[2] = m[entry & 0xffff];
+                _ecc_mul_add(input);
+            }
+        }
+
+        return (input[3], input[4]);
+    }
+
+    function update_hash_scalar(uint256 v, uint256[{{absorbing_length + 1}}] memory absorbing, uint256 pos) internal pure {
+        absorbing[pos++] = 0x02;
+        absorbing[pos++] = v;
+    }
+
+    function update_hash_point(uint256 x, uint256 y, uint256[{{absorbing_length + 1}}] memory absorbing, uint256 pos) internal pure {
+        absorbing[pos++] = 0x01;
+        absorbing[pos++] = x;
+        absorbing[pos++] = y;
+    }
+
+    function to_scalar(bytes32 r) private pure returns (uint256 v) {
+        uint256 tmp = uint256(r);
+        tmp = fr_reverse(tmp);
+        v = tmp % 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001;
+    }
+
+    {%- if keccak %}
+    function hash(uint256[{{absorbing_length + 1}}] memory absorbing, uint256 length) private pure returns (bytes32[1] memory v) {
+        assembly {
+            mstore(v, keccak256(absorbing, length))
+        }
+    }
+    {%- else %}
+    function hash(uint256[{{absorbing_length + 1}}] memory absorbing, uint256 length) private view returns (bytes32[1] memory v) {
+        bool success;
+        assembly {
+            success := staticcall(sub(gas(), 2000), 2, absorbing, length, v, 32)
+            switch success case 0 { invalid() }
+        }
+        assert(success);
+    }
+    {%- endif %}
+
+    function squeeze_challenge(uint256[{{absorbing_length + 1}}] memory absorbing, uint32 length) internal view returns (uint256 v) {
+        absorbing[length] = 0;
+        bytes32 res = hash(absorbing, length * 32 + 1)[0];
+        v = to_scalar(res);
+        absorbing[0] = uint256(res);
+        length = 1;
+    }
+
+    function get_verify_circuit_g2_s() internal pure returns (G2Point memory s) {
+        s.x[0] = uint256({{verify_circuit_s_g2_x0}});
+        s.x[1] = uint256({{verify_circuit_s_g2_x1}});
+        s.y[0] = uint256({{verify_circuit_s_g2_y0}});
+        s.y[1] = uint256({{verify_circuit_s_g2_y1}});
+    }
+
+    function get_verify_circuit_g2_n() internal pure returns (G2Point memory n) {
+        n.x[0] = uint256({{verify_circuit_n_g2_x0}});
+        n.x[1] = uint256({{verify_circuit_n_g2_x1}});
+        n.y[0] = uint256({{verify_circuit_n_g2_y0}});
+        n.y[1] = uint256({{verify_circuit_n_g2_y1}});
+    }
+
+    function get_target_circuit_g2_s() internal pure returns (G2Point memory s) {
+        s.x[0] = uint256({{target_circuit_s_g2_x0}});
+        s.x[1] = uint256({{target_circuit_s_g2_x1}});
+        s.y[0] = uint256({{target_circuit_s_g2_y0}});
+        s.y[1] = uint256({{target_circuit_s_g2_y1}});
+    }
+
+    function get_target_circuit_g2_n() internal pure returns (G2Point memory n) {
+        n.x[0] = uint256({{target_circuit_n_g2_x0}});
+        n.x[1] = uint256({{target_circuit_n_g2_x1}});
+        n.y[0] = uint256({{target_circuit_n_g2_y0}});
+        n.y[1] = uint256({{target_circuit_n_g2_y1}});
+    }
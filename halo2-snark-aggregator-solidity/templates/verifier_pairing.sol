@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: GPL-3.0
+pragma solidity {{ pragma }};
+
+// Precompile helpers shared by the verifiers generated with a shared library.
+// Deploy it once and link it into each verifier, they reach it by delegatecall.
+library VerifierPairing {
+    error PrecompileFailed(uint256 precompile);
+
+    uint256 constant q_mod =
+        21888242871839275222246405745257275088548364400416034343698204186575808495617;
+
+    // (g1 x, g1 y, g2 x[0], g2 x[1], g2 y[0], g2 y[1]) for each pair
+    function pairing(uint256[] memory input) public view returns (bool) {
+        uint256[1] memory result;
+        bool ret;
+
+        assembly {
+            ret := staticcall(
+                gas(),
+                8,
+                add(input, 0x20),
+                mul(mload(input), 0x20),
+                result,
+                0x20
+            )
+        }
+        if (!ret) revert PrecompileFailed(8);
+        return result[0] != 0;
+    }
+
+    function fr_pow(uint256 a, uint256 power) public view returns (uint256) {
+        uint256[6] memory input;
+        uint256[1] memory result;
+        bool ret;
+
+        input[0] = 32;
+        input[1] = 32;
+        input[2] = 32;
+        input[3] = a;
+        input[4] = power;
+        input[5] = q_mod;
+
+        assembly {
+            ret := staticcall(gas(), 0x05, input, 0xc0, result, 0x20)
+        }
+        if (!ret) revert PrecompileFailed(5);
+
+        return result[0];
+    }
+
+    function ecc_add(uint256 ax, uint256 ay, uint256 bx, uint256 by)
+        public
+        view
+        returns (uint256, uint256)
+    {
+        bool ret = false;
+        uint256[2] memory r;
+        uint256[4] memory input_points;
+
+        input_points[0] = ax;
+        input_points[1] = ay;
+        input_points[2] = bx;
+        input_points[3] = by;
+
+        assembly {
+            ret := staticcall(gas(), 6, input_points, 0x80, r, 0x40)
+        }
+        if (!ret) revert PrecompileFailed(6);
+
+        return (r[0], r[1]);
+    }
+
+    function ecc_mul(uint256 px, uint256 py, uint256 s)
+        public
+        view
+        returns (uint256, uint256)
+    {
+        uint256[3] memory input;
+        bool ret = false;
+        uint256[2] memory r;
+
+        input[0] = px;
+        input[1] = py;
+        input[2] = s;
+
+        assembly {
+            ret := staticcall(gas(), 7, input, 0x60, r, 0x40)
+        }
+        if (!ret) revert PrecompileFailed(7);
+
+        return (r[0], r[1]);
+    }
+
+    // p * s + q
+    function ecc_mul_add(uint256 px, uint256 py, uint256 s, uint256 qx, uint256 qy)
+        public
+        view
+        returns (uint256, uint256)
+    {
+        uint256[5] memory input;
+        bool ret = false;
+
+        input[0] = px;
+        input[1] = py;
+        input[2] = s;
+        input[3] = qx;
+        input[4] = qy;
+
+        assembly {
+            ret := staticcall(gas(), 7, input, 0x60, add(input, 0x20), 0x40)
+        }
+        if (!ret) revert PrecompileFailed(7);
+
+        assembly {
+            ret := staticcall(gas(), 6, add(input, 0x20), 0x80, add(input, 0x60), 0x40)
+        }
+        if (!ret) revert PrecompileFailed(6);
+
+        return (input[3], input[4]);
+    }
+}
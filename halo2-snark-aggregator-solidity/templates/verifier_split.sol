@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: {{ license }}
+pragma solidity {{ pragma }};
+{%- for hint in optimizer_hints %}
+// {{ hint }}
+{%- endfor %}
+{%- if shared_lib %}
+
+import "./verifier_pairing.sol";
+{%- endif %}
+
+// The statements of get_wx_wg don't fit in one contract under the EIP-170
+// code size limit, so they are split into steps deployed on their own before
+// the verifier, which takes their addresses in order.
+// Each step takes and returns the working memory of the previous one.
+abstract contract {{ contract_name }}Base {
+{% include "verifier_lib.sol" %}
+}
+{% for step in steps %}
+contract {{ contract_name }}Step{{ loop.index0 }} is {{ contract_name }}Base {
+    function step(
+        uint256[] calldata proof,
+        uint256[{{instance_size}}] memory instances,
+        uint256[{{memory_size}}] memory m,
+        uint256[{{absorbing_length + 1}}] memory absorbing{% if vk_size > 0 %},
+        uint256[{{ vk_size }}] memory vk{% endif %}
+    )
+        external
+        view
+        returns (uint256[{{memory_size}}] memory, uint256[{{absorbing_length + 1}}] memory)
+    {
+        uint256 t0 = 0;
+        uint256 t1 = 0;
+
+        {% for statement in step %}
+        {{statement}}
+        {%- endfor %}
+        return (m, absorbing);
+    }
+}
+{% endfor %}
+{% include "verifier_interface.sol" %}
+contract {{ contract_name }} is {{ contract_name }}Base, IVerifier, IERC165 {
+    error InvalidStep(uint256 index, address step);
+
+    // Immutables live in the code, so a proxy delegating to the verifier calls the same steps.
+{%- for step in steps %}
+    address immutable step{{ loop.index0 }};
+{%- endfor %}
+
+{%- if vk_size > 0 %}
+    error InvalidVerifyingKey(address vk);
+
+    // Constants of the steps, stored as the code of the VerifyingKey contract
+    // after a leading 0x00 byte, see verifier_vk.sol.
+    address immutable vk_address;
+{% endif %}
+    constructor(
+        address[{{ steps | length }}] memory steps{% if vk_size > 0 %},
+        address vk{% endif %}
+    ) {
+        {%- if vk_size > 0 %}
+        if (vk.code.length != {{ vk_size * 32 + 1 }}) revert InvalidVerifyingKey(vk);
+        vk_address = vk;
+        {%- endif %}
+        for (uint256 i = 0; i < {{ steps | length }}; i++) {
+            if (steps[i].code.length == 0) revert InvalidStep(i, steps[i]);
+        }
+        {%- for step in steps %}
+        step{{ loop.index0 }} = steps[{{ loop.index0 }}];
+        {%- endfor %}
+    }
+{%- if vk_size > 0 %}
+
+    function load_vk() internal view returns (uint256[{{ vk_size }}] memory vk) {
+        address addr = vk_address;
+        assembly {
+            extcodecopy(addr, vk, 1, {{ vk_size * 32 }})
+        }
+    }
+{%- endif %}
+
+    function get_wx_wg(uint256[] {{ data_location }} proof, uint256[{{instance_size}}] memory instances)
+        internal
+        view
+        returns (uint256, uint256, uint256, uint256)
+    {
+        uint256[{{memory_size}}] memory m;
+        uint256[{{absorbing_length + 1}}] memory absorbing;
+        {%- if vk_size > 0 %}
+        uint256[{{ vk_size }}] memory vk = load_vk();
+        {%- endif %}
+        {% for step in steps %}
+        (m, absorbing) = {{ contract_name }}Step{{ loop.index0 }}(step{{ loop.index0 }}).step(proof, instances, m, absorbing{% if vk_size > 0 %}, vk{% endif %});
+        {%- endfor %}
+        return ({{ wx }}, {{ wg }});
+    }
+
+{% include "verifier_verify.sol" %}}
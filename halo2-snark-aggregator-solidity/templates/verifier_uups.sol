@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: {{ license }}
+pragma solidity {{ pragma }};
+{%- for hint in optimizer_hints %}
+// {{ hint }}
+{%- endfor %}
+
+import "./verifier.sol";
+
+// UUPS implementation of the verifier, deployed behind VerifierProxy so callers
+// keep the proxy address across circuit upgrades. The upgrade logic lives in the
+// implementation: only the owner can upgrade, and only to an implementation
+// pinning the vk hash it expects.
+contract UpgradeableVerifier is {{ contract_name }} {
+    error NotOwner(address caller);
+    error NotProxy();
+    error AlreadyInitialized();
+    error InvalidImplementation(address implementation);
+    error VkHashMismatch(bytes32 vkHash);
+
+    event Upgraded(address indexed implementation, bytes32 vkHash);
+    event OwnershipTransferred(address indexed previousOwner, address indexed newOwner);
+
+    // keccak256("eip1967.proxy.implementation") - 1
+    bytes32 internal constant IMPLEMENTATION_SLOT =
+        0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc;
+    // keccak256("eip1967.proxy.admin") - 1
+    bytes32 internal constant OWNER_SLOT =
+        0xb53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103;
+    // hash of the verify circuit vk this implementation checks proofs of
+    bytes32 internal constant VK_HASH = 0x{{ vk_hash }};
+
+    address private immutable self = address(this);
+{%- if steps > 0 %}
+
+    // The steps of the split verifier are immutables of its code, so the proxy uses them too.
+    constructor(address[{{ steps }}] memory steps) {{ contract_name }}(steps) {}
+{%- endif %}
+
+    modifier onlyProxy() {
+        if (address(this) == self) revert NotProxy();
+        _;
+    }
+
+    modifier onlyOwner() {
+        if (msg.sender != owner()) revert NotOwner(msg.sender);
+        _;
+    }
+
+    function vkHash() external pure returns (bytes32) {
+        return VK_HASH;
+    }
+
+    // ERC-1822, checked by VerifierProxy and upgradeTo.
+    function proxiableUUID() external view returns (bytes32) {
+        if (address(this) != self) revert NotProxy();
+        return IMPLEMENTATION_SLOT;
+    }
+
+    function owner() public view returns (address o) {
+        bytes32 slot = OWNER_SLOT;
+        assembly {
+            o := sload(slot)
+        }
+    }
+
+    // Called by the VerifierProxy constructor.
+    function initialize(address newOwner) external onlyProxy {
+        if (owner() != address(0)) revert AlreadyInitialized();
+        set_owner(newOwner);
+    }
+
+    function transferOwnership(address newOwner) external onlyProxy onlyOwner {
+        set_owner(newOwner);
+    }
+
+    function upgradeTo(address implementation, bytes32 expectedVkHash) external onlyProxy onlyOwner {
+        if (implementation.code.length == 0) revert InvalidImplementation(implementation);
+        try UpgradeableVerifier(implementation).proxiableUUID() returns (bytes32 uuid) {
+            if (uuid != IMPLEMENTATION_SLOT) revert InvalidImplementation(implementation);
+        } catch {
+            revert InvalidImplementation(implementation);
+        }
+        bytes32 vk_hash = UpgradeableVerifier(implementation).vkHash();
+        if (vk_hash != expectedVkHash) revert VkHashMismatch(vk_hash);
+
+        bytes32 slot = IMPLEMENTATION_SLOT;
+        assembly {
+            sstore(slot, implementation)
+        }
+        emit Upgraded(implementation, vk_hash);
+    }
+
+    function set_owner(address newOwner) internal {
+        emit OwnershipTransferred(owner(), newOwner);
+        bytes32 slot = OWNER_SLOT;
+        assembly {
+            sstore(slot, newOwner)
+        }
+    }
+}
+
+// ERC-1967 proxy forwarding every call to the current UpgradeableVerifier.
+contract VerifierProxy {
+    error InvalidImplementation(address implementation);
+
+    bytes32 internal constant IMPLEMENTATION_SLOT =
+        0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc;
+
+    constructor(address implementation, address owner) {
+        if (UpgradeableVerifier(implementation).proxiableUUID() != IMPLEMENTATION_SLOT) {
+            revert InvalidImplementation(implementation);
+        }
+        bytes32 slot = IMPLEMENTATION_SLOT;
+        assembly {
+            sstore(slot, implementation)
+        }
+        (bool ok, bytes memory ret) = implementation.delegatecall(
+            abi.encodeCall(UpgradeableVerifier.initialize, (owner))
+        );
+        if (!ok) {
+            assembly {
+                revert(add(ret, 0x20), mload(ret))
+            }
+        }
+    }
+
+    fallback() external {
+        bytes32 slot = IMPLEMENTATION_SLOT;
+        assembly {
+            let implementation := sload(slot)
+            calldatacopy(0, 0, calldatasize())
+            let ok := delegatecall(gas(), implementation, 0, calldatasize(), 0, 0)
+            returndatacopy(0, 0, returndatasize())
+            switch ok
+            case 0 {
+                revert(0, returndatasize())
+            }
+            default {
+                return(0, returndatasize())
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+    // final pair coordinates followed by the aggregated target circuit instances
+    function load_instances(uint256[] {{ data_location }} target_circuit_final_pair)
+        internal
+        pure
+        returns (uint256[{{instance_size}}] memory instances)
+    {
+        if (target_circuit_final_pair.length != {{instance_size}}) {
+            revert InvalidPublicInputLength(target_circuit_final_pair.length);
+        }
+        unchecked {
+            for (uint256 i = 0; i < 4; i++) {
+                if (target_circuit_final_pair[i] >= p_mod) revert InvalidPublicInput(i);
+            }
+            for (uint256 i = 4; i < {{instance_size}}; i++) {
+                if (target_circuit_final_pair[i] >= q_mod) revert InvalidPublicInput(i);
+            }
+        }
+
+        instances[0] = target_circuit_final_pair[0] & ((1 << 136) - 1);
+        instances[1] = (target_circuit_final_pair[0] >> 136) + ((target_circuit_final_pair[1] & 1) << 136);
+        instances[2] = target_circuit_final_pair[2] & ((1 << 136) - 1);
+        instances[3] = (target_circuit_final_pair[2] >> 136) + ((target_circuit_final_pair[3] & 1) << 136);
+        {% for statement in instance_assign %}
+        {{statement}}
+        {%- endfor %}
+    }
+{%- if check_fn != "verify" %}
+
+    event ProofVerified(bytes32 instancesHash);
+{%- endif %}
+
+    // Reverts on an invalid proof, called by every entry point below.
+    function verify_proof(
+        uint256[] {{ data_location }} proof,
+        uint256[] {{ data_location }} target_circuit_final_pair
+    ) internal view {
+        uint256[{{instance_size}}] memory instances = load_instances(target_circuit_final_pair);
+
+        (uint256 x0, uint256 y0, uint256 x1, uint256 y1) = get_wx_wg(proof, instances);
+
+        // both pairing checks in a single call, see code_generator::pairing
+        uint256[] memory input = new uint256[](24);
+        {%- for statement in pairing_input %}
+        {{statement}}
+        {%- endfor %}
+        uint256 r = uint256(keccak256(abi.encodePacked(input))) % q_mod;
+        {%- for statement in pairing_scale %}
+        {{statement}}
+        {%- endfor %}
+
+        if (!pairing_call(input)) revert ProofInvalid(0);
+    }
+
+    function {{ check_fn }}(
+        uint256[] calldata proof,
+        uint256[] calldata target_circuit_final_pair
+    ) public view {
+        verify_proof(proof, target_circuit_final_pair);
+    }
+{%- if check_fn != "verify" %}
+
+    // Calling convention selected by SolidityOptions, check() reverts on an invalid proof.
+    function verify(uint256[] calldata proof, uint256[] calldata target_circuit_final_pair)
+        public
+        {%- if not emit_event %} view{% endif %}
+        {%- if not revert_on_failure %} returns (bool){% endif %}
+    {
+{%- if revert_on_failure %}
+        verify_proof(proof, target_circuit_final_pair);
+{%- else %}
+        try this.check(proof, target_circuit_final_pair) {} catch {
+            return false;
+        }
+{%- endif %}
+{%- if emit_event %}
+        emit ProofVerified(keccak256(abi.encodePacked(target_circuit_final_pair)));
+{%- endif %}
+{%- if not revert_on_failure %}
+        return true;
+{%- endif %}
+    }
+{%- endif %}
+
+    error InvalidProofLength(uint256 length);
+
+    // IVerifier entry, returns true on a valid proof and reverts with the errors of
+    // verify_proof otherwise, so the reason reaches the caller.
+    function verify(bytes calldata proof, uint256[] calldata instances)
+        external
+        view
+        override
+        returns (bool)
+    {
+        if (proof.length % 32 != 0) revert InvalidProofLength(proof.length);
+
+        uint256[] calldata words;
+        assembly {
+            words.offset := proof.offset
+            words.length := shr(5, proof.length)
+        }
+
+        verify_proof(words, instances);
+        return true;
+    }
+
+    function supportsInterface(bytes4 interfaceId) external pure override returns (bool) {
+        return
+            interfaceId == type(IVerifier).interfaceId ||
+            interfaceId == type(IERC165).interfaceId;
+    }
+{%- if compressed %}
+
+    error InvalidCompressedCalldata(uint256 offset);
+
+    // Bit i is set if proof word i is the x coordinate of a point, see CalldataLayout::compress.
+    bytes constant PROOF_POINTS = hex"{{ proof_points }}";
+    bytes constant FINAL_PAIR_POINTS = hex"05";
+
+    // sqrt(a) = a^((p + 1) / 4) as p = 3 mod 4
+    function fp_sqrt(uint256 a) internal view returns (uint256 r) {
+        bool ret;
+        assembly {
+            let input := mload(0x40)
+            mstore(input, 0x20)
+            mstore(add(input, 0x20), 0x20)
+            mstore(add(input, 0x40), 0x20)
+            mstore(add(input, 0x60), a)
+            mstore(add(input, 0x80), 0xc19139cb84c680a6e14116da060561765e05aa45a1c72a34f082305b61f3f52)
+            mstore(add(input, 0xa0), p_mod)
+            ret := staticcall(gas(), 0x05, input, 0xc0, input, 0x20)
+            r := mload(input)
+        }
+        if (!ret) revert PrecompileFailed(5);
+    }
+
+    function decompress_point(uint256 word, uint256 offset) internal view returns (uint256 x, uint256 y) {
+        if (word == 1 << 254) {
+            return (0, 0);
+        }
+        x = word & ((1 << 255) - 1);
+        if (x >= p_mod) revert InvalidCompressedCalldata(offset);
+        uint256 y2 = addmod(mulmod(mulmod(x, x, p_mod), x, p_mod), 3, p_mod);
+        y = fp_sqrt(y2);
+        if (mulmod(y, y, p_mod) != y2) revert InvalidCompressedCalldata(offset);
+        if ((y & 1) != (word >> 255)) {
+            y = p_mod - y;
+        }
+    }
+
+    function decompress_words(bytes calldata blob, uint256 pos, uint256 n, bytes memory points)
+        internal
+        view
+        returns (uint256[] memory words, uint256)
+    {
+        words = new uint256[](n);
+        uint256 i = 0;
+        while (i < n) {
+            uint256 word;
+            assembly {
+                word := calldataload(add(blob.offset, pos))
+            }
+            if ((uint8(points[i / 8]) >> (i % 8)) & 1 == 1) {
+                (words[i], words[i + 1]) = decompress_point(word, pos);
+                i += 2;
+            } else {
+                words[i] = word;
+                i += 1;
+            }
+            pos += 32;
+        }
+        return (words, pos);
+    }
+
+    // Same as verify() on the blob packed by CalldataLayout::compress, but always reverts
+    // on an invalid proof.
+    function verify_compressed(bytes calldata blob)
+        external
+        {%- if not emit_event %} view{% endif %}
+    {
+        if (blob.length != {{ compressed_length }}) revert InvalidCompressedCalldata(blob.length);
+        (uint256[] memory proof, uint256 pos) = decompress_words(blob, 0, {{ proof_len }}, PROOF_POINTS);
+        (uint256[] memory final_pair, ) = decompress_words(blob, pos, {{ instance_size }}, FINAL_PAIR_POINTS);
+        verify_proof(proof, final_pair);
+{%- if emit_event %}
+        emit ProofVerified(keccak256(abi.encodePacked(final_pair)));
+{%- endif %}
+    }
+{%- endif %}
@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: {{ license }}
+pragma solidity {{ pragma }};
+{%- for hint in optimizer_hints %}
+// {{ hint }}
+{%- endfor %}
+
+// Verifying key constants read by the Verifier with extcodecopy. The table is
+// returned as the runtime code, behind a 0x00 byte (STOP) so it can't be
+// called and never starts with the 0xEF prefix rejected by EIP-3541.
+contract VerifyingKey {
+    constructor() {
+        bytes memory code = hex"00{{ table }}";
+        assembly {
+            return(add(code, 0x20), mload(code))
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use halo2_proofs::arithmetic::{BaseExt, CurveAffine};
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_snark_aggregator_api::systems::pairing::multi_pairing_native;
+use halo2_snark_aggregator_circuit::envelope::{is_envelope, ProofEnvelope};
+use halo2_snark_aggregator_circuit::fs::{try_load_instances, try_read_params};
+use halo2_snark_aggregator_circuit::verify_circuit::{Halo2VerifierCircuit, VerifyCheck};
+use pairing_bn256::bn256::{Bn256, Fq, G1Affine};
+use std::io::Cursor;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+// Verifies the verify circuit proof the same way as the sdk `verify_check` command.
+// Takes the verify_circuit.vkey, verify_circuit.params, verify_circuit_instance.data
+// and verify_circuit_proof.data files written by the sdk, or verify_circuit_proof.envelope
+// in place of the proof, which is then checked against the vk and instances first. The
+// whole params are needed: VerifyingKey::read rebuilds the evaluation domain from them.
+#[wasm_bindgen]
+pub fn verify_aggregated(
+    vk_bytes: &[u8],
+    params_bytes: &[u8],
+    instances: &[u8],
+    proof: &[u8],
+) -> bool {
+    verify_with_label(vk_bytes, params_bytes, instances, proof, None)
+}
+
+// verify_aggregated of a proof made with the transcript_label file of the sdk folder.
+#[wasm_bindgen]
+pub fn verify_aggregated_with_label(
+    vk_bytes: &[u8],
+    params_bytes: &[u8],
+    instances: &[u8],
+    proof: &[u8],
+    label: &[u8],
+) -> bool {
+    verify_with_label(vk_bytes, params_bytes, instances, proof, Some(label))
+}
+
+fn verify_with_label(
+    vk_bytes: &[u8],
+    params_bytes: &[u8],
+    instances: &[u8],
+    proof: &[u8],
+    label: Option<&[u8]>,
+) -> bool {
+    let params = match try_read_params::<G1Affine>(params_bytes) {
+        Ok(params) => params,
+        Err(_) => return false,
+    };
+    let vk = match VerifyingKey::<G1Affine>::read::<_, Halo2VerifierCircuit<'_, Bn256>>(
+        &mut Cursor::new(vk_bytes),
+        &params,
+    ) {
+        Ok(vk) => vk,
+        Err(_) => return false,
+    };
+
+    let verify_instance = match try_load_instances::<Bn256>(instances) {
+        Ok(verify_instance) => verify_instance,
+        Err(_) => return false,
+    };
+    let verify_proof = if is_envelope(proof) {
+        match ProofEnvelope::open_verify_circuit(proof, &vk, verify_instance[0][0].len()) {
+            Ok(envelope) => envelope.proof,
+            Err(_) => return false,
+        }
+    } else {
+        proof.to_vec()
+    };
+    let check = VerifyCheck::<G1Affine> {
+        verify_params: Rc::new(params),
+        verify_vk: Rc::new(vk),
+        verify_public_inputs_size: verify_instance[0][0].len(),
+        verify_instance,
+        verify_proof,
+        transcript_label: label.map(|label| label.to_vec()),
+    };
+    check.call::<Bn256>().is_ok()
+}
+
+// (w_x, w_g) of verify_circuit_final_pair.data, its first 4 little endian words.
+fn read_final_pair(final_pair: &[u8]) -> Option<(G1Affine, G1Affine)> {
+    let cursor = &mut Cursor::new(final_pair);
+    let mut point = || {
+        let x = Fq::read(cursor).ok()?;
+        let y = Fq::read(cursor).ok()?;
+        Option::from(G1Affine::from_xy(x, y))
+    };
+    Some((point()?, point()?))
+}
+
+// Checks the final pair of an aggregation proof, e(w_x, s_g2) e(w_g, -g2) == 1, on
+// the params of the target circuits. This is the check the verifier contract runs
+// on top of the verify circuit proof.
+#[wasm_bindgen]
+pub fn verify_final_pair(target_params_bytes: &[u8], final_pair: &[u8]) -> bool {
+    let params = match try_read_params::<G1Affine>(target_params_bytes) {
+        Ok(params) => params,
+        Err(_) => return false,
+    };
+    let params = match params.verifier::<Bn256>(0) {
+        Ok(params) => params,
+        Err(_) => return false,
+    };
+
+    match read_final_pair(final_pair) {
+        Some((w_x, w_g)) => multi_pairing_native::<Bn256>(&[(w_x, params.s_g2), (w_g, -params.g2)]),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::poly::commitment::Params;
+
+    #[test]
+    fn test_verify_final_pair() {
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(4);
+        let mut params_bytes = vec![];
+        params.write(&mut params_bytes).unwrap();
+
+        let word = |v: u64| {
+            let mut bytes = v.to_le_bytes().to_vec();
+            bytes.resize(32, 0);
+            bytes
+        };
+        // (1, 2) is the generator, (1, 1) is not on the curve
+        let generator = [word(1), word(2)].concat();
+        let pair = [generator.clone(), generator.clone()].concat();
+        assert!(read_final_pair(&pair).is_some());
+        assert!(!verify_final_pair(&params_bytes, &pair));
+
+        let off_curve = [generator, word(1), word(1)].concat();
+        assert!(read_final_pair(&off_curve).is_none());
+        assert!(!verify_final_pair(&params_bytes, &off_curve));
+        assert!(!verify_final_pair(&params_bytes, &word(1)));
+        assert!(!verify_final_pair(&[], &pair));
+    }
+}